@@ -17,7 +17,7 @@ use gpui::{
 use language::LanguageRegistry;
 use live_kit_client::{LocalAudioTrack, LocalTrackPublication, LocalVideoTrack, RoomUpdate};
 use postage::{sink::Sink, stream::Stream, watch};
-use project::Project;
+use project::{project_settings::ProjectSettings, Project};
 use settings::Settings as _;
 use std::{future::Future, mem, sync::Arc, time::Duration};
 use util::{post_inc, ResultExt, TryFutureExt};
@@ -1222,6 +1222,7 @@ impl Room {
         let request = self.client.request(proto::ShareProject {
             room_id: self.id(),
             worktrees: project.read(cx).worktree_metadata_protos(cx),
+            approve_join_requests: ProjectSettings::get_global(cx).approve_join_requests,
         });
         cx.spawn(|this, mut cx| async move {
             let response = request.await?;
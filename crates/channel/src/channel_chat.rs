@@ -51,6 +51,7 @@ pub struct ChannelMessage {
     pub nonce: u128,
     pub mentions: Vec<(Range<usize>, UserId)>,
     pub reply_to_message_id: Option<u64>,
+    pub edited_at: Option<OffsetDateTime>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -93,6 +94,7 @@ impl EventEmitter<ChannelChatEvent> for ChannelChat {}
 pub fn init(client: &Arc<Client>) {
     client.add_model_message_handler(ChannelChat::handle_message_sent);
     client.add_model_message_handler(ChannelChat::handle_message_removed);
+    client.add_model_message_handler(ChannelChat::handle_message_updated);
 }
 
 impl ChannelChat {
@@ -189,6 +191,7 @@ impl ChannelChat {
                     mentions: message.mentions.clone(),
                     nonce,
                     reply_to_message_id: message.reply_to_message_id,
+                    edited_at: None,
                 },
                 &(),
             ),
@@ -234,6 +237,46 @@ impl ChannelChat {
         })
     }
 
+    /// Replaces the body of one of the current user's recent messages. The message is
+    /// updated in place right away, and restored if the server rejects the edit.
+    pub fn update_message(
+        &mut self,
+        id: u64,
+        message: MessageParams,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<Task<Result<()>>> {
+        if message.text.trim().is_empty() {
+            Err(anyhow!("message body can't be empty"))?;
+        }
+        let old_message = self
+            .find_loaded_message(id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no such message"))?;
+        if !old_message.is_within_edit_window() {
+            Err(anyhow!("message can no longer be edited"))?;
+        }
+
+        let mut new_message = old_message.clone();
+        new_message.body = message.text.clone();
+        new_message.mentions = message.mentions.clone();
+        new_message.edited_at = Some(OffsetDateTime::now_utc());
+        self.message_updated(new_message, cx);
+
+        let request = self.rpc.request(proto::EditChannelMessage {
+            channel_id: self.channel_id.0,
+            message_id: id,
+            body: message.text,
+            mentions: mentions_to_proto(&message.mentions),
+        });
+        Ok(cx.spawn(move |this, mut cx| async move {
+            if let Err(error) = request.await {
+                this.update(&mut cx, |this, cx| this.message_updated(old_message, cx))?;
+                return Err(error);
+            }
+            Ok(())
+        }))
+    }
+
     pub fn load_more_messages(&mut self, cx: &mut ModelContext<Self>) -> Option<Task<Option<()>>> {
         if self.loaded_all_messages {
             return None;
@@ -501,6 +544,14 @@ impl ChannelChat {
 
         let message = ChannelMessage::from_proto(message, &user_store, &mut cx).await?;
         this.update(&mut cx, |this, cx| {
+            // Mentions are recorded before the message is inserted, which can
+            // acknowledge it if the chat is being read.
+            let current_user_id = this.user_store.read(cx).current_user().map(|user| user.id);
+            if current_user_id.is_some_and(|user_id| message.mentions_user(user_id)) {
+                this.channel_store.update(cx, |store, cx| {
+                    store.record_mention(this.channel_id, &message, cx)
+                });
+            }
             this.insert_messages(SumTree::from_item(message, &()), cx);
             cx.emit(ChannelChatEvent::NewMessage {
                 channel_id: this.channel_id,
@@ -523,6 +574,22 @@ impl ChannelChat {
         Ok(())
     }
 
+    async fn handle_message_updated(
+        this: Model<Self>,
+        message: TypedEnvelope<proto::ChannelMessageUpdate>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let user_store = this.update(&mut cx, |this, _| this.user_store.clone())?;
+        let message = message
+            .payload
+            .message
+            .ok_or_else(|| anyhow!("empty message"))?;
+        let message = ChannelMessage::from_proto(message, &user_store, &mut cx).await?;
+        this.update(&mut cx, |this, cx| this.message_updated(message, cx))?;
+        Ok(())
+    }
+
     fn insert_messages(&mut self, messages: SumTree<ChannelMessage>, cx: &mut ModelContext<Self>) {
         if let Some((first_message, last_message)) = messages.first().zip(messages.last()) {
             let nonces = messages
@@ -582,6 +649,26 @@ impl ChannelChat {
         }
     }
 
+    fn message_updated(&mut self, message: ChannelMessage, cx: &mut ModelContext<Self>) {
+        let mut cursor = self.messages.cursor::<ChannelMessageId>();
+        let mut messages = cursor.slice(&message.id, Bias::Left, &());
+        if let Some(item) = cursor.item() {
+            if item.id == message.id {
+                let ix = messages.summary().count;
+                messages.push(message, &());
+                cursor.next(&());
+                messages.append(cursor.suffix(&()), &());
+                drop(cursor);
+                self.messages = messages;
+                cx.emit(ChannelChatEvent::MessagesUpdated {
+                    old_range: ix..ix + 1,
+                    new_count: 1,
+                });
+                cx.notify();
+            }
+        }
+    }
+
     fn message_removed(&mut self, id: u64, cx: &mut ModelContext<Self>) {
         let mut cursor = self.messages.cursor::<ChannelMessageId>();
         let mut messages = cursor.slice(&ChannelMessageId::Saved(id), Bias::Left, &());
@@ -613,6 +700,11 @@ async fn messages_from_proto(
 }
 
 impl ChannelMessage {
+    /// Whether the message was sent recently enough for its sender to edit it.
+    pub fn is_within_edit_window(&self) -> bool {
+        OffsetDateTime::now_utc() - self.timestamp <= rpc::CHANNEL_MESSAGE_EDIT_WINDOW
+    }
+
     pub async fn from_proto(
         message: proto::ChannelMessage,
         user_store: &Model<UserStore>,
@@ -641,6 +733,10 @@ impl ChannelMessage {
                 .ok_or_else(|| anyhow!("nonce is required"))?
                 .into(),
             reply_to_message_id: message.reply_to_message_id,
+            edited_at: message
+                .edited_at
+                .map(|edited_at| OffsetDateTime::from_unix_timestamp(edited_at as i64))
+                .transpose()?,
         })
     }
 
@@ -648,6 +744,15 @@ impl ChannelMessage {
         matches!(self.id, ChannelMessageId::Pending(_))
     }
 
+    /// Whether someone other than the given user mentioned them in this message.
+    pub fn mentions_user(&self, user_id: UserId) -> bool {
+        self.sender.id != user_id
+            && self
+                .mentions
+                .iter()
+                .any(|(_, mentioned_user_id)| *mentioned_user_id == user_id)
+    }
+
     pub async fn from_proto_vec(
         proto_messages: Vec<proto::ChannelMessage>,
         user_store: &Model<UserStore>,
@@ -1,10 +1,12 @@
 mod channel_index;
 
-use crate::{channel_buffer::ChannelBuffer, channel_chat::ChannelChat, ChannelMessage};
+use crate::{
+    channel_buffer::ChannelBuffer, channel_chat::ChannelChat, ChannelMessage, ChannelMessageId,
+};
 use anyhow::{anyhow, Result};
 use channel_index::ChannelIndex;
 use client::{ChannelId, Client, Subscription, User, UserId, UserStore};
-use collections::{hash_map, HashMap, HashSet};
+use collections::{hash_map, BTreeSet, HashMap, HashSet};
 use futures::{channel::mpsc, future::Shared, Future, FutureExt, StreamExt};
 use gpui::{
     AppContext, AsyncAppContext, Context, EventEmitter, Global, Model, ModelContext, SharedString,
@@ -88,6 +90,8 @@ pub struct ChannelState {
     latest_notes_versions: Option<NotesVersion>,
     observed_chat_message: Option<u64>,
     observed_notes_versions: Option<NotesVersion>,
+    unread_message_count: usize,
+    unread_mentions: BTreeSet<u64>,
     role: Option<ChannelRole>,
     projects: HashSet<HostedProjectId>,
 }
@@ -161,6 +165,11 @@ pub struct MembershipSortKey<'a> {
 pub enum ChannelEvent {
     ChannelCreated(ChannelId),
     ChannelRenamed(ChannelId),
+    /// Someone mentioned the current user in a message that they haven't read.
+    Mentioned {
+        channel_id: ChannelId,
+        message: ChannelMessage,
+    },
 }
 
 impl EventEmitter<ChannelEvent> for ChannelStore {}
@@ -382,6 +391,72 @@ impl ChannelStore {
             .is_some_and(|state| state.has_new_messages())
     }
 
+    /// The number of messages sent to the channel since the current user last
+    /// read it. The server reports the exact count when the channel list is
+    /// loaded, and each message that arrives afterwards adds one to it.
+    pub fn unread_message_count(&self, channel_id: ChannelId) -> usize {
+        self.channel_states
+            .get(&channel_id)
+            .map_or(0, |state| state.unread_message_count)
+    }
+
+    /// The number of unread messages in the channel that mention the current user.
+    pub fn unread_mention_count(&self, channel_id: ChannelId) -> usize {
+        self.channel_states
+            .get(&channel_id)
+            .map_or(0, |state| state.unread_mentions.len())
+    }
+
+    /// The number of unread messages in every channel that mention the current user.
+    pub fn total_unread_mention_count(&self) -> usize {
+        self.channel_states
+            .values()
+            .map(|state| state.unread_mentions.len())
+            .sum()
+    }
+
+    /// Records a message that mentions the current user. The first time an
+    /// unread message is recorded, [`ChannelEvent::Mentioned`] is emitted.
+    pub fn record_mention(
+        &mut self,
+        channel_id: ChannelId,
+        message: &ChannelMessage,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let ChannelMessageId::Saved(message_id) = message.id else {
+            return;
+        };
+        if self.insert_unread_mention(channel_id, message_id) {
+            cx.emit(ChannelEvent::Mentioned {
+                channel_id,
+                message: message.clone(),
+            });
+            cx.notify();
+        }
+    }
+
+    /// Records an unread mention that the current user was already notified
+    /// about, such as one loaded from their notification history, without
+    /// emitting [`ChannelEvent::Mentioned`].
+    pub fn record_unread_mention(
+        &mut self,
+        channel_id: ChannelId,
+        message_id: u64,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.insert_unread_mention(channel_id, message_id) {
+            cx.notify();
+        }
+    }
+
+    fn insert_unread_mention(&mut self, channel_id: ChannelId, message_id: u64) -> bool {
+        let state = self.channel_states.entry(channel_id).or_default();
+        let is_read = state
+            .observed_chat_message
+            .is_some_and(|observed_message_id| message_id <= observed_message_id);
+        !is_read && state.unread_mentions.insert(message_id)
+    }
+
     pub fn last_acknowledge_message_id(&self, channel_id: ChannelId) -> Option<u64> {
         self.channel_states.get(&channel_id).and_then(|state| {
             if let Some(last_message_id) = state.latest_chat_message {
@@ -1150,11 +1225,20 @@ impl ChannelStore {
                     .update_latest_notes_version(latest_buffer_version.epoch, &version)
             }
 
+            let unread_message_counts = payload
+                .unread_channel_message_counts
+                .into_iter()
+                .map(|unread| (unread.channel_id, unread.count as usize))
+                .collect::<HashMap<_, _>>();
             for latest_channel_message in payload.latest_channel_message_ids {
-                self.channel_states
+                let state = self
+                    .channel_states
                     .entry(ChannelId(latest_channel_message.channel_id))
-                    .or_default()
-                    .update_latest_message_id(latest_channel_message.message_id);
+                    .or_default();
+                state.update_latest_message_id(latest_channel_message.message_id);
+                if let Some(count) = unread_message_counts.get(&latest_channel_message.channel_id) {
+                    state.unread_message_count = *count;
+                }
             }
 
             for hosted_project in payload.hosted_projects {
@@ -1269,9 +1353,19 @@ impl ChannelState {
     fn acknowledge_message_id(&mut self, message_id: u64) {
         let observed = self.observed_chat_message.get_or_insert(message_id);
         *observed = (*observed).max(message_id);
+        let observed = *observed;
+        self.unread_mentions
+            .retain(|mentioned_message_id| *mentioned_message_id > observed);
+        if !self.has_new_messages() {
+            self.unread_message_count = 0;
+        }
     }
 
     fn update_latest_message_id(&mut self, message_id: u64) {
+        let is_newer = |id: Option<u64>| id.map_or(true, |id| message_id > id);
+        if is_newer(self.latest_chat_message) && is_newer(self.observed_chat_message) {
+            self.unread_message_count += 1;
+        }
         self.latest_chat_message =
             Some(message_id.max(self.latest_chat_message.unwrap_or_default()));
     }
@@ -1,11 +1,12 @@
 use crate::channel_chat::ChannelChatEvent;
 
 use super::*;
-use client::{test::FakeServer, Client, UserStore};
+use client::{test::FakeServer, ChannelId, Client, User, UserStore};
 use clock::FakeSystemClock;
 use gpui::{AppContext, Context, Model, TestAppContext};
 use rpc::proto::{self};
 use settings::SettingsStore;
+use std::{cell::RefCell, rc::Rc};
 use util::http::FakeHttpClient;
 
 #[gpui::test]
@@ -132,6 +133,91 @@ fn test_dangling_channel_paths(cx: &mut AppContext) {
     assert_channels(&channel_store, &[(0, "a".to_string())], cx);
 }
 
+#[gpui::test]
+fn test_unread_messages_and_mentions(cx: &mut AppContext) {
+    let channel_store = init_test(cx);
+    let channel_id = ChannelId(1);
+    let latest_message_id = |message_id| proto::UpdateChannels {
+        latest_channel_message_ids: vec![proto::ChannelMessageId {
+            channel_id: channel_id.0,
+            message_id,
+        }],
+        ..Default::default()
+    };
+    update_channels(
+        &channel_store,
+        proto::UpdateChannels {
+            channels: vec![proto::Channel {
+                id: channel_id.0,
+                name: "the-channel".to_string(),
+                visibility: proto::ChannelVisibility::Members as i32,
+                parent_path: Vec::new(),
+            }],
+            unread_channel_message_counts: vec![proto::UnreadChannelMessageCount {
+                channel_id: channel_id.0,
+                count: 7,
+            }],
+            ..latest_message_id(10)
+        },
+        cx,
+    );
+    channel_store.update(cx, |store, cx| {
+        assert_eq!(store.unread_message_count(channel_id), 7);
+        store.acknowledge_message_id(channel_id, 10, cx);
+        assert_eq!(store.unread_message_count(channel_id), 0);
+    });
+
+    let mentions = Rc::new(RefCell::new(Vec::new()));
+    cx.subscribe(&channel_store, {
+        let mentions = mentions.clone();
+        move |_, event: &ChannelEvent, _| {
+            if let ChannelEvent::Mentioned { message, .. } = event {
+                mentions.borrow_mut().push(message.id);
+            }
+        }
+    })
+    .detach();
+
+    update_channels(&channel_store, latest_message_id(11), cx);
+    update_channels(&channel_store, latest_message_id(12), cx);
+    update_channels(&channel_store, latest_message_id(12), cx);
+    channel_store.update(cx, |store, cx| {
+        assert_eq!(store.unread_message_count(channel_id), 2);
+
+        // Mentions are only reported once, and not at all once they've been read.
+        store.record_mention(channel_id, &message(12), cx);
+        store.record_mention(channel_id, &message(12), cx);
+        store.record_mention(channel_id, &message(9), cx);
+        assert_eq!(store.unread_mention_count(channel_id), 1);
+        assert_eq!(store.total_unread_mention_count(), 1);
+
+        store.acknowledge_message_id(channel_id, 12, cx);
+        assert_eq!(store.unread_message_count(channel_id), 0);
+        assert_eq!(store.unread_mention_count(channel_id), 0);
+        store.record_mention(channel_id, &message(12), cx);
+        assert_eq!(store.total_unread_mention_count(), 0);
+
+        // Mentions loaded from the notification history count as unread
+        // without being reported again.
+        store.record_unread_mention(channel_id, 13, cx);
+        assert_eq!(store.unread_mention_count(channel_id), 1);
+    });
+    assert_eq!(*mentions.borrow(), [ChannelMessageId::Saved(12)]);
+
+    fn message(id: u64) -> ChannelMessage {
+        ChannelMessage {
+            id: ChannelMessageId::Saved(id),
+            body: "hi @nathansobo".into(),
+            timestamp: time::OffsetDateTime::UNIX_EPOCH,
+            sender: Arc::new(User::default()),
+            nonce: id as u128,
+            mentions: vec![(3..14, 5)],
+            reply_to_message_id: None,
+            edited_at: None,
+        }
+    }
+}
+
 #[gpui::test]
 async fn test_channel_messages(cx: &mut TestAppContext) {
     let user_id = 5;
@@ -186,6 +272,7 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     mentions: vec![],
                     nonce: Some(1.into()),
                     reply_to_message_id: None,
+                    edited_at: None,
                 },
                 proto::ChannelMessage {
                     id: 11,
@@ -195,6 +282,7 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     mentions: vec![],
                     nonce: Some(2.into()),
                     reply_to_message_id: None,
+                    edited_at: None,
                 },
             ],
             done: false,
@@ -243,6 +331,7 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
             mentions: vec![],
             nonce: Some(3.into()),
             reply_to_message_id: None,
+            edited_at: None,
         }),
     });
 
@@ -297,6 +386,7 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     nonce: Some(4.into()),
                     mentions: vec![],
                     reply_to_message_id: None,
+                    edited_at: None,
                 },
                 proto::ChannelMessage {
                     id: 9,
@@ -306,6 +396,7 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     nonce: Some(5.into()),
                     mentions: vec![],
                     reply_to_message_id: None,
+                    edited_at: None,
                 },
             ],
         },
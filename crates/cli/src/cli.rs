@@ -14,7 +14,19 @@ pub enum CliRequest {
     //
     // Since Zed CLI has to be installed separately, there can be situations when old CLI is
     // querying new Zed editors, support both formats by using `String` here and parsing it on Zed side later.
-    Open { paths: Vec<String>, wait: bool },
+    Open {
+        paths: Vec<String>,
+        wait: bool,
+    },
+    /// Replaces every match of `query` in the project at `path`, printing the
+    /// edits that were made, or only the ones that would be with `dry_run`.
+    Replace {
+        path: String,
+        query: String,
+        replacement: String,
+        regex: bool,
+        dry_run: bool,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
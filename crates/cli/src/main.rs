@@ -2,7 +2,7 @@
 
 use anyhow::{anyhow, Context, Result};
 use clap::Parser;
-use cli::{CliRequest, CliResponse};
+use cli::{ipc::IpcReceiver, CliRequest, CliResponse};
 use serde::Deserialize;
 use std::{
     ffi::OsStr,
@@ -30,6 +30,17 @@ struct Args {
     /// Custom Zed.app path
     #[clap(short, long)]
     bundle_path: Option<PathBuf>,
+    /// Replace every match of QUERY with REPLACEMENT in the project at the given
+    /// path, or in the current directory, and print the edits.
+    #[clap(long, number_of_values = 2, value_names = &["QUERY", "REPLACEMENT"])]
+    replace: Option<Vec<String>>,
+    /// Treat the `--replace` query as a regular expression, whose capture
+    /// groups can be referred to in the replacement as `$1`, `$2`, etc.
+    #[clap(long, requires = "replace")]
+    regex: bool,
+    /// Print the edits that `--replace` would make, without making them.
+    #[clap(long, requires = "replace")]
+    dry_run: bool,
 }
 
 fn parse_path_with_position(
@@ -56,6 +67,30 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(replace) = args.replace {
+        let (query, replacement) = match replace.as_slice() {
+            [query, replacement] => (query.clone(), replacement.clone()),
+            _ => return Err(anyhow!("--replace takes a query and a replacement")),
+        };
+        let path = match args.paths_with_position.as_slice() {
+            [] => std::env::current_dir()?,
+            [path] => path.path_like.clone(),
+            _ => return Err(anyhow!("--replace takes at most one path")),
+        };
+        let path =
+            fs::canonicalize(&path).with_context(|| format!("path {path:?} canonicalization"))?;
+
+        let (tx, rx) = bundle.launch()?;
+        tx.send(CliRequest::Replace {
+            path: path.display().to_string(),
+            query,
+            replacement,
+            regex: args.regex,
+            dry_run: args.dry_run,
+        })?;
+        return handle_responses(rx);
+    }
+
     for path in args
         .paths_with_position
         .iter()
@@ -83,6 +118,10 @@ fn main() -> Result<()> {
         wait: args.wait,
     })?;
 
+    handle_responses(rx)
+}
+
+fn handle_responses(rx: IpcReceiver<CliResponse>) -> Result<()> {
     while let Ok(response) = rx.recv() {
         match response {
             CliResponse::Ping => {}
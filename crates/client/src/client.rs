@@ -1,6 +1,8 @@
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
 
+pub mod credentials_provider;
+pub mod idle;
 pub mod telemetry;
 pub mod user;
 
@@ -12,6 +14,7 @@ use async_tungstenite::tungstenite::{
 };
 use clock::SystemClock;
 use collections::HashMap;
+use credentials_provider::{CredentialsProvider, KeychainCredentialsProvider};
 use futures::{
     channel::oneshot, future::LocalBoxFuture, AsyncReadExt, FutureExt, SinkExt, StreamExt,
     TryFutureExt as _, TryStreamExt,
@@ -19,6 +22,7 @@ use futures::{
 use gpui::{
     actions, AnyModel, AnyWeakModel, AppContext, AsyncAppContext, Global, Model, Task, WeakModel,
 };
+use idle::IdleSettings;
 use lazy_static::lazy_static;
 use parking_lot::RwLock;
 use postage::watch;
@@ -102,6 +106,7 @@ impl Settings for ClientSettings {
 
 pub fn init_settings(cx: &mut AppContext) {
     TelemetrySettings::register(cx);
+    IdleSettings::register(cx);
     cx.update_global(|store: &mut SettingsStore, cx| {
         store.register_setting::<ClientSettings>(cx);
     });
@@ -144,6 +149,39 @@ pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
             }
         }
     });
+
+    let idle_detector = idle::init(cx);
+    cx.observe(&idle_detector, {
+        let client = client.clone();
+        move |idle_detector, cx| {
+            let is_idle = idle_detector.read(cx).is_idle();
+            if let Some(client) = client.upgrade() {
+                client.telemetry.set_idle(is_idle);
+                let away = is_idle && IdleSettings::get_global(cx).set_away;
+                client.send(proto::SetAway { away }).ok();
+            }
+        }
+    })
+    .detach();
+
+    // The server forgets that a connection was away once it closes, so tell it
+    // again whenever the client reconnects while idle.
+    if let Some(mut status) = client.upgrade().map(|client| client.status()) {
+        cx.spawn(|cx| async move {
+            while let Some(status) = status.next().await {
+                if !matches!(status, Status::Connected { .. }) {
+                    continue;
+                }
+                let away = cx.update(|cx| {
+                    idle_detector.read(cx).is_idle() && IdleSettings::get_global(cx).set_away
+                });
+                if let (Ok(true), Some(client)) = (away, client.upgrade()) {
+                    client.send(proto::SetAway { away: true }).ok();
+                }
+            }
+        })
+        .detach();
+    }
 }
 
 struct GlobalClient(Arc<Client>);
@@ -156,6 +194,7 @@ pub struct Client {
     http: Arc<HttpClientWithUrl>,
     telemetry: Arc<Telemetry>,
     state: RwLock<ClientState>,
+    credentials_provider: RwLock<Arc<dyn CredentialsProvider>>,
 
     #[allow(clippy::type_complexity)]
     #[cfg(any(test, feature = "test-support"))]
@@ -274,7 +313,7 @@ enum WeakSubscriber {
     Pending(Vec<Box<dyn AnyTypedEnvelope>>),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Credentials {
     pub user_id: u64,
     pub access_token: String,
@@ -433,6 +472,7 @@ impl Client {
             telemetry: Telemetry::new(clock, http.clone(), cx),
             http,
             state: Default::default(),
+            credentials_provider: RwLock::new(Arc::new(KeychainCredentialsProvider)),
 
             #[cfg(any(test, feature = "test-support"))]
             authenticate: Default::default(),
@@ -488,6 +528,13 @@ impl Client {
         self
     }
 
+    /// Replaces the store that access tokens are read from and written to,
+    /// which is the keychain by default.
+    pub fn set_credentials_provider(&self, provider: Arc<dyn CredentialsProvider>) -> &Self {
+        *self.credentials_provider.write() = provider;
+        self
+    }
+
     pub fn global(cx: &AppContext) -> Arc<Self> {
         cx.global::<GlobalClient>().0.clone()
     }
@@ -741,7 +788,7 @@ impl Client {
     }
 
     pub async fn has_keychain_credentials(&self, cx: &AsyncAppContext) -> bool {
-        read_credentials_from_keychain(cx).await.is_some()
+        self.read_credentials_from_keychain(cx).await.is_some()
     }
 
     #[async_recursion(?Send)]
@@ -772,7 +819,7 @@ impl Client {
         let mut read_from_keychain = false;
         let mut credentials = self.state.read().credentials.clone();
         if credentials.is_none() && try_keychain {
-            credentials = read_credentials_from_keychain(cx).await;
+            credentials = self.read_credentials_from_keychain(cx).await;
             read_from_keychain = credentials.is_some();
         }
         if credentials.is_none() {
@@ -810,7 +857,9 @@ impl Client {
                     Ok(conn) => {
                         self.state.write().credentials = Some(credentials.clone());
                         if !read_from_keychain && IMPERSONATE_LOGIN.is_none() {
-                            write_credentials_to_keychain(credentials, cx).await.log_err();
+                            self.write_credentials_to_keychain(&credentials, cx)
+                                .await
+                                .log_err();
                         }
 
                         futures::select_biased! {
@@ -824,7 +873,7 @@ impl Client {
                     Err(EstablishConnectionError::Unauthorized) => {
                         self.state.write().credentials.take();
                         if read_from_keychain {
-                            delete_credentials_from_keychain(cx).await.log_err();
+                            self.delete_credentials_from_keychain(cx).await.log_err();
                             self.set_status(Status::SignedOut, cx);
                             self.authenticate_and_connect(false, cx).await
                         } else {
@@ -1400,42 +1449,36 @@ impl Client {
     pub fn telemetry(&self) -> &Arc<Telemetry> {
         &self.telemetry
     }
-}
 
-async fn read_credentials_from_keychain(cx: &AsyncAppContext) -> Option<Credentials> {
-    if IMPERSONATE_LOGIN.is_some() {
-        return None;
-    }
-
-    let (user_id, access_token) = cx
-        .update(|cx| cx.read_credentials(&ClientSettings::get_global(cx).server_url))
-        .log_err()?
-        .await
-        .log_err()??;
+    async fn read_credentials_from_keychain(&self, cx: &AsyncAppContext) -> Option<Credentials> {
+        if IMPERSONATE_LOGIN.is_some() {
+            return None;
+        }
 
-    Some(Credentials {
-        user_id: user_id.parse().ok()?,
-        access_token: String::from_utf8(access_token).ok()?,
-    })
-}
+        let provider = self.credentials_provider.read().clone();
+        let server_url = cx
+            .update(|cx| ClientSettings::get_global(cx).server_url.clone())
+            .log_err()?;
+        provider.read_credentials(&server_url, cx).await.log_err()?
+    }
 
-async fn write_credentials_to_keychain(
-    credentials: Credentials,
-    cx: &AsyncAppContext,
-) -> Result<()> {
-    cx.update(move |cx| {
-        cx.write_credentials(
-            &ClientSettings::get_global(cx).server_url,
-            &credentials.user_id.to_string(),
-            credentials.access_token.as_bytes(),
-        )
-    })?
-    .await
-}
+    async fn write_credentials_to_keychain(
+        &self,
+        credentials: &Credentials,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
+        let provider = self.credentials_provider.read().clone();
+        let server_url = cx.update(|cx| ClientSettings::get_global(cx).server_url.clone())?;
+        provider
+            .write_credentials(&server_url, credentials, cx)
+            .await
+    }
 
-async fn delete_credentials_from_keychain(cx: &AsyncAppContext) -> Result<()> {
-    cx.update(move |cx| cx.delete_credentials(&ClientSettings::get_global(cx).server_url))?
-        .await
+    async fn delete_credentials_from_keychain(&self, cx: &AsyncAppContext) -> Result<()> {
+        let provider = self.credentials_provider.read().clone();
+        let server_url = cx.update(|cx| ClientSettings::get_global(cx).server_url.clone())?;
+        provider.delete_credentials(&server_url, cx).await
+    }
 }
 
 const WORKTREE_URL_PREFIX: &str = "zed://worktrees/";
@@ -1458,7 +1501,7 @@ pub fn decode_worktree_url(url: &str) -> Option<(u64, String)> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test::FakeServer;
+    use crate::test::{FakeCredentialsProvider, FakeServer};
 
     use clock::FakeSystemClock;
     use gpui::{BackgroundExecutor, Context, TestAppContext};
@@ -1508,6 +1551,64 @@ mod tests {
         assert_eq!(server.auth_count(), 2); // Client re-authenticated due to an invalid token
     }
 
+    #[gpui::test]
+    async fn test_stored_credentials(cx: &mut TestAppContext) {
+        init_test(cx);
+        let user_id = 5;
+        let credentials_provider = Arc::new(FakeCredentialsProvider::default());
+        let new_client = |cx: &mut TestAppContext| {
+            let client = cx.update(|cx| {
+                Client::new(
+                    Arc::new(FakeSystemClock::default()),
+                    FakeHttpClient::with_404_response(),
+                    cx,
+                )
+            });
+            client.set_credentials_provider(credentials_provider.clone());
+            client
+        };
+        let server_url = cx.update(|cx| ClientSettings::get_global(cx).server_url.clone());
+
+        let client = new_client(cx);
+        let server = FakeServer::for_client(user_id, &client, cx).await;
+        assert_eq!(server.auth_count(), 1);
+        assert_eq!(
+            credentials_provider.credentials(&server_url),
+            Some(Credentials {
+                user_id,
+                access_token: "0".into(),
+            })
+        );
+        client.disconnect(&cx.to_async());
+
+        // A new client, as after a restart, connects with the stored access token.
+        let client = new_client(cx);
+        server.intercept_connections(&client);
+        client
+            .authenticate_and_connect(true, &cx.to_async())
+            .await
+            .unwrap();
+        assert_eq!(server.auth_count(), 1);
+        client.disconnect(&cx.to_async());
+
+        // A stored access token that's no longer valid is replaced.
+        server.roll_access_token();
+        let client = new_client(cx);
+        server.intercept_connections(&client);
+        client
+            .authenticate_and_connect(true, &cx.to_async())
+            .await
+            .unwrap();
+        assert_eq!(server.auth_count(), 2);
+        assert_eq!(
+            credentials_provider.credentials(&server_url),
+            Some(Credentials {
+                user_id,
+                access_token: "1".into(),
+            })
+        );
+    }
+
     #[gpui::test(iterations = 10)]
     async fn test_connection_timeout(executor: BackgroundExecutor, cx: &mut TestAppContext) {
         init_test(cx);
@@ -1654,18 +1755,15 @@ mod tests {
         });
         let server = FakeServer::for_client(user_id, &client, cx).await;
 
-        let (done_tx1, mut done_rx1) = smol::channel::unbounded();
-        let (done_tx2, mut done_rx2) = smol::channel::unbounded();
-        client.add_model_message_handler(
+        let handled_model_ids = Arc::new(Mutex::new(Vec::new()));
+        client.add_model_message_handler({
+            let handled_model_ids = handled_model_ids.clone();
             move |model: Model<TestModel>, _: TypedEnvelope<proto::JoinProject>, _, mut cx| {
-                match model.update(&mut cx, |model, _| model.id).unwrap() {
-                    1 => done_tx1.try_send(()).unwrap(),
-                    2 => done_tx2.try_send(()).unwrap(),
-                    _ => unreachable!(),
-                }
+                let model_id = model.update(&mut cx, |model, _| model.id).unwrap();
+                handled_model_ids.lock().push(model_id);
                 async { Ok(()) }
-            },
-        );
+            }
+        });
         let model1 = cx.new_model(|_| TestModel {
             id: 1,
             subscription: None,
@@ -1697,8 +1795,9 @@ mod tests {
 
         server.send(proto::JoinProject { project_id: 1 });
         server.send(proto::JoinProject { project_id: 2 });
-        done_rx1.next().await.unwrap();
-        done_rx2.next().await.unwrap();
+        server.send(proto::JoinProject { project_id: 3 });
+        cx.run_until_parked();
+        assert_eq!(*handled_model_ids.lock(), [1, 2]);
     }
 
     #[gpui::test]
@@ -1715,25 +1814,27 @@ mod tests {
         let server = FakeServer::for_client(user_id, &client, cx).await;
 
         let model = cx.new_model(|_| TestModel::default());
-        let (done_tx1, _done_rx1) = smol::channel::unbounded();
-        let (done_tx2, mut done_rx2) = smol::channel::unbounded();
-        let subscription1 = client.add_message_handler(
-            model.downgrade(),
+        let handled_count1 = Arc::new(Mutex::new(0));
+        let handled_count2 = Arc::new(Mutex::new(0));
+        let subscription1 = client.add_message_handler(model.downgrade(), {
+            let handled_count1 = handled_count1.clone();
             move |_, _: TypedEnvelope<proto::Ping>, _, _| {
-                done_tx1.try_send(()).unwrap();
+                *handled_count1.lock() += 1;
                 async { Ok(()) }
-            },
-        );
+            }
+        });
         drop(subscription1);
-        let _subscription2 = client.add_message_handler(
-            model.downgrade(),
+        let _subscription2 = client.add_message_handler(model.downgrade(), {
+            let handled_count2 = handled_count2.clone();
             move |_, _: TypedEnvelope<proto::Ping>, _, _| {
-                done_tx2.try_send(()).unwrap();
+                *handled_count2.lock() += 1;
                 async { Ok(()) }
-            },
-        );
+            }
+        });
         server.send(proto::Ping {});
-        done_rx2.next().await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(*handled_count1.lock(), 0);
+        assert_eq!(*handled_count2.lock(), 1);
     }
 
     #[gpui::test]
@@ -1750,22 +1851,109 @@ mod tests {
         let server = FakeServer::for_client(user_id, &client, cx).await;
 
         let model = cx.new_model(|_| TestModel::default());
-        let (done_tx, mut done_rx) = smol::channel::unbounded();
-        let subscription = client.add_message_handler(
-            model.clone().downgrade(),
+        let handled_count = Arc::new(Mutex::new(0));
+        let subscription = client.add_message_handler(model.clone().downgrade(), {
+            let handled_count = handled_count.clone();
             move |model: Model<TestModel>, _: TypedEnvelope<proto::Ping>, _, mut cx| {
                 model
                     .update(&mut cx, |model, _| model.subscription.take())
                     .unwrap();
-                done_tx.try_send(()).unwrap();
+                *handled_count.lock() += 1;
                 async { Ok(()) }
-            },
-        );
+            }
+        });
         model.update(cx, |model, _| {
             model.subscription = Some(subscription);
         });
         server.send(proto::Ping {});
-        done_rx.next().await.unwrap();
+        server.send(proto::Ping {});
+        cx.run_until_parked();
+        assert_eq!(*handled_count.lock(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_simulating_project_collaborators(cx: &mut TestAppContext) {
+        init_test(cx);
+        let user_id = 5;
+        let client = cx.update(|cx| {
+            Client::new(
+                Arc::new(FakeSystemClock::default()),
+                FakeHttpClient::with_404_response(),
+                cx,
+            )
+        });
+        let server = FakeServer::for_client(user_id, &client, cx).await;
+
+        let events = Arc::new(Mutex::new(Vec::<String>::new()));
+        client.add_model_message_handler({
+            let events = events.clone();
+            move |_: Model<TestModel>,
+                  envelope: TypedEnvelope<proto::AddProjectCollaborator>,
+                  _,
+                  _| {
+                let user_id = envelope.payload.collaborator.unwrap().user_id;
+                events.lock().push(format!("user {user_id} joined"));
+                async { Ok(()) }
+            }
+        });
+        client.add_model_message_handler({
+            let events = events.clone();
+            move |_: Model<TestModel>, _: TypedEnvelope<proto::UpdateProjectCollaborator>, _, _| {
+                events.lock().push("collaborator rejoined".into());
+                async { Ok(()) }
+            }
+        });
+        client.add_model_message_handler({
+            let events = events.clone();
+            move |_: Model<TestModel>, _: TypedEnvelope<proto::RemoveProjectCollaborator>, _, _| {
+                events.lock().push("collaborator left".into());
+                async { Ok(()) }
+            }
+        });
+        client.add_model_request_handler({
+            let events = events.clone();
+            move |_: Model<TestModel>, envelope: TypedEnvelope<proto::UpdateBuffer>, _, _| {
+                let buffer_id = envelope.payload.buffer_id;
+                events.lock().push(format!("buffer {buffer_id} updated"));
+                async { Ok(proto::Ack {}) }
+            }
+        });
+        let model = cx.new_model(|_| TestModel::default());
+        let _subscription = client
+            .subscribe_to_entity(1)
+            .unwrap()
+            .set_model(&model, &mut cx.to_async());
+
+        let peer_id = server.join_project(1, 7, 1);
+        cx.run_until_parked();
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            ["user 7 joined"]
+        );
+
+        server
+            .update_selections(1, 2, 1, 0, Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            ["buffer 2 updated"]
+        );
+
+        let new_peer_id = server.rejoin_project(1, peer_id);
+        assert_ne!(new_peer_id, peer_id);
+        cx.run_until_parked();
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            ["collaborator rejoined"]
+        );
+
+        server.leave_project(1, new_peer_id);
+        cx.run_until_parked();
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            ["collaborator left"]
+        );
     }
 
     #[derive(Default)]
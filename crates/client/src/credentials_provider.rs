@@ -0,0 +1,79 @@
+use crate::Credentials;
+use anyhow::{Context as _, Result};
+use futures::{future::LocalBoxFuture, FutureExt as _};
+use gpui::AsyncAppContext;
+
+/// Stores the access tokens that the client authenticates with, keyed by the
+/// URL of the server they were issued by.
+pub trait CredentialsProvider: Send + Sync {
+    fn read_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        cx: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<Option<Credentials>>>;
+
+    fn write_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        credentials: &'a Credentials,
+        cx: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<()>>;
+
+    fn delete_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        cx: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<()>>;
+}
+
+/// Keeps credentials in the platform's credential store, which is the
+/// Keychain on macOS, so that they survive restarts.
+pub struct KeychainCredentialsProvider;
+
+impl CredentialsProvider for KeychainCredentialsProvider {
+    fn read_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        cx: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<Option<Credentials>>> {
+        async move {
+            let Some((user_id, access_token)) = cx.update(|cx| cx.read_credentials(url))?.await?
+            else {
+                return Ok(None);
+            };
+            Ok(Some(Credentials {
+                user_id: user_id.parse().context("invalid user id in keychain")?,
+                access_token: String::from_utf8(access_token)
+                    .context("invalid access token in keychain")?,
+            }))
+        }
+        .boxed_local()
+    }
+
+    fn write_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        credentials: &'a Credentials,
+        cx: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<()>> {
+        async move {
+            cx.update(|cx| {
+                cx.write_credentials(
+                    url,
+                    &credentials.user_id.to_string(),
+                    credentials.access_token.as_bytes(),
+                )
+            })?
+            .await
+        }
+        .boxed_local()
+    }
+
+    fn delete_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        cx: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<()>> {
+        async move { cx.update(|cx| cx.delete_credentials(url))?.await }.boxed_local()
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use gpui::{AppContext, Context, Global, Model, ModelContext, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::time::{Duration, Instant};
+
+/// How often windows are checked for new input.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+pub struct IdleSettings {
+    pub timeout_minutes: u64,
+    pub set_away: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct IdleSettingsContent {
+    /// How many minutes without keyboard or mouse input before Zed
+    /// considers you idle. Set to 0 to never become idle.
+    ///
+    /// Default: 10
+    pub timeout_minutes: Option<u64>,
+    /// Whether to show you as away to your contacts while you're idle.
+    ///
+    /// Default: true
+    pub set_away: Option<bool>,
+}
+
+impl Settings for IdleSettings {
+    const KEY: Option<&'static str> = Some("idle");
+
+    type FileContent = IdleSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
+
+impl IdleSettings {
+    fn timeout(&self) -> Option<Duration> {
+        (self.timeout_minutes > 0).then(|| Duration::from_secs(self.timeout_minutes * 60))
+    }
+}
+
+struct GlobalIdleDetector(Model<IdleDetector>);
+
+impl Global for GlobalIdleDetector {}
+
+/// Tracks whether the user has stopped interacting with every Zed window,
+/// notifying observers when they become idle or active again.
+pub struct IdleDetector {
+    last_input: Instant,
+    is_idle: bool,
+    _poll_task: Task<()>,
+}
+
+pub fn init(cx: &mut AppContext) -> Model<IdleDetector> {
+    IdleSettings::register(cx);
+    let detector = cx.new_model(IdleDetector::new);
+    cx.set_global(GlobalIdleDetector(detector.clone()));
+    detector
+}
+
+impl IdleDetector {
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalIdleDetector>()
+            .map(|detector| detector.0.clone())
+    }
+
+    fn new(cx: &mut ModelContext<Self>) -> Self {
+        cx.observe_global::<SettingsStore>(|this, cx| this.update_idle(cx))
+            .detach();
+
+        Self {
+            last_input: Instant::now(),
+            is_idle: false,
+            _poll_task: cx.spawn(|this, mut cx| async move {
+                loop {
+                    cx.background_executor().timer(POLL_INTERVAL).await;
+                    let Ok(last_input) = cx.update(|cx| {
+                        cx.windows()
+                            .into_iter()
+                            .filter_map(|window| {
+                                window.update(cx, |_, cx| cx.last_input_timestamp()).ok()
+                            })
+                            .max()
+                    }) else {
+                        break;
+                    };
+                    let updated = this.update(&mut cx, |this, cx| {
+                        if let Some(last_input) = last_input {
+                            this.last_input = this.last_input.max(last_input);
+                        }
+                        this.update_idle(cx);
+                    });
+                    if updated.is_err() {
+                        break;
+                    }
+                }
+            }),
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.is_idle
+    }
+
+    fn update_idle(&mut self, cx: &mut ModelContext<Self>) {
+        let is_idle = IdleSettings::get_global(cx)
+            .timeout()
+            .map_or(false, |timeout| self.last_input.elapsed() >= timeout);
+        if is_idle != self.is_idle {
+            self.is_idle = is_idle;
+            cx.notify();
+        }
+    }
+}
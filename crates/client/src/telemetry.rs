@@ -49,6 +49,7 @@ struct TelemetryState {
     first_event_date_time: Option<DateTime<Utc>>,
     event_coalescer: EventCoalescer,
     max_queue_size: usize,
+    is_idle: bool,
 }
 
 #[cfg(debug_assertions)]
@@ -98,6 +99,7 @@ impl Telemetry {
             first_event_date_time: None,
             event_coalescer: EventCoalescer::new(clock.clone()),
             max_queue_size: MAX_QUEUE_LEN,
+            is_idle: false,
         }));
 
         #[cfg(not(debug_assertions))]
@@ -364,6 +366,10 @@ impl Telemetry {
             let executor = self.executor.clone();
             state.flush_events_task = Some(self.executor.spawn(async move {
                 executor.timer(FLUSH_INTERVAL).await;
+                // Hold off on sending events until the user comes back.
+                while this.state.lock().is_idle {
+                    executor.timer(FLUSH_INTERVAL).await;
+                }
                 this.flush_events();
             }));
         }
@@ -407,6 +413,11 @@ impl Telemetry {
         self.state.lock().is_staff
     }
 
+    /// Pauses periodic event flushes while the user is idle.
+    pub fn set_idle(self: &Arc<Self>, is_idle: bool) {
+        self.state.lock().is_idle = is_idle;
+    }
+
     pub fn flush_events(self: &Arc<Self>) {
         let mut state = self.state.lock();
         state.first_event_date_time = None;
@@ -629,6 +640,33 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_flush_paused_while_idle(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+        init_test(cx);
+        let clock = Arc::new(FakeSystemClock::new(
+            Utc.with_ymd_and_hms(1990, 4, 12, 12, 0, 0).unwrap(),
+        ));
+        let http = FakeHttpClient::with_200_response();
+        let installation_id = Some("installation_id".to_string());
+        let session_id = "session_id".to_string();
+
+        cx.update(|cx| {
+            let telemetry = Telemetry::new(clock.clone(), http, cx);
+            telemetry.state.lock().max_queue_size = 4;
+            telemetry.start(installation_id, session_id, cx);
+
+            telemetry.report_app_event("test".to_string());
+            telemetry.set_idle(true);
+
+            executor.advance_clock(FLUSH_INTERVAL * 3);
+            assert_eq!(telemetry.state.lock().events_queue.len(), 1);
+
+            telemetry.set_idle(false);
+            executor.advance_clock(FLUSH_INTERVAL);
+            assert!(is_empty_state(&telemetry));
+        });
+    }
+
     // TODO:
     // Test settings
     // Update FakeHTTPClient to keep track of the number of requests and assert on it
@@ -1,7 +1,15 @@
-use crate::{Client, Connection, Credentials, EstablishConnectionError, UserStore};
+use crate::{
+    credentials_provider::CredentialsProvider, Client, Connection, Credentials,
+    EstablishConnectionError, UserStore,
+};
 use anyhow::{anyhow, Result};
-use futures::{stream::BoxStream, StreamExt};
-use gpui::{BackgroundExecutor, Context, Model, TestAppContext};
+use collections::HashMap;
+use futures::{
+    future::{self, LocalBoxFuture},
+    stream::BoxStream,
+    FutureExt as _, StreamExt,
+};
+use gpui::{AsyncAppContext, BackgroundExecutor, Context, Model, TestAppContext};
 use parking_lot::Mutex;
 use rpc::{
     proto::{self, GetPrivateUserInfo, GetPrivateUserInfoResponse},
@@ -23,6 +31,7 @@ struct FakeServerState {
     forbid_connections: bool,
     auth_count: usize,
     access_token: usize,
+    next_collaborator_id: u32,
 }
 
 impl FakeServer {
@@ -37,10 +46,23 @@ impl FakeServer {
             user_id: client_user_id,
             executor: cx.executor(),
         };
+        server.intercept_connections(client);
 
+        client
+            .authenticate_and_connect(false, &cx.to_async())
+            .await
+            .unwrap();
+
+        server
+    }
+
+    /// Routes the given client's authentication and connections to this
+    /// server instead of the real one, without connecting it.
+    pub fn intercept_connections(&self, client: &Arc<Client>) {
+        let client_user_id = self.user_id;
         client
             .override_authenticate({
-                let state = Arc::downgrade(&server.state);
+                let state = Arc::downgrade(&self.state);
                 move |cx| {
                     let state = state.clone();
                     cx.spawn(move |_| async move {
@@ -56,8 +78,8 @@ impl FakeServer {
                 }
             })
             .override_establish_connection({
-                let peer = Arc::downgrade(&server.peer);
-                let state = Arc::downgrade(&server.state);
+                let peer = Arc::downgrade(&self.peer);
+                let state = Arc::downgrade(&self.state);
                 move |credentials, cx| {
                     let peer = peer.clone();
                     let state = state.clone();
@@ -99,13 +121,6 @@ impl FakeServer {
                     })
                 }
             });
-
-        client
-            .authenticate_and_connect(false, &cx.to_async())
-            .await
-            .unwrap();
-
-        server
     }
 
     pub fn disconnect(&self) {
@@ -181,14 +196,140 @@ impl FakeServer {
         }
     }
 
+    /// Simulates another user joining the shared project, returning the peer id the server
+    /// assigned to them.
+    pub fn join_project(&self, project_id: u64, user_id: u64, replica_id: u32) -> proto::PeerId {
+        let peer_id = self.next_collaborator_peer_id();
+        self.send(proto::AddProjectCollaborator {
+            project_id,
+            collaborator: Some(proto::Collaborator {
+                peer_id: Some(peer_id),
+                replica_id,
+                user_id,
+            }),
+        });
+        peer_id
+    }
+
+    /// Simulates a collaborator leaving the shared project.
+    pub fn leave_project(&self, project_id: u64, peer_id: proto::PeerId) {
+        self.send(proto::RemoveProjectCollaborator {
+            project_id,
+            peer_id: Some(peer_id),
+        });
+    }
+
+    /// Simulates a collaborator reconnecting to the shared project, which gives them a new
+    /// peer id that is returned.
+    pub fn rejoin_project(&self, project_id: u64, old_peer_id: proto::PeerId) -> proto::PeerId {
+        let new_peer_id = self.next_collaborator_peer_id();
+        self.send(proto::UpdateProjectCollaborator {
+            project_id,
+            old_peer_id: Some(old_peer_id),
+            new_peer_id: Some(new_peer_id),
+        });
+        new_peer_id
+    }
+
+    /// Simulates a collaborator sending operations, such as edits, for a buffer in the
+    /// shared project. Resolves once the client has applied them.
+    pub async fn update_buffer(
+        &self,
+        project_id: u64,
+        buffer_id: u64,
+        operations: Vec<proto::Operation>,
+    ) -> Result<()> {
+        self.peer
+            .request(
+                self.connection_id(),
+                proto::UpdateBuffer {
+                    project_id,
+                    buffer_id,
+                    operations,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Simulates a collaborator moving their cursors and selections in a buffer of the
+    /// shared project.
+    pub async fn update_selections(
+        &self,
+        project_id: u64,
+        buffer_id: u64,
+        replica_id: u32,
+        lamport_timestamp: u32,
+        selections: Vec<proto::Selection>,
+    ) -> Result<()> {
+        self.update_buffer(
+            project_id,
+            buffer_id,
+            vec![proto::Operation {
+                variant: Some(proto::operation::Variant::UpdateSelections(
+                    proto::operation::UpdateSelections {
+                        replica_id,
+                        lamport_timestamp,
+                        selections,
+                        line_mode: false,
+                        cursor_shape: proto::CursorShape::CursorBar as i32,
+                    },
+                )),
+            }],
+        )
+        .await
+    }
+
+    /// Simulates a collaborator asking the host to save a buffer of the shared project, once
+    /// it has observed the given version.
+    pub async fn save_buffer(
+        &self,
+        project_id: u64,
+        buffer_id: u64,
+        version: Vec<proto::VectorClockEntry>,
+    ) -> Result<proto::BufferSaved> {
+        self.peer
+            .request(
+                self.connection_id(),
+                proto::SaveBuffer {
+                    project_id,
+                    buffer_id,
+                    version,
+                },
+            )
+            .await
+    }
+
     pub fn respond<T: proto::RequestMessage>(&self, receipt: Receipt<T>, response: T::Response) {
         self.peer.respond(receipt, response).unwrap()
     }
 
+    pub fn respond_with_error<T: proto::RequestMessage>(&self, receipt: Receipt<T>, message: &str) {
+        self.peer
+            .respond_with_error(
+                receipt,
+                proto::Error {
+                    message: message.into(),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+    }
+
     fn connection_id(&self) -> ConnectionId {
         self.state.lock().connection_id.expect("not connected")
     }
 
+    fn next_collaborator_peer_id(&self) -> proto::PeerId {
+        let mut state = self.state.lock();
+        state.next_collaborator_id += 1;
+        // The server's own connections are owned by 0, so these never collide with them.
+        proto::PeerId {
+            owner_id: 1,
+            id: state.next_collaborator_id,
+        }
+    }
+
     pub async fn build_user_store(
         &self,
         client: Arc<Client>,
@@ -212,3 +353,46 @@ impl Drop for FakeServer {
         self.disconnect();
     }
 }
+
+/// Keeps credentials in memory instead of the keychain.
+#[derive(Default)]
+pub struct FakeCredentialsProvider {
+    credentials: Mutex<HashMap<String, Credentials>>,
+}
+
+impl FakeCredentialsProvider {
+    pub fn credentials(&self, url: &str) -> Option<Credentials> {
+        self.credentials.lock().get(url).cloned()
+    }
+}
+
+impl CredentialsProvider for FakeCredentialsProvider {
+    fn read_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        _: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<Option<Credentials>>> {
+        future::ready(Ok(self.credentials(url))).boxed_local()
+    }
+
+    fn write_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        credentials: &'a Credentials,
+        _: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<()>> {
+        self.credentials
+            .lock()
+            .insert(url.to_string(), credentials.clone());
+        future::ready(Ok(())).boxed_local()
+    }
+
+    fn delete_credentials<'a>(
+        &'a self,
+        url: &'a str,
+        _: &'a AsyncAppContext,
+    ) -> LocalBoxFuture<'a, Result<()>> {
+        self.credentials.lock().remove(url);
+        future::ready(Ok(())).boxed_local()
+    }
+}
@@ -66,6 +66,7 @@ pub struct Contact {
     pub user: Arc<User>,
     pub online: bool,
     pub busy: bool,
+    pub away: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -736,6 +737,7 @@ impl Contact {
             user,
             online: contact.online,
             busy: contact.busy,
+            away: contact.away,
         })
     }
 }
@@ -49,7 +49,9 @@ CREATE TABLE "projects" (
     "host_user_id" INTEGER REFERENCES users (id) NOT NULL,
     "host_connection_id" INTEGER,
     "host_connection_server_id" INTEGER REFERENCES servers (id) ON DELETE CASCADE,
-    "unregistered" BOOLEAN NOT NULL DEFAULT FALSE
+    "unregistered" BOOLEAN NOT NULL DEFAULT FALSE,
+    "read_only" BOOLEAN NOT NULL DEFAULT FALSE,
+    "approve_join_requests" BOOLEAN NOT NULL DEFAULT FALSE
 );
 CREATE INDEX "index_projects_on_host_connection_server_id" ON "projects" ("host_connection_server_id");
 CREATE INDEX "index_projects_on_host_connection_id_and_host_connection_server_id" ON "projects" ("host_connection_id", "host_connection_server_id");
@@ -77,6 +79,7 @@ CREATE TABLE "worktree_entries" (
     "inode" INTEGER NOT NULL,
     "mtime_seconds" INTEGER NOT NULL,
     "mtime_nanos" INTEGER NOT NULL,
+    "size" INTEGER NOT NULL DEFAULT 0,
     "is_symlink" BOOL NOT NULL,
     "is_external" BOOL NOT NULL,
     "is_ignored" BOOL NOT NULL,
@@ -219,7 +222,8 @@ CREATE TABLE IF NOT EXISTS "channel_messages" (
     "body" TEXT NOT NULL,
     "sent_at" TIMESTAMP,
     "nonce" BLOB NOT NULL,
-    "reply_to_message_id" INTEGER DEFAULT NULL
+    "reply_to_message_id" INTEGER DEFAULT NULL,
+    "edited_at" TIMESTAMP DEFAULT NULL
 );
 CREATE INDEX "index_channel_messages_on_channel_id" ON "channel_messages" ("channel_id");
 CREATE UNIQUE INDEX "index_channel_messages_on_sender_id_nonce" ON "channel_messages" ("sender_id", "nonce");
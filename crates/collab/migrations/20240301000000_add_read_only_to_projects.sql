@@ -0,0 +1 @@
+ALTER TABLE "projects" ADD COLUMN "read_only" BOOLEAN NOT NULL DEFAULT FALSE;
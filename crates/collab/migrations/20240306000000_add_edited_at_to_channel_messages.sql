@@ -0,0 +1 @@
+ALTER TABLE "channel_messages" ADD COLUMN "edited_at" TIMESTAMP WITHOUT TIME ZONE DEFAULT NULL;
@@ -0,0 +1 @@
+ALTER TABLE "projects" ADD COLUMN "approve_join_requests" BOOLEAN NOT NULL DEFAULT FALSE;
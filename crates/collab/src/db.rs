@@ -458,6 +458,15 @@ pub struct CreatedChannelMessage {
     pub notifications: NotificationBatch,
 }
 
+pub struct UpdatedChannelMessage {
+    pub message: proto::ChannelMessage,
+    pub participant_connection_ids: Vec<ConnectionId>,
+    pub notifications: NotificationBatch,
+    /// Mention notifications that were removed because the edit no longer mentions
+    /// their recipients.
+    pub deleted_mention_notifications: Vec<(UserId, NotificationId)>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, FromQueryResult, Serialize, Deserialize)]
 pub struct Invite {
     pub email_address: String,
@@ -593,6 +602,7 @@ pub struct ChannelsForUser {
     pub observed_channel_messages: Vec<proto::ChannelMessageId>,
     pub latest_buffer_versions: Vec<proto::ChannelBufferVersion>,
     pub latest_channel_messages: Vec<proto::ChannelMessageId>,
+    pub unread_channel_messages: Vec<proto::UnreadChannelMessageCount>,
 }
 
 #[derive(Debug)]
@@ -673,6 +683,7 @@ pub struct Project {
     pub collaborators: Vec<ProjectCollaborator>,
     pub worktrees: BTreeMap<u64, Worktree>,
     pub language_servers: Vec<proto::LanguageServer>,
+    pub read_only: bool,
 }
 
 pub struct ProjectCollaborator {
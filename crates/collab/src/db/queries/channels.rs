@@ -652,6 +652,10 @@ impl Database {
             .observed_channel_messages(&channel_ids, user_id, &*tx)
             .await?;
 
+        let unread_channel_messages = self
+            .unread_channel_message_counts(&channel_ids, user_id, &*tx)
+            .await?;
+
         let hosted_projects = self
             .get_hosted_projects(&channel_ids, &roles_by_channel_id, &*tx)
             .await?;
@@ -665,6 +669,7 @@ impl Database {
             latest_channel_messages,
             observed_buffer_versions,
             observed_channel_messages,
+            unread_channel_messages,
         })
     }
 
@@ -162,6 +162,9 @@ impl Database {
                         lower_half: nonce.1,
                     }),
                     reply_to_message_id: row.reply_to_message_id.map(|id| id.to_proto()),
+                    edited_at: row
+                        .edited_at
+                        .map(|edited_at| edited_at.assume_utc().unix_timestamp() as u64),
                 }
             })
             .collect::<Vec<_>>();
@@ -248,6 +251,7 @@ impl Database {
                 nonce: ActiveValue::Set(Uuid::from_u128(nonce)),
                 id: ActiveValue::NotSet,
                 reply_to_message_id: ActiveValue::Set(reply_to_message_id),
+                edited_at: ActiveValue::NotSet,
             })
             .on_conflict(
                 OnConflict::columns([
@@ -269,28 +273,8 @@ impl Database {
                     let mentioned_user_ids =
                         mentions.iter().map(|m| m.user_id).collect::<HashSet<_>>();
 
-                    let mentions = mentions
-                        .iter()
-                        .filter_map(|mention| {
-                            let range = mention.range.as_ref()?;
-                            if !body.is_char_boundary(range.start as usize)
-                                || !body.is_char_boundary(range.end as usize)
-                            {
-                                return None;
-                            }
-                            Some(channel_message_mention::ActiveModel {
-                                message_id: ActiveValue::Set(message_id),
-                                start_offset: ActiveValue::Set(range.start as i32),
-                                end_offset: ActiveValue::Set(range.end as i32),
-                                user_id: ActiveValue::Set(UserId::from_proto(mention.user_id)),
-                            })
-                        })
-                        .collect::<Vec<_>>();
-                    if !mentions.is_empty() {
-                        channel_message_mention::Entity::insert_many(mentions)
-                            .exec(&*tx)
-                            .await?;
-                    }
+                    self.insert_channel_message_mentions(message_id, body, mentions, &*tx)
+                        .await?;
 
                     for mentioned_user in mentioned_user_ids {
                         notifications.extend(
@@ -334,6 +318,145 @@ impl Database {
         .await
     }
 
+    /// Replaces the body and mentions of a channel message. Only the message's sender can
+    /// edit it, and only within [`rpc::CHANNEL_MESSAGE_EDIT_WINDOW`] of sending it.
+    pub async fn update_channel_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        user_id: UserId,
+        body: &str,
+        mentions: &[proto::ChatMention],
+        edited_at: OffsetDateTime,
+    ) -> Result<UpdatedChannelMessage> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &*tx).await?;
+            self.check_user_is_channel_participant(&channel, user_id, &*tx)
+                .await?;
+
+            let message = channel_message::Entity::find_by_id(message_id)
+                .filter(channel_message::Column::ChannelId.eq(channel_id))
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such message"))?;
+            if message.sender_id != user_id {
+                Err(anyhow!("operation could not be completed"))?;
+            }
+            if edited_at - message.sent_at.assume_utc() > rpc::CHANNEL_MESSAGE_EDIT_WINDOW {
+                Err(anyhow!("message can no longer be edited"))?;
+            }
+
+            let previously_mentioned_user_ids = channel_message_mention::Entity::find()
+                .filter(channel_message_mention::Column::MessageId.eq(message_id))
+                .all(&*tx)
+                .await?
+                .into_iter()
+                .map(|mention| mention.user_id)
+                .collect::<HashSet<_>>();
+            channel_message_mention::Entity::delete_many()
+                .filter(channel_message_mention::Column::MessageId.eq(message_id))
+                .exec(&*tx)
+                .await?;
+            self.insert_channel_message_mentions(message_id, body, mentions, &*tx)
+                .await?;
+
+            let edited_at = edited_at.to_offset(time::UtcOffset::UTC);
+            let edited_at = time::PrimitiveDateTime::new(edited_at.date(), edited_at.time());
+            let message = channel_message::ActiveModel {
+                id: ActiveValue::Unchanged(message_id),
+                body: ActiveValue::Set(body.to_string()),
+                edited_at: ActiveValue::Set(Some(edited_at)),
+                ..Default::default()
+            }
+            .update(&*tx)
+            .await?;
+
+            let mention = rpc::Notification::ChannelMessageMention {
+                message_id: message_id.to_proto(),
+                sender_id: user_id.to_proto(),
+                channel_id: channel_id.to_proto(),
+            };
+            let mentioned_user_ids = mentions
+                .iter()
+                .map(|mention| UserId::from_proto(mention.user_id))
+                .collect::<HashSet<_>>();
+
+            // Users who are no longer mentioned lose their notification, if they haven't read it.
+            let mut deleted_mention_notifications = Vec::new();
+            for unmentioned_user in previously_mentioned_user_ids.difference(&mentioned_user_ids) {
+                if let Some(notification_id) = self
+                    .remove_notification(*unmentioned_user, mention.clone(), &*tx)
+                    .await?
+                {
+                    deleted_mention_notifications.push((*unmentioned_user, notification_id));
+                }
+            }
+
+            // Only users who weren't already mentioned are notified again.
+            let mut notifications = Vec::new();
+            for mentioned_user in mentioned_user_ids.difference(&previously_mentioned_user_ids) {
+                notifications.extend(
+                    self.create_notification(*mentioned_user, mention.clone(), false, &*tx)
+                        .await?,
+                );
+            }
+
+            let participant_connection_ids = channel_chat_participant::Entity::find()
+                .filter(channel_chat_participant::Column::ChannelId.eq(channel_id))
+                .all(&*tx)
+                .await?
+                .into_iter()
+                .map(|row| row.connection())
+                .collect();
+
+            let message = self
+                .load_channel_messages(vec![message], &*tx)
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("failed to update message"))?;
+
+            Ok(UpdatedChannelMessage {
+                message,
+                participant_connection_ids,
+                notifications,
+                deleted_mention_notifications,
+            })
+        })
+        .await
+    }
+
+    async fn insert_channel_message_mentions(
+        &self,
+        message_id: MessageId,
+        body: &str,
+        mentions: &[proto::ChatMention],
+        tx: &DatabaseTransaction,
+    ) -> Result<()> {
+        let mentions = mentions
+            .iter()
+            .filter_map(|mention| {
+                let range = mention.range.as_ref()?;
+                if !body.is_char_boundary(range.start as usize)
+                    || !body.is_char_boundary(range.end as usize)
+                {
+                    return None;
+                }
+                Some(channel_message_mention::ActiveModel {
+                    message_id: ActiveValue::Set(message_id),
+                    start_offset: ActiveValue::Set(range.start as i32),
+                    end_offset: ActiveValue::Set(range.end as i32),
+                    user_id: ActiveValue::Set(UserId::from_proto(mention.user_id)),
+                })
+            })
+            .collect::<Vec<_>>();
+        if !mentions.is_empty() {
+            channel_message_mention::Entity::insert_many(mentions)
+                .exec(tx)
+                .await?;
+        }
+        Ok(())
+    }
+
     pub async fn observe_channel_message(
         &self,
         channel_id: ChannelId,
@@ -466,6 +589,67 @@ impl Database {
         Ok(results)
     }
 
+    /// Returns the number of messages in each of the given channels that were
+    /// sent by someone else after the last message the user has observed.
+    pub async fn unread_channel_message_counts(
+        &self,
+        channel_ids: &[ChannelId],
+        user_id: UserId,
+        tx: &DatabaseTransaction,
+    ) -> Result<Vec<proto::UnreadChannelMessageCount>> {
+        #[derive(Debug, FromQueryResult)]
+        struct UnreadCount {
+            channel_id: ChannelId,
+            count: i64,
+        }
+
+        let mut values = String::new();
+        for id in channel_ids {
+            if !values.is_empty() {
+                values.push_str(", ");
+            }
+            write!(&mut values, "({})", id).unwrap();
+        }
+
+        if values.is_empty() {
+            return Ok(Vec::default());
+        }
+
+        let sql = format!(
+            r#"
+            SELECT
+                channel_messages.channel_id as channel_id,
+                COUNT(*) as count
+            FROM channel_messages
+            LEFT JOIN observed_channel_messages
+                ON observed_channel_messages.channel_id = channel_messages.channel_id
+                AND observed_channel_messages.user_id = {user_id}
+            WHERE
+                channel_messages.channel_id in ({values})
+                AND channel_messages.sender_id != {user_id}
+                AND (
+                    observed_channel_messages.channel_message_id IS NULL
+                    OR channel_messages.id > observed_channel_messages.channel_message_id
+                )
+            GROUP BY channel_messages.channel_id
+            "#,
+        );
+
+        let stmt = Statement::from_string(self.pool.get_database_backend(), sql);
+        let mut rows = UnreadCount::find_by_statement(stmt).stream(&*tx).await?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await {
+            let row = row?;
+            results.push(proto::UnreadChannelMessageCount {
+                channel_id: row.channel_id.to_proto(),
+                count: row.count as u64,
+            });
+        }
+
+        Ok(results)
+    }
+
     /// Removes the channel message with the given ID.
     pub async fn remove_channel_message(
         &self,
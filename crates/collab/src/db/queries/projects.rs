@@ -28,6 +28,7 @@ impl Database {
         room_id: RoomId,
         connection: ConnectionId,
         worktrees: &[proto::WorktreeMetadata],
+        approve_join_requests: bool,
     ) -> Result<RoomGuard<(ProjectId, proto::Room)>> {
         self.room_transaction(room_id, |tx| async move {
             let participant = room_participant::Entity::find()
@@ -63,6 +64,7 @@ impl Database {
                 host_connection_server_id: ActiveValue::set(Some(ServerId(
                     connection.owner_id as i32,
                 ))),
+                approve_join_requests: ActiveValue::set(approve_join_requests),
                 ..Default::default()
             }
             .insert(&*tx)
@@ -246,6 +248,7 @@ impl Database {
                         inode: ActiveValue::set(entry.inode as i64),
                         mtime_seconds: ActiveValue::set(mtime.seconds as i64),
                         mtime_nanos: ActiveValue::set(mtime.nanos as i32),
+                        size: ActiveValue::set(entry.size as i64),
                         is_symlink: ActiveValue::set(entry.is_symlink),
                         is_ignored: ActiveValue::set(entry.is_ignored),
                         is_external: ActiveValue::set(entry.is_external),
@@ -266,6 +269,7 @@ impl Database {
                         worktree_entry::Column::Inode,
                         worktree_entry::Column::MtimeSeconds,
                         worktree_entry::Column::MtimeNanos,
+                        worktree_entry::Column::Size,
                         worktree_entry::Column::IsSymlink,
                         worktree_entry::Column::IsIgnored,
                         worktree_entry::Column::GitStatus,
@@ -607,6 +611,7 @@ impl Database {
                                 seconds: db_entry.mtime_seconds as u64,
                                 nanos: db_entry.mtime_nanos as u32,
                             }),
+                            size: db_entry.size as u64,
                             is_symlink: db_entry.is_symlink,
                             is_ignored: db_entry.is_ignored,
                             is_external: db_entry.is_external,
@@ -699,6 +704,7 @@ impl Database {
                     })
                     .collect(),
                 worktrees,
+                read_only: project.read_only,
                 language_servers: language_servers
                     .into_iter()
                     .map(|language_server| proto::LanguageServer {
@@ -874,6 +880,14 @@ impl Database {
                 Err(anyhow!("not authorized to edit projects"))?;
             }
 
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such project"))?;
+            if project.read_only {
+                Err(anyhow!("project is read-only"))?;
+            }
+
             let host = project_collaborator::Entity::find()
                 .filter(
                     project_collaborator::Column::ProjectId
@@ -890,6 +904,96 @@ impl Database {
         .map(|guard| guard.into_inner())
     }
 
+    /// Marks a shared project as read-only for guests, or lifts that restriction, returning the
+    /// connections of the guests that need to be notified. Only the host may do this.
+    pub async fn set_project_read_only(
+        &self,
+        project_id: ProjectId,
+        connection_id: ConnectionId,
+        read_only: bool,
+    ) -> Result<RoomGuard<Vec<ConnectionId>>> {
+        let room_id = self.room_id_for_project(project_id).await?;
+        self.room_transaction(room_id, |tx| async move {
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such project"))?;
+            if project.host_connection()? != connection_id {
+                Err(anyhow!("only the host can change a project's permissions"))?;
+            }
+
+            project::Entity::update(project::ActiveModel {
+                read_only: ActiveValue::set(read_only),
+                ..project.into_active_model()
+            })
+            .exec(&*tx)
+            .await?;
+
+            let guest_connection_ids = project_collaborator::Entity::find()
+                .filter(
+                    project_collaborator::Column::ProjectId
+                        .eq(project_id)
+                        .and(project_collaborator::Column::IsHost.eq(false)),
+                )
+                .all(&*tx)
+                .await?
+                .into_iter()
+                .map(|collaborator| collaborator.connection())
+                .collect();
+            Ok(guest_connection_ids)
+        })
+        .await
+    }
+
+    /// Sets whether the host of a shared project has to let guests in before they can join it.
+    pub async fn set_project_join_approval(
+        &self,
+        project_id: ProjectId,
+        connection_id: ConnectionId,
+        approve_join_requests: bool,
+    ) -> Result<()> {
+        let room_id = self.room_id_for_project(project_id).await?;
+        self.room_transaction(room_id, |tx| async move {
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such project"))?;
+            if project.host_connection()? != connection_id {
+                Err(anyhow!("only the host can change a project's permissions"))?;
+            }
+
+            project::Entity::update(project::ActiveModel {
+                approve_join_requests: ActiveValue::set(approve_join_requests),
+                ..project.into_active_model()
+            })
+            .exec(&*tx)
+            .await?;
+            Ok(())
+        })
+        .await
+        .map(|guard| guard.into_inner())
+    }
+
+    /// Returns the host connection that has to let the given guest into a shared project,
+    /// if the host approves guests before they join.
+    pub async fn host_to_approve_join(
+        &self,
+        project_id: ProjectId,
+        connection_id: ConnectionId,
+    ) -> Result<Option<ConnectionId>> {
+        let host_connection_id = self
+            .host_for_read_only_project_request(project_id, connection_id)
+            .await?;
+        self.transaction(|tx| async move {
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such project"))?;
+            Ok(project.approve_join_requests.then_some(host_connection_id))
+        })
+        .await
+    }
+
     pub async fn project_collaborators_for_buffer_update(
         &self,
         project_id: ProjectId,
@@ -926,14 +1030,24 @@ impl Database {
                 })
                 .collect::<Vec<_>>();
 
-            if collaborators
+            let Some(current_collaborator) = collaborators
                 .iter()
-                .any(|collaborator| collaborator.connection_id == connection_id)
-            {
-                Ok(collaborators)
-            } else {
+                .find(|collaborator| collaborator.connection_id == connection_id)
+            else {
                 Err(anyhow!("no such project"))?
+            };
+
+            if requires_write && !current_collaborator.is_host {
+                let project = project::Entity::find_by_id(project_id)
+                    .one(&*tx)
+                    .await?
+                    .ok_or_else(|| anyhow!("no such project"))?;
+                if project.read_only {
+                    Err(anyhow!("project is read-only"))?;
+                }
             }
+
+            Ok(collaborators)
         })
         .await
     }
@@ -627,6 +627,7 @@ impl Database {
                                         seconds: db_entry.mtime_seconds as u64,
                                         nanos: db_entry.mtime_nanos as u32,
                                     }),
+                                    size: db_entry.size as u64,
                                     is_symlink: db_entry.is_symlink,
                                     is_ignored: db_entry.is_ignored,
                                     is_external: db_entry.is_external,
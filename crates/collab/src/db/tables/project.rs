@@ -12,6 +12,8 @@ pub struct Model {
     pub host_user_id: UserId,
     pub host_connection_id: Option<i32>,
     pub host_connection_server_id: Option<ServerId>,
+    pub read_only: bool,
+    pub approve_join_requests: bool,
 }
 
 impl Model {
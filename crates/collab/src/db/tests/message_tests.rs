@@ -316,6 +316,46 @@ async fn test_unseen_channel_messages(db: &Arc<Database>) {
             },
         ]
     );
+
+    // Check that every message the observer hasn't seen is counted, and that
+    // the sender's own messages are never unread.
+    let unread_counts = |user_id| async move {
+        let mut counts = db
+            .transaction(|tx| async move {
+                db.unread_channel_message_counts(&[channel_1, channel_2], user_id, &*tx)
+                    .await
+            })
+            .await
+            .unwrap();
+        counts.sort_by_key(|count| count.channel_id);
+        counts
+    };
+
+    assert_eq!(
+        unread_counts(observer).await,
+        [
+            rpc::proto::UnreadChannelMessageCount {
+                channel_id: channel_1.to_proto(),
+                count: 3,
+            },
+            rpc::proto::UnreadChannelMessageCount {
+                channel_id: channel_2.to_proto(),
+                count: 1,
+            },
+        ]
+    );
+    assert!(unread_counts(user).await.is_empty());
+
+    db.observe_channel_message(channel_1, observer, third_message)
+        .await
+        .unwrap();
+    assert_eq!(
+        unread_counts(observer).await,
+        [rpc::proto::UnreadChannelMessageCount {
+            channel_id: channel_2.to_proto(),
+            count: 1,
+        }]
+    );
 }
 
 test_both_dbs!(
@@ -419,3 +459,76 @@ async fn test_channel_message_mentions(db: &Arc<Database>) {
         ]
     );
 }
+
+test_both_dbs!(
+    test_channel_message_edit_window,
+    test_channel_message_edit_window_postgres,
+    test_channel_message_edit_window_sqlite
+);
+
+async fn test_channel_message_edit_window(db: &Arc<Database>) {
+    let user = new_test_user(db, "user@example.com").await;
+    let channel = db.create_channel("channel", None, user).await.unwrap().0;
+
+    let owner_id = db.create_server("test").await.unwrap().0 as u32;
+    db.join_channel_chat(channel.id, rpc::ConnectionId { owner_id, id: 0 }, user)
+        .await
+        .unwrap();
+
+    let old_message_id = db
+        .create_channel_message(
+            channel.id,
+            user,
+            "old",
+            &[],
+            OffsetDateTime::now_utc() - rpc::CHANNEL_MESSAGE_EDIT_WINDOW * 2,
+            1,
+            None,
+        )
+        .await
+        .unwrap()
+        .message_id;
+    let new_message_id = db
+        .create_channel_message(
+            channel.id,
+            user,
+            "new",
+            &[],
+            OffsetDateTime::now_utc(),
+            2,
+            None,
+        )
+        .await
+        .unwrap()
+        .message_id;
+
+    db.update_channel_message(
+        channel.id,
+        old_message_id,
+        user,
+        "old, edited",
+        &[],
+        OffsetDateTime::now_utc(),
+    )
+    .await
+    .unwrap_err();
+    db.update_channel_message(
+        channel.id,
+        new_message_id,
+        user,
+        "new, edited",
+        &[],
+        OffsetDateTime::now_utc(),
+    )
+    .await
+    .unwrap();
+
+    let messages = db
+        .get_channel_messages(channel.id, user, 2, None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|message| message.body)
+        .collect::<Vec<_>>();
+    assert_eq!(messages, &["old", "new, edited"]);
+}
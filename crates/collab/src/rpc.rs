@@ -5,7 +5,8 @@ use crate::{
     db::{
         self, BufferId, ChannelId, ChannelRole, ChannelsForUser, CreatedChannelMessage, Database,
         InviteMemberResult, MembershipUpdated, MessageId, NotificationId, ProjectId,
-        RemoveChannelMemberResult, RespondToChannelInvite, RoomId, ServerId, User, UserId,
+        RemoveChannelMemberResult, RespondToChannelInvite, RoomId, ServerId, UpdatedChannelMessage,
+        User, UserId,
     },
     executor::Executor,
     AppState, Error, Result,
@@ -55,7 +56,7 @@ use std::{
     ops::{Deref, DerefMut},
     rc::Rc,
     sync::{
-        atomic::{AtomicBool, Ordering::SeqCst},
+        atomic::{AtomicBool, AtomicU32, Ordering::SeqCst},
         Arc,
     },
     time::{Duration, Instant},
@@ -68,6 +69,9 @@ use util::SemanticVersion;
 
 pub const RECONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 pub const CLEANUP_TIMEOUT: Duration = Duration::from_secs(10);
+pub const JOIN_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60);
+
+static NEXT_JOIN_REQUEST_ID: AtomicU32 = AtomicU32::new(0);
 
 const MESSAGE_COUNT_PER_PAGE: usize = 100;
 const MAX_MESSAGE_LEN: usize = 1024;
@@ -108,7 +112,7 @@ struct Session {
     peer: Arc<Peer>,
     connection_pool: Arc<parking_lot::Mutex<ConnectionPool>>,
     live_kit_client: Option<Arc<dyn live_kit_server::api::Client>>,
-    _executor: Executor,
+    executor: Executor,
 }
 
 impl Session {
@@ -210,6 +214,8 @@ impl Server {
             .add_request_handler(join_project)
             .add_message_handler(leave_project)
             .add_request_handler(update_project)
+            .add_request_handler(set_project_read_only)
+            .add_request_handler(set_project_join_approval)
             .add_request_handler(update_worktree)
             .add_message_handler(start_language_server)
             .add_message_handler(update_language_server)
@@ -227,6 +233,9 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::SynchronizeBuffers>)
             .add_request_handler(forward_read_only_project_request::<proto::InlayHints>)
             .add_request_handler(forward_read_only_project_request::<proto::OpenBufferByPath>)
+            .add_request_handler(forward_read_only_project_request::<proto::OpenRemoteItem>)
+            .add_request_handler(forward_read_only_project_request::<proto::DownloadFileChunk>)
+            .add_request_handler(forward_read_only_project_request::<proto::CloseRemoteItem>)
             .add_request_handler(forward_mutating_project_request::<proto::GetCompletions>)
             .add_request_handler(
                 forward_mutating_project_request::<proto::ApplyCompletionAdditionalEdits>,
@@ -248,16 +257,21 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::OnTypeFormatting>)
             .add_request_handler(forward_mutating_project_request::<proto::SaveBuffer>)
             .add_message_handler(create_buffer_for_peer)
+            .add_message_handler(update_remote_item)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateBufferFile>)
+            .add_message_handler(share_clipboard_entry)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateDiffBase>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdatePinnedPaths>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdateActivePath>)
             .add_request_handler(get_users)
             .add_request_handler(fuzzy_search_users)
             .add_request_handler(request_contact)
             .add_request_handler(remove_contact)
+            .add_message_handler(set_away)
             .add_request_handler(respond_to_contact_request)
             .add_request_handler(create_channel)
             .add_request_handler(delete_channel)
@@ -277,6 +291,7 @@ impl Server {
             .add_message_handler(leave_channel_chat)
             .add_request_handler(send_channel_message)
             .add_request_handler(remove_channel_message)
+            .add_request_handler(edit_channel_message)
             .add_request_handler(get_channel_messages)
             .add_request_handler(get_channel_messages_by_id)
             .add_request_handler(get_notifications)
@@ -620,7 +635,7 @@ impl Server {
                 peer: this.peer.clone(),
                 connection_pool: this.connection_pool.clone(),
                 live_kit_client: this.app_state.live_kit_client.clone(),
-                _executor: executor.clone()
+                executor: executor.clone()
             };
             update_user_contacts(user_id, &session).await?;
 
@@ -1544,6 +1559,7 @@ async fn share_project(
             RoomId::from_proto(request.room_id),
             session.connection_id,
             &request.worktrees,
+            request.approve_join_requests,
         )
         .await?;
     response.send(proto::ShareProjectResponse {
@@ -1585,6 +1601,52 @@ async fn join_project(
 
     tracing::info!(%project_id, "join project");
 
+    // Hosts that approve guests decide whether the guest may join before any of the
+    // project is sent.
+    let host_connection_id = session
+        .db()
+        .await
+        .host_to_approve_join(project_id, session.connection_id)
+        .await?;
+    if let Some(host_connection_id) = host_connection_id {
+        // Combining the server's id with a counter keeps request ids unique across
+        // servers, so the host can tell apart several requests from the same user.
+        let request_id = (u64::from(session.connection_id.owner_id) << 32)
+            | u64::from(NEXT_JOIN_REQUEST_ID.fetch_add(1, SeqCst));
+        let join_request = session.peer.request(
+            host_connection_id,
+            proto::RequestJoinProject {
+                project_id: project_id.to_proto(),
+                requester_id: guest_user_id.to_proto(),
+                request_id,
+            },
+        );
+        let allow = futures::select_biased! {
+            join_response = join_request.fuse() => match join_response {
+                Ok(join_response) => join_response.allow,
+                // A host that can't ask its user lets everyone in, as it would without approval.
+                Err(error) if error.error_tag("unhandled_message").is_some() => true,
+                Err(error) => Err(error)?,
+            },
+            _ = session.executor.sleep(JOIN_APPROVAL_TIMEOUT).fuse() => {
+                session
+                    .peer
+                    .send(
+                        host_connection_id,
+                        proto::CancelJoinRequest {
+                            project_id: project_id.to_proto(),
+                            request_id,
+                        },
+                    )
+                    .trace_err();
+                Err(anyhow!("the host didn't respond to the request to join the project"))?
+            }
+        };
+        if !allow {
+            Err(anyhow!("the host declined the request to join the project"))?;
+        }
+    }
+
     let (project, replica_id) = &mut *session
         .db()
         .await
@@ -1632,6 +1694,7 @@ async fn join_project(
         replica_id: replica_id.0 as u32,
         collaborators: collaborators.clone(),
         language_servers: project.language_servers.clone(),
+        read_only: project.read_only,
     })?;
 
     for (worktree_id, worktree) in mem::take(&mut project.worktrees) {
@@ -1750,6 +1813,51 @@ async fn update_project(
     Ok(())
 }
 
+/// Lets the host of a project prevent guests from editing it, or allow it again.
+async fn set_project_read_only(
+    request: proto::SetProjectReadOnly,
+    response: Response<proto::SetProjectReadOnly>,
+    session: Session,
+) -> Result<()> {
+    let project_id = ProjectId::from_proto(request.project_id);
+    let guest_connection_ids = session
+        .db()
+        .await
+        .set_project_read_only(project_id, session.connection_id, request.read_only)
+        .await?;
+    broadcast(
+        Some(session.connection_id),
+        guest_connection_ids.iter().copied(),
+        |connection_id| {
+            session
+                .peer
+                .forward_send(session.connection_id, connection_id, request.clone())
+        },
+    );
+    response.send(proto::Ack {})?;
+
+    Ok(())
+}
+
+/// Lets the host of a project choose whether guests have to be let in before they join.
+async fn set_project_join_approval(
+    request: proto::SetProjectJoinApproval,
+    response: Response<proto::SetProjectJoinApproval>,
+    session: Session,
+) -> Result<()> {
+    session
+        .db()
+        .await
+        .set_project_join_approval(
+            ProjectId::from_proto(request.project_id),
+            session.connection_id,
+            request.approve_join_requests,
+        )
+        .await?;
+    response.send(proto::Ack {})?;
+    Ok(())
+}
+
 /// Updates other participants with changes to the worktree
 async fn update_worktree(
     request: proto::UpdateWorktree,
@@ -1937,6 +2045,23 @@ async fn create_buffer_for_peer(
     Ok(())
 }
 
+/// Send the host's state for a remote item to the guest that opened it.
+async fn update_remote_item(request: proto::UpdateRemoteItem, session: Session) -> Result<()> {
+    session
+        .db()
+        .await
+        .check_user_is_project_host(
+            ProjectId::from_proto(request.project_id),
+            session.connection_id,
+        )
+        .await?;
+    let peer_id = request.peer_id.ok_or_else(|| anyhow!("invalid peer id"))?;
+    session
+        .peer
+        .forward_send(session.connection_id, peer_id.into(), request)?;
+    Ok(())
+}
+
 /// Notify other participants that a buffer has been updated. This is
 /// allowed for guests as long as the update is limited to selections.
 async fn update_buffer(
@@ -2022,6 +2147,34 @@ async fn broadcast_project_message_from_host<T: EntityMessage<Entity = ShareProj
     Ok(())
 }
 
+/// Share a clipboard entry from any collaborator with the rest of the project.
+async fn share_clipboard_entry(
+    request: proto::ShareClipboardEntry,
+    session: Session,
+) -> Result<()> {
+    if request.text.len() > rpc::MAX_SHARED_CLIPBOARD_ENTRY_LEN {
+        return Err(anyhow!("clipboard entry is too long"))?;
+    }
+
+    let project_id = ProjectId::from_proto(request.project_id);
+    let project_connection_ids = session
+        .db()
+        .await
+        .project_connection_ids(project_id, session.connection_id)
+        .await?;
+
+    broadcast(
+        Some(session.connection_id),
+        project_connection_ids.iter().copied(),
+        |connection_id| {
+            session
+                .peer
+                .forward_send(session.connection_id, connection_id, request.clone())
+        },
+    );
+    Ok(())
+}
+
 /// Start following another user in a call.
 async fn follow(
     request: proto::Follow,
@@ -2287,6 +2440,21 @@ async fn respond_to_contact_request(
     Ok(())
 }
 
+/// Mark the current connection as idle or active, notifying the user's contacts
+/// if this changes whether the user is away.
+async fn set_away(request: proto::SetAway, session: Session) -> Result<()> {
+    let away_changed = {
+        let mut pool = session.connection_pool().await;
+        let was_away = pool.is_user_away(session.user_id);
+        pool.set_connection_away(session.connection_id, request.away)?;
+        was_away != pool.is_user_away(session.user_id)
+    };
+    if away_changed {
+        update_user_contacts(session.user_id, &session).await?;
+    }
+    Ok(())
+}
+
 /// Remove a contact.
 async fn remove_contact(
     request: proto::RemoveContact,
@@ -3045,6 +3213,7 @@ async fn send_channel_message(
         timestamp: timestamp.unix_timestamp() as u64,
         nonce: Some(nonce),
         reply_to_message_id: request.reply_to_message_id,
+        edited_at: None,
     };
     broadcast(
         Some(session.connection_id),
@@ -3107,6 +3276,72 @@ async fn remove_channel_message(
     Ok(())
 }
 
+/// Edit the body of a channel message
+async fn edit_channel_message(
+    request: proto::EditChannelMessage,
+    response: Response<proto::EditChannelMessage>,
+    session: Session,
+) -> Result<()> {
+    let body = request.body.trim().to_string();
+    if body.len() > MAX_MESSAGE_LEN {
+        return Err(anyhow!("message is too long"))?;
+    }
+    if body.is_empty() {
+        return Err(anyhow!("message can't be blank"))?;
+    }
+
+    let channel_id = ChannelId::from_proto(request.channel_id);
+    let message_id = MessageId::from_proto(request.message_id);
+    let UpdatedChannelMessage {
+        message,
+        participant_connection_ids,
+        notifications,
+        deleted_mention_notifications,
+    } = session
+        .db()
+        .await
+        .update_channel_message(
+            channel_id,
+            message_id,
+            session.user_id,
+            &body,
+            &request.mentions,
+            OffsetDateTime::now_utc(),
+        )
+        .await?;
+    broadcast(
+        Some(session.connection_id),
+        participant_connection_ids,
+        |connection| {
+            session.peer.send(
+                connection,
+                proto::ChannelMessageUpdate {
+                    channel_id: channel_id.to_proto(),
+                    message: Some(message.clone()),
+                },
+            )
+        },
+    );
+    response.send(proto::Ack {})?;
+
+    let connection_pool = session.connection_pool().await;
+    send_notifications(&*connection_pool, &session.peer, notifications);
+    for (user_id, notification_id) in deleted_mention_notifications {
+        for connection_id in connection_pool.user_connection_ids(user_id) {
+            session
+                .peer
+                .send(
+                    connection_id,
+                    proto::DeleteNotification {
+                        notification_id: notification_id.to_proto(),
+                    },
+                )
+                .trace_err();
+        }
+    }
+    Ok(())
+}
+
 /// Mark a channel message as read
 async fn acknowledge_channel_message(
     request: proto::AckChannelMessage,
@@ -3382,6 +3617,21 @@ fn build_channels_update(
     }
 
     update.latest_channel_buffer_versions = channels.latest_buffer_versions;
+    // Report a count for every channel with messages, so that clients treat
+    // the channels that have none unread as read rather than counting the
+    // latest message as a new one.
+    update.unread_channel_message_counts = channels
+        .latest_channel_messages
+        .iter()
+        .map(|latest| proto::UnreadChannelMessageCount {
+            channel_id: latest.channel_id,
+            count: channels
+                .unread_channel_messages
+                .iter()
+                .find(|unread| unread.channel_id == latest.channel_id)
+                .map_or(0, |unread| unread.count),
+        })
+        .collect();
     update.latest_channel_message_ids = channels.latest_channel_messages;
 
     for (channel_id, participants) in channels.channel_participants {
@@ -3433,6 +3683,7 @@ fn contact_for_user(user_id: UserId, busy: bool, pool: &ConnectionPool) -> proto
         user_id: user_id.to_proto(),
         online: pool.is_user_online(user_id),
         busy,
+        away: pool.is_user_away(user_id),
     }
 }
 
@@ -41,6 +41,7 @@ pub struct Connection {
     pub user_id: UserId,
     pub admin: bool,
     pub zed_version: ZedVersion,
+    pub away: bool,
 }
 
 impl ConnectionPool {
@@ -63,6 +64,7 @@ impl ConnectionPool {
                 user_id,
                 admin,
                 zed_version,
+                away: false,
             },
         );
         let connected_user = self.connected_users.entry(user_id).or_default();
@@ -121,6 +123,24 @@ impl ConnectionPool {
             .is_empty()
     }
 
+    pub fn set_connection_away(&mut self, connection_id: ConnectionId, away: bool) -> Result<()> {
+        let connection = self
+            .connections
+            .get_mut(&connection_id)
+            .ok_or_else(|| anyhow!("no such connection"))?;
+        connection.away = away;
+        Ok(())
+    }
+
+    /// A user is away when they're online, but have been idle on every
+    /// connection.
+    pub fn is_user_away(&self, user_id: UserId) -> bool {
+        self.is_user_online(user_id)
+            && self
+                .user_connections(user_id)
+                .all(|connection| connection.away)
+    }
+
     #[cfg(test)]
     pub fn check_invariants(&self) {
         for (connection_id, connection) in &self.connections {
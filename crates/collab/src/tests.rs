@@ -13,6 +13,7 @@ mod notification_tests;
 mod random_channel_buffer_tests;
 mod random_project_collaboration_tests;
 mod randomized_test_helpers;
+mod remote_item_tests;
 mod test_server;
 
 pub use randomized_test_helpers::{
@@ -263,6 +263,132 @@ async fn test_remove_channel_message(
     assert_messages(&channel_chat_c, expected_messages, cx_c);
 }
 
+#[gpui::test]
+async fn test_edit_channel_message(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+    cx_c: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let client_c = server.create_client(cx_c, "user_c").await;
+
+    let channel_id = server
+        .make_channel(
+            "the-channel",
+            None,
+            (&client_a, cx_a),
+            &mut [(&client_b, cx_b), (&client_c, cx_c)],
+        )
+        .await;
+
+    let channel_chat_a = client_a
+        .channel_store()
+        .update(cx_a, |store, cx| store.open_channel_chat(channel_id, cx))
+        .await
+        .unwrap();
+    let channel_chat_b = client_b
+        .channel_store()
+        .update(cx_b, |store, cx| store.open_channel_chat(channel_id, cx))
+        .await
+        .unwrap();
+
+    let message_id = channel_chat_a
+        .update(cx_a, |c, cx| c.send_message("one".into(), cx).unwrap())
+        .await
+        .unwrap();
+    channel_chat_a
+        .update(cx_a, |c, cx| c.send_message("two".into(), cx).unwrap())
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    // Client A edits their first message, mentioning client C.
+    channel_chat_a
+        .update(cx_a, |c, cx| {
+            c.update_message(
+                message_id,
+                MessageParams {
+                    text: "one, @user_c".into(),
+                    mentions: vec![(5..12, client_c.id())],
+                    reply_to_message_id: None,
+                },
+                cx,
+            )
+            .unwrap()
+        })
+        .await
+        .unwrap();
+
+    // Client B sees the message change in place.
+    executor.run_until_parked();
+    let expected_messages = &["one, @user_c", "two"];
+    assert_messages(&channel_chat_a, expected_messages, cx_a);
+    assert_messages(&channel_chat_b, expected_messages, cx_b);
+    channel_chat_b.read_with(cx_b, |chat, _| {
+        let message = chat.find_loaded_message(message_id).unwrap();
+        assert!(message.edited_at.is_some());
+        assert_eq!(message.mentions, vec![(5..12, client_c.id())]);
+    });
+
+    // Client C is notified of the new mention.
+    client_c.notification_store().read_with(cx_c, |store, _| {
+        assert_eq!(store.notification_count(), 2);
+        assert_eq!(
+            store.notification_at(0).unwrap().notification,
+            Notification::ChannelMessageMention {
+                message_id,
+                sender_id: client_a.id(),
+                channel_id: channel_id.0,
+            }
+        );
+    });
+
+    // Client A edits the mention back out, and client C's notification goes away.
+    channel_chat_a
+        .update(cx_a, |c, cx| {
+            c.update_message(message_id, "one, again".into(), cx)
+                .unwrap()
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let expected_messages = &["one, again", "two"];
+    assert_messages(&channel_chat_a, expected_messages, cx_a);
+    assert_messages(&channel_chat_b, expected_messages, cx_b);
+    client_c.notification_store().read_with(cx_c, |store, _| {
+        assert_eq!(store.notification_count(), 1);
+    });
+
+    // Client B can't edit client A's messages.
+    channel_chat_b
+        .update(cx_b, |c, cx| {
+            c.update_message(message_id, "hijacked".into(), cx).unwrap()
+        })
+        .await
+        .unwrap_err();
+    executor.run_until_parked();
+    assert_messages(&channel_chat_a, expected_messages, cx_a);
+    assert_messages(&channel_chat_b, expected_messages, cx_b);
+
+    // Client C joins the channel chat, and sees the edited message.
+    let channel_chat_c = client_c
+        .channel_store()
+        .update(cx_c, |store, cx| store.open_channel_chat(channel_id, cx))
+        .await
+        .unwrap();
+    assert_messages(&channel_chat_c, expected_messages, cx_c);
+    channel_chat_c.read_with(cx_c, |chat, _| {
+        assert!(chat
+            .find_loaded_message(message_id)
+            .unwrap()
+            .edited_at
+            .is_some());
+    });
+}
+
 #[track_caller]
 fn assert_messages(chat: &Model<ChannelChat>, messages: &[&str], cx: &mut TestAppContext) {
     assert_eq!(
@@ -466,3 +592,68 @@ async fn test_chat_replies(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext)
         )
     });
 }
+
+#[gpui::test]
+async fn test_channel_mention_notifications_when_unfocused(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+
+    let channel_id = server
+        .make_channel(
+            "the-channel",
+            None,
+            (&client_a, cx_a),
+            &mut [(&client_b, cx_b)],
+        )
+        .await;
+
+    let channel_chat_a = client_a
+        .channel_store()
+        .update(cx_a, |store, cx| store.open_channel_chat(channel_id, cx))
+        .await
+        .unwrap();
+    let mention_b = |text: &str| MessageParams {
+        text: text.into(),
+        mentions: vec![(text.find('@').unwrap()..text.len(), client_b.id())],
+        reply_to_message_id: None,
+    };
+
+    // None of client B's windows has focus, so the mention is posted to the
+    // platform's notification center.
+    channel_chat_a
+        .update(cx_a, |c, cx| {
+            c.send_message(mention_b("hi @user_b"), cx).unwrap()
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert_eq!(
+        cx_b.posted_notifications(),
+        [(
+            "user_a mentioned you in #the-channel".to_string(),
+            "hi @user_b".to_string()
+        )]
+    );
+    client_b.channel_store().read_with(cx_b, |store, _| {
+        assert_eq!(store.unread_mention_count(channel_id), 1);
+    });
+
+    // Once client B has a focused window, mentions are only counted.
+    cx_b.add_empty_window().update(|cx| cx.activate_window());
+    channel_chat_a
+        .update(cx_a, |c, cx| {
+            c.send_message(mention_b("again @user_b"), cx).unwrap()
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert_eq!(cx_b.posted_notifications().len(), 1);
+    client_b.channel_store().read_with(cx_b, |store, _| {
+        assert_eq!(store.unread_mention_count(channel_id), 2);
+    });
+}
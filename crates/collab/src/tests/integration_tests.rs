@@ -1,5 +1,5 @@
 use crate::{
-    rpc::{CLEANUP_TIMEOUT, RECONNECT_TIMEOUT},
+    rpc::{CLEANUP_TIMEOUT, JOIN_APPROVAL_TIMEOUT, RECONNECT_TIMEOUT},
     tests::{channel_id, room_participants, RoomParticipants, TestClient, TestServer},
 };
 use call::{room, ActiveCall, ParticipantLocation, Room};
@@ -19,10 +19,11 @@ use language::{
 use live_kit_client::MacOSDisplay;
 use lsp::LanguageServerId;
 use project::{
-    search::SearchQuery, DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
+    project_settings::ProjectSettings, search::SearchQuery, DiagnosticSummary, DownloadProgress,
+    FormatTrigger, HoverBlockKind, Project, ProjectPath, DOWNLOAD_CHUNK_SIZE,
 };
 use rand::prelude::*;
-use rpc::proto::ChannelRole;
+use rpc::proto::{self, ChannelRole};
 use serde_json::json;
 use settings::SettingsStore;
 use std::{
@@ -3165,6 +3166,7 @@ async fn test_fs_operations(
         .await
         .unwrap();
     executor.run_until_parked();
+    assert_eq!(client_a.fs().trashed_paths(), [Path::new("/dir/DIR")]);
 
     worktree_a.read_with(cx_a, |worktree, _| {
         assert_eq!(
@@ -3186,12 +3188,21 @@ async fn test_fs_operations(
         );
     });
 
+    // Whether files are trashed follows the host's settings.
+    cx_a.update(|cx| {
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.permanently_delete = true;
+            });
+        });
+    });
     project_b
         .update(cx_b, |project, cx| {
             project.delete_entry(entry.id, cx).unwrap()
         })
         .await
         .unwrap();
+    assert_eq!(client_a.fs().trashed_paths(), [Path::new("/dir/DIR")]);
 
     worktree_a.read_with(cx_a, |worktree, _| {
         assert_eq!(
@@ -3524,6 +3535,533 @@ async fn test_editing_while_guest_opens_buffer(
     buffer_b.read_with(cx_b, |buf, _| assert_eq!(buf.text(), text));
 }
 
+#[gpui::test]
+async fn test_shared_clipboard(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a.fs().insert_tree("/dir", json!({})).await;
+    let (project_a, _) = client_a.build_local_project("/dir", cx_a).await;
+
+    // Nothing can be shared before the project is.
+    assert!(project_a
+        .update(cx_a, |project, cx| {
+            project.share_clipboard_entry("local".into(), cx)
+        })
+        .is_err());
+
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+    let shared_clipboard = |project: &Model<Project>, cx: &mut TestAppContext| {
+        project.read_with(cx, |project, _| {
+            project
+                .shared_clipboard()
+                .map(|entry| (entry.author_id, entry.text.clone()))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    project_b
+        .update(cx_b, |project, cx| {
+            project.share_clipboard_entry("from b".into(), cx)
+        })
+        .unwrap();
+    project_a
+        .update(cx_a, |project, cx| {
+            project.share_clipboard_entry("from a".into(), cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+
+    let expected = vec![
+        (Some(client_a.user_id().unwrap()), "from a".to_string()),
+        (Some(client_b.user_id().unwrap()), "from b".to_string()),
+    ];
+    assert_eq!(shared_clipboard(&project_a, cx_a), expected);
+    assert_eq!(shared_clipboard(&project_b, cx_b), expected);
+
+    // Sharing the same text again moves it to the top.
+    project_b
+        .update(cx_b, |project, cx| {
+            project.share_clipboard_entry("from b".into(), cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+    assert_eq!(
+        shared_clipboard(&project_a, cx_a),
+        [
+            (Some(client_b.user_id().unwrap()), "from b".to_string()),
+            (Some(client_a.user_id().unwrap()), "from a".to_string()),
+        ]
+    );
+
+    // Text over the size limit isn't shared.
+    assert!(project_b
+        .update(cx_b, |project, cx| {
+            project.share_clipboard_entry("b".repeat(rpc::MAX_SHARED_CLIPBOARD_ENTRY_LEN + 1), cx)
+        })
+        .is_err());
+    executor.run_until_parked();
+    assert_eq!(shared_clipboard(&project_a, cx_a).len(), 2);
+    assert_eq!(shared_clipboard(&project_b, cx_b).len(), 2);
+}
+
+#[gpui::test]
+async fn test_shared_pinned_paths(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    cx_a.update(|cx| {
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.share_pinned_files = true;
+            });
+        });
+    });
+    client_a
+        .fs()
+        .insert_tree("/dir", json!({ "a.txt": "", "b.txt": "", "c.txt": "" }))
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+    let pinned_paths = |project: &Model<Project>, cx: &mut TestAppContext| {
+        project.read_with(cx, |project, _| {
+            project
+                .all_pinned_paths()
+                .map(|path| path.path.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        })
+    };
+    let project_path = |path: &str| ProjectPath {
+        worktree_id,
+        path: Path::new(path).into(),
+    };
+
+    // Guests' own pins come first, and are kept when the host's pins change.
+    project_b.update(cx_b, |project, cx| {
+        project.toggle_pinned_path(project_path("b.txt"), cx)
+    });
+    project_a.update(cx_a, |project, cx| {
+        project.toggle_pinned_path(project_path("a.txt"), cx);
+        project.toggle_pinned_path(project_path("b.txt"), cx);
+    });
+    executor.run_until_parked();
+    assert_eq!(pinned_paths(&project_a, cx_a), ["a.txt", "b.txt"]);
+    assert_eq!(pinned_paths(&project_b, cx_b), ["b.txt", "a.txt"]);
+
+    // Files that are deleted are unpinned.
+    client_a
+        .fs()
+        .remove_file("/dir/a.txt".as_ref(), Default::default())
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert_eq!(pinned_paths(&project_a, cx_a), ["b.txt"]);
+    assert_eq!(pinned_paths(&project_b, cx_b), ["b.txt"]);
+}
+
+#[gpui::test]
+async fn test_collaborator_active_paths(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+    let active_call_b = cx_b.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree("/dir", json!({ "a.txt": "a", "b.txt": "b" }))
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let path_a: ProjectPath = (worktree_id, "a.txt").into();
+    let path_b: ProjectPath = (worktree_id, "b.txt").into();
+
+    // The host's active file is sent to guests when they join.
+    project_a.update(cx_a, |project, cx| {
+        project.set_active_path(Some(path_a.clone()), cx)
+    });
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let users_at_path = |project: &Model<Project>, path: &ProjectPath, cx: &mut TestAppContext| {
+        project.read_with(cx, |project, _| {
+            project
+                .collaborators_at_path(path)
+                .map(|collaborator| collaborator.user_id)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        users_at_path(&project_b, &path_a, cx_b),
+        [client_a.user_id().unwrap()]
+    );
+    assert!(users_at_path(&project_a, &path_a, cx_a).is_empty());
+
+    project_b.update(cx_b, |project, cx| {
+        project.set_active_path(Some(path_b.clone()), cx)
+    });
+    executor.run_until_parked();
+    assert_eq!(
+        users_at_path(&project_a, &path_b, cx_a),
+        [client_b.user_id().unwrap()]
+    );
+
+    // Closing the active file clears it.
+    project_a.update(cx_a, |project, cx| project.set_active_path(None, cx));
+    executor.run_until_parked();
+    assert!(users_at_path(&project_b, &path_a, cx_b).is_empty());
+
+    // Collaborators who leave are no longer shown.
+    active_call_b
+        .update(cx_b, |call, cx| call.hang_up(cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert!(users_at_path(&project_a, &path_b, cx_a).is_empty());
+}
+
+#[gpui::test]
+async fn test_downloading_files_as_guest(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    // Spans several chunks.
+    let large_contents = "x".repeat(DOWNLOAD_CHUNK_SIZE as usize * 2 + 7);
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "a-contents",
+                "sub": {
+                    "large.txt": large_contents,
+                    "nested": { "b.txt": "b-contents" },
+                },
+            }),
+        )
+        .await;
+    client_b.fs().insert_tree("/downloads", json!({})).await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    let download = |path: &str, destination: &str, cx_b: &mut TestAppContext| {
+        project_b
+            .update(cx_b, |project, cx| {
+                project.download_entry((worktree_id, path).into(), PathBuf::from(destination), cx)
+            })
+            .unwrap()
+    };
+
+    // Downloads are refused until the host allows them.
+    assert!(download("a.txt", "/downloads/a.txt", cx_b)
+        .task
+        .await
+        .is_err());
+
+    cx_a.update(|cx| {
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.allow_guest_downloads = true;
+            });
+        });
+    });
+    download("a.txt", "/downloads/a.txt", cx_b)
+        .task
+        .await
+        .unwrap();
+    assert_eq!(
+        client_b
+            .fs()
+            .load("/downloads/a.txt".as_ref())
+            .await
+            .unwrap(),
+        "a-contents"
+    );
+
+    let download_sub = download("sub", "/downloads/sub", cx_b);
+    let progress = download_sub.progress.clone();
+    download_sub.task.await.unwrap();
+    assert_eq!(
+        *progress.borrow(),
+        DownloadProgress {
+            downloaded_bytes: large_contents.len() as u64 + "b-contents".len() as u64,
+            total_bytes: large_contents.len() as u64 + "b-contents".len() as u64,
+        }
+    );
+    assert_eq!(
+        client_b
+            .fs()
+            .load("/downloads/sub/large.txt".as_ref())
+            .await
+            .unwrap(),
+        large_contents
+    );
+    assert_eq!(
+        client_b
+            .fs()
+            .load("/downloads/sub/nested/b.txt".as_ref())
+            .await
+            .unwrap(),
+        "b-contents"
+    );
+    // Files are written under a temporary name until they are complete.
+    assert!(!client_b
+        .fs()
+        .files()
+        .iter()
+        .any(|path| path.to_string_lossy().ends_with(".download")));
+}
+
+#[gpui::test]
+async fn test_project_read_only_for_guests(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree("/dir", json!({ "a.txt": "a-contents" }))
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+
+    // Guests joining a read-only project can't edit it.
+    project_a
+        .update(cx_a, |project, cx| {
+            project.set_read_only_for_guests(true, cx)
+        })
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    assert!(project_b.read_with(cx_b, |project, _| project.is_read_only()));
+    assert!(buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+
+    // The host can let guests edit again at any time.
+    project_a
+        .update(cx_a, |project, cx| {
+            project.set_read_only_for_guests(false, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert!(!project_b.read_with(cx_b, |project, _| project.is_read_only()));
+    assert!(!buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+    project_b
+        .update(cx_b, |project, cx| {
+            project.create_entry((worktree_id, "b.txt"), false, cx)
+        })
+        .await
+        .unwrap();
+
+    // The server rejects changes from guests while the project is read-only.
+    project_a
+        .update(cx_a, |project, cx| {
+            project.set_read_only_for_guests(true, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert!(project_b.read_with(cx_b, |project, _| project.is_read_only()));
+    assert!(buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+    assert!(project_b
+        .update(cx_b, |project, cx| {
+            project.create_entry((worktree_id, "c.txt"), false, cx)
+        })
+        .await
+        .is_err());
+
+    // The change is undone if the server doesn't acknowledge it.
+    server.forbid_connections();
+    server.disconnect_client(client_a.peer_id().unwrap());
+    executor.advance_clock(RECEIVE_TIMEOUT);
+    executor.run_until_parked();
+    let set_read_only = project_a.update(cx_a, |project, cx| {
+        project.set_read_only_for_guests(false, cx)
+    });
+    assert!(!project_a.read_with(cx_a, |project, _| project.is_read_only_for_guests()));
+    assert!(set_read_only.await.is_err());
+    assert!(project_a.read_with(cx_a, |project, _| project.is_read_only_for_guests()));
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_approving_join_requests(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+    let active_call_b = cx_b.read(ActiveCall::global);
+
+    cx_a.update(|cx| {
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.approve_join_requests = true;
+            });
+        });
+    });
+    client_a
+        .fs()
+        .insert_tree("/dir", json!({ "a.txt": "a-contents" }))
+        .await;
+    let (project_a, _) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+
+    let join_project = |cx_b: &mut TestAppContext| {
+        let room_b = active_call_b.read_with(cx_b, |call, _| call.room().unwrap().clone());
+        room_b.update(cx_b, |room, cx| {
+            room.join_project(
+                project_id,
+                client_b.app_state.languages.clone(),
+                client_b.app_state.fs.clone(),
+                cx,
+            )
+        })
+    };
+    let pending_request_ids = |cx_a: &mut TestAppContext| {
+        project_a.read_with(cx_a, |project, _| project.pending_join_request_ids())
+    };
+
+    // The guest can't join until the host responds, and is turned away if declined.
+    let join = join_project(cx_b);
+    executor.run_until_parked();
+    assert_eq!(
+        project_a.read_with(cx_a, |project, _| project.collaborators().len()),
+        0
+    );
+    let request_ids = pending_request_ids(cx_a);
+    assert_eq!(request_ids.len(), 1);
+    project_a.update(cx_a, |project, _| {
+        project.respond_to_join_request(request_ids[0], false)
+    });
+    assert!(join.await.is_err());
+
+    // Requests the host doesn't answer in time are withdrawn on both ends.
+    let join = join_project(cx_b);
+    executor.run_until_parked();
+    assert_eq!(pending_request_ids(cx_a).len(), 1);
+    executor.advance_clock(JOIN_APPROVAL_TIMEOUT);
+    assert!(join.await.is_err());
+    executor.run_until_parked();
+    assert_eq!(pending_request_ids(cx_a), Vec::<u64>::new());
+
+    // Several requests from the same user are kept apart.
+    let first_join = join_project(cx_b);
+    let second_join = join_project(cx_b);
+    executor.run_until_parked();
+    let request_ids = pending_request_ids(cx_a);
+    assert_eq!(request_ids.len(), 2);
+    project_a.update(cx_a, |project, _| {
+        for request_id in request_ids {
+            project.respond_to_join_request(request_id, false);
+        }
+    });
+    assert!(first_join.await.is_err());
+    assert!(second_join.await.is_err());
+
+    let join = join_project(cx_b);
+    executor.run_until_parked();
+    let request_ids = pending_request_ids(cx_a);
+    project_a.update(cx_a, |project, _| {
+        project.respond_to_join_request(request_ids[0], true)
+    });
+    let project_b = join.await.unwrap();
+    assert_eq!(
+        project_b.read_with(cx_b, |project, cx| project
+            .worktrees()
+            .next()
+            .unwrap()
+            .read(cx)
+            .root_name()
+            .to_string()),
+        "dir"
+    );
+
+    // Once the host stops approving guests, they join without being asked about.
+    cx_b.update(|_| drop(project_b));
+    cx_a.update(|cx| {
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.approve_join_requests = false;
+            });
+        });
+    });
+    executor.run_until_parked();
+    join_project(cx_b).await.unwrap();
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_leaving_worktree_while_opening_buffer(
     executor: BackgroundExecutor,
@@ -5667,6 +6205,57 @@ async fn test_contacts(
     }
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_contacts_away(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_a2: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_a2 = server.create_client(cx_a2, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .make_contacts(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+
+    let is_contact_away = |cx: &TestAppContext| {
+        client_b.user_store().read_with(cx, |store, _| {
+            store
+                .contacts()
+                .iter()
+                .find(|contact| contact.user.github_login == "user_a")
+                .unwrap()
+                .away
+        })
+    };
+    executor.run_until_parked();
+    assert!(!is_contact_away(cx_b));
+
+    // A user is only away once every one of their connections is idle.
+    client_a
+        .client()
+        .send(proto::SetAway { away: true })
+        .unwrap();
+    executor.run_until_parked();
+    assert!(!is_contact_away(cx_b));
+
+    client_a2
+        .client()
+        .send(proto::SetAway { away: true })
+        .unwrap();
+    executor.run_until_parked();
+    assert!(is_contact_away(cx_b));
+
+    client_a
+        .client()
+        .send(proto::SetAway { away: false })
+        .unwrap();
+    executor.run_until_parked();
+    assert!(!is_contact_away(cx_b));
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_contact_requests(
     executor: BackgroundExecutor,
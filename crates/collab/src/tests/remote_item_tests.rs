@@ -0,0 +1,135 @@
+use crate::tests::TestServer;
+use client::RECEIVE_TIMEOUT;
+use gpui::{
+    div, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, IntoElement, Model,
+    ModelContext, Render, TestAppContext, ViewContext, WindowContext,
+};
+use project::Project;
+use serde_json::json;
+use workspace::{
+    item::{Item, ItemEvent},
+    remote_item::{register_remote_item_source, RemoteItem, RemoteItemSource},
+};
+
+#[gpui::test]
+async fn test_remote_items(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let (server, client_a, client_b, channel_id) = TestServer::start2(cx_a, cx_b).await;
+    let executor = cx_a.executor();
+    cx_a.update(register_remote_item_source::<WorktreeNames>);
+    client_a.fs().insert_tree("/b", json!({})).await;
+    client_a.fs().insert_tree("/c", json!({})).await;
+
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+    let project_a = workspace_a.update(cx_a, |workspace, _| workspace.project().clone());
+
+    // The guest opens an item showing the host's state.
+    let item_b = workspace_b
+        .update(cx_b, |workspace, cx| {
+            workspace.open_remote_item::<WorktreeNamesItem>(
+                (),
+                |names, cx| WorktreeNamesItem {
+                    names,
+                    focus_handle: cx.focus_handle(),
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    item_b.update(cx_b, |item, _| assert_eq!(item.names, ["a"]));
+
+    // The item is updated when the host's state changes.
+    project_a
+        .update(cx_a, |project, cx| {
+            project.find_or_create_local_worktree("/b", true, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    item_b.update(cx_b, |item, _| assert_eq!(item.names, ["a", "b"]));
+
+    // The item keeps being updated after the guest reconnects.
+    server.disconnect_client(client_b.peer_id().unwrap());
+    executor.advance_clock(RECEIVE_TIMEOUT);
+    executor.run_until_parked();
+    project_a
+        .update(cx_a, |project, cx| {
+            project.find_or_create_local_worktree("/c", true, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    item_b.update(cx_b, |item, _| assert_eq!(item.names, ["a", "b", "c"]));
+}
+
+struct WorktreeNames {
+    project: Model<Project>,
+    _subscription: gpui::Subscription,
+}
+
+impl RemoteItemSource for WorktreeNames {
+    const KIND: &'static str = "WorktreeNames";
+    type Request = ();
+    type State = Vec<String>;
+
+    fn open(project: Model<Project>, _: (), cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            _subscription: cx.observe(&project, |_, _, cx| cx.notify()),
+            project,
+        }
+    }
+
+    fn state(&self, cx: &AppContext) -> Vec<String> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).root_name().to_string())
+            .collect()
+    }
+}
+
+struct WorktreeNamesItem {
+    names: Vec<String>,
+    focus_handle: FocusHandle,
+}
+
+impl EventEmitter<()> for WorktreeNamesItem {}
+
+impl FocusableView for WorktreeNamesItem {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for WorktreeNamesItem {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+    }
+}
+
+impl Item for WorktreeNamesItem {
+    type Event = ();
+
+    fn tab_content(&self, _: Option<usize>, _: bool, _: &WindowContext) -> AnyElement {
+        div().into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn to_item_events(_: &(), _: impl FnMut(ItemEvent)) {}
+}
+
+impl RemoteItem for WorktreeNamesItem {
+    type Source = WorktreeNames;
+
+    fn set_remote_state(&mut self, names: Vec<String>, cx: &mut ViewContext<Self>) {
+        self.names = names;
+        cx.notify();
+    }
+}
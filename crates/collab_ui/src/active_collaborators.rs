@@ -0,0 +1,67 @@
+use crate::collab_panel;
+use call::ActiveCall;
+use gpui::{Model, Render, Subscription, ViewContext};
+use ui::{prelude::*, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+/// Shows how many people are in the current call, and who they are.
+pub struct ActiveCollaborators {
+    active_call: Model<ActiveCall>,
+    _observe_active_call: Subscription,
+    _observe_room: Option<Subscription>,
+}
+
+impl ActiveCollaborators {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let active_call = ActiveCall::global(cx);
+        let _observe_active_call = cx.observe(&active_call, |this, _, cx| this.observe_room(cx));
+        let mut this = Self {
+            active_call,
+            _observe_active_call,
+            _observe_room: None,
+        };
+        this.observe_room(cx);
+        this
+    }
+
+    fn observe_room(&mut self, cx: &mut ViewContext<Self>) {
+        self._observe_room = self
+            .active_call
+            .read(cx)
+            .room()
+            .cloned()
+            .map(|room| cx.observe(&room, |_, _, cx| cx.notify()));
+        cx.notify();
+    }
+}
+
+impl Render for ActiveCollaborators {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(room) = self.active_call.read(cx).room() else {
+            return div().into_any_element();
+        };
+        let collaborators = room
+            .read(cx)
+            .remote_participants()
+            .values()
+            .map(|participant| participant.user.github_login.clone())
+            .collect::<Vec<_>>();
+        if collaborators.is_empty() {
+            return div().into_any_element();
+        }
+
+        let tooltip = format!("In call with {}", collaborators.join(", "));
+        Button::new("active-collaborators", collaborators.len().to_string())
+            .icon(IconName::Collab)
+            .icon_position(IconPosition::Start)
+            .icon_size(IconSize::Small)
+            .label_size(LabelSize::Small)
+            .tooltip(move |cx| Tooltip::text(tooltip.clone(), cx))
+            .on_click(|_, cx| cx.dispatch_action(Box::new(collab_panel::ToggleFocus)))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for ActiveCollaborators {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
@@ -1,16 +1,19 @@
 use crate::{collab_panel, ChatPanelSettings};
 use anyhow::Result;
 use call::{room, ActiveCall};
-use channel::{ChannelChat, ChannelChatEvent, ChannelMessage, ChannelMessageId, ChannelStore};
+use channel::{
+    ChannelChat, ChannelChatEvent, ChannelEvent, ChannelMessage, ChannelMessageId, ChannelStore,
+};
 use client::{ChannelId, Client};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use db::kvp::KEY_VALUE_STORE;
 use editor::Editor;
+use futures::FutureExt as _;
 use gpui::{
-    actions, div, list, prelude::*, px, Action, AppContext, AsyncWindowContext, ClipboardItem,
-    CursorStyle, DismissEvent, ElementId, EventEmitter, FocusHandle, FocusableView, FontStyle,
-    FontWeight, HighlightStyle, ListOffset, ListScrollEvent, ListState, Model, Render, StyledText,
-    Subscription, Task, View, ViewContext, VisualContext, WeakView,
+    actions, div, img, list, prelude::*, px, Action, AnyElement, AppContext, AsyncWindowContext,
+    ClipboardItem, CursorStyle, DismissEvent, ElementId, EventEmitter, FocusHandle, FocusableView,
+    FontStyle, FontWeight, HighlightStyle, ListOffset, ListScrollEvent, ListState, Model, Render,
+    StyledText, Subscription, Task, View, ViewContext, VisualContext, WeakView,
 };
 use language::LanguageRegistry;
 use menu::Confirm;
@@ -35,6 +38,7 @@ mod message_editor;
 
 const MESSAGE_LOADING_THRESHOLD: usize = 50;
 const CHAT_PANEL_KEY: &'static str = "ChatPanel";
+const MAX_IMAGE_HEIGHT: Pixels = px(240.);
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|workspace: &mut Workspace, _| {
@@ -43,6 +47,34 @@ pub fn init(cx: &mut AppContext) {
         });
     })
     .detach();
+
+    cx.subscribe(
+        &ChannelStore::global(cx),
+        |channel_store, event: &ChannelEvent, cx| {
+            if let ChannelEvent::Mentioned {
+                channel_id,
+                message,
+            } = event
+            {
+                // Mentions are only posted to the notification center while none of
+                // the app's windows has focus, since otherwise the panel's badge is seen.
+                if cx.active_window().is_some() {
+                    return;
+                }
+                let Some(channel) = channel_store.read(cx).channel_for_id(*channel_id) else {
+                    return;
+                };
+                cx.post_notification(
+                    &format!(
+                        "{} mentioned you in #{}",
+                        message.sender.github_login, channel.name
+                    ),
+                    &message.body,
+                );
+            }
+        },
+    )
+    .detach();
 }
 
 pub struct ChatPanel {
@@ -60,6 +92,8 @@ pub struct ChatPanel {
     subscriptions: Vec<gpui::Subscription>,
     is_scrolled_to_bottom: bool,
     markdown_data: HashMap<ChannelMessageId, RichText>,
+    /// Images from untrusted domains that the user chose to load.
+    loaded_images: HashSet<SharedString>,
     focus_handle: FocusHandle,
     open_context_menu: Option<(u64, Subscription)>,
     highlighted_message: Option<(u64, Task<()>)>,
@@ -124,6 +158,7 @@ impl ChatPanel {
                 active: false,
                 width: None,
                 markdown_data: Default::default(),
+                loaded_images: Default::default(),
                 focus_handle: cx.focus_handle(),
                 open_context_menu: None,
                 highlighted_message: None,
@@ -139,6 +174,10 @@ impl ChatPanel {
                     .detach_and_log_err(cx);
             }
 
+            // Keeps the badge on the panel's button up to date.
+            this.subscriptions
+                .push(cx.observe(&this.channel_store, |_, _, cx| cx.notify()));
+
             this.subscriptions.push(cx.subscribe(
                 &ActiveCall::global(cx),
                 move |this: &mut Self, call, event: &room::Event, cx| match event {
@@ -261,6 +300,13 @@ impl ChatPanel {
                 old_range,
                 new_count,
             } => {
+                // Messages that were edited in place are rendered again.
+                if let Some((chat, _)) = &self.active_chat {
+                    let chat = chat.read(cx);
+                    for ix in old_range.start..old_range.start + new_count {
+                        self.markdown_data.remove(&chat.message(ix).id);
+                    }
+                }
                 self.message_list.splice(old_range.clone(), *new_count);
                 if self.active {
                     self.acknowledge_last_message(cx);
@@ -422,8 +468,12 @@ impl ChatPanel {
             });
 
         let _is_pending = message.is_pending();
+        if !self.markdown_data.contains_key(&message.id) {
+            self.load_code_block_languages(&message, cx);
+        }
 
         let belongs_to_user = Some(message.sender.id) == self.client.user_id();
+        let can_edit_message = belongs_to_user && message.is_within_edit_window();
         let can_delete_message = belongs_to_user || is_admin;
 
         let element_id: ElementId = match message.id {
@@ -495,7 +545,16 @@ impl ChatPanel {
                                     ))
                                     .size(LabelSize::Small)
                                     .color(Color::Muted),
-                                ),
+                                )
+                                .when(message.edited_at.is_some(), |el| {
+                                    el.child(
+                                        div().ml_1().child(
+                                            Label::new("(edited)")
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        ),
+                                    )
+                                }),
                         )
                     })
                     .when(
@@ -541,13 +600,21 @@ impl ChatPanel {
                                 &message,
                             )
                         });
+                        let images = text.images.clone();
+                        let body = text.element("body".into(), cx);
+                        let images = images
+                            .into_iter()
+                            .enumerate()
+                            .map(|(ix, image)| self.render_image(ix, image, cx))
+                            .collect::<Vec<_>>();
                         el.child(
                             v_flex()
                                 .w_full()
                                 .text_ui_sm()
                                 .id(element_id)
                                 .group("")
-                                .child(text.element("body".into(), cx))
+                                .child(body)
+                                .children(images)
                                 .child(
                                     div()
                                         .absolute()
@@ -569,6 +636,7 @@ impl ChatPanel {
                                                         Some(Self::render_message_menu(
                                                             &this,
                                                             message_id,
+                                                            can_edit_message,
                                                             can_delete_message,
                                                             cx,
                                                         ))
@@ -603,6 +671,42 @@ impl ChatPanel {
             )
     }
 
+    /// Renders an image from a message, which is only fetched once the user
+    /// clicks it unless it's hosted on a trusted domain, so that senders
+    /// can't track who reads their messages.
+    fn render_image(
+        &self,
+        ix: usize,
+        image: rich_text::RemoteImage,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let trusted_domains = &ChatPanelSettings::get_global(cx).trusted_image_domains;
+        if self.loaded_images.contains(&image.url)
+            || rich_text::is_trusted_url(&image.url, trusted_domains)
+        {
+            return img(image.url.to_string())
+                .max_w_full()
+                .max_h(MAX_IMAGE_HEIGHT)
+                .into_any_element();
+        }
+
+        let host = rich_text::url_host(&image.url).unwrap_or_default();
+        let label = if image.alt.is_empty() {
+            format!("Load image from {host}")
+        } else {
+            format!("Load image \"{}\" from {host}", image.alt)
+        };
+        let url = image.url.clone();
+        Button::new(("load-image", ix), label)
+            .label_size(LabelSize::Small)
+            .on_click(cx.listener(move |this, _, cx| {
+                this.loaded_images.insert(url.clone());
+                cx.notify();
+            }))
+            .tooltip(move |cx| Tooltip::text(image.url.clone(), cx))
+            .into_any_element()
+    }
+
     fn has_open_menu(&self, message_id: Option<u64>) -> bool {
         match self.open_context_menu.as_ref() {
             Some((id, _)) => Some(*id) == message_id,
@@ -613,6 +717,7 @@ impl ChatPanel {
     fn render_message_menu(
         this: &View<Self>,
         message_id: u64,
+        can_edit_message: bool,
         can_delete_message: bool,
         cx: &mut WindowContext,
     ) -> View<ContextMenu> {
@@ -623,7 +728,7 @@ impl ChatPanel {
                     None,
                     cx.handler_for(&this, move |this, cx| {
                         this.message_editor.update(cx, |editor, cx| {
-                            editor.set_reply_to_message_id(message_id);
+                            editor.set_reply_to_message_id(message_id, cx);
                             editor.focus_handle(cx).focus(cx);
                         })
                     }),
@@ -642,6 +747,13 @@ impl ChatPanel {
                         });
                     }),
                 )
+                .when(can_edit_message, move |menu| {
+                    menu.entry(
+                        "Edit message",
+                        None,
+                        cx.handler_for(&this, move |this, cx| this.edit_message(message_id, cx)),
+                    )
+                })
                 .when(can_delete_message, move |menu| {
                     menu.entry(
                         "Delete message",
@@ -660,6 +772,34 @@ impl ChatPanel {
         menu
     }
 
+    /// Code blocks are highlighted once their languages are loaded, so the message is rendered
+    /// again after any that it uses have finished loading.
+    fn load_code_block_languages(&self, message: &ChannelMessage, cx: &mut ViewContext<Self>) {
+        let pending_languages = rich_text::code_block_languages(&message.body)
+            .into_iter()
+            .filter(|name| {
+                self.languages
+                    .language_for_name_or_extension(name)
+                    .now_or_never()
+                    .is_none()
+            })
+            .map(|name| self.languages.language_for_name_or_extension(&name))
+            .collect::<Vec<_>>();
+        if pending_languages.is_empty() {
+            return;
+        }
+
+        let message_id = message.id;
+        cx.spawn(|this, mut cx| async move {
+            futures::future::join_all(pending_languages).await;
+            this.update(&mut cx, |this, cx| {
+                this.markdown_data.remove(&message_id);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn render_markdown_with_mentions(
         language_registry: &Arc<LanguageRegistry>,
         current_user_id: u64,
@@ -674,16 +814,43 @@ impl ChatPanel {
             })
             .collect::<Vec<_>>();
 
-        rich_text::render_rich_text(message.body.clone(), &mentions, language_registry, None)
+        let text =
+            rich_text::render_rich_text(message.body.clone(), &mentions, language_registry, None);
+        // Bodies that are nothing but Markdown syntax, like an empty code block, are shown as
+        // they were written.
+        if text.text.trim().is_empty() && text.images.is_empty() && !message.body.trim().is_empty()
+        {
+            return RichText {
+                text: message.body.trim().to_string().into(),
+                highlights: Vec::new(),
+                link_ranges: Vec::new(),
+                link_urls: Arc::new([]),
+                images: Vec::new(),
+            };
+        }
+        text
     }
 
     fn send(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
         if let Some((chat, _)) = self.active_chat.as_ref() {
-            let message = self
-                .message_editor
-                .update(cx, |editor, cx| editor.take_message(cx));
+            // An edit can't clear a message; keep the edit open so it can be fixed or cancelled.
+            let message_editor = self.message_editor.read(cx);
+            if message_editor.editing_message_id().is_some() && message_editor.is_blank(cx) {
+                return;
+            }
+
+            let (message, editing_message_id) = self.message_editor.update(cx, |editor, cx| {
+                (editor.take_message(cx), editor.take_editing_message_id())
+            });
 
-            if let Some(task) = chat
+            if let Some(message_id) = editing_message_id {
+                if let Some(task) = chat
+                    .update(cx, |chat, cx| chat.update_message(message_id, message, cx))
+                    .log_err()
+                {
+                    task.detach_and_log_err(cx);
+                }
+            } else if let Some(task) = chat
                 .update(cx, |chat, cx| chat.send_message(message, cx))
                 .log_err()
             {
@@ -692,6 +859,19 @@ impl ChatPanel {
         }
     }
 
+    fn edit_message(&mut self, id: u64, cx: &mut ViewContext<Self>) {
+        let Some(body) = self
+            .active_chat()
+            .and_then(|chat| Some(chat.read(cx).find_loaded_message(id)?.body.clone()))
+        else {
+            return;
+        };
+        self.message_editor.update(cx, |editor, cx| {
+            editor.edit_message(id, &body, cx);
+            editor.focus_handle(cx).focus(cx);
+        });
+    }
+
     fn remove_message(&mut self, id: u64, cx: &mut ViewContext<Self>) {
         if let Some((chat, _)) = self.active_chat.as_ref() {
             chat.update(cx, |chat, cx| chat.remove_message(id, cx).detach())
@@ -773,14 +953,17 @@ impl ChatPanel {
     }
 
     fn close_reply_preview(&mut self, _: &CloseReplyPreview, cx: &mut ViewContext<Self>) {
-        self.message_editor
-            .update(cx, |editor, _| editor.clear_reply_to_message_id());
+        self.message_editor.update(cx, |editor, cx| {
+            editor.clear_reply_to_message_id();
+            editor.cancel_edit(cx);
+        });
     }
 }
 
 impl Render for ChatPanel {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let reply_to_message_id = self.message_editor.read(cx).reply_to_message_id();
+        let editing_message_id = self.message_editor.read(cx).editing_message_id();
 
         v_flex()
             .key_context("ChatPanel")
@@ -885,13 +1068,43 @@ impl Render for ChatPanel {
                     )
                 })
             })
+            .when(editing_message_id.is_some(), |el| {
+                el.child(
+                    h_flex()
+                        .when(!self.is_scrolled_to_bottom, |el| {
+                            el.border_t_1().border_color(cx.theme().colors().border)
+                        })
+                        .justify_between()
+                        .items_center()
+                        .py_1()
+                        .px_2()
+                        .bg(cx.theme().colors().background)
+                        .child(
+                            Label::new("Editing message")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            IconButton::new("cancel-edit", IconName::Close)
+                                .shape(ui::IconButtonShape::Square)
+                                .tooltip(|cx| {
+                                    Tooltip::for_action("Cancel edit", &CloseReplyPreview, cx)
+                                })
+                                .on_click(cx.listener(move |_, _, cx| {
+                                    cx.dispatch_action(CloseReplyPreview.boxed_clone())
+                                })),
+                        ),
+                )
+            })
             .children(
                 Some(
                     h_flex()
                         .key_context("MessageEditor")
                         .on_action(cx.listener(ChatPanel::close_reply_preview))
                         .when(
-                            !self.is_scrolled_to_bottom && reply_to_message_id.is_none(),
+                            !self.is_scrolled_to_bottom
+                                && reply_to_message_id.is_none()
+                                && editing_message_id.is_none(),
                             |el| el.border_t_1().border_color(cx.theme().colors().border),
                         )
                         .p_2()
@@ -958,6 +1171,15 @@ impl Panel for ChatPanel {
         Some("Chat Panel")
     }
 
+    fn icon_label(&self, cx: &WindowContext) -> Option<String> {
+        let count = self.channel_store.read(cx).total_unread_mention_count();
+        if count == 0 {
+            None
+        } else {
+            Some(count.to_string())
+        }
+    }
+
     fn toggle_action(&self) -> Box<dyn gpui::Action> {
         Box::new(ToggleFocus)
     }
@@ -997,6 +1219,7 @@ mod tests {
             nonce: 5,
             mentions: vec![(ranges[0].clone(), 101), (ranges[1].clone(), 102)],
             reply_to_message_id: None,
+            edited_at: None,
         };
 
         let message = ChatPanel::render_markdown_with_mentions(&language_registry, 102, &message);
@@ -1044,6 +1267,7 @@ mod tests {
             nonce: 5,
             mentions: Vec::new(),
             reply_to_message_id: None,
+            edited_at: None,
         };
 
         let message = ChatPanel::render_markdown_with_mentions(&language_registry, 102, &message);
@@ -1084,6 +1308,7 @@ mod tests {
             nonce: 5,
             mentions: Vec::new(),
             reply_to_message_id: None,
+            edited_at: None,
         };
 
         let message = ChatPanel::render_markdown_with_mentions(&language_registry, 102, &message);
@@ -1129,4 +1354,89 @@ mod tests {
             ]
         );
     }
+
+    #[gpui::test]
+    fn test_render_markdown_with_code() {
+        let language_registry = Arc::new(LanguageRegistry::test());
+        let message = |body: &str| channel::ChannelMessage {
+            id: ChannelMessageId::Saved(0),
+            body: body.to_string(),
+            timestamp: OffsetDateTime::now_utc(),
+            sender: Arc::new(client::User {
+                github_login: "fgh".into(),
+                avatar_uri: "avatar_fgh".into(),
+                id: 103,
+            }),
+            nonce: 5,
+            mentions: Vec::new(),
+            reply_to_message_id: None,
+            edited_at: None,
+        };
+
+        // Code in a language that isn't available is still shown as code.
+        let rendered = ChatPanel::render_markdown_with_mentions(
+            &language_registry,
+            102,
+            &message("run `cargo test`:\n\n```unknown-lang\nlet a = 1;\n```"),
+        );
+        let (body, ranges) = marked_text_ranges("run «cargo test»:\n\n«let a = 1;»", false);
+        assert_eq!(rendered.text, body);
+        assert_eq!(
+            rendered.highlights,
+            vec![
+                (ranges[0].clone(), Highlight::InlineCode(false)),
+                (ranges[1].clone(), Highlight::Code),
+            ]
+        );
+        assert_eq!(
+            rich_text::code_block_languages("```rs\na\n```\n```rs\nb\n```\n```\nc\n```"),
+            ["rs"]
+        );
+
+        // Bodies with nothing to show fall back to their plain text.
+        let rendered =
+            ChatPanel::render_markdown_with_mentions(&language_registry, 102, &message("```\n```"));
+        assert_eq!(rendered.text, "```\n```");
+        assert!(rendered.highlights.is_empty());
+    }
+
+    #[gpui::test]
+    fn test_render_markdown_from_untrusted_sender() {
+        let language_registry = Arc::new(LanguageRegistry::test());
+        let message = channel::ChannelMessage {
+            id: ChannelMessageId::Saved(0),
+            body:
+                "[click](javascript:alert(1)) <b>hi</b> ![logo](https://cdn.example.com/logo.png)"
+                    .to_string(),
+            timestamp: OffsetDateTime::now_utc(),
+            sender: Arc::new(client::User {
+                github_login: "fgh".into(),
+                avatar_uri: "avatar_fgh".into(),
+                id: 103,
+            }),
+            nonce: 5,
+            mentions: Vec::new(),
+            reply_to_message_id: None,
+            edited_at: None,
+        };
+
+        let message = ChatPanel::render_markdown_with_mentions(&language_registry, 102, &message);
+
+        assert_eq!(message.text, "click <b>hi</b>");
+        assert!(message.link_ranges.is_empty());
+        assert!(message.highlights.is_empty());
+        assert_eq!(
+            message.images,
+            vec![rich_text::RemoteImage {
+                url: "https://cdn.example.com/logo.png".into(),
+                alt: "logo".into(),
+            }]
+        );
+
+        let url = &message.images[0].url;
+        assert!(!rich_text::is_trusted_url(url, &[]));
+        assert!(!rich_text::is_trusted_url(url, &["ample.com".into()]));
+        assert!(rich_text::is_trusted_url(url, &["example.com".into()]));
+        assert!(rich_text::is_trusted_url(url, &["cdn.example.com".into()]));
+    }
 }
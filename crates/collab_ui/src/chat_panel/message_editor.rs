@@ -35,6 +35,7 @@ pub struct MessageEditor {
     mentions_task: Option<Task<()>>,
     channel_id: Option<ChannelId>,
     reply_to_message_id: Option<u64>,
+    editing_message_id: Option<u64>,
 }
 
 struct MessageEditorCompletionProvider(WeakView<MessageEditor>);
@@ -114,6 +115,7 @@ impl MessageEditor {
             mentions: Vec::new(),
             mentions_task: None,
             reply_to_message_id: None,
+            editing_message_id: None,
         }
     }
 
@@ -121,7 +123,13 @@ impl MessageEditor {
         self.reply_to_message_id
     }
 
-    pub fn set_reply_to_message_id(&mut self, reply_to_message_id: u64) {
+    /// Makes the next message a reply to the given one, abandoning any edit in progress.
+    pub fn set_reply_to_message_id(
+        &mut self,
+        reply_to_message_id: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.cancel_edit(cx);
         self.reply_to_message_id = Some(reply_to_message_id);
     }
 
@@ -129,6 +137,36 @@ impl MessageEditor {
         self.reply_to_message_id = None;
     }
 
+    pub fn editing_message_id(&self) -> Option<u64> {
+        self.editing_message_id
+    }
+
+    /// Fills the editor with the body of one of the user's messages, so that sending it
+    /// replaces that message instead of posting a new one.
+    pub fn edit_message(&mut self, message_id: u64, body: &str, cx: &mut ViewContext<Self>) {
+        self.reply_to_message_id = None;
+        self.editing_message_id = Some(message_id);
+        self.editor.update(cx, |editor, cx| {
+            editor.set_text(body, cx);
+            editor.move_to_end(&editor::actions::MoveToEnd, cx);
+        });
+    }
+
+    pub fn cancel_edit(&mut self, cx: &mut ViewContext<Self>) {
+        if self.editing_message_id.take().is_some() {
+            self.editor.update(cx, |editor, cx| editor.clear(cx));
+            self.mentions.clear();
+        }
+    }
+
+    pub fn take_editing_message_id(&mut self) -> Option<u64> {
+        self.editing_message_id.take()
+    }
+
+    pub fn is_blank(&self, cx: &gpui::AppContext) -> bool {
+        self.editor.read(cx).text(cx).trim().is_empty()
+    }
+
     pub fn set_channel(
         &mut self,
         channel_id: ChannelId,
@@ -33,8 +33,9 @@ use smallvec::SmallVec;
 use std::{mem, sync::Arc};
 use theme::{ActiveTheme, ThemeSettings};
 use ui::{
-    prelude::*, tooltip_container, Avatar, AvatarAvailabilityIndicator, Button, Color, ContextMenu,
-    Icon, IconButton, IconName, IconSize, Indicator, Label, ListHeader, ListItem, Tooltip,
+    prelude::*, tooltip_container, AudioStatus, Avatar, AvatarAudioStatusIndicator,
+    AvatarAvailabilityIndicator, Button, Color, ContextMenu, Icon, IconButton, IconName, IconSize,
+    Indicator, Label, ListHeader, ListItem, Tooltip,
 };
 use util::{maybe, ResultExt, TryFutureExt};
 use workspace::{
@@ -300,6 +301,7 @@ impl CollabPanel {
                             });
                         }
                     }
+                    ChannelEvent::Mentioned { .. } => {}
                 },
             ));
 
@@ -850,12 +852,33 @@ impl CollabPanel {
             self.user_store.read(cx).current_user().map(|user| user.id) == Some(user_id);
         let tooltip = format!("Follow {}", user.github_login);
 
-        let is_call_admin = ActiveCall::global(cx).read(cx).room().is_some_and(|room| {
-            room.read(cx).local_participant().role == proto::ChannelRole::Admin
-        });
+        let active_call = ActiveCall::global(cx);
+        let room = active_call.read(cx).room().map(|room| room.read(cx));
+        let is_call_admin =
+            room.is_some_and(|room| room.local_participant().role == proto::ChannelRole::Admin);
+        let (is_speaking, is_muted) = room
+            .and_then(|room| {
+                if is_current_user {
+                    Some((room.is_speaking(), room.is_muted()))
+                } else {
+                    let participant = room.remote_participants().get(&user_id)?;
+                    Some((participant.speaking, participant.muted))
+                }
+            })
+            .unwrap_or_default();
+
+        let avatar = Avatar::new(user.avatar_uri.clone())
+            .border_color(if is_speaking {
+                cx.theme().status().info
+            } else {
+                gpui::transparent_black()
+            })
+            .when(is_muted, |avatar| {
+                avatar.indicator(AvatarAudioStatusIndicator::new(AudioStatus::Muted))
+            });
 
         ListItem::new(SharedString::from(user.github_login.clone()))
-            .start_slot(Avatar::new(user.avatar_uri.clone()))
+            .start_slot(avatar)
             .child(Label::new(user.github_login.clone()))
             .selected(is_selected)
             .end_slot(if is_pending {
@@ -2298,6 +2321,7 @@ impl CollabPanel {
     ) -> impl IntoElement {
         let online = contact.online;
         let busy = contact.busy || calling;
+        let away = contact.away;
         let github_login = SharedString::from(contact.user.github_login.clone());
         let item = ListItem::new(github_login.clone())
             .indent_level(1)
@@ -2339,9 +2363,12 @@ impl CollabPanel {
                 // todo handle contacts with no avatar
                 Avatar::new(contact.user.avatar_uri.clone())
                     .indicator::<AvatarAvailabilityIndicator>(if online {
-                        Some(AvatarAvailabilityIndicator::new(match busy {
-                            true => ui::Availability::Busy,
-                            false => ui::Availability::Free,
+                        Some(AvatarAvailabilityIndicator::new(if busy {
+                            ui::Availability::Busy
+                        } else if away {
+                            ui::Availability::Away
+                        } else {
+                            ui::Availability::Free
                         }))
                     } else {
                         None
@@ -2357,6 +2384,8 @@ impl CollabPanel {
                     format!(" {} is offline", &github_login)
                 } else if busy {
                     format!(" {} is on a call", &github_login)
+                } else if away {
+                    format!(" {} is away", &github_login)
                 } else {
                     let room = ActiveCall::global(cx).read(cx).room();
                     if room.is_some() {
@@ -2514,6 +2543,8 @@ impl CollabPanel {
 
         let has_messages_notification = channel_store.has_new_messages(channel_id);
         let has_notes_notification = channel_store.has_channel_buffer_changed(channel_id);
+        let unread_message_count = channel_store.unread_message_count(channel_id);
+        let unread_mention_count = channel_store.unread_mention_count(channel_id);
 
         const FACEPILE_LIMIT: usize = 3;
         let participants = self.channel_store.read(cx).channel_participants(channel_id);
@@ -2625,6 +2656,16 @@ impl CollabPanel {
                         h_flex()
                             .id(channel_id.0 as usize)
                             .child(Label::new(channel.name.clone()))
+                            .children((unread_message_count > 0).then(|| {
+                                let (label, color) = if unread_mention_count > 0 {
+                                    (format!("@{unread_mention_count}"), Color::Accent)
+                                } else {
+                                    (unread_message_count.to_string(), Color::Muted)
+                                };
+                                div()
+                                    .ml_1()
+                                    .child(Label::new(label).size(LabelSize::Small).color(color))
+                            }))
                             .children(face_pile.map(|face_pile| face_pile.p_1())),
                     ),
             )
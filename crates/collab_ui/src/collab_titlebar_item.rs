@@ -29,6 +29,7 @@ actions!(
     [
         ShareProject,
         UnshareProject,
+        ToggleReadOnlyForGuests,
         ToggleUserMenu,
         ToggleProjectMenu,
         SwitchBranch
@@ -184,6 +185,7 @@ impl Render for CollabTitlebarItem {
                         let project = self.project.read(cx);
                         let is_local = project.is_local();
                         let is_shared = is_local && project.is_shared();
+                        let is_read_only_for_guests = project.is_read_only_for_guests();
                         let is_muted = room.is_muted();
                         let is_deafened = room.is_deafened().unwrap_or(false);
                         let is_screen_sharing = room.is_screen_sharing();
@@ -221,6 +223,33 @@ impl Render for CollabTitlebarItem {
                                 )),
                             )
                         })
+                        .when(is_shared, |this| {
+                            this.child(
+                                IconButton::new(
+                                    "toggle_guest_write_access",
+                                    ui::IconName::FileLock,
+                                )
+                                .style(ButtonStyle::Subtle)
+                                .selected_style(ButtonStyle::Tinted(TintColor::Accent))
+                                .selected(is_read_only_for_guests)
+                                .icon_size(IconSize::Small)
+                                .tooltip(move |cx| {
+                                    Tooltip::text(
+                                        if is_read_only_for_guests {
+                                            "Allow guests to edit the project"
+                                        } else {
+                                            "Make the project read-only for guests"
+                                        },
+                                        cx,
+                                    )
+                                })
+                                .on_click(cx.listener(
+                                    |this, _, cx| {
+                                        this.toggle_read_only_for_guests(&Default::default(), cx);
+                                    },
+                                )),
+                            )
+                        })
                         .child(
                             div()
                                 .child(
@@ -605,6 +634,19 @@ impl CollabTitlebarItem {
             .log_err();
     }
 
+    fn toggle_read_only_for_guests(
+        &mut self,
+        _: &ToggleReadOnlyForGuests,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.project
+            .update(cx, |project, cx| {
+                let read_only = !project.is_read_only_for_guests();
+                project.set_read_only_for_guests(read_only, cx)
+            })
+            .detach_and_log_err(cx);
+    }
+
     pub fn render_vcs_popover(
         workspace: View<Workspace>,
         cx: &mut WindowContext<'_>,
@@ -1,17 +1,22 @@
+mod active_collaborators;
 pub mod channel_view;
 pub mod chat_panel;
 pub mod collab_panel;
 mod collab_titlebar_item;
+mod connection_status;
 mod face_pile;
 pub mod notification_panel;
 pub mod notifications;
 mod panel_settings;
+mod shared_clipboard;
 
 use std::{rc::Rc, sync::Arc};
 
+pub use active_collaborators::ActiveCollaborators;
 use call::{report_call_event_for_room, ActiveCall};
 pub use collab_panel::CollabPanel;
 pub use collab_titlebar_item::CollabTitlebarItem;
+pub use connection_status::ConnectionStatus;
 use gpui::{
     actions, point, AppContext, GlobalPixels, Pixels, PlatformDisplay, Size, Task, WindowBounds,
     WindowContext, WindowKind, WindowOptions,
@@ -39,6 +44,7 @@ pub fn init(app_state: &Arc<AppState>, cx: &mut AppContext) {
     chat_panel::init(cx);
     notification_panel::init(cx);
     notifications::init(&app_state, cx);
+    shared_clipboard::init(cx);
 }
 
 pub fn toggle_screen_sharing(_: &ToggleScreenSharing, cx: &mut WindowContext) {
@@ -0,0 +1,92 @@
+use client::{Client, Status};
+use futures::StreamExt;
+use gpui::{Render, Task, ViewContext};
+use std::sync::Arc;
+use ui::{prelude::*, Tooltip};
+use workspace::{item::ItemHandle, notifications::NotifyResultExt, StatusItemView, Workspace};
+
+/// Shows when the connection to the collaboration server is being
+/// established or has been lost.
+pub struct ConnectionStatus {
+    client: Arc<Client>,
+    status: Status,
+    _watch_status: Task<()>,
+}
+
+impl ConnectionStatus {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let client = workspace.app_state().client.clone();
+        let mut status = client.status();
+        let current_status = *status.borrow();
+        let _watch_status = cx.spawn(|this, mut cx| async move {
+            while let Some(status) = status.next().await {
+                let updated = this.update(&mut cx, |this, cx| {
+                    this.status = status;
+                    cx.notify();
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            client,
+            status: current_status,
+            _watch_status,
+        }
+    }
+}
+
+impl Render for ConnectionStatus {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        let (icon, color, message, can_reconnect) = match self.status {
+            Status::Authenticating | Status::Connecting => {
+                (IconName::ArrowCircle, Color::Muted, "Connecting…", false)
+            }
+            Status::Reauthenticating | Status::Reconnecting => (
+                IconName::ArrowCircle,
+                Color::Warning,
+                "Reconnecting…",
+                false,
+            ),
+            Status::ConnectionError | Status::ConnectionLost | Status::ReconnectionError { .. } => {
+                (
+                    IconName::Disconnected,
+                    Color::Error,
+                    "Disconnected, click to reconnect",
+                    true,
+                )
+            }
+            Status::UpgradeRequired => (
+                IconName::Disconnected,
+                Color::Warning,
+                "Update Zed to collaborate",
+                false,
+            ),
+            Status::SignedOut | Status::Connected { .. } => return div().into_any_element(),
+        };
+
+        let client = self.client.clone();
+        IconButton::new("connection-status", icon)
+            .icon_size(IconSize::Small)
+            .icon_color(color)
+            .tooltip(move |cx| Tooltip::text(message, cx))
+            .when(can_reconnect, |button| {
+                button.on_click(move |_, cx| {
+                    let client = client.clone();
+                    cx.spawn(move |mut cx| async move {
+                        client
+                            .authenticate_and_connect(true, &cx)
+                            .await
+                            .notify_async_err(&mut cx);
+                    })
+                    .detach();
+                })
+            })
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for ConnectionStatus {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
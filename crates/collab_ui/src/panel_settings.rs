@@ -17,6 +17,7 @@ pub struct ChatPanelSettings {
     pub button: bool,
     pub dock: DockPosition,
     pub default_width: Pixels,
+    pub trusted_image_domains: Vec<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -42,6 +43,27 @@ pub struct PanelSettingsContent {
     pub default_width: Option<f32>,
 }
 
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ChatPanelSettingsContent {
+    /// Whether to show the panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the panel.
+    ///
+    /// Default: right
+    pub dock: Option<DockPosition>,
+    /// Default width of the panel in pixels.
+    ///
+    /// Default: 240
+    pub default_width: Option<f32>,
+    /// Domains whose images are shown in chat messages without having to
+    /// click to load them. Subdomains of these domains are trusted too.
+    ///
+    /// Default: []
+    pub trusted_image_domains: Option<Vec<String>>,
+}
+
 impl Settings for CollaborationPanelSettings {
     const KEY: Option<&'static str> = Some("collaboration_panel");
     type FileContent = PanelSettingsContent;
@@ -56,7 +78,7 @@ impl Settings for CollaborationPanelSettings {
 
 impl Settings for ChatPanelSettings {
     const KEY: Option<&'static str> = Some("chat_panel");
-    type FileContent = PanelSettingsContent;
+    type FileContent = ChatPanelSettingsContent;
     fn load(
         default_value: &Self::FileContent,
         user_values: &[&Self::FileContent],
@@ -0,0 +1,294 @@
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusableView, Render, Task, View,
+    ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::SharedClipboardEntry;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Toast, Workspace};
+
+actions!(collab, [CopyToSharedClipboard, PasteFromSharedClipboard]);
+
+/// How much of an entry's first line is shown in the picker.
+const PREVIEW_LEN: usize = 80;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(copy_to_shared_clipboard)
+            .register_action(paste_from_shared_clipboard);
+    })
+    .detach();
+}
+
+fn copy_to_shared_clipboard(
+    workspace: &mut Workspace,
+    _: &CopyToSharedClipboard,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let text = editor.update(cx, |editor, cx| {
+        let buffer = editor.buffer().read(cx).snapshot(cx);
+        editor
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| {
+                buffer
+                    .text_for_range(selection.start..selection.end)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    if text.is_empty() {
+        return;
+    }
+
+    let result = workspace
+        .project()
+        .update(cx, |project, cx| project.share_clipboard_entry(text, cx));
+    match result {
+        Ok(()) => workspace.show_toast(Toast::new(0, "Copied to the shared clipboard"), cx),
+        Err(error) => workspace.show_error(&error, cx),
+    }
+}
+
+fn paste_from_shared_clipboard(
+    workspace: &mut Workspace,
+    _: &PasteFromSharedClipboard,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let user_store = project.user_store();
+    let user_store = user_store.read(cx);
+    let entries = project
+        .shared_clipboard()
+        .map(|entry| {
+            let author = match entry.author_id {
+                Some(author_id)
+                    if user_store.current_user().map(|user| user.id) == Some(author_id) =>
+                {
+                    "you".to_string()
+                }
+                Some(author_id) => user_store.get_cached_user(author_id).map_or_else(
+                    || "a collaborator".to_string(),
+                    |user| format!("@{}", user.github_login),
+                ),
+                None => "a collaborator".to_string(),
+            };
+            (entry.clone(), author)
+        })
+        .collect::<Vec<_>>();
+
+    let weak_workspace = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| {
+        let delegate = SharedClipboardDelegate::new(cx.view().downgrade(), weak_workspace, entries);
+        SharedClipboardPicker::new(delegate, cx)
+    });
+}
+
+pub struct SharedClipboardPicker {
+    picker: View<Picker<SharedClipboardDelegate>>,
+}
+
+impl FocusableView for SharedClipboardPicker {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SharedClipboardPicker {}
+impl ModalView for SharedClipboardPicker {}
+
+impl SharedClipboardPicker {
+    fn new(delegate: SharedClipboardDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for SharedClipboardPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct SharedClipboardDelegate {
+    view: WeakView<SharedClipboardPicker>,
+    workspace: WeakView<Workspace>,
+    entries: Vec<(SharedClipboardEntry, String)>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SharedClipboardDelegate {
+    fn new(
+        view: WeakView<SharedClipboardPicker>,
+        workspace: WeakView<Workspace>,
+        entries: Vec<(SharedClipboardEntry, String)>,
+    ) -> Self {
+        Self {
+            view,
+            workspace,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for SharedClipboardDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        if self.entries.is_empty() {
+            "Nothing has been shared yet".into()
+        } else {
+            "Paste from the shared clipboard...".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, (entry, _))| {
+                let preview = preview(&entry.text);
+                StringMatchCandidate {
+                    id,
+                    char_bag: preview.as_str().into(),
+                    string: preview,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                matches.sort_unstable_by_key(|string_match| string_match.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, _| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(entry_match) = self.matches.get(self.selected_index) {
+            let text = self.entries[entry_match.candidate_id].0.text.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    if let Some(editor) = workspace.active_item_as::<Editor>(cx) {
+                        editor.update(cx, |editor, cx| editor.insert(&text, cx));
+                    }
+                })
+                .log_err();
+        }
+
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry_match = &self.matches[ix];
+        let (entry, author) = &self.entries[entry_match.candidate_id];
+        let line_count = entry.text.lines().count();
+        let details = if line_count > 1 {
+            format!("Shared by {author}, {line_count} lines")
+        } else {
+            format!("Shared by {author}")
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(
+                            entry_match.string.clone(),
+                            entry_match.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(details)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+/// The first non-blank line of the text, shortened to fit in the picker.
+fn preview(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() > PREVIEW_LEN {
+        let mut preview = line.chars().take(PREVIEW_LEN).collect::<String>();
+        preview.push('…');
+        preview
+    } else {
+        line.to_string()
+    }
+}
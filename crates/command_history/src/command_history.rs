@@ -0,0 +1,446 @@
+mod persistence;
+mod redaction;
+
+use anyhow::Result;
+use editor::{Editor, MultiBuffer};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, AppContext, ClipboardItem, DismissEvent, FocusHandle, Global, ParentElement,
+    Task, View, ViewContext, WeakView, WindowContext,
+};
+use persistence::COMMAND_HISTORY_DB;
+use picker::{Picker, PickerDelegate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{mem, path::PathBuf, sync::Arc, time::Duration};
+use time::OffsetDateTime;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{item::ItemHandle, Pane, Workspace};
+
+pub use redaction::redact_command;
+
+actions!(command_history, [Toggle, Export, Clear]);
+
+/// How many entries are kept in the history.
+const MAX_ENTRIES: usize = 1000;
+
+/// How long recorded entries are held back, so that a burst of them is
+/// written to the database at once.
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Deserialize)]
+pub struct CommandHistorySettings {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CommandHistorySettingsContent {
+    /// Whether to keep a local history of the actions you run from the command
+    /// palette, the files you open and the tasks you spawn.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+}
+
+impl Settings for CommandHistorySettings {
+    const KEY: Option<&'static str> = Some("command_history");
+
+    type FileContent = CommandHistorySettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistoryEntryKind {
+    /// An action, run from the command palette.
+    Action,
+    /// A file opened in the workspace.
+    File,
+    /// An external command, run as a task.
+    Command,
+}
+
+impl HistoryEntryKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            HistoryEntryKind::Action => "action",
+            HistoryEntryKind::File => "file",
+            HistoryEntryKind::Command => "command",
+        }
+    }
+
+    fn from_str(kind: &str) -> Option<Self> {
+        match kind {
+            "action" => Some(HistoryEntryKind::Action),
+            "file" => Some(HistoryEntryKind::File),
+            "command" => Some(HistoryEntryKind::Command),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub timestamp: OffsetDateTime,
+    pub kind: HistoryEntryKind,
+    pub description: String,
+}
+
+#[derive(Default)]
+struct History {
+    /// The most recently recorded entry, so that repeating the same thing is
+    /// only recorded once.
+    last_entry: Option<(HistoryEntryKind, String)>,
+    /// The `(timestamp, kind, description)` of the entries that haven't been
+    /// written yet, oldest first.
+    pending_entries: Vec<(i64, String, String)>,
+    flush_scheduled: bool,
+}
+
+impl Global for History {}
+
+pub fn init(cx: &mut AppContext) {
+    CommandHistorySettings::register(cx);
+    cx.set_global(History::default());
+    cx.on_app_quit(flush).detach();
+
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        for pane in workspace.panes() {
+            observe_pane(pane, cx);
+        }
+        cx.subscribe(
+            &cx.view().clone(),
+            |_, _, event: &workspace::Event, cx| match event {
+                workspace::Event::PaneAdded(pane) => observe_pane(pane, cx),
+                workspace::Event::SpawnTask(spawn_in_terminal) => record(
+                    HistoryEntryKind::Command,
+                    redact_command(&spawn_in_terminal.command, &spawn_in_terminal.args),
+                    cx,
+                ),
+                _ => {}
+            },
+        )
+        .detach();
+
+        workspace
+            .register_action(toggle)
+            .register_action(export)
+            .register_action(|_, _: &Clear, cx| {
+                let history = cx.global_mut::<History>();
+                history.last_entry = None;
+                history.pending_entries.clear();
+                cx.background_executor()
+                    .spawn(async move { COMMAND_HISTORY_DB.clear_entries().await.log_err() })
+                    .detach();
+            });
+    })
+    .detach();
+}
+
+fn observe_pane(pane: &View<Pane>, cx: &mut ViewContext<Workspace>) {
+    cx.subscribe(pane, |workspace, _, event, cx| {
+        if let workspace::pane::Event::AddItem { item } = event {
+            if let Some(path) = item_abs_path(workspace, item.as_ref(), cx) {
+                record(
+                    HistoryEntryKind::File,
+                    path.to_string_lossy().into_owned(),
+                    cx,
+                );
+            }
+        }
+    })
+    .detach();
+}
+
+fn item_abs_path(workspace: &Workspace, item: &dyn ItemHandle, cx: &AppContext) -> Option<PathBuf> {
+    let project_path = item.project_path(cx)?;
+    workspace
+        .project()
+        .read(cx)
+        .absolute_path(&project_path, cx)
+}
+
+/// Adds an entry to the history, unless the history is disabled or the entry
+/// repeats the previous one.
+pub fn record(kind: HistoryEntryKind, description: String, cx: &mut AppContext) {
+    // The history isn't kept until it's initialized, e.g. in tests.
+    if !cx.has_global::<History>() || !CommandHistorySettings::get_global(cx).enabled {
+        return;
+    }
+    let history = cx.global_mut::<History>();
+    if history
+        .last_entry
+        .as_ref()
+        .map_or(false, |(last_kind, last_description)| {
+            *last_kind == kind && *last_description == description
+        })
+    {
+        return;
+    }
+    history.last_entry = Some((kind, description.clone()));
+
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    history
+        .pending_entries
+        .push((timestamp, kind.as_str().to_string(), description));
+    if !mem::replace(&mut history.flush_scheduled, true) {
+        cx.spawn(|mut cx| async move {
+            cx.background_executor().timer(FLUSH_DELAY).await;
+            if let Ok(task) = cx.update(flush) {
+                task.await;
+            }
+        })
+        .detach();
+    }
+}
+
+/// Writes the pending entries to the database, trimming the oldest entries.
+fn flush(cx: &mut AppContext) -> Task<()> {
+    let history = cx.global_mut::<History>();
+    history.flush_scheduled = false;
+    let entries = mem::take(&mut history.pending_entries);
+    cx.background_executor().spawn(async move {
+        if !entries.is_empty() {
+            COMMAND_HISTORY_DB
+                .insert_entries(entries, MAX_ENTRIES)
+                .await
+                .log_err();
+        }
+    })
+}
+
+/// Returns the history, most recent entry first.
+pub fn recent_entries(cx: &AppContext) -> Result<Vec<HistoryEntry>> {
+    let pending_entries = cx
+        .try_global::<History>()
+        .map(|history| history.pending_entries.clone())
+        .unwrap_or_default();
+    Ok(pending_entries
+        .into_iter()
+        .rev()
+        .chain(COMMAND_HISTORY_DB.recent_entries(MAX_ENTRIES)?)
+        .take(MAX_ENTRIES)
+        .filter_map(|(timestamp, kind, description)| {
+            Some(HistoryEntry {
+                timestamp: OffsetDateTime::from_unix_timestamp(timestamp).ok()?,
+                kind: HistoryEntryKind::from_str(&kind)?,
+                description,
+            })
+        })
+        .collect())
+}
+
+fn toggle(workspace: &mut Workspace, _: &Toggle, cx: &mut ViewContext<Workspace>) {
+    let entries = recent_entries(cx).log_err().unwrap_or_default();
+    let handle = cx.view().downgrade();
+    let previous_focus_handle = cx.focused();
+    workspace.toggle_modal(cx, move |cx| {
+        let delegate = CommandHistoryDelegate::new(handle, previous_focus_handle, entries, cx);
+        Picker::uniform_list(delegate, cx).width(rems(34.))
+    });
+}
+
+/// Opens the history in a new buffer, oldest entry first, so that it can be
+/// saved or attached to a bug report.
+fn export(workspace: &mut Workspace, _: &Export, cx: &mut ViewContext<Workspace>) {
+    let Some(entries) = recent_entries(cx).log_err() else {
+        return;
+    };
+    let text = entries
+        .iter()
+        .rev()
+        .map(|entry| {
+            format!(
+                "{}\t{}\t{}\n",
+                entry.timestamp,
+                entry.kind.as_str(),
+                entry.description
+            )
+        })
+        .collect::<String>();
+
+    let project = workspace.project().clone();
+    let Some(buffer) = project
+        .update(cx, |project, cx| project.create_buffer(&text, None, cx))
+        .log_err()
+    else {
+        return;
+    };
+    let buffer =
+        cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title("Command History".into()));
+    workspace.add_item(
+        Box::new(cx.new_view(|cx| Editor::for_multibuffer(buffer, Some(project), cx))),
+        cx,
+    );
+}
+
+pub struct CommandHistoryDelegate {
+    workspace: WeakView<Workspace>,
+    /// Where actions are run again, as they would have been originally.
+    previous_focus_handle: Option<FocusHandle>,
+    entries: Vec<HistoryEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    now: OffsetDateTime,
+}
+
+impl CommandHistoryDelegate {
+    fn new(
+        workspace: WeakView<Workspace>,
+        previous_focus_handle: Option<FocusHandle>,
+        entries: Vec<HistoryEntry>,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Self {
+        Self {
+            workspace,
+            previous_focus_handle,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+            now: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+impl PickerDelegate for CommandHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search command history...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| StringMatchCandidate::new(id, entry.description.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the most recent entries first.
+                matches.sort_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    /// Runs the selected action again, or reopens the selected file. Commands
+    /// may have been redacted, so they're copied rather than run.
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+            .cloned()
+        else {
+            return;
+        };
+        cx.emit(DismissEvent);
+
+        match entry.kind {
+            HistoryEntryKind::Action => {
+                if let Some(action) = cx.build_action(&entry.description, None).log_err() {
+                    if let Some(previous_focus_handle) = &self.previous_focus_handle {
+                        cx.focus(previous_focus_handle);
+                    }
+                    cx.dispatch_action(action);
+                }
+            }
+            HistoryEntryKind::File => {
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        workspace
+                            .open_abs_path(PathBuf::from(entry.description), false, cx)
+                            .detach_and_log_err(cx);
+                    })
+                    .log_err();
+            }
+            HistoryEntryKind::Command => {
+                cx.write_to_clipboard(ClipboardItem::new(entry.description));
+            }
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+        let icon = match entry.kind {
+            HistoryEntryKind::Action => IconName::Return,
+            HistoryEntryKind::File => IconName::File,
+            HistoryEntryKind::Command => IconName::Terminal,
+        };
+        let timestamp =
+            time_format::format_localized_timestamp(self.now, entry.timestamp, cx.local_timezone());
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(Icon::new(icon).color(Color::Muted))
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(Label::new(timestamp).color(Color::Muted)),
+        )
+    }
+}
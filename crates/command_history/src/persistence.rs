@@ -0,0 +1,89 @@
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+
+define_connection!(pub static ref COMMAND_HISTORY_DB: CommandHistoryDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS command_history(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            description TEXT NOT NULL
+        ) STRICT;
+    )];
+);
+
+impl CommandHistoryDb {
+    /// Appends the given `(timestamp, kind, description)` entries, oldest
+    /// first, and removes all but the `keep` most recent entries, in a single
+    /// transaction.
+    pub async fn insert_entries(
+        &self,
+        entries: Vec<(i64, String, String)>,
+        keep: usize,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("insert_entries", || {
+                for entry in entries {
+                    conn.exec_bound(sql!(
+                        INSERT INTO command_history(timestamp, kind, description) VALUES (?, ?, ?)
+                    ))?(entry)?;
+                }
+                conn.exec_bound(sql!(
+                    DELETE FROM command_history
+                    WHERE id NOT IN (SELECT id FROM command_history ORDER BY id DESC LIMIT ?)
+                ))?(keep)
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn recent_entries(limit: usize) -> Result<Vec<(i64, String, String)>> {
+            SELECT timestamp, kind, description
+            FROM command_history
+            ORDER BY id DESC
+            LIMIT ?
+        }
+    }
+
+    query! {
+        pub async fn clear_entries() -> Result<()> {
+            DELETE FROM command_history
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandHistoryDb;
+
+    #[gpui::test]
+    async fn test_command_history_db() {
+        let db = CommandHistoryDb(db::open_test_db("test_command_history_db").await);
+        assert_eq!(db.recent_entries(10).unwrap(), Vec::new());
+
+        let entry = |timestamp, description: &str| {
+            (timestamp, "action".to_string(), description.to_string())
+        };
+        db.insert_entries(vec![entry(1, "a"), entry(2, "b")], 10)
+            .await
+            .unwrap();
+        db.insert_entries(vec![entry(3, "c")], 10).await.unwrap();
+        assert_eq!(
+            db.recent_entries(2).unwrap(),
+            vec![entry(3, "c"), entry(2, "b")]
+        );
+
+        // Older entries are trimmed as new ones are inserted.
+        db.insert_entries(vec![entry(4, "d"), entry(5, "e")], 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.recent_entries(10).unwrap(),
+            vec![entry(5, "e"), entry(4, "d"), entry(3, "c")]
+        );
+
+        db.clear_entries().await.unwrap();
+        assert_eq!(db.recent_entries(10).unwrap(), Vec::new());
+    }
+}
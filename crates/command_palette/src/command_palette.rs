@@ -9,6 +9,7 @@ use collections::HashMap;
 use command_palette_hooks::{
     CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
 };
+use db::kvp::KEY_VALUE_STORE;
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
     actions, Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global,
@@ -18,6 +19,7 @@ use picker::{Picker, PickerDelegate};
 
 use postage::{sink::Sink, stream::Stream};
 use release_channel::parse_zed_link;
+use serde::{Deserialize, Serialize};
 use ui::{h_flex, prelude::*, v_flex, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
 use util::ResultExt;
 use workspace::{ModalView, Workspace};
@@ -25,8 +27,11 @@ use zed_actions::OpenZedUrl;
 
 actions!(command_palette, [Toggle]);
 
+const HIT_COUNTS_KEY: &str = "command_palette_hit_counts";
+
 pub fn init(cx: &mut AppContext) {
     cx.set_global(HitCounts::default());
+    HitCounts::load(cx);
     cx.set_global(CommandPaletteFilter::default());
     cx.observe_new_views(CommandPalette::register).detach();
 }
@@ -134,11 +139,53 @@ impl Clone for Command {
 /// Hit count for each command in the palette.
 /// We only account for commands triggered directly via command palette and not by e.g. keystrokes because
 /// if a user already knows a keystroke for a command, they are unlikely to use a command palette to look for it.
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 struct HitCounts(HashMap<String, usize>);
 
 impl Global for HitCounts {}
 
+impl HitCounts {
+    /// Restores the hit counts saved in a previous session, so that frequently used
+    /// commands stay at the top of the palette across restarts.
+    fn load(cx: &mut AppContext) {
+        cx.spawn(|mut cx| async move {
+            let saved = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(HIT_COUNTS_KEY) })
+                .await
+                .log_err()
+                .flatten()
+                .and_then(|hit_counts| serde_json::from_str::<HitCounts>(&hit_counts).log_err());
+            if let Some(saved) = saved {
+                cx.update_global(|hit_counts: &mut HitCounts, _| {
+                    for (name, count) in saved.0 {
+                        *hit_counts.0.entry(name).or_default() += count;
+                    }
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    fn increment(name: String, cx: &mut AppContext) {
+        let hit_counts = cx.update_global(|hit_counts: &mut HitCounts, _| {
+            *hit_counts.0.entry(name).or_default() += 1;
+            serde_json::to_string(hit_counts)
+        });
+        if let Some(hit_counts) = hit_counts.log_err() {
+            cx.background_executor()
+                .spawn(async move {
+                    KEY_VALUE_STORE
+                        .write_kvp(HIT_COUNTS_KEY.into(), hit_counts)
+                        .await
+                        .log_err();
+                })
+                .detach();
+        }
+    }
+}
+
 impl CommandPaletteDelegate {
     fn new(
         command_palette: WeakView<CommandPalette>,
@@ -354,10 +401,13 @@ impl PickerDelegate for CommandPaletteDelegate {
 
         self.matches.clear();
         self.commands.clear();
-        cx.update_global(|hit_counts: &mut HitCounts, _| {
-            *hit_counts.0.entry(command.name).or_default() += 1;
-        });
+        HitCounts::increment(command.name, cx);
         let action = command.action;
+        command_history::record(
+            command_history::HistoryEntryKind::Action,
+            action.name().to_string(),
+            cx,
+        );
         cx.focus(&self.previous_focus_handle);
         self.dismissed(cx);
         cx.dispatch_action(action);
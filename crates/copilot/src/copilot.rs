@@ -1254,7 +1254,11 @@ mod tests {
             self.abs_path.clone()
         }
 
-        fn load(&self, _: &AppContext) -> Task<Result<String>> {
+        fn load(
+            &self,
+            _: Option<language::Encoding>,
+            _: &AppContext,
+        ) -> Task<Result<(String, language::Encoding)>> {
             unimplemented!()
         }
 
@@ -1264,6 +1268,7 @@ mod tests {
             _: &clock::Global,
             _: language::RopeFingerprint,
             _: language::LineEnding,
+            _: language::Encoding,
             _: std::time::SystemTime,
             _: &mut AppContext,
         ) {
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Deserialize, Debug)]
 pub struct ProjectDiagnosticsSettings {
     pub include_warnings: bool,
+    pub context_lines: u32,
 }
 
 /// Diagnostics configuration.
@@ -13,6 +14,10 @@ pub struct ProjectDiagnosticsSettingsContent {
     ///
     /// Default: true
     include_warnings: Option<bool>,
+    /// How many lines of surrounding code to show around each diagnostic.
+    ///
+    /// Default: 1
+    context_lines: Option<u32>,
 }
 
 impl settings::Settings for ProjectDiagnosticsSettings {
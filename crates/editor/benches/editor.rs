@@ -0,0 +1,201 @@
+//! Benchmarks for the editor's hot paths. Run with `cargo bench -p editor`,
+//! or profile one of them with `script/profile-editor <benchmark>`.
+//!
+//! Everything runs on gpui's deterministic test dispatcher with seeded
+//! inputs, so runs are comparable between branches.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use editor::{Editor, EditorMode};
+use gpui::{
+    point, px, size, AvailableSpace, Context, IntoElement, Model, TestAppContext, TestDispatcher,
+    View, VisualTestContext,
+};
+use language::{Buffer, BufferId, Capability, Operation};
+use multi_buffer::MultiBuffer;
+use project::Project;
+use rand::prelude::*;
+use settings::SettingsStore;
+use std::{cell::RefCell, ops::Range, rc::Rc};
+
+const LINE_COUNT: usize = 10_000;
+const CURSOR_COUNT: usize = 100;
+const COLLABORATOR_COUNT: u16 = 8;
+
+fn bench_display_map_sync(c: &mut Criterion) {
+    let mut cx = test_app_context();
+    let (editor, cx) = add_editor(&mut cx, &random_text(LINE_COUNT));
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // Each iteration edits the buffer and then syncs the display map with
+    // the edit, like the editor does when it renders after typing.
+    c.bench_function("display_map_sync", |b| {
+        b.iter(|| {
+            editor.update(cx, |editor, cx| {
+                editor.buffer().update(cx, |buffer, cx| {
+                    let len = buffer.len(cx);
+                    let offset = rng.gen_range(0..=len);
+                    buffer.edit([(offset..offset, "x\n")], None, cx);
+                });
+                black_box(editor.snapshot(cx));
+            })
+        })
+    });
+}
+
+fn bench_layout(c: &mut Criterion) {
+    // The test platform only has a real text system on macOS. Elsewhere, text
+    // layout is stubbed out, so this benchmark wouldn't measure anything.
+    if !cfg!(target_os = "macos") {
+        eprintln!("skipping layout_10k_lines: it requires the macOS text system");
+        return;
+    }
+
+    let mut cx = test_app_context();
+    let (editor, cx) = add_editor(&mut cx, &random_text(LINE_COUNT));
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // Each iteration scrolls to a random position in the buffer and lays
+    // out and paints the visible lines.
+    c.bench_function("layout_10k_lines", |b| {
+        b.iter(|| {
+            let row = rng.gen_range(0..LINE_COUNT) as f32;
+            editor.update(cx, |editor, cx| {
+                editor.set_scroll_position(point(0., row), cx);
+            });
+            let editor = editor.clone();
+            cx.draw(
+                point(px(0.), px(0.)),
+                size(
+                    AvailableSpace::Definite(px(1280.)),
+                    AvailableSpace::Definite(px(800.)),
+                ),
+                move |_| editor.into_any_element(),
+            );
+        })
+    });
+}
+
+fn bench_selection_fan_out(c: &mut Criterion) {
+    let mut cx = test_app_context();
+    let text = random_text(LINE_COUNT);
+    let (editor, cx) = add_editor(&mut cx, &text);
+    let buffer = editor.update(cx, |editor, cx| {
+        cx.focus_self();
+        editor.buffer().read(cx).as_singleton().unwrap()
+    });
+
+    // Replicas of the buffer, as opened by collaborators in a shared project.
+    let state = buffer.read_with(cx, |buffer, _| buffer.to_proto());
+    let replicas = (1..=COLLABORATOR_COUNT)
+        .map(|replica_id| {
+            cx.new_model(|_| {
+                Buffer::from_proto(replica_id, Capability::ReadWrite, state.clone(), None).unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    let operations = Rc::new(RefCell::new(Vec::<Operation>::new()));
+    cx.update(|cx| {
+        let operations = operations.clone();
+        cx.subscribe(&buffer, move |_, event, _| {
+            if let language::Event::Operation(operation) = event {
+                operations.borrow_mut().push(operation.clone());
+            }
+        })
+        .detach();
+    });
+
+    // Each iteration moves a hundred cursors, and sends the resulting
+    // selection update to every collaborator the way the server would.
+    let mut rng = StdRng::seed_from_u64(0);
+    c.bench_function("selection_fan_out", |b| {
+        b.iter_batched(
+            || random_selections(&mut rng, text.len()),
+            |selections| {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| s.select_ranges(selections));
+                });
+                let messages = operations
+                    .borrow_mut()
+                    .drain(..)
+                    .map(|operation| language::proto::serialize_operation(&operation))
+                    .collect::<Vec<_>>();
+                for replica in &replicas {
+                    let operations = messages
+                        .iter()
+                        .map(|message| {
+                            language::proto::deserialize_operation(message.clone()).unwrap()
+                        })
+                        .collect::<Vec<_>>();
+                    replica.update(cx, |replica, cx| replica.apply_ops(operations, cx).unwrap());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn test_app_context() -> TestAppContext {
+    let dispatcher = TestDispatcher::new(StdRng::seed_from_u64(0));
+    let cx = TestAppContext::new(dispatcher, None);
+    cx.update(|cx| {
+        let store = SettingsStore::test(cx);
+        cx.set_global(store);
+        theme::init(theme::LoadThemes::JustBase, cx);
+        release_channel::init("0.0.0", cx);
+        client::init_settings(cx);
+        language::init(cx);
+        Project::init_settings(cx);
+        workspace::init_settings(cx);
+        editor::init(cx);
+    });
+    cx
+}
+
+fn add_editor<'a>(
+    cx: &'a mut TestAppContext,
+    text: &str,
+) -> (View<Editor>, &'a mut VisualTestContext) {
+    let buffer: Model<Buffer> =
+        cx.new_model(|cx| Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text));
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) =
+        cx.add_window_view(|cx| Editor::new(EditorMode::Full, buffer.clone(), None, cx));
+    cx.run_until_parked();
+    (editor, cx)
+}
+
+fn random_text(line_count: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut text = String::new();
+    for _ in 0..line_count {
+        let indent = rng.gen_range(0..4) * 4;
+        let words = rng.gen_range(0..12);
+        text.extend(std::iter::repeat(' ').take(indent));
+        for _ in 0..words {
+            let len = rng.gen_range(1..10);
+            text.extend((0..len).map(|_| rng.gen_range(b'a'..=b'z') as char));
+            text.push(' ');
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn random_selections(rng: &mut StdRng, len: usize) -> Vec<Range<usize>> {
+    let mut offsets = (0..CURSOR_COUNT * 2)
+        .map(|_| rng.gen_range(0..=len))
+        .collect::<Vec<_>>();
+    offsets.sort_unstable();
+    offsets
+        .chunks(2)
+        .map(|pair| pair[0]..pair[1])
+        .collect::<Vec<_>>()
+}
+
+criterion_group!(
+    benches,
+    bench_display_map_sync,
+    bench_layout,
+    bench_selection_fan_out
+);
+criterion_main!(benches);
@@ -94,6 +94,24 @@ pub struct SelectDownByLines {
     pub(super) lines: u32,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct ExpandExcerpts {
+    #[serde(default)]
+    pub(super) lines: u32,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct SurroundSelections {
+    /// The opening half of a bracket pair, or an opening tag such as `<div>`.
+    pub open: String,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct ChangeSurroundingPair {
+    /// The opening half of a bracket pair, or an opening tag such as `<div>`.
+    pub open: String,
+}
+
 impl_actions!(
     editor,
     [
@@ -112,7 +130,10 @@ impl_actions!(
         MoveUpByLines,
         MoveDownByLines,
         SelectUpByLines,
-        SelectDownByLines
+        SelectDownByLines,
+        ExpandExcerpts,
+        SurroundSelections,
+        ChangeSurroundingPair
     ]
 );
 
@@ -144,6 +165,7 @@ gpui::actions!(
         CutToEndOfLine,
         Delete,
         DeleteLine,
+        DeleteSurroundingPair,
         DeleteToBeginningOfLine,
         DeleteToEndOfLine,
         DeleteToNextSubwordEnd,
@@ -153,10 +175,14 @@ gpui::actions!(
         DisplayCursorNames,
         DuplicateLine,
         ExpandMacroRecursively,
+        ExtractFunction,
+        ExtractVariable,
         FindAllReferences,
         Fold,
         FoldSelectedRanges,
         Format,
+        FormatMarkdownTable,
+        FormatWithReview,
         GoToDefinition,
         GoToDefinitionSplit,
         GoToDiagnostic,
@@ -213,6 +239,7 @@ gpui::actions!(
         ScrollCursorTop,
         SelectAll,
         SelectAllMatches,
+        SelectAllOccurrences,
         SelectDown,
         SelectLargerSyntaxNode,
         SelectLeft,
@@ -236,8 +263,10 @@ gpui::actions!(
         SplitSelectionIntoLines,
         Tab,
         TabPrev,
+        ToggleColumnSelectionMode,
         ToggleInlayHints,
         ToggleSoftWrap,
+        ToggleTaskListItem,
         Transpose,
         Undo,
         UndoSelection,
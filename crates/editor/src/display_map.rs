@@ -847,10 +847,17 @@ impl DisplaySnapshot {
         self.block_snapshot.longest_row()
     }
 
-    pub fn fold_for_line(self: &Self, buffer_row: u32) -> Option<FoldStatus> {
+    /// Returns the fold status of the given row. `syntax_fold_rows` holds the rows
+    /// on which a syntax fold starts, as returned by [`Self::syntax_fold_rows`], so
+    /// that callers laying out many rows only query the syntax tree once.
+    pub fn fold_for_line(
+        self: &Self,
+        buffer_row: u32,
+        syntax_fold_rows: &HashSet<u32>,
+    ) -> Option<FoldStatus> {
         if self.is_line_folded(buffer_row) {
             Some(FoldStatus::Folded)
-        } else if self.is_foldable(buffer_row) {
+        } else if syntax_fold_rows.contains(&buffer_row) || self.is_indent_foldable(buffer_row) {
             Some(FoldStatus::Foldable)
         } else {
             None
@@ -858,6 +865,57 @@ impl DisplaySnapshot {
     }
 
     pub fn is_foldable(self: &Self, buffer_row: u32) -> bool {
+        self.is_indent_foldable(buffer_row) || self.syntax_foldable_range(buffer_row).is_some()
+    }
+
+    /// Returns the rows in the given range on which a pair of brackets opens and
+    /// closes on a later row, running the language's bracket query once for the
+    /// whole range.
+    pub fn syntax_fold_rows(&self, buffer_rows: Range<u32>) -> HashSet<u32> {
+        let max_row = self.buffer_snapshot.max_buffer_row();
+        if buffer_rows.start >= buffer_rows.end || buffer_rows.start > max_row {
+            return HashSet::default();
+        }
+
+        let last_row = (buffer_rows.end - 1).min(max_row);
+        let range = Point::new(buffer_rows.start, 0).to_offset(&self.buffer_snapshot)
+            ..Point::new(last_row, self.buffer_snapshot.line_len(last_row))
+                .to_offset(&self.buffer_snapshot);
+        let Some(brackets) = self.buffer_snapshot.bracket_ranges(range.clone()) else {
+            return HashSet::default();
+        };
+
+        brackets
+            .filter(|(open, _)| range.contains(&open.start))
+            .filter_map(|(open, close)| {
+                let start = open.end.to_point(&self.buffer_snapshot);
+                let end = close.start.to_point(&self.buffer_snapshot);
+                (end.row > start.row).then_some(start.row)
+            })
+            .collect()
+    }
+
+    /// Returns the range between the first pair of brackets that opens on the given
+    /// row and closes on a later one, as determined by the language's syntax tree.
+    fn syntax_foldable_range(&self, buffer_row: u32) -> Option<Range<Point>> {
+        let line_start = Point::new(buffer_row, 0);
+        let line_end = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
+        let line_range =
+            line_start.to_offset(&self.buffer_snapshot)..line_end.to_offset(&self.buffer_snapshot);
+        let (open, close) = self
+            .buffer_snapshot
+            .bracket_ranges(line_range.clone())?
+            .filter(|(open, close)| {
+                line_range.contains(&open.start) && close.start > line_range.end
+            })
+            .min_by_key(|(open, _)| open.start)?;
+
+        let start = open.end.to_point(&self.buffer_snapshot);
+        let end = close.start.to_point(&self.buffer_snapshot);
+        (end.row > start.row).then_some(start..end)
+    }
+
+    fn is_indent_foldable(self: &Self, buffer_row: u32) -> bool {
         let max_row = self.buffer_snapshot.max_buffer_row();
         if buffer_row >= max_row {
             return false;
@@ -881,8 +939,16 @@ impl DisplaySnapshot {
     }
 
     pub fn foldable_range(self: &Self, buffer_row: u32) -> Option<Range<Point>> {
+        if self.is_line_folded(buffer_row) {
+            return None;
+        }
+
+        if let Some(range) = self.syntax_foldable_range(buffer_row) {
+            return Some(range);
+        }
+
         let start = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
-        if self.is_foldable(start.row) && !self.is_line_folded(start.row) {
+        if self.is_indent_foldable(start.row) {
             let (start_indent, _) = self.line_indent_for_buffer_row(buffer_row);
             let max_point = self.buffer_snapshot.max_point();
             let mut end = None;
@@ -0,0 +1,330 @@
+use collections::HashMap;
+use gpui::{
+    div, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, IntoElement, Model,
+    ParentElement, Render, SharedString, Styled, ViewContext, VisualContext, WindowContext,
+};
+use language::{Anchor, Buffer, ToPoint};
+use project::ProjectTransaction;
+use settings::Settings;
+use std::{ops::Range, sync::Arc};
+use theme::{ActiveTheme, ThemeSettings};
+use ui::{prelude::*, Button, Label};
+use workspace::{
+    item::{Item, ItemEvent},
+    Workspace,
+};
+
+/// An edit that has been proposed for a buffer, but not applied to it.
+#[derive(Clone)]
+pub struct ProposedEdit {
+    pub buffer: Model<Buffer>,
+    pub range: Range<Anchor>,
+    pub new_text: Arc<str>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HunkStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+struct ReviewHunk {
+    edit: ProposedEdit,
+    location: SharedString,
+    old_text: SharedString,
+    status: HunkStatus,
+}
+
+pub enum EditReviewEvent {
+    Applied,
+}
+
+/// A tab showing proposed edits side by side with the text they replace. Each
+/// hunk can be accepted or rejected, and only the accepted hunks are applied.
+pub struct EditReview {
+    title: SharedString,
+    hunks: Vec<ReviewHunk>,
+    applied: bool,
+    focus_handle: FocusHandle,
+}
+
+/// Reverts an applied transaction and opens its edits for review, so that
+/// subsystems which apply edits in bulk can let the user pick which to keep.
+pub fn review_project_transaction(
+    workspace: &mut Workspace,
+    transaction: ProjectTransaction,
+    title: impl Into<SharedString>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let mut buffers = transaction.0.into_iter().collect::<Vec<_>>();
+    buffers.sort_unstable_by_key(|(buffer, _)| buffer.read(cx).file().map(|f| f.path().clone()));
+
+    let mut edits = Vec::new();
+    for (buffer_handle, transaction) in buffers {
+        buffer_handle.update(cx, |buffer, cx| {
+            let applied = buffer.snapshot();
+            let applied_edits = applied
+                .edits_since::<usize>(&transaction.start)
+                .collect::<Vec<_>>();
+            buffer.undo_transaction(transaction.id, cx);
+            buffer.forget_transaction(transaction.id);
+
+            let reverted = buffer.snapshot();
+            for edit in applied_edits {
+                edits.push(ProposedEdit {
+                    buffer: buffer_handle.clone(),
+                    range: reverted.anchor_before(edit.old.start)
+                        ..reverted.anchor_after(edit.old.end),
+                    new_text: applied.text_for_range(edit.new).collect::<String>().into(),
+                });
+            }
+        });
+    }
+
+    if edits.is_empty() {
+        return;
+    }
+    let review = cx.new_view(|cx| EditReview::new(title, edits, cx));
+    workspace.add_item(Box::new(review), cx);
+}
+
+impl EditReview {
+    pub fn new(
+        title: impl Into<SharedString>,
+        edits: Vec<ProposedEdit>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let hunks = edits
+            .into_iter()
+            .map(|edit| {
+                let buffer = edit.buffer.read(cx);
+                let snapshot = buffer.snapshot();
+                let start = edit.range.start.to_point(&snapshot);
+                let path = buffer
+                    .file()
+                    .map(|file| file.path().to_string_lossy().to_string())
+                    .unwrap_or_else(|| "untitled".to_string());
+                let old_text = snapshot
+                    .text_for_range(edit.range.clone())
+                    .collect::<String>();
+                ReviewHunk {
+                    location: format!("{path}:{}", start.row + 1).into(),
+                    old_text: old_text.into(),
+                    status: HunkStatus::Pending,
+                    edit,
+                }
+            })
+            .collect();
+
+        Self {
+            title: title.into(),
+            hunks,
+            applied: false,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    pub fn hunk_statuses(&self) -> Vec<HunkStatus> {
+        self.hunks.iter().map(|hunk| hunk.status).collect()
+    }
+
+    pub fn set_hunk_status(&mut self, ix: usize, status: HunkStatus, cx: &mut ViewContext<Self>) {
+        if let Some(hunk) = self.hunks.get_mut(ix) {
+            hunk.status = status;
+            cx.notify();
+        }
+    }
+
+    pub fn accept_all(&mut self, cx: &mut ViewContext<Self>) {
+        for hunk in &mut self.hunks {
+            hunk.status = HunkStatus::Accepted;
+        }
+        cx.notify();
+    }
+
+    pub fn reject_all(&mut self, cx: &mut ViewContext<Self>) {
+        for hunk in &mut self.hunks {
+            hunk.status = HunkStatus::Rejected;
+        }
+        cx.notify();
+    }
+
+    /// Applies the accepted hunks to their buffers, with one undoable
+    /// transaction per buffer. Hunks that weren't accepted are discarded.
+    pub fn apply(&mut self, cx: &mut ViewContext<Self>) {
+        if self.applied {
+            return;
+        }
+
+        let mut edits_by_buffer = HashMap::<_, (Model<Buffer>, Vec<_>)>::default();
+        for hunk in &self.hunks {
+            if hunk.status == HunkStatus::Accepted {
+                let buffer = &hunk.edit.buffer;
+                edits_by_buffer
+                    .entry(buffer.entity_id())
+                    .or_insert_with(|| (buffer.clone(), Vec::new()))
+                    .1
+                    .push((hunk.edit.range.clone(), hunk.edit.new_text.clone()));
+            }
+        }
+        for (buffer, edits) in edits_by_buffer.into_values() {
+            buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+        }
+
+        self.applied = true;
+        cx.emit(EditReviewEvent::Applied);
+        cx.notify();
+    }
+
+    fn render_hunk(&self, ix: usize, hunk: &ReviewHunk, cx: &mut ViewContext<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+        let status_colors = cx.theme().status();
+        let new_text = SharedString::from(hunk.edit.new_text.to_string());
+        let editable = !self.applied;
+
+        v_flex()
+            .border_1()
+            .border_color(colors.border_variant)
+            .rounded_md()
+            .child(
+                h_flex()
+                    .p_1()
+                    .gap_2()
+                    .bg(colors.surface_background)
+                    .child(div().flex_1().child(Label::new(hunk.location.clone())))
+                    .child(
+                        Button::new(("accept-hunk", ix), "Accept")
+                            .selected(hunk.status == HunkStatus::Accepted)
+                            .disabled(!editable)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.set_hunk_status(ix, HunkStatus::Accepted, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new(("reject-hunk", ix), "Reject")
+                            .selected(hunk.status == HunkStatus::Rejected)
+                            .disabled(!editable)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.set_hunk_status(ix, HunkStatus::Rejected, cx)
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .items_start()
+                    .child(
+                        div()
+                            .w_1_2()
+                            .p_1()
+                            .bg(status_colors.deleted_background.opacity(0.2))
+                            .child(hunk.old_text.clone()),
+                    )
+                    .child(
+                        div()
+                            .w_1_2()
+                            .p_1()
+                            .bg(status_colors.created_background.opacity(0.2))
+                            .child(new_text),
+                    ),
+            )
+            .into_any_element()
+    }
+}
+
+impl Render for EditReview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let buffer_font = ThemeSettings::get_global(cx).buffer_font.family.clone();
+        let accepted_count = self
+            .hunks
+            .iter()
+            .filter(|hunk| hunk.status == HunkStatus::Accepted)
+            .count();
+        let summary = if self.applied {
+            format!("Applied {accepted_count} of {} changes", self.hunks.len())
+        } else {
+            format!("{accepted_count} of {} changes accepted", self.hunks.len())
+        };
+        let hunks = self
+            .hunks
+            .iter()
+            .enumerate()
+            .map(|(ix, hunk)| self.render_hunk(ix, hunk, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("EditReview")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(div().flex_1().child(Label::new(self.title.clone())))
+                    .child(Label::new(summary).color(Color::Muted))
+                    .when(!self.applied, |header| {
+                        header
+                            .child(
+                                Button::new("accept-all", "Accept All")
+                                    .on_click(cx.listener(|this, _, cx| this.accept_all(cx))),
+                            )
+                            .child(
+                                Button::new("reject-all", "Reject All")
+                                    .on_click(cx.listener(|this, _, cx| this.reject_all(cx))),
+                            )
+                            .child(
+                                Button::new("apply", "Apply Accepted")
+                                    .style(ButtonStyle::Filled)
+                                    .on_click(cx.listener(|this, _, cx| this.apply(cx))),
+                            )
+                    }),
+            )
+            .child(
+                v_flex()
+                    .id("edit-review-hunks")
+                    .flex_1()
+                    .p_2()
+                    .gap_2()
+                    .overflow_y_scroll()
+                    .font(buffer_font)
+                    .children(hunks),
+            )
+    }
+}
+
+impl EventEmitter<EditReviewEvent> for EditReview {}
+
+impl FocusableView for EditReview {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for EditReview {
+    type Event = EditReviewEvent;
+
+    fn tab_content(&self, _: Option<usize>, selected: bool, _: &WindowContext) -> AnyElement {
+        Label::new(format!("Review: {}", self.title))
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("edit review")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            EditReviewEvent::Applied => f(ItemEvent::UpdateTab),
+        }
+    }
+}
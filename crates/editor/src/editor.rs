@@ -15,8 +15,10 @@
 pub mod actions;
 mod blink_manager;
 pub mod display_map;
+pub mod edit_review;
 mod editor_settings;
 mod element;
+mod emmet;
 mod inlay_hint_cache;
 
 mod debounced_delay;
@@ -24,13 +26,18 @@ mod git;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
+mod image_preview;
 pub mod items;
+mod markdown_ext;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod refactor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod surround;
+mod word_highlights;
 
 #[cfg(test)]
 mod editor_tests;
@@ -66,6 +73,7 @@ use gpui::{
 };
 use highlight_matching_bracket::refresh_matching_bracket_highlights;
 use hover_popover::{hide_hover, HoverState};
+use image_preview::{refresh_diagram_previews, DiagramPreviews};
 use inlay_hint_cache::{InlayHintCache, InlaySplice, InvalidationStrategy};
 pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
@@ -89,7 +97,9 @@ pub use multi_buffer::{
 use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
-use project::{FormatTrigger, Location, Project, ProjectPath, ProjectTransaction};
+use project::{
+    power::PowerSaving, FormatTrigger, Location, Project, ProjectPath, ProjectTransaction,
+};
 use rand::prelude::*;
 use rpc::proto::*;
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager, ScrollbarAutoHide};
@@ -121,6 +131,7 @@ use ui::{
     Tooltip,
 };
 use util::{maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
+use word_highlights::{refresh_word_highlights, WordHighlight};
 use workspace::Toast;
 use workspace::{searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace};
 
@@ -130,12 +141,16 @@ const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
+const DEFAULT_EXPAND_EXCERPT_LINES: u32 = 3;
 const COPILOT_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
 pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
 #[doc(hidden)]
 pub const DOCUMENT_HIGHLIGHTS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
+/// How many times longer to debounce requests made ahead of time, like code
+/// actions and document highlights, when saving power.
+const POWER_SAVING_DEBOUNCE_FACTOR: u32 = 4;
 
 pub(crate) const FORMAT_TIMEOUT: Duration = Duration::from_secs(2);
 
@@ -279,6 +294,9 @@ pub enum SelectPhase {
     BeginColumnar {
         position: DisplayPoint,
         goal_column: u32,
+        /// Whether to start a new block at `position`, rather than extending
+        /// from the tail of the newest selection.
+        reset: bool,
     },
     Extend {
         position: DisplayPoint,
@@ -365,6 +383,8 @@ pub struct Editor {
     pub selections: SelectionsCollection,
     pub scroll_manager: ScrollManager,
     columnar_selection_tail: Option<Anchor>,
+    column_selection_mode: bool,
+    column_selection_state: Option<ColumnSelectionState>,
     add_selections_state: Option<AddSelectionsState>,
     select_next_state: Option<SelectNextState>,
     select_prev_state: Option<SelectNextState>,
@@ -377,10 +397,13 @@ pub struct Editor {
     soft_wrap_mode_override: Option<language_settings::SoftWrap>,
     project: Option<Model<Project>>,
     completion_provider: Option<Box<dyn CompletionProvider>>,
+    hover_provider: Option<Box<dyn HoverProvider>>,
     collaboration_hub: Option<Box<dyn CollaborationHub>>,
     blink_manager: Model<BlinkManager>,
     show_cursor_names: bool,
+    pending_surround: Option<surround::PendingSurround>,
     hovered_cursors: HashMap<HoveredCursor, Task<()>>,
+    active_remote_cursors: HashMap<ReplicaId, Task<()>>,
     pub show_local_selections: bool,
     mode: EditorMode,
     show_breadcrumbs: bool,
@@ -399,6 +422,7 @@ pub struct Editor {
     available_code_actions: Option<(Model<Buffer>, Arc<[CodeAction]>)>,
     code_actions_task: Option<Task<()>>,
     document_highlights_task: Option<Task<()>>,
+    word_highlights_task: Option<Task<()>>,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -412,6 +436,10 @@ pub struct Editor {
     leader_peer_id: Option<PeerId>,
     remote_id: Option<ViewId>,
     hover_state: HoverState,
+    diagram_previews: DiagramPreviews,
+    /// Whether work that was deferred until an edit was painted is scheduled, and if so, whether
+    /// a singleton buffer was among the edited ones.
+    pending_edit_refresh: Option<bool>,
     gutter_hovered: bool,
     hovered_link_state: Option<HoveredLinkState>,
     copilot_state: CopilotState,
@@ -573,6 +601,16 @@ impl SelectionHistory {
     }
 }
 
+/// A rectangular selection that is being extended from the keyboard.
+#[derive(Clone, Debug)]
+struct ColumnSelectionState {
+    tail: Anchor,
+    head: DisplayPoint,
+    /// The selections this state produced. If the selections have changed
+    /// since, the next block starts from the newest selection instead.
+    selections: Arc<[Selection<Anchor>]>,
+}
+
 #[derive(Clone, Debug)]
 struct AddSelectionsState {
     above: bool,
@@ -1494,6 +1532,8 @@ impl Editor {
             selections,
             scroll_manager: ScrollManager::new(cx),
             columnar_selection_tail: None,
+            column_selection_mode: false,
+            column_selection_state: None,
             add_selections_state: None,
             select_next_state: None,
             select_prev_state: None,
@@ -1505,6 +1545,7 @@ impl Editor {
             active_diagnostics: None,
             soft_wrap_mode_override,
             completion_provider: project.clone().map(|project| Box::new(project) as _),
+            hover_provider: project.clone().map(|project| Box::new(project) as _),
             collaboration_hub: project.clone().map(|project| Box::new(project) as _),
             project,
             blink_manager: blink_manager.clone(),
@@ -1527,6 +1568,7 @@ impl Editor {
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
+            word_highlights_task: Default::default(),
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: Default::default(),
@@ -1541,6 +1583,8 @@ impl Editor {
             leader_peer_id: None,
             remote_id: None,
             hover_state: Default::default(),
+            diagram_previews: Default::default(),
+            pending_edit_refresh: None,
             hovered_link_state: Default::default(),
             copilot_state: Default::default(),
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
@@ -1549,7 +1593,9 @@ impl Editor {
             gutter_width: Default::default(),
             style: None,
             show_cursor_names: false,
+            pending_surround: None,
             hovered_cursors: Default::default(),
+            active_remote_cursors: Default::default(),
             editor_actions: Default::default(),
             show_copilot_suggestions: mode == EditorMode::Full,
             custom_context_menu: None,
@@ -1578,6 +1624,7 @@ impl Editor {
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
+        refresh_diagram_previews(&mut this, cx);
 
         if mode == EditorMode::Full {
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
@@ -1681,6 +1728,10 @@ impl Editor {
         &self.buffer
     }
 
+    pub fn project(&self) -> Option<&Model<Project>> {
+        self.project.as_ref()
+    }
+
     pub fn workspace(&self) -> Option<View<Workspace>> {
         self.workspace.as_ref()?.0.upgrade()
     }
@@ -1750,6 +1801,18 @@ impl Editor {
         self.completion_provider = Some(hub);
     }
 
+    pub fn take_completion_provider(&mut self) -> Option<Box<dyn CompletionProvider>> {
+        self.completion_provider.take()
+    }
+
+    pub fn set_hover_provider(&mut self, provider: Box<dyn HoverProvider>) {
+        self.hover_provider = Some(provider);
+    }
+
+    pub fn take_hover_provider(&mut self) -> Option<Box<dyn HoverProvider>> {
+        self.hover_provider.take()
+    }
+
     pub fn placeholder_text(&self, _cx: &mut WindowContext) -> Option<&str> {
         self.placeholder_text.as_deref()
     }
@@ -1846,6 +1909,7 @@ impl Editor {
         old_cursor_position: &Anchor,
         cx: &mut ViewContext<Self>,
     ) {
+        self.pending_surround = None;
         if self.focus_handle.is_focused(cx) && self.leader_peer_id.is_none() {
             self.buffer.update(cx, |buffer, cx| {
                 buffer.set_active_selections(
@@ -1941,6 +2005,7 @@ impl Editor {
             }
             self.refresh_code_actions(cx);
             self.refresh_document_highlights(cx);
+            refresh_word_highlights(self, cx);
             refresh_matching_bracket_highlights(self, cx);
             self.discard_copilot_suggestion(cx);
         }
@@ -2042,7 +2107,8 @@ impl Editor {
             SelectPhase::BeginColumnar {
                 position,
                 goal_column,
-            } => self.begin_columnar_selection(position, goal_column, cx),
+                reset,
+            } => self.begin_columnar_selection(position, goal_column, reset, cx),
             SelectPhase::Extend {
                 position,
                 click_count,
@@ -2162,6 +2228,7 @@ impl Editor {
         &mut self,
         position: DisplayPoint,
         goal_column: u32,
+        reset: bool,
         cx: &mut ViewContext<Self>,
     ) {
         if !self.focus_handle.is_focused(cx) {
@@ -2169,7 +2236,13 @@ impl Editor {
         }
 
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
-        let tail = self.selections.newest::<Point>(cx).tail();
+        let tail = if reset {
+            display_map
+                .clip_point(position, Bias::Left)
+                .to_point(&display_map)
+        } else {
+            self.selections.newest::<Point>(cx).tail()
+        };
         self.columnar_selection_tail = Some(display_map.buffer_snapshot.anchor_before(tail));
 
         self.select_columns(
@@ -2329,6 +2402,55 @@ impl Editor {
         cx.notify();
     }
 
+    pub fn column_selection_mode(&self) -> bool {
+        self.column_selection_mode
+    }
+
+    pub fn toggle_column_selection_mode(
+        &mut self,
+        _: &ToggleColumnSelectionMode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.column_selection_mode = !self.column_selection_mode;
+        self.column_selection_state = None;
+        cx.notify();
+    }
+
+    /// Moves the head of the rectangular selection by the given number of
+    /// display rows and columns, selecting one range per display row.
+    fn extend_column_selection(
+        &mut self,
+        row_delta: i32,
+        column_delta: i32,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let (tail, head) = match &self.column_selection_state {
+            Some(state) if Arc::ptr_eq(&state.selections, &self.selections.disjoint_anchors()) => {
+                (state.tail.to_display_point(&display_map), state.head)
+            }
+            _ => {
+                let newest = self.selections.newest_display(cx);
+                (newest.tail(), newest.head())
+            }
+        };
+
+        let max_row = display_map.max_point().row() as i32;
+        let head = DisplayPoint::new(
+            (head.row() as i32 + row_delta).clamp(0, max_row) as u32,
+            (head.column() as i32 + column_delta).max(0) as u32,
+        );
+        self.select_columns(tail, head, head.column(), &display_map, cx);
+        self.request_autoscroll(Autoscroll::fit(), cx);
+        self.column_selection_state = Some(ColumnSelectionState {
+            tail: display_map
+                .buffer_snapshot
+                .anchor_before(tail.to_point(&display_map)),
+            head,
+            selections: self.selections.disjoint_anchors(),
+        });
+    }
+
     pub fn has_pending_nonempty_selection(&self) -> bool {
         let pending_nonempty_selection = match self.selections.pending_anchor() {
             Some(Selection { start, end, .. }) => start != end,
@@ -2342,6 +2464,9 @@ impl Editor {
     }
 
     pub fn cancel(&mut self, _: &Cancel, cx: &mut ViewContext<Self>) {
+        if self.pending_surround.take().is_some() {
+            return;
+        }
         if self.dismiss_menus_and_popups(cx) {
             return;
         }
@@ -2393,6 +2518,13 @@ impl Editor {
             return;
         }
 
+        if !text.is_empty() {
+            if let Some(pending_surround) = self.pending_surround.take() {
+                surround::complete_pending_surround(self, pending_surround, &text, cx);
+                return;
+            }
+        }
+
         let selections = self.selections.all_adjusted(cx);
         let mut brace_inserted = false;
         let mut edits = Vec::new();
@@ -2480,6 +2612,20 @@ impl Editor {
                                 continue;
                             }
                         }
+
+                        // If the user treats every closing bracket as auto-inserted, and the
+                        // selection is followed by the typed closing bracket, move past it.
+                        let always_treat_brackets_as_autoclosed = snapshot
+                            .settings_at(selection.start, cx)
+                            .always_treat_brackets_as_autoclosed;
+                        if always_treat_brackets_as_autoclosed
+                            && text.as_ref() == bracket_pair.end.as_str()
+                            && snapshot.contains_str_at(selection.end, text.as_ref())
+                        {
+                            let anchor = snapshot.anchor_after(selection.end);
+                            new_selections.push((selection.map(|_| anchor), text.len()));
+                            continue;
+                        }
                     }
                     // If an opening bracket is 1 character long and is typed while
                     // text is selected, then surround that text with the bracket pair.
@@ -2590,6 +2736,7 @@ impl Editor {
 
     pub fn newline(&mut self, _: &Newline, cx: &mut ViewContext<Self>) {
         self.transact(cx, |this, cx| {
+            let mut list_renumber_edits = Vec::new();
             let (edits, selection_fixup_info): (Vec<_>, Vec<_>) = {
                 let selections = this.selections.all::<usize>(cx);
                 let multi_buffer = this.buffer.read(cx);
@@ -2681,6 +2828,23 @@ impl Editor {
                         } else {
                             (None, false)
                         };
+                        let comment_delimiter = comment_delimiter.or_else(|| {
+                            if !is_cursor {
+                                return None;
+                            }
+                            let (continuation, renumber_edits) =
+                                markdown_ext::list_continuation(&buffer, start)?;
+                            list_renumber_edits.extend(renumber_edits.into_iter().map(
+                                |(range, text)| {
+                                    (
+                                        buffer.anchor_before(range.start)
+                                            ..buffer.anchor_after(range.end),
+                                        text,
+                                    )
+                                },
+                            ));
+                            Some(continuation)
+                        });
 
                         let capacity_for_delimiter = comment_delimiter
                             .as_deref()
@@ -2708,6 +2872,16 @@ impl Editor {
             };
 
             this.edit_with_autoindent(edits, cx);
+            if !list_renumber_edits.is_empty() {
+                // Several cursors in the same list renumber the same items.
+                let buffer = this.buffer.read(cx).snapshot(cx);
+                list_renumber_edits.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &buffer));
+                list_renumber_edits
+                    .dedup_by(|(a, _), (b, _)| a.start.cmp(&b.start, &buffer).is_eq());
+                this.buffer.update(cx, |buffer, cx| {
+                    buffer.edit(list_renumber_edits, None, cx);
+                });
+            }
             let buffer = this.buffer.read(cx).snapshot(cx);
             let new_selections = selection_fixup_info
                 .into_iter()
@@ -2919,6 +3093,29 @@ impl Editor {
         let buffer = self.buffer.read(cx).read(cx);
         let mut new_selections = Vec::new();
         for (mut selection, region) in self.selections_with_autoclose_regions(selections, &buffer) {
+            if region.is_none()
+                && selection.is_empty()
+                && buffer
+                    .settings_at(selection.start, cx)
+                    .always_treat_brackets_as_autoclosed
+            {
+                // Treat an empty bracket pair around the cursor as if it had been auto-inserted.
+                if let Some(scope) = buffer.language_scope_at(selection.start) {
+                    for (pair, enabled) in scope.brackets() {
+                        if enabled
+                            && pair.close
+                            && selection.start >= pair.start.len()
+                            && buffer
+                                .contains_str_at(selection.start - pair.start.len(), &pair.start)
+                            && buffer.contains_str_at(selection.end, &pair.end)
+                        {
+                            selection.start -= pair.start.len();
+                            selection.end += pair.end.len();
+                            break;
+                        }
+                    }
+                }
+            }
             if let (Some(region), true) = (region, selection.is_empty()) {
                 let mut range = region.range.to_offset(&buffer);
                 if selection.start == range.start {
@@ -3572,20 +3769,12 @@ impl Editor {
         title: String,
         mut cx: AsyncWindowContext,
     ) -> Result<()> {
-        let replica_id = this.update(&mut cx, |this, cx| this.replica_id(cx))?;
-
-        let mut entries = transaction.0.into_iter().collect::<Vec<_>>();
-        cx.update(|cx| {
-            entries.sort_unstable_by_key(|(buffer, _)| {
-                buffer.read(cx).file().map(|f| f.path().clone())
-            });
-        })?;
-
         // If the project transaction's edits are all contained within this editor, then
         // avoid opening a new editor to display them.
-
-        if let Some((buffer, transaction)) = entries.first() {
-            if entries.len() == 1 {
+        if transaction.0.is_empty() {
+            return Ok(());
+        } else if transaction.0.len() == 1 {
+            if let Some((buffer, transaction)) = transaction.0.iter().next() {
                 let excerpt = this.update(&mut cx, |editor, cx| {
                     editor
                         .buffer()
@@ -3610,14 +3799,63 @@ impl Editor {
                     }
                 }
             }
-        } else {
+        }
+
+        Self::preview_project_transaction(workspace, transaction, title, cx).await
+    }
+
+    /// Opens a multibuffer showing the edits in `transaction`. If there are more
+    /// edits than `workspace_edit_preview_threshold`, they're reverted and shown
+    /// side by side with the text they replace instead, and only the ones the user
+    /// accepts are applied.
+    pub async fn preview_project_transaction(
+        workspace: WeakView<Workspace>,
+        transaction: ProjectTransaction,
+        title: String,
+        mut cx: AsyncWindowContext,
+    ) -> Result<()> {
+        let replica_id = workspace.update(&mut cx, |workspace, cx| {
+            workspace.project().read(cx).replica_id()
+        })?;
+
+        let mut entries = transaction.0.into_iter().collect::<Vec<_>>();
+        if entries.is_empty() {
+            return Ok(());
+        }
+        cx.update(|cx| {
+            entries.sort_unstable_by_key(|(buffer, _)| {
+                buffer.read(cx).file().map(|f| f.path().clone())
+            });
+        })?;
+
+        let (edit_count, preview_threshold) = cx.update(|cx| {
+            let edit_count = entries
+                .iter()
+                .map(|(buffer, transaction)| {
+                    buffer
+                        .read(cx)
+                        .edited_ranges_for_transaction::<usize>(transaction)
+                        .count()
+                })
+                .sum::<usize>();
+            let preview_threshold = EditorSettings::get_global(cx).workspace_edit_preview_threshold;
+            (edit_count, preview_threshold)
+        })?;
+        let summary = workspace_edit_summary(edit_count, entries.len());
+        let title = format!("{title} ({summary})");
+
+        if edit_count > preview_threshold {
+            let transaction = ProjectTransaction(entries.into_iter().collect());
+            workspace.update(&mut cx, |workspace, cx| {
+                edit_review::review_project_transaction(workspace, transaction, title, cx);
+            })?;
             return Ok(());
         }
 
         let mut ranges_to_highlight = Vec::new();
         let excerpt_buffer = cx.new_model(|cx| {
             let mut multibuffer =
-                MultiBuffer::new(replica_id, Capability::ReadWrite).with_title(title);
+                MultiBuffer::new(replica_id, Capability::ReadWrite).with_title(title.clone());
             for (buffer_handle, transaction) in &entries {
                 let buffer = buffer_handle.read(cx);
                 ranges_to_highlight.extend(
@@ -3662,10 +3900,9 @@ impl Editor {
             return None;
         }
 
+        let debounce = power_saving_debounce(CODE_ACTIONS_DEBOUNCE_TIMEOUT, cx);
         self.code_actions_task = Some(cx.spawn(|this, mut cx| async move {
-            cx.background_executor()
-                .timer(CODE_ACTIONS_DEBOUNCE_TIMEOUT)
-                .await;
+            cx.background_executor().timer(debounce).await;
 
             let actions = if let Ok(code_actions) = project.update(&mut cx, |project, cx| {
                 project.code_actions(&start_buffer, start..end, cx)
@@ -3706,10 +3943,9 @@ impl Editor {
             return None;
         }
 
+        let debounce = power_saving_debounce(DOCUMENT_HIGHLIGHTS_DEBOUNCE_TIMEOUT, cx);
         self.document_highlights_task = Some(cx.spawn(|this, mut cx| async move {
-            cx.background_executor()
-                .timer(DOCUMENT_HIGHLIGHTS_DEBOUNCE_TIMEOUT)
-                .await;
+            cx.background_executor().timer(debounce).await;
 
             let highlights = if let Some(highlights) = project
                 .update(&mut cx, |project, cx| {
@@ -3773,6 +4009,9 @@ impl Editor {
                         }
                     }
 
+                    if !read_ranges.is_empty() || !write_ranges.is_empty() {
+                        this.clear_background_highlights::<WordHighlight>(cx);
+                    }
                     this.highlight_background::<DocumentHighlightRead>(
                         read_ranges,
                         |theme| theme.editor_document_highlight_read_background,
@@ -3920,6 +4159,22 @@ impl Editor {
         .detach();
     }
 
+    /// Shows the name next to a collaborator's cursors while they're moving them.
+    fn show_remote_cursor_name(&mut self, replica_id: ReplicaId, cx: &mut ViewContext<Self>) {
+        self.active_remote_cursors.insert(
+            replica_id,
+            cx.spawn(|this, mut cx| async move {
+                cx.background_executor().timer(CURSORS_VISIBLE_FOR).await;
+                this.update(&mut cx, |this, cx| {
+                    this.active_remote_cursors.remove(&replica_id);
+                    cx.notify()
+                })
+                .ok();
+            }),
+        );
+        cx.notify();
+    }
+
     fn next_copilot_suggestion(&mut self, _: &copilot::NextSuggestion, cx: &mut ViewContext<Self>) {
         if self.has_active_copilot_suggestion(cx) {
             self.cycle_copilot_suggestions(Direction::Next, cx);
@@ -4390,6 +4645,9 @@ impl Editor {
         if self.move_to_next_snippet_tabstop(cx) || self.read_only(cx) {
             return;
         }
+        if emmet::expand_abbreviations(self, cx) {
+            return;
+        }
 
         let mut selections = self.selections.all_adjusted(cx);
         let buffer = self.buffer.read(cx);
@@ -5502,6 +5760,11 @@ impl Editor {
     }
 
     pub fn select_left(&mut self, _: &SelectLeft, cx: &mut ViewContext<Self>) {
+        if self.column_selection_mode {
+            self.extend_column_selection(0, -1, cx);
+            return;
+        }
+
         self.change_selections(Some(Autoscroll::fit()), cx, |s| {
             s.move_heads_with(|map, head, _| (movement::left(map, head), SelectionGoal::None));
         })
@@ -5522,6 +5785,11 @@ impl Editor {
     }
 
     pub fn select_right(&mut self, _: &SelectRight, cx: &mut ViewContext<Self>) {
+        if self.column_selection_mode {
+            self.extend_column_selection(0, 1, cx);
+            return;
+        }
+
         self.change_selections(Some(Autoscroll::fit()), cx, |s| {
             s.move_heads_with(|map, head, _| (movement::right(map, head), SelectionGoal::None));
         })
@@ -5681,6 +5949,11 @@ impl Editor {
     }
 
     pub fn select_up(&mut self, _: &SelectUp, cx: &mut ViewContext<Self>) {
+        if self.column_selection_mode {
+            self.extend_column_selection(-1, 0, cx);
+            return;
+        }
+
         let text_layout_details = &self.text_layout_details(cx);
         self.change_selections(Some(Autoscroll::fit()), cx, |s| {
             s.move_heads_with(|map, head, goal| {
@@ -5769,6 +6042,11 @@ impl Editor {
     }
 
     pub fn select_down(&mut self, _: &SelectDown, cx: &mut ViewContext<Self>) {
+        if self.column_selection_mode {
+            self.extend_column_selection(1, 0, cx);
+            return;
+        }
+
         let text_layout_details = &self.text_layout_details(cx);
         self.change_selections(Some(Autoscroll::fit()), cx, |s| {
             s.move_heads_with(|map, head, goal| {
@@ -7100,8 +7378,42 @@ impl Editor {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.select(selections.to_vec());
             });
+            self.select_larger_syntax_node_stack = stack;
+            return;
+        }
+
+        // Selections that weren't expanded shrink to the syntax node inside
+        // them that is closest to their head.
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx);
+        let mut selected_smaller_node = false;
+        let new_selections = old_selections
+            .into_iter()
+            .map(|selection| {
+                if selection.is_empty() {
+                    return selection;
+                }
+                match buffer.range_for_syntax_descendant(selection.range(), !selection.reversed) {
+                    Some(new_range) => {
+                        selected_smaller_node = true;
+                        Selection {
+                            id: selection.id,
+                            start: new_range.start,
+                            end: new_range.end,
+                            goal: SelectionGoal::None,
+                            reversed: selection.reversed,
+                        }
+                    }
+                    None => selection,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if selected_smaller_node {
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
         }
-        self.select_larger_syntax_node_stack = stack;
     }
 
     pub fn move_to_enclosing_bracket(
@@ -7830,6 +8142,7 @@ impl Editor {
                         editor
                     });
 
+                    this.clear_background_highlights::<WordHighlight>(cx);
                     let ranges = this
                         .clear_background_highlights::<DocumentHighlightWrite>(cx)
                         .into_iter()
@@ -8050,6 +8363,26 @@ impl Editor {
         })
     }
 
+    fn format_with_review(
+        &mut self,
+        _: &FormatWithReview,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let project = self.project.clone()?;
+        let workspace = self.workspace()?.downgrade();
+        let buffers = self.buffer().read(cx).all_buffers();
+        let format = project.update(cx, |project, cx| {
+            project.format(buffers, true, FormatTrigger::Manual, cx)
+        });
+
+        Some(cx.spawn(|_, mut cx| async move {
+            let transaction = format.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                edit_review::review_project_transaction(workspace, transaction, "Format", cx);
+            })
+        }))
+    }
+
     fn restart_language_server(&mut self, _: &RestartLanguageServer, cx: &mut ViewContext<Self>) {
         if let Some(project) = self.project.clone() {
             self.buffer.update(cx, |multi_buffer, cx| {
@@ -8470,9 +8803,18 @@ impl Editor {
 
     pub fn soft_wrap_mode(&self, cx: &AppContext) -> SoftWrap {
         let settings = self.buffer.read(cx).settings_at(0, cx);
-        let mode = self
-            .soft_wrap_mode_override
-            .unwrap_or_else(|| settings.soft_wrap);
+        let is_large_file = self
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .map_or(false, |buffer| buffer.read(cx).is_large_file());
+        let mode = self.soft_wrap_mode_override.unwrap_or_else(|| {
+            if is_large_file {
+                language_settings::SoftWrap::None
+            } else {
+                settings.soft_wrap
+            }
+        });
         match mode {
             language_settings::SoftWrap::None => SoftWrap::None,
             language_settings::SoftWrap::EditorWidth => SoftWrap::EditorWidth,
@@ -8955,7 +9297,7 @@ impl Editor {
 
     fn on_buffer_event(
         &mut self,
-        multibuffer: Model<MultiBuffer>,
+        _: Model<MultiBuffer>,
         event: &multi_buffer::Event,
         cx: &mut ViewContext<Self>,
     ) {
@@ -8963,46 +9305,26 @@ impl Editor {
             multi_buffer::Event::Edited {
                 singleton_buffer_edited,
             } => {
+                // Bring the display map up to date now, so that the edit can be painted without
+                // waiting on anything else.
+                self.display_map.update(cx, |map, cx| map.snapshot(cx));
                 self.refresh_active_diagnostics(cx);
-                self.refresh_code_actions(cx);
                 if self.has_active_copilot_suggestion(cx) {
                     self.update_visible_copilot_suggestion(cx);
                 }
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
 
-                if *singleton_buffer_edited {
-                    if let Some(project) = &self.project {
-                        let project = project.read(cx);
-                        let languages_affected = multibuffer
-                            .read(cx)
-                            .all_buffers()
-                            .into_iter()
-                            .filter_map(|buffer| {
-                                let buffer = buffer.read(cx);
-                                let language = buffer.language()?;
-                                if project.is_local()
-                                    && project.language_servers_for_buffer(buffer, cx).count() == 0
-                                {
-                                    None
-                                } else {
-                                    Some(language)
-                                }
-                            })
-                            .cloned()
-                            .collect::<HashSet<_>>();
-                        if !languages_affected.is_empty() {
-                            self.refresh_inlay_hints(
-                                InlayHintRefreshReason::BufferEdited(languages_affected),
-                                cx,
-                            );
-                        }
+                // Everything that isn't needed to show the edit waits until it has been painted.
+                match &mut self.pending_edit_refresh {
+                    Some(pending_singleton_buffer_edited) => {
+                        *pending_singleton_buffer_edited |= *singleton_buffer_edited;
+                    }
+                    None => {
+                        self.pending_edit_refresh = Some(*singleton_buffer_edited);
+                        cx.after_next_frame(|editor, cx| editor.refresh_after_edit(cx));
                     }
                 }
-
-                let Some(project) = &self.project else { return };
-                let telemetry = project.read(cx).client().telemetry().clone();
-                telemetry.log_edit_event("editor");
             }
             multi_buffer::Event::ExcerptsAdded {
                 buffer,
@@ -9022,6 +9344,7 @@ impl Editor {
             }
             multi_buffer::Event::Reparsed => cx.emit(EditorEvent::Reparsed),
             multi_buffer::Event::LanguageChanged => {
+                refresh_diagram_previews(self, cx);
                 cx.emit(EditorEvent::Reparsed);
                 cx.notify();
             }
@@ -9035,10 +9358,56 @@ impl Editor {
             multi_buffer::Event::DiagnosticsUpdated => {
                 self.refresh_active_diagnostics(cx);
             }
+            multi_buffer::Event::RemoteSelectionsChanged { replica_id } => {
+                self.show_remote_cursor_name(*replica_id, cx);
+            }
             _ => {}
         };
     }
 
+    fn refresh_after_edit(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(singleton_buffer_edited) = self.pending_edit_refresh.take() else {
+            return;
+        };
+        self.refresh_code_actions(cx);
+        refresh_diagram_previews(self, cx);
+
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        if singleton_buffer_edited {
+            let languages_affected = {
+                let project = project.read(cx);
+                self.buffer
+                    .read(cx)
+                    .all_buffers()
+                    .into_iter()
+                    .filter_map(|buffer| {
+                        let buffer = buffer.read(cx);
+                        let language = buffer.language()?;
+                        if project.is_local()
+                            && project.language_servers_for_buffer(buffer, cx).count() == 0
+                        {
+                            None
+                        } else {
+                            Some(language)
+                        }
+                    })
+                    .cloned()
+                    .collect::<HashSet<_>>()
+            };
+            if !languages_affected.is_empty() {
+                self.refresh_inlay_hints(
+                    InlayHintRefreshReason::BufferEdited(languages_affected),
+                    cx,
+                );
+            }
+        }
+
+        let telemetry = project.read(cx).client().telemetry().clone();
+        telemetry.log_edit_event("editor");
+    }
+
     fn on_display_map_changed(&mut self, _: Model<DisplayMap>, cx: &mut ViewContext<Self>) {
         cx.notify();
     }
@@ -9056,6 +9425,8 @@ impl Editor {
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
+        refresh_diagram_previews(self, cx);
+        refresh_word_highlights(self, cx);
         cx.notify();
     }
 
@@ -9067,6 +9438,31 @@ impl Editor {
         self.searchable
     }
 
+    /// Shows more lines around the excerpts containing the selections.
+    pub fn expand_excerpts(&mut self, action: &ExpandExcerpts, cx: &mut ViewContext<Self>) {
+        if self.buffer.read(cx).is_singleton() {
+            cx.propagate();
+            return;
+        }
+
+        let lines = if action.lines == 0 {
+            DEFAULT_EXPAND_EXCERPT_LINES
+        } else {
+            action.lines
+        };
+        let selections = self.selections.all::<usize>(cx);
+        self.buffer.update(cx, |buffer, cx| {
+            let excerpt_ids = selections
+                .iter()
+                .filter_map(|selection| {
+                    let (excerpt_id, _, _) = buffer.excerpt_containing(selection.head(), cx)?;
+                    Some(excerpt_id)
+                })
+                .collect::<Vec<_>>();
+            buffer.expand_excerpts(excerpt_ids, lines, cx);
+        });
+    }
+
     fn open_excerpts(&mut self, _: &OpenExcerpts, cx: &mut ViewContext<Self>) {
         let buffer = self.buffer.read(cx);
         if buffer.is_singleton() {
@@ -9563,6 +9959,41 @@ impl CompletionProvider for Model<Project> {
     }
 }
 
+pub trait HoverProvider {
+    fn hover(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: text::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<project::Hover>>>;
+}
+
+impl HoverProvider for Model<Project> {
+    fn hover(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: text::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<project::Hover>>> {
+        self.update(cx, |project, cx| project.hover(buffer, buffer_position, cx))
+    }
+}
+
+/// Describes the size of an edit spanning several files, e.g. "12 edits in 5 files".
+fn workspace_edit_summary(edit_count: usize, file_count: usize) -> String {
+    let edits = if edit_count == 1 { "edit" } else { "edits" };
+    let files = if file_count == 1 { "file" } else { "files" };
+    format!("{edit_count} {edits} in {file_count} {files}")
+}
+
+fn power_saving_debounce(debounce: Duration, cx: &AppContext) -> Duration {
+    if PowerSaving::is_enabled(cx) {
+        debounce * POWER_SAVING_DEBOUNCE_FACTOR
+    } else {
+        debounce
+    }
+}
+
 fn inlay_hint_settings(
     location: Anchor,
     snapshot: &MultiBufferSnapshot,
@@ -17,6 +17,10 @@ pub struct EditorSettings {
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
     pub redact_private_values: bool,
+    pub workspace_edit_preview_threshold: usize,
+    pub image_previews: bool,
+    pub diagram_previews: bool,
+    pub highlight_word_occurrences: bool,
 }
 
 /// When to populate a new search's query based on the text under the cursor.
@@ -127,6 +131,30 @@ pub struct EditorSettingsContent {
     ///
     /// Default: false
     pub redact_private_values: Option<bool>,
+
+    /// The number of edits above which multi-file edits, such as renames, have to be
+    /// reviewed before they're applied.
+    ///
+    /// Default: 100
+    pub workspace_edit_preview_threshold: Option<usize>,
+
+    /// Whether to preview images when hovering paths to them.
+    ///
+    /// Default: true
+    pub image_previews: Option<bool>,
+
+    /// Whether to render Mermaid and Graphviz code blocks in Markdown files
+    /// below the block. This runs the `mmdc` and `dot` commands on the
+    /// contents of the file, so it is off unless you trust what you open.
+    ///
+    /// Default: false
+    pub diagram_previews: Option<bool>,
+
+    /// Whether to highlight the other occurrences of the word under the cursor
+    /// once it rests there.
+    ///
+    /// Default: true
+    pub highlight_word_occurrences: Option<bool>,
 }
 
 // Toolbar related settings
@@ -805,6 +805,66 @@ fn test_fold_action(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_fold_syntax_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(Default::default(), cx).await;
+    cx.set_state(indoc! {"
+        fn main() {
+            let x = foo(
+                1,
+                2,
+            );ˇ
+        }
+    "});
+
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        let mut fold_rows = snapshot
+            .syntax_fold_rows(0..6)
+            .into_iter()
+            .collect::<Vec<_>>();
+        fold_rows.sort();
+        assert_eq!(fold_rows, [0, 1]);
+        assert_eq!(
+            snapshot
+                .syntax_fold_rows(1..3)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [1]
+        );
+    });
+
+    cx.update_editor(|editor, cx| editor.fold_at(&FoldAt { buffer_row: 1 }, cx));
+    assert_eq!(
+        cx.editor(|editor, cx| editor.display_text(cx)),
+        indoc! {"
+            fn main() {
+                let x = foo(⋯);
+            }
+        "}
+    );
+
+    cx.update_editor(|editor, cx| editor.fold_at(&FoldAt { buffer_row: 0 }, cx));
+    assert_eq!(
+        cx.editor(|editor, cx| editor.display_text(cx)),
+        indoc! {"
+            fn main() {⋯}
+        "}
+    );
+
+    cx.update_editor(|editor, cx| editor.unfold_at(&UnfoldAt { buffer_row: 0 }, cx));
+    assert_eq!(
+        cx.editor(|editor, cx| editor.display_text(cx)),
+        indoc! {"
+            fn main() {
+                let x = foo(⋯);
+            }
+        "}
+    );
+}
+
 #[gpui::test]
 fn test_move_cursor(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -1989,6 +2049,89 @@ async fn test_newline_comments(cx: &mut gpui::TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_newline_continues_markdown_lists(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(markdown_lang()), cx));
+
+    // Bullet items are continued with the same marker.
+    cx.set_state(indoc! {"
+        - fooˇ
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        - foo
+        - ˇ
+    "});
+
+    // Ordered items get the next number, and the items after them are renumbered.
+    cx.set_state(indoc! {"
+        1. fooˇ
+        2. bar
+        3. baz
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        1. foo
+        2. ˇ
+        3. bar
+        4. baz
+    "});
+
+    // Task items are continued with an unchecked checkbox.
+    cx.set_state(indoc! {"
+        - [x] doneˇ
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        - [x] done
+        - [ ] ˇ
+    "});
+
+    // Pressing enter on an empty item doesn't add another one.
+    cx.set_state("- foo\n- ˇ\n");
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state("- foo\n- \nˇ\n");
+}
+
+#[gpui::test]
+async fn test_toggle_task_list_item(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(markdown_lang()), cx));
+
+    // Every item touched by the selection is toggled, and plain items become tasks.
+    cx.set_state(indoc! {"
+        - [ ] «one
+        - [x] two
+        - threeˇ»
+        - [ ] four
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| markdown_ext::toggle_task_list_item(e, &ToggleTaskListItem, cx));
+    cx.assert_editor_state(indoc! {"
+        - [x] «one
+        - [ ] two
+        - [ ] threeˇ»
+        - [ ] four
+    "});
+
+    cx.update_editor(|e, cx| markdown_ext::toggle_task_list_item(e, &ToggleTaskListItem, cx));
+    cx.assert_editor_state(indoc! {"
+        - [ ] «one
+        - [x] two
+        - [x] threeˇ»
+        - [ ] four
+    "});
+}
+
 #[gpui::test]
 fn test_insert_with_old_selections(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -2046,6 +2189,123 @@ async fn test_tab(cx: &mut gpui::TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_tab_expands_emmet_abbreviations(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.tab_size = NonZeroU32::new(2)
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let html_language = Arc::new(Language::new(
+        LanguageConfig {
+            name: "HTML".into(),
+            ..Default::default()
+        },
+        Some(tree_sitter_html::language()),
+    ));
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(html_language), cx));
+
+    // Abbreviations aren't expanded unless the setting is enabled.
+    cx.set_state(indoc! {"
+        <p>a.linkˇ</p>
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| e.tab(&Tab, cx));
+    cx.assert_editor_state(indoc! {"
+        <p>a.link  ˇ</p>
+    "});
+
+    _ = cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings
+                    .languages
+                    .entry("HTML".into())
+                    .or_default()
+                    .expand_emmet_abbreviations = Some(true);
+            });
+        })
+    });
+
+    // Every cursor expands the same abbreviation, landing in its first tab stop.
+    cx.set_state(indoc! {"
+        <div>a.linkˇ</div>
+        <p>a.linkˇ</p>
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| e.tab(&Tab, cx));
+    cx.assert_editor_state(indoc! {r#"
+        <div><a class="link" href="ˇ"></a></div>
+        <p><a class="link" href="ˇ"></a></p>
+    "#});
+    cx.update_editor(|e, cx| e.tab(&Tab, cx));
+    cx.assert_editor_state(indoc! {r#"
+        <div><a class="link" href="">ˇ</a></div>
+        <p><a class="link" href="">ˇ</a></p>
+    "#});
+
+    // Words that aren't abbreviations are indented as usual.
+    cx.set_state(indoc! {"
+        <p>helloˇ</p>
+    "});
+    cx.update_editor(|e, cx| e.tab(&Tab, cx));
+    cx.assert_editor_state(indoc! {"
+        <p>hello  ˇ</p>
+    "});
+}
+
+#[gpui::test]
+async fn test_extract_variable_without_language_server(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let rust_language = Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    ));
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_language), cx));
+
+    // Each selected expression gets its own declaration, and every name is selected.
+    cx.set_state(indoc! {"
+        fn main() {
+            let x = «bar(1)ˇ» + 3;
+            if x > 0 {
+                baz(«x * 2ˇ»);
+            }
+        }
+    "});
+    cx.executor().run_until_parked();
+    cx.update_editor(|e, cx| refactor::extract_variable(e, &ExtractVariable, cx));
+    cx.executor().run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let «extractedˇ» = bar(1);
+            let x = «extractedˇ» + 3;
+            if x > 0 {
+                let «extractedˇ» = x * 2;
+                baz(«extractedˇ»);
+            }
+        }
+    "});
+
+    // Selections that aren't a whole expression are left alone.
+    cx.set_state(indoc! {"
+        fn main() {
+            let x = ba«r(1) +ˇ» 3;
+        }
+    "});
+    cx.update_editor(|e, cx| refactor::extract_variable(e, &ExtractVariable, cx));
+    cx.executor().run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let x = ba«r(1) +ˇ» 3;
+        }
+    "});
+}
+
 #[gpui::test]
 async fn test_tab_in_leading_whitespace_auto_indents_lines(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -3709,6 +3969,54 @@ fn test_split_selection_into_lines(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_column_selection_mode(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc!(
+        r#"oneˇ
+           a
+           three"#
+    ));
+
+    // Rows that are too short to reach the block are skipped.
+    cx.update_editor(|editor, cx| {
+        editor.toggle_column_selection_mode(&ToggleColumnSelectionMode, cx);
+        editor.select_down(&SelectDown, cx);
+        editor.select_down(&SelectDown, cx);
+        editor.select_left(&SelectLeft, cx);
+    });
+    cx.assert_editor_state(indoc!(
+        r#"on«ˇe»
+           a
+           th«ˇr»ee"#
+    ));
+    cx.simulate_input("X");
+    cx.assert_editor_state(indoc!(
+        r#"onXˇ
+           a
+           thXˇee"#
+    ));
+
+    // Blocks are measured in display columns, so tabs line up with spaces.
+    cx.set_state("\tˇx\nabcdefgh");
+    cx.update_editor(|editor, cx| {
+        editor.select_down(&SelectDown, cx);
+        editor.select_right(&SelectRight, cx);
+    });
+    cx.assert_editor_state("\t«xˇ»\nabcd«eˇ»fgh");
+    cx.simulate_input("Y");
+    cx.assert_editor_state("\tYˇ\nabcdYˇfgh");
+
+    // Outside of column selection mode, selections extend as usual.
+    cx.update_editor(|editor, cx| {
+        editor.toggle_column_selection_mode(&ToggleColumnSelectionMode, cx);
+        editor.select_left(&SelectLeft, cx);
+    });
+    cx.assert_editor_state("\t«ˇY»\nabcd«ˇY»fgh");
+}
+
 #[gpui::test]
 async fn test_add_selection_above_below(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -4284,6 +4592,37 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_select_smaller_syntax_node_without_expanding(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(Language::new(
+        LanguageConfig::default(),
+        Some(tree_sitter_rust::language()),
+    ));
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.executor().run_until_parked();
+
+    // A selection that wasn't made by expanding shrinks to the node inside it
+    // that is closest to its head.
+    cx.set_state("fn a() { «let b = c(d, e);ˇ» }");
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state("fn a() { let b = «c(d, e)ˇ»; }");
+
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state("fn a() { let b = c«(d, e)ˇ»; }");
+
+    cx.set_state("fn a() { «ˇlet b = c(d, e);» }");
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state("fn a() { let «ˇb» = c(d, e); }");
+
+    // Empty selections are left alone.
+    cx.set_state("fn a() { let b = cˇ(d, e); }");
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state("fn a() { let b = cˇ(d, e); }");
+}
+
 #[gpui::test]
 async fn test_autoindent_selections(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -4519,6 +4858,66 @@ async fn test_autoclose_pairs(cx: &mut gpui::TestAppContext) {
     cx.assert_editor_state("a\"\"ˇ");
 }
 
+#[gpui::test]
+async fn test_always_treat_brackets_as_autoclosed(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.always_treat_brackets_as_autoclosed = Some(true);
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    let language = Arc::new(Language::new(
+        LanguageConfig {
+            brackets: BracketPairConfig {
+                pairs: vec![
+                    BracketPair {
+                        start: "(".to_string(),
+                        end: ")".to_string(),
+                        close: true,
+                        newline: true,
+                    },
+                    BracketPair {
+                        start: "\"".to_string(),
+                        end: "\"".to_string(),
+                        close: true,
+                        newline: false,
+                    },
+                ],
+                ..Default::default()
+            },
+            autoclose_before: ")".to_string(),
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    ));
+
+    let registry = Arc::new(LanguageRegistry::test());
+    registry.add(language.clone());
+    cx.update_buffer(|buffer, cx| {
+        buffer.set_language_registry(registry);
+        buffer.set_language(Some(language), cx);
+    });
+
+    // Type over closing brackets that were typed by the user.
+    cx.set_state("foo(ˇ)");
+    cx.update_editor(|view, cx| view.handle_input(")", cx));
+    cx.assert_editor_state("foo()ˇ");
+
+    cx.set_state("\"ˇ\"");
+    cx.update_editor(|view, cx| view.handle_input("\"", cx));
+    cx.assert_editor_state("\"\"ˇ");
+
+    // Other closing brackets are still inserted.
+    cx.set_state("foo(ˇ\")");
+    cx.update_editor(|view, cx| view.handle_input(")", cx));
+    cx.assert_editor_state("foo()ˇ\")");
+
+    // Deleting the opening bracket of an empty pair deletes the closing one too.
+    cx.set_state("foo(ˇ)");
+    cx.update_editor(|view, cx| view.backspace(&Backspace, cx));
+    cx.assert_editor_state("fooˇ");
+}
+
 #[gpui::test]
 async fn test_autoclose_with_embedded_language(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -5012,6 +5411,96 @@ async fn test_surround_with_pair(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_surround_change_and_delete_pairs(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(Default::default(), cx).await;
+
+    // Selections are surrounded, and cursors surround the word they're in.
+    cx.set_state("let a = «b + cˇ»;\nlet d = eˇf;");
+    cx.update_editor(|editor, cx| {
+        surround::surround_selections(editor, &SurroundSelections { open: "(".into() }, cx)
+    });
+    cx.assert_editor_state("let a = («b + cˇ»);\nlet d = (eˇf);");
+
+    // Without a pair, as when run from the command palette, the next character typed is
+    // used as the pair.
+    cx.set_state("let a = «b + cˇ»;");
+    cx.update_editor(|editor, cx| {
+        surround::surround_selections(editor, &SurroundSelections::default(), cx)
+    });
+    cx.assert_editor_state("let a = «b + cˇ»;");
+    cx.update_editor(|editor, cx| editor.handle_input("[", cx));
+    cx.assert_editor_state("let a = [«b + cˇ»];");
+    cx.update_editor(|editor, cx| {
+        surround::change_surrounding_pair(editor, &ChangeSurroundingPair::default(), cx)
+    });
+    cx.update_editor(|editor, cx| editor.cancel(&Cancel, cx));
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("let a = [xˇ];");
+
+    // A pair can be given by its closing half.
+    cx.set_state("let a = foo(b, ˇc);");
+    cx.update_editor(|editor, cx| {
+        surround::change_surrounding_pair(editor, &ChangeSurroundingPair { open: "]".into() }, cx)
+    });
+    cx.assert_editor_state("let a = foo[b, ˇc];");
+
+    cx.set_state(r#"let a = "bˇc";"#);
+    cx.update_editor(|editor, cx| {
+        surround::delete_surrounding_pair(editor, &DeleteSurroundingPair, cx)
+    });
+    cx.assert_editor_state("let a = bˇc;");
+    cx.update_editor(|editor, cx| editor.undo(&Undo, cx));
+    cx.assert_editor_state(r#"let a = "bˇc";"#);
+}
+
+#[gpui::test]
+async fn test_surround_change_and_delete_tags(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let html_language = Arc::new(Language::new(
+        LanguageConfig {
+            name: "HTML".into(),
+            ..Default::default()
+        },
+        Some(tree_sitter_html::language()),
+    ));
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(html_language), cx));
+
+    cx.set_state("<div><p>heˇllo</p></div>");
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, cx| {
+        surround::change_surrounding_pair(
+            editor,
+            &ChangeSurroundingPair {
+                open: r#"<span class="a">"#.into(),
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(r#"<div><span class="a">heˇllo</span></div>"#);
+
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, cx| {
+        surround::delete_surrounding_pair(editor, &DeleteSurroundingPair, cx)
+    });
+    cx.assert_editor_state("<div>heˇllo</div>");
+
+    cx.update_editor(|editor, cx| {
+        surround::surround_selections(
+            editor,
+            &SurroundSelections {
+                open: "<em>".into(),
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state("<div><em>heˇllo</em></div>");
+}
+
 #[gpui::test]
 async fn test_delete_autoclose_pair(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -5229,6 +5718,45 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_whitespace_cleanup_during_save(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.remove_trailing_whitespace_on_save = Some(true);
+        settings.defaults.ensure_final_newline_on_save = Some(true);
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file("/file.txt", Default::default()).await;
+
+    let project = Project::test(fs, ["/file.txt".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/file.txt", cx))
+        .await
+        .unwrap();
+
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    _ = editor.update(cx, |editor, cx| editor.set_text("one  \ntwo\t\nthree", cx));
+
+    editor
+        .update(cx, |editor, cx| editor.save(project.clone(), cx))
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(
+        editor.update(cx, |editor, cx| editor.text(cx)),
+        "one\ntwo\nthree\n"
+    );
+    assert!(!cx.read(|cx| editor.is_dirty(cx)));
+
+    // The whitespace changes are undone in a single step.
+    _ = editor.update(cx, |editor, cx| editor.undo(&Undo, cx));
+    assert_eq!(
+        editor.update(cx, |editor, cx| editor.text(cx)),
+        "one  \ntwo\t\nthree"
+    );
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -8426,6 +8954,62 @@ async fn test_document_format_with_prettier(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+fn test_edit_review_applies_accepted_hunks(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let buffer = cx.new_model(|cx| {
+        language::Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "one\ntwo\nthree\n",
+        )
+    });
+    let edits = buffer.update(cx, |buffer_ref, _| {
+        let snapshot = buffer_ref.snapshot();
+        [(0..3, "ONE"), (4..7, "TWO"), (8..13, "THREE")]
+            .into_iter()
+            .map(|(range, new_text)| edit_review::ProposedEdit {
+                buffer: buffer.clone(),
+                range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                new_text: new_text.into(),
+            })
+            .collect::<Vec<_>>()
+    });
+    let review = cx.add_window(|cx| edit_review::EditReview::new("Format", edits, cx));
+
+    _ = review.update(cx, |review, cx| {
+        review.set_hunk_status(0, edit_review::HunkStatus::Accepted, cx);
+        review.set_hunk_status(1, edit_review::HunkStatus::Rejected, cx);
+        review.set_hunk_status(2, edit_review::HunkStatus::Accepted, cx);
+        review.apply(cx);
+
+        // Applying twice doesn't duplicate the edits.
+        review.apply(cx);
+    });
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "ONE\ntwo\nTHREE\n"
+    );
+
+    // The accepted hunks are undone as a single step.
+    buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "one\ntwo\nthree\n"
+    );
+}
+
+fn markdown_lang() -> Arc<Language> {
+    Arc::new(Language::new(
+        LanguageConfig {
+            name: "Markdown".into(),
+            ..LanguageConfig::default()
+        },
+        Some(tree_sitter_markdown::language()),
+    ))
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(row as u32, column as u32);
     point..point
@@ -17,7 +17,7 @@ use crate::{
     SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
 };
 use anyhow::Result;
-use collections::{BTreeMap, HashMap};
+use collections::{BTreeMap, HashMap, HashSet};
 use git::diff::DiffHunkStatus;
 use gpui::{
     div, fill, outline, overlay, point, px, quad, relative, size, transparent_black, Action,
@@ -139,6 +139,7 @@ impl EditorElement {
         });
 
         crate::rust_analyzer_ext::apply_related_actions(view, cx);
+        crate::markdown_ext::apply_related_actions(view, cx);
         register_action(view, cx, Editor::move_left);
         register_action(view, cx, Editor::move_right);
         register_action(view, cx, Editor::move_down);
@@ -273,7 +274,15 @@ impl EditorElement {
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
         register_action(view, cx, Editor::open_excerpts);
+        register_action(view, cx, Editor::expand_excerpts);
+        register_action(view, cx, crate::refactor::extract_variable);
+        register_action(view, cx, crate::refactor::extract_function);
+        register_action(view, cx, crate::surround::surround_selections);
+        register_action(view, cx, crate::surround::change_surrounding_pair);
+        register_action(view, cx, crate::surround::delete_surrounding_pair);
+        register_action(view, cx, crate::word_highlights::select_all_occurrences);
         register_action(view, cx, Editor::toggle_soft_wrap);
+        register_action(view, cx, Editor::toggle_column_selection_mode);
         register_action(view, cx, Editor::toggle_inlay_hints);
         register_action(view, cx, hover_popover::hover);
         register_action(view, cx, Editor::reveal_in_finder);
@@ -289,6 +298,13 @@ impl EditorElement {
                 cx.propagate();
             }
         });
+        register_action(view, cx, |editor, action, cx| {
+            if let Some(task) = editor.format_with_review(action, cx) {
+                task.detach_and_log_err(cx);
+            } else {
+                cx.propagate();
+            }
+        });
         register_action(view, cx, Editor::restart_language_server);
         register_action(view, cx, Editor::show_character_palette);
         register_action(view, cx, |editor, action, cx| {
@@ -415,11 +431,17 @@ impl EditorElement {
 
         let point_for_position = position_map.point_for_position(text_bounds, event.position);
         let position = point_for_position.previous_valid;
-        if modifiers.shift && modifiers.alt {
+        let column_selection_click = editor.column_selection_mode()
+            && click_count == 1
+            && !modifiers.alt
+            && !modifiers.control
+            && !modifiers.command;
+        if (modifiers.shift && modifiers.alt) || column_selection_click {
             editor.select(
                 SelectPhase::BeginColumnar {
                     position,
                     goal_column: point_for_position.exact_unclipped.column(),
+                    reset: !modifiers.shift,
                 },
                 cx,
             );
@@ -1846,11 +1868,23 @@ impl EditorElement {
 
         let relative_rows = self.calculate_relative_line_numbers(&snapshot, &rows, relative_to);
 
-        for (ix, row) in snapshot
+        let buffer_rows = snapshot
             .buffer_rows(rows.start)
             .take((rows.end - rows.start) as usize)
-            .enumerate()
-        {
+            .collect::<Vec<_>>();
+        let syntax_fold_rows = if include_fold_statuses && is_singleton {
+            let mut visible_buffer_rows = buffer_rows.iter().flatten().copied();
+            let first_row = visible_buffer_rows.next();
+            let last_row = visible_buffer_rows.last().or(first_row);
+            first_row
+                .zip(last_row)
+                .map(|(first_row, last_row)| snapshot.syntax_fold_rows(first_row..last_row + 1))
+                .unwrap_or_default()
+        } else {
+            HashSet::default()
+        };
+
+        for (ix, row) in buffer_rows.into_iter().enumerate() {
             let display_row = rows.start + ix as u32;
             let (active, color) = if active_rows.contains_key(&display_row) {
                 (true, cx.theme().colors().editor_active_line_number)
@@ -1884,7 +1918,7 @@ impl EditorElement {
                         is_singleton
                             .then(|| {
                                 snapshot
-                                    .fold_for_line(buffer_row)
+                                    .fold_for_line(buffer_row, &syntax_fold_rows)
                                     .map(|fold_status| (fold_status, buffer_row, active))
                             })
                             .flatten(),
@@ -2139,7 +2173,9 @@ impl EditorElement {
                     }
                     let key = HoveredCursor{replica_id: selection.replica_id, selection_id: selection.selection.id};
 
-                    let is_shown = editor.show_cursor_names || editor.hovered_cursors.contains_key(&key);
+                    let is_shown = editor.show_cursor_names
+                        || editor.hovered_cursors.contains_key(&key)
+                        || editor.active_remote_cursors.contains_key(&selection.replica_id);
 
                     remote_selections
                         .entry(selection.replica_id)
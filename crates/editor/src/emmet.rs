@@ -0,0 +1,107 @@
+use gpui::ViewContext;
+use language::{IndentSize, Point};
+use multi_buffer::MultiBufferSnapshot;
+use snippet::{emmet, Snippet};
+use util::ResultExt;
+
+use crate::Editor;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Markup { jsx: bool },
+    Stylesheet,
+}
+
+/// Expands the Emmet abbreviation before each cursor into a snippet, returning whether
+/// there was one to expand.
+pub(crate) fn expand_abbreviations(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    let selections = editor.selections.all::<usize>(cx);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+
+    // The same snippet is inserted at every cursor, so they all need the same abbreviation.
+    let mut abbreviation = None;
+    let mut insertion_ranges = Vec::new();
+    for selection in &selections {
+        if !selection.is_empty() {
+            return false;
+        }
+        let cursor = selection.head();
+        let Some(syntax) = syntax_at(&snapshot, cursor) else {
+            return false;
+        };
+        if !snapshot.settings_at(cursor, cx).expand_emmet_abbreviations {
+            return false;
+        }
+        let line_start = snapshot.point_to_offset(Point::new(cursor.to_point(&snapshot).row, 0));
+        let line_prefix = snapshot
+            .text_for_range(line_start..cursor)
+            .collect::<String>();
+        let text = match syntax {
+            Syntax::Markup { .. } => emmet::markup_abbreviation_before(&line_prefix),
+            Syntax::Stylesheet => emmet::stylesheet_abbreviation_before(&line_prefix),
+        };
+        let Some(text) = text else {
+            return false;
+        };
+        let text = text.to_string();
+        insertion_ranges.push(cursor - text.len()..cursor);
+        if let Some((first_text, first_syntax)) = &abbreviation {
+            if *first_text != text || *first_syntax != syntax {
+                return false;
+            }
+        } else {
+            abbreviation = Some((text, syntax));
+        }
+    }
+    let Some((abbreviation, syntax)) = abbreviation else {
+        return false;
+    };
+
+    let cursor = selections[0].head();
+    let settings = editor.buffer().read(cx).settings_at(cursor, cx);
+    let indent = if settings.hard_tabs {
+        IndentSize::tab()
+    } else {
+        IndentSize::spaces(settings.tab_size.get())
+    };
+    let text = match syntax {
+        Syntax::Markup { jsx } => {
+            emmet::expand_markup(&abbreviation, &indent.chars().collect::<String>(), jsx)
+        }
+        Syntax::Stylesheet => emmet::expand_stylesheet(&abbreviation),
+    };
+    let Some(snippet) = text.and_then(|text| Snippet::parse(&text).ok()) else {
+        return false;
+    };
+
+    editor.transact(cx, |editor, cx| {
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .log_err();
+    });
+    true
+}
+
+fn syntax_at(snapshot: &MultiBufferSnapshot, offset: usize) -> Option<Syntax> {
+    let language = snapshot.language_at(offset)?;
+    match language.name().as_ref() {
+        "HTML" => Some(Syntax::Markup { jsx: false }),
+        "CSS" => Some(Syntax::Stylesheet),
+        // In scripts, only expand abbreviations within JSX, where they can't be mistaken
+        // for identifiers and expressions.
+        "JavaScript" | "TSX" => {
+            let (buffer, offset) = snapshot.point_to_buffer_offset(offset)?;
+            let layer = buffer.syntax_layer_at(offset)?;
+            let mut node = layer
+                .node()
+                .descendant_for_byte_range(offset.saturating_sub(1), offset)?;
+            loop {
+                if matches!(node.kind(), "jsx_element" | "jsx_fragment") {
+                    return Some(Syntax::Markup { jsx: true });
+                }
+                node = node.parent()?;
+            }
+        }
+        _ => None,
+    }
+}
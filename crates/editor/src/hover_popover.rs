@@ -1,21 +1,23 @@
 use crate::{
     display_map::{InlayOffset, ToDisplayPoint},
     hover_links::{InlayHighlight, RangeInEditor},
-    Anchor, AnchorRangeExt, DisplayPoint, Editor, EditorSettings, EditorSnapshot, EditorStyle,
-    ExcerptId, Hover, RangeToAnchorExt,
+    image_preview, Anchor, AnchorRangeExt, DisplayPoint, Editor, EditorSettings, EditorSnapshot,
+    EditorStyle, ExcerptId, Hover, RangeToAnchorExt,
 };
 use futures::FutureExt;
 use gpui::{
-    div, px, AnyElement, CursorStyle, Hsla, InteractiveElement, IntoElement, Model, MouseButton,
-    ParentElement, Pixels, SharedString, Size, StatefulInteractiveElement, Styled, Task,
-    ViewContext, WeakView,
+    div, img, px, AnyElement, CursorStyle, Hsla, ImageData, ImageSource, InteractiveElement,
+    IntoElement, Model, MouseButton, ParentElement, Pixels, SharedString, Size,
+    StatefulInteractiveElement, Styled, Task, ViewContext, WeakView,
+};
+use language::{
+    markdown, Bias, DiagnosticEntry, Language, LanguageRegistry, ParsedMarkdown, ToOffset,
 };
-use language::{markdown, Bias, DiagnosticEntry, Language, LanguageRegistry, ParsedMarkdown};
 
 use lsp::DiagnosticSeverity;
 use project::{HoverBlock, HoverBlockKind, InlayHintLabelPart, Project};
 use settings::Settings;
-use std::{ops::Range, sync::Arc, time::Duration};
+use std::{ops::Range, path::Path, sync::Arc, time::Duration};
 use ui::{prelude::*, Tooltip};
 use util::TryFutureExt;
 use workspace::Workspace;
@@ -111,6 +113,7 @@ pub fn hover_at_inlay(editor: &mut Editor, inlay_hover: InlayHover, cx: &mut Vie
                     symbol_range: RangeInEditor::Inlay(inlay_hover.range.clone()),
                     blocks,
                     parsed_content,
+                    image: None,
                 };
 
                 this.update(&mut cx, |this, cx| {
@@ -229,6 +232,31 @@ fn show_hover(
         }
     }
 
+    // Preview the image whose path is being hovered, if it's on the local disk
+    let image_path = EditorSettings::get_global(cx)
+        .image_previews
+        .then(|| {
+            let buffer = buffer.read(cx);
+            let file = buffer.file()?.as_local()?;
+            let file_abs_path = file.abs_path(cx);
+            let worktree_abs_path = file_abs_path
+                .ancestors()
+                .nth(file.path().components().count())
+                .map(Path::to_path_buf);
+            let (range, path) =
+                image_preview::image_path_at(buffer, buffer_position.to_offset(buffer))?;
+            let candidates = image_preview::image_path_candidates(
+                &path,
+                &file_abs_path,
+                worktree_abs_path.as_deref(),
+            );
+            Some((
+                buffer.anchor_before(range.start)..buffer.anchor_after(range.end),
+                candidates,
+            ))
+        })
+        .flatten();
+
     let task = cx.spawn(|this, mut cx| {
         async move {
             // If we need to delay, delay a set amount initially before making the lsp request
@@ -247,11 +275,11 @@ fn show_hover(
                 None
             };
 
-            // query the LSP for hover info
-            let hover_request = cx.update(|cx| {
-                project.update(cx, |project, cx| {
-                    project.hover(&buffer, buffer_position, cx)
-                })
+            // query the hover provider, which is usually the LSP, for hover info
+            let hover_request = this.update(&mut cx, |this, cx| {
+                this.hover_provider
+                    .as_ref()
+                    .map(|provider| provider.hover(&buffer, buffer_position, cx))
             })?;
 
             if let Some(delay) = delay {
@@ -289,12 +317,37 @@ fn show_hover(
                     });
             })?;
 
-            let hover_result = hover_request.await.ok().flatten();
+            let hover_result = match hover_request {
+                Some(hover_request) => hover_request.await.ok().flatten(),
+                None => None,
+            }
+            .filter(|hover_result| !hover_result.is_empty());
+            let (image_range, image) = match image_path {
+                Some((range, candidates)) => {
+                    let fs = project.update(&mut cx, |project, _| project.fs().clone())?;
+                    let image = image_preview::load_image(candidates, fs, &mut cx)
+                        .await
+                        .ok();
+                    (Some(range), image)
+                }
+                None => (None, None),
+            };
             let snapshot = this.update(&mut cx, |this, cx| this.snapshot(cx))?;
             let hover_popover = match hover_result {
-                Some(hover_result) if !hover_result.is_empty() => {
+                hover_result if hover_result.is_some() || image.is_some() => {
+                    let (blocks, range, language) = hover_result
+                        .map(|hover_result| {
+                            (
+                                hover_result.contents,
+                                hover_result.range,
+                                hover_result.language,
+                            )
+                        })
+                        .unwrap_or_default();
+                    let range = range.or_else(|| image_range.filter(|_| image.is_some()));
+
                     // Create symbol range of anchors for highlighting and filtering of future requests.
-                    let range = if let Some(range) = hover_result.range {
+                    let range = if let Some(range) = range {
                         let start = snapshot
                             .buffer_snapshot
                             .anchor_in_excerpt(excerpt_id.clone(), range.start);
@@ -309,8 +362,6 @@ fn show_hover(
 
                     let language_registry =
                         project.update(&mut cx, |p, _| p.languages().clone())?;
-                    let blocks = hover_result.contents;
-                    let language = hover_result.language;
                     let parsed_content = parse_blocks(&blocks, &language_registry, language).await;
 
                     Some(InfoPopover {
@@ -318,6 +369,7 @@ fn show_hover(
                         symbol_range: RangeInEditor::Text(range),
                         blocks,
                         parsed_content,
+                        image,
                     })
                 }
 
@@ -486,6 +538,8 @@ pub struct InfoPopover {
     symbol_range: RangeInEditor,
     pub blocks: Vec<HoverBlock>,
     parsed_content: ParsedMarkdown,
+    /// The image whose path is hovered, shown below the documentation.
+    pub image: Option<Arc<ImageData>>,
 }
 
 impl InfoPopover {
@@ -513,6 +567,19 @@ impl InfoPopover {
                 workspace,
                 cx,
             ))
+            .when_some(self.image.clone(), |popover, image| {
+                let size = image.size();
+                let (width, height) = (i32::from(size.width), i32::from(size.height));
+                // Scale the image down to fit the popover, keeping its aspect ratio.
+                let scale = (max_size.width / px(width.max(1) as f32))
+                    .min(max_size.height / px(height.max(1) as f32))
+                    .min(1.);
+                popover.child(
+                    img(ImageSource::Data(image))
+                        .w(px(width as f32 * scale))
+                        .h(px(height as f32 * scale)),
+                )
+            })
             .into_any_element()
     }
 }
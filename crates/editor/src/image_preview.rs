@@ -0,0 +1,774 @@
+use crate::{
+    display_map::{BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle},
+    markdown_ext::is_markdown_language,
+    Editor, EditorMode, EditorSettings,
+};
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+use futures::{
+    future::{join_all, Shared},
+    FutureExt as _, TryFutureExt as _,
+};
+use gpui::{
+    div, img, px, AppContext, AsyncAppContext, Global, ImageData, ImageSource, IntoElement,
+    ParentElement, Styled, Task, ViewContext,
+};
+use language::Point;
+use project::Fs;
+use settings::Settings;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io, mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use util::{
+    paths::HOME,
+    process::{ProcessRequest, ProcessRunner},
+    ResultExt,
+};
+
+/// The extensions of the image files that can be previewed.
+const IMAGE_EXTENSIONS: &[&str] = &["bmp", "gif", "ico", "jpeg", "jpg", "png", "tiff", "webp"];
+
+const DIAGRAM_PREVIEW_DEBOUNCE: Duration = Duration::from_millis(1000);
+
+/// How many images of each kind are kept decoded. Once there are more, the
+/// least recently used ones are dropped.
+const MAX_CACHED_IMAGES: usize = 32;
+
+/// The pixel height of a rendered diagram that takes up one row in the editor.
+const DIAGRAM_PIXELS_PER_ROW: f32 = 20.;
+const MAX_DIAGRAM_PREVIEW_ROWS: u8 = 24;
+
+type ImageTask = Shared<Task<Result<Arc<ImageData>, Arc<anyhow::Error>>>>;
+
+/// A diagram's kind and a hash of its source.
+type DiagramKey = (DiagramKind, u64);
+
+/// Images decoded for previews, shared by all editors.
+#[derive(Default)]
+struct ImagePreviewCache {
+    /// Images loaded from files, along with the modification time of the
+    /// file when it was loaded, so that they are reloaded when it changes.
+    files: HashMap<PathBuf, (SystemTime, CachedImage)>,
+    diagrams: HashMap<DiagramKey, CachedImage>,
+    /// The kinds of diagram whose command isn't installed, so that it is only
+    /// looked for once.
+    missing_commands: HashSet<DiagramKind>,
+    use_count: usize,
+}
+
+struct CachedImage {
+    task: ImageTask,
+    last_used: usize,
+}
+
+impl Global for ImagePreviewCache {}
+
+impl ImagePreviewCache {
+    fn next_use(&mut self) -> usize {
+        self.use_count += 1;
+        self.use_count
+    }
+}
+
+fn evict_least_recently_used<K, V>(map: &mut HashMap<K, V>, last_used: impl Fn(&V) -> usize)
+where
+    K: Clone + Eq + Hash,
+{
+    while map.len() > MAX_CACHED_IMAGES {
+        let Some(key) = map
+            .iter()
+            .min_by_key(|(_, value)| last_used(value))
+            .map(|(key, _)| key.clone())
+        else {
+            break;
+        };
+        map.remove(&key);
+    }
+}
+
+fn decode_image(bytes: &[u8]) -> Result<Arc<ImageData>> {
+    let image = image::load_from_memory(bytes)?.into_bgra8();
+    Ok(Arc::new(ImageData::new(image)))
+}
+
+/// Loads the image at the first of the given paths that exists.
+pub(crate) async fn load_image(
+    candidate_paths: Vec<PathBuf>,
+    fs: Arc<dyn Fs>,
+    cx: &mut AsyncAppContext,
+) -> Result<Arc<ImageData>> {
+    let mut found = None;
+    for abs_path in candidate_paths {
+        if let Some(metadata) = fs.metadata(&abs_path).await? {
+            if !metadata.is_dir {
+                found = Some((abs_path, metadata.mtime));
+                break;
+            }
+        }
+    }
+    let (abs_path, mtime) = found.ok_or_else(|| anyhow!("image not found"))?;
+
+    let task = cx.update(|cx| {
+        let executor = cx.background_executor().clone();
+        let cache = cx.default_global::<ImagePreviewCache>();
+        let last_used = cache.next_use();
+        if let Some((cached_mtime, cached)) = cache.files.get_mut(&abs_path) {
+            if *cached_mtime == mtime {
+                cached.last_used = last_used;
+                return cached.task.clone();
+            }
+        }
+
+        let task = executor
+            .spawn(
+                {
+                    let abs_path = abs_path.clone();
+                    async move { decode_image(&fs.load_bytes(&abs_path).await?) }
+                }
+                .map_err(Arc::new),
+            )
+            .shared();
+        cache.files.insert(
+            abs_path,
+            (
+                mtime,
+                CachedImage {
+                    task: task.clone(),
+                    last_used,
+                },
+            ),
+        );
+        evict_least_recently_used(&mut cache.files, |(_, image)| image.last_used);
+        task
+    })?;
+    task.await.map_err(|error| anyhow!("{error:#}"))
+}
+
+/// Finds the path of an image file around the given offset, as it would be
+/// written in code or in a Markdown link.
+pub(crate) fn image_path_at(
+    buffer: &text::BufferSnapshot,
+    offset: usize,
+) -> Option<(Range<usize>, String)> {
+    let row = buffer.offset_to_point(offset).row;
+    let line_start = buffer.point_to_offset(Point::new(row, 0));
+    let line_end = buffer.point_to_offset(Point::new(row, buffer.line_len(row)));
+    let line = buffer
+        .text_for_range(line_start..line_end)
+        .collect::<String>();
+    let (range, path) = image_path_in_line(&line, offset - line_start)?;
+    Some((line_start + range.start..line_start + range.end, path))
+}
+
+fn image_path_in_line(line: &str, column: usize) -> Option<(Range<usize>, String)> {
+    let is_path_char = |c: char| {
+        c.is_alphanumeric() || matches!(c, '/' | '\\' | '.' | '_' | '-' | '~' | '@' | '+' | '%')
+    };
+    let start = line[..column]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_path_char(*c))
+        .map_or(0, |(ix, c)| ix + c.len_utf8());
+    let end = line[column..]
+        .char_indices()
+        .find(|(_, c)| !is_path_char(*c))
+        .map_or(line.len(), |(ix, _)| column + ix);
+
+    // URLs are loaded by whatever displays the file, not read from disk.
+    if line[..start].ends_with(':') {
+        return None;
+    }
+    let path = &line[start..end];
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    IMAGE_EXTENSIONS
+        .contains(&extension.as_str())
+        .then(|| (start..end, path.to_string()))
+}
+
+/// The paths that an image path written in a file could refer to: relative
+/// to the file's directory or to the root of its worktree.
+pub(crate) fn image_path_candidates(
+    path: &str,
+    file_abs_path: &Path,
+    worktree_abs_path: Option<&Path>,
+) -> Vec<PathBuf> {
+    if let Some(path) = path.strip_prefix("~/") {
+        return vec![HOME.join(path)];
+    }
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return vec![path.to_path_buf()];
+    }
+    file_abs_path
+        .parent()
+        .into_iter()
+        .chain(worktree_abs_path)
+        .map(|dir| dir.join(path))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum DiagramKind {
+    Mermaid,
+    Graphviz,
+}
+
+impl DiagramKind {
+    fn for_info_string(info: &str) -> Option<Self> {
+        match info
+            .split_whitespace()
+            .next()?
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Graphviz),
+            _ => None,
+        }
+    }
+
+    fn program(&self) -> &'static str {
+        match self {
+            Self::Mermaid => "mmdc",
+            Self::Graphviz => "dot",
+        }
+    }
+
+    fn render_request(&self, source: String) -> ProcessRequest {
+        let args: &[&str] = match self {
+            Self::Mermaid => &["--input", "-", "--output", "-", "--outputFormat", "png"],
+            Self::Graphviz => &["-Tpng"],
+        };
+        ProcessRequest {
+            program: self.program().into(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            working_directory: std::env::temp_dir(),
+            stdin: source,
+        }
+    }
+}
+
+/// A fenced code block in Markdown that contains a diagram.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DiagramBlock {
+    kind: DiagramKind,
+    source: String,
+    /// The offset of the start of the line with the closing fence.
+    closing_fence: usize,
+}
+
+fn diagram_blocks(text: &str) -> Vec<DiagramBlock> {
+    let mut blocks = Vec::new();
+    let mut open_fence: Option<(Option<DiagramKind>, char, usize, usize)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim_start_matches(' ');
+        if line.len() - trimmed.len() > 3 {
+            continue;
+        }
+        let fence_char = match trimmed.chars().next() {
+            Some(c @ ('`' | '~')) => c,
+            _ => continue,
+        };
+        let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+        if fence_len < 3 {
+            continue;
+        }
+        let info = &trimmed[fence_len..];
+
+        match open_fence {
+            None => {
+                open_fence = Some((
+                    DiagramKind::for_info_string(info),
+                    fence_char,
+                    fence_len,
+                    offset,
+                ));
+            }
+            Some((kind, open_char, open_len, source_start)) => {
+                if fence_char == open_char && fence_len >= open_len && info.trim().is_empty() {
+                    if let Some(kind) = kind {
+                        blocks.push(DiagramBlock {
+                            kind,
+                            source: text[source_start..line_start].to_string(),
+                            closing_fence: line_start,
+                        });
+                    }
+                    open_fence = None;
+                }
+            }
+        }
+    }
+    blocks
+}
+
+impl DiagramBlock {
+    fn key(&self) -> DiagramKey {
+        let mut hasher = DefaultHasher::new();
+        self.source.hash(&mut hasher);
+        (self.kind, hasher.finish())
+    }
+}
+
+fn is_missing_command(error: &anyhow::Error) -> bool {
+    error.chain().any(|error| {
+        error
+            .downcast_ref::<io::Error>()
+            .map_or(false, |error| error.kind() == io::ErrorKind::NotFound)
+    })
+}
+
+/// Renders a diagram, or returns `None` if the command that renders it isn't
+/// installed.
+fn render_diagram(
+    block: DiagramBlock,
+    process_runner: Arc<dyn ProcessRunner>,
+    cx: &mut AppContext,
+) -> Option<ImageTask> {
+    let key = block.key();
+    let kind = block.kind;
+    let executor = cx.background_executor().clone();
+    let cache = cx.default_global::<ImagePreviewCache>();
+    if cache.missing_commands.contains(&kind) {
+        return None;
+    }
+    let last_used = cache.next_use();
+    if let Some(cached) = cache.diagrams.get_mut(&key) {
+        cached.last_used = last_used;
+        return Some(cached.task.clone());
+    }
+
+    let task = executor
+        .spawn(
+            async move {
+                let output = process_runner
+                    .run(kind.render_request(block.source))
+                    .await?;
+                if !output.success() {
+                    return Err(anyhow!(
+                        "rendering {kind:?} diagram failed with exit code {:?}: {}",
+                        output.exit_code,
+                        String::from_utf8_lossy(&output.stderr),
+                    ));
+                }
+                decode_image(&output.stdout)
+            }
+            // Only logged once per diagram, since the result is cached. A missing
+            // command is reported once per kind by the caller instead.
+            .inspect_err(|error| {
+                if !is_missing_command(error) {
+                    log::error!("failed to render diagram: {error:?}");
+                }
+            })
+            .map_err(Arc::new),
+        )
+        .shared();
+    cache.diagrams.insert(
+        key,
+        CachedImage {
+            task: task.clone(),
+            last_used,
+        },
+    );
+    evict_least_recently_used(&mut cache.diagrams, |image| image.last_used);
+    Some(task)
+}
+
+/// Records that a diagram failed to render because its command isn't
+/// installed, so that it isn't run again.
+fn record_missing_command(kind: DiagramKind, cx: &mut AppContext) {
+    let cache = cx.default_global::<ImagePreviewCache>();
+    if cache.missing_commands.insert(kind) {
+        cache
+            .diagrams
+            .retain(|(cached_kind, _), _| *cached_kind != kind);
+        log::warn!(
+            "`{}` was not found, so {kind:?} diagrams won't be previewed",
+            kind.program()
+        );
+    }
+}
+
+/// The blocks showing rendered diagrams below their source in an editor.
+#[derive(Default)]
+pub(crate) struct DiagramPreviews {
+    /// The diagram shown by each block, in the order they appear in the file.
+    blocks: Vec<(DiagramKey, BlockId)>,
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+/// Renders the Mermaid and Graphviz blocks of a Markdown file, off the main
+/// thread, and shows them below their source once the file stops changing.
+/// Only the blocks whose diagram changed are replaced.
+pub(crate) fn refresh_diagram_previews(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let enabled =
+        EditorSettings::get_global(cx).diagram_previews && editor.mode() == EditorMode::Full;
+    let buffer = editor.buffer().read(cx).as_singleton().filter(|buffer| {
+        enabled
+            && buffer
+                .read(cx)
+                .language()
+                .map_or(false, |language| is_markdown_language(language))
+    });
+    let (Some(buffer), Some(project)) = (buffer, editor.project.clone()) else {
+        editor.diagram_previews.refresh_task = None;
+        let blocks = mem::take(&mut editor.diagram_previews.blocks);
+        if !blocks.is_empty() {
+            editor.remove_blocks(blocks.into_iter().map(|(_, id)| id).collect(), None, cx);
+        }
+        return;
+    };
+
+    let process_runner = project.read(cx).process_runner().clone();
+    let task = cx.spawn(|editor, mut cx| {
+        async move {
+            cx.background_executor()
+                .timer(DIAGRAM_PREVIEW_DEBOUNCE)
+                .await;
+
+            let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+            let blocks = cx
+                .background_executor()
+                .spawn({
+                    let text = snapshot.text();
+                    async move { diagram_blocks(&text) }
+                })
+                .await;
+            let mut shown = editor.update(&mut cx, |editor, _| {
+                let mut shown = HashMap::<DiagramKey, usize>::default();
+                for (key, _) in &editor.diagram_previews.blocks {
+                    *shown.entry(*key).or_default() += 1;
+                }
+                shown
+            })?;
+
+            // Diagrams that are already shown keep their block, so they are
+            // neither rendered nor replaced again.
+            let mut diagrams = Vec::new();
+            let mut renders = Vec::new();
+            cx.update(|cx| {
+                for block in blocks {
+                    let key = block.key();
+                    let position = snapshot.anchor_after(block.closing_fence);
+                    if let Some(count) = shown.get_mut(&key).filter(|count| **count > 0) {
+                        *count -= 1;
+                        diagrams.push((key, position, None));
+                    } else if let Some(render) = render_diagram(block, process_runner.clone(), cx) {
+                        diagrams.push((key, position, Some(renders.len())));
+                        renders.push(render);
+                    }
+                }
+            })?;
+            let images = join_all(renders).await;
+
+            editor.update(&mut cx, |editor, cx| {
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let Some((excerpt_id, _, _)) = multibuffer.as_singleton() else {
+                    return;
+                };
+                let excerpt_id = *excerpt_id;
+
+                let mut old_blocks = mem::take(&mut editor.diagram_previews.blocks);
+                let mut blocks = Vec::new();
+                let mut new_blocks = Vec::new();
+                for (key, position, render_ix) in diagrams {
+                    match render_ix {
+                        None => {
+                            if let Some(ix) = old_blocks.iter().position(|(old, _)| *old == key) {
+                                blocks.push(old_blocks.remove(ix));
+                            }
+                        }
+                        Some(render_ix) => match &images[render_ix] {
+                            Ok(image) => {
+                                let position = multibuffer.anchor_in_excerpt(excerpt_id, position);
+                                new_blocks.push((
+                                    blocks.len(),
+                                    key,
+                                    diagram_block_properties(position, image.clone()),
+                                ));
+                            }
+                            Err(error) => {
+                                if is_missing_command(error) {
+                                    record_missing_command(key.0, cx);
+                                }
+                            }
+                        },
+                    }
+                }
+
+                if !old_blocks.is_empty() {
+                    editor.remove_blocks(
+                        old_blocks.into_iter().map(|(_, id)| id).collect(),
+                        None,
+                        cx,
+                    );
+                }
+                let (indices_and_keys, properties): (Vec<_>, Vec<_>) = new_blocks
+                    .into_iter()
+                    .map(|(ix, key, properties)| ((ix, key), properties))
+                    .unzip();
+                let block_ids = editor.insert_blocks(properties, None, cx);
+                for ((ix, key), block_id) in indices_and_keys.into_iter().zip(block_ids).rev() {
+                    blocks.insert(ix, (key, block_id));
+                }
+                editor.diagram_previews.blocks = blocks;
+            })
+        }
+        .log_err()
+    });
+    editor.diagram_previews.refresh_task = Some(task);
+}
+
+fn diagram_block_properties(
+    position: multi_buffer::Anchor,
+    image: Arc<ImageData>,
+) -> BlockProperties<multi_buffer::Anchor> {
+    let size = image.size();
+    let (width, height) = (i32::from(size.width) as f32, i32::from(size.height) as f32);
+    let rows = (height / DIAGRAM_PIXELS_PER_ROW)
+        .ceil()
+        .clamp(1., MAX_DIAGRAM_PREVIEW_ROWS as f32) as u8;
+    let aspect_ratio = width / height.max(1.);
+
+    BlockProperties {
+        position,
+        height: rows,
+        style: BlockStyle::Sticky,
+        disposition: BlockDisposition::Below,
+        render: Arc::new(move |cx: &mut BlockContext| {
+            let height = cx.line_height * rows as f32;
+            let width = (height * aspect_ratio).min(cx.max_width);
+            div()
+                .pl(cx.gutter_dimensions.width)
+                .py(px(2.))
+                .child(
+                    img(ImageSource::Data(image.clone()))
+                        .w(width)
+                        .h(height - px(4.)),
+                )
+                .into_any_element()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use language::{Language, LanguageConfig};
+    use parking_lot::Mutex;
+    use project::{FakeFs, Project};
+    use settings::SettingsStore;
+    use util::process::{FakeProcessRunner, ProcessOutput};
+
+    #[test]
+    fn test_image_path_in_line() {
+        let line = r#"let icon = load("assets/icons/file.png");"#;
+        let column = line.find("icons").unwrap();
+        let start = line.find("assets").unwrap();
+        assert_eq!(
+            image_path_in_line(line, column),
+            Some((
+                start..start + "assets/icons/file.png".len(),
+                "assets/icons/file.png".into()
+            ))
+        );
+        assert_eq!(image_path_in_line(line, line.find("load").unwrap()), None);
+
+        let line = "![logo](../docs/Logo.JPG) and [site](https://zed.dev/logo.png)";
+        assert_eq!(
+            image_path_in_line(line, line.find("Logo").unwrap()).map(|(_, path)| path),
+            Some("../docs/Logo.JPG".into())
+        );
+        assert_eq!(
+            image_path_in_line(line, line.find("zed.dev").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_image_path_candidates() {
+        assert_eq!(
+            image_path_candidates(
+                "images/a.png",
+                Path::new("/root/docs/readme.md"),
+                Some(Path::new("/root"))
+            ),
+            [
+                PathBuf::from("/root/docs/images/a.png"),
+                PathBuf::from("/root/images/a.png")
+            ]
+        );
+        assert_eq!(
+            image_path_candidates("/tmp/a.png", Path::new("/root/readme.md"), None),
+            [PathBuf::from("/tmp/a.png")]
+        );
+    }
+
+    #[test]
+    fn test_diagram_blocks() {
+        let text = indoc! {"
+            # Diagrams
+
+            ```mermaid
+            graph TD;
+              A-->B;
+            ```
+
+            ```rust
+            fn main() {}
+            ```
+
+            ~~~~ dot
+            digraph { a -> b }
+            ```
+            ~~~~
+            "};
+        let blocks = diagram_blocks(text);
+        assert_eq!(
+            blocks,
+            [
+                DiagramBlock {
+                    kind: DiagramKind::Mermaid,
+                    source: "graph TD;\n  A-->B;\n".into(),
+                    closing_fence: text.find("```\n\n```rust").unwrap(),
+                },
+                DiagramBlock {
+                    kind: DiagramKind::Graphviz,
+                    source: "digraph { a -> b }\n```\n".into(),
+                    closing_fence: text.rfind("~~~~").unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_diagram_previews(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.diagram_previews = Some(true);
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file("/notes.md", Default::default()).await;
+        let project = Project::test(fs, ["/notes.md".as_ref()], cx).await;
+        let renders = Arc::new(Mutex::new(Vec::new()));
+        project.update(cx, |project, _| {
+            let renders = renders.clone();
+            project.set_process_runner(FakeProcessRunner::create(move |request| {
+                renders.lock().push(request.program.clone());
+                if request.program != "mmdc" {
+                    return Err(io::Error::from(io::ErrorKind::NotFound).into());
+                }
+                let mut png = Vec::new();
+                image::DynamicImage::ImageRgba8(image::RgbaImage::new(4, 2))
+                    .write_to(&mut png, image::ImageOutputFormat::Png)?;
+                Ok(ProcessOutput {
+                    exit_code: Some(0),
+                    stdout: png,
+                    ..Default::default()
+                })
+            }));
+        });
+
+        let buffer = project
+            .update(cx, |project, cx| project.open_local_buffer("/notes.md", cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            let markdown = Language::new(
+                LanguageConfig {
+                    name: "Markdown".into(),
+                    ..Default::default()
+                },
+                None,
+            );
+            buffer.set_language(Some(Arc::new(markdown)), cx);
+            buffer.set_text(
+                indoc! {"
+                    # Notes
+
+                    ```mermaid
+                    graph TD; A-->B;
+                    ```
+
+                    ```dot
+                    digraph { a -> b }
+                    ```
+
+                    ```mermaid
+                    graph TD; C-->D;
+                    ```
+                "},
+                cx,
+            );
+        });
+        let (editor, cx) =
+            cx.add_window_view(|cx| Editor::for_buffer(buffer.clone(), Some(project.clone()), cx));
+        let shown_blocks = |cx: &mut gpui::VisualTestContext| {
+            editor.update(cx, |editor, _| {
+                editor
+                    .diagram_previews
+                    .blocks
+                    .iter()
+                    .map(|(_, block_id)| *block_id)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        cx.executor().advance_clock(DIAGRAM_PREVIEW_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(*renders.lock(), ["mmdc", "dot", "mmdc"]);
+        let blocks = shown_blocks(cx);
+        assert_eq!(blocks.len(), 2);
+
+        // Editing outside the diagrams renders nothing, and keeps their blocks.
+        // The missing `dot` command isn't looked for again.
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "More ")], None, cx));
+        cx.executor().advance_clock(DIAGRAM_PREVIEW_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(renders.lock().len(), 3);
+        assert_eq!(shown_blocks(cx), blocks);
+
+        // Editing a diagram only replaces its own block.
+        buffer.update(cx, |buffer, cx| {
+            let offset = buffer.text().find("C-->D").unwrap();
+            buffer.edit([(offset..offset + 1, "E")], None, cx)
+        });
+        cx.executor().advance_clock(DIAGRAM_PREVIEW_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(renders.lock().len(), 4);
+        let new_blocks = shown_blocks(cx);
+        assert_eq!(new_blocks.len(), 2);
+        assert_eq!(new_blocks[0], blocks[0]);
+        assert_ne!(new_blocks[1], blocks[1]);
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let mut map = HashMap::default();
+        for ix in 0..MAX_CACHED_IMAGES + 2 {
+            map.insert(ix, ix);
+            evict_least_recently_used(&mut map, |last_used| *last_used);
+        }
+        assert_eq!(map.len(), MAX_CACHED_IMAGES);
+        assert!(!map.contains_key(&0));
+        assert!(!map.contains_key(&1));
+        assert!(map.contains_key(&(MAX_CACHED_IMAGES + 1)));
+    }
+}
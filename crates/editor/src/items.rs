@@ -1,7 +1,7 @@
 use crate::{
     editor_settings::SeedQuerySetting, persistence::DB, scroll::ScrollAnchor, Anchor, Autoscroll,
-    Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, MultiBuffer, MultiBufferSnapshot,
-    NavigationData, ToPoint as _,
+    Editor, EditorEvent, EditorMode, EditorSettings, ExcerptId, ExcerptRange, MultiBuffer,
+    MultiBufferSnapshot, NavigationData, ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
@@ -801,6 +801,10 @@ impl Item for Editor {
         self.pixel_position_of_newest_cursor
     }
 
+    fn shows_typed_text(&self, _: &AppContext) -> bool {
+        self.mode == EditorMode::Full
+    }
+
     fn breadcrumb_location(&self) -> ToolbarItemLocation {
         if self.show_breadcrumbs {
             ToolbarItemLocation::PrimaryLeft
@@ -0,0 +1,392 @@
+use std::{ops::Range, sync::Arc};
+
+use gpui::{View, ViewContext, WindowContext};
+use language::{BufferSnapshot, Language, Point};
+use multi_buffer::MultiBufferSnapshot;
+
+use crate::{element::register_action, Editor, FormatMarkdownTable, ToggleTaskListItem};
+
+pub fn apply_related_actions(editor: &View<Editor>, cx: &mut WindowContext) {
+    let is_markdown_related = editor.update(cx, |editor, cx| {
+        editor
+            .buffer()
+            .read(cx)
+            .all_buffers()
+            .iter()
+            .any(|b| match b.read(cx).language() {
+                Some(l) => is_markdown_language(l),
+                None => false,
+            })
+    });
+
+    if is_markdown_related {
+        register_action(editor, cx, toggle_task_list_item);
+        register_action(editor, cx, format_markdown_table);
+    }
+}
+
+/// Checks or unchecks the task list items on the selected lines, turning plain list
+/// items into unchecked tasks.
+pub fn toggle_task_list_item(
+    editor: &mut Editor,
+    _: &ToggleTaskListItem,
+    cx: &mut ViewContext<'_, Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let Some((_, _, buffer)) = snapshot.as_singleton() else {
+        return;
+    };
+
+    let mut edits = Vec::new();
+    for selection in editor.selections.all::<Point>(cx) {
+        for row in selection.start.row..=selection.end.row {
+            let line_start = Point::new(row, buffer.indent_size_for_line(row).len);
+            let Some(item) = list_item_at(buffer, buffer.point_to_offset(line_start)) else {
+                continue;
+            };
+            if buffer.offset_to_point(item.marker.start).row != row {
+                continue;
+            }
+            match item.checkbox {
+                Some((range, true)) => edits.push((range, "[ ]")),
+                Some((range, false)) => edits.push((range, "[x]")),
+                None => edits.push((item.content_start..item.content_start, "[ ] ")),
+            }
+        }
+    }
+    edits.sort_by_key(|(range, _)| range.start);
+    edits.dedup_by_key(|(range, _)| range.start);
+
+    editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+}
+
+/// Aligns the columns of the tables containing the cursors.
+pub fn format_markdown_table(
+    editor: &mut Editor,
+    _: &FormatMarkdownTable,
+    cx: &mut ViewContext<'_, Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let Some((_, _, buffer)) = snapshot.as_singleton() else {
+        return;
+    };
+
+    let mut table_rows = Vec::<Range<u32>>::new();
+    for selection in editor.selections.all::<usize>(cx) {
+        let Some(table) = table_at(buffer, selection.head()) else {
+            continue;
+        };
+        let start_row = buffer.offset_to_point(table.start).row;
+        let end = buffer.offset_to_point(table.end);
+        let end_row = if end.column == 0 && end.row > start_row {
+            end.row
+        } else {
+            end.row + 1
+        };
+        if !table_rows.contains(&(start_row..end_row)) {
+            table_rows.push(start_row..end_row);
+        }
+    }
+
+    let mut edits = Vec::new();
+    for rows in table_rows {
+        let line_ranges = rows
+            .map(|row| Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+            .collect::<Vec<_>>();
+        let lines = line_ranges
+            .iter()
+            .map(|range| buffer.text_for_range(range.clone()).collect::<String>())
+            .collect::<Vec<_>>();
+        for ((range, line), formatted_line) in line_ranges
+            .into_iter()
+            .zip(&lines)
+            .zip(format_table(&lines))
+        {
+            if *line != formatted_line {
+                edits.push((range, formatted_line));
+            }
+        }
+    }
+
+    editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+}
+
+/// The text to insert after the indentation of a new line, when inserting a newline at
+/// `offset` continues a list item, along with the edits renumbering the items that follow
+/// it in an ordered list.
+pub(crate) fn list_continuation(
+    snapshot: &MultiBufferSnapshot,
+    offset: usize,
+) -> Option<(Arc<str>, Vec<(Range<usize>, String)>)> {
+    let (buffer, buffer_offset) = snapshot.point_to_buffer_offset(offset)?;
+    let item = list_item_at(buffer, buffer_offset)?;
+    let row = buffer.offset_to_point(item.marker.start).row;
+    let line_end = buffer.point_to_offset(Point::new(row, buffer.line_len(row)));
+    // Items are only continued from their first line, and pressing enter on an empty item
+    // ends the list instead of adding another empty item.
+    if buffer_offset < item.content_start
+        || buffer_offset > line_end
+        || item.content_start >= line_end
+    {
+        return None;
+    }
+
+    let marker = buffer
+        .text_for_range(item.marker.clone())
+        .collect::<String>();
+    let mut renumber_edits = Vec::new();
+    let mut continuation = match parse_ordered_marker(&marker) {
+        Some((number, delimiter)) => {
+            // Offsets into the buffer can only be used as offsets into the multibuffer
+            // when it consists of that buffer alone.
+            if snapshot.as_singleton().is_some() {
+                for (marker, next_number) in item.following_markers.iter().zip(number + 2..) {
+                    let text = buffer.text_for_range(marker.clone()).collect::<String>();
+                    if let Some((old_number, _)) = parse_ordered_marker(&text) {
+                        if old_number != next_number {
+                            let number_end = marker.end - delimiter.len_utf8();
+                            renumber_edits
+                                .push((marker.start..number_end, next_number.to_string()));
+                        }
+                    }
+                }
+            }
+            format!("{}{} ", number + 1, delimiter)
+        }
+        None => format!("{} ", marker),
+    };
+    if item.checkbox.is_some() {
+        continuation.push_str("[ ] ");
+    }
+    Some((continuation.into(), renumber_edits))
+}
+
+/// The parts of a Markdown list item that editing commands care about, as offsets into
+/// its buffer.
+struct ListItem {
+    /// The item's marker, such as `-` or `3.`.
+    marker: Range<usize>,
+    /// The `[ ]` or `[x]` following the marker of a task list item, and whether it's checked.
+    checkbox: Option<(Range<usize>, bool)>,
+    /// Where the item's content starts, after its marker and checkbox.
+    content_start: usize,
+    /// The markers of the items that follow this one in the same list.
+    following_markers: Vec<Range<usize>>,
+}
+
+fn list_item_at(buffer: &BufferSnapshot, offset: usize) -> Option<ListItem> {
+    let layer = buffer.syntax_layer_at(offset)?;
+    if !is_markdown_language(layer.language) {
+        return None;
+    }
+    let mut node = layer.node().descendant_for_byte_range(offset, offset)?;
+    while node.kind() != "list_item" {
+        node = node.parent()?;
+    }
+
+    let marker_for_item = |item: tree_sitter::Node| {
+        let marker = item
+            .child(0)
+            .filter(|child| child.kind().starts_with("list_marker"))?;
+        let text = buffer
+            .text_for_range(marker.byte_range())
+            .collect::<String>();
+        let start = marker.start_byte() + text.len() - text.trim_start().len();
+        let end = marker.start_byte() + text.trim_end().len();
+        Some(start..end)
+    };
+
+    let marker = marker_for_item(node)?;
+    let skip_whitespace = |offset: usize| {
+        offset
+            + buffer
+                .chars_at(offset)
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .count()
+    };
+    let mut content_start = skip_whitespace(marker.end);
+    let checkbox_text = buffer.chars_at(content_start).take(3).collect::<String>();
+    let checkbox = match checkbox_text.as_str() {
+        "[ ]" => Some(false),
+        "[x]" | "[X]" => Some(true),
+        _ => None,
+    }
+    .map(|checked| {
+        let range = content_start..content_start + 3;
+        content_start = skip_whitespace(range.end);
+        (range, checked)
+    });
+
+    let mut following_markers = Vec::new();
+    let mut sibling = node.next_named_sibling();
+    while let Some(item) = sibling.filter(|sibling| sibling.kind() == "list_item") {
+        following_markers.extend(marker_for_item(item));
+        sibling = item.next_named_sibling();
+    }
+
+    Some(ListItem {
+        marker,
+        checkbox,
+        content_start,
+        following_markers,
+    })
+}
+
+fn table_at(buffer: &BufferSnapshot, offset: usize) -> Option<Range<usize>> {
+    let layer = buffer.syntax_layer_at(offset)?;
+    if !is_markdown_language(layer.language) {
+        return None;
+    }
+    let mut node = layer.node().descendant_for_byte_range(offset, offset)?;
+    while !matches!(node.kind(), "table" | "pipe_table") {
+        node = node.parent()?;
+    }
+    Some(node.byte_range())
+}
+
+/// Splits an ordered list marker such as `3.` or `3)` into its number and delimiter.
+fn parse_ordered_marker(marker: &str) -> Option<(u32, char)> {
+    let delimiter = marker.chars().last().filter(|c| *c == '.' || *c == ')')?;
+    let number = marker[..marker.len() - 1].parse().ok()?;
+    Some((number, delimiter))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColumnAlignment {
+    Unspecified,
+    Left,
+    Center,
+    Right,
+}
+
+/// Pads the cells of a table's rows so that its columns line up.
+fn format_table(lines: &[String]) -> Vec<String> {
+    let indent = lines
+        .first()
+        .map_or("", |line| &line[..line.len() - line.trim_start().len()]);
+    let rows = lines
+        .iter()
+        .map(|line| split_table_row(line))
+        .collect::<Vec<_>>();
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let delimiter_row_ix = rows.iter().position(|row| is_delimiter_row(row));
+    let alignments = (0..column_count)
+        .map(|column| {
+            let Some(cell) = delimiter_row_ix.and_then(|ix| rows[ix].get(column)) else {
+                return ColumnAlignment::Unspecified;
+            };
+            match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => ColumnAlignment::Center,
+                (true, false) => ColumnAlignment::Left,
+                (false, true) => ColumnAlignment::Right,
+                (false, false) => ColumnAlignment::Unspecified,
+            }
+        })
+        .collect::<Vec<_>>();
+    let widths = (0..column_count)
+        .map(|column| {
+            rows.iter()
+                .enumerate()
+                .filter(|(ix, _)| Some(*ix) != delimiter_row_ix)
+                .filter_map(|(_, row)| Some(row.get(column)?.chars().count()))
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect::<Vec<_>>();
+
+    rows.iter()
+        .enumerate()
+        .map(|(ix, row)| {
+            let cells = (0..column_count).map(|column| {
+                let width = widths[column];
+                let alignment = alignments[column];
+                if Some(ix) == delimiter_row_ix {
+                    return match alignment {
+                        ColumnAlignment::Unspecified => "-".repeat(width),
+                        ColumnAlignment::Left => format!(":{}", "-".repeat(width - 1)),
+                        ColumnAlignment::Center => format!(":{}:", "-".repeat(width - 2)),
+                        ColumnAlignment::Right => format!("{}:", "-".repeat(width - 1)),
+                    };
+                }
+                let cell = row.get(column).map_or("", String::as_str);
+                let padding = width - cell.chars().count();
+                match alignment {
+                    ColumnAlignment::Unspecified | ColumnAlignment::Left => {
+                        format!("{cell}{}", " ".repeat(padding))
+                    }
+                    ColumnAlignment::Center => format!(
+                        "{}{cell}{}",
+                        " ".repeat(padding / 2),
+                        " ".repeat(padding - padding / 2)
+                    ),
+                    ColumnAlignment::Right => format!("{}{cell}", " ".repeat(padding)),
+                }
+            });
+            format!("{indent}| {} |", cells.collect::<Vec<_>>().join(" | "))
+        })
+        .collect()
+}
+
+fn split_table_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                cell.extend(chars.next());
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+fn is_delimiter_row(row: &[String]) -> bool {
+    row.iter().all(|cell| {
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+pub(crate) fn is_markdown_language(language: &Language) -> bool {
+    language.name().as_ref() == "Markdown"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let lines =
+            ["| Name | Qty |", "|:-|--:|", "| apples | 3 |", "| pears|12"].map(String::from);
+        assert_eq!(
+            format_table(&lines),
+            [
+                "| Name   | Qty |",
+                "| :----- | --: |",
+                "| apples |   3 |",
+                "| pears  |  12 |",
+            ]
+        );
+
+        // Escaped pipes are kept within their cell.
+        let lines = ["a | b", "--|--", r"x \| y | z"].map(String::from);
+        assert_eq!(
+            format_table(&lines),
+            ["| a      | b   |", "| ------ | --- |", r"| x \| y | z   |"]
+        );
+    }
+}
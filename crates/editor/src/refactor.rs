@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use gpui::ViewContext;
+use language::{BufferSnapshot, Point};
+use project::CodeAction;
+use util::ResultExt;
+
+use crate::{Autoscroll, Editor, ExtractFunction, ExtractVariable};
+
+/// The name given to extracted code, which is selected afterwards so it can be renamed.
+const EXTRACTED_NAME: &str = "extracted";
+
+/// Syntax nodes whose children are statements, where a declaration can be inserted.
+const STATEMENT_CONTAINER_KINDS: &[&str] = &[
+    "block",
+    "compound_statement",
+    "module",
+    "program",
+    "source_file",
+    "statement_block",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Extraction {
+    Variable,
+    Function,
+}
+
+impl Extraction {
+    fn matches(self, action: &CodeAction) -> bool {
+        let is_extraction = action
+            .lsp_action
+            .kind
+            .as_ref()
+            .map_or(false, |kind| kind.as_str().starts_with("refactor.extract"));
+        let title = action.lsp_action.title.to_lowercase();
+        let keywords: &[&str] = match self {
+            Extraction::Variable => &["variable", "constant", "local"],
+            Extraction::Function => &["function", "method"],
+        };
+        is_extraction && keywords.iter().any(|keyword| title.contains(keyword))
+    }
+}
+
+pub fn extract_variable(editor: &mut Editor, _: &ExtractVariable, cx: &mut ViewContext<Editor>) {
+    extract(editor, Extraction::Variable, cx);
+}
+
+pub fn extract_function(editor: &mut Editor, _: &ExtractFunction, cx: &mut ViewContext<Editor>) {
+    extract(editor, Extraction::Function, cx);
+}
+
+/// Extracts the selection using the language server's refactoring for it if there is one.
+/// Otherwise, variables are extracted using the syntax tree, which also handles several
+/// selections at once.
+fn extract(editor: &mut Editor, extraction: Extraction, cx: &mut ViewContext<Editor>) {
+    let selection = editor.selections.newest_anchor().clone();
+    if selection.start == selection.end {
+        return;
+    }
+
+    let code_actions =
+        editor
+            .project
+            .clone()
+            .zip(editor.workspace())
+            .and_then(|(project, workspace)| {
+                if editor.selections.count() > 1 {
+                    return None;
+                }
+                let buffer = editor.buffer.read(cx);
+                let (start_buffer, start) = buffer.text_anchor_for_position(selection.start, cx)?;
+                let (end_buffer, end) = buffer.text_anchor_for_position(selection.end, cx)?;
+                if start_buffer != end_buffer {
+                    return None;
+                }
+                let code_actions = project.update(cx, |project, cx| {
+                    project.code_actions(&start_buffer, start..end, cx)
+                });
+                Some((project, workspace, start_buffer, code_actions))
+            });
+    let Some((project, workspace, buffer, code_actions)) = code_actions else {
+        if extraction == Extraction::Variable {
+            extract_variables_with_syntax(editor, cx);
+        }
+        return;
+    };
+
+    let workspace = workspace.downgrade();
+    cx.spawn(|editor, mut cx| async move {
+        let actions = code_actions.await.log_err().unwrap_or_default();
+        let Some(action) = actions
+            .into_iter()
+            .find(|action| extraction.matches(action))
+        else {
+            if extraction == Extraction::Variable {
+                editor.update(&mut cx, |editor, cx| {
+                    extract_variables_with_syntax(editor, cx)
+                })?;
+            }
+            return Ok(());
+        };
+
+        let title = action.lsp_action.title.clone();
+        let transaction = project
+            .update(&mut cx, |project, cx| {
+                project.apply_code_action(buffer, action, true, cx)
+            })?
+            .await?;
+        Editor::open_project_transaction(&editor, workspace, transaction, title, cx).await
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Moves each selected expression into a variable declared before the statement containing
+/// it, and selects the variable's name at both places.
+fn extract_variables_with_syntax(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let Some((_, _, buffer)) = snapshot.as_singleton() else {
+        return;
+    };
+
+    let mut edits = Vec::new();
+    let mut name_positions = Vec::new();
+    for selection in editor.selections.all::<usize>(cx) {
+        let Some(extraction) = variable_extraction(buffer, selection.range()) else {
+            continue;
+        };
+        edits.push((
+            extraction.insertion_offset..extraction.insertion_offset,
+            extraction.declaration,
+        ));
+        edits.push((extraction.value_range.clone(), EXTRACTED_NAME.to_string()));
+        name_positions.push((
+            buffer.anchor_before(extraction.insertion_offset),
+            extraction.name_offset_in_declaration,
+        ));
+        name_positions.push((buffer.anchor_before(extraction.value_range.start), 0));
+    }
+    if edits.is_empty() {
+        return;
+    }
+
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        let buffer = editor.buffer.read(cx).snapshot(cx);
+        let Some((_, _, buffer)) = buffer.as_singleton() else {
+            return;
+        };
+        let name_ranges = name_positions
+            .into_iter()
+            .map(|(anchor, delta)| {
+                let start = anchor.to_offset(buffer) + delta;
+                start..start + EXTRACTED_NAME.len()
+            })
+            .collect::<Vec<_>>();
+        editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+            selections.select_ranges(name_ranges)
+        });
+    });
+}
+
+struct VariableExtraction {
+    insertion_offset: usize,
+    declaration: String,
+    name_offset_in_declaration: usize,
+    value_range: Range<usize>,
+}
+
+fn variable_extraction(buffer: &BufferSnapshot, range: Range<usize>) -> Option<VariableExtraction> {
+    let text = buffer.text_for_range(range.clone()).collect::<String>();
+    let value = text.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let start = range.start + text.len() - text.trim_start().len();
+    let value_range = start..start + value.len();
+
+    // Only whole expressions can be extracted, and not whole statements.
+    let layer = buffer.syntax_layer_at(value_range.start)?;
+    let node = layer
+        .node()
+        .descendant_for_byte_range(value_range.start, value_range.end)?;
+    if node.byte_range() != value_range {
+        return None;
+    }
+    let mut statement = node;
+    loop {
+        let parent = statement.parent()?;
+        if STATEMENT_CONTAINER_KINDS.contains(&parent.kind()) {
+            break;
+        }
+        statement = parent;
+    }
+    if statement == node {
+        return None;
+    }
+
+    let (prefix, suffix) = match layer.language.name().as_ref() {
+        "Rust" => (format!("let {EXTRACTED_NAME} = "), ";"),
+        "JavaScript" | "TypeScript" | "TSX" => (format!("const {EXTRACTED_NAME} = "), ";"),
+        "Python" => (format!("{EXTRACTED_NAME} = "), ""),
+        "Go" => (format!("{EXTRACTED_NAME} := "), ""),
+        _ => return None,
+    };
+    let row = buffer.offset_to_point(statement.start_byte()).row;
+    let indent = buffer.indent_size_for_line(row);
+    let indent_text = indent.chars().collect::<String>();
+    let name_offset_in_declaration = indent_text.len() + prefix.find(EXTRACTED_NAME)?;
+    Some(VariableExtraction {
+        insertion_offset: buffer.point_to_offset(Point::new(row, 0)),
+        declaration: format!("{indent_text}{prefix}{value}{suffix}\n"),
+        name_offset_in_declaration,
+        value_range,
+    })
+}
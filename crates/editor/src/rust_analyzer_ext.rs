@@ -117,3 +117,48 @@ pub fn expand_macro_recursively(
 fn is_rust_language(language: &Language) -> bool {
     language.name().as_ref() == "Rust"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use futures::StreamExt;
+    use project::lsp_ext_command::{ExpandedMacro, LspExpandMacro};
+
+    #[gpui::test]
+    async fn test_expand_macro_recursively(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust_analyzer(Default::default(), cx).await;
+        cx.set_state(r#"fn main() { println!(ˇ"hi"); }"#);
+        cx.executor().run_until_parked();
+        cx.lsp.take_notifications();
+
+        let mut requests = cx.handle_request::<LspExpandMacro, _, _>(|url, params, _| async move {
+            assert_eq!(params.text_document.uri, url);
+            assert_eq!(params.position, lsp::Position::new(0, 21));
+            Ok(Some(ExpandedMacro {
+                name: "println".into(),
+                expansion: "{ $crate::io::_print(format_args!(\"hi\\n\")); }".into(),
+            }))
+        });
+        cx.update_editor(|editor, cx| {
+            expand_macro_recursively(editor, &ExpandMacroRecursively, cx)
+        });
+        requests.next().await.unwrap();
+        cx.executor().run_until_parked();
+
+        let (title, expansion) = cx.update_workspace(|workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let buffer = editor.read(cx).buffer().read(cx);
+            (buffer.title(cx).to_string(), buffer.snapshot(cx).text())
+        });
+        assert_eq!(title, "println");
+        assert_eq!(
+            expansion,
+            "{ $crate::io::_print(format_args!(\"hi\\n\")); }"
+        );
+        // The expansion isn't a file, so the language server isn't told about it.
+        assert_eq!(cx.lsp.take_notifications(), []);
+    }
+}
@@ -378,6 +378,7 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        crate::word_highlights::refresh_word_highlights(self, cx);
     }
 
     pub fn scroll_position(&self, cx: &mut ViewContext<Self>) -> gpui::Point<f32> {
@@ -0,0 +1,301 @@
+use std::ops::Range;
+
+use gpui::ViewContext;
+use multi_buffer::MultiBufferSnapshot;
+
+use crate::{Autoscroll, ChangeSurroundingPair, DeleteSurroundingPair, Editor, SurroundSelections};
+
+/// Pairs that can be used even where the language doesn't define them as brackets.
+const DEFAULT_PAIRS: &[(&str, &str)] = &[("(", ")"), ("[", "]"), ("{", "}"), ("<", ">")];
+
+/// A surround action that was run without a pair, such as from the command palette, and
+/// takes it from the next character typed instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PendingSurround {
+    Surround,
+    Change,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Pair {
+    open: String,
+    close: String,
+}
+
+/// Surrounds each selection with the pair that `open` starts, or the word under each cursor
+/// when nothing is selected. Without `open`, the pair is the next character typed.
+pub fn surround_selections(
+    editor: &mut Editor,
+    action: &SurroundSelections,
+    cx: &mut ViewContext<Editor>,
+) {
+    if action.open.is_empty() {
+        editor.pending_surround = Some(PendingSurround::Surround);
+        return;
+    }
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut edits = Vec::new();
+    let mut selection_positions = Vec::new();
+    let mut last_surrounded_end = None;
+    for selection in editor.selections.all::<usize>(cx) {
+        let range = if selection.is_empty() {
+            snapshot.surrounding_word(selection.start).0
+        } else {
+            selection.range()
+        };
+        // Cursors within the same word only surround it once.
+        if last_surrounded_end.map_or(false, |end| range.start < end) {
+            continue;
+        }
+        last_surrounded_end = Some(range.end);
+
+        let pair = pair_for(&action.open, &snapshot, range.start);
+        // Text inserted at an anchor's position ends up after it, which only moves positions
+        // after the start of the range past the opening half of the pair.
+        let position = |offset: usize| {
+            let delta = if offset == range.start {
+                pair.open.len()
+            } else {
+                0
+            };
+            (snapshot.anchor_before(offset), delta)
+        };
+        selection_positions.push((
+            position(selection.start),
+            position(selection.end),
+            selection.reversed,
+        ));
+        edits.push((range.start..range.start, pair.open));
+        edits.push((range.end..range.end, pair.close));
+    }
+    if edits.is_empty() {
+        return;
+    }
+
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        let snapshot = editor.buffer.read(cx).snapshot(cx);
+        let ranges = selection_positions
+            .into_iter()
+            .map(|((start, start_delta), (end, end_delta), reversed)| {
+                let start = start.to_offset(&snapshot) + start_delta;
+                let end = end.to_offset(&snapshot) + end_delta;
+                if reversed {
+                    end..start
+                } else {
+                    start..end
+                }
+            })
+            .collect::<Vec<_>>();
+        editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+            selections.select_ranges(ranges)
+        });
+    });
+}
+
+/// Replaces the innermost bracket pair or tag enclosing each selection with the pair that
+/// `open` starts. Without `open`, the pair is the next character typed.
+pub fn change_surrounding_pair(
+    editor: &mut Editor,
+    action: &ChangeSurroundingPair,
+    cx: &mut ViewContext<Editor>,
+) {
+    if action.open.is_empty() {
+        editor.pending_surround = Some(PendingSurround::Change);
+        return;
+    }
+    replace_surrounding_pairs(editor, Some(&action.open), cx);
+}
+
+/// Runs the surround action that was waiting for a pair with the typed `text`.
+pub(crate) fn complete_pending_surround(
+    editor: &mut Editor,
+    pending: PendingSurround,
+    text: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    let open = text.to_string();
+    match pending {
+        PendingSurround::Surround => surround_selections(editor, &SurroundSelections { open }, cx),
+        PendingSurround::Change => {
+            change_surrounding_pair(editor, &ChangeSurroundingPair { open }, cx)
+        }
+    }
+}
+
+/// Removes the innermost bracket pair or tag enclosing each selection.
+pub fn delete_surrounding_pair(
+    editor: &mut Editor,
+    _: &DeleteSurroundingPair,
+    cx: &mut ViewContext<Editor>,
+) {
+    replace_surrounding_pairs(editor, None, cx);
+}
+
+fn replace_surrounding_pairs(
+    editor: &mut Editor,
+    new_open: Option<&str>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let selections = editor.selections.all::<usize>(cx);
+    let mut edits = Vec::new();
+    for selection in &selections {
+        let Some((open, close)) = enclosing_pair(&snapshot, selection.range()) else {
+            continue;
+        };
+        let pair = new_open.map(|new_open| pair_for(new_open, &snapshot, open.start));
+        let (new_open, new_close) = pair.map_or((String::new(), String::new()), |pair| {
+            (pair.open, pair.close)
+        });
+        edits.push((open, new_open));
+        edits.push((close, new_close));
+    }
+    if edits.is_empty() {
+        return;
+    }
+    // Selections inside the same pair only replace it once.
+    edits.sort_by_key(|(range, _)| range.start);
+    edits.dedup_by(|(a, _), (b, _)| a == b);
+    if edits
+        .windows(2)
+        .any(|edits| edits[0].0.end > edits[1].0.start)
+    {
+        return;
+    }
+
+    let edit_lengths = edits
+        .iter()
+        .map(|(range, text)| (range.clone(), text.len()))
+        .collect::<Vec<_>>();
+    let ranges = selections
+        .iter()
+        .map(|selection| {
+            let start = map_offset(selection.start, &edit_lengths);
+            let end = map_offset(selection.end, &edit_lengths);
+            if selection.reversed {
+                end..start
+            } else {
+                start..end
+            }
+        })
+        .collect::<Vec<_>>();
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+            selections.select_ranges(ranges)
+        });
+    });
+}
+
+/// Returns the ranges of the opening and closing halves of the innermost bracket pair or
+/// markup tag containing the given range.
+fn enclosing_pair(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<usize>,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let brackets = snapshot.innermost_enclosing_bracket_ranges(range.clone(), None);
+    let tags = snapshot.innermost_enclosing_tag_ranges(range);
+    match (brackets, tags) {
+        (Some(brackets), Some(tags)) => {
+            if brackets.0.start >= tags.0.start {
+                Some(brackets)
+            } else {
+                Some(tags)
+            }
+        }
+        (brackets, tags) => brackets.or(tags),
+    }
+}
+
+/// Resolves the pair that `open` starts: a tag such as `<div class="a">`, one of the
+/// language's bracket pairs (which can also be given by its closing half), or otherwise
+/// `open` itself on both sides.
+fn pair_for(open: &str, snapshot: &MultiBufferSnapshot, offset: usize) -> Pair {
+    if let Some(name) = tag_name(open) {
+        return Pair {
+            open: open.to_string(),
+            close: format!("</{name}>"),
+        };
+    }
+
+    let language_pairs = snapshot
+        .language_scope_at(offset)
+        .map(|scope| {
+            scope
+                .brackets()
+                .map(|(pair, _)| (pair.start.clone(), pair.end.clone()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let default_pairs = DEFAULT_PAIRS
+        .iter()
+        .map(|(start, end)| (start.to_string(), end.to_string()));
+    language_pairs
+        .into_iter()
+        .chain(default_pairs)
+        .find(|(start, end)| start == open || end == open)
+        .map_or_else(
+            || Pair {
+                open: open.to_string(),
+                close: open.to_string(),
+            },
+            |(open, close)| Pair { open, close },
+        )
+}
+
+/// Returns the element name of an opening tag like `<div class="a">`.
+fn tag_name(open: &str) -> Option<&str> {
+    let tag = open.strip_prefix('<')?.strip_suffix('>')?;
+    let name = tag.split(char::is_whitespace).next()?;
+    let is_name = !name.is_empty()
+        && name.starts_with(char::is_alphabetic)
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    is_name.then_some(name)
+}
+
+/// Maps an offset from before the given edits, which are sorted and disjoint, to after
+/// them. Offsets within a replaced range are clamped to its replacement.
+fn map_offset(offset: usize, edits: &[(Range<usize>, usize)]) -> usize {
+    let mut delta = 0isize;
+    for (range, new_len) in edits {
+        if offset <= range.start {
+            break;
+        }
+        if offset < range.end {
+            let start = (range.start as isize + delta) as usize;
+            return start + (offset - range.start).min(*new_len);
+        }
+        delta += *new_len as isize - range.len() as isize;
+    }
+    (offset as isize + delta) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_name() {
+        assert_eq!(tag_name("<div>"), Some("div"));
+        assert_eq!(tag_name("<my-element class=\"a\">"), Some("my-element"));
+        assert_eq!(tag_name("<>"), None);
+        assert_eq!(tag_name("<"), None);
+        assert_eq!(tag_name("< div>"), None);
+        assert_eq!(tag_name("(div)"), None);
+    }
+
+    #[test]
+    fn test_map_offset() {
+        let edits = [(2..3, 0), (6..7, 2)];
+        assert_eq!(map_offset(0, &edits), 0);
+        assert_eq!(map_offset(2, &edits), 2);
+        assert_eq!(map_offset(3, &edits), 2);
+        assert_eq!(map_offset(5, &edits), 4);
+        assert_eq!(map_offset(7, &edits), 7);
+        assert_eq!(map_offset(9, &edits), 9);
+    }
+}
@@ -32,9 +32,27 @@ pub struct EditorLspTestContext {
 
 impl EditorLspTestContext {
     pub async fn new(
-        mut language: Language,
+        language: Language,
         capabilities: lsp::ServerCapabilities,
         cx: &mut gpui::TestAppContext,
+    ) -> EditorLspTestContext {
+        Self::new_with_adapter(
+            language,
+            FakeLspAdapter {
+                capabilities,
+                ..Default::default()
+            },
+            cx,
+        )
+        .await
+    }
+
+    /// Like [`Self::new`], but with a fake language server that is configured by the
+    /// given adapter, such as one named after a particular server.
+    pub async fn new_with_adapter(
+        mut language: Language,
+        adapter: FakeLspAdapter,
+        cx: &mut gpui::TestAppContext,
     ) -> EditorLspTestContext {
         let app_state = cx.update(AppState::test);
 
@@ -53,12 +71,7 @@ impl EditorLspTestContext {
                 .expect("language must have a path suffix for EditorLspTestContext")
         );
 
-        let mut fake_servers = language
-            .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
-                capabilities,
-                ..Default::default()
-            }))
-            .await;
+        let mut fake_servers = language.set_fake_lsp_adapter(Arc::new(adapter)).await;
 
         let project = Project::test(app_state.fs.clone(), [], cx).await;
 
@@ -114,7 +127,29 @@ impl EditorLspTestContext {
         capabilities: lsp::ServerCapabilities,
         cx: &mut gpui::TestAppContext,
     ) -> EditorLspTestContext {
-        let language = Language::new(
+        Self::new(Self::rust_language(), capabilities, cx).await
+    }
+
+    /// A Rust editor backed by a fake language server named `rust-analyzer`, so that
+    /// rust-analyzer's extensions to the protocol are used.
+    pub async fn new_rust_analyzer(
+        capabilities: lsp::ServerCapabilities,
+        cx: &mut gpui::TestAppContext,
+    ) -> EditorLspTestContext {
+        Self::new_with_adapter(
+            Self::rust_language(),
+            FakeLspAdapter {
+                name: "rust-analyzer",
+                capabilities,
+                ..Default::default()
+            },
+            cx,
+        )
+        .await
+    }
+
+    fn rust_language() -> Language {
+        Language::new(
             LanguageConfig {
                 name: "Rust".into(),
                 matcher: LanguageMatcher {
@@ -150,9 +185,7 @@ impl EditorLspTestContext {
                 (closure_parameters "|" @open "|" @close)"#})),
             ..Default::default()
         })
-        .expect("Could not parse queries");
-
-        Self::new(language, capabilities, cx).await
+        .expect("Could not parse queries")
     }
 
     pub async fn new_typescript(
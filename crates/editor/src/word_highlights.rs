@@ -0,0 +1,180 @@
+use std::{ops::Range, time::Duration};
+
+use gpui::ViewContext;
+use language::{char_kind, CharKind, Point};
+use multi_buffer::MultiBufferSnapshot;
+use settings::Settings;
+
+use crate::{
+    power_saving_debounce, Autoscroll, Bias, DocumentHighlightRead, DocumentHighlightWrite, Editor,
+    EditorMode, EditorSettings, SelectAllOccurrences,
+};
+
+/// How long the cursor has to rest on a word before its other occurrences are highlighted.
+pub const WORD_HIGHLIGHTS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
+
+pub enum WordHighlight {}
+
+/// Highlights the other occurrences of the word under the newest cursor within the visible
+/// part of the buffer, once the cursor rests. Document highlights from a language server
+/// take precedence over them.
+pub(crate) fn refresh_word_highlights(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(word) = word_to_highlight(editor, cx) else {
+        editor.word_highlights_task = None;
+        editor.clear_background_highlights::<WordHighlight>(cx);
+        return;
+    };
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let visible_range = visible_range(editor, &snapshot);
+    let debounce = power_saving_debounce(WORD_HIGHLIGHTS_DEBOUNCE_TIMEOUT, cx);
+    editor.word_highlights_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(debounce).await;
+        let occurrences = cx
+            .background_executor()
+            .spawn(async move {
+                word_occurrences(&snapshot, &word, visible_range)
+                    .into_iter()
+                    .map(|range| {
+                        snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                if editor.has_background_highlights::<DocumentHighlightRead>()
+                    || editor.has_background_highlights::<DocumentHighlightWrite>()
+                {
+                    editor.clear_background_highlights::<WordHighlight>(cx);
+                    return;
+                }
+                editor.highlight_background::<WordHighlight>(
+                    occurrences,
+                    |theme| theme.editor_word_highlight_background,
+                    cx,
+                );
+            })
+            .ok();
+    }));
+}
+
+/// Selects every occurrence of the word under the newest cursor, so they can be edited at
+/// once.
+pub fn select_all_occurrences(
+    editor: &mut Editor,
+    _: &SelectAllOccurrences,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let head = editor.selections.newest::<usize>(cx).head();
+    let (word_range, kind) = snapshot.surrounding_word(head);
+    if kind != Some(CharKind::Word) || word_range.is_empty() {
+        return;
+    }
+    let word = snapshot.text_for_range(word_range).collect::<String>();
+    let occurrences = word_occurrences(&snapshot, &word, 0..snapshot.len());
+    if occurrences.is_empty() {
+        return;
+    }
+
+    editor.push_to_selection_history();
+    editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+        selections.select_ranges(occurrences)
+    });
+}
+
+/// Returns the word under the newest cursor, if there is a single cursor resting on an
+/// identifier and word highlights are enabled.
+fn word_to_highlight(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Option<String> {
+    if !EditorSettings::get_global(cx).highlight_word_occurrences
+        || editor.mode != EditorMode::Full
+        || editor.pending_rename.is_some()
+    {
+        return None;
+    }
+    let selection = editor.selections.newest::<usize>(cx);
+    if !selection.is_empty() {
+        return None;
+    }
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let (word_range, kind) = snapshot.surrounding_word(selection.head());
+    if kind != Some(CharKind::Word) || word_range.is_empty() {
+        return None;
+    }
+    Some(snapshot.text_for_range(word_range).collect())
+}
+
+/// The rows on screen, along with a screen's worth of rows above and below them so that
+/// small scrolls don't reveal unhighlighted occurrences. Before the editor is laid out,
+/// this is the whole buffer.
+fn visible_range(editor: &Editor, snapshot: &MultiBufferSnapshot) -> Range<usize> {
+    let Some(visible_rows) = editor.visible_line_count() else {
+        return 0..snapshot.len();
+    };
+    let visible_rows = visible_rows.ceil() as u32;
+    let top = editor.scroll_manager.anchor().anchor.to_point(snapshot).row;
+    let start = Point::new(top.saturating_sub(visible_rows), 0);
+    let end = snapshot.clip_point(Point::new(top + 2 * visible_rows, 0), Bias::Left);
+    let end = Point::new(end.row, snapshot.line_len(end.row));
+    snapshot.point_to_offset(start)..snapshot.point_to_offset(end)
+}
+
+/// Finds the occurrences of `word` in `range` that are whole words. The range should start
+/// and end at line boundaries.
+fn word_occurrences(
+    snapshot: &MultiBufferSnapshot,
+    word: &str,
+    range: Range<usize>,
+) -> Vec<Range<usize>> {
+    let scope = snapshot.language_scope_at(range.start);
+    let is_word_char = |c: char| char_kind(&scope, c) == CharKind::Word;
+    let text = snapshot.text_for_range(range.clone()).collect::<String>();
+    text.match_indices(word)
+        .filter(|(ix, _)| {
+            let before = text[..*ix].chars().next_back();
+            let after = text[ix + word.len()..].chars().next();
+            !before.map_or(false, is_word_char) && !after.map_or(false, is_word_char)
+        })
+        .map(|(ix, _)| range.start + ix..range.start + ix + word.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_test_context::EditorTestContext};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_word_highlights(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+
+        // Only whole words are highlighted, once the cursor rests.
+        cx.set_state("let foo = foo_bar + ˇfoo;\nfoo(afoo);");
+        cx.assert_editor_background_highlights::<WordHighlight>(
+            "let foo = foo_bar + foo;\nfoo(afoo);",
+        );
+        cx.executor()
+            .advance_clock(WORD_HIGHLIGHTS_DEBOUNCE_TIMEOUT);
+        cx.executor().run_until_parked();
+        cx.assert_editor_background_highlights::<WordHighlight>(
+            "let «foo» = foo_bar + «foo»;\n«foo»(afoo);",
+        );
+
+        // Moving off of a word clears the highlights right away.
+        cx.set_state("let foo = foo_bar ˇ+ foo;\nfoo(afoo);");
+        cx.assert_editor_background_highlights::<WordHighlight>(
+            "let foo = foo_bar + foo;\nfoo(afoo);",
+        );
+
+        cx.set_state("let foo = foo_bar + foo;\nfoo(afˇoo);");
+        cx.update_editor(|editor, cx| select_all_occurrences(editor, &SelectAllOccurrences, cx));
+        cx.assert_editor_state("let foo = foo_bar + foo;\nfoo(«afooˇ»);");
+
+        cx.set_state("let foo = foo_bar + fˇoo;\nfoo(afoo);");
+        cx.update_editor(|editor, cx| select_all_occurrences(editor, &SelectAllOccurrences, cx));
+        cx.assert_editor_state("let «fooˇ» = foo_bar + «fooˇ»;\n«fooˇ»(afoo);");
+    }
+}
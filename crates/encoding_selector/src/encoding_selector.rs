@@ -0,0 +1,258 @@
+mod active_buffer_encoding;
+
+pub use active_buffer_encoding::ActiveBufferEncoding;
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    ParentElement, PromptLevel, Render, Styled, View, ViewContext, VisualContext, WeakView,
+};
+use language::{Buffer, Encoding};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(encoding_selector, [ReopenWithEncoding, SaveWithEncoding]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(EncodingSelector::register).detach();
+}
+
+/// What to do with the encoding chosen in the selector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Decode the file on disk again, using the chosen encoding.
+    Reopen,
+    /// Keep the buffer's text, and write it in the chosen encoding when it's next saved.
+    Save,
+}
+
+pub struct EncodingSelector {
+    picker: View<Picker<EncodingSelectorDelegate>>,
+}
+
+impl EncodingSelector {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(move |workspace, _: &ReopenWithEncoding, cx| {
+            Self::toggle(workspace, Mode::Reopen, cx);
+        });
+        workspace.register_action(move |workspace, _: &SaveWithEncoding, cx| {
+            Self::toggle(workspace, Mode::Save, cx);
+        });
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        mode: Mode,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+
+        // The host decodes and encodes shared files, so guests can't change their encoding.
+        if buffer
+            .read(cx)
+            .file()
+            .map_or(false, |file| !file.is_local())
+        {
+            return None;
+        }
+
+        workspace.toggle_modal(cx, move |cx| EncodingSelector::new(buffer, mode, cx));
+        Some(())
+    }
+
+    fn new(buffer: Model<Buffer>, mode: Mode, cx: &mut ViewContext<Self>) -> Self {
+        let delegate = EncodingSelectorDelegate::new(cx.view().downgrade(), buffer, mode);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for EncodingSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for EncodingSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EncodingSelector {}
+impl ModalView for EncodingSelector {}
+
+pub struct EncodingSelectorDelegate {
+    encoding_selector: WeakView<EncodingSelector>,
+    buffer: Model<Buffer>,
+    mode: Mode,
+    encodings: Vec<Encoding>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl EncodingSelectorDelegate {
+    fn new(
+        encoding_selector: WeakView<EncodingSelector>,
+        buffer: Model<Buffer>,
+        mode: Mode,
+    ) -> Self {
+        let encodings = Encoding::all().to_vec();
+        let candidates = encodings
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, encoding)| {
+                StringMatchCandidate::new(candidate_id, encoding.name().to_string())
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            encoding_selector,
+            buffer,
+            mode,
+            encodings,
+            candidates,
+            matches: vec![],
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for EncodingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.mode {
+            Mode::Reopen => "Reopen with encoding...".into(),
+            Mode::Save => "Save with encoding...".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let encoding = self.encodings[mat.candidate_id];
+            match self.mode {
+                // Reopening replaces the buffer's text with the file's, so unsaved changes
+                // are only discarded once the user agrees to it.
+                Mode::Reopen if self.buffer.read(cx).is_dirty() => {
+                    let answer = cx.prompt(
+                        PromptLevel::Warning,
+                        &format!("Reopen this file with the {} encoding?", encoding.name()),
+                        Some("Your unsaved changes will be discarded."),
+                        &["Reopen", "Cancel"],
+                    );
+                    let buffer = self.buffer.clone();
+                    cx.spawn(|_, mut cx| async move {
+                        if answer.await == Ok(0) {
+                            buffer
+                                .update(&mut cx, |buffer, cx| {
+                                    buffer.reload_with_encoding(encoding, cx);
+                                })
+                                .log_err();
+                        }
+                    })
+                    .detach();
+                }
+                Mode::Reopen => {
+                    self.buffer.update(cx, |buffer, cx| {
+                        buffer.reload_with_encoding(encoding, cx);
+                    });
+                }
+                Mode::Save => self
+                    .buffer
+                    .update(cx, |buffer, cx| buffer.set_encoding(encoding, cx)),
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.encoding_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let mut label = mat.string.clone();
+        if self.buffer.read(cx).encoding() == self.encodings[mat.candidate_id] {
+            label.push_str(" (current)");
+        }
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(label, mat.positions.clone())),
+        )
+    }
+}
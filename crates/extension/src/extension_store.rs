@@ -22,7 +22,7 @@ use std::{
 use theme::{ThemeRegistry, ThemeSettings};
 use util::http::{AsyncBody, HttpClientWithUrl};
 use util::TryFutureExt;
-use util::{http::HttpClient, paths::EXTENSIONS_DIR, ResultExt};
+use util::{asset_cache::AssetCache, http::HttpClient, paths::EXTENSIONS_DIR, ResultExt};
 
 #[cfg(test)]
 mod extension_store_test;
@@ -284,11 +284,11 @@ impl ExtensionStore {
         self.extensions_being_installed.insert(extension_id.clone());
 
         cx.spawn(move |this, mut cx| async move {
-            let mut response = http_client
-                .get(&url, Default::default(), true)
+            let archive = AssetCache::global()
+                .fetch_bytes(&url, http_client.as_ref())
                 .await
                 .map_err(|err| anyhow!("error downloading extension: {}", err))?;
-            let decompressed_bytes = GzipDecoder::new(BufReader::new(response.body_mut()));
+            let decompressed_bytes = GzipDecoder::new(BufReader::new(archive.as_slice()));
             let archive = Archive::new(decompressed_bytes);
             archive
                 .unpack(extensions_dir.join(extension_id.as_ref()))
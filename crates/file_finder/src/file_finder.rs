@@ -1,8 +1,10 @@
 #[cfg(test)]
 mod file_finder_tests;
+mod file_index;
 
 use collections::{HashMap, HashSet};
 use editor::{scroll::Autoscroll, Bias, Editor};
+use file_index::{FileCandidateSet, FileIndex};
 use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
 use gpui::{
     actions, rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
@@ -37,10 +39,12 @@ pub fn init(cx: &mut AppContext) {
 }
 
 impl FileFinder {
-    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-        workspace.register_action(|workspace, _: &Toggle, cx| {
+    fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        let file_index = cx.new_model(|cx| FileIndex::new(&project, cx));
+        workspace.register_action(move |workspace, _: &Toggle, cx| {
             let Some(file_finder) = workspace.active_modal::<Self>(cx) else {
-                Self::open(workspace, cx);
+                Self::open(workspace, file_index.clone(), cx);
                 return;
             };
 
@@ -52,7 +56,11 @@ impl FileFinder {
         });
     }
 
-    fn open(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    fn open(
+        workspace: &mut Workspace,
+        file_index: Model<FileIndex>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
         let project = workspace.project().read(cx);
 
         let currently_opened_path = workspace
@@ -82,6 +90,7 @@ impl FileFinder {
                 cx.view().downgrade(),
                 weak_workspace,
                 project,
+                file_index,
                 currently_opened_path,
                 history_items,
                 cx,
@@ -116,6 +125,7 @@ pub struct FileFinderDelegate {
     file_finder: WeakView<FileFinder>,
     workspace: WeakView<Workspace>,
     project: Model<Project>,
+    file_index: Model<FileIndex>,
     search_count: usize,
     latest_search_id: usize,
     latest_search_did_cancel: bool,
@@ -361,6 +371,7 @@ impl FileFinderDelegate {
         file_finder: WeakView<FileFinder>,
         workspace: WeakView<Workspace>,
         project: Model<Project>,
+        file_index: Model<FileIndex>,
         currently_opened_path: Option<FoundPath>,
         history_items: Vec<FoundPath>,
         cx: &mut ViewContext<FileFinder>,
@@ -377,6 +388,7 @@ impl FileFinderDelegate {
             file_finder,
             workspace,
             project,
+            file_index,
             search_count: 0,
             latest_search_id: 0,
             latest_search_did_cancel: false,
@@ -407,14 +419,20 @@ impl FileFinderDelegate {
         let candidate_sets = worktrees
             .into_iter()
             .map(|worktree| {
-                let worktree = worktree.read(cx);
-                PathMatchCandidateSet {
-                    snapshot: worktree.snapshot(),
-                    include_ignored: worktree
-                        .root_entry()
-                        .map_or(false, |entry| entry.is_ignored),
-                    include_root_name,
-                }
+                let worktree_set = {
+                    let worktree = worktree.read(cx);
+                    PathMatchCandidateSet {
+                        snapshot: worktree.snapshot(),
+                        include_ignored: worktree
+                            .root_entry()
+                            .map_or(false, |entry| entry.is_ignored),
+                        include_root_name,
+                    }
+                };
+                let index = self.file_index.update(cx, |file_index, cx| {
+                    file_index.index_for_worktree(&worktree, worktree_set.include_ignored, cx)
+                });
+                (worktree_set, index)
             })
             .collect::<Vec<_>>();
 
@@ -423,6 +441,56 @@ impl FileFinderDelegate {
         self.cancel_flag = Arc::new(AtomicBool::new(false));
         let cancel_flag = self.cancel_flag.clone();
         cx.spawn(|picker, mut cx| async move {
+            let candidate_sets = cx
+                .background_executor()
+                .spawn({
+                    let query = query.path_like.path_query().to_string();
+                    async move {
+                        candidate_sets
+                            .into_iter()
+                            .map(|(worktree_set, index)| {
+                                FileCandidateSet::new(worktree_set, index, &query)
+                            })
+                            .collect::<Vec<_>>()
+                    }
+                })
+                .await;
+
+            // Paths that contain the query verbatim are matched first, so that they can be
+            // shown while every path is scanned for the final results.
+            if candidate_sets.iter().any(FileCandidateSet::is_prefiltered) {
+                let matches = fuzzy::match_path_sets(
+                    candidate_sets.as_slice(),
+                    query.path_like.path_query(),
+                    relative_to.clone(),
+                    false,
+                    100,
+                    &cancel_flag,
+                    cx.background_executor().clone(),
+                )
+                .await
+                .into_iter()
+                .map(ProjectPanelOrdMatch);
+                if cancel_flag.load(atomic::Ordering::Relaxed) {
+                    return;
+                }
+                picker
+                    .update(&mut cx, |picker, cx| {
+                        picker.delegate.set_search_matches(
+                            search_id,
+                            false,
+                            query.clone(),
+                            matches,
+                            cx,
+                        )
+                    })
+                    .log_err();
+            }
+
+            let candidate_sets = candidate_sets
+                .into_iter()
+                .map(FileCandidateSet::into_full_scan)
+                .collect::<Vec<_>>();
             let matches = fuzzy::match_path_sets(
                 candidate_sets.as_slice(),
                 query.path_like.path_query(),
@@ -404,6 +404,61 @@ async fn test_matching_cancellation(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_indexed_worktree_ranking(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/dir",
+            json!({
+                "fi": {
+                    "fi.rs": "",
+                },
+                "src": {
+                    "wififi.rs": "",
+                    "file_finder.rs": "",
+                    "fifo.rs": "",
+                    "lib.rs": "",
+                },
+                "xfifix.txt": "",
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/dir".as_ref()], cx).await;
+    let (picker, _, cx) = build_find_picker(project, cx);
+
+    // The first search starts building the worktree's index and scans every path.
+    let query = test_path_like("fifi");
+    picker
+        .update(cx, |picker, cx| {
+            picker.delegate.spawn_search(query.clone(), cx)
+        })
+        .await;
+    let unindexed_matches = picker.update(cx, |picker, _| collect_search_matches(picker));
+    assert_eq!(
+        unindexed_matches.search.first(),
+        Some(&PathBuf::from("fi/fi.rs"))
+    );
+
+    cx.run_until_parked();
+    picker.update(cx, |picker, cx| {
+        assert_eq!(picker.delegate.file_index.read(cx).ready_index_count(), 1);
+    });
+
+    // Paths that don't contain the query verbatim are still matched and ranked the same.
+    picker
+        .update(cx, |picker, cx| {
+            picker.delegate.spawn_search(query.clone(), cx)
+        })
+        .await;
+    picker.update(cx, |picker, _| {
+        assert_eq!(collect_search_matches(picker), unindexed_matches);
+    });
+}
+
 #[gpui::test]
 async fn test_ignored_root(cx: &mut TestAppContext) {
     let app_state = init_test(cx);
@@ -1456,7 +1511,7 @@ fn active_file_picker(
     })
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct SearchEntries {
     history: Vec<PathBuf>,
     search: Vec<PathBuf>,
@@ -0,0 +1,281 @@
+use collections::HashMap;
+use fuzzy::{PathIndex, PathMatchCandidate, PathMatchCandidateSet as _};
+use gpui::{Model, ModelContext, Subscription, Task};
+use project::{
+    EntryKind, PathMatchCandidateSet, PathMatchCandidateSetIter, Project, Snapshot,
+    UpdatedEntriesSet, Worktree, WorktreeId,
+};
+use std::{slice, sync::Arc};
+use util::ResultExt;
+
+/// Worktrees with fewer files than this are only matched by scanning every
+/// path, which is already fast.
+#[cfg(not(test))]
+const MIN_INDEXED_FILE_COUNT: usize = 10_000;
+#[cfg(test)]
+const MIN_INDEXED_FILE_COUNT: usize = 4;
+
+/// Keeps a [`PathIndex`] for each large worktree of a project, updated as the
+/// worktree's entries change.
+pub(crate) struct FileIndex {
+    worktrees: HashMap<WorktreeId, WorktreeIndex>,
+    _subscription: Subscription,
+}
+
+enum WorktreeIndex {
+    Building {
+        include_ignored: bool,
+        pending_changes: Vec<UpdatedEntriesSet>,
+        _task: Task<()>,
+    },
+    Ready {
+        include_ignored: bool,
+        index: Arc<PathIndex>,
+    },
+}
+
+impl FileIndex {
+    pub fn new(project: &Model<Project>, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            worktrees: HashMap::default(),
+            _subscription: cx.subscribe(project, Self::handle_project_event),
+        }
+    }
+
+    fn handle_project_event(
+        &mut self,
+        project: Model<Project>,
+        event: &project::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeRemoved(worktree_id) => {
+                self.worktrees.remove(worktree_id);
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let Some(worktree_index) = self.worktrees.get_mut(worktree_id) else {
+                    return;
+                };
+                match worktree_index {
+                    WorktreeIndex::Building {
+                        pending_changes, ..
+                    } => pending_changes.push(changes.clone()),
+                    WorktreeIndex::Ready {
+                        include_ignored,
+                        index,
+                    } => {
+                        let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx)
+                        else {
+                            return;
+                        };
+                        apply_changes(
+                            Arc::make_mut(index),
+                            worktree.read(cx).snapshot(),
+                            *include_ignored,
+                            [changes],
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the index for the worktree if it has been built, and starts
+    /// building it otherwise.
+    pub fn index_for_worktree(
+        &mut self,
+        worktree: &Model<Worktree>,
+        include_ignored: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<Arc<PathIndex>> {
+        let snapshot = worktree.read(cx).snapshot();
+        let worktree_id = snapshot.id();
+        let file_count = if include_ignored {
+            snapshot.file_count()
+        } else {
+            snapshot.visible_file_count()
+        };
+        if file_count < MIN_INDEXED_FILE_COUNT {
+            self.worktrees.remove(&worktree_id);
+            return None;
+        }
+
+        match self.worktrees.get(&worktree_id) {
+            Some(WorktreeIndex::Ready {
+                include_ignored: indexed_ignored,
+                index,
+            }) if *indexed_ignored == include_ignored => return Some(index.clone()),
+            Some(WorktreeIndex::Building {
+                include_ignored: indexed_ignored,
+                ..
+            }) if *indexed_ignored == include_ignored => return None,
+            _ => {}
+        }
+
+        let worktree = worktree.downgrade();
+        let build = cx.background_executor().spawn(async move {
+            let mut index = PathIndex::new();
+            for entry in snapshot.files(include_ignored, 0) {
+                if let EntryKind::File(char_bag) = entry.kind {
+                    index.insert(entry.path.clone(), char_bag);
+                }
+            }
+            index
+        });
+        let task = cx.spawn(|this, mut cx| async move {
+            let mut index = build.await;
+            this.update(&mut cx, |this, cx| {
+                let Some(WorktreeIndex::Building {
+                    pending_changes, ..
+                }) = this.worktrees.remove(&worktree_id)
+                else {
+                    return;
+                };
+                let Some(worktree) = worktree.upgrade() else {
+                    return;
+                };
+                apply_changes(
+                    &mut index,
+                    worktree.read(cx).snapshot(),
+                    include_ignored,
+                    &pending_changes,
+                );
+                this.worktrees.insert(
+                    worktree_id,
+                    WorktreeIndex::Ready {
+                        include_ignored,
+                        index: Arc::new(index),
+                    },
+                );
+            })
+            .log_err();
+        });
+        self.worktrees.insert(
+            worktree_id,
+            WorktreeIndex::Building {
+                include_ignored,
+                pending_changes: Vec::new(),
+                _task: task,
+            },
+        );
+        None
+    }
+
+    #[cfg(test)]
+    pub fn ready_index_count(&self) -> usize {
+        self.worktrees
+            .values()
+            .filter(|index| matches!(index, WorktreeIndex::Ready { .. }))
+            .count()
+    }
+}
+
+fn apply_changes<'a>(
+    index: &mut PathIndex,
+    snapshot: Snapshot,
+    include_ignored: bool,
+    changes: impl IntoIterator<Item = &'a UpdatedEntriesSet>,
+) {
+    for (path, _, _) in changes.into_iter().flat_map(|changes| changes.iter()) {
+        match snapshot.entry_for_path(path) {
+            Some(entry) if include_ignored || !entry.is_ignored => {
+                if let EntryKind::File(char_bag) = entry.kind {
+                    index.insert(entry.path.clone(), char_bag);
+                } else {
+                    index.remove(path);
+                }
+            }
+            _ => index.remove(path),
+        }
+    }
+}
+
+/// The paths of a worktree to match a query against: the paths that the
+/// worktree's index selects for the query, or every path when there is no
+/// index or the query is too short to narrow down.
+///
+/// The selected paths are only the ones that contain the query verbatim, so
+/// matching them gives a quick first set of results, which has to be followed
+/// by a scan of every path to find the rest.
+pub(crate) struct FileCandidateSet {
+    worktree_set: PathMatchCandidateSet,
+    prefiltered: Option<(Arc<PathIndex>, Vec<usize>)>,
+}
+
+impl FileCandidateSet {
+    pub fn new(
+        worktree_set: PathMatchCandidateSet,
+        index: Option<Arc<PathIndex>>,
+        query: &str,
+    ) -> Self {
+        let prefiltered = index.and_then(|index| {
+            let ids = index.prefilter(query)?;
+            (!ids.is_empty()).then_some((index, ids))
+        });
+        Self {
+            worktree_set,
+            prefiltered,
+        }
+    }
+
+    pub fn is_prefiltered(&self) -> bool {
+        self.prefiltered.is_some()
+    }
+
+    /// Returns the set of every path of the worktree.
+    pub fn into_full_scan(self) -> Self {
+        Self {
+            worktree_set: self.worktree_set,
+            prefiltered: None,
+        }
+    }
+}
+
+impl<'a> fuzzy::PathMatchCandidateSet<'a> for FileCandidateSet {
+    type Candidates = FileCandidates<'a>;
+
+    fn id(&self) -> usize {
+        self.worktree_set.id()
+    }
+
+    fn len(&self) -> usize {
+        match &self.prefiltered {
+            Some((_, ids)) => ids.len(),
+            None => self.worktree_set.len(),
+        }
+    }
+
+    fn prefix(&self) -> Arc<str> {
+        self.worktree_set.prefix()
+    }
+
+    fn candidates(&'a self, start: usize) -> Self::Candidates {
+        match &self.prefiltered {
+            Some((index, ids)) => FileCandidates::Prefiltered {
+                index,
+                ids: ids[start.min(ids.len())..].iter(),
+            },
+            None => FileCandidates::All(self.worktree_set.candidates(start)),
+        }
+    }
+}
+
+pub(crate) enum FileCandidates<'a> {
+    Prefiltered {
+        index: &'a PathIndex,
+        ids: slice::Iter<'a, usize>,
+    },
+    All(PathMatchCandidateSetIter<'a>),
+}
+
+impl<'a> Iterator for FileCandidates<'a> {
+    type Item = PathMatchCandidate<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            FileCandidates::Prefiltered { index, ids } => ids.find_map(|id| index.candidate(*id)),
+            FileCandidates::All(candidates) => candidates.next(),
+        }
+    }
+}
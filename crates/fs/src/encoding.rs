@@ -0,0 +1,358 @@
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
+
+/// The number of leading bytes inspected when detecting a file's encoding.
+const DETECTION_LEN: usize = 8000;
+
+/// The character encoding of a file on disk, along with whether the file
+/// starts with a byte order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::utf8()
+    }
+}
+
+impl Encoding {
+    pub fn utf8() -> Self {
+        Self::new(encoding_rs::UTF_8, false)
+    }
+
+    pub fn utf8_with_bom() -> Self {
+        Self::new(encoding_rs::UTF_8, true)
+    }
+
+    pub fn utf16le() -> Self {
+        Self::new(encoding_rs::UTF_16LE, true)
+    }
+
+    pub fn utf16be() -> Self {
+        Self::new(encoding_rs::UTF_16BE, true)
+    }
+
+    pub fn windows_1252() -> Self {
+        Self::new(encoding_rs::WINDOWS_1252, false)
+    }
+
+    pub fn shift_jis() -> Self {
+        Self::new(encoding_rs::SHIFT_JIS, false)
+    }
+
+    fn new(encoding: &'static encoding_rs::Encoding, bom: bool) -> Self {
+        Self { encoding, bom }
+    }
+
+    /// The encodings that files can be reopened or saved with.
+    pub fn all() -> [Self; 14] {
+        [
+            Self::utf8(),
+            Self::utf8_with_bom(),
+            Self::utf16le(),
+            Self::utf16be(),
+            Self::windows_1252(),
+            Self::new(encoding_rs::ISO_8859_2, false),
+            Self::new(encoding_rs::ISO_8859_15, false),
+            Self::new(encoding_rs::WINDOWS_1251, false),
+            Self::new(encoding_rs::KOI8_R, false),
+            Self::shift_jis(),
+            Self::new(encoding_rs::EUC_JP, false),
+            Self::new(encoding_rs::EUC_KR, false),
+            Self::new(encoding_rs::GBK, false),
+            Self::new(encoding_rs::BIG5, false),
+        ]
+    }
+
+    /// Looks up an encoding by the name returned from [`Encoding::name`], or
+    /// by any of the labels defined in the WHATWG encoding standard, which
+    /// never imply a byte order mark.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (label, bom) = match name.strip_suffix(" with BOM") {
+            Some(label) => (label, true),
+            None => (name, false),
+        };
+        let encoding = encoding_rs::Encoding::for_label(label.as_bytes())?;
+        if bom && name_with_bom(encoding).is_none() {
+            return None;
+        }
+        Some(Self::new(encoding, bom))
+    }
+
+    pub fn name(&self) -> &'static str {
+        if self.bom {
+            if let Some(name) = name_with_bom(self.encoding) {
+                return name;
+            }
+        }
+        self.encoding.name()
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8
+    }
+
+    /// Guesses the encoding of the given file contents, returning `None` if
+    /// they look like binary data rather than text.
+    ///
+    /// A byte order mark is always respected. Otherwise, text that is valid
+    /// UTF-8 is assumed to be UTF-8, and other text is assumed to be in one of
+    /// the common legacy encodings.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        Self::detect_in(&bytes[..bytes.len().min(DETECTION_LEN)], bytes.len())
+    }
+
+    /// Detects the encoding of the given file contents and decodes them.
+    ///
+    /// Since detection only looks at the start of the file, the encoding is
+    /// detected again from all of the contents if they turn out not to be
+    /// valid in the first guess. Any bytes that are still invalid after that
+    /// are decoded as replacement characters.
+    pub fn detect_and_decode(bytes: &[u8]) -> Option<(Self, Cow<str>)> {
+        let encoding = Self::detect(bytes)?;
+        if let Ok(text) = encoding.decode(bytes) {
+            return Some((encoding, text));
+        }
+        let encoding = Self::detect_in(bytes, bytes.len())?;
+        Some((encoding, encoding.decode_with_replacement(bytes)))
+    }
+
+    /// Detects the encoding of `head`, which is the start of contents that are
+    /// `len` bytes long.
+    fn detect_in(head: &[u8], len: usize) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(head) {
+            return Some(Self::new(encoding, true));
+        }
+
+        let is_truncated = head.len() < len;
+        if let Some(encoding) = detect_utf16_without_bom(head) {
+            return Some(Self::new(encoding, false));
+        }
+        if head.contains(&0) {
+            return None;
+        }
+        match std::str::from_utf8(head) {
+            Ok(_) => return Some(Self::utf8()),
+            // A multi-byte character may have been cut off at the end of the sample.
+            Err(error) if is_truncated && error.error_len().is_none() => return Some(Self::utf8()),
+            Err(_) => {}
+        }
+
+        let control_count = head
+            .iter()
+            .filter(|&&byte| byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+            .count();
+        if control_count * 10 > head.len() {
+            return None;
+        }
+
+        // Japanese text almost always contains kana, which rarely appear when
+        // text in a single-byte encoding happens to be valid Shift-JIS.
+        if let Some(text) = encoding_rs::SHIFT_JIS
+            .decode_without_bom_handling_and_without_replacement(if is_truncated {
+                truncate_partial_char(head)
+            } else {
+                head
+            })
+        {
+            if text
+                .chars()
+                .any(|c| matches!(c, '\u{3040}'..='\u{30ff}' | '\u{ff66}'..='\u{ff9f}'))
+            {
+                return Some(Self::shift_jis());
+            }
+        }
+
+        Some(Self::windows_1252())
+    }
+
+    /// Decodes the given bytes, which are assumed to be in this encoding,
+    /// stripping the byte order mark if there is one.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>> {
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(self.strip_bom(bytes))
+            .ok_or_else(|| anyhow!("file contents are not valid {}", self.name()))
+    }
+
+    fn decode_with_replacement<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        self.encoding
+            .decode_without_bom_handling(self.strip_bom(bytes))
+            .0
+    }
+
+    fn strip_bom<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        match encoding_rs::Encoding::for_bom(bytes) {
+            Some((encoding, bom_len)) if encoding == self.encoding => &bytes[bom_len..],
+            _ => bytes,
+        }
+    }
+
+    /// Encodes the given text in this encoding, prepending a byte order mark
+    /// if the original file had one.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        if self.is_utf8() && !self.bom {
+            return Ok(Cow::Borrowed(text.as_bytes()));
+        }
+
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE {
+            // encoding_rs only decodes UTF-16, so encode it by hand.
+            let big_endian = self.encoding == encoding_rs::UTF_16BE;
+            let to_bytes = |unit: u16| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            };
+            if self.bom {
+                bytes.extend_from_slice(&to_bytes(0xfeff));
+            }
+            for unit in text.encode_utf16() {
+                bytes.extend_from_slice(&to_bytes(unit));
+            }
+            return Ok(Cow::Owned(bytes));
+        }
+
+        if self.bom {
+            bytes.extend_from_slice(b"\xef\xbb\xbf");
+        }
+        let (encoded, _, had_unmappable_characters) = self.encoding.encode(text);
+        if had_unmappable_characters {
+            return Err(anyhow!(
+                "the text contains characters that can't be represented in {}",
+                self.name()
+            ));
+        }
+        bytes.extend_from_slice(&encoded);
+        Ok(Cow::Owned(bytes))
+    }
+}
+
+/// The name of the given encoding when files start with a byte order mark,
+/// or `None` if it doesn't have one.
+fn name_with_bom(encoding: &'static encoding_rs::Encoding) -> Option<&'static str> {
+    if encoding == encoding_rs::UTF_8 {
+        Some("UTF-8 with BOM")
+    } else if encoding == encoding_rs::UTF_16LE {
+        Some("UTF-16LE with BOM")
+    } else if encoding == encoding_rs::UTF_16BE {
+        Some("UTF-16BE with BOM")
+    } else {
+        None
+    }
+}
+
+/// Detects UTF-16 text without a byte order mark, in which most ASCII
+/// characters have a NUL byte on the same side.
+fn detect_utf16_without_bom(head: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let pair_count = head.len() / 2;
+    if pair_count < 2 {
+        return None;
+    }
+
+    let (mut even_nuls, mut odd_nuls) = (0, 0);
+    for pair in head.chunks_exact(2) {
+        if pair[0] == 0 && pair[1] != 0 {
+            even_nuls += 1;
+        } else if pair[1] == 0 && pair[0] != 0 {
+            odd_nuls += 1;
+        }
+    }
+    if odd_nuls * 10 > pair_count * 7 && even_nuls == 0 {
+        Some(encoding_rs::UTF_16LE)
+    } else if even_nuls * 10 > pair_count * 7 && odd_nuls == 0 {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Trims a trailing lead byte that may have been cut off from its trail byte
+/// when the detection sample was taken.
+fn truncate_partial_char(head: &[u8]) -> &[u8] {
+    match head.last() {
+        Some(byte) if *byte >= 0x80 => &head[..head.len() - 1],
+        _ => head,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_round_trip() {
+        let text = "naïve café\n";
+        for encoding in [
+            Encoding::utf8(),
+            Encoding::utf8_with_bom(),
+            Encoding::utf16le(),
+            Encoding::utf16be(),
+            Encoding::windows_1252(),
+        ] {
+            let bytes = encoding.encode(text).unwrap();
+            assert_eq!(
+                Encoding::detect(&bytes),
+                Some(encoding),
+                "{}",
+                encoding.name()
+            );
+            assert_eq!(encoding.decode(&bytes).unwrap(), text);
+        }
+
+        let japanese = "こんにちは、世界\n";
+        let bytes = Encoding::shift_jis().encode(japanese).unwrap();
+        assert_eq!(Encoding::detect(&bytes), Some(Encoding::shift_jis()));
+        assert_eq!(Encoding::shift_jis().decode(&bytes).unwrap(), japanese);
+
+        let utf16_without_bom = "plain text"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Encoding::detect(&utf16_without_bom),
+            Some(Encoding::new(encoding_rs::UTF_16LE, false))
+        );
+
+        assert_eq!(Encoding::detect(b"\x7fELF\x02\x01\x01\x00\x00\x00"), None);
+        assert!(Encoding::windows_1252().encode("日本").is_err());
+    }
+
+    #[test]
+    fn test_from_name() {
+        for encoding in Encoding::all() {
+            assert_eq!(Encoding::from_name(encoding.name()), Some(encoding));
+        }
+        assert_eq!(
+            Encoding::from_name("latin1"),
+            Some(Encoding::windows_1252())
+        );
+        assert_eq!(
+            Encoding::from_name("UTF-16LE"),
+            Some(Encoding::new(encoding_rs::UTF_16LE, false))
+        );
+        assert_eq!(Encoding::from_name("not an encoding"), None);
+        assert_eq!(Encoding::from_name("windows-1252 with BOM"), None);
+    }
+
+    #[test]
+    fn test_detect_and_decode_past_sample() {
+        // Valid UTF-8 for longer than the sample, followed by Latin-1.
+        let mut bytes = "a".repeat(DETECTION_LEN).into_bytes();
+        bytes.extend_from_slice(b"caf\xe9\n");
+        assert_eq!(Encoding::detect(&bytes), Some(Encoding::utf8()));
+        let (encoding, text) = Encoding::detect_and_decode(&bytes).unwrap();
+        assert_eq!(encoding, Encoding::windows_1252());
+        assert!(text.ends_with("caf\u{e9}\n"));
+
+        // UTF-16 with an unpaired surrogate can only be decoded with replacement.
+        let bytes = b"\xff\xfeh\x00\x00\xd8i\x00";
+        let (encoding, text) = Encoding::detect_and_decode(bytes).unwrap();
+        assert_eq!(encoding, Encoding::utf16le());
+        assert_eq!(text, "h\u{fffd}i");
+    }
+}
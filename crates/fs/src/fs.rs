@@ -1,3 +1,4 @@
+mod encoding;
 pub mod repository;
 
 use anyhow::{anyhow, Result};
@@ -14,12 +15,13 @@ use notify::{Config, EventKind, Watcher};
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
+pub use encoding::Encoding;
 use futures::{future::BoxFuture, Stream, StreamExt};
 use git2::Repository as LibGitRepository;
 use parking_lot::Mutex;
 use repository::GitRepository;
 use rope::Rope;
-use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use std::io::Write;
 use std::sync::Arc;
 use std::{
@@ -47,10 +49,41 @@ pub trait Fs: Send + Sync {
     async fn rename(&self, source: &Path, target: &Path, options: RenameOptions) -> Result<()>;
     async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()>;
     async fn remove_file(&self, path: &Path, options: RemoveOptions) -> Result<()>;
+    /// Whether [`Fs::trash_file`] and [`Fs::trash_dir`] are supported on this platform.
+    fn is_trash_supported(&self) -> bool {
+        false
+    }
+    /// Moves a directory to the system trash, where it can be recovered from.
+    async fn trash_dir(&self, path: &Path, _options: RemoveOptions) -> Result<()> {
+        Err(anyhow!("can't move {path:?} to the trash on this platform"))
+    }
+    /// Moves a file to the system trash, where it can be recovered from.
+    async fn trash_file(&self, path: &Path, _options: RemoveOptions) -> Result<()> {
+        Err(anyhow!("can't move {path:?} to the trash on this platform"))
+    }
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>>;
     async fn load(&self, path: &Path) -> Result<String>;
+    /// Loads a text file in the given encoding, detecting the encoding if none is given.
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)>;
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Reads up to `len` bytes of a file, starting at `offset`.
+    async fn load_range(&self, path: &Path, offset: u64, len: u64) -> Result<Vec<u8>>;
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()>;
+    /// Appends to the end of a file, creating it if it doesn't exist.
+    async fn append_bytes(&self, path: &Path, data: Vec<u8>) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn metadata(&self, path: &Path) -> Result<Option<Metadata>>;
@@ -60,6 +93,9 @@ pub trait Fs: Send + Sync {
         path: &Path,
     ) -> Result<Pin<Box<dyn Send + Stream<Item = Result<PathBuf>>>>>;
 
+    /// Watches the given path for changes. Directories are watched recursively.
+    /// A file is watched on its own, in a way that survives the file being
+    /// replaced, and only that file's events are reported.
     async fn watch(
         &self,
         path: &Path,
@@ -68,7 +104,7 @@ pub trait Fs: Send + Sync {
 
     fn open_repo(&self, abs_dot_git: &Path) -> Option<Arc<Mutex<dyn GitRepository>>>;
     fn is_fake(&self) -> bool;
-    async fn is_case_sensitive(&self) -> Result<bool>;
+    async fn is_case_sensitive(&self, path: &Path) -> Result<bool>;
     #[cfg(any(test, feature = "test-support"))]
     fn as_fake(&self) -> &FakeFs;
 }
@@ -97,16 +133,71 @@ pub struct RemoveOptions {
     pub ignore_if_not_exists: bool,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Metadata {
     pub inode: u64,
     pub mtime: SystemTime,
+    /// The size of the file in bytes.
+    pub len: u64,
     pub is_symlink: bool,
     pub is_dir: bool,
+    /// For symlinks, the canonical path of the file or directory they resolve to.
+    pub canonical_path: Option<PathBuf>,
+}
+
+/// Returns the path to watch for changes to the given path, along with the
+/// file whose events to keep. Files are often saved by replacing them, which
+/// ends watches on the file itself, so files are watched through their
+/// directory instead.
+#[cfg(not(target_os = "macos"))]
+fn watched_path(path: &Path) -> (&Path, Option<PathBuf>) {
+    match path.parent().filter(|_| path.is_file()) {
+        Some(parent_path) => (parent_path, Some(path.to_path_buf())),
+        None => (path, None),
+    }
 }
 
 pub struct RealFs;
 
+impl RealFs {
+    /// Checks whether the file system is case sensitive by attempting to create two files
+    /// that have the same name except for the casing.
+    ///
+    /// It creates both files in a temporary directory it removes at the end.
+    async fn is_case_sensitive_in_temp_dir(&self) -> Result<bool> {
+        let temp_dir = TempDir::new()?;
+        let test_file_1 = temp_dir.path().join("case_sensitivity_test.tmp");
+        let test_file_2 = temp_dir.path().join("CASE_SENSITIVITY_TEST.TMP");
+
+        let create_opts = CreateOptions {
+            overwrite: false,
+            ignore_if_exists: false,
+        };
+
+        // Create file1
+        self.create_file(&test_file_1, create_opts).await?;
+
+        // Now check whether it's possible to create file2
+        let case_sensitive = match self.create_file(&test_file_2, create_opts).await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if let Some(io_error) = e.downcast_ref::<io::Error>() {
+                    if io_error.kind() == io::ErrorKind::AlreadyExists {
+                        Ok(false)
+                    } else {
+                        Err(e)
+                    }
+                } else {
+                    Err(e)
+                }
+            }
+        };
+
+        temp_dir.close()?;
+        case_sensitive
+    }
+}
+
 #[async_trait::async_trait]
 impl Fs for RealFs {
     async fn create_dir(&self, path: &Path) -> Result<()> {
@@ -176,6 +267,32 @@ impl Fs for RealFs {
         }
     }
 
+    fn is_trash_supported(&self) -> bool {
+        true
+    }
+
+    async fn trash_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        if !options.recursive {
+            let mut children = match smol::fs::read_dir(path).await {
+                Ok(children) => children,
+                Err(err)
+                    if err.kind() == io::ErrorKind::NotFound && options.ignore_if_not_exists =>
+                {
+                    return Ok(())
+                }
+                Err(err) => Err(err)?,
+            };
+            if children.next().await.is_some() {
+                return Err(anyhow!("{path:?} is not empty"));
+            }
+        }
+        trash_item(path, options).await
+    }
+
+    async fn trash_file(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        trash_item(path, options).await
+    }
+
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>> {
         Ok(Box::new(std::fs::File::open(path)?))
     }
@@ -187,7 +304,37 @@ impl Fs for RealFs {
         Ok(text)
     }
 
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let bytes = smol::fs::read(path).await?;
+        let (encoding, text) = match encoding {
+            Some(encoding) => (encoding, encoding.decode(&bytes)?),
+            None => Encoding::detect_and_decode(&bytes)
+                .ok_or_else(|| anyhow!("{path:?} does not contain text in a supported encoding"))?,
+        };
+        Ok((text.into_owned(), encoding))
+    }
+
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        Ok(smol::fs::read(path).await?)
+    }
+
+    async fn load_range(&self, path: &Path, offset: u64, len: u64) -> Result<Vec<u8>> {
+        let mut file = smol::fs::File::open(path).await?;
+        file.seek(io::SeekFrom::Start(offset)).await?;
+        let mut data = Vec::new();
+        file.take(len).read_to_end(&mut data).await?;
+        Ok(data)
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+        self.atomic_write_bytes(path, data.into_bytes()).await
+    }
+
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         smol::unblock(move || {
             let mut tmp_file = if cfg!(target_os = "linux") {
                 // Use the directory of the destination as temp dir to avoid
@@ -197,7 +344,7 @@ impl Fs for RealFs {
             } else {
                 NamedTempFile::new()
             }?;
-            tmp_file.write_all(data.as_bytes())?;
+            tmp_file.write_all(&data)?;
             tmp_file.persist(path)?;
             Ok::<(), anyhow::Error>(())
         })
@@ -206,18 +353,68 @@ impl Fs for RealFs {
         Ok(())
     }
 
+    async fn append_bytes(&self, path: &Path, data: Vec<u8>) -> Result<()> {
+        let mut file = smol::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(&data).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_encoding(path, text, line_ending, Encoding::default())
+            .await
+    }
+
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         let buffer_size = text.summary().len.min(10 * 1024);
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        let file = smol::fs::File::create(path).await?;
-        let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
-        for chunk in chunks(text, line_ending) {
-            writer.write_all(chunk.as_bytes()).await?;
-        }
-        writer.flush().await?;
-        Ok(())
+
+        // Save through symlinks, rather than replacing them with the saved file.
+        let path = smol::fs::canonicalize(path)
+            .await
+            .unwrap_or_else(|_| path.to_path_buf());
+        let permissions = smol::fs::metadata(&path)
+            .await
+            .ok()
+            .map(|metadata| metadata.permissions());
+        let text = text.clone();
+
+        // Write to a temporary file next to the destination and then rename it into place,
+        // so that the file is never left partially written.
+        smol::unblock(move || {
+            let mut tmp_file = NamedTempFile::new_in(path.parent().unwrap_or(&paths::TEMP_DIR))?;
+            {
+                let mut writer = io::BufWriter::with_capacity(buffer_size, tmp_file.as_file_mut());
+                if encoding == Encoding::default() {
+                    for chunk in chunks(&text, line_ending) {
+                        writer.write_all(chunk.as_bytes())?;
+                    }
+                } else {
+                    let text = chunks(&text, line_ending).collect::<String>();
+                    writer.write_all(&encoding.encode(&text)?)?;
+                }
+                writer.flush()?;
+            }
+            tmp_file.as_file().sync_all()?;
+            if let Some(permissions) = permissions {
+                tmp_file.as_file().set_permissions(permissions)?;
+            }
+            tmp_file.persist(&path)?;
+            Ok::<(), anyhow::Error>(())
+        })
+        .await
     }
 
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
@@ -243,10 +440,13 @@ impl Fs for RealFs {
         };
 
         let is_symlink = symlink_metadata.file_type().is_symlink();
-        let metadata = if is_symlink {
-            smol::fs::metadata(path).await?
+        let (metadata, canonical_path) = if is_symlink {
+            (
+                smol::fs::metadata(path).await?,
+                Some(smol::fs::canonicalize(path).await?),
+            )
         } else {
-            symlink_metadata
+            (symlink_metadata, None)
         };
 
         #[cfg(unix)]
@@ -258,8 +458,10 @@ impl Fs for RealFs {
         Ok(Some(Metadata {
             inode,
             mtime: metadata.modified().unwrap(),
+            len: metadata.len(),
             is_symlink,
             is_dir: metadata.file_type().is_dir(),
+            canonical_path,
         }))
     }
 
@@ -286,9 +488,27 @@ impl Fs for RealFs {
         latency: Duration,
     ) -> Pin<Box<dyn Send + Stream<Item = Vec<Event>>>> {
         let (tx, rx) = smol::channel::unbounded();
-        let (stream, handle) = EventStream::new(&[path], latency);
+        // FSEvents tracks paths rather than inodes, so a file can be watched
+        // directly and the watch keeps firing after the file is replaced. The
+        // events carry canonical paths, so they're matched against the file's
+        // canonical path, which differs when it's reached through a symlink.
+        let file_path = if path.is_file() {
+            self.canonicalize(path).await.log_err()
+        } else {
+            None
+        };
+        let watched_path = file_path.as_deref().unwrap_or(path);
+        let (stream, handle) = EventStream::new(&[watched_path], latency);
         std::thread::spawn(move || {
-            stream.run(move |events| smol::block_on(tx.send(events)).is_ok());
+            stream.run(move |mut events| {
+                if let Some(file_path) = &file_path {
+                    events.retain(|event| &event.path == file_path);
+                    if events.is_empty() {
+                        return true;
+                    }
+                }
+                smol::block_on(tx.send(events)).is_ok()
+            });
         });
         Box::pin(rx.chain(futures::stream::once(async move {
             drop(handle);
@@ -309,40 +529,61 @@ impl Fs for RealFs {
             return Box::pin(rx);
         }
 
-        let mut watcher =
-            notify::recommended_watcher(move |res: Result<notify::Event, _>| match res {
-                Ok(event) => {
-                    let flags = match event.kind {
-                        // ITEM_REMOVED is currently the only flag we care about
-                        EventKind::Remove(_) => StreamFlags::ITEM_REMOVED,
-                        _ => StreamFlags::NONE,
-                    };
-                    let events = event
-                        .paths
-                        .into_iter()
-                        .map(|path| Event {
-                            event_id: 0,
-                            flags,
-                            path,
-                        })
-                        .collect::<Vec<_>>();
+        let (watched_path, file_path) = watched_path(path);
+        let recursive_mode = if file_path.is_some() {
+            notify::RecursiveMode::NonRecursive
+        } else {
+            notify::RecursiveMode::Recursive
+        };
+        let watcher = notify::recommended_watcher(move |res: Result<notify::Event, _>| match res {
+            Ok(event) => {
+                let flags = match event.kind {
+                    // ITEM_REMOVED is currently the only flag we care about
+                    EventKind::Remove(_) => StreamFlags::ITEM_REMOVED,
+                    _ => StreamFlags::NONE,
+                };
+                let events = event
+                    .paths
+                    .into_iter()
+                    .filter(|path| {
+                        file_path
+                            .as_ref()
+                            .map_or(true, |file_path| path == file_path)
+                    })
+                    .map(|path| Event {
+                        event_id: 0,
+                        flags,
+                        path,
+                    })
+                    .collect::<Vec<_>>();
+                if !events.is_empty() {
                     let _ = tx.try_send(events);
                 }
-                Err(err) => {
-                    log::error!("watch error: {}", err);
-                }
-            })
-            .unwrap();
-
-        watcher
-            .configure(Config::default().with_poll_interval(latency))
-            .unwrap();
+            }
+            Err(err) => {
+                log::error!("watch error: {}", err);
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.configure(Config::default().with_poll_interval(latency))?;
+            watcher.watch(watched_path, recursive_mode)?;
+            Ok(watcher)
+        });
 
-        watcher
-            .watch(path, notify::RecursiveMode::Recursive)
-            .unwrap();
+        // When the watch can't be set up, the sender has already been dropped
+        // along with the event handler, so the stream ends right away.
+        let watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                log::error!("failed to watch {}: {}", path.display(), err);
+                return Box::pin(rx);
+            }
+        };
 
-        Box::pin(rx)
+        Box::pin(rx.chain(futures::stream::once(async move {
+            drop(watcher);
+            vec![]
+        })))
     }
 
     fn open_repo(&self, dotgit_path: &Path) -> Option<Arc<Mutex<dyn GitRepository>>> {
@@ -357,41 +598,40 @@ impl Fs for RealFs {
         false
     }
 
-    /// Checks whether the file system is case sensitive by attempting to create two files
-    /// that have the same name except for the casing.
-    ///
-    /// It creates both files in a temporary directory it removes at the end.
-    async fn is_case_sensitive(&self) -> Result<bool> {
-        let temp_dir = TempDir::new()?;
-        let test_file_1 = temp_dir.path().join("case_sensitivity_test.tmp");
-        let test_file_2 = temp_dir.path().join("CASE_SENSITIVITY_TEST.TMP");
-
-        let create_opts = CreateOptions {
-            overwrite: false,
-            ignore_if_exists: false,
-        };
-
-        // Create file1
-        self.create_file(&test_file_1, create_opts).await?;
-
-        // Now check whether it's possible to create file2
-        let case_sensitive = match self.create_file(&test_file_2, create_opts).await {
-            Ok(_) => Ok(true),
-            Err(e) => {
-                if let Some(io_error) = e.downcast_ref::<io::Error>() {
-                    if io_error.kind() == io::ErrorKind::AlreadyExists {
-                        Ok(false)
+    /// Checks whether the file system containing the given path is case sensitive, by looking
+    /// up the path with the casing of one of its components flipped.
+    async fn is_case_sensitive(&self, path: &Path) -> Result<bool> {
+        let path = self.canonicalize(path).await?;
+        for ancestor in path.ancestors() {
+            let Some(file_name) = ancestor.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let flipped_file_name = file_name
+                .chars()
+                .flat_map(|c| {
+                    if c.is_lowercase() {
+                        c.to_uppercase().collect::<Vec<_>>()
                     } else {
-                        Err(e)
+                        c.to_lowercase().collect::<Vec<_>>()
                     }
-                } else {
-                    Err(e)
-                }
+                })
+                .collect::<String>();
+            if flipped_file_name == file_name {
+                continue;
             }
-        };
 
-        temp_dir.close()?;
-        case_sensitive
+            let Some(metadata) = self.metadata(ancestor).await? else {
+                continue;
+            };
+            let flipped_metadata = self
+                .metadata(&ancestor.with_file_name(flipped_file_name))
+                .await?;
+            return Ok(flipped_metadata.map_or(true, |flipped| flipped.inode != metadata.inode));
+        }
+
+        // None of the path's components contain letters, so fall back to checking a
+        // temporary directory.
+        self.is_case_sensitive_in_temp_dir().await
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -421,6 +661,8 @@ struct FakeFsState {
     buffered_events: Vec<fsevent::Event>,
     metadata_call_count: usize,
     read_dir_call_count: usize,
+    trashed_paths: Vec<PathBuf>,
+    case_sensitivity: Vec<(PathBuf, bool)>,
 }
 
 #[cfg(any(test, feature = "test-support"))]
@@ -429,7 +671,7 @@ enum FakeFsEntry {
     File {
         inode: u64,
         mtime: SystemTime,
-        content: String,
+        content: Vec<u8>,
     },
     Dir {
         inode: u64,
@@ -571,6 +813,8 @@ impl FakeFs {
                 events_paused: false,
                 read_dir_call_count: 0,
                 metadata_call_count: 0,
+                trashed_paths: Vec::new(),
+                case_sensitivity: Vec::new(),
             }),
         })
     }
@@ -579,6 +823,10 @@ impl FakeFs {
         self.write_file_internal(path, content).unwrap()
     }
 
+    pub async fn insert_file_bytes(&self, path: impl AsRef<Path>, content: Vec<u8>) {
+        self.write_file_internal(path, content).unwrap()
+    }
+
     pub async fn insert_symlink(&self, path: impl AsRef<Path>, target: PathBuf) {
         let mut state = self.state.lock();
         let path = path.as_ref();
@@ -598,7 +846,12 @@ impl FakeFs {
         state.emit_event(&[path]);
     }
 
-    pub fn write_file_internal(&self, path: impl AsRef<Path>, content: String) -> Result<()> {
+    pub fn write_file_internal(
+        &self,
+        path: impl AsRef<Path>,
+        content: impl Into<Vec<u8>>,
+    ) -> Result<()> {
+        let content = content.into();
         let mut state = self.state.lock();
         let path = path.as_ref();
         let inode = state.next_inode;
@@ -694,6 +947,15 @@ impl FakeFs {
         }
     }
 
+    /// Sets whether the file system at the given path is reported as case sensitive, which
+    /// it is everywhere by default. Paths are still always compared case-sensitively.
+    pub fn set_case_sensitive(&self, path: impl AsRef<Path>, case_sensitive: bool) {
+        self.state
+            .lock()
+            .case_sensitivity
+            .push((path.as_ref().to_path_buf(), case_sensitive));
+    }
+
     pub fn set_branch_name(&self, dot_git: &Path, branch: Option<impl Into<String>>) {
         self.with_git_state(dot_git, true, |state| {
             state.branch_name = branch.map(Into::into)
@@ -817,6 +1079,11 @@ impl FakeFs {
         self.state.lock().metadata_call_count
     }
 
+    /// The paths that have been moved to the trash, in the order they were trashed.
+    pub fn trashed_paths(&self) -> Vec<PathBuf> {
+        self.state.lock().trashed_paths.clone()
+    }
+
     fn simulate_random_delay(&self) -> impl futures::Future<Output = ()> {
         self.executor.simulate_random_delay()
     }
@@ -832,7 +1099,7 @@ impl FakeFsEntry {
         matches!(self, Self::Symlink { .. })
     }
 
-    fn file_content(&self, path: &Path) -> Result<&String> {
+    fn file_content(&self, path: &Path) -> Result<&Vec<u8>> {
         if let Self::File { content, .. } = self {
             Ok(content)
         } else {
@@ -840,7 +1107,7 @@ impl FakeFsEntry {
         }
     }
 
-    fn set_file_content(&mut self, path: &Path, new_content: String) -> Result<()> {
+    fn set_file_content(&mut self, path: &Path, new_content: Vec<u8>) -> Result<()> {
         if let Self::File { content, mtime, .. } = self {
             *mtime = SystemTime::now();
             *content = new_content;
@@ -909,7 +1176,7 @@ impl Fs for FakeFs {
         let file = Arc::new(Mutex::new(FakeFsEntry::File {
             inode,
             mtime,
-            content: String::new(),
+            content: Vec::new(),
         }));
         state.write_path(path, |entry| {
             match entry {
@@ -1000,7 +1267,7 @@ impl Fs for FakeFs {
                 e.insert(Arc::new(Mutex::new(FakeFsEntry::File {
                     inode,
                     mtime,
-                    content: String::new(),
+                    content: Vec::new(),
                 })))
                 .clone(),
             )),
@@ -1078,12 +1345,47 @@ impl Fs for FakeFs {
         Ok(())
     }
 
+    fn is_trash_supported(&self) -> bool {
+        true
+    }
+
+    async fn trash_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        let exists = self.state.lock().read_path(path).is_ok();
+        self.remove_dir(path, options).await?;
+        if exists {
+            self.state.lock().trashed_paths.push(normalize_path(path));
+        }
+        Ok(())
+    }
+
+    async fn trash_file(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        let exists = self.state.lock().read_path(path).is_ok();
+        self.remove_file(path, options).await?;
+        if exists {
+            self.state.lock().trashed_paths.push(normalize_path(path));
+        }
+        Ok(())
+    }
+
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>> {
-        let text = self.load(path).await?;
-        Ok(Box::new(io::Cursor::new(text)))
+        let bytes = self.load_bytes(path).await?;
+        Ok(Box::new(io::Cursor::new(bytes)))
     }
 
     async fn load(&self, path: &Path) -> Result<String> {
+        let bytes = self.load_bytes(path).await?;
+        String::from_utf8(bytes).map_err(|_| anyhow!("{path:?} is not valid UTF-8"))
+    }
+
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        Ok((self.load(path).await?, encoding.unwrap_or_default()))
+    }
+
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
         let path = normalize_path(path);
         self.simulate_random_delay().await;
         let state = self.state.lock();
@@ -1092,18 +1394,38 @@ impl Fs for FakeFs {
         entry.file_content(&path).cloned()
     }
 
+    async fn load_range(&self, path: &Path, offset: u64, len: u64) -> Result<Vec<u8>> {
+        let bytes = self.load_bytes(path).await?;
+        let start = (offset as usize).min(bytes.len());
+        let end = start.saturating_add(len as usize).min(bytes.len());
+        Ok(bytes[start..end].to_vec())
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+        self.atomic_write_bytes(path, data.into_bytes()).await
+    }
+
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
-        self.write_file_internal(path, data.to_string())?;
-
+        self.write_file_internal(path, data)?;
         Ok(())
     }
 
+    async fn append_bytes(&self, path: &Path, data: Vec<u8>) -> Result<()> {
+        let mut content = if self.is_file(path).await {
+            self.load_bytes(path).await?
+        } else {
+            Vec::new()
+        };
+        content.extend(data);
+        self.atomic_write_bytes(path.to_path_buf(), content).await
+    }
+
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
-        let content = chunks(text, line_ending).collect();
+        let content = chunks(text, line_ending).collect::<String>();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
@@ -1111,6 +1433,17 @@ impl Fs for FakeFs {
         Ok(())
     }
 
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        _: Encoding,
+    ) -> Result<()> {
+        // The fake file system only stores UTF-8 text.
+        self.save(path, text, line_ending).await
+    }
+
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
         let path = normalize_path(path);
         self.simulate_random_delay().await;
@@ -1140,9 +1473,11 @@ impl Fs for FakeFs {
         state.metadata_call_count += 1;
         if let Some((mut entry, _)) = state.try_read_path(&path, false) {
             let is_symlink = entry.lock().is_symlink();
+            let mut canonical_path = None;
             if is_symlink {
-                if let Some(e) = state.try_read_path(&path, true).map(|e| e.0) {
-                    entry = e;
+                if let Some((target_entry, target_path)) = state.try_read_path(&path, true) {
+                    entry = target_entry;
+                    canonical_path = Some(target_path);
                 } else {
                     return Ok(None);
                 }
@@ -1150,17 +1485,25 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    content,
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: content.len() as u64,
                     is_dir: false,
                     is_symlink,
+                    canonical_path,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: 0,
                     is_dir: true,
                     is_symlink,
+                    canonical_path,
                 },
                 FakeFsEntry::Symlink { .. } => unreachable!(),
             }))
@@ -1241,8 +1584,14 @@ impl Fs for FakeFs {
         true
     }
 
-    async fn is_case_sensitive(&self) -> Result<bool> {
-        Ok(true)
+    async fn is_case_sensitive(&self, path: &Path) -> Result<bool> {
+        let state = self.state.lock();
+        Ok(state
+            .case_sensitivity
+            .iter()
+            .rev()
+            .find(|(prefix, _)| path.starts_with(prefix))
+            .map_or(true, |(_, case_sensitive)| *case_sensitive))
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -1378,6 +1727,60 @@ async fn file_id(path: impl AsRef<Path>) -> Result<u64> {
     .await
 }
 
+async fn trash_item(path: &Path, options: RemoveOptions) -> Result<()> {
+    if let Err(err) = smol::fs::symlink_metadata(path).await {
+        if err.kind() == io::ErrorKind::NotFound && options.ignore_if_not_exists {
+            return Ok(());
+        }
+        Err(err)?;
+    }
+
+    let path = path.to_path_buf();
+    smol::unblock(move || move_to_trash(path)).await
+}
+
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: PathBuf) -> Result<()> {
+    use cocoa::{
+        base::{id, nil, BOOL, NO},
+        foundation::{NSAutoreleasePool, NSString},
+    };
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let ns_path = NSString::alloc(nil)
+            .init_str(&path.to_string_lossy())
+            .autorelease();
+        let url: id = msg_send![class!(NSURL), fileURLWithPath: ns_path];
+        let file_manager: id = msg_send![class!(NSFileManager), defaultManager];
+        let mut error: id = nil;
+        let trashed: BOOL = msg_send![
+            file_manager,
+            trashItemAtURL: url
+            resultingItemURL: nil
+            error: &mut error
+        ];
+        let result = if trashed == NO {
+            let description: id = msg_send![error, localizedDescription];
+            let description = CStr::from_ptr(description.UTF8String()).to_string_lossy();
+            Err(anyhow!(
+                "failed to move {path:?} to the trash: {description}"
+            ))
+        } else {
+            Ok(())
+        };
+        pool.drain();
+        result
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn move_to_trash(path: PathBuf) -> Result<()> {
+    trash::delete(&path).map_err(|err| anyhow!("failed to move {path:?} to the trash: {err}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1433,5 +1836,133 @@ mod tests {
             fs.load("/root/dir2/link-to-dir3/d".as_ref()).await.unwrap(),
             "D",
         );
+
+        let metadata = fs
+            .metadata("/root/dir2/link-to-dir3".as_ref())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(metadata.is_symlink && metadata.is_dir);
+        assert_eq!(
+            metadata.canonical_path,
+            Some(PathBuf::from("/root/dir2/dir3"))
+        );
+        let metadata = fs
+            .metadata("/root/dir2/dir3".as_ref())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.canonical_path, None);
+    }
+
+    #[gpui::test]
+    async fn test_fake_fs_bytes(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor.clone());
+        let path = PathBuf::from("/root/image.bin");
+        fs.create_dir("/root".as_ref()).await.unwrap();
+
+        let bytes = vec![0x89, b'P', b'N', b'G', 0xff, 0x00, 0xfe];
+        fs.atomic_write_bytes(path.clone(), bytes.clone())
+            .await
+            .unwrap();
+        fs.append_bytes(&path, vec![0xc3, 0x28]).await.unwrap();
+
+        let mut expected = bytes;
+        expected.extend([0xc3, 0x28]);
+        assert_eq!(fs.load_bytes(&path).await.unwrap(), expected);
+        assert_eq!(
+            fs.load_range(&path, 4, 3).await.unwrap(),
+            [0xff, 0x00, 0xfe]
+        );
+        assert_eq!(
+            fs.metadata(&path).await.unwrap().unwrap().len,
+            expected.len() as u64
+        );
+        assert!(fs.load(&path).await.is_err());
+    }
+
+    #[test]
+    fn test_real_fs_save() {
+        smol::block_on(async {
+            let dir = TempDir::new().unwrap();
+            let file_path = dir.path().join("file.txt");
+            std::fs::write(&file_path, "old").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&file_path, std::fs::Permissions::from_mode(0o755))
+                    .unwrap();
+            }
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&file_path, dir.path().join("link.txt")).unwrap();
+
+            let fs = RealFs;
+            fs.save(&file_path, &Rope::from("one\ntwo\n"), LineEnding::Windows)
+                .await
+                .unwrap();
+            assert_eq!(
+                std::fs::read_to_string(&file_path).unwrap(),
+                "one\r\ntwo\r\n"
+            );
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = std::fs::metadata(&file_path).unwrap().permissions().mode();
+                assert_eq!(mode & 0o777, 0o755);
+
+                // Saving a symlink writes to its target.
+                let link_path = dir.path().join("link.txt");
+                fs.save(&link_path, &Rope::from("linked"), LineEnding::Unix)
+                    .await
+                    .unwrap();
+                assert!(std::fs::symlink_metadata(&link_path)
+                    .unwrap()
+                    .file_type()
+                    .is_symlink());
+                assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "linked");
+            }
+        });
+    }
+
+    #[test]
+    fn test_real_fs_encodings() {
+        smol::block_on(async {
+            let dir = TempDir::new().unwrap();
+            let file_path = dir.path().join("latin1.txt");
+            std::fs::write(&file_path, b"caf\xe9\n").unwrap();
+
+            let fs = RealFs;
+            let (text, encoding) = fs.load_with_encoding(&file_path, None).await.unwrap();
+            assert_eq!(text, "caf\u{e9}\n");
+            assert_eq!(encoding, Encoding::windows_1252());
+
+            fs.save_with_encoding(
+                &file_path,
+                &Rope::from("d\u{e9}j\u{e0} vu\n"),
+                LineEnding::Unix,
+                encoding,
+            )
+            .await
+            .unwrap();
+            assert_eq!(std::fs::read(&file_path).unwrap(), b"d\xe9j\xe0 vu\n");
+
+            fs.save_with_encoding(
+                &file_path,
+                &Rope::from("hi\n"),
+                LineEnding::Unix,
+                Encoding::utf16le(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                std::fs::read(&file_path).unwrap(),
+                b"\xff\xfeh\x00i\x00\n\x00"
+            );
+            assert_eq!(
+                fs.load_with_encoding(&file_path, None).await.unwrap(),
+                ("hi\n".to_string(), Encoding::utf16le())
+            );
+        });
     }
 }
@@ -1,9 +1,11 @@
 mod char_bag;
 mod matcher;
+mod path_index;
 mod paths;
 mod strings;
 
 pub use char_bag::CharBag;
+pub use path_index::PathIndex;
 pub use paths::{
     match_fixed_path_set, match_path_sets, PathMatch, PathMatchCandidate, PathMatchCandidateSet,
 };
@@ -0,0 +1,205 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use crate::{CharBag, PathMatchCandidate};
+
+type Trigram = [char; 3];
+
+/// A trigram index over a set of paths that can be updated incrementally.
+///
+/// Fuzzy queries may match characters anywhere in a path, so the index can't
+/// answer a query by itself. It narrows a query down to the paths that contain
+/// each of the query's trigrams, which are the paths most likely to score well,
+/// so that they can be matched before the rest.
+#[derive(Clone, Default)]
+pub struct PathIndex {
+    entries: Vec<Option<IndexedPath>>,
+    ids_by_path: HashMap<Arc<Path>, usize>,
+    ids_by_trigram: HashMap<Trigram, Vec<usize>>,
+    removed_count: usize,
+}
+
+#[derive(Clone)]
+struct IndexedPath {
+    path: Arc<Path>,
+    char_bag: CharBag,
+}
+
+impl PathIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids_by_path.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.ids_by_path.contains_key(path)
+    }
+
+    pub fn insert(&mut self, path: Arc<Path>, char_bag: CharBag) {
+        if self.ids_by_path.contains_key(&path) {
+            return;
+        }
+
+        let id = self.entries.len();
+        for trigram in trigrams(&path.to_string_lossy()) {
+            self.ids_by_trigram.entry(trigram).or_default().push(id);
+        }
+        self.ids_by_path.insert(path.clone(), id);
+        self.entries.push(Some(IndexedPath { path, char_bag }));
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        let Some(id) = self.ids_by_path.remove(path) else {
+            return;
+        };
+
+        self.entries[id] = None;
+        self.removed_count += 1;
+        // Removed ids stay in the trigram lists until there are enough of them
+        // to be worth rebuilding the index.
+        if self.removed_count > self.entries.len() / 2 {
+            let entries = std::mem::take(&mut self.entries);
+            *self = Self::default();
+            for entry in entries.into_iter().flatten() {
+                self.insert(entry.path, entry.char_bag);
+            }
+        }
+    }
+
+    /// Returns the ids of the paths that contain every trigram of the query,
+    /// or `None` if the query has no trigrams.
+    pub fn prefilter(&self, query: &str) -> Option<Vec<usize>> {
+        let mut id_lists = trigrams(query)
+            .into_iter()
+            .map(|trigram| {
+                self.ids_by_trigram
+                    .get(&trigram)
+                    .map_or(&[][..], |ids| ids.as_slice())
+            })
+            .collect::<Vec<_>>();
+        id_lists.sort_unstable_by_key(|ids| ids.len());
+        let (shortest, rest) = id_lists.split_first()?;
+
+        let mut ids = Vec::new();
+        for &id in *shortest {
+            let is_live = self.entries[id].is_some();
+            if is_live
+                && rest
+                    .iter()
+                    .all(|other_ids| other_ids.binary_search(&id).is_ok())
+            {
+                ids.push(id);
+            }
+        }
+        Some(ids)
+    }
+
+    pub fn candidate(&self, id: usize) -> Option<PathMatchCandidate<'_>> {
+        let entry = self.entries.get(id)?.as_ref()?;
+        Some(PathMatchCandidate {
+            path: &entry.path,
+            char_bag: entry.char_bag,
+        })
+    }
+}
+
+/// Returns the distinct lowercase trigrams of each whitespace-separated word in the text.
+fn trigrams(text: &str) -> Vec<Trigram> {
+    let mut trigrams = Vec::new();
+    for word in text.split_whitespace() {
+        let chars = word
+            .chars()
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>();
+        trigrams.extend(chars.windows(3).map(|chars| [chars[0], chars[1], chars[2]]));
+    }
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(index: &mut PathIndex, path: &str) {
+        index.insert(Path::new(path).into(), CharBag::from(path));
+    }
+
+    fn prefiltered_paths(index: &PathIndex, query: &str) -> Option<Vec<String>> {
+        let ids = index.prefilter(query)?;
+        Some(
+            ids.into_iter()
+                .map(|id| {
+                    index
+                        .candidate(id)
+                        .unwrap()
+                        .path
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_prefilter() {
+        let mut index = PathIndex::new();
+        insert(&mut index, "crates/file_finder/src/file_finder.rs");
+        insert(&mut index, "crates/fuzzy/src/paths.rs");
+        insert(&mut index, "crates/project/src/project.rs");
+        insert(&mut index, "docs/File Finder.md");
+
+        assert_eq!(prefiltered_paths(&index, "fi"), None);
+        assert_eq!(
+            prefiltered_paths(&index, "FINDER"),
+            Some(vec![
+                "crates/file_finder/src/file_finder.rs".to_string(),
+                "docs/File Finder.md".to_string(),
+            ])
+        );
+        assert_eq!(
+            prefiltered_paths(&index, "src paths"),
+            Some(vec!["crates/fuzzy/src/paths.rs".to_string()])
+        );
+        assert_eq!(prefiltered_paths(&index, "ffnd"), Some(Vec::new()));
+        assert_eq!(index.prefilter("src").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut index = PathIndex::new();
+        for ix in 0..10 {
+            insert(&mut index, &format!("dir/file_{ix}.rs"));
+        }
+        insert(&mut index, "dir/file_3.rs");
+        assert_eq!(index.len(), 10);
+
+        for ix in 0..6 {
+            index.remove(Path::new(&format!("dir/file_{ix}.rs")));
+        }
+        assert_eq!(index.len(), 4);
+        assert!(!index.contains(Path::new("dir/file_0.rs")));
+        assert_eq!(
+            prefiltered_paths(&index, "file_"),
+            Some(vec![
+                "dir/file_6.rs".to_string(),
+                "dir/file_7.rs".to_string(),
+                "dir/file_8.rs".to_string(),
+                "dir/file_9.rs".to_string(),
+            ])
+        );
+
+        insert(&mut index, "dir/file_0.rs");
+        assert_eq!(
+            prefiltered_paths(&index, "le_0").unwrap(),
+            ["dir/file_0.rs"]
+        );
+    }
+}
@@ -49,7 +49,11 @@ impl GoToLine {
     }
 
     pub fn new(active_editor: View<Editor>, cx: &mut ViewContext<Self>) -> Self {
-        let line_editor = cx.new_view(|cx| Editor::single_line(cx));
+        let line_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("line[:column], or +lines or -lines", cx);
+            editor
+        });
         let line_editor_change = cx.subscribe(&line_editor, Self::on_line_editor_event);
 
         let editor = active_editor.read(cx);
@@ -116,17 +120,14 @@ impl GoToLine {
     }
 
     fn point_from_query(&self, cx: &ViewContext<Self>) -> Option<Point> {
-        let line_editor = self.line_editor.read(cx).text(cx);
-        let mut components = line_editor
-            .splitn(2, FILE_ROW_COLUMN_DELIMITER)
-            .map(str::trim)
-            .fuse();
-        let row = components.next().and_then(|row| row.parse::<u32>().ok())?;
-        let column = components.next().and_then(|col| col.parse::<u32>().ok());
-        Some(Point::new(
-            row.saturating_sub(1),
-            column.unwrap_or(0).saturating_sub(1),
-        ))
+        let query = self.line_editor.read(cx).text(cx);
+        let cursor = self
+            .active_editor
+            .read(cx)
+            .selections
+            .last::<Point>(cx)
+            .head();
+        parse_query(&query, cursor.row)
     }
 
     fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
@@ -151,6 +152,25 @@ impl GoToLine {
     }
 }
 
+/// Parses `row`, `row:column`, or a row relative to the cursor, like `+10` or
+/// `-5`. Rows and columns in the query start at 1.
+fn parse_query(query: &str, cursor_row: u32) -> Option<Point> {
+    let mut components = query
+        .splitn(2, FILE_ROW_COLUMN_DELIMITER)
+        .map(str::trim)
+        .fuse();
+    let row = components.next()?;
+    let row = if let Some(offset) = row.strip_prefix('+') {
+        cursor_row.saturating_add(offset.parse().ok()?)
+    } else if let Some(offset) = row.strip_prefix('-') {
+        cursor_row.saturating_sub(offset.parse().ok()?)
+    } else {
+        row.parse::<u32>().ok()?.saturating_sub(1)
+    };
+    let column = components.next().and_then(|col| col.parse::<u32>().ok());
+    Some(Point::new(row, column.unwrap_or(0).saturating_sub(1)))
+}
+
 impl Render for GoToLine {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         div()
@@ -186,3 +206,22 @@ impl Render for GoToLine {
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(parse_query("12", 4), Some(Point::new(11, 0)));
+        assert_eq!(parse_query("12:5", 4), Some(Point::new(11, 4)));
+        assert_eq!(parse_query(" 12 : 5 ", 4), Some(Point::new(11, 4)));
+        assert_eq!(parse_query("+10", 4), Some(Point::new(14, 0)));
+        assert_eq!(parse_query("-3:2", 4), Some(Point::new(1, 1)));
+        assert_eq!(parse_query("-10", 4), Some(Point::new(0, 0)));
+        assert_eq!(parse_query("0", 4), Some(Point::new(0, 0)));
+        assert_eq!(parse_query("", 4), None);
+        assert_eq!(parse_query("+", 4), None);
+        assert_eq!(parse_query("abc", 4), None);
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     any::{type_name, TypeId},
     cell::{Ref, RefCell, RefMut},
     marker::PhantomData,
+    mem,
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
@@ -24,6 +25,7 @@ use refineable::Refineable;
 #[cfg(any(test, feature = "test-support"))]
 pub use test_context::*;
 use util::{
+    asset_cache::AssetCache,
     http::{self, HttpClient},
     ResultExt,
 };
@@ -132,6 +134,12 @@ impl App {
         self
     }
 
+    /// Keep images downloaded by `img` elements in the given disk cache.
+    pub fn with_image_disk_cache(self, disk_cache: &'static AssetCache) -> Self {
+        self.0.borrow_mut().image_cache.set_disk_cache(disk_cache);
+        self
+    }
+
     /// Start the application. The provided callback will be called once the
     /// app is fully launched.
     pub fn run<F>(self, on_finish_launching: F)
@@ -242,6 +250,8 @@ pub struct AppContext {
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
+    low_power_mode: bool,
+    after_frame_callbacks: Vec<Box<dyn FnOnce(&mut AppContext)>>,
 }
 
 impl AppContext {
@@ -301,6 +311,8 @@ impl AppContext {
                 quit_observers: SubscriberSet::new(),
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
+                low_power_mode: false,
+                after_frame_callbacks: Vec::new(),
             }),
         });
 
@@ -566,6 +578,12 @@ impl AppContext {
         self.platform.open_url(url);
     }
 
+    /// Shows a notification in the system's notification center, which is seen even when
+    /// the app isn't active.
+    pub fn post_notification(&self, title: &str, body: &str) {
+        self.platform.post_notification(title, body);
+    }
+
     /// Returns the full pathname of the current app bundle.
     /// If the app is not being run from a bundle, returns an error.
     pub fn app_path(&self) -> Result<PathBuf> {
@@ -610,6 +628,21 @@ impl AppContext {
         self.platform.should_auto_hide_scrollbars()
     }
 
+    /// Returns whether the computer is currently running on battery power.
+    pub fn is_on_battery_power(&self) -> bool {
+        self.platform.is_on_battery_power()
+    }
+
+    /// Returns whether windows render less often while they aren't focused, to save power.
+    pub fn low_power_mode(&self) -> bool {
+        self.low_power_mode
+    }
+
+    /// Sets whether windows render less often while they aren't focused, to save power.
+    pub fn set_low_power_mode(&mut self, low_power_mode: bool) {
+        self.low_power_mode = low_power_mode;
+    }
+
     /// Restart the application.
     pub fn restart(&self) {
         self.platform.restart()
@@ -814,6 +847,36 @@ impl AppContext {
         });
     }
 
+    /// Schedules the given function to be run once the active window has presented the changes
+    /// made so far, so that work which isn't needed to draw them doesn't delay the frame. If the
+    /// active window has nothing to redraw, the function runs on the next turn of the main thread.
+    pub fn after_next_frame(&mut self, f: impl FnOnce(&mut AppContext) + 'static) {
+        self.after_frame_callbacks.push(Box::new(f));
+        if self.after_frame_callbacks.len() == 1 {
+            self.spawn(|cx| async move {
+                cx.update(|cx| {
+                    if !cx.active_window_is_dirty() {
+                        cx.run_after_frame_callbacks();
+                    }
+                })
+                .ok();
+            })
+            .detach();
+        }
+    }
+
+    pub(crate) fn run_after_frame_callbacks(&mut self) {
+        for callback in mem::take(&mut self.after_frame_callbacks) {
+            callback(self);
+        }
+    }
+
+    fn active_window_is_dirty(&self) -> bool {
+        self.active_window()
+            .and_then(|handle| self.windows.get(handle.id)?.as_ref())
+            .map_or(false, |window| window.dirty.get())
+    }
+
     /// Accessor for the application's asset source, which is provided when constructing the `App`.
     pub fn asset_source(&self) -> &Arc<dyn AssetSource> {
         &self.asset_source
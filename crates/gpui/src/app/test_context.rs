@@ -8,6 +8,7 @@ use crate::{
 };
 use anyhow::{anyhow, bail};
 use futures::{Stream, StreamExt};
+use rand::rngs::StdRng;
 use std::{cell::RefCell, future::Future, ops::Deref, rc::Rc, sync::Arc, time::Duration};
 
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
@@ -242,6 +243,17 @@ impl TestAppContext {
         self.test_platform.opened_url.borrow().clone()
     }
 
+    /// The titles and bodies of the notifications posted with cx.post_notification()
+    /// during this test.
+    pub fn posted_notifications(&self) -> Vec<(String, String)> {
+        self.test_platform.posted_notifications.borrow().clone()
+    }
+
+    /// Simulates the computer switching between battery and AC power.
+    pub fn simulate_battery_power(&self, on_battery_power: bool) {
+        self.test_platform.on_battery_power.set(on_battery_power);
+    }
+
     /// Simulates the user resizing the window to the new size.
     pub fn simulate_window_resize(&self, window_handle: AnyWindowHandle, size: Size<Pixels>) {
         self.test_window(window_handle).simulate_resize(size);
@@ -313,6 +325,26 @@ impl TestAppContext {
         self.background_executor.run_until_parked()
     }
 
+    /// Runs a single task, chosen by the test's seed. Returns false if there was nothing to run.
+    /// Together with `run_until_parked`, this lets tests interleave the work of several
+    /// contexts deterministically.
+    pub fn tick(&mut self) -> bool {
+        self.background_executor.tick()
+    }
+
+    /// Moves the simulated clock forward, firing any timers that become due and running
+    /// the tasks they wake until there is no more work to do.
+    pub fn advance_clock(&mut self, duration: Duration) {
+        self.background_executor.advance_clock(duration)
+    }
+
+    /// Returns a copy of the random number generator that decides the order in which tasks
+    /// run. It is seeded by the `SEED` environment variable, so the same seed yields the
+    /// same choices.
+    pub fn rng(&self) -> StdRng {
+        self.background_executor.rng()
+    }
+
     /// Simulate dispatching an action to the currently focused node in the window.
     pub fn dispatch_action<A>(&mut self, window: AnyWindowHandle, action: A)
     where
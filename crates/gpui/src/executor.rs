@@ -321,7 +321,7 @@ impl BackgroundExecutor {
         self.dispatcher.as_test().unwrap().deprioritize(task_label)
     }
 
-    /// in tests, move time forward. This does not run any tasks, but does make `timer`s ready.
+    /// in tests, move time forward, running the tasks whose timers become ready along the way.
     #[cfg(any(test, feature = "test-support"))]
     pub fn advance_clock(&self, duration: Duration) {
         self.dispatcher.as_test().unwrap().advance_clock(duration)
@@ -399,6 +399,25 @@ impl ForegroundExecutor {
         }
         inner::<R>(dispatcher, Box::pin(future))
     }
+
+    /// in tests, run one task, which may be a background task.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn tick(&self) -> bool {
+        self.dispatcher.as_test().unwrap().tick(false)
+    }
+
+    /// in tests, run all tasks that are ready to run, both on the main thread and in the
+    /// background. See [`BackgroundExecutor::run_until_parked`].
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn run_until_parked(&self) {
+        self.dispatcher.as_test().unwrap().run_until_parked()
+    }
+
+    /// in tests, move time forward, running the tasks whose timers become ready along the way.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn advance_clock(&self, duration: Duration) {
+        self.dispatcher.as_test().unwrap().advance_clock(duration)
+    }
 }
 
 /// Scope manages a set of tasks that are enqueued and waited on together. See [`BackgroundExecutor::scoped`].
@@ -453,3 +472,56 @@ impl<'a> Drop for Scope<'a> {
         self.executor.block(self.rx.next());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{self as gpui, BackgroundExecutor, TestAppContext, TestDispatcher};
+    use parking_lot::Mutex;
+    use rand::{rngs::StdRng, SeedableRng as _};
+    use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
+
+    #[gpui::test]
+    fn test_advance_clock(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        for id in 0..2 {
+            let events = events.clone();
+            let executor = cx.executor();
+            cx.foreground_executor()
+                .spawn(async move {
+                    events.borrow_mut().push((id, "started"));
+                    executor.timer(Duration::from_secs(1)).await;
+                    events.borrow_mut().push((id, "finished"));
+                })
+                .detach();
+        }
+
+        cx.foreground_executor().run_until_parked();
+        assert_eq!(events.borrow().len(), 2);
+        cx.advance_clock(Duration::from_millis(999));
+        assert_eq!(events.borrow().len(), 2);
+        // The executor's clock runs the woken tasks too.
+        cx.executor().advance_clock(Duration::from_millis(1));
+        assert_eq!(events.borrow().len(), 4);
+    }
+
+    #[test]
+    fn test_seeded_scheduling_order() {
+        fn run_order(seed: u64) -> Vec<usize> {
+            let executor =
+                BackgroundExecutor::new(Arc::new(TestDispatcher::new(StdRng::seed_from_u64(seed))));
+            let order = Arc::new(Mutex::new(Vec::new()));
+            for id in 0..20 {
+                let order = order.clone();
+                executor
+                    .spawn(async move { order.lock().push(id) })
+                    .detach();
+            }
+            executor.run_until_parked();
+            let order = order.lock().clone();
+            order
+        }
+
+        assert_eq!(run_order(1), run_order(1));
+        assert_ne!(run_order(1), run_order(2));
+    }
+}
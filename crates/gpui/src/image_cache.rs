@@ -6,7 +6,10 @@ use parking_lot::Mutex;
 use std::path::PathBuf;
 use std::sync::Arc;
 use thiserror::Error;
-use util::http::{self, HttpClient};
+use util::{
+    asset_cache::{self, AssetCache},
+    http::{self, HttpClient},
+};
 
 #[derive(PartialEq, Eq, Hash, Clone)]
 pub(crate) struct RenderImageParams {
@@ -26,6 +29,8 @@ pub(crate) enum Error {
     },
     #[error("image error: {0}")]
     Image(Arc<ImageError>),
+    #[error("{0}")]
+    Download(Arc<anyhow::Error>),
 }
 
 impl From<std::io::Error> for Error {
@@ -42,6 +47,7 @@ impl From<ImageError> for Error {
 
 pub(crate) struct ImageCache {
     client: Arc<dyn HttpClient>,
+    disk_cache: Option<&'static AssetCache>,
     images: Arc<Mutex<HashMap<UriOrPath, FetchImageTask>>>,
 }
 
@@ -69,10 +75,17 @@ impl ImageCache {
     pub fn new(client: Arc<dyn HttpClient>) -> Self {
         ImageCache {
             client,
+            disk_cache: None,
             images: Default::default(),
         }
     }
 
+    /// Keeps downloaded images on disk, so they aren't downloaded again
+    /// every time the app starts.
+    pub fn set_disk_cache(&mut self, disk_cache: &'static AssetCache) {
+        self.disk_cache = Some(disk_cache);
+    }
+
     pub fn get(&self, uri_or_path: impl Into<UriOrPath>, cx: &AppContext) -> FetchImageTask {
         let uri_or_path = uri_or_path.into();
         let mut images = self.images.lock();
@@ -81,6 +94,10 @@ impl ImageCache {
             Some(future) => future.clone(),
             None => {
                 let client = self.client.clone();
+                let disk_cache = self.disk_cache;
+                let executor = cx.background_executor().clone();
+                let timer: http::RetryTimer =
+                    Arc::new(move |duration| executor.timer(duration).boxed());
                 let future = cx
                     .background_executor()
                     .spawn(
@@ -93,17 +110,38 @@ impl ImageCache {
                                         Ok(Arc::new(ImageData::new(image)))
                                     }
                                     UriOrPath::Uri(uri) => {
-                                        let mut response =
-                                            client.get(uri.as_ref(), ().into(), true).await?;
-                                        let mut body = Vec::new();
-                                        response.body_mut().read_to_end(&mut body).await?;
-
-                                        if !response.status().is_success() {
-                                            return Err(Error::BadStatus {
-                                                status: response.status(),
-                                                body: String::from_utf8_lossy(&body).into_owned(),
-                                            });
-                                        }
+                                        let body = if let Some(disk_cache) = disk_cache {
+                                            disk_cache
+                                                .fetch_bytes_with_max_age(
+                                                    uri.as_ref(),
+                                                    client.as_ref(),
+                                                    // The same URL can serve a new image, e.g.
+                                                    // when someone changes their avatar.
+                                                    asset_cache::DEFAULT_MAX_AGE,
+                                                )
+                                                .await
+                                                .map_err(|error| Error::Download(Arc::new(error)))?
+                                        } else {
+                                            let mut response = client
+                                                .get_with_retries(
+                                                    uri.as_ref(),
+                                                    Some(http::DEFAULT_REQUEST_TIMEOUT),
+                                                    http::RetryPolicy::default(),
+                                                    timer,
+                                                )
+                                                .await?;
+                                            let mut body = Vec::new();
+                                            response.body_mut().read_to_end(&mut body).await?;
+
+                                            if !response.status().is_success() {
+                                                return Err(Error::BadStatus {
+                                                    status: response.status(),
+                                                    body: String::from_utf8_lossy(&body)
+                                                        .into_owned(),
+                                                });
+                                            }
+                                            body
+                                        };
 
                                         let format = image::guess_format(&body)?;
                                         let image =
@@ -95,6 +95,7 @@ pub(crate) trait Platform: 'static {
     fn window_appearance(&self) -> WindowAppearance;
 
     fn open_url(&self, url: &str);
+    fn post_notification(&self, title: &str, body: &str);
     fn on_open_urls(&self, callback: Box<dyn FnMut(Vec<String>)>);
     fn prompt_for_paths(
         &self,
@@ -124,6 +125,7 @@ pub(crate) trait Platform: 'static {
 
     fn set_cursor_style(&self, style: CursorStyle);
     fn should_auto_hide_scrollbars(&self) -> bool;
+    fn is_on_battery_power(&self) -> bool;
 
     fn write_to_clipboard(&self, item: ClipboardItem);
     fn read_from_clipboard(&self) -> Option<ClipboardItem>;
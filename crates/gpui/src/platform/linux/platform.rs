@@ -218,6 +218,9 @@ impl Platform for LinuxPlatform {
         open::that(url);
     }
 
+    //todo!(linux)
+    fn post_notification(&self, title: &str, body: &str) {}
+
     fn on_open_urls(&self, callback: Box<dyn FnMut(Vec<String>)>) {
         self.inner.callbacks.lock().open_urls = Some(callback);
     }
@@ -384,6 +387,18 @@ impl Platform for LinuxPlatform {
         false
     }
 
+    fn is_on_battery_power(&self) -> bool {
+        // A battery only reports that it's discharging when no AC adapter is supplying power.
+        let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+            return false;
+        };
+        supplies.flatten().any(|supply| {
+            let read = |name: &str| std::fs::read_to_string(supply.path().join(name));
+            read("type").map_or(false, |kind| kind.trim() == "Battery")
+                && read("status").map_or(false, |status| status.trim() == "Discharging")
+        })
+    }
+
     //todo!(linux)
     fn write_to_clipboard(&self, item: ClipboardItem) {}
 
@@ -4,6 +4,7 @@ mod dispatcher;
 mod display;
 mod display_link;
 mod events;
+mod notifications;
 
 #[cfg(not(feature = "macos-blade"))]
 mod metal_atlas;
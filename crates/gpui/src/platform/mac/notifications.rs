@@ -0,0 +1,25 @@
+use cocoa::{
+    base::{id, nil},
+    foundation::{NSAutoreleasePool, NSString},
+};
+use objc::{class, msg_send, sel, sel_impl};
+
+/// Delivers a notification to the user notification center, which shows it as a banner
+/// and keeps it in the notification list until it's dismissed.
+pub(crate) fn post_notification(title: &str, body: &str) {
+    unsafe {
+        let notification: id = msg_send![class!(NSUserNotification), alloc];
+        let notification: id = msg_send![notification, init];
+        let notification = notification.autorelease();
+        let title = NSString::alloc(nil).init_str(title).autorelease();
+        let body = NSString::alloc(nil).init_str(body).autorelease();
+        let _: () = msg_send![notification, setTitle: title];
+        let _: () = msg_send![notification, setInformativeText: body];
+
+        let center: id = msg_send![
+            class!(NSUserNotificationCenter),
+            defaultUserNotificationCenter
+        ];
+        let _: () = msg_send![center, deliverNotification: notification];
+    }
+}
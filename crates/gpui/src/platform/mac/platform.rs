@@ -1,4 +1,4 @@
-use super::{events::key_to_native, BoolExt};
+use super::{events::key_to_native, notifications, BoolExt};
 use crate::{
     Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
     ForegroundExecutor, Keymap, MacDispatcher, MacDisplay, MacTextSystem, MacWindow, Menu,
@@ -526,6 +526,10 @@ impl Platform for MacPlatform {
         self.0.lock().open_urls = Some(callback);
     }
 
+    fn post_notification(&self, title: &str, body: &str) {
+        notifications::post_notification(title, body);
+    }
+
     fn prompt_for_paths(
         &self,
         options: PathPromptOptions,
@@ -786,6 +790,21 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn is_on_battery_power(&self) -> bool {
+        unsafe {
+            let snapshot = power_sources::IOPSCopyPowerSourcesInfo();
+            if snapshot.is_null() {
+                return false;
+            }
+            let snapshot = CFType::wrap_under_create_rule(snapshot);
+            let source_type =
+                power_sources::IOPSGetProvidingPowerSourceType(snapshot.as_CFTypeRef());
+            !source_type.is_null()
+                && CFString::wrap_under_get_rule(source_type).to_string()
+                    == power_sources::kIOPSBatteryPowerValue
+        }
+    }
+
     fn write_to_clipboard(&self, item: ClipboardItem) {
         let state = self.0.lock();
         unsafe {
@@ -1179,6 +1198,19 @@ mod security {
     pub const errSecItemNotFound: OSStatus = -25300;
 }
 
+mod power_sources {
+    #![allow(non_upper_case_globals)]
+    use super::*;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        pub fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+        pub fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+    }
+
+    pub const kIOPSBatteryPowerValue: &str = "Battery Power";
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ClipboardItem;
@@ -8,7 +8,7 @@ use collections::VecDeque;
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     path::PathBuf,
     rc::{Rc, Weak},
     sync::Arc,
@@ -26,6 +26,8 @@ pub(crate) struct TestPlatform {
     current_clipboard_item: Mutex<Option<ClipboardItem>>,
     pub(crate) prompts: RefCell<TestPrompts>,
     pub opened_url: RefCell<Option<String>>,
+    pub(crate) posted_notifications: RefCell<Vec<(String, String)>>,
+    pub(crate) on_battery_power: Cell<bool>,
     weak: Weak<Self>,
 }
 
@@ -47,6 +49,8 @@ impl TestPlatform {
             current_clipboard_item: Mutex::new(None),
             weak: weak.clone(),
             opened_url: Default::default(),
+            posted_notifications: Default::default(),
+            on_battery_power: Cell::new(false),
         })
     }
 
@@ -194,6 +198,12 @@ impl Platform for TestPlatform {
         *self.opened_url.borrow_mut() = Some(url.to_string())
     }
 
+    fn post_notification(&self, title: &str, body: &str) {
+        self.posted_notifications
+            .borrow_mut()
+            .push((title.to_string(), body.to_string()));
+    }
+
     fn on_open_urls(&self, _callback: Box<dyn FnMut(Vec<String>)>) {
         unimplemented!()
     }
@@ -275,6 +285,10 @@ impl Platform for TestPlatform {
         false
     }
 
+    fn is_on_battery_power(&self) -> bool {
+        self.on_battery_power.get()
+    }
+
     fn write_to_clipboard(&self, item: ClipboardItem) {
         *self.current_clipboard_item.lock() = Some(item);
     }
@@ -39,6 +39,12 @@ pub use element_cx::*;
 
 const ACTIVE_DRAG_Z_INDEX: u16 = 1;
 
+/// The minimum time between frames drawn for unfocused windows in low power mode.
+const LOW_POWER_UNFOCUSED_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Keystrokes that take longer than a frame at 60Hz to reach the screen are logged.
+const INPUT_LATENCY_BUDGET: Duration = Duration::from_millis(16);
+
 /// A global stacking order, which is created by stacking successive z-index values.
 /// Each z-index will always be interpreted in the context of its parent z-index.
 #[derive(Debug, Deref, DerefMut, Clone, Ord, PartialOrd, PartialEq, Eq, Default)]
@@ -281,6 +287,39 @@ pub struct Window {
     focus_enabled: bool,
     pending_input: Option<PendingInput>,
     graphics_profiler_enabled: bool,
+    unpresented_keystroke_timestamp: Option<Instant>,
+    input_latency: InputLatency,
+}
+
+/// How long it takes for the effects of keystrokes to reach the screen, measured from when a
+/// key is pressed until the next frame is presented.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputLatency {
+    /// The number of keystrokes that have been measured.
+    pub samples: usize,
+    /// The latency of the most recent keystroke.
+    pub last: Duration,
+    /// The longest latency of any keystroke.
+    pub max: Duration,
+    total: Duration,
+}
+
+impl InputLatency {
+    /// The average latency of the measured keystrokes.
+    pub fn mean(&self) -> Duration {
+        if self.samples == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.samples as u32
+        }
+    }
+
+    fn record(&mut self, latency: Duration) {
+        self.samples += 1;
+        self.last = latency;
+        self.max = self.max.max(latency);
+        self.total += latency;
+    }
 }
 
 #[derive(Default, Debug)]
@@ -356,6 +395,7 @@ impl Window {
             let needs_present = needs_present.clone();
             let next_frame_callbacks = next_frame_callbacks.clone();
             let last_input_timestamp = last_input_timestamp.clone();
+            let mut last_frame_timestamp = Instant::now();
             move || {
                 let next_frame_callbacks = next_frame_callbacks.take();
                 if !next_frame_callbacks.is_empty() {
@@ -374,7 +414,14 @@ impl Window {
                     || (active.get()
                         && last_input_timestamp.get().elapsed() < Duration::from_secs(1));
 
-                if dirty.get() {
+                // In low power mode, draw unfocused windows at a reduced frame rate. A throttled
+                // window stays dirty, so it's drawn on a later frame.
+                let throttled = !active.get()
+                    && last_frame_timestamp.elapsed() < LOW_POWER_UNFOCUSED_FRAME_INTERVAL
+                    && cx.update(|cx| cx.low_power_mode()).unwrap_or(false);
+
+                if dirty.get() && !throttled {
+                    last_frame_timestamp = Instant::now();
                     measure("frame duration", || {
                         handle
                             .update(&mut cx, |_, cx| {
@@ -386,6 +433,11 @@ impl Window {
                 } else if needs_present {
                     handle.update(&mut cx, |_, cx| cx.present()).log_err();
                 }
+
+                // Work that was deferred until the active window caught up with it can run now.
+                if active.get() {
+                    cx.update(|cx| cx.run_after_frame_callbacks()).log_err();
+                }
             }
         }));
         platform_window.on_resize(Box::new({
@@ -475,6 +527,8 @@ impl Window {
             focus_enabled: true,
             pending_input: None,
             graphics_profiler_enabled: false,
+            unpresented_keystroke_timestamp: None,
+            input_latency: InputLatency::default(),
         }
     }
     fn new_focus_listener(
@@ -765,6 +819,16 @@ impl<'a> WindowContext<'a> {
         self.window.active.get()
     }
 
+    /// Returns when this window last received keyboard or mouse input.
+    pub fn last_input_timestamp(&self) -> Instant {
+        self.window.last_input_timestamp.get()
+    }
+
+    /// Returns how long keystrokes in this window have taken to reach the screen.
+    pub fn input_latency(&self) -> &InputLatency {
+        &self.window.input_latency
+    }
+
     /// Toggle zoom on the window.
     pub fn zoom_window(&self) {
         self.window.platform_window.zoom();
@@ -1094,11 +1158,18 @@ impl<'a> WindowContext<'a> {
     }
 
     #[profiling::function]
-    fn present(&self) {
+    fn present(&mut self) {
         self.window
             .platform_window
             .draw(&self.window.rendered_frame.scene);
         self.window.needs_present.set(false);
+        if let Some(keystroke_timestamp) = self.window.unpresented_keystroke_timestamp.take() {
+            let latency = keystroke_timestamp.elapsed();
+            self.window.input_latency.record(latency);
+            if latency > INPUT_LATENCY_BUDGET {
+                log::debug!("keystroke took {latency:?} to reach the screen");
+            }
+        }
         profiling::finish_frame!();
     }
 
@@ -1144,6 +1215,9 @@ impl<'a> WindowContext<'a> {
     #[profiling::function]
     pub fn dispatch_event(&mut self, event: PlatformInput) -> bool {
         self.window.last_input_timestamp.set(Instant::now());
+        let keystroke_timestamp = matches!(event, PlatformInput::KeyDown(_)).then(Instant::now);
+        let was_dirty = self.window.dirty.get();
+        let dirty_view_count = self.window.dirty_views.len();
         // Handlers may set this to false by calling `stop_propagation`.
         self.app.propagate_event = true;
         // Handlers may set this to true by calling `prevent_default`.
@@ -1232,6 +1306,18 @@ impl<'a> WindowContext<'a> {
             self.dispatch_key_event(any_key_event);
         }
 
+        // Only keystrokes that change what's on screen are measured, from the oldest one that
+        // hasn't been presented yet.
+        if let Some(keystroke_timestamp) = keystroke_timestamp {
+            let invalidated = (!was_dirty && self.window.dirty.get())
+                || self.window.dirty_views.len() > dirty_view_count;
+            if invalidated {
+                self.window
+                    .unpresented_keystroke_timestamp
+                    .get_or_insert(keystroke_timestamp);
+            }
+        }
+
         !self.app.propagate_event
     }
 
@@ -1959,6 +2045,21 @@ impl<'a, V: 'static> ViewContext<'a, V> {
         self.window_cx.on_next_frame(move |cx| view.update(cx, f));
     }
 
+    /// Schedules the given function to be run once the active window has presented the changes
+    /// made so far. See [`AppContext::after_next_frame`].
+    pub fn after_next_frame(&mut self, f: impl FnOnce(&mut V, &mut ViewContext<V>) + 'static)
+    where
+        V: 'static,
+    {
+        let view = self.view().downgrade();
+        let window_handle = self.window.handle;
+        self.window_cx.app.after_next_frame(move |cx| {
+            window_handle
+                .update(cx, |_, cx| view.update(cx, f).ok())
+                .ok();
+        });
+    }
+
     /// Schedules the given function to be run at the end of the current effect cycle, allowing entities
     /// that are currently on the stack to be returned to the app.
     pub fn defer(&mut self, f: impl FnOnce(&mut V, &mut ViewContext<V>) + 'static) {
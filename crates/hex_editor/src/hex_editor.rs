@@ -0,0 +1,553 @@
+use anyhow::{anyhow, Result};
+use editor::{Editor, EditorEvent};
+use gpui::{
+    actions, div, prelude::*, uniform_list, AnyElement, AppContext, EntityId, EventEmitter,
+    FocusHandle, FocusableView, KeyDownEvent, Model, Render, SharedString, Subscription, Task,
+    UniformListScrollHandle, View, ViewContext, VisualContext, WindowContext,
+};
+use project::{BinaryFile, BinaryFileEvent, Project};
+use settings::Settings;
+use std::path::PathBuf;
+use theme::{ActiveTheme, ThemeSettings};
+use ui::{h_flex, prelude::*, v_flex, Label};
+use util::ResultExt as _;
+use workspace::item::{Item, ItemEvent, ProjectItem};
+
+const BYTES_PER_ROW: usize = 16;
+
+actions!(
+    hex_editor,
+    [
+        MoveLeft,
+        MoveRight,
+        MoveUp,
+        MoveDown,
+        DeleteByte,
+        ToggleInputMode,
+        SelectNextMatch,
+        SelectPrevMatch
+    ]
+);
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item::<HexEditor>(cx);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InputMode {
+    Hex,
+    Ascii,
+}
+
+pub enum HexEditorEvent {
+    Edited,
+    Saved,
+    Conflict,
+}
+
+/// A workspace item that displays a binary file as offset, hex and ASCII columns.
+///
+/// Only the rows that are visible are rendered, and only their bytes are read
+/// from disk, so large files can be paged through without loading them.
+pub struct HexEditor {
+    file: Model<BinaryFile>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    query_editor: View<Editor>,
+    matches: Vec<usize>,
+    match_len: usize,
+    search_task: Option<Task<()>>,
+    cursor: usize,
+    pending_nibble: bool,
+    input_mode: InputMode,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl HexEditor {
+    pub fn new(file: Model<BinaryFile>, cx: &mut ViewContext<Self>) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Search bytes (7f 45 4c 46) or text", cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    this.update_matches(cx);
+                }
+            }),
+            cx.subscribe(&file, |this, _, event: &BinaryFileEvent, cx| match event {
+                BinaryFileEvent::Edited => cx.emit(HexEditorEvent::Edited),
+                BinaryFileEvent::Saved => cx.emit(HexEditorEvent::Saved),
+                BinaryFileEvent::Conflict => cx.emit(HexEditorEvent::Conflict),
+                BinaryFileEvent::Reloaded => {
+                    this.pending_nibble = false;
+                    this.cursor = this.cursor.min(this.file.read(cx).len());
+                    this.update_matches(cx);
+                }
+            }),
+            cx.observe(&file, |_, _, cx| cx.notify()),
+        ];
+
+        Self {
+            file,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            query_editor,
+            matches: Vec::new(),
+            match_len: 0,
+            search_task: None,
+            cursor: 0,
+            pending_nibble: false,
+            input_mode: InputMode::Hex,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn row_count(&self, cx: &AppContext) -> usize {
+        self.file.read(cx).len() / BYTES_PER_ROW + 1
+    }
+
+    fn move_cursor_to(&mut self, offset: usize, cx: &mut ViewContext<Self>) {
+        self.cursor = offset.min(self.file.read(cx).len());
+        self.pending_nibble = false;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW);
+        cx.notify();
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor + 1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(BYTES_PER_ROW), cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor + BYTES_PER_ROW, cx);
+    }
+
+    fn delete_byte(&mut self, _: &DeleteByte, cx: &mut ViewContext<Self>) {
+        let cursor = self.cursor;
+        self.file
+            .update(cx, |file, cx| file.remove_byte(cursor, cx));
+        self.move_cursor_to(cursor, cx);
+        self.update_matches(cx);
+    }
+
+    fn toggle_input_mode(&mut self, _: &ToggleInputMode, cx: &mut ViewContext<Self>) {
+        self.input_mode = match self.input_mode {
+            InputMode::Hex => InputMode::Ascii,
+            InputMode::Ascii => InputMode::Hex,
+        };
+        self.pending_nibble = false;
+        cx.notify();
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, cx: &mut ViewContext<Self>) {
+        let next = self
+            .matches
+            .iter()
+            .find(|offset| **offset > self.cursor)
+            .or_else(|| self.matches.first())
+            .copied();
+        if let Some(offset) = next {
+            self.move_cursor_to(offset, cx);
+        }
+    }
+
+    fn select_prev_match(&mut self, _: &SelectPrevMatch, cx: &mut ViewContext<Self>) {
+        let prev = self
+            .matches
+            .iter()
+            .rev()
+            .find(|offset| **offset < self.cursor)
+            .or_else(|| self.matches.last())
+            .copied();
+        if let Some(offset) = prev {
+            self.move_cursor_to(offset, cx);
+        }
+    }
+
+    fn update_matches(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let needle = parse_query(&query);
+        let match_len = needle.len();
+        let search = self.file.read(cx).search(needle, cx);
+        self.search_task = Some(cx.spawn(|this, mut cx| async move {
+            let Some(matches) = search.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.matches = matches;
+                this.match_len = match_len;
+                if let Some(offset) = this
+                    .matches
+                    .iter()
+                    .find(|offset| **offset >= this.cursor)
+                    .or_else(|| this.matches.first())
+                    .copied()
+                {
+                    this.move_cursor_to(offset, cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        // Keystrokes bubbling up from the search field shouldn't edit the file.
+        if !self.focus_handle.is_focused(cx) {
+            return;
+        }
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.command || keystroke.modifiers.control || keystroke.modifiers.alt {
+            return;
+        }
+        let input = match keystroke.ime_key.as_deref() {
+            Some(ime_key) => ime_key,
+            None if keystroke.key == "space" => " ",
+            None => keystroke.key.as_str(),
+        };
+        let mut chars = input.chars();
+        let (Some(input), None) = (chars.next(), chars.next()) else {
+            return;
+        };
+
+        let cursor = self.cursor;
+        let file = self.file.read(cx);
+        let current = if cursor == file.len() {
+            0
+        } else {
+            // The byte under the cursor hasn't been read from disk yet.
+            let Some(current) = file.read_range(cursor..cursor + 1) else {
+                return;
+            };
+            current.first().copied().unwrap_or(0)
+        };
+        match self.input_mode {
+            InputMode::Hex => {
+                let Some(digit) = input.to_digit(16).map(|digit| digit as u8) else {
+                    return;
+                };
+                if self.pending_nibble {
+                    let value = (current & 0xf0) | digit;
+                    self.file
+                        .update(cx, |file, cx| file.set_byte(cursor, value, cx));
+                    self.move_cursor_to(cursor + 1, cx);
+                } else {
+                    let value = (digit << 4) | (current & 0x0f);
+                    self.file
+                        .update(cx, |file, cx| file.set_byte(cursor, value, cx));
+                    self.pending_nibble = true;
+                    cx.notify();
+                }
+            }
+            InputMode::Ascii => {
+                if !input.is_ascii() || input.is_ascii_control() {
+                    return;
+                }
+                self.file
+                    .update(cx, |file, cx| file.set_byte(cursor, input as u8, cx));
+                self.move_cursor_to(cursor + 1, cx);
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    fn render_row(&self, row: usize, cx: &mut ViewContext<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+        let file = self.file.read(cx);
+        let start = row * BYTES_PER_ROW;
+        let end = (start + BYTES_PER_ROW).min(file.len());
+        let row_bytes = file.read_range(start..end);
+
+        let is_match = |offset: usize| {
+            let ix = self
+                .matches
+                .partition_point(|start| *start + self.match_len <= offset);
+            self.matches.get(ix).map_or(false, |start| *start <= offset)
+        };
+        let cell_background = |offset: usize, mode: InputMode| {
+            if offset == self.cursor && mode == self.input_mode {
+                Some(colors.element_selected)
+            } else if offset == self.cursor || is_match(offset) {
+                Some(colors.search_match_background)
+            } else {
+                None
+            }
+        };
+
+        let mut hex_column = h_flex().gap_1();
+        let mut ascii_column = h_flex();
+        for column in 0..BYTES_PER_ROW {
+            let offset = start + column;
+            let is_loaded = row_bytes.is_some();
+            let byte = row_bytes
+                .as_ref()
+                .and_then(|row_bytes| row_bytes.get(column).copied());
+            // Leave room for the cursor one past the end of the file, so bytes can be appended.
+            let is_visible = byte.is_some() || offset == self.cursor;
+            let (hex, ascii) = match byte {
+                Some(byte) if byte.is_ascii_graphic() || byte == b' ' => {
+                    (format!("{byte:02x}"), byte as char)
+                }
+                Some(byte) => (format!("{byte:02x}"), '.'),
+                // Bytes that are still being read from disk.
+                None if !is_loaded && offset < end => ("··".to_string(), '·'),
+                None => ("  ".to_string(), ' '),
+            };
+
+            hex_column = hex_column.child(
+                div()
+                    .when(column == BYTES_PER_ROW / 2, |this| this.ml_2())
+                    .when_some(
+                        is_visible
+                            .then(|| cell_background(offset, InputMode::Hex))
+                            .flatten(),
+                        |this, background| this.bg(background),
+                    )
+                    .child(hex),
+            );
+            ascii_column = ascii_column.child(
+                div()
+                    .when_some(
+                        is_visible
+                            .then(|| cell_background(offset, InputMode::Ascii))
+                            .flatten(),
+                        |this, background| this.bg(background),
+                    )
+                    .child(ascii.to_string()),
+            );
+        }
+
+        h_flex()
+            .id(row)
+            .gap_4()
+            .child(
+                div()
+                    .text_color(colors.editor_line_number)
+                    .child(format!("{start:08x}")),
+            )
+            .child(hex_column)
+            .child(ascii_column.text_color(colors.text_muted))
+            .into_any_element()
+    }
+
+    fn file_name(&self, cx: &AppContext) -> SharedString {
+        self.file
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+            .into()
+    }
+}
+
+/// Interprets a search query as a sequence of hex bytes when possible, e.g.
+/// `7f 45 4c 46` or `0x7f454c46`, and as UTF-8 text otherwise.
+fn parse_query(query: &str) -> Vec<u8> {
+    let digits = query
+        .trim()
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let is_hex = !digits.is_empty()
+        && digits.len() % 2 == 0
+        && digits.chars().all(|c| c.is_ascii_hexdigit());
+    if is_hex {
+        (0..digits.len())
+            .step_by(2)
+            .filter_map(|ix| u8::from_str_radix(&digits[ix..ix + 2], 16).ok())
+            .collect()
+    } else {
+        query.as_bytes().to_vec()
+    }
+}
+
+impl EventEmitter<HexEditorEvent> for HexEditor {}
+
+impl FocusableView for HexEditor {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HexEditor {
+    type Event = HexEditorEvent;
+
+    fn tab_content(&self, _: Option<usize>, selected: bool, cx: &WindowContext) -> AnyElement {
+        Label::new(self.file_name(cx))
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        Some(
+            self.file
+                .read(cx)
+                .abs_path()
+                .to_string_lossy()
+                .to_string()
+                .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("hex editor")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(EntityId, &dyn project::Item),
+    ) {
+        f(self.file.entity_id(), self.file.read(cx))
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.file.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.file.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(&mut self, _: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.file.update(cx, |file, cx| file.save(cx))
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: PathBuf,
+        _: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        Task::ready(Err(anyhow!("binary files can only be saved in place")))
+    }
+
+    fn reload(&mut self, _: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.file.update(cx, |file, cx| file.reload(cx))
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            HexEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            HexEditorEvent::Saved | HexEditorEvent::Conflict => f(ItemEvent::UpdateTab),
+        }
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = BinaryFile;
+
+    fn for_project_item(
+        _: Model<Project>,
+        file: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self::new(file, cx)
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let buffer_font = ThemeSettings::get_global(cx).buffer_font.family.clone();
+        let mode_label = match self.input_mode {
+            InputMode::Hex => "HEX",
+            InputMode::Ascii => "ASCII",
+        };
+        let match_label = if self.matches.is_empty() {
+            String::new()
+        } else {
+            format!("{} matches", self.matches.len())
+        };
+
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::delete_byte))
+            .on_action(cx.listener(Self::toggle_input_mode))
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_prev_match))
+            .on_key_down(cx.listener(Self::key_down))
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(div().flex_1().child(self.query_editor.clone()))
+                    .child(Label::new(match_label).color(Color::Muted))
+                    .child(Label::new(format!("{:08x}", self.cursor)).color(Color::Muted))
+                    .child(Label::new(mode_label).color(Color::Accent)),
+            )
+            .child(
+                div()
+                    .id("hex-view")
+                    .flex_1()
+                    .p_2()
+                    .font(buffer_font)
+                    .on_click(cx.listener(|this, _, cx| cx.focus(&this.focus_handle)))
+                    .child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "hex-rows",
+                            self.row_count(cx),
+                            |this, range, cx| {
+                                let byte_range =
+                                    range.start * BYTES_PER_ROW..range.end * BYTES_PER_ROW;
+                                this.file
+                                    .update(cx, |file, cx| file.load_range(byte_range, cx));
+                                range.map(|row| this.render_row(row, cx)).collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(parse_query("7f 45 4c 46"), vec![0x7f, 0x45, 0x4c, 0x46]);
+        assert_eq!(parse_query("0xdeadbeef"), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(parse_query("ELF"), b"ELF".to_vec());
+        assert_eq!(parse_query("abc"), b"abc".to_vec());
+        assert_eq!(parse_query(""), Vec::<u8>::new());
+    }
+}
@@ -22,12 +22,11 @@ use gpui::{AppContext, EventEmitter, HighlightStyle, ModelContext, Task, TaskLab
 use lazy_static::lazy_static;
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
-use similar::{ChangeTag, TextDiff};
 use smallvec::SmallVec;
 use smol::future::yield_now;
 use std::{
     any::Any,
-    cmp::{self, Ordering},
+    cmp::{self, Ordering, Reverse},
     collections::BTreeMap,
     ffi::OsStr,
     future::Future,
@@ -65,6 +64,10 @@ lazy_static! {
     pub static ref BUFFER_DIFF_TASK: TaskLabel = TaskLabel::new();
 }
 
+/// The length, in bytes, above which a buffer is always reparsed in the
+/// background after an edit, without blocking the main thread on the parse.
+pub const MAX_SYNC_PARSE_LEN: usize = 256 * 1024;
+
 /// Indicate whether a [Buffer] has permissions to edit.
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Capability {
@@ -89,6 +92,14 @@ pub struct Buffer {
     saved_version: clock::Global,
     /// A hash of the current contents of the buffer's file.
     file_fingerprint: RopeFingerprint,
+    /// The encoding that the buffer will be saved with.
+    encoding: Encoding,
+    /// The encoding of the buffer's file when it was last loaded
+    /// from or saved to disk.
+    saved_encoding: Encoding,
+    /// The line ending of the buffer's file when it was last loaded
+    /// from or saved to disk.
+    saved_line_ending: LineEnding,
     transaction_depth: usize,
     was_dirty_before_starting_transaction: Option<bool>,
     reload_task: Option<Task<Result<()>>>,
@@ -99,6 +110,7 @@ pub struct Buffer {
     syntax_map: Mutex<SyntaxMap>,
     parsing_in_background: bool,
     parse_count: usize,
+    cached_outline: Mutex<Option<CachedOutline>>,
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
     selections_update_count: usize,
@@ -110,6 +122,16 @@ pub struct Buffer {
     completion_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
     capability: Capability,
+    large_file: bool,
+}
+
+/// The outline of a buffer as of a given version, along with the ranges whose
+/// syntax changed in the reparses since, so that it can be updated incrementally.
+struct CachedOutline {
+    version: clock::Global,
+    syntax_changed_ranges: Vec<Range<Anchor>>,
+    theme: Option<Arc<SyntaxTheme>>,
+    items: Vec<OutlineItem<Anchor>>,
 }
 
 /// An immutable, cheaply cloneable representation of a fixed
@@ -339,6 +361,11 @@ pub enum Event {
     Reparsed,
     /// The buffer's diagnostics were updated.
     DiagnosticsUpdated,
+    /// A remote replica's selections were updated.
+    RemoteSelectionsChanged {
+        /// The replica whose selections changed.
+        replica_id: ReplicaId,
+    },
     /// The buffer gained or lost editing capabilities.
     CapabilityChanged,
     /// The buffer was explicitly requested to close.
@@ -393,16 +420,20 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file.
     fn abs_path(&self, cx: &AppContext) -> PathBuf;
 
-    /// Loads the file's contents from disk.
-    fn load(&self, cx: &AppContext) -> Task<Result<String>>;
+    /// Loads the file's contents from disk, decoding them with the given
+    /// encoding, or with a detected one if no encoding is given.
+    fn load(&self, encoding: Option<Encoding>, cx: &AppContext)
+        -> Task<Result<(String, Encoding)>>;
 
     /// Called when the buffer is reloaded from disk.
+    #[allow(clippy::too_many_arguments)]
     fn buffer_reloaded(
         &self,
         buffer_id: BufferId,
         version: &clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: SystemTime,
         cx: &mut AppContext,
     );
@@ -570,6 +601,9 @@ impl Buffer {
             rpc::proto::LineEnding::from_i32(message.line_ending)
                 .ok_or_else(|| anyhow!("missing line_ending"))?,
         ));
+        this.saved_line_ending = this.line_ending();
+        this.encoding = proto::deserialize_encoding(message.encoding.as_deref());
+        this.saved_encoding = this.encoding;
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.file_fingerprint = proto::deserialize_fingerprint(&message.saved_version_fingerprint)?;
         this.saved_mtime = message
@@ -590,6 +624,7 @@ impl Buffer {
             saved_version: proto::serialize_version(&self.saved_version),
             saved_version_fingerprint: proto::serialize_fingerprint(self.file_fingerprint),
             saved_mtime: Some(self.saved_mtime.into()),
+            encoding: Some(proto::serialize_encoding(self.encoding)),
         }
     }
 
@@ -646,6 +681,13 @@ impl Buffer {
         self
     }
 
+    /// Assign the encoding that the buffer's file was loaded with, returning the buffer.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self.saved_encoding = encoding;
+        self
+    }
+
     /// Returns the [Capability] of this buffer.
     pub fn capability(&self) -> Capability {
         self.capability
@@ -673,6 +715,9 @@ impl Buffer {
             saved_mtime,
             saved_version: buffer.version(),
             file_fingerprint: buffer.as_rope().fingerprint(),
+            encoding: Encoding::default(),
+            saved_encoding: Encoding::default(),
+            saved_line_ending: buffer.line_ending(),
             reload_task: None,
             transaction_depth: 0,
             was_dirty_before_starting_transaction: None,
@@ -681,9 +726,11 @@ impl Buffer {
             git_diff: git::diff::BufferDiff::new(),
             file,
             capability,
+            large_file: false,
             syntax_map: Mutex::new(SyntaxMap::new()),
             parsing_in_background: false,
             parse_count: 0,
+            cached_outline: Default::default(),
             sync_parse_timeout: Duration::from_millis(1),
             autoindent_requests: Default::default(),
             pending_autoindent: Default::default(),
@@ -760,6 +807,7 @@ impl Buffer {
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut ModelContext<Self>) {
         self.parse_count += 1;
         self.syntax_map.lock().clear();
+        *self.cached_outline.get_mut() = None;
         self.language = language;
         self.reparse(cx);
         cx.emit(Event::LanguageChanged);
@@ -773,6 +821,19 @@ impl Buffer {
             .set_language_registry(language_registry);
     }
 
+    /// Whether this buffer was opened in large-file mode, in which features
+    /// that scale with the size of the file, such as syntax highlighting
+    /// and soft wrap, are disabled.
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
+    /// Marks this buffer as being too large for features that scale with the
+    /// size of the file.
+    pub fn set_large_file(&mut self, large_file: bool) {
+        self.large_file = large_file;
+    }
+
     /// Assign the buffer a new [Capability].
     pub fn set_capability(&mut self, capability: Capability, cx: &mut ModelContext<Self>) {
         self.capability = capability;
@@ -789,6 +850,8 @@ impl Buffer {
     ) {
         self.saved_version = version;
         self.file_fingerprint = fingerprint;
+        self.saved_encoding = self.encoding;
+        self.saved_line_ending = self.line_ending();
         self.saved_mtime = mtime;
         cx.emit(Event::Saved);
         cx.notify();
@@ -798,19 +861,28 @@ impl Buffer {
     pub fn reload(
         &mut self,
         cx: &mut ModelContext<Self>,
+    ) -> oneshot::Receiver<Option<Transaction>> {
+        self.reload_with_encoding(self.saved_encoding, cx)
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them with the given encoding.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
     ) -> oneshot::Receiver<Option<Transaction>> {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
             let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.mtime(), file.load(cx)))
+                Some((file.mtime(), file.load(Some(encoding), cx)))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let (new_text, new_encoding) = new_text.await?;
             let diff = this
                 .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
@@ -824,6 +896,7 @@ impl Buffer {
                         this.version(),
                         this.as_rope().fingerprint(),
                         this.line_ending(),
+                        new_encoding,
                         new_mtime,
                         cx,
                     );
@@ -832,6 +905,7 @@ impl Buffer {
                         prev_version,
                         Rope::text_fingerprint(&new_text),
                         this.line_ending(),
+                        new_encoding,
                         this.saved_mtime,
                         cx,
                     );
@@ -849,12 +923,16 @@ impl Buffer {
         version: clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: SystemTime,
         cx: &mut ModelContext<Self>,
     ) {
         self.saved_version = version;
         self.file_fingerprint = fingerprint;
         self.text.set_line_ending(line_ending);
+        self.saved_line_ending = line_ending;
+        self.encoding = encoding;
+        self.saved_encoding = encoding;
         self.saved_mtime = mtime;
         if let Some(file) = self.file.as_ref().and_then(|f| f.as_local()) {
             file.buffer_reloaded(
@@ -862,6 +940,7 @@ impl Buffer {
                 &self.saved_version,
                 self.file_fingerprint,
                 self.line_ending(),
+                self.encoding,
                 self.saved_mtime,
                 cx,
             );
@@ -964,6 +1043,55 @@ impl Buffer {
         self.parse_count
     }
 
+    /// Returns the outline for the buffer.
+    ///
+    /// The outline is cached, so that afterwards only the regions that were
+    /// edited or whose syntax changed need to be queried again.
+    pub fn outline(&self, theme: Option<&Arc<SyntaxTheme>>) -> Option<Outline<Anchor>> {
+        let snapshot = self.snapshot();
+        let mut cached_outline = self.cached_outline.lock();
+        let cached_outline_for_theme =
+            cached_outline
+                .take()
+                .filter(|cached| match (&cached.theme, theme) {
+                    (Some(cached_theme), Some(theme)) => Arc::ptr_eq(cached_theme, theme),
+                    (None, None) => true,
+                    _ => false,
+                });
+        let items = match cached_outline_for_theme {
+            Some(mut cached) => {
+                let stale_ranges = cached
+                    .syntax_changed_ranges
+                    .iter()
+                    .map(|range| range.to_offset(&snapshot))
+                    .chain(
+                        snapshot
+                            .edits_since::<usize>(&cached.version)
+                            .map(|edit| edit.new),
+                    )
+                    .collect();
+                snapshot.refresh_outline_items(
+                    &mut cached.items,
+                    stale_ranges,
+                    theme.map(AsRef::as_ref),
+                )?;
+                cached.items
+            }
+            None => snapshot.outline_items_containing(
+                0..snapshot.len(),
+                true,
+                theme.map(AsRef::as_ref),
+            )?,
+        };
+        *cached_outline = Some(CachedOutline {
+            version: snapshot.version().clone(),
+            syntax_changed_ranges: Vec::new(),
+            theme: theme.cloned(),
+            items: items.clone(),
+        });
+        Some(Outline::new(items))
+    }
+
     /// The number of times selections were updated.
     pub fn selections_update_count(&self) -> usize {
         self.selections_update_count
@@ -1013,7 +1141,9 @@ impl Buffer {
     ///
     /// Meanwhile, in the foreground, we block the main thread for up to 1ms
     /// waiting on the parse to complete. As soon as it completes, we proceed
-    /// synchronously, unless a 1ms timeout elapses.
+    /// synchronously, unless a 1ms timeout elapses. Buffers longer than
+    /// [`MAX_SYNC_PARSE_LEN`] are rarely parsed within the timeout, so we don't
+    /// wait on them at all.
     ///
     /// If we time out waiting on the parse, we spawn a second task waiting
     /// until the parse does complete and return with the interpolated tree still
@@ -1052,10 +1182,13 @@ impl Buffer {
             }
         });
 
-        match cx
-            .background_executor()
-            .block_with_timeout(self.sync_parse_timeout, parse_task)
-        {
+        let parse_result = if text.len() > MAX_SYNC_PARSE_LEN {
+            Err(parse_task)
+        } else {
+            cx.background_executor()
+                .block_with_timeout(self.sync_parse_timeout, parse_task)
+        };
+        match parse_result {
             Ok(new_syntax_snapshot) => {
                 self.did_finish_parsing(new_syntax_snapshot, cx);
                 return;
@@ -1092,6 +1225,11 @@ impl Buffer {
 
     fn did_finish_parsing(&mut self, syntax_snapshot: SyntaxSnapshot, cx: &mut ModelContext<Self>) {
         self.parse_count += 1;
+        if let Some(cached_outline) = self.cached_outline.get_mut() {
+            cached_outline
+                .syntax_changed_ranges
+                .extend_from_slice(syntax_snapshot.changed_ranges());
+        }
         self.syntax_map.lock().did_parse(syntax_snapshot);
         self.request_autoindent(cx);
         cx.emit(Event::Reparsed);
@@ -1376,59 +1514,18 @@ impl Buffer {
                 let line_ending = LineEnding::detect(&new_text);
                 LineEnding::normalize(&mut new_text);
 
-                let diff = TextDiff::from_chars(old_text.as_str(), new_text.as_str());
                 let empty: Arc<str> = "".into();
-
-                let mut edits = Vec::new();
-                let mut old_offset = 0;
-                let mut new_offset = 0;
-                let mut last_edit: Option<(Range<usize>, Range<usize>)> = None;
-                for change in diff.iter_all_changes().map(Some).chain([None]) {
-                    if let Some(change) = &change {
-                        let len = change.value().len();
-                        match change.tag() {
-                            ChangeTag::Equal => {
-                                old_offset += len;
-                                new_offset += len;
-                            }
-                            ChangeTag::Delete => {
-                                let old_end_offset = old_offset + len;
-                                if let Some((last_old_range, _)) = &mut last_edit {
-                                    last_old_range.end = old_end_offset;
-                                } else {
-                                    last_edit =
-                                        Some((old_offset..old_end_offset, new_offset..new_offset));
-                                }
-                                old_offset = old_end_offset;
-                            }
-                            ChangeTag::Insert => {
-                                let new_end_offset = new_offset + len;
-                                if let Some((_, last_new_range)) = &mut last_edit {
-                                    last_new_range.end = new_end_offset;
-                                } else {
-                                    last_edit =
-                                        Some((old_offset..old_offset, new_offset..new_end_offset));
-                                }
-                                new_offset = new_end_offset;
-                            }
-                        }
-                    }
-
-                    if let Some((old_range, new_range)) = &last_edit {
-                        if old_offset > old_range.end
-                            || new_offset > new_range.end
-                            || change.is_none()
-                        {
-                            let text = if new_range.is_empty() {
-                                empty.clone()
-                            } else {
-                                new_text[new_range.clone()].into()
-                            };
-                            edits.push((old_range.clone(), text));
-                            last_edit.take();
-                        }
-                    }
-                }
+                let edits = text::diff::text_diff(&old_text, &new_text)
+                    .into_iter()
+                    .map(|(old_range, new_range)| {
+                        let text = if new_range.is_empty() {
+                            empty.clone()
+                        } else {
+                            new_text[new_range].into()
+                        };
+                        (old_range, text)
+                    })
+                    .collect();
 
                 Diff {
                     base_version,
@@ -1521,9 +1618,39 @@ impl Buffer {
     /// Checks if the buffer has unsaved changes.
     pub fn is_dirty(&self) -> bool {
         self.file_fingerprint != self.as_rope().fingerprint()
+            || self.encoding != self.saved_encoding
+            || self.line_ending() != self.saved_line_ending
             || self.file.as_ref().map_or(false, |file| file.is_deleted())
     }
 
+    /// The encoding that the buffer will be saved with.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Changes the encoding that the buffer will be saved with, leaving its
+    /// text unchanged. The buffer is dirty until it's saved in the new encoding.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut ModelContext<Self>) {
+        let was_dirty = self.is_dirty();
+        self.encoding = encoding;
+        if was_dirty != self.is_dirty() {
+            cx.emit(Event::DirtyChanged);
+        }
+        cx.notify();
+    }
+
+    /// Changes the line ending that the buffer will be saved with. The
+    /// buffer's text always uses `\n` internally, so it's unaffected. The
+    /// buffer is dirty until it's saved with the new line ending.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding, cx: &mut ModelContext<Self>) {
+        let was_dirty = self.is_dirty();
+        self.text.set_line_ending(line_ending);
+        if was_dirty != self.is_dirty() {
+            cx.emit(Event::DirtyChanged);
+        }
+        cx.notify();
+    }
+
     /// Checks if the buffer and its file have both changed since the buffer
     /// was last saved or reloaded.
     pub fn has_conflict(&self) -> bool {
@@ -1936,6 +2063,9 @@ impl Buffer {
                 );
                 self.text.lamport_clock.observe(lamport_timestamp);
                 self.selections_update_count += 1;
+                cx.emit(Event::RemoteSelectionsChanged {
+                    replica_id: lamport_timestamp.replica_id,
+                });
             }
             Operation::UpdateCompletionTriggers {
                 triggers,
@@ -2508,11 +2638,7 @@ impl BufferSnapshot {
     }
 
     /// Returns the settings for the language at the given location.
-    pub fn settings_at<'a, D: ToOffset>(
-        &self,
-        position: D,
-        cx: &'a AppContext,
-    ) -> &'a LanguageSettings {
+    pub fn settings_at<D: ToOffset>(&self, position: D, cx: &AppContext) -> Arc<LanguageSettings> {
         language_settings(self.language_at(position), self.file.as_ref(), cx)
     }
 
@@ -2595,6 +2721,67 @@ impl BufferSnapshot {
         (start..end, word_kind)
     }
 
+    /// Returns the range of a named syntax node that is strictly contained in
+    /// the given range and is a child of the smallest node containing it: the
+    /// child closest to the end of the range when `toward_end` is set, and the
+    /// one closest to its start otherwise.
+    pub fn range_for_syntax_descendant<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        toward_end: bool,
+    ) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let contains_range = |node: tree_sitter::Node| {
+            node.start_byte() <= range.start && node.end_byte() >= range.end
+        };
+        let mut result: Option<Range<usize>> = None;
+        for layer in self.syntax.layers_for_range(range.clone(), &self.text) {
+            let mut cursor = layer.node().walk();
+            if !contains_range(cursor.node()) {
+                continue;
+            }
+
+            // Descend to the smallest node that contains the range.
+            while cursor.goto_first_child_for_byte(range.start).is_some() {
+                if !contains_range(cursor.node()) {
+                    cursor.goto_parent();
+                    break;
+                }
+            }
+
+            let mut children = Vec::new();
+            if cursor.goto_first_child() {
+                loop {
+                    let node = cursor.node();
+                    if node.is_named()
+                        && node.start_byte() >= range.start
+                        && node.end_byte() <= range.end
+                        && node.byte_range().len() < range.len()
+                    {
+                        children.push(node.byte_range());
+                    }
+                    if !cursor.goto_next_sibling() {
+                        break;
+                    }
+                }
+            }
+            let layer_result = if toward_end {
+                children.pop()
+            } else {
+                children.into_iter().next()
+            };
+            if let Some(layer_result) = layer_result {
+                if result
+                    .as_ref()
+                    .map_or(true, |result| layer_result.len() > result.len())
+                {
+                    result = Some(layer_result);
+                }
+            }
+        }
+        result
+    }
+
     /// Returns the range for the closes syntax node enclosing the given range.
     pub fn range_for_syntax_ancestor<T: ToOffset>(&self, range: Range<T>) -> Option<Range<usize>> {
         let range = range.start.to_offset(self)..range.end.to_offset(self);
@@ -2828,6 +3015,63 @@ impl BufferSnapshot {
         Some(items)
     }
 
+    /// Replaces the outline items that intersect the given ranges with ones
+    /// queried from the current syntax tree, leaving the rest untouched.
+    fn refresh_outline_items(
+        &self,
+        items: &mut Vec<OutlineItem<Anchor>>,
+        mut changed_ranges: Vec<Range<usize>>,
+        theme: Option<&SyntaxTheme>,
+    ) -> Option<()> {
+        changed_ranges.sort_unstable_by_key(|range| range.start);
+        changed_ranges.dedup_by(|range, prev_range| {
+            if range.start <= prev_range.end {
+                prev_range.end = prev_range.end.max(range.end);
+                true
+            } else {
+                false
+            }
+        });
+        if changed_ranges.is_empty() {
+            return Some(());
+        }
+
+        let intersects_changed_range = |range: &Range<usize>| {
+            changed_ranges
+                .iter()
+                .any(|changed| range.end >= changed.start && range.start <= changed.end)
+        };
+        let mut items_with_offsets = items
+            .drain(..)
+            .map(|item| (item.range.to_offset(self), item))
+            .filter(|(range, _)| !intersects_changed_range(range))
+            .collect::<Vec<_>>();
+        for changed_range in &changed_ranges {
+            for item in self.outline_items_containing(changed_range.clone(), true, theme)? {
+                items_with_offsets.push((item.range.to_offset(self), item));
+            }
+        }
+
+        // Items containing several changed ranges are queried once per range.
+        items_with_offsets.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+        items_with_offsets.dedup_by(|(range, item), (prev_range, prev_item)| {
+            range == prev_range && item.text == prev_item.text
+        });
+
+        let mut stack = Vec::<Range<usize>>::new();
+        for (range, mut item) in items_with_offsets {
+            while stack.last().map_or(false, |prev_range| {
+                prev_range.start > range.start || prev_range.end < range.end
+            }) {
+                stack.pop();
+            }
+            item.depth = stack.len();
+            stack.push(range);
+            items.push(item);
+        }
+        Some(())
+    }
+
     /// For each grammar in the language, runs the provided
     /// [tree_sitter::Query] against the given range.
     pub fn matches(
@@ -2932,6 +3176,45 @@ impl BufferSnapshot {
         result
     }
 
+    /// Returns the ranges of the opening and closing tags of the innermost markup
+    /// element containing the given range, or None if no element contains it.
+    pub fn innermost_enclosing_tag_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        const OPENING_TAG_KINDS: &[&str] = &["start_tag", "jsx_opening_element"];
+        const CLOSING_TAG_KINDS: &[&str] = &["end_tag", "jsx_closing_element"];
+
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut result: Option<(Range<usize>, Range<usize>)> = None;
+        for layer in self.syntax.layers_for_range(range.clone(), &self.text) {
+            let mut node = layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end);
+            while let Some(element) = node {
+                let open = element.child(0);
+                let close = element
+                    .child_count()
+                    .checked_sub(1)
+                    .and_then(|ix| element.child(ix));
+                if let Some((open, close)) = open.zip(close) {
+                    if OPENING_TAG_KINDS.contains(&open.kind())
+                        && CLOSING_TAG_KINDS.contains(&close.kind())
+                    {
+                        if result.as_ref().map_or(true, |(existing_open, _)| {
+                            open.start_byte() > existing_open.start
+                        }) {
+                            result = Some((open.byte_range(), close.byte_range()));
+                        }
+                        break;
+                    }
+                }
+                node = element.parent();
+            }
+        }
+        result
+    }
+
     /// Returns anchor ranges for any matches of the redaction query.
     /// The buffer can be associated with multiple languages, and the redaction query associated with each
     /// will be run on the relevant section of the buffer.
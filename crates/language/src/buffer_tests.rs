@@ -2,27 +2,23 @@ use super::*;
 use crate::language_settings::{
     AllLanguageSettings, AllLanguageSettingsContent, LanguageSettingsContent,
 };
+use crate::random_collaboration::RandomCollaboration;
 use crate::Buffer;
-use clock::ReplicaId;
-use collections::BTreeMap;
 use futures::FutureExt as _;
 use gpui::{AppContext, Model};
 use gpui::{Context, TestAppContext};
 use indoc::indoc;
-use proto::deserialize_operation;
 use rand::prelude::*;
 use regex::RegexBuilder;
 use settings::SettingsStore;
 use std::{
-    env,
     ops::Range,
     time::{Duration, Instant},
 };
-use text::network::Network;
 use text::{BufferId, LineEnding};
 use text::{Point, ToPoint};
 use unindent::Unindent as _;
-use util::{assert_set_eq, post_inc, test::marked_text_ranges, RandomCharIter};
+use util::{assert_set_eq, test::marked_text_ranges};
 
 lazy_static! {
     static ref TRAILING_WHITESPACE_REGEX: Regex = RegexBuilder::new("[ \t]+$")
@@ -68,6 +64,55 @@ fn test_line_endings(cx: &mut gpui::AppContext) {
     });
 }
 
+#[gpui::test]
+fn test_encoding_change_marks_buffer_dirty(cx: &mut gpui::AppContext) {
+    init_settings(cx, |_| {});
+
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "caf\u{e9}",
+        )
+        .with_encoding(Encoding::windows_1252());
+        assert_eq!(buffer.encoding(), Encoding::windows_1252());
+        assert!(!buffer.is_dirty());
+
+        buffer.set_encoding(Encoding::utf8(), cx);
+        assert!(buffer.is_dirty());
+
+        buffer.set_encoding(Encoding::windows_1252(), cx);
+        assert!(!buffer.is_dirty());
+
+        buffer
+    });
+}
+
+#[gpui::test]
+fn test_line_ending_change_marks_buffer_dirty(cx: &mut gpui::AppContext) {
+    init_settings(cx, |_| {});
+
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "one\r\ntwo\r\n",
+        );
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert!(!buffer.is_dirty());
+
+        buffer.set_line_ending(LineEnding::Unix, cx);
+        assert!(buffer.is_dirty());
+        assert_eq!(buffer.text(), "one\ntwo\n");
+
+        buffer.set_line_ending(LineEnding::Windows, cx);
+        assert!(!buffer.is_dirty());
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_select_language() {
     let registry = Arc::new(LanguageRegistry::test());
@@ -456,6 +501,41 @@ async fn test_reparse(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_reparse_large_buffer_in_background(cx: &mut gpui::TestAppContext) {
+    let function = "fn a() { b(); }\n";
+    let text = function.repeat(MAX_SYNC_PARSE_LEN / function.len() + 1);
+    let buffer = cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(rust_lang()), cx);
+        buffer.set_sync_parse_timeout(Duration::from_secs(60));
+        buffer
+    });
+    cx.executor().run_until_parked();
+    assert!(!buffer.update(cx, |buffer, _| buffer.is_parsing()));
+
+    // Even with a generous timeout, editing doesn't wait on the parse. Until
+    // it completes, the old tree is interpolated against the edit.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "struct S;\n")], None, cx);
+        assert!(buffer.is_parsing());
+
+        let snapshot = buffer.snapshot();
+        let layers = snapshot.syntax.layers(buffer.as_text_snapshot());
+        let function = layers[0].node().child(0).unwrap();
+        assert_eq!(function.kind(), "function_item");
+        assert_eq!(function.start_byte(), "struct S;\n".len());
+    });
+
+    cx.executor().run_until_parked();
+    assert!(!buffer.update(cx, |buffer, _| buffer.is_parsing()));
+    buffer.update(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let layers = snapshot.syntax.layers(buffer.as_text_snapshot());
+        assert_eq!(layers[0].node().child(0).unwrap().kind(), "struct_item");
+    });
+}
+
 #[gpui::test]
 async fn test_resetting_language(cx: &mut gpui::TestAppContext) {
     let buffer = cx.new_model(|cx| {
@@ -613,6 +693,104 @@ async fn test_outline_nodes_with_newlines(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_outline_updates_after_reparse(cx: &mut gpui::TestAppContext) {
+    let text = r#"
+        mod a {
+            fn b() {}
+        }
+
+        fn c() {}
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(rust_lang()), cx)
+    });
+    let outline_items = |outline: Outline<Anchor>| {
+        outline
+            .items
+            .into_iter()
+            .map(|item| (item.text, item.depth))
+            .collect::<Vec<_>>()
+    };
+    let assert_outline = |buffer: &Buffer, expected: &[(&str, usize)]| {
+        let expected = expected
+            .iter()
+            .map(|(text, depth)| (text.to_string(), *depth))
+            .collect::<Vec<_>>();
+        assert_eq!(outline_items(buffer.outline(None).unwrap()), expected);
+        assert_eq!(
+            outline_items(buffer.snapshot().outline(None).unwrap()),
+            expected
+        );
+    };
+    buffer.update(cx, |buffer, _| {
+        assert_outline(buffer, &[("mod a", 0), ("fn b", 1), ("fn c", 0)]);
+    });
+
+    // Add a function to the module and rename the one outside of it.
+    buffer.update(cx, |buffer, cx| {
+        let fn_b_end = buffer.text().find("fn b() {}").unwrap() + "fn b() {}".len();
+        let fn_c = buffer.text().find("fn c").unwrap();
+        buffer.edit(
+            [
+                (fn_b_end..fn_b_end, "\n    fn d() {}"),
+                (fn_c + 3..fn_c + 4, "e"),
+            ],
+            None,
+            cx,
+        );
+    });
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_outline(
+            buffer,
+            &[("mod a", 0), ("fn b", 1), ("fn d", 1), ("fn e", 0)],
+        );
+    });
+
+    // Wrap the last function in a new module.
+    buffer.update(cx, |buffer, cx| {
+        let fn_e = buffer.text().find("fn e").unwrap();
+        let len = buffer.len();
+        buffer.edit([(fn_e..fn_e, "mod f {\n"), (len..len, "}\n")], None, cx);
+    });
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_outline(
+            buffer,
+            &[
+                ("mod a", 0),
+                ("fn b", 1),
+                ("fn d", 1),
+                ("mod f", 0),
+                ("fn e", 1),
+            ],
+        );
+    });
+
+    // Rename a function without changing the syntax tree's structure, and
+    // reparse several times before the outline is queried again.
+    buffer.update(cx, |buffer, cx| {
+        let fn_d = buffer.text().find("fn d").unwrap();
+        buffer.edit([(fn_d + 3..fn_d + 4, "g")], None, cx);
+    });
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, cx| {
+        let fn_b = buffer.text().find("fn b").unwrap();
+        buffer.edit([(fn_b..fn_b + "fn b() {}".len(), "")], None, cx);
+    });
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_outline(
+            buffer,
+            &[("mod a", 0), ("fn g", 1), ("mod f", 0), ("fn e", 1)],
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_outline_with_extra_context(cx: &mut gpui::TestAppContext) {
     let language = javascript_lang()
@@ -1972,300 +2150,10 @@ fn test_serialization(cx: &mut gpui::AppContext) {
 }
 
 #[gpui::test(iterations = 100)]
-fn test_random_collaboration(cx: &mut AppContext, mut rng: StdRng) {
-    let min_peers = env::var("MIN_PEERS")
-        .map(|i| i.parse().expect("invalid `MIN_PEERS` variable"))
-        .unwrap_or(1);
-    let max_peers = env::var("MAX_PEERS")
-        .map(|i| i.parse().expect("invalid `MAX_PEERS` variable"))
-        .unwrap_or(5);
-    let operations = env::var("OPERATIONS")
-        .map(|i| i.parse().expect("invalid `OPERATIONS` variable"))
-        .unwrap_or(10);
-
-    let base_text_len = rng.gen_range(0..10);
-    let base_text = RandomCharIter::new(&mut rng)
-        .take(base_text_len)
-        .collect::<String>();
-    let mut replica_ids = Vec::new();
-    let mut buffers = Vec::new();
-    let network = Arc::new(Mutex::new(Network::new(rng.clone())));
-    let base_buffer = cx.new_model(|cx| {
-        Buffer::new(
-            0,
-            BufferId::new(cx.entity_id().as_u64()).unwrap(),
-            base_text.as_str(),
-        )
-    });
-
-    for i in 0..rng.gen_range(min_peers..=max_peers) {
-        let buffer = cx.new_model(|cx| {
-            let state = base_buffer.read(cx).to_proto();
-            let ops = cx
-                .background_executor()
-                .block(base_buffer.read(cx).serialize_ops(None, cx));
-            let mut buffer =
-                Buffer::from_proto(i as ReplicaId, Capability::ReadWrite, state, None).unwrap();
-            buffer
-                .apply_ops(
-                    ops.into_iter()
-                        .map(|op| proto::deserialize_operation(op).unwrap()),
-                    cx,
-                )
-                .unwrap();
-            buffer.set_group_interval(Duration::from_millis(rng.gen_range(0..=200)));
-            let network = network.clone();
-            cx.subscribe(&cx.handle(), move |buffer, _, event, _| {
-                if let Event::Operation(op) = event {
-                    network
-                        .lock()
-                        .broadcast(buffer.replica_id(), vec![proto::serialize_operation(op)]);
-                }
-            })
-            .detach();
-            buffer
-        });
-
-        buffers.push(buffer);
-        replica_ids.push(i as ReplicaId);
-        network.lock().add_peer(i as ReplicaId);
-        log::info!("Adding initial peer with replica id {}", i);
-    }
-
-    log::info!("initial text: {:?}", base_text);
-
-    let mut now = Instant::now();
-    let mut mutation_count = operations;
-    let mut next_diagnostic_id = 0;
-    let mut active_selections = BTreeMap::default();
-    loop {
-        let replica_index = rng.gen_range(0..replica_ids.len());
-        let replica_id = replica_ids[replica_index];
-        let buffer = &mut buffers[replica_index];
-        let mut new_buffer = None;
-        match rng.gen_range(0..100) {
-            0..=29 if mutation_count != 0 => {
-                buffer.update(cx, |buffer, cx| {
-                    buffer.start_transaction_at(now);
-                    buffer.randomly_edit(&mut rng, 5, cx);
-                    buffer.end_transaction_at(now, cx);
-                    log::info!("buffer {} text: {:?}", buffer.replica_id(), buffer.text());
-                });
-                mutation_count -= 1;
-            }
-            30..=39 if mutation_count != 0 => {
-                buffer.update(cx, |buffer, cx| {
-                    if rng.gen_bool(0.2) {
-                        log::info!("peer {} clearing active selections", replica_id);
-                        active_selections.remove(&replica_id);
-                        buffer.remove_active_selections(cx);
-                    } else {
-                        let mut selections = Vec::new();
-                        for id in 0..rng.gen_range(1..=5) {
-                            let range = buffer.random_byte_range(0, &mut rng);
-                            selections.push(Selection {
-                                id,
-                                start: buffer.anchor_before(range.start),
-                                end: buffer.anchor_before(range.end),
-                                reversed: false,
-                                goal: SelectionGoal::None,
-                            });
-                        }
-                        let selections: Arc<[Selection<Anchor>]> = selections.into();
-                        log::info!(
-                            "peer {} setting active selections: {:?}",
-                            replica_id,
-                            selections
-                        );
-                        active_selections.insert(replica_id, selections.clone());
-                        buffer.set_active_selections(selections, false, Default::default(), cx);
-                    }
-                });
-                mutation_count -= 1;
-            }
-            40..=49 if mutation_count != 0 && replica_id == 0 => {
-                let entry_count = rng.gen_range(1..=5);
-                buffer.update(cx, |buffer, cx| {
-                    let diagnostics = DiagnosticSet::new(
-                        (0..entry_count).map(|_| {
-                            let range = buffer.random_byte_range(0, &mut rng);
-                            let range = range.to_point_utf16(buffer);
-                            let range = range.start..range.end;
-                            DiagnosticEntry {
-                                range,
-                                diagnostic: Diagnostic {
-                                    message: post_inc(&mut next_diagnostic_id).to_string(),
-                                    ..Default::default()
-                                },
-                            }
-                        }),
-                        buffer,
-                    );
-                    log::info!("peer {} setting diagnostics: {:?}", replica_id, diagnostics);
-                    buffer.update_diagnostics(LanguageServerId(0), diagnostics, cx);
-                });
-                mutation_count -= 1;
-            }
-            50..=59 if replica_ids.len() < max_peers => {
-                let old_buffer_state = buffer.read(cx).to_proto();
-                let old_buffer_ops = cx
-                    .background_executor()
-                    .block(buffer.read(cx).serialize_ops(None, cx));
-                let new_replica_id = (0..=replica_ids.len() as ReplicaId)
-                    .filter(|replica_id| *replica_id != buffer.read(cx).replica_id())
-                    .choose(&mut rng)
-                    .unwrap();
-                log::info!(
-                    "Adding new replica {} (replicating from {})",
-                    new_replica_id,
-                    replica_id
-                );
-                new_buffer = Some(cx.new_model(|cx| {
-                    let mut new_buffer = Buffer::from_proto(
-                        new_replica_id,
-                        Capability::ReadWrite,
-                        old_buffer_state,
-                        None,
-                    )
-                    .unwrap();
-                    new_buffer
-                        .apply_ops(
-                            old_buffer_ops
-                                .into_iter()
-                                .map(|op| deserialize_operation(op).unwrap()),
-                            cx,
-                        )
-                        .unwrap();
-                    log::info!(
-                        "New replica {} text: {:?}",
-                        new_buffer.replica_id(),
-                        new_buffer.text()
-                    );
-                    new_buffer.set_group_interval(Duration::from_millis(rng.gen_range(0..=200)));
-                    let network = network.clone();
-                    cx.subscribe(&cx.handle(), move |buffer, _, event, _| {
-                        if let Event::Operation(op) = event {
-                            network.lock().broadcast(
-                                buffer.replica_id(),
-                                vec![proto::serialize_operation(op)],
-                            );
-                        }
-                    })
-                    .detach();
-                    new_buffer
-                }));
-                network.lock().replicate(replica_id, new_replica_id);
-
-                if new_replica_id as usize == replica_ids.len() {
-                    replica_ids.push(new_replica_id);
-                } else {
-                    let new_buffer = new_buffer.take().unwrap();
-                    while network.lock().has_unreceived(new_replica_id) {
-                        let ops = network
-                            .lock()
-                            .receive(new_replica_id)
-                            .into_iter()
-                            .map(|op| proto::deserialize_operation(op).unwrap());
-                        if ops.len() > 0 {
-                            log::info!(
-                                "peer {} (version: {:?}) applying {} ops from the network. {:?}",
-                                new_replica_id,
-                                buffer.read(cx).version(),
-                                ops.len(),
-                                ops
-                            );
-                            new_buffer.update(cx, |new_buffer, cx| {
-                                new_buffer.apply_ops(ops, cx).unwrap();
-                            });
-                        }
-                    }
-                    buffers[new_replica_id as usize] = new_buffer;
-                }
-            }
-            60..=69 if mutation_count != 0 => {
-                buffer.update(cx, |buffer, cx| {
-                    buffer.randomly_undo_redo(&mut rng, cx);
-                    log::info!("buffer {} text: {:?}", buffer.replica_id(), buffer.text());
-                });
-                mutation_count -= 1;
-            }
-            _ if network.lock().has_unreceived(replica_id) => {
-                let ops = network
-                    .lock()
-                    .receive(replica_id)
-                    .into_iter()
-                    .map(|op| proto::deserialize_operation(op).unwrap());
-                if ops.len() > 0 {
-                    log::info!(
-                        "peer {} (version: {:?}) applying {} ops from the network. {:?}",
-                        replica_id,
-                        buffer.read(cx).version(),
-                        ops.len(),
-                        ops
-                    );
-                    buffer.update(cx, |buffer, cx| buffer.apply_ops(ops, cx).unwrap());
-                }
-            }
-            _ => {}
-        }
-
-        now += Duration::from_millis(rng.gen_range(0..=200));
-        buffers.extend(new_buffer);
-
-        for buffer in &buffers {
-            buffer.read(cx).check_invariants();
-        }
-
-        if mutation_count == 0 && network.lock().is_idle() {
-            break;
-        }
-    }
-
-    let first_buffer = buffers[0].read(cx).snapshot();
-    for buffer in &buffers[1..] {
-        let buffer = buffer.read(cx).snapshot();
-        assert_eq!(
-            buffer.version(),
-            first_buffer.version(),
-            "Replica {} version != Replica 0 version",
-            buffer.replica_id()
-        );
-        assert_eq!(
-            buffer.text(),
-            first_buffer.text(),
-            "Replica {} text != Replica 0 text",
-            buffer.replica_id()
-        );
-        assert_eq!(
-            buffer
-                .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
-                .collect::<Vec<_>>(),
-            first_buffer
-                .diagnostics_in_range::<_, usize>(0..first_buffer.len(), false)
-                .collect::<Vec<_>>(),
-            "Replica {} diagnostics != Replica 0 diagnostics",
-            buffer.replica_id()
-        );
-    }
-
-    for buffer in &buffers {
-        let buffer = buffer.read(cx).snapshot();
-        let actual_remote_selections = buffer
-            .remote_selections_in_range(Anchor::MIN..Anchor::MAX)
-            .map(|(replica_id, _, _, selections)| (replica_id, selections.collect::<Vec<_>>()))
-            .collect::<Vec<_>>();
-        let expected_remote_selections = active_selections
-            .iter()
-            .filter(|(replica_id, _)| **replica_id != buffer.replica_id())
-            .map(|(replica_id, selections)| (*replica_id, selections.iter().collect::<Vec<_>>()))
-            .collect::<Vec<_>>();
-        assert_eq!(
-            actual_remote_selections,
-            expected_remote_selections,
-            "Replica {} remote selections != expected selections",
-            buffer.replica_id()
-        );
-    }
+fn test_random_collaboration(cx: &mut AppContext, rng: StdRng) {
+    let mut collaboration = RandomCollaboration::new(rng, cx);
+    collaboration.run(cx);
+    collaboration.assert_convergence(cx);
 }
 
 #[test]
@@ -13,6 +13,8 @@ mod language_registry;
 pub mod language_settings;
 mod outline;
 pub mod proto;
+#[cfg(any(test, feature = "test-support"))]
+pub mod random_collaboration;
 mod syntax_map;
 
 #[cfg(test)]
@@ -58,6 +60,7 @@ use util::http::HttpClient;
 pub use buffer::Operation;
 pub use buffer::*;
 pub use diagnostic_set::DiagnosticEntry;
+pub use fs::Encoding;
 pub use language_registry::{
     LanguageQueries, LanguageRegistry, LanguageServerBinaryStatus, PendingLanguageServer,
     QUERY_FILENAME_PREFIXES,
@@ -944,22 +947,30 @@ impl Language {
         let query = Query::new(&grammar.ts_language, source)?;
         let mut language_capture_ix = None;
         let mut content_capture_ix = None;
+        let mut standard_language_capture_ix = None;
+        let mut standard_content_capture_ix = None;
+        // Accept both our capture names and the `injection.*` names used by
+        // the injection queries that ship with tree-sitter grammars.
         get_capture_indices(
             &query,
             &mut [
                 ("language", &mut language_capture_ix),
                 ("content", &mut content_capture_ix),
+                ("injection.language", &mut standard_language_capture_ix),
+                ("injection.content", &mut standard_content_capture_ix),
             ],
         );
+        let language_capture_ix = language_capture_ix.or(standard_language_capture_ix);
+        let content_capture_ix = content_capture_ix.or(standard_content_capture_ix);
         let patterns = (0..query.pattern_count())
             .map(|ix| {
                 let mut config = InjectionPatternConfig::default();
                 for setting in query.property_settings(ix) {
                     match setting.key.as_ref() {
-                        "language" => {
+                        "language" | "injection.language" => {
                             config.language = setting.value.clone();
                         }
-                        "combined" => {
+                        "combined" | "injection.combined" => {
                             config.combined = true;
                         }
                         _ => {}
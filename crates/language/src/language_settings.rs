@@ -1,16 +1,17 @@
 //! Provides `language`-related settings.
 
-use crate::{File, Language};
+use crate::{File, Language, LanguageServerName};
 use anyhow::Result;
 use collections::{HashMap, HashSet};
 use globset::GlobMatcher;
 use gpui::AppContext;
+use parking_lot::Mutex;
 use schemars::{
-    schema::{InstanceType, ObjectValidation, Schema, SchemaObject},
+    schema::{InstanceType, Metadata, ObjectValidation, Schema, SchemaObject, SubschemaValidation},
     JsonSchema,
 };
 use serde::{Deserialize, Serialize};
-use settings::Settings;
+use settings::{editorconfig::EditorconfigProperties, Settings, SettingsStore};
 use std::{num::NonZeroU32, path::Path, sync::Arc};
 
 /// Initializes the language settings.
@@ -19,13 +20,26 @@ pub fn init(cx: &mut AppContext) {
 }
 
 /// Returns the settings for the specified language from the provided file.
-pub fn language_settings<'a>(
+///
+/// Properties from `.editorconfig` files that apply to the file take precedence
+/// over the default settings, but not over settings the user chose explicitly.
+pub fn language_settings(
     language: Option<&Arc<Language>>,
     file: Option<&Arc<dyn File>>,
-    cx: &'a AppContext,
-) -> &'a LanguageSettings {
+    cx: &AppContext,
+) -> Arc<LanguageSettings> {
     let language_name = language.map(|l| l.name());
-    all_language_settings(file, cx).language(language_name.as_deref())
+    let all_settings = all_language_settings(file, cx);
+    let editorconfig = file.and_then(|file| {
+        cx.global::<SettingsStore>()
+            .editorconfig_properties(file.worktree_id(), file.path())
+    });
+    match editorconfig {
+        Some(properties) => all_settings.language_with_editorconfig(language_name, properties),
+        None => all_settings
+            .language_entry(language_name.as_deref())
+            .clone(),
+    }
 }
 
 /// Returns the settings for all languages from the provided file.
@@ -42,8 +56,45 @@ pub fn all_language_settings<'a>(
 pub struct AllLanguageSettings {
     /// The settings for GitHub Copilot.
     pub copilot: CopilotSettings,
-    defaults: LanguageSettings,
-    languages: HashMap<Arc<str>, LanguageSettings>,
+    defaults: Arc<LanguageSettings>,
+    languages: HashMap<Arc<str>, Arc<LanguageSettings>>,
+    user_set_defaults: UserSetSettings,
+    user_set_languages: HashMap<Arc<str>, UserSetSettings>,
+    /// The settings with `.editorconfig` properties applied, shared between
+    /// all the files that the same properties apply to.
+    editorconfig_languages:
+        Arc<Mutex<HashMap<(Option<Arc<str>>, EditorconfigProperties), Arc<LanguageSettings>>>>,
+}
+
+/// Which of the settings that `.editorconfig` files can configure were set
+/// explicitly in the user's or the project's settings.
+#[derive(Debug, Clone, Copy, Default)]
+struct UserSetSettings {
+    tab_size: bool,
+    hard_tabs: bool,
+    remove_trailing_whitespace_on_save: bool,
+    ensure_final_newline_on_save: bool,
+}
+
+impl UserSetSettings {
+    fn record(&mut self, content: &LanguageSettingsContent) {
+        self.tab_size |= content.tab_size.is_some();
+        self.hard_tabs |= content.hard_tabs.is_some();
+        self.remove_trailing_whitespace_on_save |=
+            content.remove_trailing_whitespace_on_save.is_some();
+        self.ensure_final_newline_on_save |= content.ensure_final_newline_on_save.is_some();
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            tab_size: self.tab_size || other.tab_size,
+            hard_tabs: self.hard_tabs || other.hard_tabs,
+            remove_trailing_whitespace_on_save: self.remove_trailing_whitespace_on_save
+                || other.remove_trailing_whitespace_on_save,
+            ensure_final_newline_on_save: self.ensure_final_newline_on_save
+                || other.ensure_final_newline_on_save,
+        }
+    }
 }
 
 /// The settings for a particular language.
@@ -82,6 +133,11 @@ pub struct LanguageSettings {
     pub prettier: HashMap<String, serde_json::Value>,
     /// Whether to use language servers to provide code intelligence.
     pub enable_language_server: bool,
+    /// The list of language servers to use (or disable) for this language.
+    ///
+    /// Servers earlier in the list take precedence for requests that only
+    /// one server answers.
+    pub language_servers: Vec<Arc<str>>,
     /// Controls whether Copilot provides suggestion immediately (true)
     /// or waits for a `copilot::Toggle` (false).
     pub show_copilot_suggestions: bool,
@@ -89,10 +145,15 @@ pub struct LanguageSettings {
     pub show_whitespaces: ShowWhitespaceSetting,
     /// Whether to start a new line with a comment when a previous line is a comment as well.
     pub extend_comment_on_newline: bool,
+    /// Whether pressing tab after an Emmet abbreviation expands it.
+    pub expand_emmet_abbreviations: bool,
     /// Inlay hint related settings.
     pub inlay_hints: InlayHintSettings,
     /// Whether to automatically close brackets.
     pub use_autoclose: bool,
+    /// Whether to type over and delete closing brackets that were not
+    /// auto-inserted, as if they had been.
+    pub always_treat_brackets_as_autoclosed: bool,
     /// Which code actions to run on save
     pub code_actions_on_format: HashMap<String, bool>,
 }
@@ -195,6 +256,22 @@ pub struct LanguageSettingsContent {
     /// Default: true
     #[serde(default)]
     pub enable_language_server: Option<bool>,
+    /// The list of language servers to use (or disable) for this language.
+    ///
+    /// This array should consist of language server names, as well as the
+    /// following special tokens:
+    /// - `"!<language_server_name>"` - A language server name prefixed with
+    ///   a `!` will be disabled.
+    /// - `"..."` - A placeholder referring to the rest of the language servers
+    ///   registered for this language.
+    ///
+    /// Servers earlier in the list are preferred for requests that only one
+    /// server answers, such as hover or go to definition, while diagnostics
+    /// and completions are merged from all of them.
+    ///
+    /// Default: ["..."]
+    #[serde(default)]
+    pub language_servers: Option<Vec<Arc<str>>>,
     /// Controls whether Copilot provides suggestion immediately (true)
     /// or waits for a `copilot::Toggle` (false).
     ///
@@ -209,6 +286,12 @@ pub struct LanguageSettingsContent {
     /// Default: true
     #[serde(default)]
     pub extend_comment_on_newline: Option<bool>,
+    /// Whether pressing tab after an Emmet abbreviation, such as `ul>li*3`,
+    /// expands it. This applies to HTML, CSS, and JSX in JavaScript and TSX.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub expand_emmet_abbreviations: Option<bool>,
     /// Inlay hint related settings.
     #[serde(default)]
     pub inlay_hints: Option<InlayHintSettings>,
@@ -218,6 +301,15 @@ pub struct LanguageSettingsContent {
     /// Default: true
     pub use_autoclose: Option<bool>,
 
+    /// Controls how the editor handles the autoclosed characters.
+    /// When set to `false`(default), skipping over and auto-removing of the closing characters
+    /// happen only for auto-inserted characters.
+    /// Otherwise(when `true`), the closing characters are always skipped over and auto-removed
+    /// no matter how they were inserted.
+    ///
+    /// Default: false
+    pub always_treat_brackets_as_autoclosed: Option<bool>,
+
     /// Which code actions to run on save
     ///
     /// Default: {} (or {"source.organizeImports": true} for Go).
@@ -372,9 +464,63 @@ impl InlayHintSettings {
     }
 }
 
+impl LanguageSettings {
+    /// A token representing the rest of the available language servers.
+    const REST_OF_LANGUAGE_SERVERS: &'static str = "...";
+
+    /// Returns the language servers to use, in order of precedence, out of
+    /// the ones available for the language.
+    pub fn customized_language_servers(
+        &self,
+        available_language_servers: &[LanguageServerName],
+    ) -> Vec<LanguageServerName> {
+        let mut enabled_language_servers = Vec::new();
+        let mut disabled_language_servers = HashSet::default();
+        for language_server in &self.language_servers {
+            match language_server.strip_prefix('!') {
+                Some(disabled) => {
+                    disabled_language_servers.insert(disabled);
+                }
+                None => enabled_language_servers.push(language_server.as_ref()),
+            }
+        }
+
+        let is_listed = |name: &LanguageServerName| {
+            self.language_servers
+                .iter()
+                .any(|language_server| language_server.trim_start_matches('!') == name.0.as_ref())
+        };
+
+        let mut language_servers = Vec::new();
+        for enabled in enabled_language_servers {
+            if enabled == Self::REST_OF_LANGUAGE_SERVERS {
+                language_servers.extend(
+                    available_language_servers
+                        .iter()
+                        .filter(|name| !is_listed(name))
+                        .cloned(),
+                );
+            } else if let Some(name) = available_language_servers
+                .iter()
+                .find(|name| name.0.as_ref() == enabled)
+            {
+                if !disabled_language_servers.contains(enabled) && !language_servers.contains(name)
+                {
+                    language_servers.push(name.clone());
+                }
+            }
+        }
+        language_servers
+    }
+}
+
 impl AllLanguageSettings {
     /// Returns the [`LanguageSettings`] for the language with the specified name.
     pub fn language<'a>(&'a self, language_name: Option<&str>) -> &'a LanguageSettings {
+        self.language_entry(language_name)
+    }
+
+    fn language_entry(&self, language_name: Option<&str>) -> &Arc<LanguageSettings> {
         if let Some(name) = language_name {
             if let Some(overrides) = self.languages.get(name) {
                 return overrides;
@@ -383,6 +529,56 @@ impl AllLanguageSettings {
         &self.defaults
     }
 
+    /// Returns the settings for a language with `.editorconfig` properties
+    /// applied, computing them only the first time these properties are seen.
+    fn language_with_editorconfig(
+        &self,
+        language_name: Option<Arc<str>>,
+        properties: EditorconfigProperties,
+    ) -> Arc<LanguageSettings> {
+        self.editorconfig_languages
+            .lock()
+            .entry((language_name, properties))
+            .or_insert_with_key(|(language_name, properties)| {
+                Arc::new(self.apply_editorconfig(language_name.as_deref(), properties))
+            })
+            .clone()
+    }
+
+    /// Applies `.editorconfig` properties to the settings for a language,
+    /// except for the ones the user has set explicitly.
+    fn apply_editorconfig(
+        &self,
+        language_name: Option<&str>,
+        properties: &EditorconfigProperties,
+    ) -> LanguageSettings {
+        let mut settings = self.language(language_name).clone();
+        let user_set = language_name
+            .and_then(|name| self.user_set_languages.get(name))
+            .copied()
+            .unwrap_or_default()
+            .union(self.user_set_defaults);
+        if !user_set.tab_size {
+            merge(&mut settings.tab_size, properties.tab_size());
+        }
+        if !user_set.hard_tabs {
+            merge(&mut settings.hard_tabs, properties.hard_tabs());
+        }
+        if !user_set.remove_trailing_whitespace_on_save {
+            merge(
+                &mut settings.remove_trailing_whitespace_on_save,
+                properties.trim_trailing_whitespace,
+            );
+        }
+        if !user_set.ensure_final_newline_on_save {
+            merge(
+                &mut settings.ensure_final_newline_on_save,
+                properties.insert_final_newline,
+            );
+        }
+        settings
+    }
+
     /// Returns whether GitHub Copilot is enabled for the given path.
     pub fn copilot_enabled_for_path(&self, path: &Path) -> bool {
         !self
@@ -454,7 +650,7 @@ impl settings::Settings for AllLanguageSettings {
         let mut defaults: LanguageSettings =
             serde_json::from_value(serde_json::to_value(&default_value.defaults)?)?;
 
-        let mut languages = HashMap::default();
+        let mut languages = HashMap::<Arc<str>, LanguageSettings>::default();
         for (language_name, settings) in &default_value.languages {
             let mut language_settings = defaults.clone();
             merge_settings(&mut language_settings, settings);
@@ -472,7 +668,17 @@ impl settings::Settings for AllLanguageSettings {
             .and_then(|c| c.disabled_globs.as_ref())
             .ok_or_else(Self::missing_default)?;
 
+        let mut user_set_defaults = UserSetSettings::default();
+        let mut user_set_languages = HashMap::<Arc<str>, UserSetSettings>::default();
         for user_settings in user_settings {
+            user_set_defaults.record(&user_settings.defaults);
+            for (language_name, user_language_settings) in &user_settings.languages {
+                user_set_languages
+                    .entry(language_name.clone())
+                    .or_default()
+                    .record(user_language_settings);
+            }
+
             if let Some(copilot) = user_settings.features.as_ref().and_then(|f| f.copilot) {
                 copilot_enabled = copilot;
             }
@@ -510,8 +716,14 @@ impl settings::Settings for AllLanguageSettings {
                     .filter_map(|g| Some(globset::Glob::new(g).ok()?.compile_matcher()))
                     .collect(),
             },
-            defaults,
-            languages,
+            defaults: Arc::new(defaults),
+            languages: languages
+                .into_iter()
+                .map(|(name, settings)| (name, Arc::new(settings)))
+                .collect(),
+            user_set_defaults,
+            user_set_languages,
+            editorconfig_languages: Default::default(),
         })
     }
 
@@ -541,6 +753,8 @@ impl settings::Settings for AllLanguageSettings {
                         )
                     })
                     .collect(),
+                // Flag misspelled or unknown language names, whose settings would be ignored.
+                additional_properties: Some(Box::new(Schema::Bool(false))),
                 ..Default::default()
             })),
             ..Default::default()
@@ -564,7 +778,24 @@ impl settings::Settings for AllLanguageSettings {
                 // For backward compatibility
                 (
                     "language_overrides".to_owned(),
-                    Schema::new_ref("#/definitions/Languages".into()),
+                    SchemaObject {
+                        metadata: Some(Box::new(Metadata {
+                            deprecated: true,
+                            ..Default::default()
+                        })),
+                        subschemas: Some(Box::new(SubschemaValidation {
+                            all_of: Some(vec![Schema::new_ref("#/definitions/Languages".into())]),
+                            ..Default::default()
+                        })),
+                        extensions: [(
+                            "deprecationMessage".to_owned(),
+                            "Use `languages` instead.".into(),
+                        )]
+                        .into_iter()
+                        .collect(),
+                        ..Default::default()
+                    }
+                    .into(),
                 ),
             ]);
 
@@ -577,6 +808,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     merge(&mut settings.hard_tabs, src.hard_tabs);
     merge(&mut settings.soft_wrap, src.soft_wrap);
     merge(&mut settings.use_autoclose, src.use_autoclose);
+    merge(
+        &mut settings.always_treat_brackets_as_autoclosed,
+        src.always_treat_brackets_as_autoclosed,
+    );
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
     merge(
@@ -603,6 +838,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.enable_language_server,
         src.enable_language_server,
     );
+    merge(&mut settings.language_servers, src.language_servers.clone());
     merge(
         &mut settings.show_copilot_suggestions,
         src.show_copilot_suggestions,
@@ -612,10 +848,54 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.extend_comment_on_newline,
         src.extend_comment_on_newline,
     );
+    merge(
+        &mut settings.expand_emmet_abbreviations,
+        src.expand_emmet_abbreviations,
+    );
     merge(&mut settings.inlay_hints, src.inlay_hints);
-    fn merge<T>(target: &mut T, value: Option<T>) {
-        if let Some(value) = value {
-            *target = value;
-        }
+}
+
+fn merge<T>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    fn test_customized_language_servers(cx: &mut AppContext) {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        init(cx);
+
+        let available =
+            ["rust-analyzer", "clippy-lsp", "typos"].map(|name| LanguageServerName(name.into()));
+        let resolve = |language_servers: &[&str], cx: &AppContext| {
+            let mut settings = AllLanguageSettings::get_global(cx).language(None).clone();
+            settings.language_servers = language_servers.iter().map(|&name| name.into()).collect();
+            settings
+                .customized_language_servers(&available)
+                .into_iter()
+                .map(|name| name.0.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            resolve(&["..."], cx),
+            ["rust-analyzer", "clippy-lsp", "typos"]
+        );
+        assert_eq!(
+            resolve(&["typos", "..."], cx),
+            ["typos", "rust-analyzer", "clippy-lsp"]
+        );
+        assert_eq!(
+            resolve(&["...", "!clippy-lsp"], cx),
+            ["rust-analyzer", "typos"]
+        );
+        assert_eq!(resolve(&["clippy-lsp", "unknown"], cx), ["clippy-lsp"]);
+        assert_eq!(resolve(&[], cx), Vec::<String>::new());
     }
 }
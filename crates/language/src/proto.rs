@@ -40,6 +40,18 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Deserializes a [`crate::Encoding`] from the RPC representation, falling back
+/// to UTF-8 for encodings that aren't known.
+pub fn deserialize_encoding(name: Option<&str>) -> crate::Encoding {
+    name.and_then(crate::Encoding::from_name)
+        .unwrap_or_default()
+}
+
+/// Serializes a [`crate::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: crate::Encoding) -> String {
+    encoding.name().to_string()
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
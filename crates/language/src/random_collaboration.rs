@@ -0,0 +1,326 @@
+//! A harness for randomized tests of buffers that are edited concurrently by several replicas.
+//!
+//! Replicas are connected through a [`Network`] that duplicates and reorders the operations
+//! it delivers. Every choice is made with the test's seeded random number generator, so a
+//! failure can be reproduced by running the test again with the `SEED` it printed. The
+//! number of replicas and operations can be configured with the `MIN_PEERS`, `MAX_PEERS`
+//! and `OPERATIONS` environment variables.
+
+use crate::{
+    diagnostic_set::DiagnosticSet,
+    proto::{self, deserialize_operation, serialize_operation},
+    Buffer, Capability, Diagnostic, DiagnosticEntry, Event, LanguageServerId,
+};
+use clock::ReplicaId;
+use collections::BTreeMap;
+use gpui::{AppContext, Context as _, Model};
+use parking_lot::Mutex;
+use rand::prelude::*;
+use std::{
+    env,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use text::{network::Network, Anchor, BufferId, OffsetRangeExt as _, Selection, SelectionGoal};
+use util::{post_inc, RandomCharIter};
+
+/// A set of replicas of one buffer, which are randomly mutated and synchronized.
+pub struct RandomCollaboration {
+    rng: StdRng,
+    network: Arc<Mutex<Network<proto::Operation, StdRng>>>,
+    replica_ids: Vec<ReplicaId>,
+    buffers: Vec<Model<Buffer>>,
+    active_selections: BTreeMap<ReplicaId, Arc<[Selection<Anchor>]>>,
+    max_peers: usize,
+    remaining_mutations: usize,
+    next_diagnostic_id: usize,
+    now: Instant,
+}
+
+impl RandomCollaboration {
+    /// Creates between `MIN_PEERS` and `MAX_PEERS` replicas of a random base text.
+    pub fn new(mut rng: StdRng, cx: &mut AppContext) -> Self {
+        let min_peers = env_var("MIN_PEERS").unwrap_or(1);
+        let max_peers = env_var("MAX_PEERS").unwrap_or(5);
+        let operations = env_var("OPERATIONS").unwrap_or(10);
+
+        let base_text_len = rng.gen_range(0..10);
+        let base_text = RandomCharIter::new(&mut rng)
+            .take(base_text_len)
+            .collect::<String>();
+        log::info!("initial text: {:?}", base_text);
+        let base_buffer = cx.new_model(|cx| {
+            Buffer::new(
+                0,
+                BufferId::new(cx.entity_id().as_u64()).unwrap(),
+                base_text.as_str(),
+            )
+        });
+
+        let mut this = Self {
+            network: Arc::new(Mutex::new(Network::new(rng.clone()))),
+            rng,
+            replica_ids: Vec::new(),
+            buffers: Vec::new(),
+            active_selections: BTreeMap::default(),
+            max_peers,
+            remaining_mutations: operations,
+            next_diagnostic_id: 0,
+            now: Instant::now(),
+        };
+        for replica_id in 0..this.rng.gen_range(min_peers..=max_peers) {
+            let buffer = this.replicate(&base_buffer, replica_id as ReplicaId, cx);
+            this.buffers.push(buffer);
+            this.replica_ids.push(replica_id as ReplicaId);
+            this.network.lock().add_peer(replica_id as ReplicaId);
+            log::info!("Adding initial peer with replica id {}", replica_id);
+        }
+        this
+    }
+
+    /// The replicas of the buffer, ordered by replica id.
+    pub fn buffers(&self) -> &[Model<Buffer>] {
+        &self.buffers
+    }
+
+    /// Mutates the replicas and delivers their operations until all of the mutations have
+    /// been made and every operation has been received.
+    pub fn run(&mut self, cx: &mut AppContext) {
+        while self.step(cx) {}
+    }
+
+    /// Performs a single random action: an edit, an undo or redo, a change of selections or
+    /// diagnostics, the addition of a replica, or the delivery of operations to a replica.
+    /// Returns false once there's nothing left to do.
+    pub fn step(&mut self, cx: &mut AppContext) -> bool {
+        let replica_index = self.rng.gen_range(0..self.replica_ids.len());
+        let replica_id = self.replica_ids[replica_index];
+        let buffer = self.buffers[replica_index].clone();
+        let mut new_buffer = None;
+        match self.rng.gen_range(0..100) {
+            0..=29 if self.remaining_mutations != 0 => {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.start_transaction_at(self.now);
+                    buffer.randomly_edit(&mut self.rng, 5, cx);
+                    buffer.end_transaction_at(self.now, cx);
+                    log::info!("buffer {} text: {:?}", buffer.replica_id(), buffer.text());
+                });
+                self.remaining_mutations -= 1;
+            }
+            30..=39 if self.remaining_mutations != 0 => {
+                buffer.update(cx, |buffer, cx| {
+                    if self.rng.gen_bool(0.2) {
+                        log::info!("peer {} clearing active selections", replica_id);
+                        self.active_selections.remove(&replica_id);
+                        buffer.remove_active_selections(cx);
+                    } else {
+                        let mut selections = Vec::new();
+                        for id in 0..self.rng.gen_range(1..=5) {
+                            let range = buffer.random_byte_range(0, &mut self.rng);
+                            selections.push(Selection {
+                                id,
+                                start: buffer.anchor_before(range.start),
+                                end: buffer.anchor_before(range.end),
+                                reversed: false,
+                                goal: SelectionGoal::None,
+                            });
+                        }
+                        let selections: Arc<[Selection<Anchor>]> = selections.into();
+                        log::info!(
+                            "peer {} setting active selections: {:?}",
+                            replica_id,
+                            selections
+                        );
+                        self.active_selections
+                            .insert(replica_id, selections.clone());
+                        buffer.set_active_selections(selections, false, Default::default(), cx);
+                    }
+                });
+                self.remaining_mutations -= 1;
+            }
+            40..=49 if self.remaining_mutations != 0 && replica_id == 0 => {
+                let entry_count = self.rng.gen_range(1..=5);
+                buffer.update(cx, |buffer, cx| {
+                    let diagnostics = DiagnosticSet::new(
+                        (0..entry_count).map(|_| {
+                            let range = buffer.random_byte_range(0, &mut self.rng);
+                            DiagnosticEntry {
+                                range: range.to_point_utf16(buffer),
+                                diagnostic: Diagnostic {
+                                    message: post_inc(&mut self.next_diagnostic_id).to_string(),
+                                    ..Default::default()
+                                },
+                            }
+                        }),
+                        buffer,
+                    );
+                    log::info!("peer {} setting diagnostics: {:?}", replica_id, diagnostics);
+                    buffer.update_diagnostics(LanguageServerId(0), diagnostics, cx);
+                });
+                self.remaining_mutations -= 1;
+            }
+            50..=59 if self.replica_ids.len() < self.max_peers => {
+                let new_replica_id = (0..=self.replica_ids.len() as ReplicaId)
+                    .filter(|new_replica_id| *new_replica_id != replica_id)
+                    .choose(&mut self.rng)
+                    .unwrap();
+                log::info!(
+                    "Adding new replica {} (replicating from {})",
+                    new_replica_id,
+                    replica_id
+                );
+                let replica = self.replicate(&buffer, new_replica_id, cx);
+                self.network.lock().replicate(replica_id, new_replica_id);
+
+                if new_replica_id as usize == self.replica_ids.len() {
+                    self.replica_ids.push(new_replica_id);
+                    new_buffer = Some(replica);
+                } else {
+                    // An existing replica is replaced, as if it had rejoined.
+                    while self.network.lock().has_unreceived(new_replica_id) {
+                        self.receive(&replica, new_replica_id, cx);
+                    }
+                    self.buffers[new_replica_id as usize] = replica;
+                }
+            }
+            60..=69 if self.remaining_mutations != 0 => {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.randomly_undo_redo(&mut self.rng, cx);
+                    log::info!("buffer {} text: {:?}", buffer.replica_id(), buffer.text());
+                });
+                self.remaining_mutations -= 1;
+            }
+            _ if self.network.lock().has_unreceived(replica_id) => {
+                self.receive(&buffer, replica_id, cx);
+            }
+            _ => {}
+        }
+
+        self.now += Duration::from_millis(self.rng.gen_range(0..=200));
+        self.buffers.extend(new_buffer);
+
+        for buffer in &self.buffers {
+            buffer.read(cx).check_invariants();
+        }
+
+        self.remaining_mutations != 0 || !self.network.lock().is_idle()
+    }
+
+    /// Asserts that every replica has the same version, text and diagnostics, and that each
+    /// one shows the selections that the other replicas last set.
+    pub fn assert_convergence(&self, cx: &AppContext) {
+        let first_buffer = self.buffers[0].read(cx).snapshot();
+        for buffer in &self.buffers[1..] {
+            let buffer = buffer.read(cx).snapshot();
+            assert_eq!(
+                buffer.version(),
+                first_buffer.version(),
+                "Replica {} version != Replica 0 version",
+                buffer.replica_id()
+            );
+            assert_eq!(
+                buffer.text(),
+                first_buffer.text(),
+                "Replica {} text != Replica 0 text",
+                buffer.replica_id()
+            );
+            assert_eq!(
+                buffer
+                    .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+                    .collect::<Vec<_>>(),
+                first_buffer
+                    .diagnostics_in_range::<_, usize>(0..first_buffer.len(), false)
+                    .collect::<Vec<_>>(),
+                "Replica {} diagnostics != Replica 0 diagnostics",
+                buffer.replica_id()
+            );
+        }
+
+        for buffer in &self.buffers {
+            let buffer = buffer.read(cx).snapshot();
+            let actual_remote_selections = buffer
+                .remote_selections_in_range(Anchor::MIN..Anchor::MAX)
+                .map(|(replica_id, _, _, selections)| (replica_id, selections.collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            let expected_remote_selections = self
+                .active_selections
+                .iter()
+                .filter(|(replica_id, _)| **replica_id != buffer.replica_id())
+                .map(|(replica_id, selections)| {
+                    (*replica_id, selections.iter().collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                actual_remote_selections,
+                expected_remote_selections,
+                "Replica {} remote selections != expected selections",
+                buffer.replica_id()
+            );
+        }
+    }
+
+    /// Creates a replica of the given buffer that broadcasts its operations to the network.
+    fn replicate(
+        &mut self,
+        buffer: &Model<Buffer>,
+        replica_id: ReplicaId,
+        cx: &mut AppContext,
+    ) -> Model<Buffer> {
+        let state = buffer.read(cx).to_proto();
+        let ops = cx
+            .background_executor()
+            .block(buffer.read(cx).serialize_ops(None, cx));
+        let group_interval = Duration::from_millis(self.rng.gen_range(0..=200));
+        let network = self.network.clone();
+        cx.new_model(|cx| {
+            let mut buffer =
+                Buffer::from_proto(replica_id, Capability::ReadWrite, state, None).unwrap();
+            buffer
+                .apply_ops(
+                    ops.into_iter().map(|op| deserialize_operation(op).unwrap()),
+                    cx,
+                )
+                .unwrap();
+            log::info!("replica {} text: {:?}", replica_id, buffer.text());
+            buffer.set_group_interval(group_interval);
+            cx.subscribe(&cx.handle(), move |buffer, _, event, _| {
+                if let Event::Operation(op) = event {
+                    network
+                        .lock()
+                        .broadcast(buffer.replica_id(), vec![serialize_operation(op)]);
+                }
+            })
+            .detach();
+            buffer
+        })
+    }
+
+    /// Applies some of the operations that the network has for the given replica.
+    fn receive(&mut self, buffer: &Model<Buffer>, replica_id: ReplicaId, cx: &mut AppContext) {
+        let ops = self
+            .network
+            .lock()
+            .receive(replica_id)
+            .into_iter()
+            .map(|op| deserialize_operation(op).unwrap())
+            .collect::<Vec<_>>();
+        if !ops.is_empty() {
+            log::info!(
+                "peer {} (version: {:?}) applying {} ops from the network. {:?}",
+                replica_id,
+                buffer.read(cx).version(),
+                ops.len(),
+                ops
+            );
+            buffer.update(cx, |buffer, cx| buffer.apply_ops(ops, cx).unwrap());
+        }
+    }
+}
+
+fn env_var(name: &str) -> Option<usize> {
+    env::var(name).ok().map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid `{name}` variable"))
+    })
+}
@@ -33,6 +33,7 @@ pub struct SyntaxSnapshot {
     parsed_version: clock::Global,
     interpolated_version: clock::Global,
     language_registry_version: usize,
+    changed_ranges: Vec<Range<Anchor>>,
 }
 
 #[derive(Default)]
@@ -254,6 +255,11 @@ impl SyntaxSnapshot {
         self.layers.is_empty()
     }
 
+    /// The ranges whose syntax changed during the most recent reparse.
+    pub fn changed_ranges(&self) -> &[Range<Anchor>] {
+        &self.changed_ranges
+    }
+
     fn interpolate(&mut self, text: &BufferSnapshot) {
         let edits = text
             .anchored_edits_since::<(usize, Point)>(&self.interpolated_version)
@@ -383,6 +389,7 @@ impl SyntaxSnapshot {
             .edits_since::<usize>(&self.parsed_version)
             .map(|edit| edit.new)
             .collect::<Vec<_>>();
+        self.changed_ranges.clear();
         self.reparse_with_ranges(text, root_language.clone(), edit_ranges, registry.as_ref());
 
         if let Some(registry) = registry {
@@ -658,6 +665,11 @@ impl SyntaxSnapshot {
                         changed_ranges = vec![step_start_byte..step_end_byte];
                     }
 
+                    self.changed_ranges
+                        .extend(changed_ranges.iter().map(|range| {
+                            text.anchor_before(range.start)..text.anchor_after(range.end)
+                        }));
+
                     if let (Some((config, registry)), false) = (
                         grammar.injection_config.as_ref().zip(registry.as_ref()),
                         changed_ranges.is_empty(),
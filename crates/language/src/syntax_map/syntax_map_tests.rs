@@ -253,6 +253,55 @@ fn test_dynamic_language_injection() {
     assert!(!syntax_map.contains_unknown_injections());
 }
 
+#[gpui::test]
+fn test_standard_injection_capture_names() {
+    let registry = Arc::new(LanguageRegistry::test());
+    let typescript = Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "TypeScript".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["ts".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_typescript::language_typescript()),
+        )
+        .with_injection_query(
+            r#"
+                (call_expression
+                    function: (identifier) @_name
+                    arguments: (template_string) @injection.content
+                    (#eq? @_name "html")
+                    (#set! injection.language "html"))
+            "#,
+        )
+        .unwrap(),
+    );
+    registry.add(typescript.clone());
+    registry.add(Arc::new(html_lang()));
+
+    let buffer = Buffer::new(
+        0,
+        BufferId::new(1).unwrap(),
+        "const page = html`<div>hi</div>`;".to_string(),
+    );
+
+    let mut syntax_map = SyntaxMap::new();
+    syntax_map.set_language_registry(registry);
+    syntax_map.reparse(typescript, &buffer);
+    assert_layers_for_range(
+        &syntax_map,
+        &buffer,
+        Point::new(0, 20)..Point::new(0, 20),
+        &[
+            "...(call_expression function: (identifier) arguments: (template_string)...",
+            "...(element (start_tag (tag_name)) (text) (end_tag (tag_name)))...",
+        ],
+    );
+}
+
 #[gpui::test]
 fn test_typing_multiple_new_injections() {
     let (buffer, syntax_map) = test_edit_sequence(
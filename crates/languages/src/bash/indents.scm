@@ -0,0 +1,10 @@
+(if_statement "fi" @end) @indent
+(do_group "done" @end) @indent
+(case_statement "esac" @end) @indent
+
+(elif_clause) @outdent
+(else_clause) @outdent
+
+(_ "[" "]" @end) @indent
+(_ "{" "}" @end) @indent
+(_ "(" ")" @end) @indent
@@ -0,0 +1,23 @@
+(call_expression
+  function: (identifier) @_name
+  arguments: (template_string) @content
+  (#eq? @_name "css")
+  (#set! "language" "css"))
+
+(call_expression
+  function: (identifier) @_name
+  arguments: (template_string) @content
+  (#eq? @_name "html")
+  (#set! "language" "html"))
+
+(call_expression
+  function: (identifier) @_name
+  arguments: (template_string) @content
+  (#match? @_name "^(sql|SQL)$")
+  (#set! "language" "sql"))
+
+(call_expression
+  function: (identifier) @_name
+  arguments: (template_string) @content
+  (#match? @_name "^(gql|graphql)$")
+  (#set! "language" "graphql"))
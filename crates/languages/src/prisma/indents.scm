@@ -0,0 +1 @@
+(_ "{" "}" @end) @indent
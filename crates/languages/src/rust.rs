@@ -9,6 +9,7 @@ use regex::Regex;
 use smol::fs::{self, File};
 use std::{any::Any, borrow::Cow, env::consts, path::PathBuf, str, sync::Arc};
 use util::{
+    asset_cache::AssetCache,
     async_maybe,
     fs::remove_matching,
     github::{latest_github_release, GitHubLspBinaryVersion},
@@ -66,12 +67,11 @@ impl LspAdapter for RustLspAdapter {
         let destination_path = container_dir.join(format!("rust-analyzer-{}", version.name));
 
         if fs::metadata(&destination_path).await.is_err() {
-            let mut response = delegate
-                .http_client()
-                .get(&version.url, Default::default(), true)
+            let archive = AssetCache::global()
+                .fetch_bytes(&version.url, delegate.http_client().as_ref())
                 .await
                 .map_err(|err| anyhow!("error downloading release: {}", err))?;
-            let decompressed_bytes = GzipDecoder::new(BufReader::new(response.body_mut()));
+            let decompressed_bytes = GzipDecoder::new(BufReader::new(archive.as_slice()));
             let mut file = File::create(&destination_path).await?;
             futures::io::copy(decompressed_bytes, &mut file).await?;
             // todo!("windows")
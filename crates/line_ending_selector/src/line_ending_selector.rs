@@ -0,0 +1,171 @@
+mod active_line_ending;
+
+pub use active_line_ending::ActiveLineEnding;
+use editor::Editor;
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    ParentElement, Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::{Buffer, LineEnding};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, Label, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(line_ending_selector, [ConvertLineEndings]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(LineEndingSelector::register).detach();
+}
+
+/// The short name shown for a line ending, e.g. in the status bar.
+fn line_ending_label(line_ending: LineEnding) -> &'static str {
+    match line_ending {
+        LineEnding::Unix => "LF",
+        LineEnding::Windows => "CRLF",
+    }
+}
+
+pub struct LineEndingSelector {
+    picker: View<Picker<LineEndingSelectorDelegate>>,
+}
+
+impl LineEndingSelector {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(move |workspace, _: &ConvertLineEndings, cx| {
+            Self::toggle(workspace, cx);
+        });
+    }
+
+    pub fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+
+        // The host writes shared files to disk, so guests can't change their line endings.
+        if buffer
+            .read(cx)
+            .file()
+            .map_or(false, |file| !file.is_local())
+        {
+            return None;
+        }
+
+        workspace.toggle_modal(cx, move |cx| LineEndingSelector::new(buffer, cx));
+        Some(())
+    }
+
+    fn new(buffer: Model<Buffer>, cx: &mut ViewContext<Self>) -> Self {
+        let delegate = LineEndingSelectorDelegate::new(cx.view().downgrade(), buffer, cx);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for LineEndingSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for LineEndingSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for LineEndingSelector {}
+impl ModalView for LineEndingSelector {}
+
+pub struct LineEndingSelectorDelegate {
+    line_ending_selector: WeakView<LineEndingSelector>,
+    buffer: Model<Buffer>,
+    line_endings: [LineEnding; 2],
+    selected_index: usize,
+}
+
+impl LineEndingSelectorDelegate {
+    fn new(
+        line_ending_selector: WeakView<LineEndingSelector>,
+        buffer: Model<Buffer>,
+        cx: &AppContext,
+    ) -> Self {
+        let line_endings = [LineEnding::Unix, LineEnding::Windows];
+        let current_line_ending = buffer.read(cx).line_ending();
+        let selected_index = line_endings
+            .iter()
+            .position(|line_ending| *line_ending == current_line_ending)
+            .unwrap_or(0);
+        Self {
+            line_ending_selector,
+            buffer,
+            line_endings,
+            selected_index,
+        }
+    }
+}
+
+impl PickerDelegate for LineEndingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Convert line endings to...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.line_endings.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(line_ending) = self.line_endings.get(self.selected_index).copied() {
+            self.buffer
+                .update(cx, |buffer, cx| buffer.set_line_ending(line_ending, cx));
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.line_ending_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, _: String, _: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        Task::ready(())
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let line_ending = self.line_endings[ix];
+        let mut label = match line_ending {
+            LineEnding::Unix => "LF (Unix)".to_string(),
+            LineEnding::Windows => "CRLF (Windows)".to_string(),
+        };
+        if self.buffer.read(cx).line_ending() == line_ending {
+            label.push_str(" (current)");
+        }
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(Label::new(label)),
+        )
+    }
+}
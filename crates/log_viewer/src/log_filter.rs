@@ -0,0 +1,211 @@
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Detects the severity of a log line from the first level keyword it contains.
+    pub fn for_line(line: &str) -> Option<Self> {
+        line.split(|c: char| !c.is_ascii_alphabetic())
+            .filter(|word| !word.is_empty())
+            .take(8)
+            .find_map(|word| match word {
+                "TRACE" | "trace" => Some(Self::Trace),
+                "DEBUG" | "debug" => Some(Self::Debug),
+                "INFO" | "info" => Some(Self::Info),
+                "WARN" | "WARNING" | "warn" | "warning" => Some(Self::Warning),
+                "ERROR" | "FATAL" | "error" | "fatal" => Some(Self::Error),
+                _ => None,
+            })
+    }
+}
+
+/// A query typed into the log filter bar.
+///
+/// Plain words must all appear in a line (case-insensitively) for it to be shown, while
+/// `since:<timestamp>` and `until:<timestamp>` restrict lines to a time window.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    terms: Vec<String>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+impl LogFilter {
+    pub fn parse(query: &str) -> Self {
+        let mut filter = Self::default();
+        for word in query.split_whitespace() {
+            if let Some(since) = word.strip_prefix("since:") {
+                filter.since = Some(since.to_string());
+            } else if let Some(until) = word.strip_prefix("until:") {
+                filter.until = Some(until.to_string());
+            } else {
+                filter.terms.push(word.to_lowercase());
+            }
+        }
+        filter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.since.is_none() && self.until.is_none()
+    }
+
+    /// Returns the ranges of rows in `text` that don't match the filter.
+    ///
+    /// Lines without a leading timestamp, such as the continuation of a stack trace,
+    /// are attributed to the most recent timestamp that precedes them.
+    pub fn hidden_rows(&self, text: &str) -> Vec<Range<u32>> {
+        self.hidden_rows_from(text, 0, &mut None)
+    }
+
+    /// Like [`Self::hidden_rows`], for `text` that starts at `first_row` of a log.
+    ///
+    /// `timestamp` is the most recent timestamp before `text`, and is updated to the most
+    /// recent one at its end, so that text appended later can be filtered on its own.
+    pub fn hidden_rows_from(
+        &self,
+        text: &str,
+        first_row: u32,
+        timestamp: &mut Option<String>,
+    ) -> Vec<Range<u32>> {
+        let mut hidden_rows: Vec<Range<u32>> = Vec::new();
+        let mut current_timestamp = None;
+        for (row, line) in text.split('\n').enumerate() {
+            let row = first_row + row as u32;
+            if let Some(timestamp) = leading_timestamp(line) {
+                current_timestamp = Some(timestamp);
+            }
+            if self.matches(line, current_timestamp.or(timestamp.as_deref())) {
+                continue;
+            }
+            match hidden_rows.last_mut() {
+                Some(range) if range.end == row => range.end = row + 1,
+                _ => hidden_rows.push(row..row + 1),
+            }
+        }
+        if let Some(current_timestamp) = current_timestamp {
+            *timestamp = Some(current_timestamp.to_string());
+        }
+        hidden_rows
+    }
+
+    fn matches(&self, line: &str, timestamp: Option<&str>) -> bool {
+        if self.since.is_some() || self.until.is_some() {
+            let Some(timestamp) = timestamp else {
+                return false;
+            };
+            // Timestamps in a single log share a format, so comparing a prefix of the
+            // same length as the bound orders them chronologically.
+            let comparable = |bound: &str| &timestamp[..timestamp.len().min(bound.len())];
+            if self
+                .since
+                .as_deref()
+                .map_or(false, |since| comparable(since) < since)
+            {
+                return false;
+            }
+            if self
+                .until
+                .as_deref()
+                .map_or(false, |until| comparable(until) > until)
+            {
+                return false;
+            }
+        }
+
+        if self.terms.is_empty() {
+            return true;
+        }
+        let line = line.to_lowercase();
+        self.terms.iter().all(|term| line.contains(term.as_str()))
+    }
+}
+
+/// Returns the timestamp a log line starts with, such as `2024-02-28T10:15:00Z` or
+/// `2024-02-28 10:15:00.123`, optionally wrapped in brackets.
+fn leading_timestamp(line: &str) -> Option<&str> {
+    let line = line.trim_start().trim_start_matches('[');
+    if !line.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut end = line
+        .find(|c: char| !(c.is_ascii_digit() || "-:.TZ+".contains(c)))
+        .unwrap_or(line.len());
+    // Allow a single space between the date and the time.
+    let rest = &line[end..];
+    if rest.starts_with(' ') && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        end += 1 + rest[1..]
+            .find(|c: char| !(c.is_ascii_digit() || ":.Z+".contains(c)))
+            .unwrap_or(rest.len() - 1);
+    }
+    let timestamp = &line[..end];
+    timestamp.contains('-').then_some(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_for_line() {
+        assert_eq!(
+            Severity::for_line("2024-02-28T10:15:00Z ERROR failed to connect"),
+            Some(Severity::Error)
+        );
+        assert_eq!(
+            Severity::for_line("[2024-02-28 10:15:00 WARN  server] slow request"),
+            Some(Severity::Warning)
+        );
+        assert_eq!(Severity::for_line("    at main.rs:12"), None);
+    }
+
+    #[test]
+    fn test_leading_timestamp() {
+        assert_eq!(
+            leading_timestamp("2024-02-28T10:15:00Z INFO started"),
+            Some("2024-02-28T10:15:00Z")
+        );
+        assert_eq!(
+            leading_timestamp("[2024-02-28 10:15:00.123] started"),
+            Some("2024-02-28 10:15:00.123")
+        );
+        assert_eq!(leading_timestamp("42 requests"), None);
+        assert_eq!(leading_timestamp("INFO started"), None);
+    }
+
+    #[test]
+    fn test_hidden_rows() {
+        let text = "\
+2024-02-28T10:00:00Z INFO starting
+2024-02-28T10:01:00Z ERROR connection refused
+    at connect (net.rs:10)
+2024-02-28T10:02:00Z INFO retrying
+2024-02-28T10:03:00Z ERROR connection refused";
+
+        assert_eq!(LogFilter::parse("").hidden_rows(text), vec![]);
+        assert_eq!(
+            LogFilter::parse("error").hidden_rows(text),
+            vec![0..1, 2..4]
+        );
+        assert_eq!(
+            LogFilter::parse("since:2024-02-28T10:01 until:2024-02-28T10:02").hidden_rows(text),
+            vec![0..1, 4..5]
+        );
+
+        // Appended lines without a timestamp of their own use the last one before them.
+        let filter = LogFilter::parse("until:2024-02-28T10:02");
+        let mut timestamp = None;
+        assert_eq!(filter.hidden_rows_from(text, 0, &mut timestamp), vec![4..5]);
+        assert_eq!(timestamp.as_deref(), Some("2024-02-28T10:03:00Z"));
+        assert_eq!(
+            filter.hidden_rows_from("    at connect (net.rs:10)", 5, &mut timestamp),
+            vec![5..6]
+        );
+    }
+}
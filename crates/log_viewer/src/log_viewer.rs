@@ -0,0 +1,448 @@
+mod log_filter;
+
+use collections::HashSet;
+use editor::{Anchor, Editor, EditorEvent, MoveToEnd};
+use gpui::{
+    actions, AppContext, EntityId, EventEmitter, Global, HighlightStyle, IntoElement,
+    ParentElement, Render, Styled, Subscription, View, ViewContext, VisualContext, WeakView,
+};
+use language::Point;
+use log_filter::{LogFilter, Severity};
+use std::{ops::Range, path::Path};
+use theme::ActiveTheme;
+use ui::{h_flex, prelude::*, IconButton, IconName, Label, Tooltip};
+use workspace::{
+    item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+};
+
+actions!(log_viewer, [ToggleLogMode, ToggleFollowTail]);
+
+const LOG_FILE_EXTENSIONS: &[&str] = &["log", "out"];
+
+enum TraceLines {}
+enum DebugLines {}
+enum InfoLines {}
+enum WarningLines {}
+enum ErrorLines {}
+
+/// Editors whose log mode was toggled explicitly, overriding the default based on
+/// their file extension.
+#[derive(Default)]
+struct LogModeOverrides(HashSet<EntityId>);
+
+impl Global for LogModeOverrides {}
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(LogModeOverrides::default());
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleLogMode, cx| {
+            let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+                return;
+            };
+            let editor_id = editor.entity_id();
+            cx.update_global(|overrides: &mut LogModeOverrides, _| {
+                if !overrides.0.remove(&editor_id) {
+                    overrides.0.insert(editor_id);
+                }
+            });
+        });
+    })
+    .detach();
+}
+
+fn is_log_mode(editor: &View<Editor>, cx: &AppContext) -> bool {
+    let is_log_file = editor
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .as_singleton()
+        .and_then(|buffer| buffer.read(cx).file().map(|file| file.path().clone()))
+        .map_or(false, |path| has_log_extension(&path));
+    let is_overridden = cx
+        .try_global::<LogModeOverrides>()
+        .map_or(false, |overrides| overrides.0.contains(&editor.entity_id()));
+    is_log_file != is_overridden
+}
+
+fn has_log_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| LOG_FILE_EXTENSIONS.contains(&extension))
+}
+
+/// A toolbar row shown for editors in log mode. It colors lines by severity, keeps the
+/// cursor at the end of the file as it grows unless it was moved elsewhere, and hides
+/// lines that don't match the filter query by folding them, leaving the buffer itself
+/// untouched.
+pub struct LogToolbar {
+    editor: Option<WeakView<Editor>>,
+    filter_editor: View<Editor>,
+    filter: LogFilter,
+    hidden_row_count: u32,
+    follow_tail: bool,
+    processed: Option<ProcessedText>,
+    editor_subscription: Option<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// The part of the active editor's text that has been highlighted and filtered.
+struct ProcessedText {
+    /// The buffer's version when it was processed, or `None` for multi-buffers.
+    version: Option<clock::Global>,
+    len: usize,
+    /// The most recent timestamp in the text, which applies to lines appended later
+    /// until one of them has its own.
+    timestamp: Option<String>,
+    lines_by_severity: [Vec<Range<Anchor>>; 5],
+}
+
+impl LogToolbar {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let filter_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Filter lines, e.g. error since:2024-02-28T10:00", cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe(&filter_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    this.filter = LogFilter::parse(&this.filter_editor.read(cx).text(cx));
+                    this.refresh(true, cx);
+                }
+            }),
+            cx.observe_global::<LogModeOverrides>(|this, cx| {
+                let location = this.location(cx);
+                if location == ToolbarItemLocation::Hidden {
+                    this.clear(cx);
+                } else {
+                    this.refresh(true, cx);
+                }
+                cx.emit(ToolbarItemEvent::ChangeLocation(location));
+            }),
+        ];
+
+        Self {
+            editor: None,
+            filter_editor,
+            filter: LogFilter::default(),
+            hidden_row_count: 0,
+            follow_tail: true,
+            processed: None,
+            editor_subscription: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn active_editor(&self) -> Option<View<Editor>> {
+        self.editor.as_ref()?.upgrade()
+    }
+
+    fn location(&self, cx: &AppContext) -> ToolbarItemLocation {
+        match self.active_editor() {
+            Some(editor) if is_log_mode(&editor, cx) => ToolbarItemLocation::Secondary,
+            _ => ToolbarItemLocation::Hidden,
+        }
+    }
+
+    fn toggle_follow_tail(&mut self, _: &ToggleFollowTail, cx: &mut ViewContext<Self>) {
+        self.follow_tail = !self.follow_tail;
+        if self.follow_tail {
+            if let Some(editor) = self.active_editor() {
+                editor.update(cx, |editor, cx| editor.move_to_end(&MoveToEnd, cx));
+            }
+        }
+        cx.notify();
+    }
+
+    /// Applies severity highlights and the line filter to the active editor's text.
+    ///
+    /// When text was only appended since the last time, and `rescan` isn't set, only
+    /// the new lines are processed, so that following a growing log stays cheap.
+    fn refresh(&mut self, rescan: bool, cx: &mut ViewContext<Self>) {
+        let Some(editor) = self.active_editor() else {
+            return;
+        };
+        let filter = self.filter.clone();
+        let follow_tail = self.follow_tail;
+        let status = cx.theme().status().clone();
+        let previous = self.processed.take();
+        let mut hidden_row_count = self.hidden_row_count;
+        let processed = editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton();
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let version = buffer.as_ref().map(|buffer| buffer.read(cx).version());
+
+            // Only scroll along with new lines if the cursor was at the end already, so
+            // that reading earlier parts of the log isn't interrupted.
+            let was_at_end = previous.as_ref().map_or(true, |previous| {
+                editor.selections.newest::<usize>(cx).head() >= previous.len
+            });
+
+            let appended_to = previous.filter(|previous| {
+                !rescan
+                    && previous.len > 0
+                    && buffer.as_ref().zip(previous.version.as_ref()).map_or(
+                        false,
+                        |(buffer, version)| {
+                            buffer
+                                .read(cx)
+                                .edits_since::<usize>(version)
+                                .all(|edit| edit.old.start >= previous.len)
+                        },
+                    )
+                    && snapshot.reversed_chars_at(previous.len).next() == Some('\n')
+            });
+            let mut processed = match appended_to {
+                Some(previous) => previous,
+                None => {
+                    hidden_row_count = 0;
+                    editor.unfold_ranges([0..snapshot.len()], true, false, cx);
+                    ProcessedText {
+                        version: None,
+                        len: 0,
+                        timestamp: None,
+                        lines_by_severity: Default::default(),
+                    }
+                }
+            };
+
+            // The empty line after a trailing newline is left alone, so that lines
+            // appended there don't extend the highlights and folds before them.
+            let first_row = snapshot.offset_to_point(processed.len).row;
+            let text = snapshot
+                .text_for_range(processed.len..snapshot.len())
+                .collect::<String>();
+            let text = text.strip_suffix('\n').unwrap_or(&text);
+            if !text.is_empty() {
+                let row_range = |rows: Range<u32>| {
+                    let end_row = rows.end - 1;
+                    snapshot.anchor_before(Point::new(rows.start, 0))
+                        ..snapshot.anchor_after(Point::new(end_row, snapshot.line_len(end_row)))
+                };
+                for (row, line) in text.split('\n').enumerate() {
+                    if let Some(severity) = Severity::for_line(line) {
+                        let row = first_row + row as u32;
+                        processed.lines_by_severity[severity as usize]
+                            .push(row_range(row..row + 1));
+                    }
+                }
+
+                let hidden_rows =
+                    filter.hidden_rows_from(text, first_row, &mut processed.timestamp);
+                hidden_row_count += hidden_rows
+                    .iter()
+                    .map(|rows| rows.end - rows.start)
+                    .sum::<u32>();
+                editor.fold_ranges(
+                    hidden_rows.into_iter().map(|rows| {
+                        // Fold from the end of the preceding visible line, so that the
+                        // hidden lines collapse into a placeholder rather than leaving a
+                        // blank row.
+                        let end_row = rows.end - 1;
+                        let end = Point::new(end_row, snapshot.line_len(end_row));
+                        let start = match rows.start.checked_sub(1) {
+                            Some(row) => Point::new(row, snapshot.line_len(row)),
+                            None => Point::zero(),
+                        };
+                        start..end
+                    }),
+                    false,
+                    cx,
+                );
+            }
+
+            let [trace, debug, info, warning, error] = processed.lines_by_severity.clone();
+            let style = |color| HighlightStyle {
+                color: Some(color),
+                ..Default::default()
+            };
+            editor.highlight_text::<TraceLines>(trace, style(status.hint), cx);
+            editor.highlight_text::<DebugLines>(debug, style(status.hidden), cx);
+            editor.highlight_text::<InfoLines>(info, style(status.info), cx);
+            editor.highlight_text::<WarningLines>(warning, style(status.warning), cx);
+            editor.highlight_text::<ErrorLines>(error, style(status.error), cx);
+
+            if follow_tail && was_at_end {
+                editor.move_to_end(&MoveToEnd, cx);
+            }
+            processed.version = version;
+            processed.len = snapshot.len();
+            processed
+        });
+        self.processed = Some(processed);
+        self.hidden_row_count = hidden_row_count;
+        cx.notify();
+    }
+
+    fn clear(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(editor) = self.active_editor() else {
+            return;
+        };
+        editor.update(cx, |editor, cx| {
+            editor.clear_highlights::<TraceLines>(cx);
+            editor.clear_highlights::<DebugLines>(cx);
+            editor.clear_highlights::<InfoLines>(cx);
+            editor.clear_highlights::<WarningLines>(cx);
+            editor.clear_highlights::<ErrorLines>(cx);
+            if !self.filter.is_empty() {
+                let len = editor.buffer().read(cx).len(cx);
+                editor.unfold_ranges([0..len], true, false, cx);
+            }
+        });
+        self.hidden_row_count = 0;
+        self.processed = None;
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for LogToolbar {}
+
+impl ToolbarItemView for LogToolbar {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) -> ToolbarItemLocation {
+        self.editor = None;
+        self.editor_subscription = None;
+        self.processed = None;
+        let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) else {
+            return ToolbarItemLocation::Hidden;
+        };
+
+        self.editor = Some(editor.downgrade());
+        if !is_log_mode(&editor, cx) {
+            return ToolbarItemLocation::Hidden;
+        }
+
+        // Clean buffers are reloaded when their file changes on disk, which shows up as
+        // an edit, so this is also how the view follows a growing log file.
+        self.editor_subscription = Some(cx.subscribe(&editor, |this, _, event, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.refresh(false, cx);
+            }
+        }));
+        self.refresh(true, cx);
+        ToolbarItemLocation::Secondary
+    }
+}
+
+impl Render for LogToolbar {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let hidden_label = if self.hidden_row_count > 0 {
+            format!("{} lines hidden", self.hidden_row_count)
+        } else {
+            String::new()
+        };
+
+        h_flex()
+            .key_context("LogToolbar")
+            .on_action(cx.listener(Self::toggle_follow_tail))
+            .w_full()
+            .gap_2()
+            .child(
+                h_flex()
+                    .flex_1()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .rounded_lg()
+                    .child(Icon::new(IconName::Filter).color(Color::Muted))
+                    .child(self.filter_editor.clone()),
+            )
+            .child(Label::new(hidden_label).color(Color::Muted))
+            .child(
+                IconButton::new("log-follow-tail", IconName::ArrowDown)
+                    .selected(self.follow_tail)
+                    .on_click(
+                        cx.listener(|this, _, cx| this.toggle_follow_tail(&ToggleFollowTail, cx)),
+                    )
+                    .tooltip(|cx| Tooltip::for_action("Follow Tail", &ToggleFollowTail, cx)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::ToPoint;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{Buffer, BufferId};
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_following_appended_lines(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            init(cx);
+        });
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(
+                0,
+                BufferId::new(cx.entity_id().as_u64()).unwrap(),
+                "INFO starting\nERROR failed\n",
+            )
+        });
+        let cx = cx.add_empty_window();
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer.clone(), None, cx));
+        cx.update(|cx| {
+            cx.update_global(|overrides: &mut LogModeOverrides, _| {
+                overrides.0.insert(editor.entity_id());
+            })
+        });
+        let _toolbar = cx.new_view(|cx| {
+            let mut toolbar = LogToolbar::new(cx);
+            toolbar.set_active_pane_item(Some(&editor), cx);
+            toolbar
+        });
+
+        let append = |text: &str, cx: &mut VisualTestContext| {
+            buffer.update(cx, |buffer, cx| {
+                let len = buffer.len();
+                buffer.edit([(len..len, text)], None, cx);
+            });
+            cx.run_until_parked();
+        };
+        let state = |cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let rows = |ranges: Option<(HighlightStyle, &[Range<Anchor>])>| {
+                    ranges
+                        .map(|(_, ranges)| ranges)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|range| range.start.to_point(&snapshot).row)
+                        .collect::<Vec<_>>()
+                };
+                (
+                    rows(editor.text_highlights::<ErrorLines>(cx)),
+                    rows(editor.text_highlights::<WarningLines>(cx)),
+                    editor.selections.newest::<usize>(cx).head() == snapshot.len(),
+                )
+            })
+        };
+        assert_eq!(state(cx), (vec![1], vec![], true));
+
+        // Appended lines are highlighted, and the cursor follows them while it's at
+        // the end.
+        append("ERROR again\n", cx);
+        assert_eq!(state(cx), (vec![1, 2], vec![], true));
+
+        // Once the cursor moves away from the end, it stays where it is.
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |selections| selections.select_ranges([0..0]))
+        });
+        append("WARN slow\n", cx);
+        assert_eq!(state(cx), (vec![1, 2], vec![3], false));
+
+        // Edits before the end cause the whole log to be processed again.
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "ERROR ")], None, cx));
+        cx.run_until_parked();
+        assert_eq!(state(cx), (vec![0, 1, 2], vec![3], false));
+    }
+}
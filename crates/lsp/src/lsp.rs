@@ -522,6 +522,11 @@ impl LanguageServer {
                         document_changes: Some(true),
                         ..WorkspaceEditClientCapabilities::default()
                     }),
+                    file_operations: Some(WorkspaceFileOperationsClientCapabilities {
+                        did_rename: Some(true),
+                        will_rename: Some(true),
+                        ..Default::default()
+                    }),
                     ..Default::default()
                 }),
                 text_document: Some(TextDocumentClientCapabilities {
@@ -1184,6 +1189,54 @@ impl FakeLanguageServer {
         }
     }
 
+    /// Waits for the next notification the client sends that isn't handled, panicking if
+    /// it isn't of the specified type.
+    pub async fn expect_notification<T: notification::Notification>(&mut self) -> T::Params {
+        use futures::StreamExt as _;
+
+        self.server.executor.start_waiting();
+        let (method, params) = self
+            .notifications_rx
+            .next()
+            .await
+            .expect("fake language server was dropped");
+        assert_eq!(
+            method,
+            T::METHOD,
+            "expected a {:?} notification, but received {}",
+            T::METHOD,
+            params
+        );
+        serde_json::from_str::<T::Params>(&params).unwrap()
+    }
+
+    /// Returns the methods and parameters of the unhandled notifications the client has sent
+    /// since they were last received, without waiting for more.
+    pub fn take_notifications(&mut self) -> Vec<(String, Value)> {
+        let mut notifications = Vec::new();
+        while let Ok((method, params)) = self.notifications_rx.try_recv() {
+            notifications.push((method, serde_json::from_str(&params).unwrap()));
+        }
+        notifications
+    }
+
+    /// Responds to every request of a specific kind with the given result. Removes any
+    /// existing handler for the specified request type.
+    pub fn respond_with<T>(
+        &self,
+        result: T::Result,
+    ) -> futures::channel::mpsc::UnboundedReceiver<()>
+    where
+        T: 'static + request::Request,
+        T::Params: 'static + Send,
+        T::Result: 'static + Clone + Send,
+    {
+        self.handle_request::<T, _, _>(move |_, _| {
+            let result = result.clone();
+            async move { Ok(result) }
+        })
+    }
+
     /// Registers a handler for a specific kind of request. Removes any existing handler for specified request type.
     pub fn handle_request<T, F, Fut>(
         &self,
@@ -1288,6 +1341,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt as _;
     use gpui::TestAppContext;
 
     #[ctor::ctor]
@@ -1359,6 +1413,69 @@ mod tests {
         fake.receive_notification::<notification::Exit>().await;
     }
 
+    #[gpui::test]
+    async fn test_fake_canned_responses_and_notifications(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            release_channel::init("0.0.0", cx);
+        });
+        let (server, mut fake) =
+            FakeLanguageServer::new("the-lsp".to_string(), Default::default(), cx.to_async());
+        let server = cx.update(|cx| server.initialize(None, cx)).await.unwrap();
+        fake.expect_notification::<notification::Initialized>()
+            .await;
+
+        let mut responded = fake.respond_with::<request::HoverRequest>(Some(Hover {
+            contents: HoverContents::Scalar(MarkedString::String("canned".to_string())),
+            range: None,
+        }));
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier::new(Url::from_str("file://a/b").unwrap()),
+                position: Position::new(0, 0),
+            },
+            work_done_progress_params: Default::default(),
+        };
+        for _ in 0..2 {
+            let hover = server
+                .request::<request::HoverRequest>(params.clone())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                hover.contents,
+                HoverContents::Scalar(MarkedString::String("canned".to_string()))
+            );
+            responded.next().await.unwrap();
+        }
+
+        server
+            .notify::<notification::DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    Url::from_str("file://a/b").unwrap(),
+                    "rust".to_string(),
+                    0,
+                    "".to_string(),
+                ),
+            })
+            .unwrap();
+        server
+            .notify::<notification::DidCloseTextDocument>(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier::new(Url::from_str("file://a/b").unwrap()),
+            })
+            .unwrap();
+        fake.expect_notification::<notification::DidOpenTextDocument>()
+            .await;
+        cx.executor().run_until_parked();
+        assert_eq!(
+            fake.take_notifications(),
+            [(
+                "textDocument/didClose".to_string(),
+                json!({ "textDocument": { "uri": "file://a/b" } })
+            )]
+        );
+        assert_eq!(fake.take_notifications(), []);
+    }
+
     #[gpui::test]
     async fn test_read_headers() {
         let mut buf = Vec::new();
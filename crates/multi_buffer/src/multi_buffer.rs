@@ -94,6 +94,9 @@ pub enum Event {
     Closed,
     DirtyChanged,
     DiagnosticsUpdated,
+    RemoteSelectionsChanged {
+        replica_id: ReplicaId,
+    },
 }
 
 #[derive(Clone)]
@@ -399,6 +402,19 @@ impl MultiBuffer {
         self.len(cx) != 0
     }
 
+    /// Returns the outline of the buffer, if this multi-buffer contains a single one.
+    ///
+    /// Unlike [`MultiBufferSnapshot::outline`], this reuses the outline computed
+    /// by a previous call for the parts of the buffer whose syntax didn't change.
+    pub fn outline(
+        &self,
+        theme: Option<&Arc<SyntaxTheme>>,
+        cx: &AppContext,
+    ) -> Option<Outline<Anchor>> {
+        let outline = self.as_singleton()?.read(cx).outline(theme)?;
+        self.read(cx).outline_from_buffer_outline(outline)
+    }
+
     pub fn symbols_containing<T: ToOffset>(
         &self,
         offset: T,
@@ -1398,6 +1414,117 @@ impl MultiBuffer {
         cx.notify();
     }
 
+    /// Grows the context of the given excerpts by `line_count` lines in each direction,
+    /// stopping at neighboring excerpts of the same buffer so that they don't overlap.
+    pub fn expand_excerpts(
+        &mut self,
+        excerpt_ids: impl IntoIterator<Item = ExcerptId>,
+        line_count: u32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if line_count == 0 {
+            return;
+        }
+        self.sync(cx);
+
+        let mut snapshot = self.snapshot.borrow_mut();
+        let mut locators = excerpt_ids
+            .into_iter()
+            .map(|excerpt_id| snapshot.excerpt_locator_for_id(excerpt_id).clone())
+            .collect::<Vec<_>>();
+        locators.sort_unstable();
+        locators.dedup();
+
+        let mut new_excerpts = SumTree::new();
+        let mut cursor = snapshot.excerpts.cursor::<(Option<&Locator>, usize)>();
+        let mut edits = Vec::new();
+        for locator in &locators {
+            new_excerpts.append(cursor.slice(&Some(locator), Bias::Left, &()), &());
+            let Some(excerpt) = cursor.item().filter(|excerpt| excerpt.locator == *locator) else {
+                continue;
+            };
+            let excerpt = excerpt.clone();
+            let old_start = cursor.start().1;
+            cursor.next(&());
+
+            let buffer = &excerpt.buffer;
+            let max_point = buffer.max_point();
+            let context = excerpt.range.context.to_point(buffer);
+            let mut start = Point::new(context.start.row.saturating_sub(line_count), 0);
+            let end_row = cmp::min(context.end.row + line_count, max_point.row);
+            let mut end = if context.end.column == 0 {
+                Point::new(end_row, 0)
+            } else {
+                Point::new(end_row, buffer.line_len(end_row))
+            };
+            if let Some(previous) = new_excerpts
+                .last()
+                .filter(|previous| previous.buffer_id == excerpt.buffer_id)
+            {
+                let previous_end = previous.range.context.end.to_point(buffer);
+                if previous_end <= context.start {
+                    let min_start = if previous_end.column == 0 {
+                        previous_end
+                    } else {
+                        Point::new(previous_end.row + 1, 0)
+                    };
+                    start = start.max(min_start.min(context.start));
+                }
+            }
+            if let Some(next) = cursor
+                .item()
+                .filter(|next| next.buffer_id == excerpt.buffer_id)
+            {
+                let next_start = next.range.context.start.to_point(buffer);
+                if next_start >= context.end {
+                    let max_end = if context.end.column == 0 || next_start.row == 0 {
+                        Point::new(next_start.row, 0)
+                    } else {
+                        let row = next_start.row - 1;
+                        Point::new(row, buffer.line_len(row))
+                    };
+                    end = end.min(max_end.max(context.end));
+                }
+            }
+
+            let new_excerpt = Excerpt::new(
+                excerpt.id,
+                excerpt.locator.clone(),
+                excerpt.buffer_id,
+                buffer.clone(),
+                ExcerptRange {
+                    context: buffer.anchor_before(start)..buffer.anchor_after(end),
+                    primary: excerpt.range.primary.clone(),
+                },
+                excerpt.has_trailing_newline,
+            );
+            let new_start = new_excerpts.summary().text.len;
+            edits.push(Edit {
+                old: old_start..old_start + excerpt.text_summary.len,
+                new: new_start..new_start + new_excerpt.text_summary.len,
+            });
+            new_excerpts.push(new_excerpt, &());
+        }
+        let suffix = cursor.suffix(&());
+        let changed_trailing_excerpt = suffix.is_empty();
+        new_excerpts.append(suffix, &());
+        drop(cursor);
+        snapshot.excerpts = new_excerpts;
+
+        if edits.is_empty() {
+            return;
+        }
+        if changed_trailing_excerpt {
+            snapshot.trailing_excerpt_update_count += 1;
+        }
+
+        self.subscriptions.publish_mut(edits);
+        cx.emit(Event::Edited {
+            singleton_buffer_edited: false,
+        });
+        cx.notify();
+    }
+
     pub fn wait_for_anchors<'a>(
         &self,
         anchors: impl 'a + Iterator<Item = Anchor>,
@@ -1465,6 +1592,11 @@ impl MultiBuffer {
             language::Event::LanguageChanged => Event::LanguageChanged,
             language::Event::Reparsed => Event::Reparsed,
             language::Event::DiagnosticsUpdated => Event::DiagnosticsUpdated,
+            language::Event::RemoteSelectionsChanged { replica_id } => {
+                Event::RemoteSelectionsChanged {
+                    replica_id: *replica_id,
+                }
+            }
             language::Event::Closed => Event::Closed,
             language::Event::CapabilityChanged => {
                 self.capability = buffer.read(cx).capability();
@@ -1534,11 +1666,7 @@ impl MultiBuffer {
             .and_then(|(buffer, offset, _)| buffer.read(cx).language_at(offset))
     }
 
-    pub fn settings_at<'a, T: ToOffset>(
-        &self,
-        point: T,
-        cx: &'a AppContext,
-    ) -> &'a LanguageSettings {
+    pub fn settings_at<T: ToOffset>(&self, point: T, cx: &AppContext) -> Arc<LanguageSettings> {
         let mut language = None;
         let mut file = None;
         if let Some((buffer, offset, _)) = self.point_to_buffer_offset(point, cx) {
@@ -2954,6 +3082,28 @@ impl MultiBufferSnapshot {
         ))
     }
 
+    /// Returns the ranges of the opening and closing tags of the innermost markup element
+    /// containing the given range, or None if there is none within the range's excerpt.
+    pub fn innermost_enclosing_tag_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+
+        let (open, close) = excerpt
+            .buffer()
+            .innermost_enclosing_tag_ranges(excerpt.map_range_to_buffer(range))?;
+        if !excerpt.contains_buffer_range(open.start..close.end) {
+            return None;
+        }
+
+        Some((
+            excerpt.map_range_from_buffer(open),
+            excerpt.map_range_from_buffer(close),
+        ))
+    }
+
     /// Returns enclosing bracket ranges containing the given range or returns None if the range is
     /// not contained in a single excerpt
     pub fn enclosing_bracket_ranges<'a, T: ToOffset>(
@@ -3051,11 +3201,7 @@ impl MultiBufferSnapshot {
             .and_then(|(buffer, offset)| buffer.language_at(offset))
     }
 
-    pub fn settings_at<'a, T: ToOffset>(
-        &'a self,
-        point: T,
-        cx: &'a AppContext,
-    ) -> &'a LanguageSettings {
+    pub fn settings_at<T: ToOffset>(&self, point: T, cx: &AppContext) -> Arc<LanguageSettings> {
         let mut language = None;
         let mut file = None;
         if let Some((buffer, offset)) = self.point_to_buffer_offset(point) {
@@ -3271,9 +3417,32 @@ impl MultiBufferSnapshot {
         Some(excerpt.map_range_from_buffer(ancestor_buffer_range))
     }
 
+    pub fn range_for_syntax_descendant<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        toward_end: bool,
+    ) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+
+        let descendant_buffer_range = excerpt
+            .buffer()
+            .range_for_syntax_descendant(excerpt.map_range_to_buffer(range), toward_end)?;
+
+        Some(excerpt.map_range_from_buffer(descendant_buffer_range))
+    }
+
     pub fn outline(&self, theme: Option<&SyntaxTheme>) -> Option<Outline<Anchor>> {
-        let (excerpt_id, _, buffer) = self.as_singleton()?;
+        let (_, _, buffer) = self.as_singleton()?;
         let outline = buffer.outline(theme)?;
+        self.outline_from_buffer_outline(outline)
+    }
+
+    fn outline_from_buffer_outline(
+        &self,
+        outline: Outline<text::Anchor>,
+    ) -> Option<Outline<Anchor>> {
+        let (excerpt_id, _, _) = self.as_singleton()?;
         Some(Outline::new(
             outline
                 .items
@@ -4756,6 +4925,68 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_expand_excerpts(cx: &mut AppContext) {
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(
+                0,
+                BufferId::new(cx.entity_id().as_u64()).unwrap(),
+                sample_text(20, 3, 'a'),
+            )
+        });
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let excerpt_ids = multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts(
+                buffer.clone(),
+                [
+                    ExcerptRange {
+                        context: Point::new(3, 0)..Point::new(4, 3),
+                        primary: None,
+                    },
+                    ExcerptRange {
+                        context: Point::new(7, 0)..Point::new(7, 3),
+                        primary: None,
+                    },
+                ],
+                cx,
+            )
+        });
+        let subscription = multibuffer.update(cx, |multibuffer, _| multibuffer.subscribe());
+        assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "ddd\neee\nhhh");
+
+        // Expanding stops before the next excerpt of the same buffer.
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.expand_excerpts([excerpt_ids[0]], 2, cx)
+        });
+        assert_eq!(
+            multibuffer.read(cx).snapshot(cx).text(),
+            "bbb\nccc\nddd\neee\nfff\nggg\nhhh"
+        );
+        assert_eq!(
+            subscription.consume().into_inner(),
+            [Edit {
+                old: 0..7,
+                new: 0..23
+            }]
+        );
+
+        // And after the previous one.
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.expand_excerpts([excerpt_ids[1]], 1, cx)
+        });
+        assert_eq!(
+            multibuffer.read(cx).snapshot(cx).text(),
+            "bbb\nccc\nddd\neee\nfff\nggg\nhhh\niii"
+        );
+        assert_eq!(
+            subscription.consume().into_inner(),
+            [Edit {
+                old: 24..27,
+                new: 24..31
+            }]
+        );
+    }
+
     #[gpui::test]
     async fn test_stream_excerpts_with_context_lines(cx: &mut TestAppContext) {
         let buffer = cx.new_model(|cx| {
@@ -9,7 +9,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use util::http::HttpClient;
+use util::{asset_cache::AssetCache, http::HttpClient};
 
 const VERSION: &str = "v18.15.0";
 
@@ -101,13 +101,12 @@ impl RealNodeRuntime {
 
             let file_name = format!("node-{VERSION}-{os}-{arch}.tar.gz");
             let url = format!("https://nodejs.org/dist/{VERSION}/{file_name}");
-            let mut response = self
-                .http
-                .get(&url, Default::default(), true)
+            let tarball = AssetCache::global()
+                .fetch_bytes(&url, self.http.as_ref())
                 .await
                 .context("error downloading Node binary tarball")?;
 
-            let decompressed_bytes = GzipDecoder::new(BufReader::new(response.body_mut()));
+            let decompressed_bytes = GzipDecoder::new(BufReader::new(tarball.as_slice()));
             let archive = Archive::new(decompressed_bytes);
             archive.unpack(&node_containing_dir).await?;
         }
@@ -0,0 +1,266 @@
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A Jupyter notebook, as stored in an `.ipynb` file.
+///
+/// Only the cell sources are edited. Every other field is kept as it was read, so that
+/// saving doesn't drop metadata or outputs written by other tools.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Notebook {
+    pub cells: Vec<Cell>,
+    #[serde(flatten)]
+    rest: Map<String, Value>,
+}
+
+impl Notebook {
+    pub fn parse(text: &str) -> Result<Self> {
+        serde_json::from_str(text).context("failed to parse notebook")
+    }
+
+    /// Serializes the notebook with the single-space indentation Jupyter uses, to keep
+    /// diffs against files saved by Jupyter small.
+    pub fn to_json(&self) -> Result<String> {
+        let mut json = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+        self.serialize(&mut serializer)?;
+        json.push(b'\n');
+        Ok(String::from_utf8(json)?)
+    }
+
+    /// The name of the language the notebook's kernel runs, such as `python`.
+    pub fn language_name(&self) -> Option<&str> {
+        let metadata = self.rest.get("metadata")?;
+        metadata
+            .pointer("/language_info/name")
+            .or_else(|| metadata.pointer("/kernelspec/language"))
+            .and_then(Value::as_str)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellKind {
+    Code,
+    Markdown,
+    Raw,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cell(Map<String, Value>);
+
+impl Cell {
+    pub fn kind(&self) -> CellKind {
+        match self.0.get("cell_type").and_then(Value::as_str) {
+            Some("code") => CellKind::Code,
+            Some("markdown") => CellKind::Markdown,
+            _ => CellKind::Raw,
+        }
+    }
+
+    pub fn source(&self) -> String {
+        multiline_string(self.0.get("source"))
+    }
+
+    /// Replaces the cell's source, storing it as a list of lines like Jupyter does.
+    pub fn set_source(&mut self, source: &str) {
+        let lines = source
+            .split_inclusive('\n')
+            .map(|line| Value::String(line.to_string()))
+            .collect();
+        self.0.insert("source".to_string(), Value::Array(lines));
+    }
+
+    pub fn execution_count(&self) -> Option<u64> {
+        self.0.get("execution_count")?.as_u64()
+    }
+
+    pub fn outputs(&self) -> Vec<CellOutput> {
+        let Some(outputs) = self.0.get("outputs").and_then(Value::as_array) else {
+            return Vec::new();
+        };
+        outputs.iter().filter_map(CellOutput::from_json).collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CellOutput {
+    Text(String),
+    Error(String),
+    /// Output that can't be shown as text, identified by its MIME type.
+    Unsupported(String),
+}
+
+impl CellOutput {
+    const TEXT_MIME_TYPES: &'static [&'static str] = &["text/markdown", "text/plain"];
+
+    fn from_json(output: &Value) -> Option<Self> {
+        match output.get("output_type")?.as_str()? {
+            "stream" => Some(Self::Text(multiline_string(output.get("text")))),
+            "execute_result" | "display_data" => {
+                let data = output.get("data")?.as_object()?;
+                Self::TEXT_MIME_TYPES
+                    .iter()
+                    .find_map(|mime_type| data.get(*mime_type))
+                    .map(|text| Self::Text(multiline_string(Some(text))))
+                    .or_else(|| {
+                        let mime_type = data.keys().next()?;
+                        Some(Self::Unsupported(mime_type.clone()))
+                    })
+            }
+            "error" => {
+                let traceback = output
+                    .get("traceback")
+                    .and_then(Value::as_array)
+                    .map(|lines| {
+                        lines
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .map(strip_ansi_escapes)
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+                if traceback.is_empty() {
+                    let name = output.get("ename")?.as_str()?;
+                    let value = output.get("evalue")?.as_str()?;
+                    Some(Self::Error(format!("{name}: {value}")))
+                } else {
+                    Some(Self::Error(traceback))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        match self {
+            Self::Text(text) | Self::Error(text) => text.trim_end_matches('\n').to_string(),
+            Self::Unsupported(mime_type) => format!("<{mime_type} output>"),
+        }
+    }
+}
+
+/// nbformat allows multiline strings to be stored either as a single string or as a
+/// list of lines.
+fn multiline_string(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Tracebacks are colored with ANSI escape sequences, which are dropped when shown as
+/// plain text.
+fn strip_ansi_escapes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": "# Title"
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "hello\n"
+     ]
+    },
+    {
+     "data": {
+      "image/png": "iVBORw0KGgo="
+     },
+     "metadata": {},
+     "output_type": "display_data"
+    },
+    {
+     "ename": "ZeroDivisionError",
+     "evalue": "division by zero",
+     "output_type": "error",
+     "traceback": [
+      "\u001b[0;31mZeroDivisionError\u001b[0m: division by zero"
+     ]
+    }
+   ],
+   "source": [
+    "print('hello')\n",
+    "1 / 0"
+   ]
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    #[test]
+    fn test_parse_notebook() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook.language_name(), Some("python"));
+        assert_eq!(notebook.cells.len(), 2);
+
+        let markdown = &notebook.cells[0];
+        assert_eq!(markdown.kind(), CellKind::Markdown);
+        assert_eq!(markdown.source(), "# Title");
+        assert_eq!(markdown.outputs(), vec![]);
+
+        let code = &notebook.cells[1];
+        assert_eq!(code.kind(), CellKind::Code);
+        assert_eq!(code.source(), "print('hello')\n1 / 0");
+        assert_eq!(code.execution_count(), Some(2));
+        assert_eq!(
+            code.outputs(),
+            vec![
+                CellOutput::Text("hello\n".to_string()),
+                CellOutput::Unsupported("image/png".to_string()),
+                CellOutput::Error("ZeroDivisionError: division by zero".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_notebook() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook.to_json().unwrap(), NOTEBOOK);
+
+        notebook.cells[1].set_source("x = 1\nx + 1");
+        let notebook = Notebook::parse(&notebook.to_json().unwrap()).unwrap();
+        assert_eq!(notebook.cells[1].source(), "x = 1\nx + 1");
+        assert_eq!(notebook.cells[1].outputs().len(), 3);
+    }
+}
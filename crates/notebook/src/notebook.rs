@@ -0,0 +1,14 @@
+use gpui::{actions, AppContext};
+use workspace::Workspace;
+
+pub mod ipynb;
+pub mod notebook_view;
+
+actions!(notebook, [OpenNotebook]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        notebook_view::NotebookView::register(workspace, cx);
+    })
+    .detach();
+}
@@ -0,0 +1,393 @@
+use crate::{
+    ipynb::{Cell, CellKind, CellOutput, Notebook},
+    OpenNotebook,
+};
+use anyhow::Result;
+use collections::HashSet;
+use editor::{
+    display_map::{BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle},
+    Editor, EditorEvent, ExcerptRange, MultiBuffer,
+};
+use gpui::{
+    AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Subscription,
+    Task, View, ViewContext, WindowContext,
+};
+use language::{Buffer, BufferId, Capability};
+use project::Project;
+use std::{any::TypeId, mem, sync::Arc};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::NotifyResultExt,
+    ItemNavHistory, Workspace,
+};
+
+/// Output blocks are cut off after this many lines, so that a long output doesn't push
+/// the following cells out of view.
+const MAX_OUTPUT_LINES: usize = 24;
+
+/// Shows a Jupyter notebook as a multi-buffer with an excerpt for each cell, and the
+/// outputs of code cells below them.
+///
+/// Cells are edited in buffers of their own. Saving writes their sources back into the
+/// notebook and saves the `.ipynb` buffer the notebook was opened from.
+pub struct NotebookView {
+    project: Model<Project>,
+    source_buffer: Model<Buffer>,
+    notebook: Notebook,
+    cell_buffers: Vec<Model<Buffer>>,
+    excerpts: Model<MultiBuffer>,
+    editor: View<Editor>,
+    output_blocks: HashSet<BlockId>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl NotebookView {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(Self::open);
+    }
+
+    fn open(workspace: &mut Workspace, _: &OpenNotebook, cx: &mut ViewContext<Workspace>) {
+        let Some(source_buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        let Some(notebook) =
+            Notebook::parse(&source_buffer.read(cx).text()).notify_err(workspace, cx)
+        else {
+            return;
+        };
+
+        let project = workspace.project().clone();
+        let view = cx.new_view(|cx| Self::new(project, source_buffer, notebook, cx));
+        workspace.add_item(Box::new(view), cx);
+    }
+
+    pub fn new(
+        project: Model<Project>,
+        source_buffer: Model<Buffer>,
+        notebook: Notebook,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let replica_id = project.read(cx).replica_id();
+        let excerpts = cx.new_model(|_| MultiBuffer::new(replica_id, Capability::ReadWrite));
+        let editor =
+            cx.new_view(|cx| Editor::for_multibuffer(excerpts.clone(), Some(project.clone()), cx));
+        let subscriptions = vec![
+            cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(event.clone());
+            }),
+            cx.subscribe(&source_buffer, |_, _, event: &language::Event, cx| {
+                if let language::Event::FileHandleChanged = event {
+                    cx.emit(EditorEvent::TitleChanged);
+                }
+            }),
+        ];
+
+        let mut this = Self {
+            project,
+            source_buffer,
+            notebook,
+            cell_buffers: Vec::new(),
+            excerpts,
+            editor,
+            output_blocks: HashSet::default(),
+            _subscriptions: subscriptions,
+        };
+        this.rebuild_cells(cx);
+        this
+    }
+
+    /// Replaces the cells shown in the editor with the ones in `self.notebook`.
+    fn rebuild_cells(&mut self, cx: &mut ViewContext<Self>) {
+        let languages = self.project.read(cx).languages().clone();
+        let cell_buffers = self
+            .notebook
+            .cells
+            .iter()
+            .map(|cell| {
+                cx.new_model(|cx| {
+                    let mut buffer = Buffer::new(
+                        0,
+                        BufferId::new(cx.entity_id().as_u64()).unwrap(),
+                        cell.source(),
+                    );
+                    buffer.set_language_registry(languages.clone());
+                    buffer
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let excerpt_ids = self.excerpts.update(cx, |excerpts, cx| {
+            excerpts.clear(cx);
+            cell_buffers
+                .iter()
+                .flat_map(|buffer| {
+                    let len = buffer.read(cx).len();
+                    let range = ExcerptRange {
+                        context: 0..len,
+                        primary: None,
+                    };
+                    excerpts.push_excerpts(buffer.clone(), [range], cx)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let blocks = self
+            .notebook
+            .cells
+            .iter()
+            .zip(excerpt_ids)
+            .filter_map(|(cell, excerpt_id)| {
+                let lines = output_lines(cell);
+                if lines.is_empty() {
+                    return None;
+                }
+                Some(BlockProperties {
+                    position: snapshot.anchor_in_excerpt(excerpt_id, language::Anchor::MAX),
+                    height: lines.len() as u8,
+                    style: BlockStyle::Flex,
+                    render: Arc::new(move |cx: &mut BlockContext| render_output_lines(&lines, cx)),
+                    disposition: BlockDisposition::Below,
+                })
+            })
+            .collect::<Vec<_>>();
+        let old_blocks = mem::take(&mut self.output_blocks);
+        self.output_blocks = self.editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_blocks, None, cx);
+            editor.insert_blocks(blocks, None, cx).into_iter().collect()
+        });
+
+        let code_language = self
+            .notebook
+            .language_name()
+            .map(|name| languages.language_for_name(name));
+        let markdown_language = languages.language_for_name("Markdown");
+        let cells = self
+            .notebook
+            .cells
+            .iter()
+            .map(Cell::kind)
+            .zip(cell_buffers.clone())
+            .collect::<Vec<_>>();
+        cx.spawn(|_, mut cx| async move {
+            let code_language = match code_language {
+                Some(language) => language.await.log_err(),
+                None => None,
+            };
+            let markdown_language = markdown_language.await.log_err();
+            for (kind, buffer) in cells {
+                let language = match kind {
+                    CellKind::Code => code_language.clone(),
+                    CellKind::Markdown => markdown_language.clone(),
+                    CellKind::Raw => None,
+                };
+                if language.is_some() {
+                    buffer.update(&mut cx, |buffer, cx| buffer.set_language(language, cx))?;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        self.cell_buffers = cell_buffers;
+        cx.notify();
+    }
+}
+
+/// The lines shown below a code cell: its execution count followed by its outputs.
+fn output_lines(cell: &Cell) -> Vec<(SharedString, bool)> {
+    let outputs = cell.outputs();
+    if outputs.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = Vec::new();
+    if let Some(execution_count) = cell.execution_count() {
+        lines.push((format!("Out[{execution_count}]:").into(), false));
+    }
+    for output in outputs {
+        let is_error = matches!(output, CellOutput::Error(_));
+        lines.extend(
+            output
+                .text()
+                .lines()
+                .map(|line| (SharedString::from(line.to_string()), is_error)),
+        );
+    }
+    if lines.len() > MAX_OUTPUT_LINES {
+        let hidden_line_count = lines.len() - MAX_OUTPUT_LINES + 1;
+        lines.truncate(MAX_OUTPUT_LINES - 1);
+        lines.push((format!("… {hidden_line_count} more lines").into(), false));
+    }
+    lines
+}
+
+fn render_output_lines(lines: &[(SharedString, bool)], cx: &mut BlockContext) -> AnyElement {
+    let line_height = cx.line_height;
+    let text_color = cx.theme().colors().text_muted;
+    let error_color = cx.theme().status().error;
+    v_flex()
+        .id(cx.block_id)
+        .pl(cx.gutter_dimensions.width)
+        .text_buffer(cx)
+        .children(lines.iter().map(|(line, is_error)| {
+            div()
+                .h(line_height)
+                .text_color(if *is_error { error_color } else { text_color })
+                .child(line.clone())
+        }))
+        .into_any_element()
+}
+
+impl FocusableView for NotebookView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for NotebookView {}
+
+impl Item for NotebookView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn std::any::Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn tab_content(
+        &self,
+        _detail: Option<usize>,
+        selected: bool,
+        cx: &WindowContext,
+    ) -> AnyElement {
+        let title = self
+            .source_buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().to_string())
+            .unwrap_or_else(|| "untitled".to_string());
+        let color = if selected {
+            Color::Default
+        } else {
+            Color::Muted
+        };
+        h_flex()
+            .gap_2()
+            .child(Icon::new(IconName::FileDoc).color(color))
+            .child(Label::new(title).color(color))
+            .into_any()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("notebook")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        f(self.source_buffer.entity_id(), self.source_buffer.read(cx))
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.excerpts.read(cx).is_dirty(cx)
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.source_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        for (cell, buffer) in self.notebook.cells.iter_mut().zip(&self.cell_buffers) {
+            cell.set_source(&buffer.read(cx).text());
+        }
+        let json = match self.notebook.to_json() {
+            Ok(json) => json,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        self.source_buffer
+            .update(cx, |buffer, cx| buffer.set_text(json, cx));
+
+        let save = project.update(cx, |project, cx| {
+            project.save_buffer(self.source_buffer.clone(), cx)
+        });
+        let cell_buffers = self.cell_buffers.clone();
+        cx.spawn(|_, mut cx| async move {
+            save.await?;
+            for buffer in cell_buffers {
+                buffer.update(&mut cx, |buffer, cx| {
+                    let fingerprint = buffer.as_rope().fingerprint();
+                    let mtime = buffer.saved_mtime();
+                    buffer.did_save(buffer.version(), fingerprint, mtime, cx);
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let buffers = HashSet::from_iter([self.source_buffer.clone()]);
+        let reload = project.update(cx, |project, cx| project.reload_buffers(buffers, true, cx));
+        cx.spawn(|this, mut cx| async move {
+            reload.await?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook = Notebook::parse(&this.source_buffer.read(cx).text())?;
+                this.rebuild_cells(cx);
+                anyhow::Ok(())
+            })?
+        })
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+}
+
+impl Render for NotebookView {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .key_context("NotebookView")
+            .size_full()
+            .child(self.editor.clone())
+    }
+}
@@ -315,6 +315,33 @@ impl NotificationStore {
                 this.loaded_all_notifications = true;
             }
 
+            // Mentions loaded from the notification history only seed the
+            // unread counts, while new ones are also reported to the user.
+            for entry in notifications.iter().filter(|entry| !entry.is_read) {
+                if let Notification::ChannelMessageMention {
+                    channel_id,
+                    message_id,
+                    ..
+                } = entry.notification
+                {
+                    let channel_id = ChannelId(channel_id);
+                    if options.is_new {
+                        let message = messages
+                            .iter()
+                            .find(|message| message.id == ChannelMessageId::Saved(message_id));
+                        if let Some(message) = message {
+                            this.channel_store.update(cx, |store, cx| {
+                                store.record_mention(channel_id, message, cx)
+                            });
+                        }
+                    } else {
+                        this.channel_store.update(cx, |store, cx| {
+                            store.record_unread_mention(channel_id, message_id, cx)
+                        });
+                    }
+                }
+            }
+
             this.channel_messages
                 .extend(messages.into_iter().filter_map(|message| {
                     if let ChannelMessageId::Saved(id) = message.id {
@@ -33,8 +33,7 @@ pub fn toggle(editor: View<Editor>, _: &Toggle, cx: &mut WindowContext) {
         .read(cx)
         .buffer()
         .read(cx)
-        .snapshot(cx)
-        .outline(Some(&cx.theme().syntax()));
+        .outline(Some(cx.theme().syntax()), cx);
 
     if let Some((workspace, outline)) = editor.read(cx).workspace().zip(outline) {
         workspace.update(cx, |workspace, cx| {
@@ -0,0 +1,171 @@
+use crate::{search::SearchQuery, Project};
+use anyhow::Result;
+use collections::HashSet;
+use gpui::{ModelContext, Task};
+use language::ToOffset;
+use std::{fmt, ops::Range, path::PathBuf};
+
+/// A single match that was, or would be, replaced by a batch edit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchEdit {
+    /// The range of the match, as an offset into the file before editing.
+    pub range: Range<usize>,
+    /// The zero-based row on which the match starts.
+    pub row: u32,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+/// The edits that a batch edit made, or would make, to one file.
+#[derive(Clone, Debug)]
+pub struct BatchFileEdit {
+    /// The path of the file, including the name of its worktree's root.
+    pub path: Option<PathBuf>,
+    pub edits: Vec<BatchEdit>,
+    /// Whether the file had unsaved changes before it was edited. Such files
+    /// aren't saved, so that those changes are still only in the editor.
+    pub had_unsaved_changes: bool,
+}
+
+/// The result of [`Project::batch_replace`]. In a dry run, it describes the
+/// edits without anything having been changed.
+#[derive(Clone, Debug, Default)]
+pub struct BatchEditSummary {
+    pub dry_run: bool,
+    /// The files with at least one edit, sorted by path.
+    pub files: Vec<BatchFileEdit>,
+}
+
+impl BatchEditSummary {
+    pub fn edit_count(&self) -> usize {
+        self.files.iter().map(|file| file.edits.len()).sum()
+    }
+}
+
+/// Lists every edit as a removed and an added line under its location, then
+/// summarizes the number of edits. This is the output of a dry run.
+impl fmt::Display for BatchEditSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            let path = file
+                .path
+                .as_ref()
+                .map_or("untitled".into(), |path| path.to_string_lossy());
+            for edit in &file.edits {
+                writeln!(f, "{}:{}", path, edit.row + 1)?;
+                for line in edit.old_text.lines() {
+                    writeln!(f, "- {line}")?;
+                }
+                for line in edit.new_text.lines() {
+                    writeln!(f, "+ {line}")?;
+                }
+            }
+        }
+
+        let edit_count = self.edit_count();
+        let file_count = self.files.len();
+        write!(
+            f,
+            "{} {} {} in {} {}",
+            if self.dry_run { "would make" } else { "made" },
+            edit_count,
+            if edit_count == 1 { "edit" } else { "edits" },
+            file_count,
+            if file_count == 1 { "file" } else { "files" },
+        )?;
+        let unsaved_count = self
+            .files
+            .iter()
+            .filter(|file| file.had_unsaved_changes)
+            .count();
+        if !self.dry_run && unsaved_count > 0 {
+            write!(
+                f,
+                ", {unsaved_count} of which had unsaved changes and weren't saved"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Project {
+    /// Finds every match of the query in the project and replaces it with the
+    /// text returned by `replace`, skipping matches for which it returns `None`.
+    ///
+    /// Edits are made through buffers, so that open editors and collaborators
+    /// observe them. Buffers that had no unsaved changes are then saved, while
+    /// the others are left for the user to review. When `dry_run` is set,
+    /// nothing is changed and the returned summary describes what would be.
+    pub fn batch_replace(
+        &mut self,
+        query: SearchQuery,
+        dry_run: bool,
+        replace: impl 'static + Fn(&str) -> Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<BatchEditSummary>> {
+        let matches = self.search(query, cx);
+        cx.spawn(move |this, mut cx| async move {
+            let mut summary = BatchEditSummary {
+                dry_run,
+                files: Vec::new(),
+            };
+            let mut buffers_to_save = HashSet::default();
+            while let Ok((buffer, ranges)) = matches.recv().await {
+                let (edits, had_unsaved_changes) = buffer.update(&mut cx, |buffer, cx| {
+                    let had_unsaved_changes = buffer.is_dirty();
+                    let snapshot = buffer.snapshot();
+                    let edits = ranges
+                        .iter()
+                        .filter_map(|range| {
+                            let range =
+                                range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot);
+                            let old_text =
+                                snapshot.text_for_range(range.clone()).collect::<String>();
+                            let new_text = replace(&old_text).filter(|text| *text != old_text)?;
+                            Some(BatchEdit {
+                                row: snapshot.offset_to_point(range.start).row,
+                                range,
+                                old_text,
+                                new_text,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    if !dry_run && !edits.is_empty() {
+                        buffer.edit(
+                            edits
+                                .iter()
+                                .map(|edit| (edit.range.clone(), edit.new_text.clone())),
+                            None,
+                            cx,
+                        );
+                    }
+                    (edits, had_unsaved_changes)
+                })?;
+                // Buffers without edits are dropped here, rather than being kept
+                // open until the whole project has been searched.
+                if edits.is_empty() {
+                    continue;
+                }
+
+                let path = buffer.update(&mut cx, |buffer, cx| {
+                    buffer.file().map(|file| file.full_path(cx))
+                })?;
+                if !dry_run && !had_unsaved_changes {
+                    buffers_to_save.insert(buffer);
+                }
+                summary.files.push(BatchFileEdit {
+                    path,
+                    edits,
+                    had_unsaved_changes,
+                });
+            }
+            summary.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+            if !buffers_to_save.is_empty() {
+                this.update(&mut cx, |this, cx| this.save_buffers(buffers_to_save, cx))?
+                    .await?;
+            }
+            Ok(summary)
+        })
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    io::Read as _,
+    mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+use fs::{Fs, Metadata, RenameOptions};
+use futures::StreamExt as _;
+use gpui::{AppContext, EventEmitter, ModelContext, Task};
+use util::ResultExt as _;
+
+use crate::{Item, ProjectEntryId, ProjectPath};
+
+/// The number of leading bytes inspected when deciding whether a file is binary.
+const BINARY_DETECTION_LEN: usize = 8000;
+
+/// Bytes are read from disk in pages of this size, and only once they're displayed.
+const PAGE_SIZE: usize = 64 * 1024;
+
+/// The amount of the file that is held in memory at a time while searching or saving.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Returns whether the given file contents should be treated as binary, which
+/// is the case when the start of the file isn't text in any supported encoding.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(BINARY_DETECTION_LEN)];
+    language::Encoding::detect(head).is_none()
+}
+
+/// Reads the start of the file at `abs_path` and reports whether it looks binary.
+pub(crate) async fn file_is_binary(fs: &dyn Fs, abs_path: &Path) -> Result<bool> {
+    let reader = fs.open_sync(abs_path).await?;
+    let mut head = Vec::with_capacity(BINARY_DETECTION_LEN);
+    reader
+        .take(BINARY_DETECTION_LEN as u64)
+        .read_to_end(&mut head)?;
+    Ok(is_binary(&head))
+}
+
+pub enum BinaryFileEvent {
+    Edited,
+    Saved,
+    Reloaded,
+    /// The file changed on disk while it had unsaved edits.
+    Conflict,
+}
+
+/// A span of the edited file, either taken unchanged from the file on disk or
+/// holding bytes that were typed since it was last saved.
+#[derive(Clone, Debug)]
+enum Piece {
+    Disk { offset: usize, len: usize },
+    Added(Vec<u8>),
+}
+
+impl Piece {
+    fn len(&self) -> usize {
+        match self {
+            Piece::Disk { len, .. } => *len,
+            Piece::Added(bytes) => bytes.len(),
+        }
+    }
+
+    fn slice(&self, range: Range<usize>) -> Piece {
+        match self {
+            Piece::Disk { offset, .. } => Piece::Disk {
+                offset: offset + range.start,
+                len: range.len(),
+            },
+            Piece::Added(bytes) => Piece::Added(bytes[range].to_vec()),
+        }
+    }
+}
+
+/// A file in the project whose contents aren't text, and that is therefore
+/// edited as raw bytes rather than through a [`language::Buffer`].
+///
+/// The file is never read into memory as a whole. Edits are kept as a list of
+/// pieces that refer either to ranges of the file on disk or to new bytes, and
+/// the on-disk ranges are read a page at a time as they're displayed.
+pub struct BinaryFile {
+    fs: Arc<dyn Fs>,
+    path: ProjectPath,
+    abs_path: PathBuf,
+    entry_id: Option<ProjectEntryId>,
+    pieces: Vec<Piece>,
+    len: usize,
+    mtime: SystemTime,
+    pages: HashMap<usize, Arc<[u8]>>,
+    loading_pages: HashSet<usize>,
+    /// Incremented whenever the file on disk is replaced, so that pages read
+    /// from a previous version are discarded.
+    disk_version: usize,
+    is_dirty: bool,
+    has_conflict: bool,
+    is_saving: bool,
+    _watch: Task<()>,
+}
+
+impl EventEmitter<BinaryFileEvent> for BinaryFile {}
+
+impl BinaryFile {
+    pub(crate) fn new(
+        fs: Arc<dyn Fs>,
+        path: ProjectPath,
+        abs_path: PathBuf,
+        entry_id: Option<ProjectEntryId>,
+        metadata: Metadata,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let watch = cx.spawn({
+            let fs = fs.clone();
+            let abs_path = abs_path.clone();
+            |this, mut cx| async move {
+                let events = fs.watch(&abs_path, Duration::from_millis(100)).await;
+                futures::pin_mut!(events);
+                while events.next().await.is_some() {
+                    let Some(metadata) = fs.metadata(&abs_path).await.log_err().flatten() else {
+                        continue;
+                    };
+                    if this
+                        .update(&mut cx, |this, cx| this.file_changed_on_disk(metadata, cx))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let mut this = Self {
+            fs,
+            path,
+            abs_path,
+            entry_id,
+            pieces: Vec::new(),
+            len: 0,
+            mtime: metadata.mtime,
+            pages: HashMap::default(),
+            loading_pages: HashSet::default(),
+            disk_version: 0,
+            is_dirty: false,
+            has_conflict: false,
+            is_saving: false,
+            _watch: watch,
+        };
+        this.reset_to_disk(&metadata);
+        this
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn abs_path(&self) -> &PathBuf {
+        &self.abs_path
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    pub fn has_conflict(&self) -> bool {
+        self.has_conflict
+    }
+
+    /// Returns the bytes in `range`, or `None` if some of them haven't been read
+    /// from disk yet. Call [`Self::load_range`] to read them.
+    pub fn read_range(&self, range: Range<usize>) -> Option<Vec<u8>> {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let mut bytes = Vec::with_capacity(range.len());
+        for (piece, piece_range) in self.pieces_in_range(range) {
+            match piece {
+                Piece::Added(added) => bytes.extend_from_slice(&added[piece_range]),
+                Piece::Disk { offset, .. } => {
+                    let disk_range = offset + piece_range.start..offset + piece_range.end;
+                    for page_ix in pages_in_range(&disk_range) {
+                        let page = self.pages.get(&page_ix)?;
+                        let page_start = page_ix * PAGE_SIZE;
+                        let start = disk_range.start.max(page_start) - page_start;
+                        let end = (disk_range.end.min(page_start + PAGE_SIZE) - page_start)
+                            .min(page.len());
+                        bytes.extend_from_slice(page.get(start..end).unwrap_or_default());
+                    }
+                }
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Starts reading the pages of the file on disk that back `range`, notifying
+    /// once they're available.
+    pub fn load_range(&mut self, range: Range<usize>, cx: &mut ModelContext<Self>) {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let mut page_ixs = Vec::new();
+        for (piece, piece_range) in self.pieces_in_range(range) {
+            if let Piece::Disk { offset, .. } = piece {
+                let disk_range = offset + piece_range.start..offset + piece_range.end;
+                page_ixs.extend(pages_in_range(&disk_range).filter(|page_ix| {
+                    !self.pages.contains_key(page_ix) && !self.loading_pages.contains(page_ix)
+                }));
+            }
+        }
+
+        for page_ix in page_ixs {
+            if !self.loading_pages.insert(page_ix) {
+                continue;
+            }
+            let fs = self.fs.clone();
+            let abs_path = self.abs_path.clone();
+            let disk_version = self.disk_version;
+            cx.spawn(|this, mut cx| async move {
+                let page = fs
+                    .load_range(&abs_path, (page_ix * PAGE_SIZE) as u64, PAGE_SIZE as u64)
+                    .await;
+                this.update(&mut cx, |this, cx| {
+                    if this.disk_version != disk_version {
+                        return;
+                    }
+                    this.loading_pages.remove(&page_ix);
+                    if let Some(page) = page.log_err() {
+                        this.pages.insert(page_ix, page.into());
+                        cx.notify();
+                    }
+                })
+                .ok();
+            })
+            .detach();
+        }
+    }
+
+    /// Overwrites the byte at `offset`, or appends it if `offset` is the end of the file.
+    pub fn set_byte(&mut self, offset: usize, value: u8, cx: &mut ModelContext<Self>) {
+        if self.is_saving || offset > self.len {
+            return;
+        }
+        if offset < self.len {
+            if self.read_range(offset..offset + 1) == Some(vec![value]) {
+                return;
+            }
+            self.splice(offset..offset + 1, &[value]);
+        } else {
+            self.splice(offset..offset, &[value]);
+        }
+        self.edited(cx);
+    }
+
+    pub fn remove_byte(&mut self, offset: usize, cx: &mut ModelContext<Self>) {
+        if !self.is_saving && offset < self.len {
+            self.splice(offset..offset + 1, &[]);
+            self.edited(cx);
+        }
+    }
+
+    /// Returns the offsets of all occurrences of `needle` in the file, reading
+    /// it in chunks on the background executor.
+    pub fn search(&self, needle: Vec<u8>, cx: &AppContext) -> Task<Result<Vec<usize>>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let pieces = self.pieces.clone();
+        let len = self.len;
+        cx.background_executor().spawn(async move {
+            let mut matches = Vec::new();
+            if needle.is_empty() || needle.len() > len {
+                return Ok(matches);
+            }
+            let mut offset = 0;
+            while offset < len {
+                // Overlap consecutive chunks so matches spanning them aren't missed.
+                let end = (offset + CHUNK_SIZE + needle.len() - 1).min(len);
+                let chunk = read_pieces(fs.as_ref(), &abs_path, &pieces, offset..end).await?;
+                matches.extend(
+                    chunk
+                        .windows(needle.len())
+                        .enumerate()
+                        .filter_map(|(ix, window)| (window == needle).then_some(offset + ix)),
+                );
+                offset += CHUNK_SIZE;
+            }
+            Ok(matches)
+        })
+    }
+
+    /// Writes the file next to its destination a chunk at a time, then moves it
+    /// into place, so that the original stays readable until the save completes.
+    pub fn save(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.is_saving {
+            return Task::ready(Err(anyhow!("{:?} is already being saved", self.abs_path)));
+        }
+        self.is_saving = true;
+
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let pieces = self.pieces.clone();
+        let len = self.len;
+        cx.spawn(|this, mut cx| async move {
+            let result = async {
+                let abs_path = fs.canonicalize(&abs_path).await.unwrap_or(abs_path);
+                let file_name = abs_path
+                    .file_name()
+                    .ok_or_else(|| anyhow!("invalid path {abs_path:?}"))?;
+                let temp_path =
+                    abs_path.with_file_name(format!(".{}.save", file_name.to_string_lossy()));
+
+                let mut offset = 0;
+                loop {
+                    let end = (offset + CHUNK_SIZE).min(len);
+                    let chunk = read_pieces(fs.as_ref(), &abs_path, &pieces, offset..end).await?;
+                    if offset == 0 {
+                        fs.atomic_write_bytes(temp_path.clone(), chunk).await?;
+                    } else {
+                        fs.append_bytes(&temp_path, chunk).await?;
+                    }
+                    offset = end;
+                    if offset == len {
+                        break;
+                    }
+                }
+                fs.rename(
+                    &temp_path,
+                    &abs_path,
+                    RenameOptions {
+                        overwrite: true,
+                        ignore_if_exists: false,
+                    },
+                )
+                .await?;
+                fs.metadata(&abs_path)
+                    .await?
+                    .ok_or_else(|| anyhow!("{abs_path:?} was removed while saving"))
+            }
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.is_saving = false;
+                let metadata = result?;
+                this.reset_to_disk(&metadata);
+                cx.emit(BinaryFileEvent::Saved);
+                cx.notify();
+                Ok(())
+            })?
+        })
+    }
+
+    pub fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(|this, mut cx| async move {
+            let metadata = fs
+                .metadata(&abs_path)
+                .await?
+                .ok_or_else(|| anyhow!("{abs_path:?} no longer exists"))?;
+            this.update(&mut cx, |this, cx| {
+                this.reset_to_disk(&metadata);
+                cx.emit(BinaryFileEvent::Reloaded);
+                cx.notify();
+            })
+        })
+    }
+
+    fn file_changed_on_disk(&mut self, metadata: Metadata, cx: &mut ModelContext<Self>) {
+        if self.is_saving || metadata.mtime == self.mtime {
+            return;
+        }
+        if self.is_dirty {
+            if !self.has_conflict {
+                self.has_conflict = true;
+                cx.emit(BinaryFileEvent::Conflict);
+                cx.notify();
+            }
+        } else {
+            self.reset_to_disk(&metadata);
+            cx.emit(BinaryFileEvent::Reloaded);
+            cx.notify();
+        }
+    }
+
+    fn reset_to_disk(&mut self, metadata: &Metadata) {
+        self.len = metadata.len as usize;
+        self.pieces = vec![Piece::Disk {
+            offset: 0,
+            len: self.len,
+        }];
+        self.mtime = metadata.mtime;
+        self.pages.clear();
+        self.loading_pages.clear();
+        self.disk_version += 1;
+        self.is_dirty = false;
+        self.has_conflict = false;
+    }
+
+    fn edited(&mut self, cx: &mut ModelContext<Self>) {
+        self.is_dirty = true;
+        cx.emit(BinaryFileEvent::Edited);
+        cx.notify();
+    }
+
+    /// Replaces the bytes in `range` with `new_bytes`.
+    fn splice(&mut self, range: Range<usize>, new_bytes: &[u8]) {
+        let mut pieces = Vec::with_capacity(self.pieces.len() + 2);
+        let mut inserted = false;
+        let mut piece_start = 0;
+        for piece in mem::take(&mut self.pieces) {
+            let piece_end = piece_start + piece.len();
+            if piece_start < range.start {
+                push_piece(
+                    &mut pieces,
+                    piece.slice(0..range.start.min(piece_end) - piece_start),
+                );
+            }
+            if !inserted && range.start < piece_end {
+                push_piece(&mut pieces, Piece::Added(new_bytes.to_vec()));
+                inserted = true;
+            }
+            if piece_end > range.end {
+                push_piece(
+                    &mut pieces,
+                    piece.slice(range.end.max(piece_start) - piece_start..piece.len()),
+                );
+            }
+            piece_start = piece_end;
+        }
+        if !inserted {
+            push_piece(&mut pieces, Piece::Added(new_bytes.to_vec()));
+        }
+        self.pieces = pieces;
+        self.len = self.len - range.len() + new_bytes.len();
+    }
+
+    /// Returns the pieces overlapping `range`, along with the part of each piece that overlaps.
+    fn pieces_in_range(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (&Piece, Range<usize>)> + '_ {
+        pieces_in_range(&self.pieces, range)
+    }
+}
+
+fn pieces_in_range(
+    pieces: &[Piece],
+    range: Range<usize>,
+) -> impl Iterator<Item = (&Piece, Range<usize>)> + '_ {
+    let mut piece_start = 0;
+    pieces.iter().filter_map(move |piece| {
+        let start = piece_start;
+        let end = start + piece.len();
+        piece_start = end;
+        (start < range.end && end > range.start).then(|| {
+            (
+                piece,
+                range.start.max(start) - start..range.end.min(end) - start,
+            )
+        })
+    })
+}
+
+fn pages_in_range(disk_range: &Range<usize>) -> Range<usize> {
+    disk_range.start / PAGE_SIZE..(disk_range.end + PAGE_SIZE - 1) / PAGE_SIZE
+}
+
+fn push_piece(pieces: &mut Vec<Piece>, piece: Piece) {
+    if piece.len() == 0 {
+        return;
+    }
+    match (pieces.last_mut(), &piece) {
+        (Some(Piece::Added(last)), Piece::Added(bytes)) => {
+            last.extend_from_slice(bytes);
+            return;
+        }
+        (
+            Some(Piece::Disk {
+                offset: last_offset,
+                len: last_len,
+            }),
+            Piece::Disk { offset, len },
+        ) if *last_offset + *last_len == *offset => {
+            *last_len += len;
+            return;
+        }
+        _ => {}
+    }
+    pieces.push(piece);
+}
+
+async fn read_pieces(
+    fs: &dyn Fs,
+    abs_path: &Path,
+    pieces: &[Piece],
+    range: Range<usize>,
+) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(range.len());
+    for (piece, piece_range) in pieces_in_range(pieces, range) {
+        match piece {
+            Piece::Added(added) => bytes.extend_from_slice(&added[piece_range]),
+            Piece::Disk { offset, .. } => {
+                let data = fs
+                    .load_range(
+                        abs_path,
+                        (offset + piece_range.start) as u64,
+                        piece_range.len() as u64,
+                    )
+                    .await?;
+                bytes.extend(data);
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+impl Item for BinaryFile {
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.path.clone())
+    }
+}
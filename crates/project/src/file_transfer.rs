@@ -0,0 +1,275 @@
+use crate::{Project, ProjectPath};
+use anyhow::{anyhow, Context as _, Result};
+use client::{proto, Client, TypedEnvelope};
+use fs::{Fs, RemoveOptions, RenameOptions};
+use gpui::{AsyncAppContext, Model, ModelContext, Task};
+use postage::watch;
+use project_core::{project_settings::ProjectSettings, worktree::WorktreeId};
+use rpc::{ErrorCode, ErrorExt as _};
+use settings::Settings;
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt as _;
+
+/// The most bytes of a file that a host sends in response to a single request.
+pub const DOWNLOAD_CHUNK_SIZE: u64 = 256 * 1024;
+
+/// How many times a file is downloaded from the start after it changes on the
+/// host partway through, before giving up.
+const MAX_DOWNLOAD_ATTEMPTS: usize = 3;
+
+/// How much of a download has been transferred so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl DownloadProgress {
+    /// The fraction of the download that has been transferred, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            1.
+        } else {
+            self.downloaded_bytes as f32 / self.total_bytes as f32
+        }
+    }
+}
+
+/// A download of a file or directory from the host of a shared project.
+/// Dropping it cancels the download.
+pub struct FileDownload {
+    pub progress: watch::Receiver<DownloadProgress>,
+    pub task: Task<Result<()>>,
+}
+
+impl Project {
+    /// Downloads a file, or every file in a directory, from the host of this
+    /// shared project into `destination`, which is the local path that the
+    /// entry is written to. The host has to allow downloads with the
+    /// `allow_guest_downloads` setting.
+    pub fn download_entry(
+        &self,
+        project_path: ProjectPath,
+        destination: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<FileDownload> {
+        let project_id = self
+            .remote_id()
+            .filter(|_| self.is_remote())
+            .ok_or_else(|| anyhow!("only guests of a shared project can download files"))?;
+        let worktree = self
+            .worktree_for_id(project_path.worktree_id, cx)
+            .ok_or_else(|| anyhow!("no such worktree"))?;
+        let snapshot = worktree.read(cx).snapshot();
+        let entry = snapshot
+            .entry_for_path(&project_path.path)
+            .ok_or_else(|| anyhow!("no such entry {:?}", project_path.path))?;
+
+        let files = if entry.is_dir() {
+            snapshot
+                .descendent_entries(false, true, &entry.path)
+                .filter(|file| !file.is_private)
+                .map(|file| {
+                    let relative_path = file.path.strip_prefix(&entry.path).unwrap_or(&file.path);
+                    // Paths come from the host, and must not write outside of the destination.
+                    if !relative_path
+                        .components()
+                        .all(|component| matches!(component, Component::Normal(_)))
+                    {
+                        return Err(anyhow!("invalid path in download {:?}", file.path));
+                    }
+                    Ok((
+                        file.path.clone(),
+                        destination.join(relative_path),
+                        file.size,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            vec![(entry.path.clone(), destination, entry.size)]
+        };
+
+        let (mut progress_tx, progress_rx) = watch::channel();
+        *progress_tx.borrow_mut() = DownloadProgress {
+            downloaded_bytes: 0,
+            total_bytes: files.iter().map(|(_, _, size)| size).sum(),
+        };
+
+        let client = self.client.clone();
+        let fs = self.fs.clone();
+        let worktree_id = project_path.worktree_id;
+        let task = cx.background_executor().spawn(async move {
+            for (path, destination, _) in files {
+                if let Some(parent) = destination.parent() {
+                    fs.create_dir(parent).await?;
+                }
+                // Chunks are written next to the destination as they arrive, and
+                // the file only replaces the destination once it is complete.
+                let download_path = partial_download_path(&destination);
+                let request = proto::DownloadFileChunk {
+                    project_id,
+                    worktree_id: worktree_id.to_proto(),
+                    path: path.to_string_lossy().into(),
+                    offset: 0,
+                };
+                let mut result = download_file(
+                    &client,
+                    fs.as_ref(),
+                    request,
+                    &download_path,
+                    &mut progress_tx,
+                )
+                .await;
+                if result.is_ok() {
+                    result = fs
+                        .rename(
+                            &download_path,
+                            &destination,
+                            RenameOptions {
+                                overwrite: true,
+                                ignore_if_exists: false,
+                            },
+                        )
+                        .await;
+                }
+                if result.is_err() {
+                    fs.remove_file(
+                        &download_path,
+                        RemoveOptions {
+                            ignore_if_not_exists: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .log_err();
+                }
+                result?;
+            }
+            Ok(())
+        });
+
+        Ok(FileDownload {
+            progress: progress_rx,
+            task,
+        })
+    }
+
+    pub(crate) async fn handle_download_file_chunk(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DownloadFileChunk>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::DownloadFileChunkResponse> {
+        let path = PathBuf::from(&envelope.payload.path);
+        let (fs, abs_path) = this.update(&mut cx, |this, cx| {
+            if !ProjectSettings::get_global(cx).allow_guest_downloads {
+                return Err(ErrorCode::Forbidden
+                    .message("the host doesn't allow downloads".into())
+                    .anyhow());
+            }
+            let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+            let worktree = this
+                .worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("no such worktree"))?;
+            let worktree = worktree
+                .read(cx)
+                .as_local()
+                .ok_or_else(|| anyhow!("can't download from a remote worktree"))?;
+            let entry = worktree
+                .entry_for_path(&path)
+                .filter(|entry| entry.is_file())
+                .ok_or_else(|| anyhow!("no such file {:?}", path))?;
+            if entry.is_private {
+                return Err(anyhow!(ErrorCode::UnsharedItem));
+            }
+            // Rejects paths that would escape the worktree.
+            let abs_path = worktree
+                .absolutize(&path)
+                .context("resolving the file's path")?;
+            anyhow::Ok((this.fs.clone(), abs_path))
+        })??;
+
+        // The metadata is read for every chunk, so that the guest can tell if
+        // the file changed since the previous one.
+        let metadata = fs
+            .metadata(&abs_path)
+            .await?
+            .ok_or_else(|| anyhow!("no such file {:?}", path))?;
+        let data = fs
+            .load_range(&abs_path, envelope.payload.offset, DOWNLOAD_CHUNK_SIZE)
+            .await?;
+        Ok(proto::DownloadFileChunkResponse {
+            data,
+            total_len: metadata.len,
+            mtime: Some(metadata.mtime.into()),
+        })
+    }
+}
+
+/// Where a file is written while it is being downloaded to `destination`.
+fn partial_download_path(destination: &Path) -> PathBuf {
+    let file_name = destination
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    destination.with_file_name(format!(".{file_name}.download"))
+}
+
+/// Downloads a file from the host into `download_path` one chunk at a time,
+/// starting over if the file changes on the host in the meantime.
+async fn download_file(
+    client: &Client,
+    fs: &dyn Fs,
+    request: proto::DownloadFileChunk,
+    download_path: &Path,
+    progress_tx: &mut watch::Sender<DownloadProgress>,
+) -> Result<()> {
+    for _ in 0..MAX_DOWNLOAD_ATTEMPTS {
+        let mut offset = 0;
+        let mut version = None;
+        let changed = loop {
+            let response = client
+                .request(proto::DownloadFileChunk {
+                    offset,
+                    ..request.clone()
+                })
+                .await?;
+            let chunk_version = (response.mtime.clone(), response.total_len);
+            if *version.get_or_insert_with(|| chunk_version.clone()) != chunk_version {
+                break true;
+            }
+
+            let chunk_len = response.data.len() as u64;
+            if offset == 0 {
+                fs.atomic_write_bytes(download_path.to_path_buf(), response.data)
+                    .await?;
+            } else {
+                fs.append_bytes(download_path, response.data).await?;
+            }
+            offset += chunk_len;
+
+            {
+                let mut progress = progress_tx.borrow_mut();
+                progress.downloaded_bytes += chunk_len;
+                // The file may have changed on the host since the guest's snapshot
+                // of the worktree was taken.
+                progress.total_bytes = progress.total_bytes.max(progress.downloaded_bytes);
+            }
+
+            if chunk_len == 0 || offset >= response.total_len {
+                break false;
+            }
+        };
+        if !changed {
+            return Ok(());
+        }
+        progress_tx.borrow_mut().downloaded_bytes -= offset;
+    }
+    Err(anyhow!(
+        "{:?} kept changing on the host during the download",
+        request.path
+    ))
+}
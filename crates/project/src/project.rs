@@ -1,4 +1,7 @@
+mod batch_edit;
+pub mod binary_file;
 pub mod debounced_delay;
+mod file_transfer;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
@@ -16,13 +19,16 @@ use collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque};
 use copilot::Copilot;
 use debounced_delay::DebouncedDelay;
 use futures::{
-    channel::mpsc::{self, UnboundedReceiver},
+    channel::{
+        mpsc::{self, UnboundedReceiver},
+        oneshot,
+    },
     future::{try_join_all, Shared},
     select,
     stream::FuturesUnordered,
-    AsyncWriteExt, Future, FutureExt, StreamExt, TryFutureExt,
+    Future, FutureExt, StreamExt, TryFutureExt,
 };
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, Context, Entity, EventEmitter,
     Model, ModelContext, PromptLevel, Task, WeakModel,
@@ -59,16 +65,18 @@ use rand::prelude::*;
 use rpc::{ErrorCode, ErrorExt as _};
 use search::SearchQuery;
 use serde::Serialize;
-use settings::{Settings, SettingsStore};
+use settings::{
+    editorconfig::{Editorconfig, EDITORCONFIG_FILE_NAME},
+    Settings, SettingsStore,
+};
 use sha2::{Digest, Sha256};
-use similar::{ChangeTag, TextDiff};
 use smol::channel::{Receiver, Sender};
 use smol::lock::Semaphore;
 use std::{
     cmp::{self, Ordering},
     convert::TryInto,
     env,
-    ffi::OsString,
+    ffi::{OsStr, OsString},
     hash::Hash,
     mem,
     num::NonZeroU32,
@@ -85,13 +93,23 @@ use std::{
 use terminals::Terminals;
 use text::{Anchor, BufferId};
 use util::{
-    debug_panic, defer, http::HttpClient, merge_json_value_into,
-    paths::LOCAL_SETTINGS_RELATIVE_PATH, post_inc, ResultExt, TryFutureExt as _,
+    cancellation::CancellationToken,
+    debug_panic, defer,
+    http::HttpClient,
+    merge_json_value_into,
+    paths::LOCAL_SETTINGS_RELATIVE_PATH,
+    post_inc,
+    process::{self, ProcessRequest, ProcessRunner},
+    ResultExt, TryFutureExt as _,
 };
 
+pub use batch_edit::{BatchEdit, BatchEditSummary, BatchFileEdit};
+pub use binary_file::{BinaryFile, BinaryFileEvent};
+pub use file_transfer::{DownloadProgress, FileDownload, DOWNLOAD_CHUNK_SIZE};
 pub use fs::*;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use project_core::power;
 pub use project_core::project_settings;
 pub use project_core::worktree::{self, *};
 pub use task_inventory::Inventory;
@@ -99,6 +117,10 @@ pub use task_inventory::Inventory;
 const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
 const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const BUFFER_RESYNC_INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const BUFFER_RESYNC_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const WILL_RENAME_FILES_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_SHARED_CLIPBOARD_ENTRIES: usize = 20;
 
 pub trait Item {
     fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId>;
@@ -108,7 +130,15 @@ pub trait Item {
 pub struct Project {
     worktrees: Vec<WorktreeHandle>,
     active_entry: Option<ProjectEntryId>,
+    pinned_paths: Vec<ProjectPath>,
+    /// The files pinned by the host of this remote project, if it shares them.
+    host_pinned_paths: Vec<ProjectPath>,
+    shared_clipboard: VecDeque<SharedClipboardEntry>,
+    /// The file that each collaborator currently has active in their workspace.
+    collaborator_active_paths: HashMap<proto::PeerId, ProjectPath>,
     buffer_ordered_messages_tx: mpsc::UnboundedSender<BufferOrderedMessage>,
+    /// Cancels detached background work, like searches, when the project is released.
+    background_work: CancellationToken,
     languages: Arc<LanguageRegistry>,
     supplementary_language_servers:
         HashMap<LanguageServerId, (LanguageServerName, Arc<LanguageServer>)>,
@@ -122,6 +152,7 @@ pub struct Project {
     next_diagnostic_group_id: usize,
     user_store: Model<UserStore>,
     fs: Arc<dyn Fs>,
+    process_runner: Arc<dyn ProcessRunner>,
     client_state: ProjectClientState,
     collaborators: HashMap<proto::PeerId, Collaborator>,
     client_subscriptions: Vec<client::Subscription>,
@@ -129,6 +160,7 @@ pub struct Project {
     next_buffer_id: BufferId,
     opened_buffer: (watch::Sender<()>, watch::Receiver<()>),
     shared_buffers: HashMap<proto::PeerId, HashSet<BufferId>>,
+    pending_join_requests: HashMap<u64, oneshot::Sender<bool>>,
     #[allow(clippy::type_complexity)]
     loading_buffers_by_path: HashMap<
         ProjectPath,
@@ -146,6 +178,8 @@ pub struct Project {
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     buffers_being_formatted: HashSet<BufferId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
+    buffers_needing_lsp_sync: HashSet<WeakModel<Buffer>>,
+    pending_buffer_operations: Vec<(BufferId, Operation)>,
     git_diff_debouncer: DebouncedDelay,
     nonce: u128,
     _maintain_buffer_languages: Task<()>,
@@ -209,12 +243,15 @@ enum ProjectClientState {
     Local,
     Shared {
         remote_id: u64,
+        read_only_for_guests: bool,
+        approve_join_requests: bool,
         updates_tx: mpsc::UnboundedSender<LocalProjectUpdate>,
         _send_updates: Task<Result<()>>,
     },
     Remote {
         sharing_has_stopped: bool,
         capability: Capability,
+        read_only_for_guests: bool,
         remote_id: u64,
         replica_id: ReplicaId,
     },
@@ -269,6 +306,7 @@ pub enum Event {
     },
     RemoteIdChanged(Option<u64>),
     DisconnectedFromHost,
+    Rejoined,
     Closed,
     DeletedEntry(ProjectEntryId),
     CollaboratorUpdated {
@@ -277,8 +315,18 @@ pub enum Event {
     },
     CollaboratorJoined(proto::PeerId),
     CollaboratorLeft(proto::PeerId),
+    JoinRequested {
+        request_id: u64,
+        requester_id: u64,
+    },
+    JoinRequestCancelled {
+        request_id: u64,
+    },
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
+    PinnedPathsChanged,
+    SharedClipboardChanged,
+    CollaboratorActivePathsChanged,
 }
 
 pub enum LanguageServerState {
@@ -309,6 +357,14 @@ pub struct LanguageServerProgress {
     pub last_update_at: Instant,
 }
 
+/// Text that a collaborator copied to the shared clipboard of a shared project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedClipboardEntry {
+    /// The user who shared the text, if known.
+    pub author_id: Option<u64>,
+    pub text: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct ProjectPath {
     pub worktree_id: WorktreeId,
@@ -432,6 +488,14 @@ impl Hover {
 #[derive(Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
+/// An entry that is about to be renamed, for notifying language servers.
+struct FileRename {
+    worktree_id: WorktreeId,
+    old_abs_path: PathBuf,
+    new_abs_path: PathBuf,
+    is_dir: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatTrigger {
     Save,
@@ -486,6 +550,7 @@ impl Project {
 
     pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
         Self::init_settings(cx);
+        power::init(cx);
 
         client.add_model_message_handler(Self::handle_add_collaborator);
         client.add_model_message_handler(Self::handle_update_project_collaborator);
@@ -495,6 +560,12 @@ impl Project {
         client.add_model_message_handler(Self::handle_start_language_server);
         client.add_model_message_handler(Self::handle_update_language_server);
         client.add_model_message_handler(Self::handle_update_project);
+        client.add_model_message_handler(Self::handle_set_project_read_only);
+        client.add_model_message_handler(Self::handle_update_pinned_paths);
+        client.add_model_message_handler(Self::handle_share_clipboard_entry);
+        client.add_model_message_handler(Self::handle_update_active_path);
+        client.add_model_request_handler(Self::handle_request_join_project);
+        client.add_model_message_handler(Self::handle_cancel_join_request);
         client.add_model_message_handler(Self::handle_unshare_project);
         client.add_model_message_handler(Self::handle_create_buffer_for_peer);
         client.add_model_message_handler(Self::handle_update_buffer_file);
@@ -532,6 +603,7 @@ impl Project {
         client.add_model_request_handler(Self::handle_open_buffer_by_id);
         client.add_model_request_handler(Self::handle_open_buffer_by_path);
         client.add_model_request_handler(Self::handle_save_buffer);
+        client.add_model_request_handler(Self::handle_download_file_chunk);
         client.add_model_message_handler(Self::handle_update_diff_base);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
     }
@@ -555,10 +627,12 @@ impl Project {
             Self {
                 worktrees: Vec::new(),
                 buffer_ordered_messages_tx: tx,
+                background_work: CancellationToken::new(),
                 collaborators: Default::default(),
                 next_buffer_id: BufferId::new(1).unwrap(),
                 opened_buffers: Default::default(),
                 shared_buffers: Default::default(),
+                pending_join_requests: Default::default(),
                 incomplete_remote_buffers: Default::default(),
                 loading_buffers_by_path: Default::default(),
                 loading_local_worktrees: Default::default(),
@@ -566,6 +640,10 @@ impl Project {
                 local_buffer_ids_by_entry_id: Default::default(),
                 buffer_snapshots: Default::default(),
                 join_project_response_message_id: 0,
+                pinned_paths: Vec::new(),
+                host_pinned_paths: Vec::new(),
+                shared_clipboard: VecDeque::new(),
+                collaborator_active_paths: Default::default(),
                 client_state: ProjectClientState::Local,
                 opened_buffer: watch::channel(),
                 client_subscriptions: Vec::new(),
@@ -581,6 +659,7 @@ impl Project {
                 client,
                 user_store,
                 fs,
+                process_runner: process::runner(),
                 next_entry_id: Default::default(),
                 next_diagnostic_group_id: Default::default(),
                 supplementary_language_servers: HashMap::default(),
@@ -590,6 +669,8 @@ impl Project {
                 last_workspace_edits_by_language_server: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffers_needing_diff: Default::default(),
+                buffers_needing_lsp_sync: Default::default(),
+                pending_buffer_operations: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
@@ -645,15 +726,21 @@ impl Project {
             let mut this = Self {
                 worktrees: Vec::new(),
                 buffer_ordered_messages_tx: tx,
+                background_work: CancellationToken::new(),
                 loading_buffers_by_path: Default::default(),
                 next_buffer_id: BufferId::new(1).unwrap(),
                 opened_buffer: watch::channel(),
                 shared_buffers: Default::default(),
+                pending_join_requests: Default::default(),
                 incomplete_remote_buffers: Default::default(),
                 loading_local_worktrees: Default::default(),
                 local_buffer_ids_by_path: Default::default(),
                 local_buffer_ids_by_entry_id: Default::default(),
                 active_entry: None,
+                pinned_paths: Vec::new(),
+                host_pinned_paths: Vec::new(),
+                shared_clipboard: VecDeque::new(),
+                collaborator_active_paths: Default::default(),
                 collaborators: Default::default(),
                 join_project_response_message_id: response.message_id,
                 _maintain_buffer_languages: Self::maintain_buffer_languages(languages.clone(), cx),
@@ -661,6 +748,7 @@ impl Project {
                 languages,
                 user_store: user_store.clone(),
                 fs,
+                process_runner: process::runner(),
                 next_entry_id: Default::default(),
                 next_diagnostic_group_id: Default::default(),
                 client_subscriptions: Default::default(),
@@ -672,6 +760,7 @@ impl Project {
                 client_state: ProjectClientState::Remote {
                     sharing_has_stopped: false,
                     capability: Capability::ReadWrite,
+                    read_only_for_guests: response.payload.read_only,
                     remote_id,
                     replica_id,
                 },
@@ -698,6 +787,8 @@ impl Project {
                 opened_buffers: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffers_needing_diff: Default::default(),
+                buffers_needing_lsp_sync: Default::default(),
+                pending_buffer_operations: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
@@ -741,6 +832,7 @@ impl Project {
     }
 
     fn release(&mut self, cx: &mut AppContext) {
+        self.background_work.cancel();
         match &self.client_state {
             ProjectClientState::Local => {}
             ProjectClientState::Shared { .. } => {
@@ -755,7 +847,9 @@ impl Project {
         }
     }
 
-    fn shutdown_language_servers(
+    /// Shuts down this project's language servers. This also happens when the
+    /// app quits, if it hasn't happened already.
+    pub fn shutdown_language_servers(
         &mut self,
         _cx: &mut ModelContext<Self>,
     ) -> impl Future<Output = ()> {
@@ -832,7 +926,7 @@ impl Project {
                     language_formatters_to_check.push((
                         buffer_file.map(|f| f.worktree_id(cx)),
                         Arc::clone(language),
-                        settings.clone(),
+                        settings,
                     ));
                 }
             }
@@ -845,21 +939,35 @@ impl Project {
         let new_lsp_settings = ProjectSettings::get_global(cx).lsp.clone();
         let current_lsp_settings = &self.current_lsp_settings;
         for (worktree_id, started_lsp_name) in self.language_server_ids.keys() {
-            let language = languages.iter().find_map(|l| {
-                let adapter = l
-                    .lsp_adapters()
-                    .iter()
-                    .find(|adapter| &adapter.name == started_lsp_name)?;
-                Some((l, adapter))
-            });
-            if let Some((language, adapter)) = language {
+            let languages_using_server = languages
+                .iter()
+                .filter_map(|l| {
+                    let adapter = l
+                        .lsp_adapters()
+                        .iter()
+                        .find(|adapter| &adapter.name == started_lsp_name)?;
+                    Some((l, adapter))
+                })
+                .collect::<Vec<_>>();
+            if !languages_using_server.is_empty() {
                 let worktree = self.worktree_for_id(*worktree_id, cx);
                 let file = worktree.as_ref().and_then(|tree| {
                     tree.update(cx, |tree, cx| tree.root_file(cx).map(|f| f as _))
                 });
-                if !language_settings(Some(language), file.as_ref(), cx).enable_language_server {
+                // A server shared by several languages keeps running as long as
+                // any of them still has it enabled.
+                let enabled_language = languages_using_server.into_iter().find(|(language, _)| {
+                    let settings = language_settings(Some(language), file.as_ref(), cx);
+                    settings.enable_language_server
+                        && settings
+                            .customized_language_servers(&lsp_adapter_names(language))
+                            .contains(started_lsp_name)
+                });
+                let Some((language, adapter)) = enabled_language else {
                     language_servers_to_stop.push((*worktree_id, started_lsp_name.clone()));
-                } else if let Some(worktree) = worktree {
+                    continue;
+                };
+                if let Some(worktree) = worktree {
                     let server_name = &adapter.name.0;
                     match (
                         current_lsp_settings.get(server_name),
@@ -911,6 +1019,8 @@ impl Project {
             self.restart_language_servers(worktree, language, cx);
         }
 
+        self.update_join_approval(cx);
+
         if self.copilot_lsp_subscription.is_none() {
             if let Some(copilot) = Copilot::global(cx) {
                 for buffer in self.opened_buffers.values() {
@@ -973,6 +1083,15 @@ impl Project {
         &self.fs
     }
 
+    pub fn process_runner(&self) -> &Arc<dyn ProcessRunner> {
+        &self.process_runner
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn set_process_runner(&mut self, process_runner: Arc<dyn ProcessRunner>) {
+        self.process_runner = process_runner;
+    }
+
     pub fn remote_id(&self) -> Option<u64> {
         match self.client_state {
             ProjectClientState::Local => None,
@@ -1172,46 +1291,262 @@ impl Project {
         new_path: impl Into<Arc<Path>>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Option<Entry>>> {
-        let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
-            return Task::ready(Ok(None));
-        };
-        let new_path = new_path.into();
+        let task = self.rename_entries(vec![(entry_id, new_path.into())], cx);
+        cx.foreground_executor().spawn(async move {
+            let (mut entries, _) = task.await?;
+            Ok(entries.pop().flatten())
+        })
+    }
+
+    /// Renames or moves each entry to its new path within its worktree.
+    ///
+    /// Entries inside a directory that is also being renamed move along with
+    /// it, so they're skipped.
+    ///
+    /// In local projects, language servers are first asked for the edits that
+    /// the renames imply, such as updated import paths. Those edits are applied
+    /// and returned along with the renamed entries.
+    pub fn rename_entries(
+        &mut self,
+        renames: Vec<(ProjectEntryId, Arc<Path>)>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<(Vec<Option<Entry>>, ProjectTransaction)>> {
+        let mut worktree_renames = Vec::new();
+        for (entry_id, new_path) in renames {
+            let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
+                continue;
+            };
+            let Some(old_path) = worktree
+                .read(cx)
+                .entry_for_id(entry_id)
+                .map(|entry| entry.path.clone())
+            else {
+                continue;
+            };
+            worktree_renames.push((worktree, entry_id, old_path, new_path));
+        }
+        let renamed_paths = worktree_renames
+            .iter()
+            .map(|(worktree, _, old_path, _)| (worktree.entity_id(), old_path.clone()))
+            .collect::<Vec<_>>();
+        worktree_renames.retain(|(worktree, _, old_path, _)| {
+            !renamed_paths.iter().any(|(worktree_id, path)| {
+                *worktree_id == worktree.entity_id()
+                    && old_path != path
+                    && old_path.starts_with(path)
+            })
+        });
+
         if self.is_local() {
-            worktree.update(cx, |worktree, cx| {
-                worktree
-                    .as_local_mut()
-                    .unwrap()
-                    .rename_entry(entry_id, new_path, cx)
+            let mut file_renames = Vec::new();
+            for (worktree, entry_id, old_path, new_path) in &worktree_renames {
+                let worktree = worktree.read(cx);
+                let (Some(local_worktree), Some(entry)) =
+                    (worktree.as_local(), worktree.entry_for_id(*entry_id))
+                else {
+                    continue;
+                };
+                let abs_path = local_worktree.abs_path();
+                file_renames.push(FileRename {
+                    worktree_id: worktree.id(),
+                    old_abs_path: abs_path.join(old_path),
+                    new_abs_path: abs_path.join(new_path),
+                    is_dir: entry.is_dir(),
+                });
+            }
+            let will_rename = self.will_rename_files(&file_renames, cx);
+
+            cx.spawn(move |this, mut cx| async move {
+                let transaction = will_rename.await;
+                let mut entries = Vec::new();
+                for (worktree, entry_id, _, new_path) in worktree_renames {
+                    let entry = worktree
+                        .update(&mut cx, |worktree, cx| {
+                            worktree
+                                .as_local_mut()
+                                .unwrap()
+                                .rename_entry(entry_id, new_path, cx)
+                        })?
+                        .await?;
+                    entries.push(entry);
+                }
+                this.update(&mut cx, |this, _| this.did_rename_files(&file_renames))?;
+                Ok((entries, transaction))
             })
         } else {
             let client = self.client.clone();
             let project_id = self.remote_id().unwrap();
 
             cx.spawn(move |_, mut cx| async move {
-                let response = client
-                    .request(proto::RenameProjectEntry {
-                        project_id,
-                        entry_id: entry_id.to_proto(),
-                        new_path: new_path.to_string_lossy().into(),
-                    })
-                    .await?;
-                match response.entry {
-                    Some(entry) => worktree
-                        .update(&mut cx, |worktree, cx| {
-                            worktree.as_remote_mut().unwrap().insert_entry(
-                                entry,
-                                response.worktree_scan_id as usize,
-                                cx,
-                            )
-                        })?
-                        .await
-                        .map(Some),
-                    None => Ok(None),
+                let mut entries = Vec::new();
+                for (worktree, entry_id, _, new_path) in worktree_renames {
+                    let response = client
+                        .request(proto::RenameProjectEntry {
+                            project_id,
+                            entry_id: entry_id.to_proto(),
+                            new_path: new_path.to_string_lossy().into(),
+                        })
+                        .await?;
+                    let entry = match response.entry {
+                        Some(entry) => Some(
+                            worktree
+                                .update(&mut cx, |worktree, cx| {
+                                    worktree.as_remote_mut().unwrap().insert_entry(
+                                        entry,
+                                        response.worktree_scan_id as usize,
+                                        cx,
+                                    )
+                                })?
+                                .await?,
+                        ),
+                        None => None,
+                    };
+                    entries.push(entry);
                 }
+                Ok((entries, ProjectTransaction::default()))
             })
         }
     }
 
+    /// Sends `workspace/willRenameFiles` to the language servers that asked to
+    /// be told about these renames, and applies the edits they respond with.
+    fn will_rename_files(
+        &self,
+        renames: &[FileRename],
+        cx: &mut ModelContext<Self>,
+    ) -> Task<ProjectTransaction> {
+        let mut requests = Vec::new();
+        for (adapter, server, files) in
+            self.file_renames_by_server(renames, |operations| operations.will_rename.as_ref())
+        {
+            let adapter = adapter.clone();
+            let server = server.clone();
+            let request =
+                server.request::<lsp::request::WillRenameFiles>(lsp::RenameFilesParams { files });
+            requests.push((adapter, server, request));
+        }
+
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this, mut cx| async move {
+            let mut transaction = ProjectTransaction::default();
+            let Some(this) = this.upgrade() else {
+                return transaction;
+            };
+            for (adapter, server, request) in requests {
+                // The rename waits for these edits, so don't let a slow server
+                // hold it up for long.
+                let mut timeout = executor.timer(WILL_RENAME_FILES_TIMEOUT).fuse();
+                let edit = select! {
+                    edit = request.fuse() => edit,
+                    _ = timeout => {
+                        log::warn!("{} timed out responding to willRenameFiles", server.name());
+                        continue;
+                    }
+                };
+                let Some(Some(edit)) = edit.log_err() else {
+                    continue;
+                };
+                if let Some(edits) = Self::deserialize_workspace_edit(
+                    this.clone(),
+                    edit,
+                    true,
+                    adapter,
+                    server,
+                    &mut cx,
+                )
+                .await
+                .log_err()
+                {
+                    transaction.0.extend(edits.0);
+                }
+            }
+            transaction
+        })
+    }
+
+    fn did_rename_files(&self, renames: &[FileRename]) {
+        for (_, server, files) in
+            self.file_renames_by_server(renames, |operations| operations.did_rename.as_ref())
+        {
+            server
+                .notify::<lsp::notification::DidRenameFiles>(lsp::RenameFilesParams { files })
+                .log_err();
+        }
+    }
+
+    /// Groups the renames by the running language servers whose file operation
+    /// filters, selected by `registration`, match the renamed paths.
+    fn file_renames_by_server<'a>(
+        &'a self,
+        renames: &[FileRename],
+        registration: impl Fn(
+            &lsp::WorkspaceFileOperationsServerCapabilities,
+        ) -> Option<&lsp::FileOperationRegistrationOptions>,
+    ) -> Vec<(
+        &'a Arc<CachedLspAdapter>,
+        &'a Arc<LanguageServer>,
+        Vec<lsp::FileRename>,
+    )> {
+        let mut renames_by_server = Vec::new();
+        let worktree_ids = renames
+            .iter()
+            .map(|rename| rename.worktree_id)
+            .collect::<HashSet<_>>();
+        for worktree_id in worktree_ids {
+            for (adapter, _, server) in self.language_servers_for_worktree(worktree_id) {
+                let Some(options) = server
+                    .capabilities()
+                    .workspace
+                    .as_ref()
+                    .and_then(|workspace| workspace.file_operations.as_ref())
+                    .and_then(&registration)
+                else {
+                    continue;
+                };
+                let files = renames
+                    .iter()
+                    .filter(|rename| {
+                        rename.worktree_id == worktree_id
+                            && file_operation_filters_match(
+                                options,
+                                &rename.old_abs_path,
+                                rename.is_dir,
+                            )
+                    })
+                    .filter_map(|rename| {
+                        Some(lsp::FileRename {
+                            old_uri: lsp::Url::from_file_path(&rename.old_abs_path)
+                                .ok()?
+                                .to_string(),
+                            new_uri: lsp::Url::from_file_path(&rename.new_abs_path)
+                                .ok()?
+                                .to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                if !files.is_empty() {
+                    renames_by_server.push((adapter, server, files));
+                }
+            }
+        }
+        renames_by_server
+    }
+
+    /// Whether deleting entries moves them to the system trash, rather than removing them
+    /// permanently. This is up to the host for guests of a shared project, so it is unknown
+    /// to them.
+    pub fn deletes_to_trash(&self, cx: &AppContext) -> Option<bool> {
+        if self.is_local() {
+            Some(
+                !ProjectSettings::get_global(cx).permanently_delete && self.fs.is_trash_supported(),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Deletes the entry from disk, moving it to the system trash unless the host of the
+    /// project deletes files permanently.
     pub fn delete_entry(
         &mut self,
         entry_id: ProjectEntryId,
@@ -1221,9 +1556,12 @@ impl Project {
 
         cx.emit(Event::DeletedEntry(entry_id));
 
-        if self.is_local() {
+        if let Some(trash) = self.deletes_to_trash(cx) {
             worktree.update(cx, |worktree, cx| {
-                worktree.as_local_mut().unwrap().delete_entry(entry_id, cx)
+                worktree
+                    .as_local_mut()
+                    .unwrap()
+                    .delete_entry(entry_id, trash, cx)
             })
         } else {
             let client = self.client.clone();
@@ -1340,12 +1678,29 @@ impl Project {
                     })
                     .log_err();
             }
+            for (directory, content) in store.editorconfigs(worktree.entity_id().as_u64() as usize)
+            {
+                self.client
+                    .send(proto::UpdateWorktreeSettings {
+                        project_id,
+                        worktree_id,
+                        path: directory
+                            .join(EDITORCONFIG_FILE_NAME)
+                            .to_string_lossy()
+                            .into(),
+                        content: Some(content.to_string()),
+                    })
+                    .log_err();
+            }
         }
 
         let (updates_tx, mut updates_rx) = mpsc::unbounded();
         let client = self.client.clone();
         self.client_state = ProjectClientState::Shared {
             remote_id: project_id,
+            read_only_for_guests: false,
+            // The room sends the setting along when sharing the project.
+            approve_join_requests: ProjectSettings::get_global(cx).approve_join_requests,
             updates_tx,
             _send_updates: cx.spawn(move |this, mut cx| async move {
                 while let Some(update) = updates_rx.next().await {
@@ -1480,9 +1835,8 @@ impl Project {
                 )
             })
             .collect();
-        self.buffer_ordered_messages_tx
-            .unbounded_send(BufferOrderedMessage::Resync)
-            .unwrap();
+        self.send_buffer_ordered_message(BufferOrderedMessage::Resync);
+        cx.emit(Event::Rejoined);
         cx.notify();
         Ok(())
     }
@@ -1502,7 +1856,10 @@ impl Project {
         if let ProjectClientState::Shared { remote_id, .. } = self.client_state {
             self.client_state = ProjectClientState::Local;
             self.collaborators.clear();
+            self.collaborator_active_paths.clear();
             self.shared_buffers.clear();
+            self.shared_clipboard.clear();
+            self.pending_join_requests.clear();
             self.client_subscriptions.clear();
 
             for worktree_handle in self.worktrees.iter_mut() {
@@ -1551,15 +1908,137 @@ impl Project {
             } else {
                 Capability::ReadOnly
             };
-        if let ProjectClientState::Remote { capability, .. } = &mut self.client_state {
-            if *capability == new_capability {
-                return;
+        self.update_remote_capability(cx, |capability, _| *capability = new_capability);
+    }
+
+    /// Prevents guests from editing this shared project, or allows it again for those whose
+    /// role lets them edit.
+    pub fn set_read_only_for_guests(
+        &mut self,
+        read_only: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let ProjectClientState::Shared {
+            remote_id,
+            read_only_for_guests,
+            ..
+        } = &mut self.client_state
+        else {
+            return Task::ready(Err(anyhow!("project is not shared")));
+        };
+        if *read_only_for_guests == read_only {
+            return Task::ready(Ok(()));
+        }
+
+        *read_only_for_guests = read_only;
+        cx.notify();
+        let request = self.client.request(proto::SetProjectReadOnly {
+            project_id: *remote_id,
+            read_only,
+        });
+        cx.spawn(|this, mut cx| async move {
+            if let Err(error) = request.await {
+                // Guests were never told, so show the setting they still have.
+                this.update(&mut cx, |this, cx| {
+                    if let ProjectClientState::Shared {
+                        read_only_for_guests,
+                        ..
+                    } = &mut this.client_state
+                    {
+                        if *read_only_for_guests == read_only {
+                            *read_only_for_guests = !read_only;
+                            cx.notify();
+                        }
+                    }
+                })?;
+                return Err(error);
+            }
+            Ok(())
+        })
+    }
+
+    /// Tells the server whether guests have to be let in by the host, when that setting
+    /// changes while the project is shared.
+    fn update_join_approval(&mut self, cx: &mut ModelContext<Self>) {
+        let approve = ProjectSettings::get_global(cx).approve_join_requests;
+        let ProjectClientState::Shared {
+            remote_id,
+            approve_join_requests,
+            ..
+        } = &mut self.client_state
+        else {
+            return;
+        };
+        if *approve_join_requests == approve {
+            return;
+        }
+
+        *approve_join_requests = approve;
+        let request = self.client.request(proto::SetProjectJoinApproval {
+            project_id: *remote_id,
+            approve_join_requests: approve,
+        });
+        cx.background_executor()
+            .spawn(async move {
+                request.await?;
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+    }
+
+    /// Whether the host has prevented guests from editing this project.
+    pub fn is_read_only_for_guests(&self) -> bool {
+        match &self.client_state {
+            ProjectClientState::Shared {
+                read_only_for_guests,
+                ..
             }
+            | ProjectClientState::Remote {
+                read_only_for_guests,
+                ..
+            } => *read_only_for_guests,
+            ProjectClientState::Local => false,
+        }
+    }
+
+    /// Lets a guest who asked to join this shared project in, or turns them away.
+    pub fn respond_to_join_request(&mut self, request_id: u64, allow: bool) {
+        if let Some(response) = self.pending_join_requests.remove(&request_id) {
+            response.send(allow).ok();
+        }
+    }
+
+    pub fn has_pending_join_request(&self, request_id: u64) -> bool {
+        self.pending_join_requests.contains_key(&request_id)
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn pending_join_request_ids(&self) -> Vec<u64> {
+        self.pending_join_requests.keys().copied().collect()
+    }
+
+    fn update_remote_capability(
+        &mut self,
+        cx: &mut ModelContext<Self>,
+        update: impl FnOnce(&mut Capability, &mut bool),
+    ) {
+        let old_capability = self.capability();
+        let ProjectClientState::Remote {
+            capability,
+            read_only_for_guests,
+            ..
+        } = &mut self.client_state
+        else {
+            return;
+        };
+        update(capability, read_only_for_guests);
 
-            *capability = new_capability;
+        let new_capability = self.capability();
+        if new_capability != old_capability {
             for buffer in self.opened_buffers() {
                 buffer.update(cx, |buffer, cx| buffer.set_capability(new_capability, cx));
             }
+            cx.notify();
         }
     }
 
@@ -1572,6 +2051,7 @@ impl Project {
             *sharing_has_stopped = true;
 
             self.collaborators.clear();
+            self.collaborator_active_paths.clear();
 
             for worktree in &self.worktrees {
                 if let Some(worktree) = worktree.upgrade() {
@@ -1616,6 +2096,10 @@ impl Project {
 
     pub fn capability(&self) -> Capability {
         match &self.client_state {
+            ProjectClientState::Remote {
+                read_only_for_guests: true,
+                ..
+            } => Capability::ReadOnly,
             ProjectClientState::Remote { capability, .. } => *capability,
             ProjectClientState::Shared { .. } | ProjectClientState::Local => Capability::ReadWrite,
         }
@@ -1658,6 +2142,51 @@ impl Project {
         &mut self,
         path: ProjectPath,
         cx: &mut ModelContext<Self>,
+    ) -> Task<Result<(Option<ProjectEntryId>, AnyModel)>> {
+        if self.is_local() {
+            if let Some(abs_path) = self.absolute_path(&path, cx) {
+                let fs = self.fs.clone();
+                return cx.spawn(move |this, mut cx| async move {
+                    if binary_file::file_is_binary(fs.as_ref(), &abs_path)
+                        .await
+                        .unwrap_or(false)
+                    {
+                        let metadata = fs
+                            .metadata(&abs_path)
+                            .await?
+                            .ok_or_else(|| anyhow!("{abs_path:?} does not exist"))?;
+                        let (project_entry_id, binary_file) =
+                            this.update(&mut cx, |this, cx| {
+                                let project_entry_id = this.entry_for_path(&path, cx).map(|e| e.id);
+                                let binary_file = cx.new_model(|cx| {
+                                    BinaryFile::new(
+                                        fs,
+                                        path,
+                                        abs_path,
+                                        project_entry_id,
+                                        metadata,
+                                        cx,
+                                    )
+                                });
+                                (project_entry_id, binary_file)
+                            })?;
+                        let binary_file: &AnyModel = &binary_file;
+                        return Ok((project_entry_id, binary_file.clone()));
+                    }
+
+                    this.update(&mut cx, |this, cx| this.open_buffer_path(path, cx))?
+                        .await
+                });
+            }
+        }
+
+        self.open_buffer_path(path, cx)
+    }
+
+    fn open_buffer_path(
+        &mut self,
+        path: ProjectPath,
+        cx: &mut ModelContext<Self>,
     ) -> Task<Result<(Option<ProjectEntryId>, AnyModel)>> {
         let task = self.open_buffer(path.clone(), cx);
         cx.spawn(move |_, cx| async move {
@@ -1940,8 +2469,11 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Result<()> {
         self.request_buffer_diff_recalculation(buffer, cx);
+        let is_large_file =
+            ProjectSettings::get_global(cx).is_large_file(buffer.read(cx).len() as u64);
         buffer.update(cx, |buffer, _| {
-            buffer.set_language_registry(self.languages.clone())
+            buffer.set_language_registry(self.languages.clone());
+            buffer.set_large_file(is_large_file);
         });
 
         let remote_id = buffer.read(cx).remote_id();
@@ -2142,6 +2674,9 @@ impl Project {
         buffer_handle: &Model<Buffer>,
         cx: &mut ModelContext<Self>,
     ) {
+        if buffer_handle.read(cx).is_large_file() {
+            return;
+        }
         if let Some(copilot) = Copilot::global(cx) {
             copilot.update(cx, |copilot, cx| copilot.register_buffer(buffer_handle, cx));
         }
@@ -2182,6 +2717,7 @@ impl Project {
         }
 
         let mut needs_resync_with_host = false;
+        let mut failed_resync_attempts = 0;
         let mut changes = rx.ready_chunks(MAX_BATCH_SIZE);
 
         while let Some(changes) = changes.next().await {
@@ -2205,12 +2741,34 @@ impl Project {
 
                     BufferOrderedMessage::Resync => {
                         operations_by_buffer_id.clear();
-                        if this
+                        let synchronized = this
                             .update(&mut cx, |this, cx| this.synchronize_remote_buffers(cx))?
-                            .await
-                            .is_ok()
-                        {
+                            .await;
+                        if let Err(error) = synchronized {
+                            // Operations are only kept in the buffers until they're synchronized
+                            // with the host, so keep retrying while the project is still joined,
+                            // backing off so an unavailable host isn't flooded with requests.
+                            log::error!("failed to synchronize buffers with host: {error:#}");
+                            needs_resync_with_host = true;
+                            let retry_delay = BUFFER_RESYNC_INITIAL_RETRY_DELAY
+                                .saturating_mul(2u32.saturating_pow(failed_resync_attempts))
+                                .min(BUFFER_RESYNC_MAX_RETRY_DELAY);
+                            failed_resync_attempts += 1;
+                            this.update(&mut cx, |this, cx| {
+                                if this.is_remote() && !this.is_disconnected() {
+                                    let tx = this.buffer_ordered_messages_tx.clone();
+                                    let timer = cx.background_executor().timer(retry_delay);
+                                    cx.background_executor()
+                                        .spawn(this.background_work.run(async move {
+                                            timer.await;
+                                            tx.unbounded_send(BufferOrderedMessage::Resync).ok();
+                                        }))
+                                        .detach();
+                                }
+                            })?;
+                        } else {
                             needs_resync_with_host = false;
+                            failed_resync_attempts = 0;
                         }
                     }
 
@@ -2270,111 +2828,36 @@ impl Project {
 
         match event {
             BufferEvent::Operation(operation) => {
-                self.buffer_ordered_messages_tx
-                    .unbounded_send(BufferOrderedMessage::Operation {
-                        buffer_id: buffer.read(cx).remote_id(),
-                        operation: language::proto::serialize_operation(operation),
-                    })
-                    .ok();
+                // Operations are sent to collaborators once the frame showing them has been
+                // presented, so that serializing them doesn't delay it.
+                self.pending_buffer_operations
+                    .push((buffer.read(cx).remote_id(), operation.clone()));
+                if self.pending_buffer_operations.len() == 1 {
+                    let this = cx.weak_model();
+                    cx.after_next_frame(move |cx| {
+                        this.update(cx, |this, _| this.flush_buffer_operations())
+                            .ok();
+                    });
+                }
             }
 
             BufferEvent::Edited { .. } => {
-                let buffer = buffer.read(cx);
-                let file = File::from_dyn(buffer.file())?;
-                let abs_path = file.as_local()?.abs_path(cx);
-                let uri = lsp::Url::from_file_path(abs_path).unwrap();
-                let next_snapshot = buffer.text_snapshot();
-
-                let language_servers: Vec<_> = self
-                    .language_servers_for_buffer(buffer, cx)
-                    .map(|i| i.1.clone())
-                    .collect();
-
-                for language_server in language_servers {
-                    let language_server = language_server.clone();
-
-                    let buffer_snapshots = self
-                        .buffer_snapshots
-                        .get_mut(&buffer.remote_id())
-                        .and_then(|m| m.get_mut(&language_server.server_id()))?;
-                    let previous_snapshot = buffer_snapshots.last()?;
-
-                    let build_incremental_change = || {
-                        buffer
-                            .edits_since::<(PointUtf16, usize)>(
-                                previous_snapshot.snapshot.version(),
-                            )
-                            .map(|edit| {
-                                let edit_start = edit.new.start.0;
-                                let edit_end = edit_start + (edit.old.end.0 - edit.old.start.0);
-                                let new_text = next_snapshot
-                                    .text_for_range(edit.new.start.1..edit.new.end.1)
-                                    .collect();
-                                lsp::TextDocumentContentChangeEvent {
-                                    range: Some(lsp::Range::new(
-                                        point_to_lsp(edit_start),
-                                        point_to_lsp(edit_end),
-                                    )),
-                                    range_length: None,
-                                    text: new_text,
-                                }
-                            })
-                            .collect()
-                    };
-
-                    let document_sync_kind = language_server
-                        .capabilities()
-                        .text_document_sync
-                        .as_ref()
-                        .and_then(|sync| match sync {
-                            lsp::TextDocumentSyncCapability::Kind(kind) => Some(*kind),
-                            lsp::TextDocumentSyncCapability::Options(options) => options.change,
-                        });
-
-                    let content_changes: Vec<_> = match document_sync_kind {
-                        Some(lsp::TextDocumentSyncKind::FULL) => {
-                            vec![lsp::TextDocumentContentChangeEvent {
-                                range: None,
-                                range_length: None,
-                                text: next_snapshot.text(),
-                            }]
-                        }
-                        Some(lsp::TextDocumentSyncKind::INCREMENTAL) => build_incremental_change(),
-                        _ => {
-                            #[cfg(any(test, feature = "test-support"))]
-                            {
-                                build_incremental_change()
-                            }
-
-                            #[cfg(not(any(test, feature = "test-support")))]
-                            {
-                                continue;
-                            }
-                        }
-                    };
-
-                    let next_version = previous_snapshot.version + 1;
-
-                    buffer_snapshots.push(LspBufferSnapshot {
-                        version: next_version,
-                        snapshot: next_snapshot.clone(),
+                File::from_dyn(buffer.read(cx).file())?.as_local()?;
+                self.buffers_needing_lsp_sync.insert(buffer.downgrade());
+                if self.buffers_needing_lsp_sync.len() == 1 {
+                    // Language servers are synced once the frame showing the edit has been
+                    // presented, so that it doesn't wait on them. Edits made in the meantime
+                    // are sent together.
+                    let this = cx.weak_model();
+                    cx.after_next_frame(move |cx| {
+                        this.update(cx, |this, cx| this.flush_lsp_changes(cx)).ok();
                     });
-
-                    language_server
-                        .notify::<lsp::notification::DidChangeTextDocument>(
-                            lsp::DidChangeTextDocumentParams {
-                                text_document: lsp::VersionedTextDocumentIdentifier::new(
-                                    uri.clone(),
-                                    next_version,
-                                ),
-                                content_changes,
-                            },
-                        )
-                        .log_err();
                 }
             }
 
             BufferEvent::Saved => {
+                self.flush_buffer_operations();
+                self.flush_lsp_changes(cx);
                 let file = File::from_dyn(buffer.read(cx).file())?;
                 let worktree_id = file.worktree_id(cx);
                 let abs_path = file.as_local()?.abs_path(cx);
@@ -2422,14 +2905,12 @@ impl Project {
                                             language_server_id,
                                             cx,
                                         );
-                                        this.buffer_ordered_messages_tx
-                                            .unbounded_send(
-                                                BufferOrderedMessage::LanguageServerUpdate {
-                                                    language_server_id,
-                                                    message:proto::update_language_server::Variant::DiskBasedDiagnosticsUpdated(Default::default())
-                                                },
-                                            )
-                                            .ok();
+                                        this.send_buffer_ordered_message(
+                                            BufferOrderedMessage::LanguageServerUpdate {
+                                                language_server_id,
+                                                message:proto::update_language_server::Variant::DiskBasedDiagnosticsUpdated(Default::default())
+                                            },
+                                        );
                                     }).ok();
                                 }
                             });
@@ -2469,6 +2950,136 @@ impl Project {
         None
     }
 
+    /// Sends the buffer operations that haven't been sent to collaborators yet. This has to
+    /// happen before anything else is sent through `buffer_ordered_messages_tx`.
+    fn flush_buffer_operations(&mut self) {
+        for (buffer_id, operation) in mem::take(&mut self.pending_buffer_operations) {
+            self.buffer_ordered_messages_tx
+                .unbounded_send(BufferOrderedMessage::Operation {
+                    buffer_id,
+                    operation: language::proto::serialize_operation(&operation),
+                })
+                .ok();
+        }
+    }
+
+    fn send_buffer_ordered_message(&mut self, message: BufferOrderedMessage) {
+        self.flush_buffer_operations();
+        self.buffer_ordered_messages_tx.unbounded_send(message).ok();
+    }
+
+    /// Sends the edits made to buffers since they were last synced to their language servers.
+    /// This has to happen before anything else is sent to those servers about the buffers.
+    fn flush_lsp_changes(&mut self, cx: &mut ModelContext<Self>) {
+        for buffer in mem::take(&mut self.buffers_needing_lsp_sync) {
+            if let Some(buffer) = buffer.upgrade() {
+                self.send_lsp_changes(&buffer, cx);
+            }
+        }
+    }
+
+    fn send_lsp_changes(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<()> {
+        let buffer = buffer.read(cx);
+        let file = File::from_dyn(buffer.file())?;
+        let abs_path = file.as_local()?.abs_path(cx);
+        let uri = lsp::Url::from_file_path(abs_path).unwrap();
+        let next_snapshot = buffer.text_snapshot();
+
+        let language_servers: Vec<_> = self
+            .language_servers_for_buffer(buffer, cx)
+            .map(|i| i.1.clone())
+            .collect();
+
+        for language_server in language_servers {
+            let language_server = language_server.clone();
+
+            let buffer_snapshots = self
+                .buffer_snapshots
+                .get_mut(&buffer.remote_id())
+                .and_then(|m| m.get_mut(&language_server.server_id()))?;
+            let previous_snapshot = buffer_snapshots.last()?;
+            if previous_snapshot.snapshot.version() == next_snapshot.version() {
+                continue;
+            }
+
+            let build_incremental_change = || {
+                buffer
+                    .edits_since::<(PointUtf16, usize)>(previous_snapshot.snapshot.version())
+                    .map(|edit| {
+                        let edit_start = edit.new.start.0;
+                        let edit_end = edit_start + (edit.old.end.0 - edit.old.start.0);
+                        let new_text = next_snapshot
+                            .text_for_range(edit.new.start.1..edit.new.end.1)
+                            .collect();
+                        lsp::TextDocumentContentChangeEvent {
+                            range: Some(lsp::Range::new(
+                                point_to_lsp(edit_start),
+                                point_to_lsp(edit_end),
+                            )),
+                            range_length: None,
+                            text: new_text,
+                        }
+                    })
+                    .collect()
+            };
+
+            let document_sync_kind = language_server
+                .capabilities()
+                .text_document_sync
+                .as_ref()
+                .and_then(|sync| match sync {
+                    lsp::TextDocumentSyncCapability::Kind(kind) => Some(*kind),
+                    lsp::TextDocumentSyncCapability::Options(options) => options.change,
+                });
+
+            let content_changes: Vec<_> = match document_sync_kind {
+                Some(lsp::TextDocumentSyncKind::FULL) => {
+                    vec![lsp::TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text: next_snapshot.text(),
+                    }]
+                }
+                Some(lsp::TextDocumentSyncKind::INCREMENTAL) => build_incremental_change(),
+                _ => {
+                    #[cfg(any(test, feature = "test-support"))]
+                    {
+                        build_incremental_change()
+                    }
+
+                    #[cfg(not(any(test, feature = "test-support")))]
+                    {
+                        continue;
+                    }
+                }
+            };
+
+            let next_version = previous_snapshot.version + 1;
+
+            buffer_snapshots.push(LspBufferSnapshot {
+                version: next_version,
+                snapshot: next_snapshot.clone(),
+            });
+
+            language_server
+                .notify::<lsp::notification::DidChangeTextDocument>(
+                    lsp::DidChangeTextDocumentParams {
+                        text_document: lsp::VersionedTextDocumentIdentifier::new(
+                            uri.clone(),
+                            next_version,
+                        ),
+                        content_changes,
+                    },
+                )
+                .log_err();
+        }
+        Some(())
+    }
+
     fn request_buffer_diff_recalculation(
         &mut self,
         buffer: &Model<Buffer>,
@@ -2670,7 +3281,11 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Option<()> {
         // If the buffer has a language, set it and start the language server if we haven't already.
+        // Large files are left as plain text, so they're never parsed or sent to language servers.
         let buffer = buffer_handle.read(cx);
+        if buffer.is_large_file() {
+            return None;
+        }
         let full_path = buffer.file()?.full_path(cx);
         let content = buffer.as_rope();
         let new_language = self
@@ -2697,7 +3312,7 @@ impl Project {
         });
 
         let buffer_file = buffer.read(cx).file().cloned();
-        let settings = language_settings(Some(&new_language), buffer_file.as_ref(), cx).clone();
+        let settings = language_settings(Some(&new_language), buffer_file.as_ref(), cx);
         let buffer_file = File::from_dyn(buffer_file.as_ref());
         let worktree = buffer_file.as_ref().map(|f| f.worktree_id(cx));
         if let Some(prettier_plugins) =
@@ -2725,8 +3340,19 @@ impl Project {
             return;
         }
 
-        for adapter in language.lsp_adapters() {
-            self.start_language_server(worktree, adapter.clone(), language.clone(), cx);
+        let available_lsp_adapters = language.lsp_adapters();
+        let enabled_lsp_adapters = settings
+            .customized_language_servers(&lsp_adapter_names(&language))
+            .into_iter()
+            .filter_map(|name| {
+                available_lsp_adapters
+                    .iter()
+                    .find(|adapter| adapter.name == name)
+                    .cloned()
+            })
+            .collect::<Vec<_>>();
+        for adapter in enabled_lsp_adapters {
+            self.start_language_server(worktree, adapter, language.clone(), cx);
         }
     }
 
@@ -3581,12 +4207,13 @@ impl Project {
                 if is_disk_based_diagnostics_progress {
                     language_server_status.has_pending_diagnostic_updates = true;
                     self.disk_based_diagnostics_started(language_server_id, cx);
-                    self.buffer_ordered_messages_tx
-                        .unbounded_send(BufferOrderedMessage::LanguageServerUpdate {
-                            language_server_id,
-                            message: proto::update_language_server::Variant::DiskBasedDiagnosticsUpdating(Default::default())
-                        })
-                        .ok();
+                    self.send_buffer_ordered_message(BufferOrderedMessage::LanguageServerUpdate {
+                        language_server_id,
+                        message:
+                            proto::update_language_server::Variant::DiskBasedDiagnosticsUpdating(
+                                Default::default(),
+                            ),
+                    });
                 } else {
                     self.on_lsp_work_start(
                         language_server_id,
@@ -3598,18 +4225,16 @@ impl Project {
                         },
                         cx,
                     );
-                    self.buffer_ordered_messages_tx
-                        .unbounded_send(BufferOrderedMessage::LanguageServerUpdate {
-                            language_server_id,
-                            message: proto::update_language_server::Variant::WorkStart(
-                                proto::LspWorkStart {
-                                    token,
-                                    message: report.message,
-                                    percentage: report.percentage.map(|p| p as u32),
-                                },
-                            ),
-                        })
-                        .ok();
+                    self.send_buffer_ordered_message(BufferOrderedMessage::LanguageServerUpdate {
+                        language_server_id,
+                        message: proto::update_language_server::Variant::WorkStart(
+                            proto::LspWorkStart {
+                                token,
+                                message: report.message,
+                                percentage: report.percentage.map(|p| p as u32),
+                            },
+                        ),
+                    });
                 }
             }
             lsp::WorkDoneProgress::Report(report) => {
@@ -3624,18 +4249,16 @@ impl Project {
                         },
                         cx,
                     );
-                    self.buffer_ordered_messages_tx
-                        .unbounded_send(BufferOrderedMessage::LanguageServerUpdate {
-                            language_server_id,
-                            message: proto::update_language_server::Variant::WorkProgress(
-                                proto::LspWorkProgress {
-                                    token,
-                                    message: report.message,
-                                    percentage: report.percentage.map(|p| p as u32),
-                                },
-                            ),
-                        })
-                        .ok();
+                    self.send_buffer_ordered_message(BufferOrderedMessage::LanguageServerUpdate {
+                        language_server_id,
+                        message: proto::update_language_server::Variant::WorkProgress(
+                            proto::LspWorkProgress {
+                                token,
+                                message: report.message,
+                                percentage: report.percentage.map(|p| p as u32),
+                            },
+                        ),
+                    });
                 }
             }
             lsp::WorkDoneProgress::End(_) => {
@@ -3644,25 +4267,21 @@ impl Project {
                 if is_disk_based_diagnostics_progress {
                     language_server_status.has_pending_diagnostic_updates = false;
                     self.disk_based_diagnostics_finished(language_server_id, cx);
-                    self.buffer_ordered_messages_tx
-                        .unbounded_send(BufferOrderedMessage::LanguageServerUpdate {
-                            language_server_id,
-                            message:
-                                proto::update_language_server::Variant::DiskBasedDiagnosticsUpdated(
-                                    Default::default(),
-                                ),
-                        })
-                        .ok();
+                    self.send_buffer_ordered_message(BufferOrderedMessage::LanguageServerUpdate {
+                        language_server_id,
+                        message:
+                            proto::update_language_server::Variant::DiskBasedDiagnosticsUpdated(
+                                Default::default(),
+                            ),
+                    });
                 } else {
                     self.on_lsp_work_end(language_server_id, token.clone(), cx);
-                    self.buffer_ordered_messages_tx
-                        .unbounded_send(BufferOrderedMessage::LanguageServerUpdate {
-                            language_server_id,
-                            message: proto::update_language_server::Variant::WorkEnd(
-                                proto::LspWorkEnd { token },
-                            ),
-                        })
-                        .ok();
+                    self.send_buffer_ordered_message(BufferOrderedMessage::LanguageServerUpdate {
+                        language_server_id,
+                        message: proto::update_language_server::Variant::WorkEnd(
+                            proto::LspWorkEnd { token },
+                        ),
+                    });
                 }
             }
         }
@@ -4178,7 +4797,7 @@ impl Project {
                     &buffers_with_paths_and_servers
                 {
                     let settings = buffer.update(&mut cx, |buffer, cx| {
-                        language_settings(buffer.language(), buffer.file(), cx).clone()
+                        language_settings(buffer.language(), buffer.file(), cx)
                     })?;
 
                     let remove_trailing_whitespace = settings.remove_trailing_whitespace_on_save;
@@ -4322,6 +4941,7 @@ impl Project {
                         | (_, FormatOnSave::External { command, arguments }) => {
                             if let Some(buffer_abs_path) = buffer_abs_path {
                                 format_operation = Self::format_via_external_command(
+                                    &project,
                                     buffer,
                                     buffer_abs_path,
                                     command,
@@ -4453,6 +5073,7 @@ impl Project {
         tab_size: NonZeroU32,
         cx: &mut AsyncAppContext,
     ) -> Result<Vec<(Range<Anchor>, String)>> {
+        this.update(cx, |this, cx| this.flush_lsp_changes(cx))?;
         let uri = lsp::Url::from_file_path(abs_path)
             .map_err(|_| anyhow!("failed to convert abs path to uri"))?;
         let text_document = lsp::TextDocumentIdentifier::new(uri);
@@ -4496,6 +5117,7 @@ impl Project {
     }
 
     async fn format_via_external_command(
+        this: &WeakModel<Self>,
         buffer: &Model<Buffer>,
         buffer_abs_path: &Path,
         command: &str,
@@ -4513,31 +5135,23 @@ impl Project {
         })?;
 
         if let Some(working_dir_path) = working_dir_path {
-            let mut child =
-                smol::process::Command::new(command)
-                    .args(arguments.iter().map(|arg| {
-                        arg.replace("{buffer_path}", &buffer_abs_path.to_string_lossy())
-                    }))
-                    .current_dir(&working_dir_path)
-                    .stdin(smol::process::Stdio::piped())
-                    .stdout(smol::process::Stdio::piped())
-                    .stderr(smol::process::Stdio::piped())
-                    .spawn()?;
-            let stdin = child
-                .stdin
-                .as_mut()
-                .ok_or_else(|| anyhow!("failed to acquire stdin"))?;
-            let text = buffer.update(cx, |buffer, _| buffer.as_rope().clone())?;
-            for chunk in text.chunks() {
-                stdin.write_all(chunk.as_bytes()).await?;
-            }
-            stdin.flush().await?;
-
-            let output = child.output().await?;
-            if !output.status.success() {
+            let process_runner = this.update(cx, |this, _| this.process_runner.clone())?;
+            let text = buffer.update(cx, |buffer, _| buffer.text())?;
+            let output = process_runner
+                .run(ProcessRequest {
+                    program: command.to_string(),
+                    args: arguments
+                        .iter()
+                        .map(|arg| arg.replace("{buffer_path}", &buffer_abs_path.to_string_lossy()))
+                        .collect(),
+                    working_directory: working_dir_path,
+                    stdin: text,
+                })
+                .await?;
+            if !output.success() {
                 return Err(anyhow!(
                     "command failed with exit code {:?}:\nstdout: {}\nstderr: {}",
-                    output.status.code(),
+                    output.exit_code,
                     String::from_utf8_lossy(&output.stdout),
                     String::from_utf8_lossy(&output.stderr),
                 ));
@@ -5521,24 +6135,50 @@ impl Project {
         }
 
         let mut project_transaction = ProjectTransaction::default();
-        for operation in operations {
-            match operation {
-                lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Create(op)) => {
-                    let abs_path = op
-                        .uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-
-                    if let Some(parent_path) = abs_path.parent() {
-                        fs.create_dir(parent_path).await?;
+        // Apply the edit as a whole, reverting the buffer edits that were already made if any
+        // of its operations fail.
+        let result = async {
+            for operation in operations {
+                match operation {
+                    lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Create(op)) => {
+                        let abs_path = op
+                            .uri
+                            .to_file_path()
+                            .map_err(|_| anyhow!("can't convert URI to path"))?;
+
+                        if let Some(parent_path) = abs_path.parent() {
+                            fs.create_dir(parent_path).await?;
+                        }
+                        if abs_path.ends_with("/") {
+                            fs.create_dir(&abs_path).await?;
+                        } else {
+                            fs.create_file(
+                                &abs_path,
+                                op.options
+                                    .map(|options| fs::CreateOptions {
+                                        overwrite: options.overwrite.unwrap_or(false),
+                                        ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
+                                    })
+                                    .unwrap_or_default(),
+                            )
+                            .await?;
+                        }
                     }
-                    if abs_path.ends_with("/") {
-                        fs.create_dir(&abs_path).await?;
-                    } else {
-                        fs.create_file(
-                            &abs_path,
+
+                    lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(op)) => {
+                        let source_abs_path = op
+                            .old_uri
+                            .to_file_path()
+                            .map_err(|_| anyhow!("can't convert URI to path"))?;
+                        let target_abs_path = op
+                            .new_uri
+                            .to_file_path()
+                            .map_err(|_| anyhow!("can't convert URI to path"))?;
+                        fs.rename(
+                            &source_abs_path,
+                            &target_abs_path,
                             op.options
-                                .map(|options| fs::CreateOptions {
+                                .map(|options| fs::RenameOptions {
                                     overwrite: options.overwrite.unwrap_or(false),
                                     ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
                                 })
@@ -5546,101 +6186,86 @@ impl Project {
                         )
                         .await?;
                     }
-                }
 
-                lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(op)) => {
-                    let source_abs_path = op
-                        .old_uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-                    let target_abs_path = op
-                        .new_uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-                    fs.rename(
-                        &source_abs_path,
-                        &target_abs_path,
-                        op.options
-                            .map(|options| fs::RenameOptions {
-                                overwrite: options.overwrite.unwrap_or(false),
-                                ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
+                    lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Delete(op)) => {
+                        let abs_path = op
+                            .uri
+                            .to_file_path()
+                            .map_err(|_| anyhow!("can't convert URI to path"))?;
+                        let options = op
+                            .options
+                            .map(|options| fs::RemoveOptions {
+                                recursive: options.recursive.unwrap_or(false),
+                                ignore_if_not_exists: options.ignore_if_not_exists.unwrap_or(false),
                             })
-                            .unwrap_or_default(),
-                    )
-                    .await?;
-                }
-
-                lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Delete(op)) => {
-                    let abs_path = op
-                        .uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-                    let options = op
-                        .options
-                        .map(|options| fs::RemoveOptions {
-                            recursive: options.recursive.unwrap_or(false),
-                            ignore_if_not_exists: options.ignore_if_not_exists.unwrap_or(false),
-                        })
-                        .unwrap_or_default();
-                    if abs_path.ends_with("/") {
-                        fs.remove_dir(&abs_path, options).await?;
-                    } else {
-                        fs.remove_file(&abs_path, options).await?;
+                            .unwrap_or_default();
+                        if abs_path.ends_with("/") {
+                            fs.remove_dir(&abs_path, options).await?;
+                        } else {
+                            fs.remove_file(&abs_path, options).await?;
+                        }
                     }
-                }
 
-                lsp::DocumentChangeOperation::Edit(op) => {
-                    let buffer_to_edit = this
-                        .update(cx, |this, cx| {
-                            this.open_local_buffer_via_lsp(
-                                op.text_document.uri,
-                                language_server.server_id(),
-                                lsp_adapter.name.clone(),
-                                cx,
-                            )
-                        })?
-                        .await?;
-
-                    let edits = this
-                        .update(cx, |this, cx| {
-                            let edits = op.edits.into_iter().map(|edit| match edit {
-                                OneOf::Left(edit) => edit,
-                                OneOf::Right(edit) => edit.text_edit,
-                            });
-                            this.edits_from_lsp(
-                                &buffer_to_edit,
-                                edits,
-                                language_server.server_id(),
-                                op.text_document.version,
-                                cx,
-                            )
-                        })?
-                        .await?;
+                    lsp::DocumentChangeOperation::Edit(op) => {
+                        let buffer_to_edit = this
+                            .update(cx, |this, cx| {
+                                this.open_local_buffer_via_lsp(
+                                    op.text_document.uri,
+                                    language_server.server_id(),
+                                    lsp_adapter.name.clone(),
+                                    cx,
+                                )
+                            })?
+                            .await?;
+
+                        let edits = this
+                            .update(cx, |this, cx| {
+                                let edits = op.edits.into_iter().map(|edit| match edit {
+                                    OneOf::Left(edit) => edit,
+                                    OneOf::Right(edit) => edit.text_edit,
+                                });
+                                this.edits_from_lsp(
+                                    &buffer_to_edit,
+                                    edits,
+                                    language_server.server_id(),
+                                    op.text_document.version,
+                                    cx,
+                                )
+                            })?
+                            .await?;
 
-                    let transaction = buffer_to_edit.update(cx, |buffer, cx| {
-                        buffer.finalize_last_transaction();
-                        buffer.start_transaction();
-                        for (range, text) in edits {
-                            buffer.edit([(range, text)], None, cx);
-                        }
-                        let transaction = if buffer.end_transaction(cx).is_some() {
-                            let transaction = buffer.finalize_last_transaction().unwrap().clone();
-                            if !push_to_history {
-                                buffer.forget_transaction(transaction.id);
+                        let transaction = buffer_to_edit.update(cx, |buffer, cx| {
+                            buffer.finalize_last_transaction();
+                            buffer.start_transaction();
+                            for (range, text) in edits {
+                                buffer.edit([(range, text)], None, cx);
                             }
-                            Some(transaction)
-                        } else {
-                            None
-                        };
-
-                        transaction
-                    })?;
-                    if let Some(transaction) = transaction {
-                        project_transaction.0.insert(buffer_to_edit, transaction);
+                            if buffer.end_transaction(cx).is_some() {
+                                Some(buffer.finalize_last_transaction().unwrap().clone())
+                            } else {
+                                None
+                            }
+                        })?;
+                        if let Some(transaction) = transaction {
+                            project_transaction.0.insert(buffer_to_edit, transaction);
+                        }
                     }
                 }
             }
+            anyhow::Ok(())
         }
+        .await;
+
+        for (buffer, transaction) in &project_transaction.0 {
+            buffer.update(cx, |buffer, cx| {
+                if result.is_err() {
+                    buffer.undo_transaction(transaction.id, cx);
+                } else if !push_to_history {
+                    buffer.forget_transaction(transaction.id);
+                }
+            })?;
+        }
+        result?;
 
         Ok(project_transaction)
     }
@@ -5994,7 +6619,7 @@ impl Project {
             })
             .collect();
         cx.background_executor()
-            .spawn(Self::background_search(
+            .spawn(self.background_work.run(Self::background_search(
                 unnamed_files,
                 opened_buffers,
                 cx.background_executor().clone(),
@@ -6004,14 +6629,14 @@ impl Project {
                 path_count,
                 snapshots,
                 matching_paths_tx,
-            ))
+            )))
             .detach();
 
-        let (buffers, buffers_rx) = Self::sort_candidates_and_open_buffers(matching_paths_rx, cx);
+        let (buffers, buffers_rx) = self.sort_candidates_and_open_buffers(matching_paths_rx, cx);
         let background = cx.background_executor().clone();
         let (result_tx, result_rx) = smol::channel::bounded(1024);
         cx.background_executor()
-            .spawn(async move {
+            .spawn(self.background_work.run(async move {
                 let Ok(buffers) = buffers.await else {
                     return;
                 };
@@ -6109,7 +6734,7 @@ impl Project {
                         });
                     })
                     .await;
-            })
+            }))
             .detach();
         result_rx
     }
@@ -6236,6 +6861,14 @@ impl Project {
                                 while let Some(ignored_abs_path) =
                                     ignored_paths_to_process.pop_front()
                                 {
+                                    let Ok(ignored_path) =
+                                        ignored_abs_path.strip_prefix(snapshot.abs_path())
+                                    else {
+                                        continue;
+                                    };
+                                    if snapshot.is_path_excluded(ignored_path.to_path_buf()) {
+                                        continue;
+                                    }
                                     if let Some(fs_metadata) = fs
                                         .metadata(&ignored_abs_path)
                                         .await
@@ -6263,11 +6896,7 @@ impl Project {
                                                 }
                                             }
                                         } else if !fs_metadata.is_symlink {
-                                            if !query.file_matches(Some(&ignored_abs_path))
-                                                || snapshot.is_path_excluded(
-                                                    ignored_entry.path.to_path_buf(),
-                                                )
-                                            {
+                                            if !query.file_matches(Some(&ignored_abs_path)) {
                                                 continue;
                                             }
                                             let matches = if let Some(file) = fs
@@ -6287,13 +6916,7 @@ impl Project {
                                             if matches {
                                                 let project_path = SearchMatchCandidate::Path {
                                                     worktree_id: snapshot.id(),
-                                                    path: Arc::from(
-                                                        ignored_abs_path
-                                                            .strip_prefix(snapshot.abs_path())
-                                                            .expect(
-                                                                "scanning worktree-related files",
-                                                            ),
-                                                    ),
+                                                    path: Arc::from(ignored_path),
                                                     is_ignored: true,
                                                 };
                                                 if matching_paths_tx
@@ -6330,8 +6953,18 @@ impl Project {
         if self.is_local() {
             let language_server = match server {
                 LanguageServerToQuery::Primary => {
-                    match self.primary_language_server_for_buffer(buffer, cx) {
-                        Some((_, server)) => Some(Arc::clone(server)),
+                    // Prefer the first server, in the order configured in the settings,
+                    // that is capable of handling this request.
+                    let servers = self
+                        .language_servers_for_buffer(buffer, cx)
+                        .map(|(_, server)| server)
+                        .collect::<Vec<_>>();
+                    match servers
+                        .iter()
+                        .find(|server| request.check_capabilities(server.capabilities()))
+                        .or(servers.first())
+                    {
+                        Some(server) => Some(Arc::clone(server)),
                         None => return Task::ready(Ok(Default::default())),
                     }
                 }
@@ -6342,11 +6975,13 @@ impl Project {
             let file = File::from_dyn(buffer.file()).and_then(File::as_local);
             if let (Some(file), Some(language_server)) = (file, language_server) {
                 let lsp_params = request.to_lsp(&file.abs_path(cx), buffer, &language_server, cx);
-                return cx.spawn(move |this, cx| async move {
+                return cx.spawn(move |this, mut cx| async move {
                     if !request.check_capabilities(language_server.capabilities()) {
                         return Ok(Default::default());
                     }
 
+                    this.update(&mut cx, |this, cx| this.flush_lsp_changes(cx))?;
+
                     let result = language_server.request::<R::LspRequest>(lsp_params).await;
                     let response = match result {
                         Ok(response) => response,
@@ -6405,6 +7040,7 @@ impl Project {
     }
 
     fn sort_candidates_and_open_buffers(
+        &self,
         mut matching_paths_rx: Receiver<SearchMatchCandidate>,
         cx: &mut ModelContext<Self>,
     ) -> (
@@ -6416,6 +7052,7 @@ impl Project {
     ) {
         let (buffers_tx, buffers_rx) = smol::channel::bounded(1024);
         let (sorted_buffers_tx, sorted_buffers_rx) = futures::channel::oneshot::channel();
+        let background_work = self.background_work.clone();
         cx.spawn(move |this, cx| async move {
             let mut buffers = Vec::new();
             let mut ignored_buffers = Vec::new();
@@ -6443,29 +7080,32 @@ impl Project {
                 }
                 let this = this.clone();
                 let buffers_tx = buffers_tx.clone();
-                cx.spawn(move |mut cx| async move {
-                    let buffer = match candidate {
-                        SearchMatchCandidate::OpenBuffer { buffer, .. } => Some(buffer),
-                        SearchMatchCandidate::Path {
-                            worktree_id, path, ..
-                        } => this
-                            .update(&mut cx, |this, cx| {
-                                this.open_buffer((worktree_id, path), cx)
-                            })?
-                            .await
-                            .log_err(),
-                    };
-                    if let Some(buffer) = buffer {
-                        let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
-                        buffers_tx
-                            .send((Some((buffer, snapshot)), index))
-                            .await
-                            .log_err();
-                    } else {
-                        buffers_tx.send((None, index)).await.log_err();
-                    }
+                let background_work = background_work.clone();
+                cx.spawn(move |mut cx| {
+                    background_work.run(async move {
+                        let buffer = match candidate {
+                            SearchMatchCandidate::OpenBuffer { buffer, .. } => Some(buffer),
+                            SearchMatchCandidate::Path {
+                                worktree_id, path, ..
+                            } => this
+                                .update(&mut cx, |this, cx| {
+                                    this.open_buffer((worktree_id, path), cx)
+                                })?
+                                .await
+                                .log_err(),
+                        };
+                        if let Some(buffer) = buffer {
+                            let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+                            buffers_tx
+                                .send((Some((buffer, snapshot)), index))
+                                .await
+                                .log_err();
+                        } else {
+                            buffers_tx.send((None, index)).await.log_err();
+                        }
 
-                    Ok::<_, anyhow::Error>(())
+                        Ok::<_, anyhow::Error>(())
+                    })
                 })
                 .detach();
             }
@@ -6614,6 +7254,10 @@ impl Project {
         })
         .detach();
 
+        let mut pinned_paths = self.pinned_paths.clone();
+        pinned_paths.retain(|pinned| pinned.worktree_id != id_to_remove);
+        self.set_pinned_paths(pinned_paths, cx);
+
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
                 let id = worktree.read(cx).id();
@@ -6633,12 +7277,14 @@ impl Project {
     fn add_worktree(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
         cx.observe(worktree, |_, _, cx| cx.notify()).detach();
         if worktree.read(cx).is_local() {
+            self.load_editorconfigs_above_worktree(worktree, cx);
             cx.subscribe(worktree, |this, worktree, event, cx| match event {
                 worktree::Event::UpdatedEntries(changes) => {
                     this.update_local_worktree_buffers(&worktree, changes, cx);
                     this.update_local_worktree_language_servers(&worktree, changes, cx);
                     this.update_local_worktree_settings(&worktree, changes, cx);
                     this.update_prettier_settings(&worktree, changes, cx);
+                    this.prune_pinned_paths(worktree.read(cx).id(), changes, cx);
                     cx.emit(Event::WorktreeUpdatedEntries(
                         worktree.read(cx).id(),
                         changes.clone(),
@@ -6960,9 +7606,73 @@ impl Project {
                 }
             }
 
-            anyhow::Ok(())
+            anyhow::Ok(())
+        })
+        .detach();
+    }
+
+    /// Loads the `.editorconfig` files in the directories containing a local
+    /// worktree, up to the first one that is marked as the root.
+    fn load_editorconfigs_above_worktree(
+        &self,
+        worktree: &Model<Worktree>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let worktree_id = worktree.entity_id();
+        let remote_worktree_id = worktree.read(cx).id();
+        let abs_path = worktree.read(cx).abs_path().clone();
+        let fs = self.fs.clone();
+        let client = self.client.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let mut editorconfigs = Vec::new();
+            for directory in abs_path.ancestors().skip(1) {
+                let abs_editorconfig_path = directory.join(EDITORCONFIG_FILE_NAME);
+                if !fs.is_file(&abs_editorconfig_path).await {
+                    continue;
+                }
+                let Some(content) = fs.load(&abs_editorconfig_path).await.log_err() else {
+                    continue;
+                };
+                let is_root = Editorconfig::parse(&content).is_root();
+                let Ok(root_path) = abs_path.strip_prefix(directory) else {
+                    continue;
+                };
+                editorconfigs.push((Arc::<Path>::from(Path::new("..").join(root_path)), content));
+                if is_root {
+                    break;
+                }
+            }
+            if editorconfigs.is_empty() {
+                return Ok(());
+            }
+
+            this.update(&mut cx, |this, cx| {
+                let project_id = this.remote_id();
+                cx.update_global::<SettingsStore, _>(|store, _| {
+                    for (directory, content) in editorconfigs {
+                        if let Some(project_id) = project_id {
+                            client
+                                .send(proto::UpdateWorktreeSettings {
+                                    project_id,
+                                    worktree_id: remote_worktree_id.to_proto(),
+                                    path: directory
+                                        .join(EDITORCONFIG_FILE_NAME)
+                                        .to_string_lossy()
+                                        .into_owned(),
+                                    content: Some(content.clone()),
+                                })
+                                .log_err();
+                        }
+                        store.set_editorconfig(
+                            worktree_id.as_u64() as usize,
+                            directory,
+                            Some(&content),
+                        );
+                    }
+                });
+            })
         })
-        .detach();
+        .detach_and_log_err(cx);
     }
 
     fn update_local_worktree_settings(
@@ -6977,8 +7687,27 @@ impl Project {
         let remote_worktree_id = worktree.id();
 
         let mut settings_contents = Vec::new();
+        let mut editorconfig_contents = Vec::new();
         for (path, _, change) in changes.iter() {
-            if path.ends_with(&*LOCAL_SETTINGS_RELATIVE_PATH) {
+            if path.file_name() == Some(OsStr::new(EDITORCONFIG_FILE_NAME)) {
+                let Some(directory) = path.parent() else {
+                    continue;
+                };
+                let directory = Arc::from(directory);
+                let fs = self.fs.clone();
+                let removed = *change == PathChange::Removed;
+                let abs_path = worktree.absolutize(path);
+                editorconfig_contents.push(async move {
+                    (
+                        directory,
+                        if removed {
+                            None
+                        } else {
+                            Some(async move { fs.load(&abs_path?).await }.await)
+                        },
+                    )
+                });
+            } else if path.ends_with(&*LOCAL_SETTINGS_RELATIVE_PATH) {
                 let settings_dir = Arc::from(
                     path.ancestors()
                         .nth(LOCAL_SETTINGS_RELATIVE_PATH.components().count())
@@ -7000,7 +7729,7 @@ impl Project {
             }
         }
 
-        if settings_contents.is_empty() {
+        if settings_contents.is_empty() && editorconfig_contents.is_empty() {
             return;
         }
 
@@ -7008,8 +7737,31 @@ impl Project {
         cx.spawn(move |_, cx| async move {
             let settings_contents: Vec<(Arc<Path>, _)> =
                 futures::future::join_all(settings_contents).await;
+            let editorconfig_contents: Vec<(Arc<Path>, _)> =
+                futures::future::join_all(editorconfig_contents).await;
             cx.update(|cx| {
                 cx.update_global::<SettingsStore, _>(|store, cx| {
+                    for (directory, file_content) in editorconfig_contents {
+                        let file_content = file_content.and_then(|content| content.log_err());
+                        store.set_editorconfig(
+                            worktree_id.as_u64() as usize,
+                            directory.clone(),
+                            file_content.as_deref(),
+                        );
+                        if let Some(remote_id) = project_id {
+                            client
+                                .send(proto::UpdateWorktreeSettings {
+                                    project_id: remote_id,
+                                    worktree_id: remote_worktree_id.to_proto(),
+                                    path: directory
+                                        .join(EDITORCONFIG_FILE_NAME)
+                                        .to_string_lossy()
+                                        .into_owned(),
+                                    content: file_content,
+                                })
+                                .log_err();
+                        }
+                    }
                     for (directory, file_content) in settings_contents {
                         let file_content = file_content.and_then(|content| content.log_err());
                         store
@@ -7038,6 +7790,125 @@ impl Project {
         .detach();
     }
 
+    /// The entries of the project's shared clipboard, most recent first.
+    pub fn shared_clipboard(&self) -> impl Iterator<Item = &SharedClipboardEntry> {
+        self.shared_clipboard.iter()
+    }
+
+    /// Publishes text to the shared clipboard of every collaborator in this
+    /// shared project. Nothing is shared unless this is called explicitly.
+    pub fn share_clipboard_entry(
+        &mut self,
+        text: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let project_id = self
+            .remote_id()
+            .ok_or_else(|| anyhow!("project is not shared"))?;
+        if text.len() > rpc::MAX_SHARED_CLIPBOARD_ENTRY_LEN {
+            return Err(anyhow!("text is too long to share"));
+        }
+        self.client.send(proto::ShareClipboardEntry {
+            project_id,
+            text: text.clone(),
+        })?;
+        let author_id = self.client.user_id();
+        self.push_shared_clipboard_entry(SharedClipboardEntry { author_id, text }, cx);
+        Ok(())
+    }
+
+    fn push_shared_clipboard_entry(
+        &mut self,
+        entry: SharedClipboardEntry,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.shared_clipboard
+            .retain(|existing| existing.text != entry.text);
+        self.shared_clipboard.push_front(entry);
+        self.shared_clipboard.truncate(MAX_SHARED_CLIPBOARD_ENTRIES);
+        cx.emit(Event::SharedClipboardChanged);
+    }
+
+    /// The files pinned to the top of the project panel, in the order they were pinned.
+    pub fn pinned_paths(&self) -> &[ProjectPath] {
+        &self.pinned_paths
+    }
+
+    /// The pinned files to show: this user's own pins, followed by those of the
+    /// host that aren't among them.
+    pub fn all_pinned_paths(&self) -> impl Iterator<Item = &ProjectPath> {
+        self.pinned_paths.iter().chain(
+            self.host_pinned_paths
+                .iter()
+                .filter(|path| !self.pinned_paths.contains(path)),
+        )
+    }
+
+    pub fn is_pinned(&self, path: &ProjectPath) -> bool {
+        self.pinned_paths.contains(path)
+    }
+
+    /// Pins the given path, or unpins it if it's already pinned.
+    pub fn toggle_pinned_path(&mut self, path: ProjectPath, cx: &mut ModelContext<Self>) {
+        let mut pinned_paths = self.pinned_paths.clone();
+        if let Some(ix) = pinned_paths.iter().position(|pinned| *pinned == path) {
+            pinned_paths.remove(ix);
+        } else {
+            pinned_paths.push(path);
+        }
+        self.set_pinned_paths(pinned_paths, cx);
+    }
+
+    /// Replaces the pinned paths. When `share_pinned_files` is enabled, a host's
+    /// pinned paths are sent to the guests of its shared project.
+    pub fn set_pinned_paths(&mut self, paths: Vec<ProjectPath>, cx: &mut ModelContext<Self>) {
+        if self.pinned_paths == paths {
+            return;
+        }
+        self.pinned_paths = paths;
+        self.send_pinned_paths(cx);
+        cx.emit(Event::PinnedPathsChanged);
+    }
+
+    /// Unpins the files that were removed from a worktree.
+    fn prune_pinned_paths(
+        &mut self,
+        worktree_id: WorktreeId,
+        changes: &[(Arc<Path>, ProjectEntryId, PathChange)],
+        cx: &mut ModelContext<Self>,
+    ) {
+        let mut pinned_paths = self.pinned_paths.clone();
+        pinned_paths.retain(|pinned| {
+            pinned.worktree_id != worktree_id
+                || !changes
+                    .iter()
+                    .any(|(path, _, change)| *change == PathChange::Removed && *path == pinned.path)
+        });
+        self.set_pinned_paths(pinned_paths, cx);
+    }
+
+    fn send_pinned_paths(&self, cx: &AppContext) {
+        if !self.is_shared() || !ProjectSettings::get_global(cx).share_pinned_files {
+            return;
+        }
+        let Some(project_id) = self.remote_id() else {
+            return;
+        };
+        self.client
+            .send(proto::UpdatePinnedPaths {
+                project_id,
+                paths: self
+                    .pinned_paths
+                    .iter()
+                    .map(|path| proto::PinnedPath {
+                        worktree_id: path.worktree_id.to_proto(),
+                        path: path.path.to_string_lossy().into(),
+                    })
+                    .collect(),
+            })
+            .log_err();
+    }
+
     pub fn set_active_path(&mut self, entry: Option<ProjectPath>, cx: &mut ModelContext<Self>) {
         let new_active_entry = entry.and_then(|project_path| {
             let worktree = self.worktree_for_id(project_path.worktree_id, cx)?;
@@ -7046,10 +7917,45 @@ impl Project {
         });
         if new_active_entry != self.active_entry {
             self.active_entry = new_active_entry;
+            self.send_active_path(cx);
             cx.emit(Event::ActiveEntryChanged(new_active_entry));
         }
     }
 
+    /// Tells the other participants of a shared project which file is active here, so
+    /// they can show it in their project panels and tab bars.
+    fn send_active_path(&self, cx: &AppContext) {
+        if (!self.is_shared() && !self.is_remote()) || self.is_disconnected() {
+            return;
+        }
+        let Some(project_id) = self.remote_id() else {
+            return;
+        };
+        let active_path = self
+            .active_entry
+            .and_then(|entry_id| self.path_for_entry(entry_id, cx));
+        self.client
+            .send(proto::UpdateActivePath {
+                project_id,
+                worktree_id: active_path.as_ref().map(|path| path.worktree_id.to_proto()),
+                path: active_path
+                    .map(|path| path.path.to_string_lossy().into())
+                    .unwrap_or_default(),
+            })
+            .log_err();
+    }
+
+    /// The collaborators who currently have the given file active.
+    pub fn collaborators_at_path<'a>(
+        &'a self,
+        path: &'a ProjectPath,
+    ) -> impl Iterator<Item = &'a Collaborator> + 'a {
+        self.collaborator_active_paths
+            .iter()
+            .filter(move |(_, active_path)| *active_path == path)
+            .filter_map(|(peer_id, _)| self.collaborators.get(peer_id))
+    }
+
     pub fn language_servers_running_disk_based_diagnostics(
         &self,
     ) -> impl Iterator<Item = LanguageServerId> + '_ {
@@ -7185,12 +8091,84 @@ impl Project {
             cx.emit(Event::CollaboratorJoined(collaborator.peer_id));
             this.collaborators
                 .insert(collaborator.peer_id, collaborator);
+            this.send_pinned_paths(cx);
+            this.send_active_path(cx);
             cx.notify();
         })?;
 
         Ok(())
     }
 
+    async fn handle_update_pinned_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdatePinnedPaths>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.host_pinned_paths = envelope
+                .payload
+                .paths
+                .into_iter()
+                .map(|path| ProjectPath {
+                    worktree_id: WorktreeId::from_proto(path.worktree_id),
+                    path: PathBuf::from(path.path).into(),
+                })
+                .collect();
+            cx.emit(Event::PinnedPathsChanged);
+        })
+    }
+
+    async fn handle_update_active_path(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateActivePath>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let sender_id = envelope.original_sender_id()?;
+        this.update(&mut cx, |this, cx| {
+            let active_path = envelope.payload.worktree_id.map(|worktree_id| ProjectPath {
+                worktree_id: WorktreeId::from_proto(worktree_id),
+                path: PathBuf::from(envelope.payload.path).into(),
+            });
+            let changed = if let Some(active_path) = active_path {
+                this.collaborator_active_paths
+                    .insert(sender_id, active_path.clone())
+                    != Some(active_path)
+            } else {
+                this.collaborator_active_paths.remove(&sender_id).is_some()
+            };
+            if changed {
+                cx.emit(Event::CollaboratorActivePathsChanged);
+            }
+        })
+    }
+
+    async fn handle_share_clipboard_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ShareClipboardEntry>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let sender_id = envelope.original_sender_id()?;
+        if envelope.payload.text.len() > rpc::MAX_SHARED_CLIPBOARD_ENTRY_LEN {
+            return Err(anyhow!("shared clipboard entry is too long"));
+        }
+        this.update(&mut cx, |this, cx| {
+            let author_id = this
+                .collaborators
+                .get(&sender_id)
+                .map(|collaborator| collaborator.user_id);
+            this.push_shared_clipboard_entry(
+                SharedClipboardEntry {
+                    author_id,
+                    text: envelope.payload.text,
+                },
+                cx,
+            );
+        })
+    }
+
     async fn handle_update_project_collaborator(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateProjectCollaborator>,
@@ -7212,6 +8190,10 @@ impl Project {
                 .ok_or_else(|| anyhow!("received UpdateProjectCollaborator for unknown peer"))?;
             let is_host = collaborator.replica_id == 0;
             this.collaborators.insert(new_peer_id, collaborator);
+            if let Some(active_path) = this.collaborator_active_paths.remove(&old_peer_id) {
+                this.collaborator_active_paths
+                    .insert(new_peer_id, active_path);
+            }
 
             let buffers = this.shared_buffers.remove(&old_peer_id);
             log::info!(
@@ -7227,9 +8209,7 @@ impl Project {
             if is_host {
                 this.opened_buffers
                     .retain(|_, buffer| !matches!(buffer, OpenBuffer::Operations(_)));
-                this.buffer_ordered_messages_tx
-                    .unbounded_send(BufferOrderedMessage::Resync)
-                    .unwrap();
+                this.send_buffer_ordered_message(BufferOrderedMessage::Resync);
             }
 
             cx.emit(Event::CollaboratorUpdated {
@@ -7263,6 +8243,9 @@ impl Project {
                 }
             }
             this.shared_buffers.remove(&peer_id);
+            if this.collaborator_active_paths.remove(&peer_id).is_some() {
+                cx.emit(Event::CollaboratorActivePathsChanged);
+            }
 
             cx.emit(Event::CollaboratorLeft(peer_id));
             cx.notify();
@@ -7285,6 +8268,60 @@ impl Project {
         })?
     }
 
+    async fn handle_set_project_read_only(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SetProjectReadOnly>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.update_remote_capability(cx, |_, read_only_for_guests| {
+                *read_only_for_guests = envelope.payload.read_only
+            });
+        })
+    }
+
+    async fn handle_request_join_project(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RequestJoinProject>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::RespondToJoinRequest> {
+        let request_id = envelope.payload.request_id;
+        let requester_id = envelope.payload.requester_id;
+        let response = this.update(&mut cx, |this, cx| {
+            if !ProjectSettings::get_global(cx).approve_join_requests {
+                return None;
+            }
+            let (tx, rx) = oneshot::channel();
+            this.pending_join_requests.insert(request_id, tx);
+            cx.emit(Event::JoinRequested {
+                request_id,
+                requester_id,
+            });
+            Some(rx)
+        })?;
+        let allow = match response {
+            Some(response) => response.await.unwrap_or(false),
+            None => true,
+        };
+        Ok(proto::RespondToJoinRequest { allow })
+    }
+
+    async fn handle_cancel_join_request(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CancelJoinRequest>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let request_id = envelope.payload.request_id;
+        this.update(&mut cx, |this, cx| {
+            if this.pending_join_requests.remove(&request_id).is_some() {
+                cx.emit(Event::JoinRequestCancelled { request_id });
+            }
+        })
+    }
+
     async fn handle_update_worktree(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateWorktree>,
@@ -7312,15 +8349,28 @@ impl Project {
         this.update(&mut cx, |this, cx| {
             let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
             if let Some(worktree) = this.worktree_for_id(worktree_id, cx) {
+                let path = PathBuf::from(&envelope.payload.path);
                 cx.update_global::<SettingsStore, _>(|store, cx| {
-                    store
-                        .set_local_settings(
-                            worktree.entity_id().as_u64() as usize,
-                            PathBuf::from(&envelope.payload.path).into(),
-                            envelope.payload.content.as_ref().map(String::as_str),
-                            cx,
-                        )
-                        .log_err();
+                    // The host sends `.editorconfig` files by their own path,
+                    // and settings files by the directory they apply to.
+                    if path.file_name() == Some(OsStr::new(EDITORCONFIG_FILE_NAME)) {
+                        if let Some(directory) = path.parent() {
+                            store.set_editorconfig(
+                                worktree.entity_id().as_u64() as usize,
+                                directory.into(),
+                                envelope.payload.content.as_deref(),
+                            );
+                        }
+                    } else {
+                        store
+                            .set_local_settings(
+                                worktree.entity_id().as_u64() as usize,
+                                path.into(),
+                                envelope.payload.content.as_ref().map(String::as_str),
+                                cx,
+                            )
+                            .log_err();
+                    }
                 });
             }
             Ok(())
@@ -7364,13 +8414,10 @@ impl Project {
                 .ok_or_else(|| anyhow!("worktree not found"))
         })??;
         let worktree_scan_id = worktree.update(&mut cx, |worktree, _| worktree.scan_id())?;
-        let entry = worktree
-            .update(&mut cx, |worktree, cx| {
+        let entry = this
+            .update(&mut cx, |this, cx| {
                 let new_path = PathBuf::from(envelope.payload.new_path);
-                worktree
-                    .as_local_mut()
-                    .unwrap()
-                    .rename_entry(entry_id, new_path, cx)
+                this.rename_entry(entry_id, new_path, cx)
             })?
             .await?;
         Ok(proto::ProjectEntryResponse {
@@ -7413,8 +8460,11 @@ impl Project {
         mut cx: AsyncAppContext,
     ) -> Result<proto::ProjectEntryResponse> {
         let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-
-        this.update(&mut cx, |_, cx| cx.emit(Event::DeletedEntry(entry_id)))?;
+        // Whether the file is trashed is the host's choice, not the guest's.
+        let trash = this.update(&mut cx, |this, cx| {
+            cx.emit(Event::DeletedEntry(entry_id));
+            this.deletes_to_trash(cx).unwrap_or(false)
+        })?;
 
         let worktree = this.update(&mut cx, |this, cx| {
             this.worktree_for_entry(entry_id, cx)
@@ -7426,7 +8476,7 @@ impl Project {
                 worktree
                     .as_local_mut()
                     .unwrap()
-                    .delete_entry(entry_id, cx)
+                    .delete_entry(entry_id, trash, cx)
                     .ok_or_else(|| anyhow!("invalid entry"))
             })??
             .await?;
@@ -7738,6 +8788,7 @@ impl Project {
         _: Arc<Client>,
         mut cx: AsyncAppContext,
     ) -> Result<proto::BufferSaved> {
+        let sender_id = envelope.original_sender_id().ok();
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let (project_id, buffer) = this.update(&mut cx, |this, _cx| {
             let project_id = this.remote_id().ok_or_else(|| anyhow!("not connected"))?;
@@ -7748,21 +8799,62 @@ impl Project {
                 .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))?;
             anyhow::Ok((project_id, buffer))
         })??;
+        let requested_version = deserialize_version(&envelope.payload.version);
         buffer
             .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+                buffer.wait_for_version(requested_version.clone())
             })?
             .await?;
         let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
 
-        this.update(&mut cx, |this, cx| this.save_buffer(buffer.clone(), cx))?
-            .await?;
+        // A guest's save never overwrites changes they haven't seen.
+        let conflict = buffer.update(&mut cx, |buffer, _| {
+            if buffer.has_conflict() {
+                Some(proto::SaveConflict::FileChangedOnDisk)
+            } else if buffer.is_dirty()
+                && buffer.version().get(buffer.replica_id())
+                    > requested_version.get(buffer.replica_id())
+            {
+                Some(proto::SaveConflict::UnsavedHostChanges)
+            } else {
+                None
+            }
+        })?;
+        if let Some(conflict) = conflict {
+            this.update(&mut cx, |this, cx| {
+                let requester = sender_id
+                    .and_then(|peer_id| this.collaborators.get(&peer_id))
+                    .and_then(|collaborator| {
+                        this.user_store
+                            .read(cx)
+                            .get_cached_user(collaborator.user_id)
+                    })
+                    .map_or("A collaborator".to_string(), |user| {
+                        user.github_login.clone()
+                    });
+                let path = buffer.read(cx).file().map_or("a file".to_string(), |file| {
+                    file.path().to_string_lossy().to_string()
+                });
+                let reason = match conflict {
+                    proto::SaveConflict::UnsavedHostChanges => "you have unsaved changes to it",
+                    proto::SaveConflict::FileChangedOnDisk => "it has changed on disk",
+                };
+                cx.emit(Event::Notification(format!(
+                    "{requester} tried to save {path}, but {reason}."
+                )));
+            })?;
+        } else {
+            this.update(&mut cx, |this, cx| this.save_buffer(buffer.clone(), cx))?
+                .await?;
+        }
+
         Ok(buffer.update(&mut cx, |buffer, _| proto::BufferSaved {
             project_id,
             buffer_id: buffer_id.into(),
             version: serialize_version(buffer.saved_version()),
             mtime: Some(buffer.saved_mtime().into()),
             fingerprint: language::proto::serialize_fingerprint(buffer.saved_version_fingerprint()),
+            conflict: conflict.map(|conflict| conflict as i32),
         })?)
     }
 
@@ -7861,25 +8953,28 @@ impl Project {
                             line_ending: language::proto::serialize_line_ending(
                                 buffer.line_ending(),
                             ) as i32,
+                            encoding: Some(language::proto::serialize_encoding(buffer.encoding())),
                         })
                         .log_err();
 
                     cx.background_executor()
                         .spawn(
-                            async move {
-                                let operations = operations.await;
-                                for chunk in split_operations(operations) {
-                                    client
-                                        .request(proto::UpdateBuffer {
-                                            project_id,
-                                            buffer_id: buffer_id.into(),
-                                            operations: chunk,
-                                        })
-                                        .await?;
+                            this.background_work.run(
+                                async move {
+                                    let operations = operations.await;
+                                    for chunk in split_operations(operations) {
+                                        client
+                                            .request(proto::UpdateBuffer {
+                                                project_id,
+                                                buffer_id: buffer_id.into(),
+                                                operations: chunk,
+                                            })
+                                            .await?;
+                                    }
+                                    anyhow::Ok(())
                                 }
-                                anyhow::Ok(())
-                            }
-                            .log_err(),
+                                .log_err(),
+                            ),
                         )
                         .detach();
                 }
@@ -8493,6 +9588,7 @@ impl Project {
         };
 
         let client = self.client.clone();
+        let background_work = self.background_work.clone();
         cx.spawn(move |this, mut cx| async move {
             let (buffers, incomplete_buffer_ids) = this.update(&mut cx, |this, cx| {
                 let buffers = this
@@ -8561,10 +9657,10 @@ impl Project {
             // creates these buffers for us again to unblock any waiting futures.
             for id in incomplete_buffer_ids {
                 cx.background_executor()
-                    .spawn(client.request(proto::OpenBufferById {
+                    .spawn(background_work.run(client.request(proto::OpenBufferById {
                         project_id,
                         id: id.into(),
-                    }))
+                    })))
                     .detach();
             }
 
@@ -8745,6 +9841,7 @@ impl Project {
             proto::LineEnding::from_i32(payload.line_ending)
                 .ok_or_else(|| anyhow!("missing line ending"))?,
         );
+        let encoding = language::proto::deserialize_encoding(payload.encoding.as_deref());
         let mtime = payload
             .mtime
             .ok_or_else(|| anyhow!("missing mtime"))?
@@ -8763,7 +9860,7 @@ impl Project {
                 });
             if let Some(buffer) = buffer {
                 buffer.update(cx, |buffer, cx| {
-                    buffer.did_reload(version, fingerprint, line_ending, mtime, cx);
+                    buffer.did_reload(version, fingerprint, line_ending, encoding, mtime, cx);
                 });
             }
             Ok(())
@@ -8826,40 +9923,17 @@ impl Project {
                 // we can identify the changes more precisely, preserving the locations
                 // of any anchors positioned in the unchanged regions.
                 if range.end.row > range.start.row {
-                    let mut offset = range.start.to_offset(&snapshot);
+                    let offset = range.start.to_offset(&snapshot);
                     let old_text = snapshot.text_for_range(range).collect::<String>();
 
-                    let diff = TextDiff::from_lines(old_text.as_str(), &new_text);
-                    let mut moved_since_edit = true;
-                    for change in diff.iter_all_changes() {
-                        let tag = change.tag();
-                        let value = change.value();
-                        match tag {
-                            ChangeTag::Equal => {
-                                offset += value.len();
-                                moved_since_edit = true;
-                            }
-                            ChangeTag::Delete => {
-                                let start = snapshot.anchor_after(offset);
-                                let end = snapshot.anchor_before(offset + value.len());
-                                if moved_since_edit {
-                                    edits.push((start..end, String::new()));
-                                } else {
-                                    edits.last_mut().unwrap().0.end = end;
-                                }
-                                offset += value.len();
-                                moved_since_edit = false;
-                            }
-                            ChangeTag::Insert => {
-                                if moved_since_edit {
-                                    let anchor = snapshot.anchor_after(offset);
-                                    edits.push((anchor..anchor, value.to_string()));
-                                } else {
-                                    edits.last_mut().unwrap().1.push_str(value);
-                                }
-                                moved_since_edit = false;
-                            }
-                        }
+                    for hunk in text::diff::line_diff(&old_text, &new_text) {
+                        let start = snapshot.anchor_after(offset + hunk.old_bytes.start);
+                        let end = if hunk.old_bytes.is_empty() {
+                            start
+                        } else {
+                            snapshot.anchor_before(offset + hunk.old_bytes.end)
+                        };
+                        edits.push((start..end, new_text[hunk.new_bytes].to_string()));
                     }
                 } else if range.end == range.start {
                     let anchor = snapshot.anchor_after(range.start);
@@ -8980,13 +10054,10 @@ impl Project {
     ) -> Vec<LanguageServerId> {
         if let Some((file, language)) = File::from_dyn(buffer.file()).zip(buffer.language()) {
             let worktree_id = file.worktree_id(cx);
-            language
-                .lsp_adapters()
-                .iter()
-                .flat_map(|adapter| {
-                    let key = (worktree_id, adapter.name.clone());
-                    self.language_server_ids.get(&key).copied()
-                })
+            language_settings(Some(language), buffer.file(), cx)
+                .customized_language_servers(&lsp_adapter_names(language))
+                .into_iter()
+                .flat_map(|name| self.language_server_ids.get(&(worktree_id, name)).copied())
                 .collect()
         } else {
             Vec::new()
@@ -8994,6 +10065,14 @@ impl Project {
     }
 }
 
+fn lsp_adapter_names(language: &Arc<Language>) -> Vec<LanguageServerName> {
+    language
+        .lsp_adapters()
+        .iter()
+        .map(|adapter| adapter.name.clone())
+        .collect()
+}
+
 fn subscribe_for_copilot_events(
     copilot: &Model<Copilot>,
     cx: &mut ModelContext<'_, Project>,
@@ -9364,3 +10443,34 @@ async fn load_shell_environment(dir: &Path) -> Result<HashMap<String, String>> {
     }
     Ok(parsed_env)
 }
+
+fn file_operation_filters_match(
+    options: &lsp::FileOperationRegistrationOptions,
+    abs_path: &Path,
+    is_dir: bool,
+) -> bool {
+    options.filters.iter().any(|filter| {
+        if filter
+            .scheme
+            .as_deref()
+            .map_or(false, |scheme| scheme != "file")
+        {
+            return false;
+        }
+        match filter.pattern.matches {
+            Some(lsp::FileOperationPatternKind::File) if is_dir => return false,
+            Some(lsp::FileOperationPatternKind::Folder) if !is_dir => return false,
+            _ => {}
+        }
+        let ignore_case = filter
+            .pattern
+            .options
+            .as_ref()
+            .and_then(|options| options.ignore_case)
+            .unwrap_or(false);
+        GlobBuilder::new(&filter.pattern.glob)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_or(false, |glob| glob.compile_matcher().is_match(abs_path))
+    })
+}
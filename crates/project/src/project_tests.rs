@@ -1,9 +1,10 @@
 use crate::{Event, *};
+use client::test::FakeServer;
 use fs::FakeFs;
-use futures::{future, StreamExt};
+use futures::{future, FutureExt as _, StreamExt};
 use gpui::AppContext;
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
+    language_settings::{AllLanguageSettings, FormatOnSave, LanguageSettingsContent},
     tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
@@ -13,7 +14,12 @@ use pretty_assertions::assert_eq;
 use serde_json::json;
 use std::{os, task::Poll};
 use unindent::Unindent as _;
-use util::{assert_set_eq, paths::PathMatcher, test::temp_tree};
+use util::{
+    assert_set_eq,
+    paths::PathMatcher,
+    process::{FakeProcessRunner, ProcessOutput},
+    test::temp_tree,
+};
 
 #[gpui::test]
 async fn test_block_via_channel(cx: &mut gpui::TestAppContext) {
@@ -143,6 +149,85 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     });
 }
 
+#[gpui::test]
+async fn test_editorconfig_and_project_specific_settings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".editorconfig": "root = true\n[*]\nindent_style = tab\ntrim_trailing_whitespace = false\n",
+            "the-root": {
+                ".editorconfig": "[*.rs]\nindent_size = 3\n",
+                "a": {
+                    "a.rs": "fn a() {\n\tA\n}"
+                },
+                "b": {
+                    ".zed": {
+                        "settings.json": r#"{ "tab_size": 2 }"#
+                    },
+                    "b.rs": "fn b() {\n\tB\n}"
+                }
+            }
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir/the-root".as_ref()], cx).await;
+    let worktree = project.update(cx, |project, _| project.worktrees().next().unwrap());
+
+    cx.executor().run_until_parked();
+    cx.update(|cx| {
+        let tree = worktree.read(cx);
+        let settings_for = |path: &str| {
+            language_settings(
+                None,
+                Some(
+                    &(File::for_entry(tree.entry_for_path(path).unwrap().clone(), worktree.clone())
+                        as _),
+                ),
+                cx,
+            )
+        };
+
+        // The worktree's `.editorconfig` overrides the default tab size, and
+        // the one above the worktree applies to all its files.
+        let settings_a = settings_for("a/a.rs");
+        assert_eq!(settings_a.tab_size.get(), 3);
+        assert!(settings_a.hard_tabs);
+        assert!(!settings_a.remove_trailing_whitespace_on_save);
+
+        // Settings files take precedence over `.editorconfig` files.
+        let settings_b = settings_for("b/b.rs");
+        assert_eq!(settings_b.tab_size.get(), 2);
+        assert!(settings_b.hard_tabs);
+    });
+
+    fs.save(
+        "/dir/the-root/.editorconfig".as_ref(),
+        &"[*.rs]\nindent_size = 5\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    cx.update(|cx| {
+        let tree = worktree.read(cx);
+        let settings_a = language_settings(
+            None,
+            Some(
+                &(File::for_entry(
+                    tree.entry_for_path("a/a.rs").unwrap().clone(),
+                    worktree.clone(),
+                ) as _),
+            ),
+            cx,
+        );
+        assert_eq!(settings_a.tab_size.get(), 5);
+    });
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1472,6 +1557,110 @@ async fn test_toggling_enable_language_server(cx: &mut gpui::TestAppContext) {
         .await;
 }
 
+#[gpui::test]
+async fn test_toggling_language_server_shared_by_languages(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut css = Language::new(
+        LanguageConfig {
+            name: Arc::from("CSS"),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["css".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    );
+    let _fake_css_servers = css
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            name: "tailwind-lsp",
+            ..Default::default()
+        }))
+        .await;
+    let mut html = Language::new(
+        LanguageConfig {
+            name: Arc::from("HTML"),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["html".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    );
+    let mut fake_html_servers = html
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            name: "tailwind-lsp",
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "index.html": "" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| {
+        project.languages.add(Arc::new(css));
+        project.languages.add(Arc::new(html));
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/index.html", cx)
+        })
+        .await
+        .unwrap();
+    let mut fake_server = fake_html_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+    let server_ids = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            project
+                .language_servers_for_buffer(buffer.read(cx), cx)
+                .map(|(_, server)| server.server_id())
+                .collect::<Vec<_>>()
+        })
+    };
+    let initial_server_ids = server_ids(cx);
+    assert_eq!(initial_server_ids.len(), 1);
+
+    // Disabling the server for one of its languages leaves it running for the others.
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.languages.insert(
+                    Arc::from("CSS"),
+                    LanguageSettingsContent {
+                        enable_language_server: Some(false),
+                        ..Default::default()
+                    },
+                );
+            });
+        })
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(server_ids(cx), initial_server_ids);
+    assert!(fake_html_servers.try_next().is_err());
+
+    // It stops once it's disabled for every language.
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.languages.insert(
+                    Arc::from("HTML"),
+                    LanguageSettingsContent {
+                        enable_language_server: Some(false),
+                        ..Default::default()
+                    },
+                );
+            });
+        })
+    });
+    fake_server
+        .receive_notification::<lsp::notification::Exit>()
+        .await;
+}
+
 #[gpui::test(iterations = 3)]
 async fn test_transforming_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1886,6 +2075,56 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_batching_edits_sent_to_language_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_servers = language.set_fake_lsp_adapter(Default::default()).await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "fn a() {}" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let mut fake_server = fake_servers.next().await.unwrap();
+    fake_server
+        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+        .await;
+
+    // Edits made before the language server is synced are sent together.
+    buffer.update(cx, |buffer, cx| buffer.edit([(9..9, "\n")], None, cx));
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(10..10, "fn b() {}")], None, cx)
+    });
+    let change = fake_server
+        .receive_notification::<lsp::notification::DidChangeTextDocument>()
+        .await;
+    assert_eq!(change.text_document.version, 1);
+    assert_eq!(change.content_changes.len(), 1);
+    assert_eq!(change.content_changes[0].text, "\nfn b() {}");
+    cx.executor().run_until_parked();
+    assert!(fake_server
+        .take_notifications()
+        .iter()
+        .all(|(method, _)| method != "textDocument/didChange"));
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2702,92 +2941,448 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
-#[gpui::test(iterations = 30)]
-async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_guest_save_conflicts(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let fs = FakeFs::new(cx.executor().clone());
-    fs.insert_tree(
-        "/dir",
-        json!({
-            "file1": "the original contents",
-        }),
-    )
-    .await;
-
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "file1": "abc" })).await;
     let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-    let worktree = project.read_with(cx, |project, _| project.worktrees().next().unwrap());
+    let client = project.read_with(cx, |project, _| project.client());
+    client.add_model_request_handler(Project::handle_save_buffer);
+    let server = FakeServer::for_client(1, &client, cx).await;
+    project
+        .update(cx, |project, cx| project.shared(1, cx))
+        .unwrap();
+
+    let notifications = Arc::new(Mutex::new(Vec::new()));
+    cx.update(|cx| {
+        let notifications = notifications.clone();
+        cx.subscribe(&project, move |_, event, _| {
+            if let Event::Notification(message) = event {
+                notifications.lock().push(message.clone());
+            }
+        })
+        .detach();
+    });
+
     let buffer = project
         .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
         .await
         .unwrap();
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id().to_proto());
+    let version = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            language::proto::serialize_version(&buffer.version())
+        })
+    };
 
-    // Simulate buffer diffs being slow, so that they don't complete before
-    // the next file change occurs.
-    cx.executor().deprioritize(*language::BUFFER_DIFF_TASK);
+    // The host edits the buffer after the guest asked to save it.
+    let guest_version = version(cx);
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "host ")], None, cx));
+    let response = server
+        .save_buffer(1, buffer_id, guest_version)
+        .await
+        .unwrap();
+    assert_eq!(
+        response.conflict,
+        Some(proto::SaveConflict::UnsavedHostChanges as i32)
+    );
+    assert_eq!(fs.load("/dir/file1".as_ref()).await.unwrap(), "abc");
+    assert_eq!(notifications.lock().len(), 1);
 
-    // Change the buffer's file on disk, and then wait for the file change
-    // to be detected by the worktree, so that the buffer starts reloading.
-    fs.save(
-        "/dir/file1".as_ref(),
-        &"the first contents".into(),
-        Default::default(),
-    )
-    .await
-    .unwrap();
-    worktree.next_event(cx);
+    // Once the guest has seen the host's edits, the buffer is saved.
+    let response = server.save_buffer(1, buffer_id, version(cx)).await.unwrap();
+    assert_eq!(response.conflict, None);
+    assert_eq!(fs.load("/dir/file1".as_ref()).await.unwrap(), "host abc");
 
-    // Change the buffer's file again. Depending on the random seed, the
-    // previous file change may still be in progress.
+    // The file changes on disk while the buffer has unsaved changes.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "more ")], None, cx));
     fs.save(
         "/dir/file1".as_ref(),
-        &"the second contents".into(),
+        &"external".into(),
         Default::default(),
     )
     .await
     .unwrap();
-    worktree.next_event(cx);
-
     cx.executor().run_until_parked();
-    let on_disk_text = fs.load(Path::new("/dir/file1")).await.unwrap();
-    buffer.read_with(cx, |buffer, _| {
-        assert_eq!(buffer.text(), on_disk_text);
-        assert!(!buffer.is_dirty(), "buffer should not be dirty");
-        assert!(!buffer.has_conflict(), "buffer should not be dirty");
-    });
+    assert!(buffer.read_with(cx, |buffer, _| buffer.has_conflict()));
+    let response = server.save_buffer(1, buffer_id, version(cx)).await.unwrap();
+    assert_eq!(
+        response.conflict,
+        Some(proto::SaveConflict::FileChangedOnDisk as i32)
+    );
+    assert_eq!(fs.load("/dir/file1".as_ref()).await.unwrap(), "external");
+    assert_eq!(notifications.lock().len(), 2);
 }
 
-#[gpui::test(iterations = 30)]
-async fn test_edit_buffer_while_it_reloads(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_guest_buffer_resync_backs_off(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let fs = FakeFs::new(cx.executor().clone());
-    fs.insert_tree(
-        "/dir",
-        json!({
-            "file1": "the original contents",
-        }),
-    )
-    .await;
+    let fs = FakeFs::new(cx.executor());
+    let mut languages = LanguageRegistry::test();
+    languages.set_executor(cx.executor());
+    let clock = Arc::new(clock::FakeSystemClock::default());
+    let http_client = util::http::FakeHttpClient::with_404_response();
+    let client = cx.update(|cx| Client::new(clock, http_client, cx));
+    let server = FakeServer::for_client(1, &client, cx).await;
+    let user_store = server.build_user_store(client.clone(), cx).await;
+
+    let project = cx.spawn({
+        let languages = Arc::new(languages);
+        |cx| {
+            Project::remote(
+                1,
+                client,
+                user_store,
+                languages,
+                fs,
+                proto::ChannelRole::Member,
+                cx,
+            )
+        }
+    });
+    let join_project = server.receive::<proto::JoinProject>().await.unwrap();
+    server.respond(
+        join_project.receipt(),
+        proto::JoinProjectResponse {
+            replica_id: 1,
+            ..Default::default()
+        },
+    );
+    let project = project.await.unwrap();
 
-    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-    let worktree = project.read_with(cx, |project, _| project.worktrees().next().unwrap());
-    let buffer = project
-        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
-        .await
-        .unwrap();
+    // The guest keeps retrying a failed resync, waiting longer after each failure.
+    project.update(cx, |project, _| {
+        project.send_buffer_ordered_message(BufferOrderedMessage::Resync)
+    });
+    let request = server.receive::<proto::SynchronizeBuffers>().await.unwrap();
+    server.respond_with_error(request.receipt(), "host unavailable");
 
-    // Simulate buffer diffs being slow, so that they don't complete before
-    // the next file change occurs.
-    cx.executor().deprioritize(*language::BUFFER_DIFF_TASK);
+    cx.executor()
+        .advance_clock(BUFFER_RESYNC_INITIAL_RETRY_DELAY);
+    let request = server.receive::<proto::SynchronizeBuffers>().await.unwrap();
+    server.respond_with_error(request.receipt(), "host unavailable");
 
-    // Change the buffer's file on disk, and then wait for the file change
-    // to be detected by the worktree, so that the buffer starts reloading.
-    fs.save(
-        "/dir/file1".as_ref(),
-        &"the first contents".into(),
-        Default::default(),
-    )
+    cx.executor()
+        .advance_clock(BUFFER_RESYNC_INITIAL_RETRY_DELAY);
+    assert!(server
+        .receive::<proto::SynchronizeBuffers>()
+        .now_or_never()
+        .is_none());
+    cx.executor()
+        .advance_clock(BUFFER_RESYNC_INITIAL_RETRY_DELAY);
+    let request = server.receive::<proto::SynchronizeBuffers>().await.unwrap();
+
+    // Once the resync succeeds, the guest stops retrying.
+    server.respond(
+        request.receipt(),
+        proto::SynchronizeBuffersResponse::default(),
+    );
+    cx.executor().advance_clock(BUFFER_RESYNC_MAX_RETRY_DELAY);
+    assert!(server
+        .receive::<proto::SynchronizeBuffers>()
+        .now_or_never()
+        .is_none());
+}
+
+#[gpui::test]
+async fn test_format_on_save_via_external_command(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.format_on_save = Some(FormatOnSave::External {
+                    command: "upcase".into(),
+                    arguments: vec!["--stdin-filepath".into(), "{buffer_path}".into()].into(),
+                });
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "file1": "one\ntwo\n" }))
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    project.update(cx, |project, _| {
+        let requests = requests.clone();
+        project.set_process_runner(FakeProcessRunner::create(move |request| {
+            let stdout = request.stdin.to_uppercase().into_bytes();
+            requests.lock().push(request);
+            Ok(ProcessOutput {
+                exit_code: Some(0),
+                stdout,
+                stderr: Vec::new(),
+            })
+        }));
+    });
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Save,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *requests.lock(),
+        [ProcessRequest {
+            program: "upcase".into(),
+            args: vec!["--stdin-filepath".into(), "/dir/file1".into()],
+            working_directory: PathBuf::from("/dir"),
+            stdin: "zero\none\ntwo\n".into(),
+        }]
+    );
+    assert_eq!(
+        buffer.update(cx, |buffer, _| buffer.text()),
+        "ZERO\nONE\nTWO\n"
+    );
+}
+
+#[gpui::test]
+async fn test_open_and_save_binary_file(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "text.txt": "plain text",
+        }),
+    )
+    .await;
+    fs.insert_file_bytes("/dir/data.bin", vec![0xff, b'B', 0, b'C', b'D'])
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+
+    let (_, text_item) = project
+        .update(cx, |project, cx| {
+            project.open_path((worktree_id, "text.txt").into(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(text_item.downcast::<Buffer>().is_ok());
+
+    let (entry_id, binary_item) = project
+        .update(cx, |project, cx| {
+            project.open_path((worktree_id, "data.bin").into(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(entry_id.is_some());
+    let binary_file = binary_item.downcast::<BinaryFile>().unwrap();
+
+    // Bytes are only available once the range containing them has been loaded.
+    binary_file.update(cx, |file, cx| {
+        assert_eq!(file.len(), 5);
+        assert_eq!(file.read_range(0..5), None);
+        file.load_range(0..5, cx);
+    });
+    cx.executor().run_until_parked();
+    binary_file.read_with(cx, |file, _| {
+        assert_eq!(file.read_range(0..5), Some(vec![0xff, b'B', 0, b'C', b'D']));
+    });
+
+    let matches = binary_file
+        .read_with(cx, |file, cx| file.search(b"CD".to_vec(), cx))
+        .await
+        .unwrap();
+    assert_eq!(matches, vec![3]);
+
+    binary_file.update(cx, |file, cx| {
+        file.set_byte(0, b'Z', cx);
+        file.remove_byte(1, cx);
+        file.set_byte(4, b'!', cx);
+        assert!(file.is_dirty());
+        assert_eq!(file.read_range(0..5), Some(vec![b'Z', 0, b'C', b'D', b'!']));
+    });
+    binary_file
+        .update(cx, |file, cx| file.save(cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert!(binary_file.read_with(cx, |file, _| !file.is_dirty()));
+    assert_eq!(
+        fs.load_bytes(Path::new("/dir/data.bin")).await.unwrap(),
+        vec![b'Z', 0, b'C', b'D', b'!']
+    );
+
+    // A clean file is reloaded when it changes on disk.
+    fs.insert_file_bytes("/dir/data.bin", vec![0xfe, 0]).await;
+    cx.executor().run_until_parked();
+    binary_file.update(cx, |file, cx| {
+        assert_eq!(file.len(), 2);
+        assert!(!file.has_conflict());
+        file.set_byte(0, 1, cx);
+    });
+
+    // A dirty file is marked as conflicted instead.
+    fs.insert_file_bytes("/dir/data.bin", vec![0xfd]).await;
+    cx.executor().run_until_parked();
+    binary_file.read_with(cx, |file, _| {
+        assert_eq!(file.len(), 2);
+        assert!(file.is_dirty());
+        assert!(file.has_conflict());
+    });
+}
+
+#[gpui::test(iterations = 30)]
+async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "file1": "the original contents",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree = project.read_with(cx, |project, _| project.worktrees().next().unwrap());
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+
+    // Simulate buffer diffs being slow, so that they don't complete before
+    // the next file change occurs.
+    cx.executor().deprioritize(*language::BUFFER_DIFF_TASK);
+
+    // Change the buffer's file on disk, and then wait for the file change
+    // to be detected by the worktree, so that the buffer starts reloading.
+    fs.save(
+        "/dir/file1".as_ref(),
+        &"the first contents".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    worktree.next_event(cx);
+
+    // Change the buffer's file again. Depending on the random seed, the
+    // previous file change may still be in progress.
+    fs.save(
+        "/dir/file1".as_ref(),
+        &"the second contents".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    worktree.next_event(cx);
+
+    cx.executor().run_until_parked();
+    let on_disk_text = fs.load(Path::new("/dir/file1")).await.unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), on_disk_text);
+        assert!(!buffer.is_dirty(), "buffer should not be dirty");
+        assert!(!buffer.has_conflict(), "buffer should not be dirty");
+    });
+}
+
+#[gpui::test]
+async fn test_file_outside_worktree_changes_on_disk(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "file1": "the original contents",
+        }),
+    )
+    .await;
+    fs.insert_tree(
+        "/dependency",
+        json!({
+            "lib.rs": "the original contents",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dependency/lib.rs", cx))
+        .await
+        .unwrap();
+
+    // Replace the file on disk, as editors that save atomically do.
+    fs.remove_file("/dependency/lib.rs".as_ref(), Default::default())
+        .await
+        .unwrap();
+    fs.insert_file("/dependency/lib.rs", "the new contents".into())
+        .await;
+    cx.executor().run_until_parked();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "the new contents");
+        assert!(!buffer.is_dirty());
+    });
+
+    // Changes on disk conflict with unsaved edits.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "unsaved ")], None, cx));
+    fs.save(
+        "/dependency/lib.rs".as_ref(),
+        &"the newest contents".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "unsaved the new contents");
+        assert!(buffer.has_conflict());
+    });
+}
+
+#[gpui::test(iterations = 30)]
+async fn test_edit_buffer_while_it_reloads(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "file1": "the original contents",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree = project.read_with(cx, |project, _| project.worktrees().next().unwrap());
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+
+    // Simulate buffer diffs being slow, so that they don't complete before
+    // the next file change occurs.
+    cx.executor().deprioritize(*language::BUFFER_DIFF_TASK);
+
+    // Change the buffer's file on disk, and then wait for the file change
+    // to be detected by the worktree, so that the buffer starts reloading.
+    fs.save(
+        "/dir/file1".as_ref(),
+        &"the first contents".into(),
+        Default::default(),
+    )
     .await
     .unwrap();
     worktree.next_event(cx);
@@ -3829,48 +4424,387 @@ async fn test_rename(cx: &mut gpui::TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_search(cx: &mut gpui::TestAppContext) {
+async fn test_large_files_are_not_parsed(cx: &mut gpui::TestAppContext) {
     init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.large_file_threshold = Some(16);
+            });
+        });
+    });
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "one.rs": "const ONE: usize = 1;",
-            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-            "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-            "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            "small.rs": "fn a() {}",
+            "large.rs": "fn a() {}\n// ✓ é\nfn c() {}",
         }),
     )
     .await;
+
     let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-    assert_eq!(
-        search(
-            &project,
-            SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap(),
-            cx
-        )
-        .await
-        .unwrap(),
-        HashMap::from_iter([
-            ("two.rs".to_string(), vec![6..9]),
-            ("three.rs".to_string(), vec![37..40])
-        ])
+    let language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
     );
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
 
-    let buffer_4 = project
+    let small_buffer = project
         .update(cx, |project, cx| {
-            project.open_local_buffer("/dir/four.rs", cx)
+            project.open_local_buffer("/dir/small.rs", cx)
+        })
+        .await
+        .unwrap();
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/large.rs", cx)
         })
         .await
         .unwrap();
-    buffer_4.update(cx, |buffer, cx| {
-        let text = "two::TWO";
-        buffer.edit([(20..28, text), (31..43, text)], None, cx);
-    });
 
-    assert_eq!(
-        search(
+    small_buffer.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert_eq!(buffer.language().unwrap().name().as_ref(), "Rust");
+    });
+    large_buffer.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert!(buffer.language().is_none());
+        assert_eq!(buffer.text(), "fn a() {}\n// ✓ é\nfn c() {}");
+    });
+}
+
+#[gpui::test]
+async fn test_rename_entry_applies_will_rename_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let rust_files = lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".to_string()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".to_string(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        will_rename: Some(rust_files.clone()),
+                        did_rename: Some(rust_files),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "pub const ONE: usize = 1;",
+            "two.rs": "use one::ONE;",
+            "README.md": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/two.rs", cx)
+        })
+        .await
+        .unwrap();
+    let mut fake_server = fake_servers.next().await.unwrap();
+
+    let id_for_path = |path: &'static str, cx: &mut gpui::TestAppContext| {
+        project.update(cx, |project, cx| {
+            let tree = project.worktrees().next().unwrap();
+            tree.read(cx).entry_for_path(path).unwrap().id
+        })
+    };
+    let one_id = id_for_path("one.rs", cx);
+    let readme_id = id_for_path("README.md", cx);
+
+    let response = project.update(cx, |project, cx| {
+        project.rename_entries(
+            vec![
+                (one_id, Path::new("uno.rs").into()),
+                (readme_id, Path::new("README.txt").into()),
+            ],
+            cx,
+        )
+    });
+    fake_server
+        .handle_request::<lsp::request::WillRenameFiles, _, _>(|params, _| async move {
+            // Only the files matching the server's filters are sent.
+            assert_eq!(
+                params.files,
+                vec![lsp::FileRename {
+                    old_uri: "file:///dir/one.rs".to_string(),
+                    new_uri: "file:///dir/uno.rs".to_string(),
+                }]
+            );
+            Ok(Some(lsp::WorkspaceEdit {
+                changes: Some(
+                    [(
+                        lsp::Url::from_file_path("/dir/two.rs").unwrap(),
+                        vec![lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 7)),
+                            "uno".to_string(),
+                        )],
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+                ..Default::default()
+            }))
+        })
+        .next()
+        .await
+        .unwrap();
+    let (entries, transaction) = response.await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(transaction.0.len(), 1);
+    buffer.update(cx, |buffer, _| assert_eq!(buffer.text(), "use uno::ONE;"));
+
+    let notification = fake_server
+        .receive_notification::<lsp::notification::DidRenameFiles>()
+        .await;
+    assert_eq!(notification.files.len(), 1);
+    project.update(cx, |project, cx| {
+        let tree = project.worktrees().next().unwrap();
+        assert!(tree.read(cx).entry_for_path("uno.rs").is_some());
+        assert!(tree.read(cx).entry_for_path("README.txt").is_some());
+    });
+}
+
+#[gpui::test]
+async fn test_rename_entries_skips_entries_in_renamed_directories(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a": { "b.rs": "" },
+            "dest": {},
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let (a_id, b_id) = project.update(cx, |project, cx| {
+        let tree = project.worktrees().next().unwrap();
+        let tree = tree.read(cx);
+        (
+            tree.entry_for_path("a").unwrap().id,
+            tree.entry_for_path("a/b.rs").unwrap().id,
+        )
+    });
+
+    // Moving a directory along with a file inside it only moves the directory.
+    let (entries, _) = project
+        .update(cx, |project, cx| {
+            project.rename_entries(
+                vec![
+                    (a_id, Path::new("dest/a").into()),
+                    (b_id, Path::new("dest/b.rs").into()),
+                ],
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    cx.executor().run_until_parked();
+    project.update(cx, |project, cx| {
+        let tree = project.worktrees().next().unwrap();
+        assert!(tree.read(cx).entry_for_path("dest/a/b.rs").is_some());
+        assert!(tree.read(cx).entry_for_path("dest/b.rs").is_none());
+    });
+}
+
+#[gpui::test]
+async fn test_failed_rename_reverts_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                rename_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;"
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let response = project.update(cx, |project, cx| {
+        project.perform_rename(buffer.clone(), 7, "THREE".to_string(), true, cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::Rename, _, _>(|_, _| async move {
+            Ok(Some(lsp::WorkspaceEdit {
+                document_changes: Some(lsp::DocumentChanges::Edits(vec![
+                    lsp::TextDocumentEdit {
+                        text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                            uri: lsp::Url::from_file_path("/dir/one.rs").unwrap(),
+                            version: None,
+                        },
+                        edits: vec![lsp::OneOf::Left(lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 9)),
+                            "THREE".to_string(),
+                        ))],
+                    },
+                    // The server refers to a version of this file that doesn't exist.
+                    lsp::TextDocumentEdit {
+                        text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                            uri: lsp::Url::from_file_path("/dir/two.rs").unwrap(),
+                            version: Some(100),
+                        },
+                        edits: vec![lsp::OneOf::Left(lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 24), lsp::Position::new(0, 27)),
+                            "THREE".to_string(),
+                        ))],
+                    },
+                ])),
+                ..Default::default()
+            }))
+        })
+        .next()
+        .await
+        .unwrap();
+    assert!(response.await.is_err());
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "const ONE: usize = 1;");
+        assert!(!buffer.is_dirty());
+    });
+}
+
+#[gpui::test]
+async fn test_releasing_project_cancels_background_work(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+        .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let background_work = project.read_with(cx, |project, _| project.background_work.clone());
+    let (_tx, rx) = futures::channel::oneshot::channel::<()>();
+    let in_flight = cx.executor().spawn(background_work.run(rx));
+    cx.executor().run_until_parked();
+    assert!(!background_work.is_cancelled());
+
+    cx.update(|_| drop(project));
+    cx.executor().run_until_parked();
+    assert!(background_work.is_cancelled());
+    assert_eq!(in_flight.await, None);
+}
+
+#[gpui::test]
+async fn test_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("two.rs".to_string(), vec![6..9]),
+            ("three.rs".to_string(), vec![37..40])
+        ])
+    );
+
+    let buffer_4 = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/four.rs", cx)
+        })
+        .await
+        .unwrap();
+    buffer_4.update(cx, |buffer, cx| {
+        let text = "two::TWO";
+        buffer.edit([(20..28, text), (31..43, text)], None, cx);
+    });
+
+    assert_eq!(
+        search(
             &project,
             SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap(),
             cx
@@ -4318,6 +5252,59 @@ async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_in_gitignored_dirs_respects_file_scan_exclusions(
+    cx: &mut gpui::TestAppContext,
+) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.file_scan_exclusions = Some(vec![
+                    "**/node_modules/eslint".to_string(),
+                    "**/*.json".to_string(),
+                ]);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            ".gitignore": "/node_modules\n",
+            "node_modules": {
+                "eslint": {
+                    "index.ts": "const eslint_key = 'eslint value'",
+                },
+                "prettier": {
+                    "index.ts": "const prettier_key = 'prettier value'",
+                    "package.json": r#"{ "other_key": "other value" }"#,
+                },
+            },
+            "main.ts": "const main_key = 'main value'",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::text("key", false, false, true, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("main.ts".to_string(), vec![11..14]),
+            ("node_modules/prettier/index.ts".to_string(), vec![15..18]),
+        ]),
+        "Excluded files and directories inside of ignored directories should not be searched"
+    );
+}
+
 #[test]
 fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("**/*.js"), "");
@@ -4395,6 +5382,104 @@ async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     assert!(result.is_err())
 }
 
+#[gpui::test]
+async fn test_batch_replace(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            "three.rs": "const THREE: usize = 3;",
+            "four.rs": "const FOUR: usize = one::ONE * 4;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let query =
+        SearchQuery::regex("one::(\\w+)", false, true, false, Vec::new(), Vec::new()).unwrap();
+
+    let summary = project
+        .update(cx, |project, cx| {
+            project.batch_replace(
+                query.clone(),
+                true,
+                |text| Some(text.replace("one::", "crate::one::")),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        summary.to_string(),
+        [
+            "dir/four.rs:1",
+            "- one::ONE",
+            "+ crate::one::ONE",
+            "dir/two.rs:1",
+            "- one::ONE",
+            "+ crate::one::ONE",
+            "dir/two.rs:1",
+            "- one::ONE",
+            "+ crate::one::ONE",
+            "would make 3 edits in 2 files",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        fs.load("/dir/two.rs".as_ref()).await.unwrap(),
+        "const TWO: usize = one::ONE + one::ONE;"
+    );
+
+    // Files with unsaved changes are edited, but not saved.
+    let four = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/four.rs", cx)
+        })
+        .await
+        .unwrap();
+    four.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "// Unsaved\n")], None, cx)
+    });
+
+    let summary = project
+        .update(cx, |project, cx| {
+            project.batch_replace(
+                query,
+                false,
+                |text| Some(text.replace("one::", "crate::one::")),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(summary.edit_count(), 3);
+    assert!(summary
+        .to_string()
+        .ends_with("made 3 edits in 2 files, 1 of which had unsaved changes and weren't saved"));
+    assert_eq!(
+        fs.load("/dir/two.rs".as_ref()).await.unwrap(),
+        "const TWO: usize = crate::one::ONE + crate::one::ONE;"
+    );
+    assert_eq!(
+        fs.load("/dir/four.rs".as_ref()).await.unwrap(),
+        "const FOUR: usize = one::ONE * 4;"
+    );
+    four.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "// Unsaved\nconst FOUR: usize = crate::one::ONE * 4;"
+        );
+        assert!(buffer.is_dirty());
+    });
+    assert_eq!(
+        fs.load("/dir/one.rs".as_ref()).await.unwrap(),
+        "const ONE: usize = 1;"
+    );
+}
+
 async fn search(
     project: &Model<Project>,
     query: SearchQuery,
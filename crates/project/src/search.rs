@@ -93,11 +93,8 @@ impl SearchQuery {
         let mut query = query.to_string();
         let initial_query = Arc::from(query.as_str());
         if whole_word {
-            let mut word_query = String::new();
-            word_query.push_str("\\b");
-            word_query.push_str(&query);
-            word_query.push_str("\\b");
-            query = word_query
+            // Group the query so that word boundaries apply to every alternative.
+            query = format!("\\b(?:{query})\\b");
         }
 
         let multiline = query.contains('\n') || query.contains("\\n");
@@ -299,6 +296,8 @@ impl SearchQuery {
                         matches.push(mat.start()..mat.end());
                     }
                 } else {
+                    // Lines that fit within a chunk are searched in place. Only
+                    // lines spanning several chunks are copied before searching.
                     let mut line = String::new();
                     let mut line_offset = 0;
                     for (chunk_ix, chunk) in rope.chunks().chain(["\n"]).enumerate() {
@@ -306,18 +305,30 @@ impl SearchQuery {
                             yield_now().await;
                         }
 
-                        for (newline_ix, text) in chunk.split('\n').enumerate() {
-                            if newline_ix > 0 {
-                                for mat in regex.find_iter(&line) {
-                                    let start = line_offset + mat.start();
-                                    let end = line_offset + mat.end();
-                                    matches.push(start..end);
-                                }
+                        let mut lines = chunk.split('\n').peekable();
+                        let first_line = lines.next().unwrap_or_default();
+                        if lines.peek().is_none() {
+                            line.push_str(first_line);
+                            continue;
+                        }
 
-                                line_offset += line.len() + 1;
-                                line.clear();
+                        let first_line = if line.is_empty() {
+                            first_line
+                        } else {
+                            line.push_str(first_line);
+                            line.as_str()
+                        };
+                        push_line_matches(regex, first_line, line_offset, &mut matches);
+                        line_offset += first_line.len() + 1;
+                        line.clear();
+
+                        while let Some(text) = lines.next() {
+                            if lines.peek().is_some() {
+                                push_line_matches(regex, text, line_offset, &mut matches);
+                                line_offset += text.len() + 1;
+                            } else {
+                                line.push_str(text);
                             }
-                            line.push_str(text);
                         }
                     }
                 }
@@ -401,6 +412,17 @@ impl SearchQuery {
     }
 }
 
+fn push_line_matches(
+    regex: &Regex,
+    line: &str,
+    line_offset: usize,
+    matches: &mut Vec<Range<usize>>,
+) {
+    for mat in regex.find_iter(line) {
+        matches.push(line_offset + mat.start()..line_offset + mat.end());
+    }
+}
+
 fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>> {
     glob_set
         .split(',')
@@ -416,6 +438,38 @@ fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gpui::{Context, TestAppContext};
+    use language::{Buffer, BufferId};
+
+    #[test]
+    fn whole_word_regex_applies_to_every_alternative() {
+        let query =
+            SearchQuery::regex("foo|bar", true, false, false, Vec::new(), Vec::new()).unwrap();
+        assert!(query.detect("a bar b".as_bytes()).unwrap());
+        assert!(!query.detect("foobar".as_bytes()).unwrap());
+    }
+
+    #[gpui::test]
+    async fn regex_search_across_chunks(cx: &mut TestAppContext) {
+        let line = "the quick brown fox jumps over the lazy dog";
+        let text = [line; 20].join("\n");
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(
+                0,
+                BufferId::new(cx.entity_id().as_u64()).unwrap(),
+                text.clone(),
+            )
+        });
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        let query =
+            SearchQuery::regex("fox.*dog$", false, true, false, Vec::new(), Vec::new()).unwrap();
+        let matches = query.search(&snapshot, None).await;
+        assert_eq!(matches.len(), 20);
+        for range in matches {
+            assert_eq!(&text[range], "fox jumps over the lazy dog");
+        }
+    }
 
     #[test]
     fn path_matcher_creation_for_valid_paths() {
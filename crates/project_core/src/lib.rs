@@ -8,6 +8,7 @@ use rpc::proto;
 use serde::Serialize;
 
 mod ignore;
+pub mod power;
 pub mod project_settings;
 pub mod worktree;
 #[cfg(test)]
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use gpui::{AppContext, Context, Global, Model, ModelContext, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// How often the power source is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// When to throttle background work to reduce energy use.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSavingMode {
+    /// Save power while the computer is running on battery.
+    #[default]
+    OnBattery,
+    /// Always save power.
+    Always,
+    /// Never save power.
+    Never,
+}
+
+struct PowerSavingSetting(PowerSavingMode);
+
+/// When to throttle background work, like file scanning and indexing, to
+/// reduce energy use.
+///
+/// Default: on_battery
+#[derive(Clone, Default, JsonSchema, Deserialize, Serialize)]
+#[serde(transparent)]
+struct PowerSavingSettingOverride(Option<PowerSavingMode>);
+
+impl Settings for PowerSavingSetting {
+    const KEY: Option<&'static str> = Some("power_saving");
+
+    type FileContent = PowerSavingSettingOverride;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> Result<Self> {
+        Ok(Self(
+            Self::json_merge(default_value, user_values)?
+                .0
+                .ok_or_else(Self::missing_default)?,
+        ))
+    }
+}
+
+struct GlobalPowerSaving(Model<PowerSaving>);
+
+impl Global for GlobalPowerSaving {}
+
+/// Tracks whether background work should be throttled to save power,
+/// notifying observers when that changes.
+pub struct PowerSaving {
+    on_battery_power: bool,
+    enabled: Arc<AtomicBool>,
+    _poll_task: Task<()>,
+}
+
+pub fn init(cx: &mut AppContext) {
+    PowerSavingSetting::register(cx);
+    let power_saving = cx.new_model(PowerSaving::new);
+    cx.set_global(GlobalPowerSaving(power_saving));
+}
+
+impl PowerSaving {
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalPowerSaving>()
+            .map(|power_saving| power_saving.0.clone())
+    }
+
+    /// Returns whether background work should currently be throttled.
+    pub fn is_enabled(cx: &AppContext) -> bool {
+        Self::global(cx).map_or(false, |power_saving| power_saving.read(cx).enabled())
+    }
+
+    /// Returns a flag that tracks whether power saving is enabled, for work
+    /// that runs on background threads.
+    pub fn enabled_flag(cx: &AppContext) -> Arc<AtomicBool> {
+        Self::global(cx).map_or_else(Default::default, |power_saving| {
+            power_saving.read(cx).enabled.clone()
+        })
+    }
+
+    fn new(cx: &mut ModelContext<Self>) -> Self {
+        cx.observe_global::<SettingsStore>(|this, cx| this.update_enabled(cx))
+            .detach();
+
+        let mut this = Self {
+            on_battery_power: cx.is_on_battery_power(),
+            enabled: Default::default(),
+            _poll_task: cx.spawn(|this, mut cx| async move {
+                loop {
+                    cx.background_executor().timer(POLL_INTERVAL).await;
+                    let updated = this.update(&mut cx, |this, cx| {
+                        this.on_battery_power = cx.is_on_battery_power();
+                        this.update_enabled(cx);
+                    });
+                    if updated.is_err() {
+                        break;
+                    }
+                }
+            }),
+        };
+        this.update_enabled(cx);
+        this
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(SeqCst)
+    }
+
+    fn update_enabled(&mut self, cx: &mut ModelContext<Self>) {
+        let enabled = match PowerSavingSetting::get_global(cx).0 {
+            PowerSavingMode::OnBattery => self.on_battery_power,
+            PowerSavingMode::Always => true,
+            PowerSavingMode::Never => false,
+        };
+        if enabled != self.enabled.swap(enabled, SeqCst) {
+            cx.set_low_power_mode(enabled);
+            cx.notify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    fn test_power_saving_follows_power_source(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            init(cx);
+        });
+        assert!(!cx.read(PowerSaving::is_enabled));
+        assert!(!cx.read(|cx| cx.low_power_mode()));
+
+        cx.simulate_battery_power(true);
+        cx.executor().advance_clock(POLL_INTERVAL);
+        assert!(cx.read(PowerSaving::is_enabled));
+        assert!(cx.read(|cx| cx.low_power_mode()));
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<PowerSavingSetting>(cx, |mode| {
+                    mode.0 = Some(PowerSavingMode::Never);
+                });
+            });
+        });
+        assert!(!cx.read(PowerSaving::is_enabled));
+
+        cx.simulate_battery_power(false);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<PowerSavingSetting>(cx, |mode| {
+                    mode.0 = Some(PowerSavingMode::Always);
+                });
+            });
+        });
+        assert!(cx.read(PowerSaving::is_enabled));
+    }
+}
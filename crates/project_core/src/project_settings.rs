@@ -39,6 +39,55 @@ pub struct ProjectSettings {
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Whether to eagerly scan directories that symlinks inside the worktree
+    /// point to when they are outside of it. Otherwise they are only scanned
+    /// once they are expanded.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub scan_external_symlinks: bool,
+
+    /// Whether deleting files and folders removes them permanently instead of moving them
+    /// to the system trash. This also applies to files that guests delete from projects
+    /// you share.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub permanently_delete: bool,
+
+    /// Whether collaborators must be let in by the host before joining a shared project.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub approve_join_requests: bool,
+
+    /// Files larger than this many bytes are opened in large-file mode: they're
+    /// read in chunks, and aren't highlighted, sent to language servers or soft wrapped.
+    ///
+    /// Default: 20000000
+    #[serde(default)]
+    pub large_file_threshold: Option<u64>,
+
+    /// Whether the files you pin in a shared project are shown to its guests.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub share_pinned_files: bool,
+
+    /// Whether guests of a project you're sharing can download its files to their own disk.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub allow_guest_downloads: bool,
+}
+
+impl ProjectSettings {
+    /// Whether a file of the given size should be opened in large-file mode.
+    pub fn is_large_file(&self, len: u64) -> bool {
+        self.large_file_threshold
+            .map_or(false, |threshold| len > threshold)
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -1,7 +1,8 @@
 use crate::{
-    ignore::IgnoreStack, project_settings::ProjectSettings, DiagnosticSummary, ProjectEntryId,
+    ignore::IgnoreStack, power::PowerSaving, project_settings::ProjectSettings, DiagnosticSummary,
+    ProjectEntryId,
 };
-use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ::ignore::gitignore::{gitconfig_excludes_path, Gitignore, GitignoreBuilder};
 use anyhow::{anyhow, Context as _, Result};
 use client::{proto, Client};
 use clock::ReplicaId;
@@ -29,11 +30,11 @@ use gpui::{
 use itertools::Itertools;
 use language::{
     proto::{
-        deserialize_fingerprint, deserialize_version, serialize_fingerprint, serialize_line_ending,
-        serialize_version,
+        deserialize_fingerprint, deserialize_version, serialize_encoding, serialize_fingerprint,
+        serialize_line_ending, serialize_version,
     },
-    Buffer, Capability, DiagnosticEntry, File as _, LineEnding, PointUtf16, Rope, RopeFingerprint,
-    Unclipped,
+    Buffer, Capability, DiagnosticEntry, Encoding, File as _, LineEnding, PointUtf16, Rope,
+    RopeFingerprint, Unclipped,
 };
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
@@ -56,7 +57,7 @@ use std::{
     path::{Path, PathBuf},
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering::SeqCst},
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
         Arc,
     },
     time::{Duration, SystemTime},
@@ -68,6 +69,13 @@ use util::{
     ResultExt,
 };
 
+/// How long to wait for further file system events before processing a batch of
+/// them, when saving power.
+const POWER_SAVING_FS_EVENTS_DELAY: Duration = Duration::from_secs(2);
+
+/// Files opened in large-file mode are read this many bytes at a time.
+const LARGE_FILE_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
 pub struct WorktreeId(usize);
 
@@ -227,11 +235,16 @@ pub struct LocalSnapshot {
     /// All of the gitignore files in the worktree, indexed by their relative path.
     /// The boolean indicates whether the gitignore needs to be updated.
     ignores_by_parent_abs_path: HashMap<Arc<Path>, (Arc<Gitignore>, bool)>,
+    /// The rules from the global git excludes file and each repository's
+    /// `.git/info/exclude`, which apply beneath all of the repository's
+    /// `.gitignore` files, indexed by the repository's root.
+    repository_excludes: HashMap<Arc<Path>, Arc<Gitignore>>,
     /// All of the git repositories in the worktree, indexed by the project entry
     /// id of their parent directory.
     git_repositories: TreeMap<ProjectEntryId, LocalRepositoryEntry>,
     file_scan_exclusions: Vec<PathMatcher>,
     private_files: Vec<PathMatcher>,
+    scan_external_symlinks: bool,
 }
 
 struct BackgroundScannerState {
@@ -321,7 +334,7 @@ impl Worktree {
             .await
             .context("failed to stat worktree path")?;
 
-        let fs_case_sensitive = fs.is_case_sensitive().await.unwrap_or_else(|e| {
+        let fs_case_sensitive = fs.is_case_sensitive(&abs_path).await.unwrap_or_else(|e| {
             log::error!(
                 "Failed to determine whether filesystem is case sensitive (falling back to true) due to error: {e:#}"
             );
@@ -345,11 +358,16 @@ impl Worktree {
                         "private_files",
                     );
 
+                    let new_scan_external_symlinks =
+                        ProjectSettings::get_global(cx).scan_external_symlinks;
+
                     if new_file_scan_exclusions != this.snapshot.file_scan_exclusions
                         || new_private_files != this.snapshot.private_files
+                        || new_scan_external_symlinks != this.snapshot.scan_external_symlinks
                     {
                         this.snapshot.file_scan_exclusions = new_file_scan_exclusions;
                         this.snapshot.private_files = new_private_files;
+                        this.snapshot.scan_external_symlinks = new_scan_external_symlinks;
 
                         log::info!(
                             "Re-scanning directories, new scan exclude files: {:?}, new dotenv files: {:?}",
@@ -400,7 +418,9 @@ impl Worktree {
                     ProjectSettings::get(Some((cx.handle().entity_id().as_u64() as usize, &Path::new(""))), cx).private_files.as_deref(),
                     "private_files",
                 ),
+                scan_external_symlinks: ProjectSettings::get_global(cx).scan_external_symlinks,
                 ignores_by_parent_abs_path: Default::default(),
+                repository_excludes: Default::default(),
                 git_repositories: Default::default(),
                 snapshot: Snapshot {
                     id: WorktreeId::from_usize(cx.entity_id().as_u64() as usize),
@@ -603,6 +623,35 @@ impl Worktree {
         }
     }
 
+    /// Whether the file system containing this worktree distinguishes paths that only differ
+    /// in casing. This is only known for local worktrees, and assumed for remote ones.
+    pub fn is_case_sensitive(&self) -> bool {
+        match self {
+            Worktree::Local(worktree) => worktree.fs_case_sensitive,
+            Worktree::Remote(_) => true,
+        }
+    }
+
+    /// Returns the entry that a new file or directory at the given path would clash with,
+    /// which on a case-insensitive file system includes an entry whose name only differs in
+    /// casing.
+    pub fn conflicting_entry_for_path(&self, path: &Path) -> Option<&Entry> {
+        if let Some(entry) = self.entry_for_path(path) {
+            return Some(entry);
+        }
+        if self.is_case_sensitive() {
+            return None;
+        }
+
+        let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+        let parent_path = path.parent().unwrap_or(Path::new(""));
+        self.child_entries(parent_path).find(|entry| {
+            entry.path.file_name().map_or(false, |name| {
+                name.to_string_lossy().to_lowercase() == file_name
+            })
+        })
+    }
+
     pub fn replica_id(&self) -> ReplicaId {
         match self {
             Worktree::Local(_) => 0,
@@ -648,12 +697,13 @@ fn start_background_scan_tasks(
     cx: &mut ModelContext<'_, Worktree>,
 ) -> Vec<Task<()>> {
     let (scan_states_tx, mut scan_states_rx) = mpsc::unbounded();
+    let power_saving = PowerSaving::enabled_flag(cx);
     let background_scanner = cx.background_executor().spawn({
         let abs_path = abs_path.to_path_buf();
         let background = cx.background_executor().clone();
         async move {
             let events = fs.watch(&abs_path, Duration::from_millis(100)).await;
-            let case_sensitive = fs.is_case_sensitive().await.unwrap_or_else(|e| {
+            let case_sensitive = fs.is_case_sensitive(&abs_path).await.unwrap_or_else(|e| {
                 log::error!(
                     "Failed to determine whether filesystem is case sensitive (falling back to true) due to error: {e:#}"
                 );
@@ -669,6 +719,7 @@ fn start_background_scan_tasks(
                 background,
                 scan_requests_rx,
                 path_prefixes_to_scan_rx,
+                power_saving,
             )
             .run(events)
             .await;
@@ -732,7 +783,7 @@ impl LocalWorktree {
     ) -> Task<Result<Model<Buffer>>> {
         let path = Arc::from(path);
         cx.spawn(move |this, mut cx| async move {
-            let (file, contents, diff_base) = this
+            let (file, contents, encoding, diff_base) = this
                 .update(&mut cx, |t, cx| t.as_local().unwrap().load(&path, cx))?
                 .await?;
             let text_buffer = cx
@@ -746,6 +797,7 @@ impl LocalWorktree {
                     Some(Arc::new(file)),
                     Capability::ReadWrite,
                 )
+                .with_encoding(encoding)
             })
         })
     }
@@ -1014,18 +1066,36 @@ impl LocalWorktree {
         &self,
         path: &Path,
         cx: &mut ModelContext<Worktree>,
-    ) -> Task<Result<(File, String, Option<String>)>> {
+    ) -> Task<Result<(File, String, Encoding, Option<String>)>> {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
+        let project_settings = ProjectSettings::get_global(cx).clone();
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let len = fs
+                .metadata(&abs_path)
+                .await?
+                .map_or(0, |metadata| metadata.len);
+            let is_large_file = project_settings.is_large_file(len);
+            let (text, encoding) = if is_large_file {
+                let fs = fs.clone();
+                let abs_path = abs_path.clone();
+                cx.background_executor()
+                    .spawn(async move { load_large_file(fs.as_ref(), &abs_path, len).await })
+                    .await?
+            } else {
+                fs.load_with_encoding(&abs_path, None).await?
+            };
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
-            if let Some(repo) = snapshot.repository_for_path(&path) {
+            // Large files don't get a git diff, since computing it scales with their size.
+            if let Some(repo) = snapshot
+                .repository_for_path(&path)
+                .filter(|_| !is_large_file)
+            {
                 if let Some(repo_path) = repo.work_directory.relativize(&snapshot, &path).log_err()
                 {
                     if let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory) {
@@ -1059,6 +1129,7 @@ impl LocalWorktree {
                         is_private: entry.is_private,
                     },
                     text,
+                    encoding,
                     diff_base,
                 )),
                 None => {
@@ -1083,6 +1154,7 @@ impl LocalWorktree {
                             is_private,
                         },
                         text,
+                        encoding,
                         diff_base,
                     ))
                 }
@@ -1106,7 +1178,13 @@ impl LocalWorktree {
         let text = buffer.as_rope().clone();
         let fingerprint = text.fingerprint();
         let version = buffer.version();
-        let save = self.write_file(path.as_ref(), text, buffer.line_ending(), cx);
+        let save = self.write_file(
+            path.as_ref(),
+            text,
+            buffer.line_ending(),
+            buffer.encoding(),
+            cx,
+        );
         let fs = Arc::clone(&self.fs);
         let abs_path = self.absolutize(&path);
         let is_private = self.snapshot.is_path_private(&path);
@@ -1168,6 +1246,7 @@ impl LocalWorktree {
                     version: serialize_version(&version),
                     mtime: Some(mtime.into()),
                     fingerprint: serialize_fingerprint(fingerprint),
+                    conflict: None,
                 })?;
             }
 
@@ -1246,14 +1325,16 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Option<Entry>>> {
         let path: Arc<Path> = path.into();
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
-        let write = cx
-            .background_executor()
-            .spawn(async move { fs.save(&abs_path?, &text, line_ending).await });
+        let write = cx.background_executor().spawn(async move {
+            fs.save_with_encoding(&abs_path?, &text, line_ending, encoding)
+                .await
+        });
 
         cx.spawn(|this, mut cx| async move {
             write.await?;
@@ -1267,6 +1348,7 @@ impl LocalWorktree {
     pub fn delete_entry(
         &self,
         entry_id: ProjectEntryId,
+        trash: bool,
         cx: &mut ModelContext<Worktree>,
     ) -> Option<Task<Result<()>>> {
         let entry = self.entry_for_id(entry_id)?.clone();
@@ -1274,17 +1356,23 @@ impl LocalWorktree {
         let fs = self.fs.clone();
 
         let delete = cx.background_executor().spawn(async move {
+            let abs_path = abs_path?;
             if entry.is_file() {
-                fs.remove_file(&abs_path?, Default::default()).await?;
+                if trash {
+                    fs.trash_file(&abs_path, Default::default()).await?;
+                } else {
+                    fs.remove_file(&abs_path, Default::default()).await?;
+                }
             } else {
-                fs.remove_dir(
-                    &abs_path?,
-                    RemoveOptions {
-                        recursive: true,
-                        ignore_if_not_exists: false,
-                    },
-                )
-                .await?;
+                let options = RemoveOptions {
+                    recursive: true,
+                    ignore_if_not_exists: false,
+                };
+                if trash {
+                    fs.trash_dir(&abs_path, options).await?;
+                } else {
+                    fs.remove_dir(&abs_path, options).await?;
+                }
             }
             anyhow::Ok(entry.path)
         });
@@ -1313,6 +1401,7 @@ impl LocalWorktree {
             None => return Task::ready(Ok(None)),
         };
         let new_path = new_path.into();
+        let lowest_ancestor = self.lowest_ancestor(&new_path);
         let abs_old_path = self.absolutize(&old_path);
         let abs_new_path = self.absolutize(&new_path);
         let fs = self.fs.clone();
@@ -1321,6 +1410,13 @@ impl LocalWorktree {
             let abs_old_path = abs_old_path?;
             let abs_new_path = abs_new_path?;
 
+            // Renaming to a path in directories that don't exist yet moves the entry there.
+            if let Some(abs_new_parent) = abs_new_path.parent() {
+                if fs.metadata(abs_new_parent).await?.is_none() {
+                    fs.create_dir(abs_new_parent).await?;
+                }
+            }
+
             let abs_old_path_lower = abs_old_path.to_str().map(|p| p.to_lowercase());
             let abs_new_path_lower = abs_new_path.to_str().map(|p| p.to_lowercase());
 
@@ -1343,12 +1439,33 @@ impl LocalWorktree {
 
         cx.spawn(|this, mut cx| async move {
             rename.await?;
-            this.update(&mut cx, |this, cx| {
-                this.as_local_mut()
-                    .unwrap()
-                    .refresh_entry(new_path.clone(), Some(old_path), cx)
-            })?
-            .await
+            let (result, refreshes) = this.update(&mut cx, |this, cx| {
+                let this = this.as_local_mut().unwrap();
+                let mut refreshes = Vec::new();
+                let created_paths = new_path
+                    .parent()
+                    .and_then(|new_parent| new_parent.strip_prefix(&lowest_ancestor).ok());
+                if let Some(created_paths) = created_paths {
+                    for created_path in created_paths.ancestors() {
+                        if created_path != Path::new("") {
+                            refreshes.push(this.refresh_entry(
+                                lowest_ancestor.join(created_path).into(),
+                                None,
+                                cx,
+                            ));
+                        }
+                    }
+                }
+                (
+                    this.refresh_entry(new_path.clone(), Some(old_path), cx),
+                    refreshes,
+                )
+            })?;
+            for refresh in refreshes {
+                refresh.await.log_err();
+            }
+
+            result.await
         })
     }
 
@@ -1543,6 +1660,45 @@ impl LocalWorktree {
     }
 }
 
+/// Reads a file that is opened in large-file mode a chunk at a time, so that its
+/// contents are never held in memory twice. Files that aren't UTF-8 are instead
+/// decoded as a whole, after detecting their encoding.
+async fn load_large_file(fs: &dyn Fs, abs_path: &Path, len: u64) -> Result<(String, Encoding)> {
+    let mut text = String::with_capacity(len as usize);
+    let mut encoding = Encoding::utf8();
+    let mut pending = Vec::new();
+    let mut offset = 0;
+    loop {
+        let chunk = fs
+            .load_range(abs_path, offset, LARGE_FILE_CHUNK_SIZE)
+            .await?;
+        if chunk.is_empty() {
+            break;
+        }
+        if offset == 0 && chunk.starts_with(b"\xEF\xBB\xBF") {
+            encoding = Encoding::utf8_with_bom();
+            pending.extend_from_slice(&chunk[3..]);
+        } else {
+            pending.extend_from_slice(&chunk);
+        }
+        offset += chunk.len() as u64;
+
+        // A character may be split across chunks, in which case its first bytes
+        // are kept until the next chunk is read.
+        let valid_len = match std::str::from_utf8(&pending) {
+            Ok(valid) => valid.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(_) => return fs.load_with_encoding(abs_path, None).await,
+        };
+        text.push_str(std::str::from_utf8(&pending[..valid_len])?);
+        pending.drain(..valid_len);
+    }
+    if !pending.is_empty() {
+        return fs.load_with_encoding(abs_path, None).await;
+    }
+    Ok((text, encoding))
+}
+
 impl RemoteWorktree {
     fn snapshot(&self) -> Snapshot {
         self.snapshot.clone()
@@ -1572,6 +1728,15 @@ impl RemoteWorktree {
                     version: serialize_version(&version),
                 })
                 .await?;
+            match response.conflict.and_then(proto::SaveConflict::from_i32) {
+                Some(proto::SaveConflict::UnsavedHostChanges) => Err(anyhow!(
+                    "the host has changes to this file that haven't reached you yet"
+                ))?,
+                Some(proto::SaveConflict::FileChangedOnDisk) => {
+                    Err(anyhow!("this file has changed on the host's disk"))?
+                }
+                None => {}
+            }
             let version = deserialize_version(&response.version);
             let fingerprint = deserialize_fingerprint(&response.fingerprint)?;
             let mtime = response
@@ -2258,6 +2423,7 @@ impl LocalSnapshot {
 
     fn ignore_stack_for_abs_path(&self, abs_path: &Path, is_dir: bool) -> Arc<IgnoreStack> {
         let mut new_ignores = Vec::new();
+        let mut repository_excludes = None;
         for (index, ancestor) in abs_path.ancestors().enumerate() {
             if index > 0 {
                 if let Some((ignore, _)) = self.ignores_by_parent_abs_path.get(ancestor) {
@@ -2267,11 +2433,18 @@ impl LocalSnapshot {
                 }
             }
             if ancestor.join(&*DOT_GIT).is_dir() {
+                repository_excludes = self
+                    .repository_excludes
+                    .get(ancestor)
+                    .map(|excludes| (ancestor, excludes.clone()));
                 break;
             }
         }
 
         let mut ignore_stack = IgnoreStack::none();
+        if let Some((repository_root, excludes)) = repository_excludes {
+            ignore_stack = ignore_stack.append(repository_root.into(), excludes);
+        }
         for (parent_abs_path, ignore) in new_ignores.into_iter().rev() {
             if ignore_stack.is_abs_path_ignored(parent_abs_path, true) {
                 ignore_stack = IgnoreStack::all();
@@ -2404,7 +2577,7 @@ impl LocalSnapshot {
 
 impl BackgroundScannerState {
     fn should_scan_directory(&self, entry: &Entry) -> bool {
-        (!entry.is_external && !entry.is_ignored)
+        ((!entry.is_external || self.snapshot.scan_external_symlinks) && !entry.is_ignored)
             || entry.path.file_name() == Some(*DOT_GIT)
             || self.scanned_dirs.contains(&entry.id) // If we've ever scanned it, keep scanning
             || self
@@ -2745,6 +2918,61 @@ async fn build_gitignore(abs_path: &Path, fs: &dyn Fs) -> Result<Gitignore> {
     Ok(builder.build()?)
 }
 
+/// Builds the rules git applies to a whole repository in addition to its
+/// `.gitignore` files: the global excludes file and `.git/info/exclude`.
+async fn build_repository_excludes(repository_root: &Path, fs: &dyn Fs) -> Option<Gitignore> {
+    let excludes_paths = global_excludes_path(fs)
+        .await
+        .into_iter()
+        .chain([repository_root.join(&*DOT_GIT).join("info").join("exclude")]);
+    let mut builder = GitignoreBuilder::new(repository_root);
+    let mut has_excludes = false;
+    for excludes_path in excludes_paths {
+        if let Ok(contents) = fs.load(&excludes_path).await {
+            for line in contents.lines() {
+                builder
+                    .add_line(Some(excludes_path.clone()), line)
+                    .log_err();
+            }
+            has_excludes = true;
+        }
+    }
+    if has_excludes {
+        builder.build().log_err()
+    } else {
+        None
+    }
+}
+
+/// The global excludes file git uses. Unlike [`gitconfig_excludes_path`], this
+/// honors a `GIT_CONFIG_GLOBAL` that points git at a different global config.
+async fn global_excludes_path(fs: &dyn Fs) -> Option<PathBuf> {
+    let Some(config_path) = std::env::var_os("GIT_CONFIG_GLOBAL") else {
+        return gitconfig_excludes_path();
+    };
+    let config = fs.load(Path::new(&config_path)).await.ok();
+    let excludes_file = config.as_deref().and_then(|config| {
+        config.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            key.trim()
+                .eq_ignore_ascii_case("excludesfile")
+                .then(|| value.trim().trim_matches('"'))
+        })
+    });
+    match excludes_file {
+        Some(path) => match path.strip_prefix("~/") {
+            Some(path) => Some(util::paths::HOME.join(path)),
+            None => Some(PathBuf::from(path)),
+        },
+        None => {
+            let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| util::paths::HOME.join(".config"));
+            Some(config_dir.join("git").join("ignore"))
+        }
+    }
+}
+
 impl WorktreeId {
     pub fn from_usize(handle_id: usize) -> Self {
         Self(handle_id)
@@ -2927,12 +3155,16 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &AppContext) -> Task<Result<String>> {
+    fn load(
+        &self,
+        encoding: Option<Encoding>,
+        cx: &AppContext,
+    ) -> Task<Result<(String, Encoding)>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
         cx.background_executor()
-            .spawn(async move { fs.load(&abs_path?).await })
+            .spawn(async move { fs.load_with_encoding(&abs_path?, encoding).await })
     }
 
     fn buffer_reloaded(
@@ -2941,6 +3173,7 @@ impl language::LocalFile for File {
         version: &clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: SystemTime,
         cx: &mut AppContext,
     ) {
@@ -2955,6 +3188,7 @@ impl language::LocalFile for File {
                     mtime: Some(mtime.into()),
                     fingerprint: serialize_fingerprint(fingerprint),
                     line_ending: serialize_line_ending(line_ending) as i32,
+                    encoding: Some(serialize_encoding(encoding)),
                 })
                 .log_err();
         }
@@ -3024,6 +3258,8 @@ pub struct Entry {
     pub path: Arc<Path>,
     pub inode: u64,
     pub mtime: SystemTime,
+    /// The size of the file in bytes, or zero for directories.
+    pub size: u64,
     pub is_symlink: bool,
 
     /// Whether this entry is ignored by Git.
@@ -3094,6 +3330,7 @@ impl Entry {
             path,
             inode: metadata.inode,
             mtime: metadata.mtime,
+            size: metadata.len,
             is_symlink: metadata.is_symlink,
             is_ignored: false,
             is_external: false,
@@ -3284,6 +3521,7 @@ struct BackgroundScanner {
     path_prefixes_to_scan_rx: channel::Receiver<Arc<Path>>,
     next_entry_id: Arc<AtomicUsize>,
     phase: BackgroundScannerPhase,
+    power_saving: Arc<AtomicBool>,
 }
 
 #[derive(PartialEq)]
@@ -3303,6 +3541,7 @@ impl BackgroundScanner {
         executor: BackgroundExecutor,
         scan_requests_rx: channel::Receiver<ScanRequest>,
         path_prefixes_to_scan_rx: channel::Receiver<Arc<Path>>,
+        power_saving: Arc<AtomicBool>,
     ) -> Self {
         Self {
             fs,
@@ -3322,6 +3561,7 @@ impl BackgroundScanner {
                 changed_paths: Default::default(),
             }),
             phase: BackgroundScannerPhase::InitialScan,
+            power_saving,
         }
     }
 
@@ -3344,6 +3584,14 @@ impl BackgroundScanner {
             }
             if ancestor.join(&*DOT_GIT).is_dir() {
                 // Reached root of git repository.
+                if let Some(excludes) = build_repository_excludes(ancestor, self.fs.as_ref()).await
+                {
+                    self.state
+                        .lock()
+                        .snapshot
+                        .repository_excludes
+                        .insert(ancestor.into(), Arc::new(excludes));
+                }
                 break;
             }
         }
@@ -3423,6 +3671,11 @@ impl BackgroundScanner {
                     let Some(events) = events else { break };
                     let mut paths = fs::fs_events_paths(events);
 
+                    // When saving power, wait for further events so that bursts of changes
+                    // are processed in fewer, larger batches.
+                    if self.power_saving.load(SeqCst) {
+                        self.executor.timer(POWER_SAVING_FS_EVENTS_DELAY).await;
+                    }
                     while let Poll::Ready(Some(more_events)) = futures::poll!(fs_events_rx.next()) {
                         paths.extend(fs::fs_events_paths(more_events));
                     }
@@ -3720,8 +3973,49 @@ impl BackgroundScanner {
         let mut root_canonical_path = None;
         let mut new_entries: Vec<Entry> = Vec::new();
         let mut new_jobs: Vec<Option<ScanJob>> = Vec::new();
-        let mut child_paths = self.fs.read_dir(&job.abs_path).await?;
-        while let Some(child_abs_path) = child_paths.next().await {
+        let child_paths = self
+            .fs
+            .read_dir(&job.abs_path)
+            .await?
+            .collect::<Vec<_>>()
+            .await;
+
+        // Load the excludes of repositories nested in the worktree before any of their
+        // `.gitignore` files, which take precedence over them.
+        let contains_dot_git = child_paths.iter().any(|child_abs_path| {
+            child_abs_path
+                .as_ref()
+                .is_ok_and(|child_abs_path| child_abs_path.file_name() == Some(*DOT_GIT))
+        });
+        let has_excludes = self
+            .state
+            .lock()
+            .snapshot
+            .repository_excludes
+            .contains_key(&job.abs_path);
+        if contains_dot_git
+            && !has_excludes
+            && self
+                .fs
+                .metadata(&job.abs_path.join(*DOT_GIT))
+                .await
+                .ok()
+                .flatten()
+                .is_some_and(|metadata| metadata.is_dir)
+        {
+            if let Some(excludes) = build_repository_excludes(&job.abs_path, self.fs.as_ref()).await
+            {
+                let excludes = Arc::new(excludes);
+                self.state
+                    .lock()
+                    .snapshot
+                    .repository_excludes
+                    .insert(job.abs_path.clone(), excludes.clone());
+                ignore_stack = ignore_stack.append(job.abs_path.clone(), excludes);
+            }
+        }
+
+        for child_abs_path in child_paths {
             let child_abs_path: Arc<Path> = match child_abs_path {
                 Ok(child_abs_path) => child_abs_path.into(),
                 Err(error) => {
@@ -3803,19 +4097,7 @@ impl BackgroundScanner {
 
             if job.is_external {
                 child_entry.is_external = true;
-            } else if child_metadata.is_symlink {
-                let canonical_path = match self.fs.canonicalize(&child_abs_path).await {
-                    Ok(path) => path,
-                    Err(err) => {
-                        log::error!(
-                            "error reading target of symlink {:?}: {:?}",
-                            child_abs_path,
-                            err
-                        );
-                        continue;
-                    }
-                };
-
+            } else if let Some(canonical_path) = &child_metadata.canonical_path {
                 // lazily canonicalize the root path in order to determine if
                 // symlinks point outside of the worktree.
                 let root_canonical_path = match &root_canonical_path {
@@ -4070,30 +4352,51 @@ impl BackgroundScanner {
         let mut snapshot = self.state.lock().snapshot.clone();
         let mut ignores_to_update = Vec::new();
         let mut ignores_to_delete = Vec::new();
+        let mut updated_ignores = Vec::new();
         let abs_path = snapshot.abs_path.clone();
-        for (parent_abs_path, (_, needs_update)) in &mut snapshot.ignores_by_parent_abs_path {
+        for (parent_abs_path, (ignore, needs_update)) in &mut snapshot.ignores_by_parent_abs_path {
             if let Ok(parent_path) = parent_abs_path.strip_prefix(&abs_path) {
-                if *needs_update {
-                    *needs_update = false;
-                    if snapshot.snapshot.entry_for_path(parent_path).is_some() {
-                        ignores_to_update.push(parent_abs_path.clone());
-                    }
-                }
-
+                let parent_exists = snapshot.snapshot.entry_for_path(parent_path).is_some();
                 let ignore_path = parent_path.join(&*GITIGNORE);
                 if snapshot.snapshot.entry_for_path(ignore_path).is_none() {
+                    // The subtree of a deleted .gitignore always needs to be
+                    // re-evaluated, even if its removal raced with this update.
                     ignores_to_delete.push(parent_abs_path.clone());
+                    if parent_exists {
+                        ignores_to_update.push(parent_abs_path.clone());
+                    }
+                } else if *needs_update {
+                    *needs_update = false;
+                    updated_ignores.push((parent_abs_path.clone(), ignore.clone()));
+                    if parent_exists {
+                        ignores_to_update.push(parent_abs_path.clone());
+                    }
                 }
             }
         }
 
-        for parent_abs_path in ignores_to_delete {
-            snapshot.ignores_by_parent_abs_path.remove(&parent_abs_path);
-            self.state
-                .lock()
-                .snapshot
-                .ignores_by_parent_abs_path
-                .remove(&parent_abs_path);
+        {
+            let mut state = self.state.lock();
+            // Only re-evaluate each changed .gitignore once, unless it has been
+            // reloaded again since this update began.
+            for (parent_abs_path, ignore) in updated_ignores {
+                if let Some((current_ignore, needs_update)) = state
+                    .snapshot
+                    .ignores_by_parent_abs_path
+                    .get_mut(&parent_abs_path)
+                {
+                    if Arc::ptr_eq(current_ignore, &ignore) {
+                        *needs_update = false;
+                    }
+                }
+            }
+            for parent_abs_path in ignores_to_delete {
+                snapshot.ignores_by_parent_abs_path.remove(&parent_abs_path);
+                state
+                    .snapshot
+                    .ignores_by_parent_abs_path
+                    .remove(&parent_abs_path);
+            }
         }
 
         let (ignore_queue_tx, ignore_queue_rx) = channel::unbounded();
@@ -4654,6 +4957,7 @@ impl<'a> From<&'a Entry> for proto::Entry {
             path: entry.path.to_string_lossy().into(),
             inode: entry.inode,
             mtime: Some(entry.mtime.into()),
+            size: entry.size,
             is_symlink: entry.is_symlink,
             is_ignored: entry.is_ignored,
             is_external: entry.is_external,
@@ -4681,6 +4985,7 @@ impl<'a> TryFrom<(&'a CharBag, proto::Entry)> for Entry {
                 path,
                 inode: entry.inode,
                 mtime: mtime.into(),
+                size: entry.size,
                 is_symlink: entry.is_symlink,
                 is_ignored: entry.is_ignored,
                 is_external: entry.is_external,
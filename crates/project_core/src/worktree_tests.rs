@@ -445,6 +445,68 @@ async fn test_symlinks_pointing_outside(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_scanning_external_symlinks(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |project_settings| {
+                project_settings.scan_external_symlinks = true;
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "dir1": {
+                "deps": {},
+                "src": {
+                    "a.rs": "",
+                },
+            },
+            "dir2": {
+                "b.rs": "",
+            }
+        }),
+    )
+    .await;
+
+    // An external directory containing a symlink back to one of its ancestors.
+    fs.insert_symlink("/root/dir1/deps/dep-dir2", "../../dir2".into())
+        .await;
+    fs.insert_symlink("/root/dir2/root", "..".into()).await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root/dir1"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    tree.read_with(cx, |tree, _| {
+        let dep_dir = tree.entry_for_path("deps/dep-dir2").unwrap();
+        assert!(dep_dir.is_symlink);
+        assert!(dep_dir.is_external);
+        assert_eq!(dep_dir.kind, EntryKind::Dir);
+
+        let dep_file = tree.entry_for_path("deps/dep-dir2/b.rs").unwrap();
+        assert!(dep_file.is_external);
+
+        // The cycle back to the worktree's root is not followed forever.
+        assert!(tree
+            .entry_for_path("deps/dep-dir2/root/dir1/deps/dep-dir2/b.rs")
+            .is_none());
+    });
+}
+
 #[cfg(target_os = "macos")]
 #[gpui::test]
 async fn test_renaming_case_only(cx: &mut TestAppContext) {
@@ -793,6 +855,99 @@ async fn test_dirs_no_longer_ignored(cx: &mut TestAppContext) {
     assert_eq!(read_dir_count_3 - read_dir_count_2, 2);
 }
 
+#[gpui::test]
+async fn test_nested_gitignore_changes(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "a": {
+                "one.txt": "",
+                "two.log": "",
+            },
+            "b": {
+                "three.log": "",
+            },
+        }),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    let changed_paths = Arc::new(Mutex::new(Vec::<Arc<Path>>::new()));
+    tree.update(cx, |_, cx| {
+        let changed_paths = changed_paths.clone();
+        cx.subscribe(&cx.handle(), move |_, _, event, _| {
+            if let Event::UpdatedEntries(changes) = event {
+                changed_paths
+                    .lock()
+                    .extend(changes.iter().map(|(path, _, _)| path.clone()));
+            }
+        })
+        .detach();
+    });
+    let ignored_paths = |cx: &mut TestAppContext| {
+        tree.read_with(cx, |tree, _| {
+            tree.entries(true)
+                .filter(|entry| entry.is_ignored)
+                .map(|entry| entry.path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // Creating a .gitignore ignores matching files in its subtree.
+    fs.save(
+        "/root/a/.gitignore".as_ref(),
+        &"*.log\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(ignored_paths(cx), ["a/two.log"]);
+    assert!(changed_paths
+        .lock()
+        .contains(&Path::new("a/two.log").into()));
+
+    // Editing it re-evaluates the subtree.
+    changed_paths.lock().clear();
+    fs.save(
+        "/root/a/.gitignore".as_ref(),
+        &"*.txt\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(ignored_paths(cx), ["a/one.txt"]);
+    let changes = mem::take(&mut *changed_paths.lock());
+    assert!(changes.contains(&Path::new("a/one.txt").into()));
+    assert!(changes.contains(&Path::new("a/two.log").into()));
+    assert!(!changes.contains(&Path::new("b/three.log").into()));
+
+    // Deleting it un-ignores the files again.
+    fs.remove_file("/root/a/.gitignore".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(ignored_paths(cx), Vec::<String>::new());
+    assert!(changed_paths
+        .lock()
+        .contains(&Path::new("a/one.txt").into()));
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_rescan_with_gitignore(cx: &mut TestAppContext) {
     init_test(cx);
@@ -936,6 +1091,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("tracked-dir/file.txt"),
             "hello".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -946,6 +1102,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("ignored-dir/file.txt"),
             "world".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -960,6 +1117,93 @@ async fn test_write_file(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_repository_excludes(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+    let dir = temp_tree(json!({
+        "config": {
+            "gitconfig": "",
+            "ignore": "globally-excluded-file\n",
+        },
+        "tree": {
+            ".git": {
+                "info": {
+                    "exclude": "excluded-file\nreincluded-file\n",
+                },
+            },
+            ".gitignore": "ignored-file\n!reincluded-file\n",
+            "tracked-file": "",
+            "ignored-file": "",
+            "excluded-file": "",
+            "globally-excluded-file": "",
+            "reincluded-file": "",
+            "nested": {
+                ".git": {
+                    "info": {
+                        "exclude": "nested-excluded-file\n",
+                    },
+                },
+                "nested-excluded-file": "",
+                "globally-excluded-file": "",
+                "excluded-file": "",
+            },
+        },
+    }));
+
+    // Point git at a global config in the test directory, so the user's own
+    // excludes don't affect the test.
+    let config_dir = dir.path().join("config");
+    std::fs::write(
+        config_dir.join("gitconfig"),
+        format!(
+            "[core]\n\texcludesFile = {}\n",
+            config_dir.join("ignore").display()
+        ),
+    )
+    .unwrap();
+    std::env::set_var("GIT_CONFIG_GLOBAL", config_dir.join("gitconfig"));
+
+    let tree = Worktree::local(
+        build_client(cx),
+        dir.path().join("tree"),
+        true,
+        Arc::new(RealFs),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    tree.read_with(cx, |tree, _| {
+        assert!(!tree.entry_for_path("tracked-file").unwrap().is_ignored);
+        assert!(tree.entry_for_path("ignored-file").unwrap().is_ignored);
+        assert!(tree.entry_for_path("excluded-file").unwrap().is_ignored);
+        assert!(
+            tree.entry_for_path("globally-excluded-file")
+                .unwrap()
+                .is_ignored
+        );
+        // Rules in `.gitignore` files take precedence over `.git/info/exclude`.
+        assert!(!tree.entry_for_path("reincluded-file").unwrap().is_ignored);
+
+        // Nested repositories have their own excludes.
+        assert!(
+            tree.entry_for_path("nested/nested-excluded-file")
+                .unwrap()
+                .is_ignored
+        );
+        assert!(
+            tree.entry_for_path("nested/globally-excluded-file")
+                .unwrap()
+                .is_ignored
+        );
+    });
+    std::env::remove_var("GIT_CONFIG_GLOBAL");
+}
+
 #[gpui::test]
 async fn test_file_scan_exclusions(cx: &mut TestAppContext) {
     init_test(cx);
@@ -1684,7 +1928,7 @@ fn randomly_mutate_worktree(
     match rng.gen_range(0_u32..100) {
         0..=33 if entry.path.as_ref() != Path::new("") => {
             log::info!("deleting entry {:?} ({})", entry.path, entry.id.0);
-            worktree.delete_entry(entry.id, cx).unwrap()
+            worktree.delete_entry(entry.id, false, cx).unwrap()
         }
         ..=66 if entry.path.as_ref() != Path::new("") => {
             let other_entry = snapshot.entries(false).choose(rng).unwrap();
@@ -1726,8 +1970,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_executor().spawn(async move {
                     task.await?;
                     Ok(())
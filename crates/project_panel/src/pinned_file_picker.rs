@@ -0,0 +1,195 @@
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusableView, Render, Task, View, ViewContext,
+    VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::ProjectPath;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::OpenPinnedFile;
+
+pub(crate) fn toggle(
+    workspace: &mut Workspace,
+    _: &OpenPinnedFile,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let pinned_paths = project
+        .all_pinned_paths()
+        .filter_map(|path| {
+            let worktree = project.worktree_for_id(path.worktree_id, cx)?;
+            let label =
+                worktree.read(cx).root_name().to_string() + "/" + &path.path.to_string_lossy();
+            Some((path.clone(), label))
+        })
+        .collect::<Vec<_>>();
+    let workspace_handle = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| {
+        let delegate =
+            PinnedFilePickerDelegate::new(cx.view().downgrade(), workspace_handle, pinned_paths);
+        PinnedFilePicker::new(delegate, cx)
+    });
+}
+
+pub struct PinnedFilePicker {
+    picker: View<Picker<PinnedFilePickerDelegate>>,
+}
+
+impl FocusableView for PinnedFilePicker {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for PinnedFilePicker {}
+impl ModalView for PinnedFilePicker {}
+
+impl PinnedFilePicker {
+    fn new(delegate: PinnedFilePickerDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for PinnedFilePicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct PinnedFilePickerDelegate {
+    view: WeakView<PinnedFilePicker>,
+    workspace: WeakView<Workspace>,
+    /// The pinned paths, along with the labels shown for them.
+    pinned_paths: Vec<(ProjectPath, String)>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PinnedFilePickerDelegate {
+    fn new(
+        view: WeakView<PinnedFilePicker>,
+        workspace: WeakView<Workspace>,
+        pinned_paths: Vec<(ProjectPath, String)>,
+    ) -> Self {
+        Self {
+            view,
+            workspace,
+            pinned_paths,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for PinnedFilePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Open a pinned file...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .pinned_paths
+            .iter()
+            .enumerate()
+            .map(|(id, (_, label))| StringMatchCandidate {
+                id,
+                char_bag: label.as_str().into(),
+                string: label.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, _| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(path_match) = self.matches.get(self.selected_index) {
+            let (path, _) = self.pinned_paths[path_match.candidate_id].clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    workspace
+                        .open_path(path, None, true, cx)
+                        .detach_and_log_err(cx);
+                })
+                .log_err();
+        }
+
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let path_match = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    path_match.string.clone(),
+                    path_match.positions.clone(),
+                )),
+        )
+    }
+}
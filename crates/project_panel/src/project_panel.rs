@@ -1,4 +1,5 @@
 pub mod file_associations;
+mod pinned_file_picker;
 mod project_panel_settings;
 use client::{ErrorCode, ErrorExt};
 use settings::Settings;
@@ -8,30 +9,39 @@ use editor::{actions::Cancel, scroll::Autoscroll, Editor};
 use file_associations::FileAssociations;
 
 use anyhow::{anyhow, Result};
-use collections::{hash_map, HashMap};
+use collections::{hash_map, BTreeSet, HashMap};
 use gpui::{
     actions, div, overlay, px, uniform_list, Action, AppContext, AssetSource, AsyncWindowContext,
     ClipboardItem, DismissEvent, Div, EventEmitter, FocusHandle, FocusableView, InteractiveElement,
-    KeyContext, Model, MouseButton, MouseDownEvent, ParentElement, Pixels, Point, PromptLevel,
-    Render, Stateful, Styled, Subscription, Task, UniformListScrollHandle, View, ViewContext,
-    VisualContext as _, WeakView, WindowContext,
+    KeyContext, Model, MouseButton, MouseDownEvent, ParentElement, PathPromptOptions, Pixels,
+    Point, PromptLevel, Render, Stateful, Styled, Subscription, Task, UniformListScrollHandle,
+    View, ViewContext, VisualContext as _, WeakView, WindowContext,
 };
 use menu::{Confirm, SelectNext, SelectPrev};
+use postage::stream::Stream as _;
 use project::{
-    repository::GitFileStatus, Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath,
-    Worktree, WorktreeId,
+    repository::GitFileStatus, DownloadProgress, Entry, EntryKind, FileDownload, Fs, Project,
+    ProjectEntryId, ProjectPath, Worktree, WorktreeId,
 };
 use project_panel_settings::{ProjectPanelDockPosition, ProjectPanelSettings};
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, ffi::OsStr, ops::Range, path::Path, sync::Arc};
+use std::{
+    cmp::Ordering,
+    ffi::OsStr,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
 use theme::ThemeSettings;
-use ui::{prelude::*, v_flex, ContextMenu, Icon, KeyBinding, Label, ListItem};
+use time::OffsetDateTime;
+use ui::{prelude::*, v_flex, ContextMenu, Icon, KeyBinding, Label, ListItem, Tooltip};
 use unicase::UniCase;
 use util::{maybe, ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     notifications::DetachAndPromptErr,
-    Workspace,
+    Workspace, WORKSPACE_DB,
 };
 
 const PROJECT_PANEL_KEY: &'static str = "ProjectPanel";
@@ -46,6 +56,8 @@ pub struct ProjectPanel {
     last_worktree_root_id: Option<ProjectEntryId>,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
     selection: Option<Selection>,
+    /// Entries marked with shift-click, which are moved together when one of them is dragged.
+    marked_entries: BTreeSet<ProjectEntryId>,
     context_menu: Option<(View<ContextMenu>, Point<Pixels>, Subscription)>,
     edit_state: Option<EditState>,
     filename_editor: View<Editor>,
@@ -54,6 +66,17 @@ pub struct ProjectPanel {
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
+    /// Pinned paths restored from the last session whose worktrees haven't been added yet.
+    unresolved_pinned_paths: Vec<PathBuf>,
+    pending_pinned_serialization: Task<Option<()>>,
+    /// Entries being downloaded from the host of a shared project.
+    downloads: HashMap<ProjectEntryId, ActiveDownload>,
+}
+
+struct ActiveDownload {
+    progress: DownloadProgress,
+    _observe_progress: Task<()>,
+    _download: Task<()>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -98,6 +121,9 @@ pub struct EntryDetails {
     is_cut: bool,
     git_status: Option<GitFileStatus>,
     is_dotenv: bool,
+    /// The file's size in bytes, or `None` for directories.
+    size: Option<u64>,
+    mtime: SystemTime,
 }
 
 actions!(
@@ -120,6 +146,10 @@ actions!(
         Open,
         ToggleFocus,
         NewSearchInDirectory,
+        TogglePinned,
+        OpenPinnedFile,
+        DownloadEntry,
+        CancelDownload,
     ]
 );
 
@@ -135,6 +165,7 @@ pub fn init(assets: impl AssetSource, cx: &mut AppContext) {
         workspace.register_action(|workspace, _: &ToggleFocus, cx| {
             workspace.toggle_panel_focus::<ProjectPanel>(cx);
         });
+        workspace.register_action(pinned_file_picker::toggle);
     })
     .detach();
 }
@@ -193,6 +224,16 @@ impl ProjectPanel {
                     this.update_visible_entries(None, cx);
                     cx.notify();
                 }
+                project::Event::WorktreeAdded => {
+                    this.restore_pinned_paths(cx);
+                }
+                project::Event::PinnedPathsChanged => {
+                    this.serialize_pinned_paths(cx);
+                    cx.notify();
+                }
+                project::Event::CollaboratorActivePathsChanged => {
+                    cx.notify();
+                }
                 _ => {}
             })
             .detach();
@@ -236,10 +277,14 @@ impl ProjectPanel {
                 context_menu: None,
                 filename_editor,
                 clipboard_entry: None,
+                marked_entries: BTreeSet::new(),
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
                 width: None,
                 pending_serialization: Task::ready(None),
+                unresolved_pinned_paths: Vec::new(),
+                pending_pinned_serialization: Task::ready(None),
+                downloads: HashMap::default(),
             };
             this.update_visible_entries(None, cx);
 
@@ -322,6 +367,27 @@ impl ProjectPanel {
         workspace: WeakView<Workspace>,
         mut cx: AsyncWindowContext,
     ) -> Result<View<Self>> {
+        let workspace_id = workspace.update(&mut cx, |workspace, cx| {
+            workspace
+                .project()
+                .read(cx)
+                .is_local()
+                .then(|| workspace.database_id())
+        })?;
+        let pinned_paths = cx
+            .background_executor()
+            .spawn(async move {
+                let Some(workspace_id) = workspace_id else {
+                    return Ok(Vec::new());
+                };
+                let mut pinned_paths = WORKSPACE_DB.pinned_paths(workspace_id)?;
+                // Files deleted while the workspace was closed are no longer pinned.
+                pinned_paths.retain(|path| path.exists());
+                anyhow::Ok(pinned_paths)
+            })
+            .await
+            .log_err()
+            .unwrap_or_default();
         let serialized_panel = cx
             .background_executor()
             .spawn(async move { KEY_VALUE_STORE.read_kvp(PROJECT_PANEL_KEY) })
@@ -342,6 +408,12 @@ impl ProjectPanel {
                     cx.notify();
                 });
             }
+            if !pinned_paths.is_empty() {
+                panel.update(cx, |panel, cx| {
+                    panel.unresolved_pinned_paths = pinned_paths;
+                    panel.restore_pinned_paths(cx);
+                });
+            }
             panel
         })
     }
@@ -362,6 +434,55 @@ impl ProjectPanel {
         );
     }
 
+    fn restore_pinned_paths(&mut self, cx: &mut ViewContext<Self>) {
+        if self.unresolved_pinned_paths.is_empty() {
+            return;
+        }
+
+        let project = self.project.read(cx);
+        let mut pinned_paths = project.pinned_paths().to_vec();
+        self.unresolved_pinned_paths.retain(|abs_path| {
+            let Some((worktree, path)) = project.find_local_worktree(abs_path, cx) else {
+                return true;
+            };
+            let project_path = ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: path.into(),
+            };
+            if !pinned_paths.contains(&project_path) {
+                pinned_paths.push(project_path);
+            }
+            false
+        });
+        self.project
+            .update(cx, |project, cx| project.set_pinned_paths(pinned_paths, cx));
+    }
+
+    fn serialize_pinned_paths(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        if !project.is_local() {
+            return;
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let workspace_id = workspace.read(cx).database_id();
+        let abs_paths = project
+            .pinned_paths()
+            .iter()
+            .filter_map(|path| {
+                let worktree = project.worktree_for_id(path.worktree_id, cx)?;
+                Some(worktree.read(cx).abs_path().join(&path.path))
+            })
+            .chain(self.unresolved_pinned_paths.iter().cloned())
+            .collect::<Vec<_>>();
+        self.pending_pinned_serialization = cx.background_executor().spawn(
+            WORKSPACE_DB
+                .set_pinned_paths(workspace_id, abs_paths)
+                .log_err(),
+        );
+    }
+
     fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
         if !self.focus_handle.contains_focused(cx) {
             cx.emit(Event::Focus);
@@ -394,6 +515,15 @@ impl ProjectPanel {
             let worktree_id = worktree.id();
             let is_local = project.is_local();
             let is_read_only = project.is_read_only();
+            let is_downloading = self.downloads.contains_key(&entry.id);
+            let pin_label = if project.is_pinned(&ProjectPath {
+                worktree_id,
+                path: entry.path.clone(),
+            }) {
+                "Unpin"
+            } else {
+                "Pin"
+            };
 
             let context_menu = ContextMenu::build(cx, |menu, cx| {
                 menu.context(self.focus_handle.clone()).when_else(
@@ -403,6 +533,15 @@ impl ProjectPanel {
                             .when(is_dir, |menu| {
                                 menu.action("Search Inside", Box::new(NewSearchInDirectory))
                             })
+                            .when(!is_dir, |menu| {
+                                menu.action(pin_label, Box::new(TogglePinned))
+                            })
+                            .separator()
+                            .when_else(
+                                is_downloading,
+                                |menu| menu.action("Cancel Download", Box::new(CancelDownload)),
+                                |menu| menu.action("Download…", Box::new(DownloadEntry)),
+                            )
                     },
                     |menu| {
                         menu.when(is_local, |menu| {
@@ -441,9 +580,19 @@ impl ProjectPanel {
                             menu.action("Open in Terminal", Box::new(OpenInTerminal))
                                 .action("Search Inside", Box::new(NewSearchInDirectory))
                         })
+                        .when(!is_dir, |menu| {
+                            menu.separator().action(pin_label, Box::new(TogglePinned))
+                        })
                         .separator()
                         .action("Rename", Box::new(Rename))
                         .when(!is_root, |menu| menu.action("Delete", Box::new(Delete)))
+                        .when(!is_local, |menu| {
+                            menu.separator().when_else(
+                                is_downloading,
+                                |menu| menu.action("Cancel Download", Box::new(CancelDownload)),
+                                |menu| menu.action("Download…", Box::new(DownloadEntry)),
+                            )
+                        })
                     },
                 )
             });
@@ -599,7 +748,16 @@ impl ProjectPanel {
         let entry = worktree.read(cx).entry_for_id(edit_state.entry_id)?.clone();
         let filename = self.filename_editor.read(cx).text(cx);
 
-        let path_already_exists = |path| worktree.read(cx).entry_for_path(path).is_some();
+        // On case-insensitive file systems, entries can be renamed to a different casing of
+        // their own name, but not to one of another entry's.
+        let path_already_exists = |path: &Path| {
+            worktree
+                .read(cx)
+                .conflicting_entry_for_path(path)
+                .map_or(false, |existing| {
+                    existing.id != entry.id || existing.path.as_ref() == path
+                })
+        };
         let edit_task;
         let edited_entry_id;
         if is_new_entry {
@@ -617,18 +775,40 @@ impl ProjectPanel {
                 project.create_entry((worktree_id, &new_path), is_dir, cx)
             });
         } else {
+            // Names containing separators move the entry, relative to its current directory.
+            let relative_path = Path::new(filename.trim_start_matches("/"));
+            if relative_path
+                .components()
+                .any(|component| component == Component::ParentDir)
+            {
+                return None;
+            }
             let new_path = if let Some(parent) = entry.path.clone().parent() {
-                parent.join(&filename)
+                parent.join(relative_path)
             } else {
-                filename.clone().into()
+                relative_path.to_path_buf()
             };
             if path_already_exists(new_path.as_path()) {
                 return None;
             }
 
             edited_entry_id = entry.id;
-            edit_task = self.project.update(cx, |project, cx| {
-                project.rename_entry(entry.id, new_path.as_path(), cx)
+            let rename_task = self.project.update(cx, |project, cx| {
+                project.rename_entries(vec![(entry.id, new_path.into())], cx)
+            });
+            let workspace = self.workspace.clone();
+            edit_task = cx.spawn(|_, cx| async move {
+                let (mut entries, transaction) = rename_task.await?;
+                if !transaction.0.is_empty() {
+                    Editor::preview_project_transaction(
+                        workspace,
+                        transaction,
+                        "Rename".into(),
+                        cx,
+                    )
+                    .await?;
+                }
+                Ok(entries.pop().flatten())
             });
         };
 
@@ -793,13 +973,27 @@ impl ProjectPanel {
             let Selection { entry_id, .. } = self.selection?;
             let path = self.project.read(cx).path_for_entry(entry_id, cx)?.path;
             let file_name = path.file_name()?;
-
-            let answer = cx.prompt(
-                PromptLevel::Info,
-                &format!("Delete {file_name:?}?"),
-                None,
-                &["Delete", "Cancel"],
-            );
+            let answer = match self.project.read(cx).deletes_to_trash(cx) {
+                Some(true) => cx.prompt(
+                    PromptLevel::Info,
+                    &format!("Move {file_name:?} to the trash?"),
+                    None,
+                    &["Move to Trash", "Cancel"],
+                ),
+                Some(false) => cx.prompt(
+                    PromptLevel::Info,
+                    &format!("Permanently delete {file_name:?}?"),
+                    None,
+                    &["Delete", "Cancel"],
+                ),
+                // The host decides whether a guest's deletes go to the trash.
+                None => cx.prompt(
+                    PromptLevel::Info,
+                    &format!("Delete {file_name:?} from the shared project?"),
+                    None,
+                    &["Delete", "Cancel"],
+                ),
+            };
 
             cx.spawn(|this, mut cx| async move {
                 if answer.await != Ok(0) {
@@ -952,6 +1146,120 @@ impl ProjectPanel {
         });
     }
 
+    fn toggle_pinned(&mut self, _: &TogglePinned, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if entry.is_dir() {
+                return;
+            }
+            let project_path = ProjectPath {
+                worktree_id: worktree.id(),
+                path: entry.path.clone(),
+            };
+            self.project.update(cx, |project, cx| {
+                project.toggle_pinned_path(project_path, cx)
+            });
+        }
+    }
+
+    fn download_entry(&mut self, _: &DownloadEntry, cx: &mut ViewContext<Self>) {
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        if self.project.read(cx).is_local() || self.downloads.contains_key(&entry.id) {
+            return;
+        }
+
+        let entry_id = entry.id;
+        let project_path = ProjectPath {
+            worktree_id: worktree.id(),
+            path: entry.path.clone(),
+        };
+        let file_name = entry
+            .path
+            .file_name()
+            .map(OsStr::to_os_string)
+            .unwrap_or_else(|| worktree.root_name().into());
+        let destination = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Some(directory) = destination
+                .await?
+                .and_then(|paths| paths.into_iter().next())
+            else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| {
+                let download = this.project.update(cx, |project, cx| {
+                    project.download_entry(project_path, directory.join(file_name), cx)
+                })?;
+                this.track_download(entry_id, download, cx);
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_prompt_err("Failed to download", cx, download_error_detail);
+    }
+
+    fn track_download(
+        &mut self,
+        entry_id: ProjectEntryId,
+        download: FileDownload,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let FileDownload { mut progress, task } = download;
+        let observe_progress = cx.spawn(|this, mut cx| async move {
+            while let Some(progress) = progress.recv().await {
+                let updated = this.update(&mut cx, |this, cx| {
+                    if let Some(download) = this.downloads.get_mut(&entry_id) {
+                        download.progress = progress;
+                        cx.notify();
+                    }
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+        let download = cx.spawn(|this, mut cx| async move {
+            let result = task.await;
+            this.update(&mut cx, |this, cx| {
+                this.downloads.remove(&entry_id);
+                cx.notify();
+                if let Err(error) = result {
+                    let detail = download_error_detail(&error, cx)
+                        .unwrap_or_else(|| format!("{error:?}. Please try again."));
+                    cx.prompt(
+                        PromptLevel::Critical,
+                        "Failed to download",
+                        Some(&detail),
+                        &["Ok"],
+                    )
+                    .detach();
+                }
+            })
+            .ok();
+        });
+        self.downloads.insert(
+            entry_id,
+            ActiveDownload {
+                progress: DownloadProgress::default(),
+                _observe_progress: observe_progress,
+                _download: download,
+            },
+        );
+        cx.notify();
+    }
+
+    fn cancel_download(&mut self, _: &CancelDownload, cx: &mut ViewContext<Self>) {
+        if let Some((_, entry)) = self.selected_entry(cx) {
+            if self.downloads.remove(&entry.id).is_some() {
+                cx.notify();
+            }
+        }
+    }
+
     fn copy_path(&mut self, _: &CopyPath, cx: &mut ViewContext<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             cx.write_to_clipboard(ClipboardItem::new(
@@ -1005,35 +1313,51 @@ impl ProjectPanel {
         }
     }
 
-    fn move_entry(
+    fn move_entries(
         &mut self,
-        entry_to_move: ProjectEntryId,
+        entries_to_move: Vec<ProjectEntryId>,
         destination: ProjectEntryId,
         destination_is_file: bool,
         cx: &mut ViewContext<Self>,
     ) {
-        let destination_worktree = self.project.update(cx, |project, cx| {
-            let entry_path = project.path_for_entry(entry_to_move, cx)?;
+        let Some((destination_worktree, task)) = self.project.update(cx, |project, cx| {
             let destination_entry_path = project.path_for_entry(destination, cx)?.path.clone();
-
             let mut destination_path = destination_entry_path.as_ref();
             if destination_is_file {
                 destination_path = destination_path.parent()?;
             }
 
-            let mut new_path = destination_path.to_path_buf();
-            new_path.push(entry_path.path.file_name()?);
-            if new_path != entry_path.path.as_ref() {
-                let task = project.rename_entry(entry_to_move, new_path, cx);
-                cx.foreground_executor().spawn(task).detach_and_log_err(cx);
-            }
-
-            Some(project.worktree_id_for_entry(destination, cx)?)
-        });
+            let renames = entries_to_move
+                .into_iter()
+                .filter_map(|entry_to_move| {
+                    let entry_path = project.path_for_entry(entry_to_move, cx)?;
+                    // Directories can't be moved into themselves.
+                    if destination_path.starts_with(&entry_path.path) {
+                        return None;
+                    }
+                    let new_path = destination_path.join(entry_path.path.file_name()?);
+                    (new_path != entry_path.path.as_ref())
+                        .then(|| (entry_to_move, Arc::from(new_path)))
+                })
+                .collect::<Vec<_>>();
+            let task = project.rename_entries(renames, cx);
+            Some((project.worktree_id_for_entry(destination, cx)?, task))
+        }) else {
+            return;
+        };
 
-        if let Some(destination_worktree) = destination_worktree {
-            self.expand_entry(destination_worktree, destination, cx);
-        }
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, cx| async move {
+            let (_, transaction) = task.await?;
+            if !transaction.0.is_empty() {
+                Editor::preview_project_transaction(workspace, transaction, "Move".into(), cx)
+                    .await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        self.marked_entries.clear();
+        self.expand_entry(destination_worktree, destination, cx);
     }
 
     fn index_for_selection(&self, selection: Selection) -> Option<(usize, usize, usize)> {
@@ -1152,6 +1476,7 @@ impl ProjectPanel {
                         path: entry.path.join("\0").into(),
                         inode: 0,
                         mtime: entry.mtime,
+                        size: 0,
                         is_symlink: false,
                         is_ignored: false,
                         is_external: false,
@@ -1334,6 +1659,8 @@ impl ProjectPanel {
                             .map_or(false, |e| e.is_cut() && e.entry_id() == entry.id),
                         git_status: status,
                         is_dotenv: entry.is_private,
+                        size: entry.is_file().then_some(entry.size),
+                        mtime: entry.mtime,
                     };
 
                     if let Some(edit_state) = &self.edit_state {
@@ -1375,7 +1702,8 @@ impl ProjectPanel {
         let show_editor = details.is_editing && !details.is_processing;
         let is_selected = self
             .selection
-            .map_or(false, |selection| selection.entry_id == entry_id);
+            .map_or(false, |selection| selection.entry_id == entry_id)
+            || self.marked_entries.contains(&entry_id);
         let width = self.width.unwrap_or(px(0.));
 
         let filename_text_color = details
@@ -1397,6 +1725,19 @@ impl ProjectPanel {
         let file_name = details.filename.clone();
         let icon = details.icon.clone();
         let depth = details.depth;
+        let metadata: Option<SharedString> = details.size.map(|size| {
+            let modified = time_format::format_localized_timestamp(
+                OffsetDateTime::now_utc(),
+                details.mtime.into(),
+                cx.local_timezone(),
+            );
+            format!("{} · Modified {modified}", format_file_size(size)).into()
+        });
+        let presence_dots = self
+            .project
+            .read(cx)
+            .path_for_entry(entry_id, cx)
+            .and_then(|path| workspace::render_presence_dots(&self.project, &path, cx));
         div()
             .id(entry_id.to_proto() as usize)
             .on_drag(entry_id, move |entry_id, cx| {
@@ -1410,7 +1751,12 @@ impl ProjectPanel {
                 style.bg(cx.theme().colors().drop_target_background)
             })
             .on_drop(cx.listener(move |this, dragged_id: &ProjectEntryId, cx| {
-                this.move_entry(*dragged_id, entry_id, kind.is_file(), cx);
+                let entries_to_move = if this.marked_entries.contains(dragged_id) {
+                    this.marked_entries.iter().copied().collect()
+                } else {
+                    vec![*dragged_id]
+                };
+                this.move_entries(entries_to_move, entry_id, kind.is_file(), cx);
             }))
             .child(
                 ListItem::new(entry_id.to_proto() as usize)
@@ -1430,10 +1776,27 @@ impl ProjectPanel {
                         }
                         .ml_1(),
                     )
+                    .children(presence_dots.map(|dots| dots.ml_1()))
+                    .end_slot::<Label>(self.downloads.get(&entry_id).map(|download| {
+                        Label::new(format!("{:.0}%", download.progress.fraction() * 100.))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    }))
+                    .when_some(metadata.filter(|_| !show_editor), |item, metadata| {
+                        item.tooltip(move |cx| Tooltip::text(metadata.clone(), cx))
+                    })
                     .on_click(cx.listener(move |this, event: &gpui::ClickEvent, cx| {
                         if event.down.button == MouseButton::Right {
                             return;
                         }
+                        if event.down.modifiers.shift {
+                            if !this.marked_entries.remove(&entry_id) {
+                                this.marked_entries.insert(entry_id);
+                            }
+                            cx.notify();
+                            return;
+                        }
+                        this.marked_entries.clear();
                         if !show_editor {
                             if kind.is_dir() {
                                 this.toggle_expanded(entry_id, cx);
@@ -1457,6 +1820,55 @@ impl ProjectPanel {
             )
     }
 
+    fn render_pinned_entries(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let project = self.project.read(cx);
+        let show_file_icons = ProjectPanelSettings::get_global(cx).file_icons;
+        let items = project
+            .all_pinned_paths()
+            .enumerate()
+            .filter_map(|(ix, project_path)| {
+                let worktree = project.worktree_for_id(project_path.worktree_id, cx)?;
+                let entry_id = worktree.read(cx).entry_for_path(&project_path.path)?.id;
+                let file_name = project_path.path.file_name()?.to_string_lossy().to_string();
+                let icon = show_file_icons
+                    .then(|| FileAssociations::get_icon(&project_path.path, cx))
+                    .flatten();
+                Some(
+                    ListItem::new(("pinned-entry", ix))
+                        .indent_level(1)
+                        .indent_step_size(px(ProjectPanelSettings::get_global(cx).indent_size))
+                        .child(if let Some(icon) = icon {
+                            div().child(Icon::from_path(icon.to_string()).color(Color::Muted))
+                        } else {
+                            div().size(IconSize::default().rems()).invisible()
+                        })
+                        .child(div().ml_1().child(Label::new(file_name)))
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.open_entry(entry_id, true, cx);
+                        })),
+                )
+            })
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            return None;
+        }
+
+        Some(
+            v_flex()
+                .pb_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    h_flex().px_2().child(
+                        Label::new("Pinned")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                )
+                .children(items),
+        )
+    }
+
     fn dispatch_context(&self, cx: &ViewContext<Self>) -> KeyContext {
         let mut dispatch_context = KeyContext::default();
         dispatch_context.add("ProjectPanel");
@@ -1533,7 +1945,7 @@ impl Render for ProjectPanel {
         let project = self.project.read(cx);
 
         if has_worktree {
-            div()
+            v_flex()
                 .id("project-panel")
                 .size_full()
                 .relative()
@@ -1549,6 +1961,11 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
                 .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::toggle_pinned))
+                .when(!project.is_local(), |el| {
+                    el.on_action(cx.listener(Self::download_entry))
+                        .on_action(cx.listener(Self::cancel_download))
+                })
                 .when(!project.is_read_only(), |el| {
                     el.on_action(cx.listener(Self::new_file))
                         .on_action(cx.listener(Self::new_directory))
@@ -1573,6 +1990,7 @@ impl Render for ProjectPanel {
                     }),
                 )
                 .track_focus(&self.focus_handle)
+                .children(self.render_pinned_entries(cx))
                 .child(
                     uniform_list(
                         cx.view().clone(),
@@ -1736,18 +2154,52 @@ impl ClipboardEntry {
     }
 }
 
+fn download_error_detail(error: &anyhow::Error, _: &mut WindowContext) -> Option<String> {
+    match error.error_code() {
+        ErrorCode::Forbidden => Some("The host doesn't allow downloading files from this project. They can enable `allow_guest_downloads` in their settings.".into()),
+        ErrorCode::UnsharedItem => Some("This file is not shared by the host. This could be because it has been marked as `private`".into()),
+        _ => None,
+    }
+}
+
+/// Formats a file size in bytes using binary units, e.g. `1.5 KB`.
+fn format_file_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut size = size as f64 / 1024.;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024. {
+            break;
+        }
+        size /= 1024.;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use collections::HashSet;
     use gpui::{TestAppContext, View, VisualTestContext, WindowHandle};
     use pretty_assertions::assert_eq;
-    use project::{project_settings::ProjectSettings, FakeFs};
+    use project::{project_settings::ProjectSettings, FakeFs, Fs as _};
     use serde_json::json;
     use settings::SettingsStore;
     use std::path::{Path, PathBuf};
     use workspace::AppState;
 
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(0), "0 B");
+        assert_eq!(format_file_size(1023), "1023 B");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(5 * 1024 * 1024), "5.0 MB");
+    }
+
     #[gpui::test]
     async fn test_visible_list(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -2520,6 +2972,59 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_pinning_files(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                "a": { "one.txt": "" },
+                "two.txt": ""
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        let pinned_paths = |cx: &mut VisualTestContext| {
+            project.read_with(cx, |project, _| {
+                project
+                    .pinned_paths()
+                    .iter()
+                    .map(|path| path.path.to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        select_path(&panel, "root1/two.txt", cx);
+        panel.update(cx, |panel, cx| panel.toggle_pinned(&TogglePinned, cx));
+        select_path(&panel, "root1/a/one.txt", cx);
+        panel.update(cx, |panel, cx| panel.toggle_pinned(&TogglePinned, cx));
+        assert_eq!(pinned_paths(cx), ["two.txt", "a/one.txt"]);
+
+        // Directories can't be pinned.
+        select_path(&panel, "root1/a", cx);
+        panel.update(cx, |panel, cx| panel.toggle_pinned(&TogglePinned, cx));
+        assert_eq!(pinned_paths(cx), ["two.txt", "a/one.txt"]);
+
+        select_path(&panel, "root1/two.txt", cx);
+        panel.update(cx, |panel, cx| panel.toggle_pinned(&TogglePinned, cx));
+        assert_eq!(pinned_paths(cx), ["a/one.txt"]);
+
+        // Deleted files are unpinned.
+        fs.remove_file("/root1/a/one.txt".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(pinned_paths(cx), Vec::<String>::new());
+    }
+
     #[gpui::test]
     async fn test_copy_paste_directory(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -3046,6 +3551,137 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_rename_into_new_directories(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/src",
+            json!({
+                "test": {
+                    "first.rs": "// First Rust file",
+                    "second.rs": "// Second Rust file",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/src".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "src/test/first.rs", cx);
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("../first.rs", cx));
+            assert!(
+                panel.confirm_edit(cx).is_none(),
+                "Should not allow moving entries out of their parent directory"
+            )
+        });
+
+        select_path(&panel, "src/test/first.rs", cx);
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("nested/dir/first.rs", cx));
+        });
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "        v nested",
+                "            v dir",
+                "                  first.rs  <== selected",
+                "          second.rs",
+            ]
+        );
+        assert_eq!(
+            fs.files(),
+            &[
+                PathBuf::from("/src/test/second.rs"),
+                PathBuf::from("/src/test/nested/dir/first.rs"),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_on_case_insensitive_worktree(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/src",
+            json!({
+                "test": {
+                    "First.rs": "// First Rust file",
+                    "second.rs": "// Second Rust file",
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree("/other", json!({ "Third.rs": "" })).await;
+        fs.set_case_sensitive("/src", false);
+
+        let project = Project::test(fs.clone(), ["/src".as_ref(), "/other".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.read(|cx| {
+            let case_sensitivity = project
+                .read(cx)
+                .worktrees()
+                .map(|worktree| worktree.read(cx).is_case_sensitive())
+                .collect::<Vec<_>>();
+            assert_eq!(case_sensitivity, &[false, true]);
+        });
+
+        select_path(&panel, "src/test/second.rs", cx);
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("first.rs", cx));
+            assert!(
+                panel.confirm_edit(cx).is_none(),
+                "Should not allow names that only differ from another entry's in casing"
+            )
+        });
+
+        select_path(&panel, "src/test/First.rs", cx);
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("first.rs", cx));
+        });
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          first.rs  <== selected",
+                "          second.rs",
+                "v other",
+                "      Third.rs",
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_autoreveal_and_gitignored_files(cx: &mut gpui::TestAppContext) {
         init_test_with_editor(cx);
@@ -4,9 +4,10 @@ use gpui::{
     actions, rems, AppContext, DismissEvent, FontWeight, Model, ParentElement, StyledText, Task,
     View, ViewContext, WeakView, WindowContext,
 };
+use language::{Anchor, Buffer, CodeLabel, ToPoint};
 use ordered_float::OrderedFloat;
 use picker::{Picker, PickerDelegate};
-use project::{Project, Symbol};
+use project::{Item as _, Project, ProjectPath, Symbol};
 use std::{borrow::Cow, cmp::Reverse, sync::Arc};
 use theme::ActiveTheme;
 use util::ResultExt;
@@ -40,6 +41,7 @@ pub struct ProjectSymbolsDelegate {
     project: Model<Project>,
     selected_match_index: usize,
     symbols: Vec<Symbol>,
+    outline_symbols: Option<Vec<OutlineSymbol>>,
     visible_match_candidates: Vec<StringMatchCandidate>,
     external_match_candidates: Vec<StringMatchCandidate>,
     show_worktree_root_name: bool,
@@ -53,6 +55,7 @@ impl ProjectSymbolsDelegate {
             project,
             selected_match_index: 0,
             symbols: Default::default(),
+            outline_symbols: None,
             visible_match_candidates: Default::default(),
             external_match_candidates: Default::default(),
             matches: Default::default(),
@@ -79,10 +82,10 @@ impl ProjectSymbolsDelegate {
             cx.background_executor().clone(),
         ));
         let sort_key_for_match = |mat: &StringMatch| {
-            let symbol = &self.symbols[mat.candidate_id];
+            let label = self.label_for_candidate(mat.candidate_id);
             (
                 Reverse(OrderedFloat(mat.score)),
-                &symbol.label.text[symbol.label.filter_range.clone()],
+                &label.text[label.filter_range.clone()],
             )
         };
 
@@ -92,8 +95,10 @@ impl ProjectSymbolsDelegate {
         matches.append(&mut external_matches);
 
         for mat in &mut matches {
-            let symbol = &self.symbols[mat.candidate_id];
-            let filter_start = symbol.label.filter_range.start;
+            let filter_start = self
+                .label_for_candidate(mat.candidate_id)
+                .filter_range
+                .start;
             for position in &mut mat.positions {
                 *position += filter_start;
             }
@@ -102,6 +107,77 @@ impl ProjectSymbolsDelegate {
         self.matches = matches;
         self.set_selected_index(0, cx);
     }
+
+    fn label_for_candidate(&self, candidate_id: usize) -> &CodeLabel {
+        match candidate_id.checked_sub(self.symbols.len()) {
+            Some(ix) => &self.outline_symbols()[ix].label,
+            None => &self.symbols[candidate_id].label,
+        }
+    }
+
+    fn path_for_candidate(&self, candidate_id: usize) -> &ProjectPath {
+        match candidate_id.checked_sub(self.symbols.len()) {
+            Some(ix) => &self.outline_symbols()[ix].path,
+            None => &self.symbols[candidate_id].path,
+        }
+    }
+
+    fn outline_symbols(&self) -> &[OutlineSymbol] {
+        self.outline_symbols.as_deref().unwrap_or_default()
+    }
+
+    /// Collects symbols from the syntax trees of open buffers that aren't
+    /// served by any language server, so they can still be found via search.
+    fn collect_outline_symbols(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        if self.outline_symbols.is_some() {
+            return;
+        }
+
+        let project = self.project.read(cx);
+        let mut outline_symbols = Vec::new();
+        if project.is_local() {
+            for buffer in project.opened_buffers() {
+                let buffer_ref = buffer.read(cx);
+                if project
+                    .language_servers_for_buffer(buffer_ref, cx)
+                    .next()
+                    .is_some()
+                {
+                    continue;
+                }
+                let Some(path) = buffer_ref.project_path(cx) else {
+                    continue;
+                };
+                let Some(outline) = buffer_ref.snapshot().outline(None) else {
+                    continue;
+                };
+                outline_symbols.extend(outline.items.into_iter().map(|item| {
+                    let mut label = CodeLabel::plain(item.text, None);
+                    if let Some((first, last)) =
+                        item.name_ranges.first().zip(item.name_ranges.last())
+                    {
+                        label.filter_range = first.start..last.end;
+                    }
+                    OutlineSymbol {
+                        buffer: buffer.clone(),
+                        path: path.clone(),
+                        label,
+                        range: item.range,
+                    }
+                }));
+            }
+        }
+        self.outline_symbols = Some(outline_symbols);
+    }
+}
+
+/// A symbol found in the outline of an open buffer, used as a fallback for
+/// buffers that no language server is able to answer symbol queries for.
+struct OutlineSymbol {
+    buffer: Model<Buffer>,
+    path: ProjectPath,
+    label: CodeLabel,
+    range: std::ops::Range<Anchor>,
 }
 
 impl PickerDelegate for ProjectSymbolsDelegate {
@@ -111,15 +187,34 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     }
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
-        if let Some(symbol) = self
-            .matches
-            .get(self.selected_match_index)
-            .map(|mat| self.symbols[mat.candidate_id].clone())
-        {
+        let Some(mat) = self.matches.get(self.selected_match_index) else {
+            return;
+        };
+        if let Some(ix) = mat.candidate_id.checked_sub(self.symbols.len()) {
+            let outline_symbol = &self.outline_symbols()[ix];
+            let buffer = outline_symbol.buffer.clone();
+            let position = outline_symbol.range.start.to_point(&buffer.read(cx));
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    let editor = if secondary {
+                        workspace.split_project_item::<Editor>(buffer, cx)
+                    } else {
+                        workspace.open_project_item::<Editor>(buffer, cx)
+                    };
+
+                    editor.update(cx, |editor, cx| {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_ranges([position..position])
+                        });
+                    });
+                })
+                .log_err();
+            cx.emit(DismissEvent);
+        } else {
+            let symbol = self.symbols[mat.candidate_id].clone();
             let buffer = self.project.update(cx, |project, cx| {
                 project.open_buffer_for_symbol(&symbol, cx)
             });
-            let symbol = symbol.clone();
             let workspace = self.workspace.clone();
             cx.spawn(|_, mut cx| async move {
                 let buffer = buffer.await?;
@@ -162,6 +257,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     }
 
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.collect_outline_symbols(cx);
         self.filter(&query, cx);
         self.show_worktree_root_name = self.project.read(cx).visible_worktrees(cx).count() > 1;
         let symbols = self
@@ -172,25 +268,31 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             if let Some(symbols) = symbols {
                 this.update(&mut cx, |this, cx| {
                     let delegate = &mut this.delegate;
+                    delegate.symbols = symbols;
+
                     let project = delegate.project.read(cx);
-                    let (visible_match_candidates, external_match_candidates) = symbols
-                        .iter()
+                    let labels = delegate.symbols.iter().map(|symbol| &symbol.label).chain(
+                        delegate
+                            .outline_symbols()
+                            .iter()
+                            .map(|symbol| &symbol.label),
+                    );
+                    let (visible_match_candidates, external_match_candidates) = labels
                         .enumerate()
-                        .map(|(id, symbol)| {
+                        .map(|(id, label)| {
                             StringMatchCandidate::new(
                                 id,
-                                symbol.label.text[symbol.label.filter_range.clone()].to_string(),
+                                label.text[label.filter_range.clone()].to_string(),
                             )
                         })
                         .partition(|candidate| {
                             project
-                                .entry_for_path(&symbols[candidate.id].path, cx)
+                                .entry_for_path(delegate.path_for_candidate(candidate.id), cx)
                                 .map_or(false, |e| !e.is_ignored)
                         });
 
                     delegate.visible_match_candidates = visible_match_candidates;
                     delegate.external_match_candidates = external_match_candidates;
-                    delegate.symbols = symbols;
                     delegate.filter(&query, cx);
                 })
                 .log_err();
@@ -205,13 +307,14 @@ impl PickerDelegate for ProjectSymbolsDelegate {
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
         let string_match = &self.matches[ix];
-        let symbol = &self.symbols[string_match.candidate_id];
-        let syntax_runs = styled_runs_for_code_label(&symbol.label, cx.theme().syntax());
+        let symbol_label = self.label_for_candidate(string_match.candidate_id);
+        let symbol_path = self.path_for_candidate(string_match.candidate_id);
+        let syntax_runs = styled_runs_for_code_label(symbol_label, cx.theme().syntax());
 
-        let mut path = symbol.path.path.to_string_lossy();
+        let mut path = symbol_path.path.to_string_lossy();
         if self.show_worktree_root_name {
             let project = self.project.read(cx);
-            if let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx) {
+            if let Some(worktree) = project.worktree_for_id(symbol_path.worktree_id, cx) {
                 path = Cow::Owned(format!(
                     "{}{}{}",
                     worktree.read(cx).root_name(),
@@ -220,7 +323,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
                 ));
             }
         }
-        let label = symbol.label.text.clone();
+        let label = symbol_label.text.clone();
         let path = path.to_string().clone();
 
         let highlights = gpui::combine_highlights(
@@ -390,6 +493,78 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_project_symbols_from_outline(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        // No language server is registered for this language, so its symbols
+        // can only come from the outlines of open buffers.
+        let language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(language::tree_sitter_rust::language()),
+        )
+        .with_outline_query(r#"(function_item "fn" @context name: (_) @name) @item"#)
+        .unwrap();
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({ "main.rs": "fn one() {}\nfn two() {}\n", "other.rs": "fn three() {}\n" }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        project.update(cx, |project, _| project.languages().add(Arc::new(language)));
+
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/main.rs", cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let symbols = cx.new_view(|cx| {
+            Picker::uniform_list(
+                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone()),
+                cx,
+            )
+        });
+
+        symbols.update(cx, |p, cx| {
+            p.update_matches("one".to_string(), cx);
+        });
+        cx.run_until_parked();
+        symbols.update(cx, |symbols, _| {
+            let delegate = &symbols.delegate;
+            assert_eq!(delegate.matches.len(), 1);
+            assert_eq!(delegate.matches[0].string, "one");
+            let candidate_id = delegate.matches[0].candidate_id;
+            assert_eq!(delegate.label_for_candidate(candidate_id).text, "fn one");
+            assert_eq!(
+                delegate.path_for_candidate(candidate_id).path.as_ref(),
+                Path::new("main.rs")
+            );
+        });
+
+        // Files that aren't open don't contribute any symbols.
+        symbols.update(cx, |p, cx| {
+            p.update_matches("three".to_string(), cx);
+        });
+        cx.run_until_parked();
+        symbols.update(cx, |symbols, _| {
+            assert_eq!(symbols.delegate.matches.len(), 0);
+        });
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let store = SettingsStore::test(cx);
@@ -0,0 +1,167 @@
+use crate::{create_project_from_template, ProjectTemplate};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusableView, Render, Task, View, ViewContext,
+    VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+pub struct ProjectTemplatePicker {
+    picker: View<Picker<ProjectTemplatePickerDelegate>>,
+}
+
+impl FocusableView for ProjectTemplatePicker {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ProjectTemplatePicker {}
+impl ModalView for ProjectTemplatePicker {}
+
+impl ProjectTemplatePicker {
+    pub fn new(delegate: ProjectTemplatePickerDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for ProjectTemplatePicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct ProjectTemplatePickerDelegate {
+    view: WeakView<ProjectTemplatePicker>,
+    workspace: WeakView<Workspace>,
+    templates: Vec<ProjectTemplate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl ProjectTemplatePickerDelegate {
+    pub fn new(
+        view: WeakView<ProjectTemplatePicker>,
+        workspace: WeakView<Workspace>,
+        templates: Vec<ProjectTemplate>,
+    ) -> Self {
+        Self {
+            view,
+            workspace,
+            templates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for ProjectTemplatePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Select a project template...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .templates
+            .iter()
+            .enumerate()
+            .map(|(id, template)| StringMatchCandidate {
+                id,
+                char_bag: template.name.as_ref().into(),
+                string: template.name.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, _| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(template_match) = self.matches.get(self.selected_index) {
+            let template = self.templates[template_match.candidate_id].clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    create_project_from_template(workspace, template, cx)
+                })
+                .log_err();
+        }
+
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let template_match = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    template_match.string.clone(),
+                    template_match.positions.clone(),
+                )),
+        )
+    }
+}
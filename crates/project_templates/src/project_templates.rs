@@ -0,0 +1,310 @@
+mod project_template_picker;
+
+use anyhow::{anyhow, Context as _, Result};
+use fs::Fs;
+use futures::StreamExt;
+use gpui::{actions, AppContext, SharedString, ViewContext};
+use project_template_picker::{ProjectTemplatePicker, ProjectTemplatePickerDelegate};
+use rust_embed::RustEmbed;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+use util::{paths, ResultExt};
+use workspace::{notifications::DetachAndPromptErr, Workspace};
+
+actions!(project_templates, [NewProject]);
+
+#[derive(RustEmbed)]
+#[folder = "../../assets"]
+#[include = "project_templates/**/*"]
+#[exclude = "*.DS_Store"]
+struct ProjectTemplateAssets;
+
+const BUILT_IN_TEMPLATES_DIR: &str = "project_templates/";
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(new_project);
+    })
+    .detach();
+}
+
+/// A directory of files that a new project can be created from. Built-in
+/// templates are bundled with Zed, and user templates are the directories
+/// in `~/.config/zed/templates`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectTemplate {
+    pub name: SharedString,
+    source: TemplateSource,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TemplateSource {
+    BuiltIn,
+    User(PathBuf),
+}
+
+/// A file in a template, with its path relative to the template's root.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateFile {
+    pub path: PathBuf,
+    pub content: Vec<u8>,
+}
+
+impl ProjectTemplate {
+    pub fn built_in() -> Vec<Self> {
+        let mut names = ProjectTemplateAssets::iter()
+            .filter_map(|path| {
+                let path = path.strip_prefix(BUILT_IN_TEMPLATES_DIR)?;
+                Some(path.split('/').next()?.to_string())
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| Self {
+                name: name.into(),
+                source: TemplateSource::BuiltIn,
+            })
+            .collect()
+    }
+
+    pub async fn user(templates_dir: &Path, fs: &dyn Fs) -> Result<Vec<Self>> {
+        let mut templates = Vec::new();
+        if !is_dir(templates_dir, fs).await? {
+            return Ok(templates);
+        }
+
+        let mut entries = fs.read_dir(templates_dir).await?;
+        while let Some(path) = entries.next().await {
+            let path = path?;
+            if !is_dir(&path, fs).await? {
+                continue;
+            }
+            if let Some(name) = path.file_name() {
+                templates.push(Self {
+                    name: name.to_string_lossy().into_owned().into(),
+                    source: TemplateSource::User(path),
+                });
+            }
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    pub async fn files(&self, fs: &dyn Fs) -> Result<Vec<TemplateFile>> {
+        match &self.source {
+            TemplateSource::BuiltIn => {
+                let prefix = format!("{BUILT_IN_TEMPLATES_DIR}{}/", self.name);
+                Ok(ProjectTemplateAssets::iter()
+                    .filter_map(|path| {
+                        let relative_path = path.strip_prefix(&prefix)?;
+                        let content = ProjectTemplateAssets::get(&path)?.data;
+                        Some(TemplateFile {
+                            path: PathBuf::from(relative_path),
+                            content: content.into_owned(),
+                        })
+                    })
+                    .collect())
+            }
+            TemplateSource::User(root) => {
+                let mut files = Vec::new();
+                let mut dirs = vec![root.clone()];
+                while let Some(dir) = dirs.pop() {
+                    let mut entries = fs.read_dir(&dir).await?;
+                    while let Some(path) = entries.next().await {
+                        let path = path?;
+                        if is_dir(&path, fs).await? {
+                            dirs.push(path);
+                        } else {
+                            files.push(TemplateFile {
+                                content: fs.load_bytes(&path).await?,
+                                path: path.strip_prefix(root)?.to_path_buf(),
+                            });
+                        }
+                    }
+                }
+                Ok(files)
+            }
+        }
+    }
+}
+
+async fn is_dir(path: &Path, fs: &dyn Fs) -> Result<bool> {
+    Ok(fs
+        .metadata(path)
+        .await?
+        .map_or(false, |metadata| metadata.is_dir))
+}
+
+/// Replaces every `{{variable}}` in `text` that has a value in `variables`.
+/// Other text between braces is left as-is.
+pub fn substitute_variables<'a>(text: &'a str, variables: &[(&str, &str)]) -> Cow<'a, str> {
+    if !text.contains("{{") {
+        return Cow::Borrowed(text);
+    }
+
+    let mut text = text.to_string();
+    for (name, value) in variables {
+        text = text.replace(&format!("{{{{{name}}}}}"), value);
+    }
+    Cow::Owned(text)
+}
+
+/// Creates a new directory at `destination` containing the template's files.
+/// The `project_name` variable is set to the name of the new directory.
+pub async fn create_project(files: &[TemplateFile], destination: &Path, fs: &dyn Fs) -> Result<()> {
+    if fs.metadata(destination).await?.is_some() {
+        return Err(anyhow!("{destination:?} already exists"));
+    }
+    let project_name = destination
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid project path {destination:?}"))?
+        .to_string_lossy();
+    let variables = [("project_name", project_name.as_ref())];
+
+    fs.create_dir(destination).await?;
+    for file in files {
+        let path = destination.join(&file.path);
+        if let Some(parent) = path.parent() {
+            fs.create_dir(parent).await?;
+        }
+        match std::str::from_utf8(&file.content) {
+            Ok(text) => {
+                let text = substitute_variables(text, &variables).into_owned();
+                fs.atomic_write(path.clone(), text).await
+            }
+            Err(_) => {
+                fs.atomic_write_bytes(path.clone(), file.content.clone())
+                    .await
+            }
+        }
+        .with_context(|| format!("failed to write {path:?}"))?;
+    }
+    Ok(())
+}
+
+fn new_project(workspace: &mut Workspace, _: &NewProject, cx: &mut ViewContext<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    cx.spawn(|workspace, mut cx| async move {
+        let mut templates = ProjectTemplate::built_in();
+        templates.extend(
+            ProjectTemplate::user(&paths::PROJECT_TEMPLATES_DIR, fs.as_ref())
+                .await
+                .log_err()
+                .unwrap_or_default(),
+        );
+        workspace.update(&mut cx, |workspace, cx| {
+            let weak_workspace = cx.view().downgrade();
+            workspace.toggle_modal(cx, |cx| {
+                let delegate = ProjectTemplatePickerDelegate::new(
+                    cx.view().downgrade(),
+                    weak_workspace,
+                    templates,
+                );
+                ProjectTemplatePicker::new(delegate, cx)
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+pub(crate) fn create_project_from_template(
+    workspace: &mut Workspace,
+    template: ProjectTemplate,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    let destination = cx.prompt_for_new_path(&paths::HOME);
+    cx.spawn(|workspace, mut cx| async move {
+        let Some(destination) = destination.await? else {
+            return Ok(());
+        };
+        let files = template.files(fs.as_ref()).await?;
+        create_project(&files, &destination, fs.as_ref()).await?;
+        workspace
+            .update(&mut cx, |workspace, cx| {
+                workspace.open_workspace_for_paths(false, vec![destination], cx)
+            })?
+            .await
+    })
+    .detach_and_prompt_err("Failed to create project", cx, |_, _| None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[test]
+    fn test_substitute_variables() {
+        let variables = [("project_name", "my-app")];
+        assert_eq!(
+            substitute_variables("name = \"{{project_name}}\"", &variables),
+            "name = \"my-app\""
+        );
+        assert_eq!(
+            substitute_variables("{{unknown}} {{ project_name }}", &variables),
+            "{{unknown}} {{ project_name }}"
+        );
+    }
+
+    #[test]
+    fn test_built_in_templates() {
+        let names = ProjectTemplate::built_in()
+            .into_iter()
+            .map(|template| template.name)
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"Rust".into()));
+    }
+
+    #[gpui::test]
+    async fn test_create_project_from_user_template(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/templates",
+            json!({
+                "web": {
+                    "index.html": "<title>{{project_name}}</title>",
+                    "css": { "style.css": "body {}" }
+                },
+                "notes.txt": "not a template",
+            }),
+        )
+        .await;
+
+        let templates = ProjectTemplate::user(Path::new("/templates"), fs.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "web");
+
+        let files = templates[0].files(fs.as_ref()).await.unwrap();
+        create_project(&files, Path::new("/projects/site"), fs.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load(Path::new("/projects/site/index.html"))
+                .await
+                .unwrap(),
+            "<title>site</title>"
+        );
+        assert_eq!(
+            fs.load(Path::new("/projects/site/css/style.css"))
+                .await
+                .unwrap(),
+            "body {}"
+        );
+
+        // Existing directories are never overwritten.
+        assert!(
+            create_project(&files, Path::new("/projects/site"), fs.as_ref())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,14 +1,19 @@
 use assistant::assistant_settings::AssistantSettings;
 use assistant::{AssistantPanel, InlineAssist};
 use editor::{Editor, EditorSettings};
+use encoding_selector::ReopenWithEncoding;
 
 use gpui::{
-    Action, ClickEvent, ElementId, EventEmitter, InteractiveElement, ParentElement, Render, Styled,
-    Subscription, View, ViewContext, WeakView,
+    Action, AnchorCorner, ClickEvent, ElementId, EventEmitter, InteractiveElement, ParentElement,
+    Render, Styled, Subscription, View, ViewContext, WeakView,
 };
+use line_ending_selector::ConvertLineEndings;
 use search::{buffer_search, BufferSearchBar};
 use settings::{Settings, SettingsStore};
-use ui::{prelude::*, ButtonSize, ButtonStyle, IconButton, IconName, IconSize, Tooltip};
+use ui::{
+    popover_menu, prelude::*, ButtonSize, ButtonStyle, ContextMenu, IconButton, IconName, IconSize,
+    Tooltip,
+};
 use workspace::{
     item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
 };
@@ -122,11 +127,37 @@ impl Render for QuickActionBar {
             },
         );
 
+        // Guests can't change how the host reads and writes shared files.
+        let reopen_menu = editor
+            .read(cx)
+            .active_excerpt(cx)
+            .filter(|(_, buffer, _)| buffer.read(cx).file().map_or(false, |file| file.is_local()))
+            .filter(|_| editor.is_singleton(cx))
+            .map(|_| {
+                popover_menu("reopen menu")
+                    .menu(|cx| {
+                        Some(ContextMenu::build(cx, |menu, _| {
+                            menu.action("Reopen with Encoding…", ReopenWithEncoding.boxed_clone())
+                                .action("Change Line Endings…", ConvertLineEndings.boxed_clone())
+                                .action("Change Language…", language_selector::Toggle.boxed_clone())
+                        }))
+                    })
+                    .anchor(AnchorCorner::TopRight)
+                    .trigger(
+                        IconButton::new("toggle reopen menu", IconName::File)
+                            .size(ButtonSize::Compact)
+                            .icon_size(IconSize::Small)
+                            .style(ButtonStyle::Subtle)
+                            .tooltip(|cx| Tooltip::text("Reopen With…", cx)),
+                    )
+            });
+
         h_flex()
             .id("quick action bar")
             .gap_2()
             .children(inlay_hints_button)
             .children(search_button)
+            .children(reopen_menu)
             .when(AssistantSettings::get_global(cx).button, |bar| {
                 bar.child(assistant_button)
             })
@@ -31,12 +31,25 @@ impl From<HighlightId> for Highlight {
     }
 }
 
+/// URL schemes that links may use. Links with any other scheme, like
+/// `javascript:` or `file:`, are rendered as plain text.
+const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
 #[derive(Debug, Clone)]
 pub struct RichText {
     pub text: SharedString,
     pub highlights: Vec<(Range<usize>, Highlight)>,
     pub link_ranges: Vec<Range<usize>>,
     pub link_urls: Arc<[String]>,
+    /// Remote images referenced by the text, which aren't part of the
+    /// rendered text and are left to the caller to load and display.
+    pub images: Vec<RemoteImage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteImage {
+    pub url: SharedString,
+    pub alt: SharedString,
 }
 
 /// Allows one to specify extra links to the rendered markdown, which can be used
@@ -103,7 +116,7 @@ impl RichText {
             let link_urls = self.link_urls.clone();
             move |ix, cx| {
                 let url = &link_urls[ix];
-                if url.starts_with("http") {
+                if is_safe_url(url) {
                     cx.open_url(url);
                 }
             }
@@ -133,6 +146,7 @@ pub fn render_markdown_mut(
     highlights: &mut Vec<(Range<usize>, Highlight)>,
     link_ranges: &mut Vec<Range<usize>>,
     link_urls: &mut Vec<String>,
+    images: &mut Vec<RemoteImage>,
 ) {
     use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
 
@@ -140,6 +154,8 @@ pub fn render_markdown_mut(
     let mut italic_depth = 0;
     let mut strikethrough_depth = 0;
     let mut link_url = None;
+    let mut image = None;
+    let mut in_code_block = false;
     let mut current_language = None;
     let mut list_stack = Vec::new();
 
@@ -148,8 +164,14 @@ pub fn render_markdown_mut(
         let prev_len = text.len();
         match event {
             Event::Text(t) => {
-                if let Some(language) = &current_language {
+                if let Some(RemoteImage { alt, .. }) = &mut image {
+                    *alt = format!("{alt}{t}").into();
+                } else if let Some(language) = &current_language {
                     render_code(text, highlights, t.as_ref(), language);
+                } else if in_code_block {
+                    // Code in a language that isn't available is shown without syntax highlighting.
+                    text.push_str(t.as_ref());
+                    highlights.push((prev_len..text.len(), Highlight::Code));
                 } else {
                     while let Some(mention) = mentions.first() {
                         if !source_range.contains_inclusive(&mention.range) {
@@ -195,7 +217,7 @@ pub fn render_markdown_mut(
                         let mut finder = linkify::LinkFinder::new();
                         finder.kinds(&[linkify::LinkKind::Url]);
                         let mut last_link_len = prev_len;
-                        for link in finder.links(&t) {
+                        for link in finder.links(&t).filter(|link| is_safe_url(link.as_str())) {
                             let start = link.start();
                             let end = link.end();
                             let range = (prev_len + start)..(prev_len + end);
@@ -267,11 +289,14 @@ pub fn render_markdown_mut(
                 }
                 Tag::CodeBlock(kind) => {
                     new_paragraph(text, &mut list_stack);
-                    current_language = if let CodeBlockKind::Fenced(language) = kind {
-                        language_registry
-                            .language_for_name(language.as_ref())
-                            .now_or_never()
-                            .and_then(Result::ok)
+                    in_code_block = true;
+                    current_language = if let CodeBlockKind::Fenced(info) = kind {
+                        code_block_language_name(&info).and_then(|name| {
+                            language_registry
+                                .language_for_name_or_extension(name)
+                                .now_or_never()
+                                .and_then(Result::ok)
+                        })
                     } else {
                         language.cloned()
                     }
@@ -284,7 +309,18 @@ pub fn render_markdown_mut(
                     dest_url,
                     title: _,
                     id: _,
-                } => link_url = Some(dest_url.to_string()),
+                } => link_url = Some(dest_url.to_string()).filter(|url| is_safe_url(url)),
+                Tag::Image {
+                    link_type: _,
+                    dest_url,
+                    title: _,
+                    id: _,
+                } => {
+                    image = Some(RemoteImage {
+                        url: dest_url.to_string().into(),
+                        alt: SharedString::default(),
+                    })
+                }
                 Tag::List(number) => {
                     list_stack.push((number, false));
                 }
@@ -311,14 +347,26 @@ pub fn render_markdown_mut(
             },
             Event::End(tag) => match tag {
                 TagEnd::Heading(_) => bold_depth -= 1,
-                TagEnd::CodeBlock => current_language = None,
+                TagEnd::CodeBlock => {
+                    in_code_block = false;
+                    current_language = None;
+                }
                 TagEnd::Emphasis => italic_depth -= 1,
                 TagEnd::Strong => bold_depth -= 1,
                 TagEnd::Strikethrough => strikethrough_depth -= 1,
                 TagEnd::Link => link_url = None,
+                TagEnd::Image => {
+                    if let Some(image) = image.take() {
+                        if url_host(&image.url).is_some() {
+                            images.push(image);
+                        }
+                    }
+                }
                 TagEnd::List(_) => drop(list_stack.pop()),
                 _ => {}
             },
+            // HTML is never interpreted, and is shown as it was written.
+            Event::Html(html) | Event::InlineHtml(html) => text.push_str(html.as_ref()),
             Event::HardBreak => text.push('\n'),
             Event::SoftBreak => text.push('\n'),
             _ => {}
@@ -336,6 +384,7 @@ pub fn render_rich_text(
     let mut highlights = Vec::new();
     let mut link_ranges = Vec::new();
     let mut link_urls = Vec::new();
+    let mut images = Vec::new();
     render_markdown_mut(
         &block,
         mentions,
@@ -345,17 +394,84 @@ pub fn render_rich_text(
         &mut highlights,
         &mut link_ranges,
         &mut link_urls,
+        &mut images,
     );
     text.truncate(text.trim_end().len());
+    // Code blocks end with a newline that was just trimmed.
+    highlights.retain_mut(|(range, _)| {
+        range.end = range.end.min(text.len());
+        range.start < range.end
+    });
 
     RichText {
         text: SharedString::from(text),
         link_urls: link_urls.into(),
         link_ranges,
         highlights,
+        images,
     }
 }
 
+/// Returns the names of the languages of the fenced code blocks in the given Markdown, like
+/// `rust` or `rs` for a block that starts with ` ```rust `.
+pub fn code_block_languages(block: &str) -> Vec<String> {
+    use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+
+    let mut languages = Vec::new();
+    for event in Parser::new(block) {
+        if let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) = event {
+            if let Some(name) = code_block_language_name(&info) {
+                if !languages.iter().any(|language| language == name) {
+                    languages.push(name.to_string());
+                }
+            }
+        }
+    }
+    languages
+}
+
+/// The language is the first word of a fenced code block's info string.
+fn code_block_language_name(info: &str) -> Option<&str> {
+    info.split_whitespace().next()
+}
+
+/// Returns whether a link to the given URL is safe to open, which excludes
+/// schemes like `javascript:` that could run code or access local files.
+pub fn is_safe_url(url: &str) -> bool {
+    url.split_once(':').map_or(false, |(scheme, _)| {
+        SAFE_URL_SCHEMES
+            .iter()
+            .any(|safe_scheme| scheme.eq_ignore_ascii_case(safe_scheme))
+    })
+}
+
+/// Returns the host of an `http` or `https` URL.
+pub fn url_host(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_and_port = authority.rsplit('@').next()?;
+    let host = match host_and_port.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host_and_port,
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// Returns whether the URL's host is one of the given domains, or a subdomain of one.
+pub fn is_trusted_url(url: &str, trusted_domains: &[String]) -> bool {
+    let Some(host) = url_host(url) else {
+        return false;
+    };
+    let host = host.to_ascii_lowercase();
+    trusted_domains.iter().any(|domain| {
+        let domain = domain.to_ascii_lowercase();
+        host == domain || host.ends_with(&format!(".{domain}"))
+    })
+}
+
 pub fn render_code(
     text: &mut String,
     highlights: &mut Vec<(Range<usize>, Highlight)>,
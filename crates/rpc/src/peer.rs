@@ -519,6 +519,7 @@ impl Peer {
                 "message {} was not handled",
                 envelope.payload_type_name()
             ))
+            .with_tag("unhandled_message", envelope.payload_type_name())
             .to_proto();
         let message_id = connection
             .next_message_id
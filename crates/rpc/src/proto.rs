@@ -287,6 +287,23 @@ messages!(
     (LspExtExpandMacro, Background),
     (LspExtExpandMacroResponse, Background),
     (SetRoomParticipantRole, Foreground),
+    (SetProjectReadOnly, Foreground),
+    (SetProjectJoinApproval, Foreground),
+    (RequestJoinProject, Foreground),
+    (RespondToJoinRequest, Foreground),
+    (CancelJoinRequest, Foreground),
+    (UpdatePinnedPaths, Foreground),
+    (SetAway, Foreground),
+    (OpenRemoteItem, Foreground),
+    (OpenRemoteItemResponse, Foreground),
+    (UpdateRemoteItem, Foreground),
+    (CloseRemoteItem, Foreground),
+    (ShareClipboardEntry, Foreground),
+    (DownloadFileChunk, Background),
+    (DownloadFileChunkResponse, Background),
+    (EditChannelMessage, Foreground),
+    (ChannelMessageUpdate, Foreground),
+    (UpdateActivePath, Foreground),
 );
 
 request_messages!(
@@ -373,6 +390,13 @@ request_messages!(
     (UpdateWorktree, Ack),
     (LspExtExpandMacro, LspExtExpandMacroResponse),
     (SetRoomParticipantRole, Ack),
+    (SetProjectReadOnly, Ack),
+    (SetProjectJoinApproval, Ack),
+    (RequestJoinProject, RespondToJoinRequest),
+    (OpenRemoteItem, OpenRemoteItemResponse),
+    (CloseRemoteItem, Ack),
+    (DownloadFileChunk, DownloadFileChunkResponse),
+    (EditChannelMessage, Ack),
 );
 
 entity_messages!(
@@ -382,10 +406,13 @@ entity_messages!(
     ApplyCompletionAdditionalEdits,
     BufferReloaded,
     BufferSaved,
+    CancelJoinRequest,
+    CloseRemoteItem,
     CopyProjectEntry,
     CreateBufferForPeer,
     CreateProjectEntry,
     DeleteProjectEntry,
+    DownloadFileChunk,
     ExpandProjectEntry,
     FormatBuffers,
     GetCodeActions,
@@ -404,26 +431,34 @@ entity_messages!(
     OpenBufferById,
     OpenBufferByPath,
     OpenBufferForSymbol,
+    OpenRemoteItem,
     PerformRename,
     PrepareRename,
     RefreshInlayHints,
     ReloadBuffers,
     RemoveProjectCollaborator,
+    RequestJoinProject,
     RenameProjectEntry,
     ResolveCompletionDocumentation,
     ResolveInlayHint,
     SaveBuffer,
     SearchProject,
+    SetProjectJoinApproval,
+    SetProjectReadOnly,
+    ShareClipboardEntry,
     StartLanguageServer,
     SynchronizeBuffers,
     UnshareProject,
+    UpdateActivePath,
     UpdateBuffer,
     UpdateBufferFile,
     UpdateDiagnosticSummary,
     UpdateDiffBase,
     UpdateLanguageServer,
+    UpdatePinnedPaths,
     UpdateProject,
     UpdateProjectCollaborator,
+    UpdateRemoteItem,
     UpdateWorktree,
     UpdateWorktreeSettings,
     LspExtExpandMacro,
@@ -432,6 +467,7 @@ entity_messages!(
 entity_messages!(
     {channel_id, Channel},
     ChannelMessageSent,
+    ChannelMessageUpdate,
     RemoveChannelMessage,
     UpdateChannelBuffer,
     UpdateChannelBufferCollaborators,
@@ -11,4 +11,12 @@ pub use notification::*;
 pub use peer::*;
 mod macros;
 
-pub const PROTOCOL_VERSION: u32 = 68;
+use std::time::Duration;
+
+pub const PROTOCOL_VERSION: u32 = 69;
+
+/// How long after sending a channel message its sender can still edit it.
+pub const CHANNEL_MESSAGE_EDIT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// The longest text, in bytes, that can be put on a project's shared clipboard.
+pub const MAX_SHARED_CLIPBOARD_ENTRY_LEN: usize = 64 * 1024;
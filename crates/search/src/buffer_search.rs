@@ -3,10 +3,11 @@ mod registrar;
 use crate::{
     history::SearchHistory,
     mode::{next_mode, SearchMode},
+    persistence,
     search_bar::render_nav_button,
     ActivateRegexMode, ActivateTextMode, CycleMode, NextHistoryQuery, PreviousHistoryQuery,
-    ReplaceAll, ReplaceNext, SearchOptions, SelectAllMatches, SelectNextMatch, SelectPrevMatch,
-    ToggleCaseSensitive, ToggleReplace, ToggleWholeWord,
+    ReplaceAll, ReplaceNext, SearchAgain, SearchOptions, SelectAllMatches, SelectNextMatch,
+    SelectPrevMatch, ToggleCaseSensitive, ToggleReplace, ToggleWholeWord,
 };
 use collections::HashMap;
 use editor::{
@@ -31,7 +32,7 @@ use util::ResultExt;
 use workspace::{
     item::ItemHandle,
     searchable::{Direction, SearchEvent, SearchableItemHandle, WeakSearchableItemHandle},
-    ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+    ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace, WorkspaceId,
 };
 
 pub use registrar::DivRegistrar;
@@ -77,6 +78,8 @@ pub struct BufferSearchBar {
     search_history: SearchHistory,
     current_mode: SearchMode,
     replace_enabled: bool,
+    workspace_id: Option<WorkspaceId>,
+    _workspace_search_options_subscription: Option<Subscription>,
 }
 
 impl BufferSearchBar {
@@ -505,6 +508,12 @@ impl BufferSearchBar {
         registrar.register_handler(WithResults(|this, action: &SelectAllMatches, cx| {
             this.select_all_matches(action, cx);
         }));
+        registrar.register_handler(ForDeployed(|this, action: &SearchAgain, cx| {
+            this.search_again(action, cx);
+        }));
+        registrar.register_handler(ForDismissed(|this, action: &SearchAgain, cx| {
+            this.search_again(action, cx);
+        }));
         registrar.register_handler(ForDeployed(|this, _: &editor::actions::Cancel, cx| {
             this.dismiss(&Dismiss, cx);
         }));
@@ -544,6 +553,46 @@ impl BufferSearchBar {
             current_mode: SearchMode::default(),
             active_search: None,
             replace_enabled: false,
+            workspace_id: None,
+            _workspace_search_options_subscription: None,
+        }
+    }
+
+    /// Keeps the search options in sync with the workspace's other search bars,
+    /// restoring the ones last used when the workspace is opened again.
+    pub fn set_workspace_id(&mut self, workspace_id: WorkspaceId, cx: &mut ViewContext<Self>) {
+        self.workspace_id = Some(workspace_id);
+        self._workspace_search_options_subscription = Some(
+            cx.observe_global::<persistence::WorkspaceSearchOptions>(Self::restore_search_options),
+        );
+        persistence::load_search_options(workspace_id, cx);
+        self.restore_search_options(cx);
+    }
+
+    fn restore_search_options(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((options, mode)) = self
+            .workspace_id
+            .and_then(|workspace_id| persistence::search_options(workspace_id, cx))
+        else {
+            return;
+        };
+        if options != self.default_options || mode != self.current_mode {
+            self.default_options = options;
+            self.search_options = options;
+            self.current_mode = mode;
+            let _ = self.update_matches(cx);
+            cx.notify();
+        }
+    }
+
+    fn persist_search_options(&self, cx: &mut ViewContext<Self>) {
+        if let Some(workspace_id) = self.workspace_id {
+            persistence::set_search_options(
+                workspace_id,
+                self.search_options,
+                self.current_mode,
+                cx,
+            );
         }
     }
 
@@ -626,6 +675,23 @@ impl BufferSearchBar {
         }
     }
 
+    /// Re-runs the most recent query in the active item, showing the search bar if needed.
+    fn search_again(&mut self, _: &SearchAgain, cx: &mut ViewContext<Self>) {
+        let Some(query) = self.search_history.last().map(str::to_string) else {
+            return;
+        };
+        if !self.show(cx) {
+            return;
+        }
+
+        let search = self.search(&query, Some(self.search_options), cx);
+        cx.spawn(|this, mut cx| async move {
+            search.await?;
+            this.update(&mut cx, |this, cx| this.activate_current_match(cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn activate_current_match(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(match_ix) = self.active_match_index {
             if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
@@ -714,6 +780,7 @@ impl BufferSearchBar {
             return;
         }
         self.current_mode = mode;
+        self.persist_search_options(cx);
         let _ = self.update_matches(cx);
         cx.notify();
     }
@@ -728,6 +795,7 @@ impl BufferSearchBar {
     fn toggle_search_option(&mut self, search_option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(search_option);
         self.default_options = self.search_options;
+        self.persist_search_options(cx);
         let _ = self.update_matches(cx);
         cx.notify();
     }
@@ -1961,4 +2029,71 @@ mod tests {
             assert!(display_points_of(editor.all_text_background_highlights(cx)).is_empty(),);
         });
     }
+
+    #[gpui::test]
+    async fn test_search_again(cx: &mut TestAppContext) {
+        let (editor, search_bar, cx) = init_test(cx);
+        search_bar
+            .update(cx, |search_bar, cx| {
+                search_bar.search("expression", None, cx)
+            })
+            .await
+            .unwrap();
+        search_bar.update(cx, |search_bar, cx| {
+            search_bar.dismiss(&Dismiss, cx);
+            search_bar
+                .query_editor
+                .update(cx, |editor, cx| editor.clear(cx));
+        });
+
+        search_bar.update(cx, |search_bar, cx| {
+            search_bar.search_again(&SearchAgain, cx);
+        });
+        cx.run_until_parked();
+        search_bar.update(cx, |search_bar, cx| {
+            assert!(!search_bar.is_dismissed());
+            assert_eq!(search_bar.query(cx), "expression");
+            assert_eq!(search_bar.active_match_index, Some(0));
+        });
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.all_text_background_highlights(cx).len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_search_options_shared_across_workspace(cx: &mut TestAppContext) {
+        let (_editor, search_bar, cx) = init_test(cx);
+        cx.executor().allow_parking();
+        let other_search_bar = cx.new_view(BufferSearchBar::new);
+        let other_workspace_search_bar = cx.new_view(BufferSearchBar::new);
+        search_bar.update(cx, |search_bar, cx| search_bar.set_workspace_id(1, cx));
+        other_search_bar.update(cx, |search_bar, cx| search_bar.set_workspace_id(1, cx));
+        other_workspace_search_bar.update(cx, |search_bar, cx| search_bar.set_workspace_id(2, cx));
+
+        search_bar.update(cx, |search_bar, cx| {
+            search_bar.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+            search_bar.activate_search_mode(SearchMode::Regex, cx);
+        });
+        cx.run_until_parked();
+
+        other_search_bar.update(cx, |search_bar, _| {
+            assert_eq!(search_bar.search_options, SearchOptions::CASE_SENSITIVE);
+            assert_eq!(search_bar.current_mode, SearchMode::Regex);
+        });
+        other_workspace_search_bar.update(cx, |search_bar, _| {
+            assert_eq!(search_bar.search_options, SearchOptions::NONE);
+            assert_eq!(search_bar.current_mode, SearchMode::Text);
+        });
+
+        // Search bars added to the workspace later start with its current options.
+        let new_search_bar = cx.new_view(|cx| {
+            let mut search_bar = BufferSearchBar::new(cx);
+            search_bar.set_workspace_id(1, cx);
+            search_bar
+        });
+        new_search_bar.update(cx, |search_bar, _| {
+            assert_eq!(search_bar.search_options, SearchOptions::CASE_SENSITIVE);
+            assert_eq!(search_bar.current_mode, SearchMode::Regex);
+        });
+    }
 }
@@ -45,6 +45,10 @@ impl SearchHistory {
         Some(&self.history[next_index])
     }
 
+    pub fn last(&self) -> Option<&str> {
+        self.history.last().map(String::as_str)
+    }
+
     pub fn current(&self) -> Option<&str> {
         Some(&self.history[self.selected?])
     }
@@ -0,0 +1,163 @@
+use crate::{mode::SearchMode, SearchOptions};
+use anyhow::Result;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{AppContext, Global, Task};
+use serde::{Deserialize, Serialize};
+use workspace::WorkspaceId;
+
+/// The search toggles last used in each workspace, shared by all of its search
+/// bars. A workspace without an entry hasn't started loading its stored options.
+#[derive(Default)]
+pub(crate) struct WorkspaceSearchOptions(HashMap<WorkspaceId, Option<(SearchOptions, SearchMode)>>);
+
+impl Global for WorkspaceSearchOptions {}
+
+/// The search toggles last used in a workspace, restored when the workspace
+/// is opened again.
+#[derive(Serialize, Deserialize)]
+struct SerializedSearchOptions {
+    whole_word: bool,
+    case_sensitive: bool,
+    include_ignored: bool,
+    regex: bool,
+}
+
+fn search_options_key(workspace_id: WorkspaceId) -> String {
+    format!("search-options-{workspace_id}")
+}
+
+pub(crate) fn search_options(
+    workspace_id: WorkspaceId,
+    cx: &AppContext,
+) -> Option<(SearchOptions, SearchMode)> {
+    cx.try_global::<WorkspaceSearchOptions>()?
+        .0
+        .get(&workspace_id)
+        .copied()
+        .flatten()
+}
+
+/// Reads the workspace's stored options in the background, the first time the
+/// workspace is seen. Observers of [`WorkspaceSearchOptions`] are notified once
+/// they're available.
+pub(crate) fn load_search_options(workspace_id: WorkspaceId, cx: &mut AppContext) {
+    let state = cx.default_global::<WorkspaceSearchOptions>();
+    if state.0.contains_key(&workspace_id) {
+        return;
+    }
+    state.0.insert(workspace_id, None);
+
+    let read = read_search_options(workspace_id, cx);
+    cx.spawn(|mut cx| async move {
+        let options = read.await?;
+        cx.update(|cx| {
+            let state = cx.default_global::<WorkspaceSearchOptions>();
+            if let Some(entry) = state.0.get_mut(&workspace_id) {
+                // Options changed while loading take precedence over the stored ones.
+                if entry.is_none() {
+                    *entry = options;
+                }
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Shares the options with the workspace's other search bars, and stores them
+/// for the next time the workspace is opened. Semantic search is not shared,
+/// since it may be unavailable elsewhere or by then.
+pub(crate) fn set_search_options(
+    workspace_id: WorkspaceId,
+    options: SearchOptions,
+    mode: SearchMode,
+    cx: &mut AppContext,
+) {
+    let mode = if mode == SearchMode::Regex {
+        SearchMode::Regex
+    } else {
+        SearchMode::Text
+    };
+    cx.default_global::<WorkspaceSearchOptions>()
+        .0
+        .insert(workspace_id, Some((options, mode)));
+    write_search_options(workspace_id, options, mode, cx).detach_and_log_err(cx);
+}
+
+fn read_search_options(
+    workspace_id: WorkspaceId,
+    cx: &AppContext,
+) -> Task<Result<Option<(SearchOptions, SearchMode)>>> {
+    cx.background_executor().spawn(async move {
+        let Some(json) = KEY_VALUE_STORE.read_kvp(&search_options_key(workspace_id))? else {
+            return Ok(None);
+        };
+        let serialized = serde_json::from_str::<SerializedSearchOptions>(&json)?;
+
+        let mut options = SearchOptions::NONE;
+        options.set(SearchOptions::WHOLE_WORD, serialized.whole_word);
+        options.set(SearchOptions::CASE_SENSITIVE, serialized.case_sensitive);
+        options.set(SearchOptions::INCLUDE_IGNORED, serialized.include_ignored);
+        let mode = if serialized.regex {
+            SearchMode::Regex
+        } else {
+            SearchMode::Text
+        };
+        Ok(Some((options, mode)))
+    })
+}
+
+fn write_search_options(
+    workspace_id: WorkspaceId,
+    options: SearchOptions,
+    mode: SearchMode,
+    cx: &AppContext,
+) -> Task<Result<()>> {
+    let serialized = SerializedSearchOptions {
+        whole_word: options.contains(SearchOptions::WHOLE_WORD),
+        case_sensitive: options.contains(SearchOptions::CASE_SENSITIVE),
+        include_ignored: options.contains(SearchOptions::INCLUDE_IGNORED),
+        regex: mode == SearchMode::Regex,
+    };
+    cx.background_executor().spawn(async move {
+        KEY_VALUE_STORE
+            .write_kvp(
+                search_options_key(workspace_id),
+                serde_json::to_string(&serialized)?,
+            )
+            .await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_search_options_round_trip(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let workspace_id = 1234;
+        let options = SearchOptions::CASE_SENSITIVE | SearchOptions::INCLUDE_IGNORED;
+
+        let read = cx.update(|cx| read_search_options(workspace_id, cx));
+        assert_eq!(read.await.unwrap(), None);
+
+        let write =
+            cx.update(|cx| write_search_options(workspace_id, options, SearchMode::Regex, cx));
+        write.await.unwrap();
+        let read = cx.update(|cx| read_search_options(workspace_id, cx));
+        assert_eq!(read.await.unwrap(), Some((options, SearchMode::Regex)));
+
+        // Semantic search falls back to text search when restored.
+        let write = cx.update(|cx| {
+            write_search_options(workspace_id, SearchOptions::NONE, SearchMode::Semantic, cx)
+        });
+        write.await.unwrap();
+        let read = cx.update(|cx| read_search_options(workspace_id, cx));
+        assert_eq!(
+            read.await.unwrap(),
+            Some((SearchOptions::NONE, SearchMode::Text))
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use crate::{
-    history::SearchHistory, mode::SearchMode, ActivateRegexMode, ActivateSemanticMode,
+    history::SearchHistory, mode::SearchMode, persistence, ActivateRegexMode, ActivateSemanticMode,
     ActivateTextMode, CycleMode, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
-    SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored,
-    ToggleReplace, ToggleWholeWord,
+    SearchAgain, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
+    ToggleIncludeIgnored, ToggleReplace, ToggleWholeWord,
 };
 use anyhow::{Context as _, Result};
 use collections::HashMap;
@@ -105,6 +105,9 @@ pub fn init(cx: &mut AppContext) {
                 search_bar.select_next_match(action, cx)
             },
         );
+        register_workspace_action(workspace, move |search_bar, action: &SearchAgain, cx| {
+            search_bar.search_again(action, cx)
+        });
 
         // Only handle search_in_new if there is a search present
         register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
@@ -168,6 +171,7 @@ pub struct ProjectSearchView {
     filters_enabled: bool,
     replace_enabled: bool,
     current_mode: SearchMode,
+    workspace_id: Option<WorkspaceId>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -575,6 +579,7 @@ impl Item for ProjectSearchView {
     }
 
     fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.workspace_id = Some(workspace.database_id());
         self.results_editor
             .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
     }
@@ -634,11 +639,25 @@ impl Item for ProjectSearchView {
 impl ProjectSearchView {
     fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) {
         self.filters_enabled = !self.filters_enabled;
-        cx.update_global(|state: &mut ActiveSettings, cx| {
-            state.0.insert(
-                self.model.read(cx).project.downgrade(),
-                self.current_settings(),
+        self.update_active_settings(cx);
+    }
+
+    /// Remembers the current settings for new searches in the project, and for
+    /// the next time the workspace is opened.
+    fn update_active_settings(&self, cx: &mut ViewContext<Self>) {
+        let settings = self.current_settings();
+        if let Some(workspace_id) = self.workspace_id {
+            persistence::set_search_options(
+                workspace_id,
+                settings.search_options,
+                settings.current_mode,
+                cx,
             );
+        }
+        cx.update_global(|state: &mut ActiveSettings, cx| {
+            state
+                .0
+                .insert(self.model.read(cx).project.downgrade(), settings);
         });
     }
 
@@ -651,12 +670,7 @@ impl ProjectSearchView {
     }
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(option);
-        cx.update_global(|state: &mut ActiveSettings, cx| {
-            state.0.insert(
-                self.model.read(cx).project.downgrade(),
-                self.current_settings(),
-            );
-        });
+        self.update_active_settings(cx);
     }
 
     fn index_project(&mut self, cx: &mut ViewContext<Self>) {
@@ -790,13 +804,7 @@ impl ProjectSearchView {
             }
         }
 
-        cx.update_global(|state: &mut ActiveSettings, cx| {
-            state.0.insert(
-                self.model.read(cx).project.downgrade(),
-                self.current_settings(),
-            );
-        });
-
+        self.update_active_settings(cx);
         cx.notify();
     }
     fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
@@ -966,6 +974,7 @@ impl ProjectSearchView {
             filters_enabled,
             current_mode,
             replace_enabled: false,
+            workspace_id: None,
             _subscriptions: subscriptions,
         };
         this.model_changed(cx);
@@ -1092,7 +1101,13 @@ impl ProjectSearchView {
             let settings = if let Some(settings) = settings {
                 Some(settings.clone())
             } else {
-                None
+                persistence::search_options(workspace.database_id(), cx).map(
+                    |(search_options, current_mode)| ProjectSearchSettings {
+                        search_options,
+                        filters_enabled: false,
+                        current_mode,
+                    },
+                )
             };
 
             let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
@@ -1558,6 +1573,23 @@ impl ProjectSearchBar {
         }
     }
 
+    fn search_again(&mut self, _: &SearchAgain, cx: &mut ViewContext<Self>) {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.update(cx, |this, cx| {
+                let last_query = this
+                    .model
+                    .read(cx)
+                    .search_history
+                    .last()
+                    .map(str::to_string);
+                if let Some(last_query) = last_query {
+                    this.set_query(&last_query, cx);
+                    this.search(cx);
+                }
+            })
+        }
+    }
+
     fn select_next_match(&mut self, _: &SelectNextMatch, cx: &mut ViewContext<Self>) {
         if let Some(search) = self.active_project_search.as_ref() {
             search.update(cx, |this, cx| {
@@ -10,6 +10,7 @@ use ui::{ButtonStyle, IconButton};
 pub mod buffer_search;
 mod history;
 mod mode;
+mod persistence;
 pub mod project_search;
 pub(crate) mod search_bar;
 
@@ -37,6 +38,7 @@ actions!(
         ActivateRegexMode,
         ReplaceAll,
         ReplaceNext,
+        SearchAgain,
     ]
 );
 
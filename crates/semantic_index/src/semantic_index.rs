@@ -10,13 +10,14 @@ use crate::semantic_index_settings::SemanticIndexSettings;
 use ai::embedding::{Embedding, EmbeddingProvider};
 use ai::providers::open_ai::{OpenAiEmbeddingProvider, OPEN_AI_API_URL};
 use anyhow::{anyhow, Context as _, Result};
+use client::idle::IdleDetector;
 use collections::{BTreeMap, HashMap, HashSet};
 use db::VectorDatabase;
 use embedding_queue::{EmbeddingQueue, FileToEmbed};
 use futures::{future, FutureExt, StreamExt};
 use gpui::{
-    AppContext, AsyncAppContext, BorrowWindow, Context, Global, Model, ModelContext, Task,
-    ViewContext, WeakModel,
+    AppContext, AsyncAppContext, BorrowWindow, Context, Global, Model, ModelContext, Subscription,
+    Task, ViewContext, WeakModel,
 };
 use language::{Anchor, Bias, Buffer, Language, LanguageRegistry};
 use lazy_static::lazy_static;
@@ -24,7 +25,7 @@ use ordered_float::OrderedFloat;
 use parking_lot::Mutex;
 use parsing::{CodeContextRetriever, Span, SpanDigest, PARSEABLE_ENTIRE_FILE_TYPES};
 use postage::watch;
-use project::{Fs, PathChange, Project, ProjectEntryId, Worktree, WorktreeId};
+use project::{power::PowerSaving, Fs, PathChange, Project, ProjectEntryId, Worktree, WorktreeId};
 use release_channel::ReleaseChannel;
 use settings::Settings;
 use smol::channel;
@@ -134,6 +135,10 @@ pub struct SemanticIndex {
     _embedding_task: Task<()>,
     _parsing_files_tasks: Vec<Task<()>>,
     projects: HashMap<WeakModel<Project>, ProjectState>,
+    /// Projects whose changes arrived while the user was idle or power saving
+    /// was enabled, to be reindexed once background indexing resumes.
+    deferred_projects: HashSet<WeakModel<Project>>,
+    _subscriptions: Vec<Subscription>,
 }
 
 struct GlobalSemanticIndex(Model<SemanticIndex>);
@@ -410,6 +415,18 @@ impl SemanticIndex {
                 }));
             }
 
+            let mut _subscriptions = Vec::new();
+            if let Some(idle_detector) = IdleDetector::global(cx) {
+                _subscriptions.push(cx.observe(&idle_detector, |this, _, cx| {
+                    this.index_deferred_projects(cx)
+                }));
+            }
+            if let Some(power_saving) = PowerSaving::global(cx) {
+                _subscriptions.push(cx.observe(&power_saving, |this, _, cx| {
+                    this.index_deferred_projects(cx)
+                }));
+            }
+
             log::trace!(
                 "semantic index task initialization took {:?} milliseconds",
                 t0.elapsed().as_millis()
@@ -423,6 +440,8 @@ impl SemanticIndex {
                 _embedding_task,
                 _parsing_files_tasks,
                 projects: Default::default(),
+                deferred_projects: Default::default(),
+                _subscriptions,
             }
         })
     }
@@ -520,7 +539,11 @@ impl SemanticIndex {
                     .await;
                 if let Some((this, project)) = this.upgrade().zip(project.upgrade()) {
                     this.update(&mut cx, |this, cx| {
-                        this.index_project(project, cx).detach_and_log_err(cx)
+                        if Self::should_defer_background_indexing(cx) {
+                            this.deferred_projects.insert(project.downgrade());
+                        } else {
+                            this.index_project(project, cx).detach_and_log_err(cx)
+                        }
                     })?;
                 }
                 anyhow::Ok(())
@@ -529,6 +552,24 @@ impl SemanticIndex {
         }
     }
 
+    /// Background indexing waits while the user is idle, and while saving power.
+    fn should_defer_background_indexing(cx: &AppContext) -> bool {
+        let is_idle = IdleDetector::global(cx)
+            .map_or(false, |idle_detector| idle_detector.read(cx).is_idle());
+        is_idle || PowerSaving::is_enabled(cx)
+    }
+
+    fn index_deferred_projects(&mut self, cx: &mut ModelContext<Self>) {
+        if Self::should_defer_background_indexing(cx) {
+            return;
+        }
+        for project in mem::take(&mut self.deferred_projects) {
+            if let Some(project) = project.upgrade() {
+                self.index_project(project, cx).detach_and_log_err(cx);
+            }
+        }
+    }
+
     fn register_worktree(
         &mut self,
         project: Model<Project>,
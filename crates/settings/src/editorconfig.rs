@@ -0,0 +1,248 @@
+//! Support for [EditorConfig](https://editorconfig.org) files, which describe
+//! how files in a directory tree should be indented and saved, independently
+//! of the editor being used.
+//!
+//! The properties for a file are found by applying every section that
+//! matches it, in every `.editorconfig` file in its ancestor directories,
+//! from the outermost to the innermost. Files above one that declares
+//! `root = true` are ignored.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::{num::NonZeroU32, path::Path};
+
+pub const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// The EditorConfig properties that apply to a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EditorconfigProperties {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<IndentSize>,
+    pub tab_width: Option<NonZeroU32>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndentSize {
+    Columns(NonZeroU32),
+    /// Indent by the width of a tab.
+    Tab,
+}
+
+impl EditorconfigProperties {
+    /// The number of columns in an indentation level.
+    pub fn tab_size(&self) -> Option<NonZeroU32> {
+        match self.indent_size {
+            Some(IndentSize::Columns(columns)) => Some(columns),
+            Some(IndentSize::Tab) | None => self.tab_width,
+        }
+    }
+
+    /// Whether to indent with tab characters.
+    pub fn hard_tabs(&self) -> Option<bool> {
+        self.indent_style.map(|style| style == IndentStyle::Tab)
+    }
+
+    /// Sets a property from a `key = value` pair. Unknown keys and invalid
+    /// values are ignored, as the specification requires, and `unset`
+    /// removes a value set by an earlier section.
+    fn set(&mut self, key: &str, value: &str) {
+        let unset = value == "unset";
+        match key {
+            "indent_style" => {
+                self.indent_style = match value {
+                    "tab" => Some(IndentStyle::Tab),
+                    "space" => Some(IndentStyle::Space),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "indent_size" => {
+                self.indent_size = match value {
+                    "tab" => Some(IndentSize::Tab),
+                    _ if unset => None,
+                    _ => match value.parse() {
+                        Ok(columns) => Some(IndentSize::Columns(columns)),
+                        Err(_) => return,
+                    },
+                }
+            }
+            "tab_width" => {
+                self.tab_width = match value.parse() {
+                    Ok(columns) => Some(columns),
+                    Err(_) if unset => None,
+                    Err(_) => return,
+                }
+            }
+            "trim_trailing_whitespace" => {
+                self.trim_trailing_whitespace =
+                    parse_bool(value, unset).unwrap_or(self.trim_trailing_whitespace)
+            }
+            "insert_final_newline" => {
+                self.insert_final_newline =
+                    parse_bool(value, unset).unwrap_or(self.insert_final_newline)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns `Some(None)` for `unset`, and `None` for an invalid value.
+fn parse_bool(value: &str, unset: bool) -> Option<Option<bool>> {
+    match value {
+        "true" => Some(Some(true)),
+        "false" => Some(Some(false)),
+        _ if unset => Some(None),
+        _ => None,
+    }
+}
+
+/// A parsed `.editorconfig` file.
+#[derive(Debug)]
+pub struct Editorconfig {
+    root: bool,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug)]
+struct Section {
+    matcher: Option<GlobMatcher>,
+    properties: Vec<(String, String)>,
+}
+
+impl Editorconfig {
+    /// Parses the contents of an `.editorconfig` file. Lines that can't be
+    /// parsed are skipped, so that one mistake doesn't discard the whole file.
+    pub fn parse(content: &str) -> Self {
+        let mut root = false;
+        let mut sections = Vec::<Section>::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(glob) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                sections.push(Section {
+                    matcher: glob_matcher(glob),
+                    properties: Vec::new(),
+                });
+            } else if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim().to_ascii_lowercase();
+                let value = value.trim().to_ascii_lowercase();
+                match sections.last_mut() {
+                    Some(section) => section.properties.push((key, value)),
+                    None if key == "root" => root = value == "true",
+                    None => {}
+                }
+            }
+        }
+        Self { root, sections }
+    }
+
+    /// Whether this file stops the search for `.editorconfig` files in
+    /// ancestor directories.
+    pub fn is_root(&self) -> bool {
+        self.root
+    }
+
+    /// Applies the sections that match `path`, which is relative to the
+    /// directory containing this file.
+    pub fn apply(&self, path: &Path, properties: &mut EditorconfigProperties) {
+        for section in &self.sections {
+            if section
+                .matcher
+                .as_ref()
+                .map_or(false, |matcher| matcher.is_match(path))
+            {
+                for (key, value) in &section.properties {
+                    properties.set(key, value);
+                }
+            }
+        }
+    }
+}
+
+/// Globs without a slash match files with that name in any directory, and
+/// other globs are relative to the directory containing the file.
+fn glob_matcher(glob: &str) -> Option<GlobMatcher> {
+    let pattern = if glob.contains('/') {
+        glob.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{glob}")
+    };
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .ok()
+        .map(|glob| glob.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_editorconfig() {
+        let editorconfig = Editorconfig::parse(
+            &"
+            # Top-level file
+            root = true
+
+            [*]
+            indent_style = space
+            indent_size = 4
+            insert_final_newline = true
+            trim_trailing_whitespace = true
+
+            [*.{md,txt}]
+            trim_trailing_whitespace = false
+
+            [Makefile]
+            indent_style = tab
+            indent_size = tab
+            tab_width = 8
+
+            [docs/**/*.md]
+            indent_size = 2
+            insert_final_newline = unset
+            "
+            .unindent(),
+        );
+        assert!(editorconfig.is_root());
+
+        let properties = |path: &str| {
+            let mut properties = EditorconfigProperties::default();
+            editorconfig.apply(Path::new(path), &mut properties);
+            properties
+        };
+
+        let rust = properties("src/main.rs");
+        assert_eq!(rust.tab_size(), NonZeroU32::new(4));
+        assert_eq!(rust.hard_tabs(), Some(false));
+        assert_eq!(rust.trim_trailing_whitespace, Some(true));
+        assert_eq!(rust.insert_final_newline, Some(true));
+
+        let makefile = properties("build/Makefile");
+        assert_eq!(makefile.tab_size(), NonZeroU32::new(8));
+        assert_eq!(makefile.hard_tabs(), Some(true));
+
+        assert_eq!(
+            properties("README.md").trim_trailing_whitespace,
+            Some(false)
+        );
+        let docs = properties("docs/guide/intro.md");
+        assert_eq!(docs.tab_size(), NonZeroU32::new(2));
+        assert_eq!(docs.insert_final_newline, None);
+    }
+}
@@ -0,0 +1,561 @@
+use serde_json::Value;
+use std::ops::Range;
+
+/// A step in the path from the root of a JSON document to a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonPathSegment {
+    Key(String),
+    Index,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonLocationKind {
+    /// The position is where an object key is, or is about to be typed.
+    Key,
+    /// The position is where a value is, or is about to be typed.
+    Value,
+}
+
+/// What a position in a JSON document refers to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonLocation {
+    /// For keys, the path of the object containing the key. For values, the
+    /// path of the value itself.
+    pub path: Vec<JsonPathSegment>,
+    pub kind: JsonLocationKind,
+    /// The range of the key or value token at the position, which is empty
+    /// when there is no token there yet.
+    pub range: Range<usize>,
+}
+
+/// A key found in a JSON document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonKey {
+    /// The path of the object containing the key.
+    pub path: Vec<JsonPathSegment>,
+    pub name: String,
+    pub range: Range<usize>,
+}
+
+/// A property that a JSON schema allows on an object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaProperty {
+    pub name: String,
+    pub description: Option<String>,
+    pub deprecation: Option<String>,
+}
+
+/// Returns what the given offset in a JSON document refers to. The document
+/// may contain comments and may be incomplete, as it is while being typed.
+pub fn json_location_at_offset(text: &str, offset: usize) -> Option<JsonLocation> {
+    let mut scanner = JsonScanner::default();
+    let mut tokens = JsonTokens::new(text);
+    while let Some((token, range)) = tokens.next() {
+        if range.start >= offset {
+            break;
+        }
+        if range.end >= offset && matches!(token, JsonToken::String | JsonToken::Literal) {
+            let kind = scanner.expected_kind()?;
+            let mut path = scanner.path();
+            if kind == JsonLocationKind::Value {
+                path.extend(scanner.pending_segment());
+            }
+            return Some(JsonLocation { path, kind, range });
+        }
+        scanner.push(token, &text[range]);
+    }
+
+    let kind = scanner.expected_kind()?;
+    let mut path = scanner.path();
+    if kind == JsonLocationKind::Value {
+        path.extend(scanner.pending_segment());
+    }
+    Some(JsonLocation {
+        path,
+        kind,
+        range: offset..offset,
+    })
+}
+
+/// Returns every object key in a JSON document, in the order they appear.
+pub fn json_keys(text: &str) -> Vec<JsonKey> {
+    let mut keys = Vec::new();
+    let mut scanner = JsonScanner::default();
+    for (token, range) in JsonTokens::new(text) {
+        if token == JsonToken::String && scanner.expected_kind() == Some(JsonLocationKind::Key) {
+            keys.push(JsonKey {
+                path: scanner.path(),
+                name: unquote(&text[range.clone()]),
+                range: range.clone(),
+            });
+        }
+        scanner.push(token, &text[range]);
+    }
+    keys
+}
+
+/// Returns the schemas that apply to the value at the given path.
+pub fn schemas_at_path<'a>(root: &'a Value, path: &[JsonPathSegment]) -> Vec<&'a Value> {
+    let mut schemas = Vec::new();
+    resolve_schema(root, root, &mut schemas, 0);
+    for segment in path {
+        let mut child_schemas = Vec::new();
+        for schema in schemas {
+            let child = match segment {
+                JsonPathSegment::Key(key) => schema
+                    .get("properties")
+                    .and_then(|properties| properties.get(key))
+                    .or_else(|| schema.get("additionalProperties").filter(|s| s.is_object())),
+                JsonPathSegment::Index => schema.get("items").and_then(|items| match items {
+                    Value::Array(items) => items.first(),
+                    items => Some(items),
+                }),
+            };
+            if let Some(child) = child {
+                resolve_schema(root, child, &mut child_schemas, 0);
+            }
+        }
+        schemas = child_schemas;
+    }
+    schemas
+}
+
+/// Returns the properties that the schemas allow on an object, in the order
+/// they are declared.
+pub fn schema_properties(root: &Value, schemas: &[&Value]) -> Vec<SchemaProperty> {
+    let mut properties = Vec::<SchemaProperty>::new();
+    for schema in schemas {
+        let Some(Value::Object(schema_properties)) = schema.get("properties") else {
+            continue;
+        };
+        for (name, property_schema) in schema_properties {
+            if properties.iter().any(|property| &property.name == name) {
+                continue;
+            }
+            let mut property_schemas = Vec::new();
+            resolve_schema(root, property_schema, &mut property_schemas, 0);
+            properties.push(SchemaProperty {
+                name: name.clone(),
+                description: schema_description(&property_schemas),
+                deprecation: schema_deprecation(&property_schemas),
+            });
+        }
+    }
+    properties
+}
+
+/// Returns the values that the schemas allow, as JSON text, for schemas that
+/// only allow a few values.
+pub fn schema_values(schemas: &[&Value]) -> Vec<String> {
+    let mut values = Vec::new();
+    for schema in schemas {
+        let allowed = match (schema.get("enum"), schema.get("const")) {
+            (Some(Value::Array(allowed)), _) => allowed.clone(),
+            (_, Some(allowed)) => vec![allowed.clone()],
+            _ if schema_has_type(schema, "boolean") => vec![Value::Bool(true), Value::Bool(false)],
+            _ => Vec::new(),
+        };
+        for value in allowed {
+            let value = value.to_string();
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+    }
+    values
+}
+
+/// Returns the first description among the schemas.
+pub fn schema_description(schemas: &[&Value]) -> Option<String> {
+    schemas.iter().find_map(|schema| {
+        schema
+            .get("description")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    })
+}
+
+/// Returns why the value is deprecated, if any of the schemas deprecate it.
+pub fn schema_deprecation(schemas: &[&Value]) -> Option<String> {
+    schemas.iter().find_map(|schema| {
+        if let Some(message) = schema.get("deprecationMessage").and_then(Value::as_str) {
+            Some(message.to_string())
+        } else if schema.get("deprecated") == Some(&Value::Bool(true)) {
+            Some("This setting is deprecated.".to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the keys of the document that are deprecated by the schema, along
+/// with why they are.
+pub fn deprecated_keys(text: &str, root: &Value) -> Vec<(JsonKey, String)> {
+    json_keys(text)
+        .into_iter()
+        .filter_map(|key| {
+            let mut path = key.path.clone();
+            path.push(JsonPathSegment::Key(key.name.clone()));
+            let deprecation = schema_deprecation(&schemas_at_path(root, &path))?;
+            Some((key, deprecation))
+        })
+        .collect()
+}
+
+/// Schemas can nest deeply through references, but not infinitely.
+const MAX_SCHEMA_DEPTH: usize = 32;
+
+/// Collects the schema and every schema it refers to or combines.
+fn resolve_schema<'a>(root: &'a Value, schema: &'a Value, out: &mut Vec<&'a Value>, depth: usize) {
+    if depth > MAX_SCHEMA_DEPTH || !schema.is_object() {
+        return;
+    }
+    out.push(schema);
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if let Some(target) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            resolve_schema(root, target, out, depth + 1);
+        }
+    }
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        if let Some(Value::Array(subschemas)) = schema.get(combinator) {
+            for subschema in subschemas {
+                resolve_schema(root, subschema, out, depth + 1);
+            }
+        }
+    }
+}
+
+fn schema_has_type(schema: &Value, type_name: &str) -> bool {
+    match schema.get("type") {
+        Some(Value::String(name)) => name == type_name,
+        Some(Value::Array(names)) => names.iter().any(|name| name == type_name),
+        _ => false,
+    }
+}
+
+fn unquote(string: &str) -> String {
+    serde_json::from_str(string).unwrap_or_else(|_| string.trim_matches('"').to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum JsonToken {
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Colon,
+    Comma,
+    String,
+    Literal,
+}
+
+/// Splits JSON text into tokens, skipping whitespace and comments. Strings
+/// that aren't terminated extend to the end of their line.
+struct JsonTokens<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> JsonTokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, offset: 0 }
+    }
+}
+
+impl<'a> Iterator for JsonTokens<'a> {
+    type Item = (JsonToken, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.text.as_bytes();
+        loop {
+            let start = self.offset;
+            let byte = *bytes.get(start)?;
+            let (token, end) = match byte {
+                b' ' | b'\t' | b'\r' | b'\n' => {
+                    self.offset += 1;
+                    continue;
+                }
+                b'/' if bytes.get(start + 1) == Some(&b'/') => {
+                    self.offset = self.text[start..]
+                        .find('\n')
+                        .map_or(self.text.len(), |ix| start + ix);
+                    continue;
+                }
+                b'/' if bytes.get(start + 1) == Some(&b'*') => {
+                    self.offset = self.text[start + 2..]
+                        .find("*/")
+                        .map_or(self.text.len(), |ix| start + 2 + ix + 2);
+                    continue;
+                }
+                b'{' => (JsonToken::OpenBrace, start + 1),
+                b'}' => (JsonToken::CloseBrace, start + 1),
+                b'[' => (JsonToken::OpenBracket, start + 1),
+                b']' => (JsonToken::CloseBracket, start + 1),
+                b':' => (JsonToken::Colon, start + 1),
+                b',' => (JsonToken::Comma, start + 1),
+                b'"' => {
+                    let mut end = start + 1;
+                    loop {
+                        match bytes.get(end) {
+                            Some(b'\\') => end += 2,
+                            Some(b'"') => break end += 1,
+                            Some(b'\n') | None => break,
+                            Some(_) => end += 1,
+                        }
+                    }
+                    (JsonToken::String, end.min(self.text.len()))
+                }
+                _ => {
+                    let end = self.text[start..]
+                        .find(|c: char| {
+                            c.is_whitespace()
+                                || matches!(c, '{' | '}' | '[' | ']' | ':' | ',' | '"')
+                        })
+                        .map_or(self.text.len(), |ix| start + ix);
+                    (JsonToken::Literal, end.max(start + 1))
+                }
+            };
+            self.offset = end;
+            return Some((token, start..end));
+        }
+    }
+}
+
+enum JsonFrame {
+    Object {
+        key: Option<String>,
+        expects_value: bool,
+    },
+    Array {
+        expects_value: bool,
+    },
+}
+
+/// Tracks the containers enclosing the current token.
+#[derive(Default)]
+struct JsonScanner {
+    frames: Vec<JsonFrame>,
+}
+
+impl JsonScanner {
+    fn push(&mut self, token: JsonToken, text: &str) {
+        match token {
+            JsonToken::OpenBrace => {
+                self.value_started();
+                self.frames.push(JsonFrame::Object {
+                    key: None,
+                    expects_value: false,
+                });
+            }
+            JsonToken::OpenBracket => {
+                self.value_started();
+                self.frames.push(JsonFrame::Array {
+                    expects_value: true,
+                });
+            }
+            JsonToken::CloseBrace | JsonToken::CloseBracket => {
+                self.frames.pop();
+            }
+            JsonToken::Colon => {
+                if let Some(JsonFrame::Object { expects_value, .. }) = self.frames.last_mut() {
+                    *expects_value = true;
+                }
+            }
+            JsonToken::Comma => match self.frames.last_mut() {
+                Some(JsonFrame::Object { key, expects_value }) => {
+                    *key = None;
+                    *expects_value = false;
+                }
+                Some(JsonFrame::Array { expects_value }) => *expects_value = true,
+                None => {}
+            },
+            JsonToken::String => match self.frames.last_mut() {
+                Some(JsonFrame::Object {
+                    key,
+                    expects_value: false,
+                }) => *key = Some(unquote(text)),
+                _ => self.value_started(),
+            },
+            JsonToken::Literal => self.value_started(),
+        }
+    }
+
+    /// Marks the value expected by the innermost container as present, so
+    /// that the container's path no longer includes it once it ends.
+    fn value_started(&mut self) {
+        if let Some(JsonFrame::Array { expects_value }) = self.frames.last_mut() {
+            *expects_value = false;
+        }
+    }
+
+    /// The path of the innermost container.
+    fn path(&self) -> Vec<JsonPathSegment> {
+        let mut path = Vec::new();
+        let Some((_, parents)) = self.frames.split_last() else {
+            return path;
+        };
+        for frame in parents {
+            match frame {
+                JsonFrame::Object { key, .. } => {
+                    path.push(JsonPathSegment::Key(key.clone().unwrap_or_default()))
+                }
+                JsonFrame::Array { .. } => path.push(JsonPathSegment::Index),
+            }
+        }
+        path
+    }
+
+    /// The path segment of the value expected next by the innermost container.
+    fn pending_segment(&self) -> Option<JsonPathSegment> {
+        match self.frames.last()? {
+            JsonFrame::Object { key, .. } => Some(JsonPathSegment::Key(key.clone()?)),
+            JsonFrame::Array { .. } => Some(JsonPathSegment::Index),
+        }
+    }
+
+    fn expected_kind(&self) -> Option<JsonLocationKind> {
+        match self.frames.last()? {
+            JsonFrame::Object {
+                expects_value: false,
+                ..
+            } => Some(JsonLocationKind::Key),
+            JsonFrame::Object { key: Some(_), .. } | JsonFrame::Array { .. } => {
+                Some(JsonLocationKind::Value)
+            }
+            JsonFrame::Object { key: None, .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(name: &str) -> JsonPathSegment {
+        JsonPathSegment::Key(name.to_string())
+    }
+
+    fn location(marked_text: &str) -> JsonLocation {
+        let offset = marked_text.find('|').unwrap();
+        let text = marked_text.replacen('|', "", 1);
+        json_location_at_offset(&text, offset).unwrap()
+    }
+
+    #[test]
+    fn test_json_location_at_offset() {
+        assert_eq!(
+            location("{\n  // a comment {\n  \"theme\": \"One|\",\n}"),
+            JsonLocation {
+                path: vec![key("theme")],
+                kind: JsonLocationKind::Value,
+                range: 30..35,
+            }
+        );
+        assert_eq!(
+            location("{ \"terminal\": { \"fo|\" } }"),
+            JsonLocation {
+                path: vec![key("terminal")],
+                kind: JsonLocationKind::Key,
+                range: 16..20,
+            }
+        );
+        assert_eq!(
+            location("{ \"terminal\": { \"font_size\": 1, | } }"),
+            JsonLocation {
+                path: vec![key("terminal")],
+                kind: JsonLocationKind::Key,
+                range: 32..32,
+            }
+        );
+        assert_eq!(
+            location("{ \"languages\": { \"Rust\": { \"tab_size\": |"),
+            JsonLocation {
+                path: vec![key("languages"), key("Rust"), key("tab_size")],
+                kind: JsonLocationKind::Value,
+                range: 39..39,
+            }
+        );
+        assert_eq!(
+            location("[{ \"bindings\": { \"a\": [\"x\", 1] }}, { \"context\": tr|"),
+            JsonLocation {
+                path: vec![JsonPathSegment::Index, key("context")],
+                kind: JsonLocationKind::Value,
+                range: 48..50,
+            }
+        );
+    }
+
+    #[test]
+    fn test_schema_lookup() {
+        let schema = json!({
+            "definitions": {
+                "Dock": { "type": "string", "enum": ["left", "right"] },
+                "Panel": {
+                    "type": "object",
+                    "properties": {
+                        "dock": {
+                            "description": "Where to dock the panel.",
+                            "allOf": [{ "$ref": "#/definitions/Dock" }]
+                        },
+                        "button": { "type": "boolean", "deprecated": true }
+                    }
+                }
+            },
+            "type": "object",
+            "properties": {
+                "project_panel": { "$ref": "#/definitions/Panel" },
+                "languages": {
+                    "type": "object",
+                    "additionalProperties": { "$ref": "#/definitions/Panel" }
+                }
+            }
+        });
+
+        let panel = schemas_at_path(&schema, &[key("project_panel")]);
+        assert_eq!(
+            schema_properties(&schema, &panel),
+            [
+                SchemaProperty {
+                    name: "dock".into(),
+                    description: Some("Where to dock the panel.".into()),
+                    deprecation: None,
+                },
+                SchemaProperty {
+                    name: "button".into(),
+                    description: None,
+                    deprecation: Some("This setting is deprecated.".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            schema_values(&schemas_at_path(
+                &schema,
+                &[key("languages"), key("Rust"), key("dock")]
+            )),
+            ["\"left\"", "\"right\""]
+        );
+        assert_eq!(
+            schema_values(&schemas_at_path(
+                &schema,
+                &[key("project_panel"), key("button")]
+            )),
+            ["true", "false"]
+        );
+
+        let text = "{ \"project_panel\": { \"button\": true }, \"languages\": { \"Go\": { \"button\": false } } }";
+        assert_eq!(
+            deprecated_keys(text, &schema)
+                .into_iter()
+                .map(|(key, _)| (key.name, key.range))
+                .collect::<Vec<_>>(),
+            [
+                ("button".to_string(), 21..29),
+                ("button".to_string(), 62..70)
+            ]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{settings_store::parse_json_with_comments, SettingsAssets};
 use anyhow::{anyhow, Context, Result};
 use collections::BTreeMap;
-use gpui::{Action, AppContext, KeyBinding, SharedString};
+use gpui::{Action, AppContext, KeyBinding, Keystroke, SharedString};
 use schemars::{
     gen::{SchemaGenerator, SchemaSettings},
     schema::{InstanceType, Schema, SchemaObject, SingleOrVec, SubschemaValidation},
@@ -9,6 +9,8 @@ use schemars::{
 };
 use serde::Deserialize;
 use serde_json::Value;
+use std::ops::Range;
+use tree_sitter::Node;
 use util::{asset_str, ResultExt};
 
 #[derive(Debug, Deserialize, Default, Clone, JsonSchema)]
@@ -39,18 +41,302 @@ impl JsonSchema for KeymapAction {
 #[derive(Deserialize)]
 struct ActionWithData(Box<str>, Value);
 
+/// A keystroke that is bound to two different actions in the same context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeymapConflict {
+    pub context: Option<String>,
+    pub keystrokes: String,
+    pub action: Value,
+    pub overridden_action: Value,
+}
+
+/// A binding from one of the keymaps that are in effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeymapBindingInfo {
+    /// The name of the keymap that defines the binding.
+    pub source: SharedString,
+    pub context: Option<String>,
+    pub keystrokes: String,
+    pub action: Value,
+    pub status: KeymapBindingStatus,
+}
+
+/// How a binding interacts with the other bindings in effect.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeymapBindingStatus {
+    Active,
+    /// A keymap loaded later binds the same keystrokes in the same context.
+    Overridden {
+        source: SharedString,
+        action: Value,
+    },
+    /// A longer binding in the same context starts with the same keystrokes,
+    /// so this one only runs after waiting for further input.
+    Shadowed {
+        keystrokes: String,
+    },
+}
+
+/// Lists the bindings of the given keymaps, which are loaded in the given order,
+/// and determines which ones are overridden or shadowed by other bindings.
+///
+/// Contexts are compared as written, so bindings whose context predicates
+/// differ but match the same elements are not reported.
+pub fn analyze_keymaps(keymaps: &[(SharedString, KeymapFile)]) -> Vec<KeymapBindingInfo> {
+    let mut bindings = Vec::<KeymapBindingInfo>::new();
+    let mut active_bindings = BTreeMap::default();
+    for (source, keymap) in keymaps {
+        for (context, keystrokes, action) in keymap.bindings() {
+            let key = (context, normalize_keystrokes(keystrokes));
+            if let Some(overridden_ix) = active_bindings.insert(key, bindings.len()) {
+                bindings[overridden_ix].status = KeymapBindingStatus::Overridden {
+                    source: source.clone(),
+                    action: action.clone(),
+                };
+            }
+            bindings.push(KeymapBindingInfo {
+                source: source.clone(),
+                context: context.map(ToString::to_string),
+                keystrokes: keystrokes.to_string(),
+                action: action.clone(),
+                status: KeymapBindingStatus::Active,
+            });
+        }
+    }
+
+    for ((context, keystrokes), &ix) in &active_bindings {
+        if bindings[ix].action.is_null() {
+            continue;
+        }
+        let prefix = format!("{keystrokes} ");
+        let longer_binding = active_bindings
+            .range((*context, prefix.clone())..)
+            .take_while(|((longer_context, longer_keystrokes), _)| {
+                longer_context == context && longer_keystrokes.starts_with(&prefix)
+            })
+            .find(|(_, &longer_ix)| !bindings[longer_ix].action.is_null());
+        if let Some((_, &longer_ix)) = longer_binding {
+            bindings[ix].status = KeymapBindingStatus::Shadowed {
+                keystrokes: bindings[longer_ix].keystrokes.clone(),
+            };
+        }
+    }
+
+    bindings
+}
+
+/// Finds the offset at which a binding is defined in the source of a keymap file. When
+/// several blocks for the context bind the keystrokes, the last one, which takes
+/// precedence, is returned.
+pub fn binding_offset(content: &str, context: Option<&str>, keystrokes: &str) -> Option<usize> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let tree = parser.parse(content, None)?;
+    let blocks = named_children(tree.root_node()).find(|node| node.kind() != "comment")?;
+
+    let normalized_keystrokes = normalize_keystrokes(keystrokes);
+    named_children(blocks)
+        .filter(|block| {
+            block.kind() == "object"
+                && pair_value(*block, content, "context")
+                    .and_then(|context| string_value(context, content))
+                    .as_deref()
+                    == context
+        })
+        .filter_map(|block| pair_value(block, content, "bindings"))
+        .flat_map(named_children)
+        .filter(|node| node.kind() == "pair")
+        .filter_map(|pair| pair.child_by_field_name("key"))
+        .filter(|key| {
+            string_value(*key, content).map(|keystrokes| normalize_keystrokes(&keystrokes))
+                == Some(normalized_keystrokes.clone())
+        })
+        .last()
+        .map(|key| key.start_byte())
+}
+
+/// Binds the given keystrokes in the keymap block for `context`, replacing the actions they
+/// are already bound to there, and returns the new keymap.json content. The last block for
+/// the context is used, since its bindings take precedence, and a new block is appended if
+/// there is none. The rest of the file, including its comments, is left as it was.
+pub fn set_keymap_bindings(
+    content: &str,
+    context: Option<&str>,
+    bindings: &[(String, Value)],
+) -> Result<String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+
+    let mut content = content.to_string();
+    for (keystrokes, action) in bindings {
+        let tree = parser
+            .parse(&content, None)
+            .context("failed to parse keymap")?;
+        let (range, new_text) =
+            keymap_binding_edit(&content, tree.root_node(), context, keystrokes, action)?;
+        content.replace_range(range, &new_text);
+    }
+    Ok(content)
+}
+
+fn keymap_binding_edit(
+    content: &str,
+    root: Node,
+    context: Option<&str>,
+    keystrokes: &str,
+    action: &Value,
+) -> Result<(Range<usize>, String)> {
+    let key = serde_json::to_string(keystrokes)?;
+    let value = serde_json::to_string(action)?;
+
+    let Some(blocks) = named_children(root).find(|node| node.kind() != "comment") else {
+        let separator = if content.is_empty() || content.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        let block = keymap_block(context, &key, &value, "  ");
+        return Ok((
+            content.len()..content.len(),
+            format!("{separator}[\n  {block}\n]\n"),
+        ));
+    };
+    if blocks.kind() != "array" {
+        return Err(anyhow!("keymap must be an array of blocks"));
+    }
+
+    let normalized_keystrokes = normalize_keystrokes(keystrokes);
+    let block_bindings = named_children(blocks)
+        .filter(|block| {
+            block.kind() == "object"
+                && pair_value(*block, content, "context")
+                    .and_then(|context| string_value(context, content))
+                    .as_deref()
+                    == context
+        })
+        .filter_map(|block| pair_value(block, content, "bindings"))
+        .filter(|bindings| bindings.kind() == "object")
+        .last();
+
+    if let Some(block_bindings) = block_bindings {
+        let pairs = named_children(block_bindings)
+            .filter(|node| node.kind() == "pair")
+            .collect::<Vec<_>>();
+        for pair in &pairs {
+            let (Some(existing_key), Some(existing_value)) = (
+                pair.child_by_field_name("key"),
+                pair.child_by_field_name("value"),
+            ) else {
+                continue;
+            };
+            let existing_keystrokes = string_value(existing_key, content);
+            if existing_keystrokes.map(|keystrokes| normalize_keystrokes(&keystrokes))
+                == Some(normalized_keystrokes.clone())
+            {
+                return Ok((existing_value.byte_range(), value));
+            }
+        }
+
+        return Ok(match pairs.last() {
+            Some(last_pair) => {
+                let separator =
+                    if last_pair.start_position().row == block_bindings.start_position().row {
+                        " ".to_string()
+                    } else {
+                        format!("\n{}", line_indent(content, last_pair.start_byte()))
+                    };
+                (
+                    last_pair.end_byte()..last_pair.end_byte(),
+                    format!(",{separator}{key}: {value}"),
+                )
+            }
+            None => (block_bindings.byte_range(), format!("{{ {key}: {value} }}")),
+        });
+    }
+
+    let last_block = named_children(blocks)
+        .filter(|node| node.kind() != "comment")
+        .last();
+    Ok(match last_block {
+        Some(last_block) => {
+            let indent = line_indent(content, last_block.start_byte());
+            let block = keymap_block(context, &key, &value, indent);
+            (
+                last_block.end_byte()..last_block.end_byte(),
+                format!(",\n{indent}{block}"),
+            )
+        }
+        None => {
+            let block = keymap_block(context, &key, &value, "  ");
+            (blocks.byte_range(), format!("[\n  {block}\n]"))
+        }
+    })
+}
+
+/// Formats a keymap block with a single binding, to be inserted at the given indentation.
+fn keymap_block(context: Option<&str>, key: &str, value: &str, indent: &str) -> String {
+    let mut block = "{\n".to_string();
+    if let Some(context) = context {
+        let context = serde_json::to_string(context).unwrap();
+        block.push_str(&format!("{indent}  \"context\": {context},\n"));
+    }
+    block.push_str(&format!(
+        "{indent}  \"bindings\": {{\n{indent}    {key}: {value}\n{indent}  }}\n{indent}}}"
+    ));
+    block
+}
+
+fn named_children<'a>(node: Node<'a>) -> impl Iterator<Item = Node<'a>> {
+    (0..node.named_child_count()).filter_map(move |ix| node.named_child(ix))
+}
+
+/// Returns the value of the given key in a JSON object node.
+fn pair_value<'a>(object: Node<'a>, content: &str, key: &str) -> Option<Node<'a>> {
+    named_children(object)
+        .filter(|node| node.kind() == "pair")
+        .find(|pair| {
+            pair.child_by_field_name("key")
+                .and_then(|pair_key| string_value(pair_key, content))
+                .as_deref()
+                == Some(key)
+        })?
+        .child_by_field_name("value")
+}
+
+fn string_value(node: Node, content: &str) -> Option<String> {
+    if node.kind() == "string" {
+        serde_json::from_str(&content[node.byte_range()]).ok()
+    } else {
+        None
+    }
+}
+
+/// Returns the whitespace at the start of the line containing the given offset.
+fn line_indent(content: &str, offset: usize) -> &str {
+    let line_start = content[..offset].rfind('\n').map_or(0, |ix| ix + 1);
+    let line = &content[line_start..offset];
+    &line[..line.len() - line.trim_start().len()]
+}
+
 impl KeymapFile {
     pub fn load_asset(asset_path: &str, cx: &mut AppContext) -> Result<()> {
         let content = asset_str::<SettingsAssets>(asset_path);
 
-        Self::parse(content.as_ref())?.add_to_cx(cx)
+        // Bundled keymaps may refer to actions from crates that aren't linked
+        // into every binary, so invalid bindings are only logged.
+        Self::parse(content.as_ref())?.add_to_cx(cx).log_err();
+        Ok(())
     }
 
     pub fn parse(content: &str) -> Result<Self> {
         parse_json_with_comments::<Self>(content)
     }
 
+    /// Binds every valid binding in the keymap. Bindings whose actions are
+    /// unknown or have invalid arguments are skipped, and reported together
+    /// in the returned error.
     pub fn add_to_cx(self, cx: &mut AppContext) -> Result<()> {
+        let mut errors = Vec::new();
         for KeymapBlock { context, bindings } in self.0 {
             let bindings = bindings
                 .into_iter()
@@ -61,39 +347,79 @@ impl KeymapFile {
                     // We want to deserialize the action data as a `RawValue` so that we can
                     // deserialize the action itself dynamically directly from the JSON
                     // string. But `RawValue` currently does not work inside of an untagged enum.
-                    match action {
-                        Value::Array(items) => {
-                            let Ok([name, data]): Result<[serde_json::Value; 2], _> =
-                                items.try_into()
-                            else {
-                                return Some(Err(anyhow!("Expected array of length 2")));
-                            };
-                            let serde_json::Value::String(name) = name else {
-                                return Some(Err(anyhow!(
-                                    "Expected first item in array to be a string."
-                                )));
-                            };
-                            cx.build_action(&name, Some(data))
-                        }
+                    let action = match action {
+                        Value::Array(items) => match <[serde_json::Value; 2]>::try_from(items) {
+                            Ok([serde_json::Value::String(name), data]) => {
+                                cx.build_action(&name, Some(data))
+                            }
+                            Ok(_) => Err(anyhow!("Expected first item in array to be a string.")),
+                            Err(_) => Err(anyhow!("Expected array of length 2")),
+                        },
                         Value::String(name) => cx.build_action(&name, None),
                         Value::Null => Ok(no_action()),
-                        _ => {
-                            return Some(Err(anyhow!("Expected two-element array, got {action:?}")))
-                        }
+                        _ => Err(anyhow!("Expected two-element array, got {action:?}")),
                     }
                     .with_context(|| {
                         format!(
                             "invalid binding value for keystroke {keystroke}, context {context:?}"
                         )
-                    })
-                    .log_err()
-                    .map(|action| KeyBinding::load(&keystroke, action, context.as_deref()))
+                    });
+
+                    match action {
+                        Ok(action) => {
+                            Some(KeyBinding::load(&keystroke, action, context.as_deref()))
+                        }
+                        Err(error) => {
+                            errors.push(format!("{error:#}"));
+                            None
+                        }
+                    }
                 })
                 .collect::<Result<Vec<_>>>()?;
 
             cx.bind_keys(bindings);
         }
-        Ok(())
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("{}", errors.join("\n")))
+        }
+    }
+
+    /// Returns every binding in the keymap, as its context, keystrokes, and action.
+    pub fn bindings(&self) -> impl Iterator<Item = (Option<&str>, &str, &Value)> {
+        self.0.iter().flat_map(|block| {
+            block.bindings.iter().map(|(keystrokes, action)| {
+                (block.context.as_deref(), keystrokes.as_str(), &action.0)
+            })
+        })
+    }
+
+    /// Finds the bindings in this keymap that replace a different action bound to the same
+    /// keystrokes in the same context, either by the base keymap or earlier in this one.
+    pub fn conflicts(&self, base: &KeymapFile) -> Vec<KeymapConflict> {
+        let mut bound_actions = BTreeMap::default();
+        for (context, keystrokes, action) in base.bindings() {
+            bound_actions.insert((context, normalize_keystrokes(keystrokes)), action);
+        }
+
+        let mut conflicts = Vec::new();
+        for (context, keystrokes, action) in self.bindings() {
+            let key = (context, normalize_keystrokes(keystrokes));
+            if let Some(overridden_action) = bound_actions.insert(key, action) {
+                if overridden_action != action && !overridden_action.is_null() && !action.is_null()
+                {
+                    conflicts.push(KeymapConflict {
+                        context: context.map(ToString::to_string),
+                        keystrokes: keystrokes.to_string(),
+                        action: action.clone(),
+                        overridden_action: overridden_action.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
     }
 
     pub fn generate_json_schema(action_names: &[SharedString]) -> serde_json::Value {
@@ -137,13 +463,32 @@ impl KeymapFile {
     }
 }
 
+/// Spells keystrokes consistently, so that e.g. `shift-cmd-p` and `cmd-shift-p` are the same.
+fn normalize_keystrokes(keystrokes: &str) -> String {
+    keystrokes
+        .split_whitespace()
+        .map(|keystroke| {
+            Keystroke::parse(keystroke)
+                .map(|keystroke| keystroke.to_string())
+                .unwrap_or_else(|_| keystroke.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 fn no_action() -> Box<dyn gpui::Action> {
     gpui::NoAction.boxed_clone()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::KeymapFile;
+    use crate::{
+        analyze_keymaps, binding_offset, set_keymap_bindings, KeymapBindingStatus, KeymapFile,
+    };
+    use gpui::{actions, TestAppContext};
+    use serde_json::{json, Value};
+
+    actions!(test_keymap, [KnownAction]);
 
     #[test]
     fn can_deserialize_keymap_with_trailing_comma() {
@@ -160,4 +505,202 @@ mod tests {
         };
         KeymapFile::parse(json).unwrap();
     }
+
+    #[test]
+    fn detects_conflicting_bindings() {
+        let base = KeymapFile::parse(
+            r#"[
+                { "bindings": { "cmd-shift-p": "command_palette::Toggle" } },
+                { "context": "Editor", "bindings": { "cmd-d": "editor::SelectNext" } }
+            ]"#,
+        )
+        .unwrap();
+        let user = KeymapFile::parse(
+            r#"[
+                { "bindings": { "shift-cmd-p": "file_finder::Toggle", "cmd-d": "editor::Cancel" } },
+                { "context": "Editor", "bindings": { "cmd-d": null } }
+            ]"#,
+        )
+        .unwrap();
+
+        let conflicts = user.conflicts(&base);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].context, None);
+        assert_eq!(conflicts[0].keystrokes, "shift-cmd-p");
+        assert_eq!(conflicts[0].action, "file_finder::Toggle");
+        assert_eq!(conflicts[0].overridden_action, "command_palette::Toggle");
+    }
+
+    #[test]
+    fn analyzes_overridden_and_shadowed_bindings() {
+        let default = KeymapFile::parse(
+            r#"[
+                { "bindings": { "cmd-shift-p": "command_palette::Toggle", "cmd-k": "workspace::Open" } },
+                { "context": "Editor", "bindings": { "cmd-d": "editor::SelectNext", "cmd-d cmd-d": "editor::SelectAll" } }
+            ]"#,
+        )
+        .unwrap();
+        let user = KeymapFile::parse(
+            r#"[
+                { "bindings": { "shift-cmd-p": "file_finder::Toggle", "cmd-k cmd-s": "workspace::Save" } },
+                { "context": "Editor", "bindings": { "cmd-d cmd-d": null } }
+            ]"#,
+        )
+        .unwrap();
+
+        let bindings = analyze_keymaps(&[("Default".into(), default), ("User".into(), user)]);
+        let status = |source: &str, keystrokes: &str| {
+            bindings
+                .iter()
+                .find(|binding| binding.source == source && binding.keystrokes == keystrokes)
+                .unwrap()
+                .status
+                .clone()
+        };
+        assert_eq!(
+            status("Default", "cmd-shift-p"),
+            KeymapBindingStatus::Overridden {
+                source: "User".into(),
+                action: "file_finder::Toggle".into(),
+            }
+        );
+        assert_eq!(
+            status("Default", "cmd-k"),
+            KeymapBindingStatus::Shadowed {
+                keystrokes: "cmd-k cmd-s".into()
+            }
+        );
+        assert_eq!(
+            status("Default", "cmd-d cmd-d"),
+            KeymapBindingStatus::Overridden {
+                source: "User".into(),
+                action: Value::Null,
+            }
+        );
+        // The longer binding in the editor was disabled, so it no longer shadows `cmd-d`.
+        assert_eq!(status("Default", "cmd-d"), KeymapBindingStatus::Active);
+        assert_eq!(status("User", "shift-cmd-p"), KeymapBindingStatus::Active);
+    }
+
+    #[test]
+    fn finds_binding_offsets() {
+        let content = r#"[
+            // "Editor": "cmd-d"
+            { "bindings": { "cmd-d": "editor::Cancel", "ctrl-x": "Editor" } },
+            { "context": "Editor", "bindings": { "cmd-d": "editor::SelectNext" } },
+            { "context": "Terminal", "bindings": { "ctrl-c": "terminal::Copy" } },
+            { "context": "Terminal", "bindings": { "ctrl-c": "terminal::Clear" } },
+            { "context": "Workspace", "bindings": { "shift-ctrl-k": "workspace::Save" } }
+        ]"#;
+        let global_offset = content.find("\"cmd-d\": \"editor::Cancel").unwrap();
+        let editor_offset = content.find("\"cmd-d\": \"editor::SelectNext").unwrap();
+        let terminal_offset = content.rfind("\"ctrl-c\"").unwrap();
+        assert_eq!(binding_offset(content, None, "cmd-d"), Some(global_offset));
+        assert_eq!(
+            binding_offset(content, Some("Editor"), "cmd-d"),
+            Some(editor_offset)
+        );
+        // The last binding takes precedence.
+        assert_eq!(
+            binding_offset(content, Some("Terminal"), "ctrl-c"),
+            Some(terminal_offset)
+        );
+        // Keystrokes are compared as parsed.
+        assert_eq!(
+            binding_offset(content, Some("Workspace"), "ctrl-shift-k"),
+            content.find("\"shift-ctrl-k\"")
+        );
+        assert_eq!(binding_offset(content, Some("Terminal"), "cmd-d"), None);
+        assert_eq!(binding_offset(content, Some("Workspace"), "cmd-d"), None);
+    }
+
+    #[test]
+    fn sets_keymap_bindings() {
+        let bindings = |keystrokes: &str, action: Value| vec![(keystrokes.to_string(), action)];
+        let content = r#"[
+  // Global bindings
+  { "bindings": { "cmd-d": "editor::Cancel" } },
+  {
+    "context": "Editor",
+    "bindings": {
+      "cmd-d": "editor::SelectNext"
+    }
+  }
+]
+"#;
+
+        // Keystrokes that are already bound in the context are rebound in place.
+        let updated = set_keymap_bindings(
+            content,
+            Some("Editor"),
+            &bindings("cmd-d", json!("editor::SelectAll")),
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            content.replace("\"editor::SelectNext\"", "\"editor::SelectAll\"")
+        );
+
+        // New keystrokes are added to the context's block.
+        let updated =
+            set_keymap_bindings(content, None, &bindings("cmd-e", json!(["x::Y", 1]))).unwrap();
+        assert_eq!(
+            updated,
+            content.replace(
+                r#""cmd-d": "editor::Cancel" }"#,
+                r#""cmd-d": "editor::Cancel", "cmd-e": ["x::Y",1] }"#
+            )
+        );
+
+        // Contexts without a block get a new one.
+        let updated =
+            set_keymap_bindings(content, Some("Terminal"), &bindings("cmd-d", Value::Null))
+                .unwrap();
+        assert_eq!(
+            updated,
+            content.replace(
+                "\n  }\n]",
+                r#"
+  },
+  {
+    "context": "Terminal",
+    "bindings": {
+      "cmd-d": null
+    }
+  }
+]"#
+            )
+        );
+        KeymapFile::parse(&updated).unwrap();
+
+        let updated = set_keymap_bindings("", None, &bindings("cmd-d", Value::Null)).unwrap();
+        assert_eq!(
+            updated,
+            "[\n  {\n    \"bindings\": {\n      \"cmd-d\": null\n    }\n  }\n]\n"
+        );
+    }
+
+    #[gpui::test]
+    fn reports_invalid_bindings(cx: &mut TestAppContext) {
+        let keymap = KeymapFile::parse(
+            r#"[
+                {
+                    "bindings": {
+                        "ctrl-a": "test_keymap::KnownAction",
+                        "ctrl-b": "test_keymap::UnknownAction",
+                        "ctrl-c": ["test_keymap::KnownAction"]
+                    }
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let error = cx
+            .update(|cx| keymap.add_to_cx(cx))
+            .unwrap_err()
+            .to_string();
+        assert!(!error.contains("ctrl-a"));
+        assert!(error.contains("ctrl-b"));
+        assert!(error.contains("ctrl-c"));
+    }
 }
@@ -1,3 +1,5 @@
+pub mod editorconfig;
+mod json_schema_info;
 mod keymap_file;
 mod settings_file;
 mod settings_store;
@@ -6,9 +8,15 @@ use rust_embed::RustEmbed;
 use std::{borrow::Cow, str};
 use util::asset_str;
 
-pub use keymap_file::KeymapFile;
+pub use json_schema_info::*;
+pub use keymap_file::{
+    analyze_keymaps, binding_offset, set_keymap_bindings, KeymapBindingInfo, KeymapBindingStatus,
+    KeymapConflict, KeymapFile,
+};
 pub use settings_file::*;
-pub use settings_store::{Settings, SettingsJsonSchemaParams, SettingsStore};
+pub use settings_store::{
+    parse_json_with_comments, Settings, SettingsJsonSchemaParams, SettingsStore,
+};
 
 #[derive(RustEmbed)]
 #[folder = "../../assets"]
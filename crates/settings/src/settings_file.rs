@@ -2,8 +2,13 @@ use crate::{settings_store::SettingsStore, Settings};
 use anyhow::{Context, Result};
 use fs::Fs;
 use futures::{channel::mpsc, StreamExt};
-use gpui::{AppContext, BackgroundExecutor};
-use std::{io::ErrorKind, path::PathBuf, sync::Arc, time::Duration};
+use gpui::{AppContext, BackgroundExecutor, Task};
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use util::{paths, ResultExt};
 
 pub const EMPTY_THEME_NAME: &'static str = "empty-theme";
@@ -91,6 +96,31 @@ pub fn handle_settings_file_changes(
     .detach();
 }
 
+/// Applies the machine-local settings file, which is layered over the user's
+/// settings and kept out of the config directory so it isn't synced.
+pub fn handle_machine_settings_file_changes(
+    mut machine_settings_file_rx: mpsc::UnboundedReceiver<String>,
+    cx: &mut AppContext,
+) {
+    cx.spawn(move |mut cx| async move {
+        while let Some(machine_settings_content) = machine_settings_file_rx.next().await {
+            let result = cx.update_global(|store: &mut SettingsStore, cx| {
+                let content = if machine_settings_content.trim().is_empty() {
+                    "{}"
+                } else {
+                    &machine_settings_content
+                };
+                store.set_machine_settings(content, cx).log_err();
+                cx.refresh();
+            });
+            if result.is_err() {
+                break; // App dropped
+            }
+        }
+    })
+    .detach();
+}
+
 async fn load_settings(fs: &Arc<dyn Fs>) -> Result<String> {
     match fs.load(&paths::SETTINGS).await {
         result @ Ok(_) => result,
@@ -115,22 +145,79 @@ pub fn update_settings_file<T: Settings>(
         let new_text = cx.read_global(|store: &SettingsStore, _cx| {
             store.new_text_for_update::<T>(old_text, update)
         })?;
-        let initial_path = paths::SETTINGS.as_path();
-        if !fs.is_file(initial_path).await {
-            fs.atomic_write(initial_path.to_path_buf(), new_text)
-                .await
-                .with_context(|| format!("Failed to write settings to file {:?}", initial_path))?;
-        } else {
-            let resolved_path = fs.canonicalize(initial_path).await.with_context(|| {
-                format!("Failed to canonicalize settings path {:?}", initial_path)
-            })?;
+        write_settings_file(&fs, new_text).await
+    })
+    .detach_and_log_err(cx);
+}
 
-            fs.atomic_write(resolved_path.clone(), new_text)
-                .await
-                .with_context(|| format!("Failed to write settings to file {:?}", resolved_path))?;
-        }
+/// Sets the value at the given key path in the user's settings file.
+pub fn update_settings_file_value(
+    fs: Arc<dyn Fs>,
+    cx: &mut AppContext,
+    key_path: Vec<String>,
+    value: serde_json::Value,
+) {
+    update_settings_file_values(fs, cx, vec![(key_path, value)]);
+}
 
-        anyhow::Ok(())
+/// Sets the values at the given key paths in the user's settings file, writing
+/// the file once.
+pub fn update_settings_file_values(
+    fs: Arc<dyn Fs>,
+    cx: &mut AppContext,
+    values: Vec<(Vec<String>, serde_json::Value)>,
+) {
+    cx.spawn(|cx| async move {
+        let old_text = load_settings(&fs).await?;
+        let new_text = cx.read_global(|store: &SettingsStore, _cx| {
+            values.iter().fold(old_text, |text, (key_path, value)| {
+                let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+                store.new_text_for_value_update(text, &key_path, value)
+            })
+        })?;
+        write_settings_file(&fs, new_text).await
     })
     .detach_and_log_err(cx);
 }
+
+async fn write_settings_file(fs: &Arc<dyn Fs>, new_text: String) -> Result<()> {
+    write_config_file(fs, paths::SETTINGS.as_path(), new_text).await
+}
+
+/// Rewrites the user's keymap file with the result of `update`, which is given its current
+/// content.
+pub fn update_keymap_file(
+    fs: Arc<dyn Fs>,
+    cx: &AppContext,
+    update: impl 'static + Send + FnOnce(String) -> Result<String>,
+) -> Task<Result<()>> {
+    cx.background_executor().spawn(async move {
+        let old_text = if fs.is_file(&paths::KEYMAP).await {
+            fs.load(&paths::KEYMAP).await?
+        } else {
+            String::new()
+        };
+        let new_text = update(old_text)?;
+        write_config_file(&fs, paths::KEYMAP.as_path(), new_text).await
+    })
+}
+
+/// Writes a config file, writing through it if it's a symlink so that the link is kept.
+async fn write_config_file(fs: &Arc<dyn Fs>, initial_path: &Path, new_text: String) -> Result<()> {
+    if !fs.is_file(initial_path).await {
+        fs.atomic_write(initial_path.to_path_buf(), new_text)
+            .await
+            .with_context(|| format!("Failed to write settings to file {:?}", initial_path))?;
+    } else {
+        let resolved_path = fs
+            .canonicalize(initial_path)
+            .await
+            .with_context(|| format!("Failed to canonicalize settings path {:?}", initial_path))?;
+
+        fs.atomic_write(resolved_path.clone(), new_text)
+            .await
+            .with_context(|| format!("Failed to write settings to file {:?}", resolved_path))?;
+    }
+
+    Ok(())
+}
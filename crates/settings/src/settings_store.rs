@@ -1,3 +1,4 @@
+use crate::editorconfig::{Editorconfig, EditorconfigProperties};
 use anyhow::{anyhow, Context, Result};
 use collections::{btree_map, hash_map, BTreeMap, HashMap};
 use gpui::{AppContext, AsyncAppContext, Global};
@@ -7,9 +8,10 @@ use serde::{de::DeserializeOwned, Deserialize as _, Serialize};
 use smallvec::SmallVec;
 use std::{
     any::{type_name, Any, TypeId},
+    cell::RefCell,
     fmt::Debug,
     ops::Range,
-    path::Path,
+    path::{Path, PathBuf},
     str,
     sync::Arc,
 };
@@ -131,7 +133,16 @@ pub struct SettingsStore {
     setting_values: HashMap<TypeId, Box<dyn AnySettingValue>>,
     raw_default_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
+    raw_machine_settings: serde_json::Value,
+    active_profile: Option<String>,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
+    /// The `.editorconfig` files in each worktree, by their directory. Files
+    /// in the worktree's ancestors are stored under `..` followed by the path
+    /// of the worktree relative to their directory.
+    editorconfigs: HashMap<usize, BTreeMap<Arc<Path>, (String, Editorconfig)>>,
+    /// The `.editorconfig` files that apply to the directories whose files
+    /// were looked up, outermost first.
+    editorconfig_chains: RefCell<HashMap<usize, HashMap<Arc<Path>, Arc<[EditorconfigInChain]>>>>,
     tab_size_callback: Option<(
         TypeId,
         Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync + 'static>,
@@ -140,13 +151,26 @@ pub struct SettingsStore {
 
 impl Global for SettingsStore {}
 
+/// An `.editorconfig` file that applies to a directory.
+#[derive(Debug)]
+struct EditorconfigInChain {
+    /// The key of the file in [`SettingsStore::editorconfigs`].
+    directory: Arc<Path>,
+    /// The path of the directory relative to the one containing the file.
+    relative_directory: PathBuf,
+}
+
 impl Default for SettingsStore {
     fn default() -> Self {
         SettingsStore {
             setting_values: Default::default(),
             raw_default_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
+            raw_machine_settings: serde_json::json!({}),
+            active_profile: None,
             raw_local_settings: Default::default(),
+            editorconfigs: Default::default(),
+            editorconfig_chains: Default::default(),
             tab_size_callback: Default::default(),
         }
     }
@@ -201,22 +225,15 @@ impl SettingsStore {
         {
             let mut user_values_stack = Vec::new();
 
-            if let Some(user_settings) = setting_value
-                .deserialize_setting(&self.raw_user_settings)
-                .log_err()
-            {
-                user_values_stack = vec![user_settings];
-            }
-
-            if let Some(release_settings) = &self
-                .raw_user_settings
-                .get(&*release_channel::RELEASE_CHANNEL.dev_name())
-            {
-                if let Some(release_settings) = setting_value
-                    .deserialize_setting(&release_settings)
-                    .log_err()
+            for user_settings in user_settings_layers(
+                &self.raw_user_settings,
+                self.active_profile.as_deref(),
+                &self.raw_machine_settings,
+            ) {
+                if let Some(user_settings) =
+                    setting_value.deserialize_setting(user_settings).log_err()
                 {
-                    user_values_stack.push(release_settings);
+                    user_values_stack.push(user_settings);
                 }
             }
 
@@ -261,6 +278,11 @@ impl SettingsStore {
         &self.raw_user_settings
     }
 
+    /// Get the default settings as a raw JSON value.
+    pub fn raw_default_settings(&self) -> &serde_json::Value {
+        &self.raw_default_settings
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn test(cx: &mut AppContext) -> Self {
         let mut this = Self::default();
@@ -300,6 +322,20 @@ impl SettingsStore {
         new_text
     }
 
+    /// Sets the value at the given key path in a JSON file, returning the new
+    /// text for that JSON file. The rest of the file is left untouched.
+    pub fn new_text_for_value_update(
+        &self,
+        mut text: String,
+        key_path: &[&str],
+        new_value: &serde_json::Value,
+    ) -> String {
+        let (range, replacement) =
+            replace_value_in_json_text(&text, key_path, self.json_tab_size(), new_value);
+        text.replace_range(range, &replacement);
+        text
+    }
+
     /// Updates the value of a setting in a JSON file, returning a list
     /// of edits to apply to the JSON file.
     pub fn edits_for_update<T: Settings>(
@@ -402,6 +438,47 @@ impl SettingsStore {
         }
     }
 
+    /// Sets the machine-local settings via a JSON string. These settings are
+    /// applied on top of the user's settings and active profile.
+    pub fn set_machine_settings(
+        &mut self,
+        machine_settings_content: &str,
+        cx: &mut AppContext,
+    ) -> Result<()> {
+        let settings: serde_json::Value = parse_json_with_comments(machine_settings_content)?;
+        if settings.is_object() {
+            self.raw_machine_settings = settings;
+            self.recompute_values(None, cx)?;
+            Ok(())
+        } else {
+            Err(anyhow!("settings must be an object"))
+        }
+    }
+
+    /// The names of the profiles defined in the user's settings.
+    pub fn profile_names(&self) -> Vec<String> {
+        self.raw_user_settings
+            .get("profiles")
+            .and_then(serde_json::Value::as_object)
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    /// Layers the named profile from the user's settings on top of them, or
+    /// goes back to the base settings when `None` is given.
+    pub fn set_active_profile(
+        &mut self,
+        profile: Option<String>,
+        cx: &mut AppContext,
+    ) -> Result<()> {
+        self.active_profile = profile;
+        self.recompute_values(None, cx)
+    }
+
     /// Add or remove a set of local settings via a JSON string.
     pub fn set_local_settings(
         &mut self,
@@ -423,10 +500,130 @@ impl SettingsStore {
     /// Add or remove a set of local settings via a JSON string.
     pub fn clear_local_settings(&mut self, root_id: usize, cx: &mut AppContext) -> Result<()> {
         self.raw_local_settings.retain(|k, _| k.0 != root_id);
+        self.editorconfigs.remove(&root_id);
+        self.editorconfig_chains.get_mut().remove(&root_id);
         self.recompute_values(Some((root_id, "".as_ref())), cx)?;
         Ok(())
     }
 
+    /// Add or remove the `.editorconfig` file in the given directory.
+    ///
+    /// Files in the worktree's ancestor directories are added under `..`
+    /// followed by the path of the worktree's root relative to their directory.
+    pub fn set_editorconfig(
+        &mut self,
+        root_id: usize,
+        directory: Arc<Path>,
+        content: Option<&str>,
+    ) {
+        let editorconfigs = self.editorconfigs.entry(root_id).or_default();
+        if let Some(content) = content {
+            editorconfigs.insert(
+                directory,
+                (content.to_string(), Editorconfig::parse(content)),
+            );
+        } else {
+            editorconfigs.remove(&directory);
+        }
+        self.editorconfig_chains.get_mut().remove(&root_id);
+    }
+
+    /// The contents of the `.editorconfig` files for the given worktree, by
+    /// their path as passed to [`SettingsStore::set_editorconfig`].
+    pub fn editorconfigs(&self, root_id: usize) -> impl '_ + Iterator<Item = (Arc<Path>, &str)> {
+        self.editorconfigs
+            .get(&root_id)
+            .into_iter()
+            .flatten()
+            .map(|(directory, (content, _))| (directory.clone(), content.as_str()))
+    }
+
+    /// Returns the properties from the `.editorconfig` files that apply to the
+    /// file at the given path.
+    pub fn editorconfig_properties(
+        &self,
+        root_id: usize,
+        path: &Path,
+    ) -> Option<EditorconfigProperties> {
+        let editorconfigs = self.editorconfigs.get(&root_id)?;
+        let file_name = path.file_name()?;
+        let directory = path.parent()?;
+        let chain = self.editorconfig_chain(root_id, directory);
+        if chain.is_empty() {
+            return None;
+        }
+
+        let mut properties = EditorconfigProperties::default();
+        for entry in chain.iter() {
+            if let Some((_, editorconfig)) = editorconfigs.get(&entry.directory) {
+                editorconfig.apply(&entry.relative_directory.join(file_name), &mut properties);
+            }
+        }
+        Some(properties)
+    }
+
+    fn editorconfig_chain(&self, root_id: usize, directory: &Path) -> Arc<[EditorconfigInChain]> {
+        if let Some(chain) = self
+            .editorconfig_chains
+            .borrow()
+            .get(&root_id)
+            .and_then(|chains| chains.get(directory))
+        {
+            return chain.clone();
+        }
+
+        let mut chain = Vec::new();
+        let mut reached_root = false;
+        if let Some(editorconfigs) = self.editorconfigs.get(&root_id) {
+            for ancestor in directory.ancestors() {
+                if let Some((ancestor, (_, editorconfig))) = editorconfigs.get_key_value(ancestor) {
+                    chain.push(EditorconfigInChain {
+                        directory: ancestor.clone(),
+                        relative_directory: directory
+                            .strip_prefix(ancestor)
+                            .unwrap_or(directory)
+                            .to_path_buf(),
+                    });
+                    if editorconfig.is_root() {
+                        reached_root = true;
+                        break;
+                    }
+                }
+            }
+
+            if !reached_root {
+                let mut outside_worktree = editorconfigs
+                    .iter()
+                    .filter_map(|(path, (_, editorconfig))| {
+                        let worktree_path = path.strip_prefix("..").ok()?;
+                        Some((path, worktree_path, editorconfig))
+                    })
+                    .collect::<Vec<_>>();
+                outside_worktree
+                    .sort_by_key(|(_, worktree_path, _)| worktree_path.components().count());
+                for (path, worktree_path, editorconfig) in outside_worktree {
+                    chain.push(EditorconfigInChain {
+                        directory: path.clone(),
+                        relative_directory: worktree_path.join(directory),
+                    });
+                    if editorconfig.is_root() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Files closer to the directory take precedence, so they're applied last.
+        chain.reverse();
+        let chain = Arc::<[EditorconfigInChain]>::from(chain);
+        self.editorconfig_chains
+            .borrow_mut()
+            .entry(root_id)
+            .or_default()
+            .insert(Arc::from(directory), chain.clone());
+        chain
+    }
+
     pub fn local_settings(&self, root_id: usize) -> impl '_ + Iterator<Item = (Arc<Path>, String)> {
         self.raw_local_settings
             .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
@@ -516,6 +713,15 @@ impl SettingsStore {
                 .insert(release_stage.to_string(), schema.into());
         }
 
+        let mut profiles_schema = SchemaObject::default();
+        profiles_schema.object().additional_properties =
+            Some(Box::new(combined_schema.schema.clone().into()));
+        combined_schema
+            .schema
+            .object()
+            .properties
+            .insert("profiles".to_string(), profiles_schema.into());
+
         serde_json::to_value(&combined_schema).unwrap()
     }
 
@@ -527,29 +733,22 @@ impl SettingsStore {
         // Reload the global and local values for every setting.
         let mut user_settings_stack = Vec::<DeserializedSetting>::new();
         let mut paths_stack = Vec::<Option<(usize, &Path)>>::new();
+        let user_settings_layers = user_settings_layers(
+            &self.raw_user_settings,
+            self.active_profile.as_deref(),
+            &self.raw_machine_settings,
+        );
         for setting_value in self.setting_values.values_mut() {
             let default_settings = setting_value.deserialize_setting(&self.raw_default_settings)?;
 
             user_settings_stack.clear();
             paths_stack.clear();
 
-            if let Some(user_settings) = setting_value
-                .deserialize_setting(&self.raw_user_settings)
-                .log_err()
-            {
-                user_settings_stack.push(user_settings);
-                paths_stack.push(None);
-            }
-
-            if let Some(release_settings) = &self
-                .raw_user_settings
-                .get(&*release_channel::RELEASE_CHANNEL.dev_name())
-            {
-                if let Some(release_settings) = setting_value
-                    .deserialize_setting(&release_settings)
-                    .log_err()
+            for user_settings in &user_settings_layers {
+                if let Some(user_settings) =
+                    setting_value.deserialize_setting(user_settings).log_err()
                 {
-                    user_settings_stack.push(release_settings);
+                    user_settings_stack.push(user_settings);
                     paths_stack.push(None);
                 }
             }
@@ -605,6 +804,31 @@ impl SettingsStore {
     }
 }
 
+/// The user-level settings, in the order that they override each other: the
+/// settings file, its section for the current release channel, the active
+/// profile, and finally the machine-local settings.
+fn user_settings_layers<'a>(
+    raw_user_settings: &'a serde_json::Value,
+    active_profile: Option<&str>,
+    raw_machine_settings: &'a serde_json::Value,
+) -> Vec<&'a serde_json::Value> {
+    let mut layers = vec![raw_user_settings];
+    if let Some(release_settings) =
+        raw_user_settings.get(&*release_channel::RELEASE_CHANNEL.dev_name())
+    {
+        layers.push(release_settings);
+    }
+    if let Some(profile_settings) = active_profile.and_then(|profile| {
+        raw_user_settings
+            .get("profiles")
+            .and_then(|profiles| profiles.get(profile))
+    }) {
+        layers.push(profile_settings);
+    }
+    layers.push(raw_machine_settings);
+    layers
+}
+
 impl Debug for SettingsStore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SettingsStore")
@@ -618,6 +842,8 @@ impl Debug for SettingsStore {
             )
             .field("default_settings", &self.raw_default_settings)
             .field("user_settings", &self.raw_user_settings)
+            .field("machine_settings", &self.raw_machine_settings)
+            .field("active_profile", &self.active_profile)
             .field("local_settings", &self.raw_local_settings)
             .finish_non_exhaustive()
     }
@@ -1042,6 +1268,78 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_settings_profiles_and_machine_settings(cx: &mut AppContext) {
+        let mut store = SettingsStore::default();
+        store.register_setting::<UserSettings>(cx);
+        store.register_setting::<TurboSetting>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "turbo": false,
+                    "user": {
+                        "name": "John Doe",
+                        "age": 30,
+                        "staff": false
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(
+                r#"{
+                    "user": { "age": 31 },
+                    "profiles": {
+                        "presentation": { "user": { "age": 50, "staff": true } },
+                        "pairing": { "turbo": true }
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+
+        assert_eq!(store.profile_names(), ["pairing", "presentation"]);
+        assert_eq!(store.get::<UserSettings>(None).age, 31);
+
+        store
+            .set_active_profile(Some("presentation".to_string()), cx)
+            .unwrap();
+        assert_eq!(store.active_profile(), Some("presentation"));
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "John Doe".to_string(),
+                age: 50,
+                staff: true,
+            }
+        );
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(false));
+
+        // Machine-local settings override both the user settings and the profile.
+        store
+            .set_machine_settings(r#"{ "user": { "name": "Jane Doe", "age": 40 } }"#, cx)
+            .unwrap();
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "Jane Doe".to_string(),
+                age: 40,
+                staff: true,
+            }
+        );
+
+        store.set_active_profile(None, cx).unwrap();
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "Jane Doe".to_string(),
+                age: 40,
+                staff: false,
+            }
+        );
+    }
+
     #[gpui::test]
     fn test_setting_store_assign_json_before_register(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
@@ -1205,6 +1503,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_setting_store_value_update() {
+        let store = SettingsStore::default();
+
+        // existing values are replaced, keeping comments
+        let new_text = store.new_text_for_value_update(
+            r#"{
+                // The user's name.
+                "user": {
+                    "name": "John Doe"
+                }
+            }"#
+            .unindent(),
+            &["user", "name"],
+            &serde_json::json!("Jane Doe"),
+        );
+        pretty_assertions::assert_eq!(
+            new_text,
+            r#"{
+                // The user's name.
+                "user": {
+                    "name": "Jane Doe"
+                }
+            }"#
+            .unindent()
+        );
+
+        // missing keys are added
+        let new_text = store.new_text_for_value_update(
+            r#"{ "one": 1 }"#.unindent(),
+            &["two"],
+            &serde_json::json!(2),
+        );
+        pretty_assertions::assert_eq!(new_text, r#"{ "two": 2, "one": 1 }"#.unindent());
+    }
+
     fn check_settings_update<T: Settings>(
         store: &mut SettingsStore,
         old_json: String,
@@ -0,0 +1,632 @@
+use anyhow::Result;
+use gpui::{actions, AppContext, PromptLevel, ViewContext};
+use serde_json::{json, Value};
+use settings::parse_json_with_comments;
+use std::path::PathBuf;
+use util::paths;
+use workspace::Workspace;
+
+actions!(
+    settings_ui,
+    [ImportVsCodeSettings, ImportSublimeTextSettings]
+);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace
+            .register_action(|workspace, _: &ImportVsCodeSettings, cx| {
+                import(workspace, ImportSource::VsCode, cx)
+            })
+            .register_action(|workspace, _: &ImportSublimeTextSettings, cx| {
+                import(workspace, ImportSource::SublimeText, cx)
+            });
+    })
+    .detach();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportSource {
+    VsCode,
+    SublimeText,
+}
+
+impl ImportSource {
+    fn name(&self) -> &'static str {
+        match self {
+            ImportSource::VsCode => "VS Code",
+            ImportSource::SublimeText => "Sublime Text",
+        }
+    }
+
+    fn user_dir(&self) -> PathBuf {
+        match self {
+            ImportSource::VsCode if cfg!(target_os = "macos") => {
+                paths::HOME.join("Library/Application Support/Code/User")
+            }
+            ImportSource::VsCode => paths::HOME.join(".config/Code/User"),
+            ImportSource::SublimeText if cfg!(target_os = "macos") => {
+                paths::HOME.join("Library/Application Support/Sublime Text/Packages/User")
+            }
+            ImportSource::SublimeText => paths::HOME.join(".config/sublime-text/Packages/User"),
+        }
+    }
+
+    fn settings_path(&self) -> PathBuf {
+        match self {
+            ImportSource::VsCode => self.user_dir().join("settings.json"),
+            ImportSource::SublimeText => self.user_dir().join("Preferences.sublime-settings"),
+        }
+    }
+
+    fn keymap_path(&self) -> PathBuf {
+        match self {
+            ImportSource::VsCode => self.user_dir().join("keybindings.json"),
+            ImportSource::SublimeText if cfg!(target_os = "macos") => {
+                self.user_dir().join("Default (OSX).sublime-keymap")
+            }
+            ImportSource::SublimeText => self.user_dir().join("Default (Linux).sublime-keymap"),
+        }
+    }
+}
+
+/// The settings and key bindings translated from another editor, along with
+/// the entries that have no Zed equivalent.
+#[derive(Debug, Default, PartialEq)]
+struct ImportedConfig {
+    settings: Vec<(Vec<String>, Value)>,
+    bindings: Vec<ImportedBinding>,
+    unmapped: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ImportedBinding {
+    context: Option<&'static str>,
+    keystrokes: String,
+    action: &'static str,
+}
+
+fn import(workspace: &mut Workspace, source: ImportSource, cx: &mut ViewContext<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    cx.spawn(|workspace, mut cx| async move {
+        let mut config = ImportedConfig::default();
+        if let Ok(content) = fs.load(&source.settings_path()).await {
+            config.import_settings(source, &parse_json_with_comments(&content)?);
+        }
+        if let Ok(content) = fs.load(&source.keymap_path()).await {
+            config.import_keymap(source, &parse_json_with_comments(&content)?);
+        }
+
+        if !config.settings.is_empty() {
+            let settings = config.settings.clone();
+            cx.update(|cx| settings::update_settings_file_values(fs.clone(), cx, settings))?;
+        }
+        if !config.bindings.is_empty() {
+            let bindings = config.bindings.clone();
+            cx.update(|cx| {
+                settings::update_keymap_file(fs.clone(), cx, move |keymap| {
+                    keymap_with_bindings(&keymap, &bindings)
+                })
+            })?
+            .await?;
+        }
+
+        let message = format!(
+            "Imported {} settings and {} key bindings from {}",
+            config.settings.len(),
+            config.bindings.len(),
+            source.name()
+        );
+        let detail = if config.unmapped.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "These entries have no equivalent in Zed:\n{}",
+                config.unmapped.join("\n")
+            ))
+        };
+        let answer = workspace.update(&mut cx, |_, cx| {
+            cx.prompt(PromptLevel::Info, &message, detail.as_deref(), &["Ok"])
+        })?;
+        answer.await.ok();
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+impl ImportedConfig {
+    fn import_settings(&mut self, source: ImportSource, settings: &Value) {
+        let Some(settings) = settings.as_object() else {
+            return;
+        };
+        for (key, value) in settings {
+            // The delay is imported along with `files.autoSave`.
+            if key == "files.autoSaveDelay" && settings.contains_key("files.autoSave") {
+                continue;
+            }
+            let imported = match source {
+                ImportSource::VsCode => vscode_setting(key, value, settings),
+                ImportSource::SublimeText => sublime_setting(key, value),
+            };
+            match imported {
+                Some((key_path, value)) => self.settings.push((
+                    key_path.split('.').map(ToString::to_string).collect(),
+                    value,
+                )),
+                None => self.unmapped.push(format!("setting `{key}`")),
+            }
+        }
+    }
+
+    fn import_keymap(&mut self, source: ImportSource, keymap: &Value) {
+        let Some(entries) = keymap.as_array() else {
+            return;
+        };
+        for entry in entries {
+            let imported = match source {
+                ImportSource::VsCode => vscode_binding(entry),
+                ImportSource::SublimeText => sublime_binding(entry),
+            };
+            match imported {
+                Some(binding) => self.bindings.push(binding),
+                None => self.unmapped.push(format!("key binding {entry}")),
+            }
+        }
+    }
+}
+
+fn vscode_setting(
+    key: &str,
+    value: &Value,
+    settings: &serde_json::Map<String, Value>,
+) -> Option<(&'static str, Value)> {
+    let imported = match key {
+        "editor.fontSize" => ("buffer_font_size", value.as_f64()?.into()),
+        "editor.fontFamily" => ("buffer_font_family", first_font_family(value)?),
+        "editor.tabSize" => ("tab_size", value.as_u64()?.into()),
+        "editor.insertSpaces" => ("hard_tabs", (!value.as_bool()?).into()),
+        "editor.wordWrap" => (
+            "soft_wrap",
+            match value.as_str()? {
+                "off" => "none",
+                "on" => "editor_width",
+                "wordWrapColumn" | "bounded" => "preferred_line_length",
+                _ => return None,
+            }
+            .into(),
+        ),
+        "editor.wordWrapColumn" => ("preferred_line_length", value.as_u64()?.into()),
+        "editor.rulers" => ("wrap_guides", rulers(value)?),
+        "editor.formatOnSave" => ("format_on_save", on_off(value.as_bool()?)),
+        "editor.renderWhitespace" => (
+            "show_whitespaces",
+            match value.as_str()? {
+                "none" => "none",
+                "selection" => "selection",
+                "all" | "boundary" | "trailing" => "all",
+                _ => return None,
+            }
+            .into(),
+        ),
+        "editor.cursorBlinking" => ("cursor_blink", (value.as_str()? != "solid").into()),
+        "editor.autoClosingBrackets" => ("use_autoclose", (value.as_str()? != "never").into()),
+        "editor.lineNumbers" => ("gutter.line_numbers", (value.as_str()? != "off").into()),
+        "editor.hover.enabled" => ("hover_popover_enabled", value.as_bool()?.into()),
+        "editor.inlayHints.enabled" => (
+            "inlay_hints.enabled",
+            match value {
+                Value::Bool(enabled) => *enabled,
+                Value::String(enabled) => enabled != "off",
+                _ => return None,
+            }
+            .into(),
+        ),
+        "files.trimTrailingWhitespace" => (
+            "remove_trailing_whitespace_on_save",
+            value.as_bool()?.into(),
+        ),
+        "files.insertFinalNewline" => ("ensure_final_newline_on_save", value.as_bool()?.into()),
+        "files.autoSave" => (
+            "autosave",
+            match value.as_str()? {
+                "off" => json!("off"),
+                "onFocusChange" => json!("on_focus_change"),
+                "onWindowChange" => json!("on_window_change"),
+                "afterDelay" => {
+                    let milliseconds = settings
+                        .get("files.autoSaveDelay")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(1000);
+                    json!({ "after_delay": { "milliseconds": milliseconds } })
+                }
+                _ => return None,
+            },
+        ),
+        "terminal.integrated.fontSize" => ("terminal.font_size", value.as_f64()?.into()),
+        "terminal.integrated.fontFamily" => ("terminal.font_family", first_font_family(value)?),
+        _ => return None,
+    };
+    Some(imported)
+}
+
+fn sublime_setting(key: &str, value: &Value) -> Option<(&'static str, Value)> {
+    let imported = match key {
+        "font_size" => ("buffer_font_size", value.as_f64()?.into()),
+        "font_face" => ("buffer_font_family", first_font_family(value)?),
+        "tab_size" => ("tab_size", value.as_u64()?.into()),
+        "translate_tabs_to_spaces" => ("hard_tabs", (!value.as_bool()?).into()),
+        "word_wrap" => (
+            "soft_wrap",
+            match value {
+                Value::Bool(true) => "editor_width",
+                Value::Bool(false) => "none",
+                _ => return None,
+            }
+            .into(),
+        ),
+        "wrap_width" => ("preferred_line_length", value.as_u64()?.into()),
+        "rulers" => ("wrap_guides", rulers(value)?),
+        "trim_trailing_white_space_on_save" => (
+            "remove_trailing_whitespace_on_save",
+            match value {
+                Value::Bool(trim) => *trim,
+                Value::String(trim) => trim != "none",
+                _ => return None,
+            }
+            .into(),
+        ),
+        "ensure_newline_at_eof_on_save" => {
+            ("ensure_final_newline_on_save", value.as_bool()?.into())
+        }
+        "auto_match_enabled" => ("use_autoclose", value.as_bool()?.into()),
+        "line_numbers" => ("gutter.line_numbers", value.as_bool()?.into()),
+        "draw_white_space" => (
+            "show_whitespaces",
+            match value.as_str()? {
+                "none" => "none",
+                "selection" => "selection",
+                "all" => "all",
+                _ => return None,
+            }
+            .into(),
+        ),
+        "caret_style" => ("cursor_blink", (value.as_str()? != "solid").into()),
+        "save_on_focus_lost" => ("autosave", on_focus_change(value.as_bool()?)),
+        _ => return None,
+    };
+    Some(imported)
+}
+
+fn vscode_binding(entry: &Value) -> Option<ImportedBinding> {
+    let keystrokes = convert_keystrokes(entry.get("key")?.as_str()?.split_whitespace())?;
+    let context = match entry.get("when") {
+        Some(when) => Some(vscode_context(when.as_str()?)?),
+        None => None,
+    };
+    let action = match entry.get("command")?.as_str()? {
+        "workbench.action.quickOpen" => "file_finder::Toggle",
+        "workbench.action.showCommands" => "command_palette::Toggle",
+        "workbench.action.findInFiles" => "pane::DeploySearch",
+        "workbench.action.gotoSymbol" => "outline::Toggle",
+        "workbench.action.showAllSymbols" => "project_symbols::Toggle",
+        "workbench.action.gotoLine" => "go_to_line::Toggle",
+        "workbench.action.files.save" => "workspace::Save",
+        "workbench.action.closeActiveEditor" => "pane::CloseActiveItem",
+        "workbench.action.nextEditor" => "pane::ActivateNextItem",
+        "workbench.action.previousEditor" => "pane::ActivatePrevItem",
+        "workbench.action.splitEditor" => "pane::SplitRight",
+        "workbench.action.toggleSidebarVisibility" => "workspace::ToggleLeftDock",
+        "workbench.action.terminal.toggleTerminal" => "terminal_panel::ToggleFocus",
+        "editor.action.commentLine" => "editor::ToggleComments",
+        "editor.action.formatDocument" => "editor::Format",
+        "editor.action.rename" => "editor::Rename",
+        "editor.action.revealDefinition" => "editor::GoToDefinition",
+        "editor.action.goToReferences" => "editor::FindAllReferences",
+        "editor.action.copyLinesDownAction" => "editor::DuplicateLine",
+        "editor.action.moveLinesUpAction" => "editor::MoveLineUp",
+        "editor.action.moveLinesDownAction" => "editor::MoveLineDown",
+        "editor.action.deleteLines" => "editor::DeleteLine",
+        "editor.action.addSelectionToNextFindMatch" => "editor::SelectNext",
+        "editor.action.selectHighlights" => "editor::SelectAllMatches",
+        "editor.action.insertCursorAbove" => "editor::AddSelectionAbove",
+        "editor.action.insertCursorBelow" => "editor::AddSelectionBelow",
+        "editor.action.quickFix" => "editor::ToggleCodeActions",
+        "editor.action.triggerSuggest" => "editor::ShowCompletions",
+        _ => return None,
+    };
+    Some(ImportedBinding {
+        context,
+        keystrokes,
+        action,
+    })
+}
+
+/// Translates the `when` clauses that correspond to a Zed key context. Bindings with any
+/// other clause aren't imported, since they would otherwise apply everywhere.
+fn vscode_context(when: &str) -> Option<&'static str> {
+    let context = match when.trim() {
+        "editorFocus" | "editorTextFocus" | "editorTextFocus && !editorReadonly" => "Editor",
+        "terminalFocus" => "Terminal",
+        "filesExplorerFocus" | "explorerViewletFocus" => "ProjectPanel",
+        _ => return None,
+    };
+    Some(context)
+}
+
+fn sublime_binding(entry: &Value) -> Option<ImportedBinding> {
+    // Sublime Text contexts test the selection and surrounding text, which key
+    // contexts can't express.
+    if entry.get("context").is_some() {
+        return None;
+    }
+    let keys = entry.get("keys")?.as_array()?;
+    let keystrokes = convert_keystrokes(keys.iter().filter_map(Value::as_str))?;
+    let args = entry.get("args");
+    let arg = |name: &str| args.and_then(|args| args.get(name)).and_then(Value::as_str);
+    let action = match entry.get("command")?.as_str()? {
+        "show_overlay" => match (arg("overlay")?, arg("text")) {
+            ("command_palette", _) => "command_palette::Toggle",
+            ("goto", Some("@")) => "outline::Toggle",
+            ("goto", Some(":")) => "go_to_line::Toggle",
+            ("goto", _) => "file_finder::Toggle",
+            _ => return None,
+        },
+        "show_panel" if arg("panel")? == "find_in_files" => "pane::DeploySearch",
+        "goto_symbol_in_project" => "project_symbols::Toggle",
+        "toggle_comment" => "editor::ToggleComments",
+        "duplicate_line" => "editor::DuplicateLine",
+        "swap_line_up" => "editor::MoveLineUp",
+        "swap_line_down" => "editor::MoveLineDown",
+        "find_under_expand" => "editor::SelectNext",
+        "find_all_under" => "editor::SelectAllMatches",
+        "split_selection_into_lines" => "editor::SplitSelectionIntoLines",
+        "goto_definition" => "editor::GoToDefinition",
+        "save" => "workspace::Save",
+        "close" => "pane::CloseActiveItem",
+        "next_view" => "pane::ActivateNextItem",
+        "prev_view" => "pane::ActivatePrevItem",
+        "toggle_side_bar" => "workspace::ToggleLeftDock",
+        "select_lines" => {
+            let forward = args?.get("forward")?.as_bool()?;
+            if forward {
+                "editor::AddSelectionBelow"
+            } else {
+                "editor::AddSelectionAbove"
+            }
+        }
+        "expand_selection" if arg("to")? == "line" => "editor::SelectLine",
+        "run_macro_file" if arg("file")?.ends_with("Delete Line.sublime-macro") => {
+            "editor::DeleteLine"
+        }
+        _ => return None,
+    };
+    Some(ImportedBinding {
+        context: None,
+        keystrokes,
+        action,
+    })
+}
+
+/// Converts chords like `ctrl+shift+p` into Zed's `ctrl-shift-p` form.
+fn convert_keystrokes<'a>(chords: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut keystrokes = Vec::new();
+    for chord in chords {
+        let mut components = Vec::new();
+        for component in chord.split('+') {
+            components.push(match component.to_lowercase().as_str() {
+                "ctrl" | "control" => "ctrl".to_string(),
+                "alt" | "option" => "alt".to_string(),
+                "shift" => "shift".to_string(),
+                "cmd" | "super" | "meta" | "win" => "cmd".to_string(),
+                "" => return None,
+                key => key.to_string(),
+            });
+        }
+        keystrokes.push(components.join("-"));
+    }
+    if keystrokes.is_empty() {
+        None
+    } else {
+        Some(keystrokes.join(" "))
+    }
+}
+
+/// Adds the imported bindings to the given keymap.json content. Keystrokes that are
+/// already bound in the same context are rebound, so importing again doesn't add
+/// duplicate bindings.
+fn keymap_with_bindings(keymap: &str, bindings: &[ImportedBinding]) -> Result<String> {
+    let mut contexts = Vec::new();
+    for binding in bindings {
+        if !contexts.contains(&binding.context) {
+            contexts.push(binding.context);
+        }
+    }
+
+    let mut keymap = keymap.to_string();
+    for context in contexts {
+        let context_bindings = bindings
+            .iter()
+            .filter(|binding| binding.context == context)
+            .map(|binding| (binding.keystrokes.clone(), json!(binding.action)))
+            .collect::<Vec<_>>();
+        keymap = settings::set_keymap_bindings(&keymap, context, &context_bindings)?;
+    }
+    Ok(keymap)
+}
+
+fn first_font_family(value: &Value) -> Option<Value> {
+    let family = value.as_str()?.split(',').next()?;
+    let family = family.trim().trim_matches(|c| c == '\'' || c == '"');
+    (!family.is_empty()).then(|| family.into())
+}
+
+fn rulers(value: &Value) -> Option<Value> {
+    let columns = value
+        .as_array()?
+        .iter()
+        .map(|ruler| ruler.as_u64().or_else(|| ruler.get("column")?.as_u64()))
+        .collect::<Option<Vec<_>>>()?;
+    Some(columns.into())
+}
+
+fn on_off(enabled: bool) -> Value {
+    if enabled { "on" } else { "off" }.into()
+}
+
+fn on_focus_change(enabled: bool) -> Value {
+    if enabled { "on_focus_change" } else { "off" }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_vscode_config() {
+        let mut config = ImportedConfig::default();
+        config.import_settings(
+            ImportSource::VsCode,
+            &json!({
+                "editor.fontSize": 13,
+                "editor.fontFamily": "'Fira Code', Menlo, monospace",
+                "editor.insertSpaces": false,
+                "files.autoSave": "afterDelay",
+                "files.autoSaveDelay": 500,
+                "workbench.colorTheme": "Monokai"
+            }),
+        );
+        config.import_keymap(
+            ImportSource::VsCode,
+            &json!([
+                { "key": "cmd+k cmd+c", "command": "editor.action.commentLine" },
+                { "key": "ctrl+d", "command": "editor.action.addSelectionToNextFindMatch", "when": "editorTextFocus" },
+                { "key": "ctrl+e", "command": "editor.action.rename", "when": "someExtension.active" },
+                { "key": "ctrl+alt+t", "command": "some.extension.command" }
+            ]),
+        );
+
+        let key_path = |key_path: &str| key_path.split('.').map(ToString::to_string).collect();
+        assert_eq!(
+            config,
+            ImportedConfig {
+                settings: vec![
+                    (key_path("buffer_font_size"), json!(13.0)),
+                    (key_path("buffer_font_family"), json!("Fira Code")),
+                    (key_path("hard_tabs"), json!(true)),
+                    (
+                        key_path("autosave"),
+                        json!({ "after_delay": { "milliseconds": 500 } })
+                    ),
+                ],
+                bindings: vec![
+                    ImportedBinding {
+                        context: None,
+                        keystrokes: "cmd-k cmd-c".to_string(),
+                        action: "editor::ToggleComments",
+                    },
+                    ImportedBinding {
+                        context: Some("Editor"),
+                        keystrokes: "ctrl-d".to_string(),
+                        action: "editor::SelectNext",
+                    },
+                ],
+                unmapped: vec![
+                    "setting `workbench.colorTheme`".to_string(),
+                    format!(
+                        "key binding {}",
+                        json!({ "key": "ctrl+e", "command": "editor.action.rename", "when": "someExtension.active" })
+                    ),
+                    format!(
+                        "key binding {}",
+                        json!({ "key": "ctrl+alt+t", "command": "some.extension.command" })
+                    ),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_import_sublime_keymap() {
+        let mut config = ImportedConfig::default();
+        config.import_keymap(
+            ImportSource::SublimeText,
+            &json!([
+                {
+                    "keys": ["super+shift+p"],
+                    "command": "show_overlay",
+                    "args": { "overlay": "command_palette" }
+                },
+                { "keys": ["ctrl+shift+up"], "command": "select_lines", "args": { "forward": false } },
+                {
+                    "keys": ["super+d"],
+                    "command": "find_under_expand",
+                    "context": [{ "key": "selection_empty", "operand": false }]
+                }
+            ]),
+        );
+        assert_eq!(
+            config
+                .bindings
+                .iter()
+                .map(|binding| (binding.keystrokes.as_str(), binding.action))
+                .collect::<Vec<_>>(),
+            [
+                ("cmd-shift-p", "command_palette::Toggle"),
+                ("ctrl-shift-up", "editor::AddSelectionAbove"),
+            ]
+        );
+        assert_eq!(config.unmapped.len(), 1);
+    }
+
+    #[test]
+    fn test_keymap_with_bindings() {
+        let bindings = [
+            ImportedBinding {
+                context: None,
+                keystrokes: "cmd-k cmd-c".to_string(),
+                action: "editor::ToggleComments",
+            },
+            ImportedBinding {
+                context: Some("Editor"),
+                keystrokes: "ctrl-d".to_string(),
+                action: "editor::SelectNext",
+            },
+        ];
+        let imported = keymap_with_bindings("", &bindings).unwrap();
+        assert_eq!(
+            imported,
+            r#"[
+  {
+    "bindings": {
+      "cmd-k cmd-c": "editor::ToggleComments"
+    }
+  },
+  {
+    "context": "Editor",
+    "bindings": {
+      "ctrl-d": "editor::SelectNext"
+    }
+  }
+]
+"#
+        );
+        // Importing the same bindings again leaves the keymap unchanged.
+        assert_eq!(
+            keymap_with_bindings(&imported, &bindings).unwrap(),
+            imported
+        );
+
+        let existing = r#"[
+  // My bindings
+  { "bindings": { "cmd-d": "editor::SelectNext" } }
+]
+"#;
+        assert_eq!(
+            keymap_with_bindings(existing, &bindings[..1]).unwrap(),
+            r#"[
+  // My bindings
+  { "bindings": { "cmd-d": "editor::SelectNext", "cmd-k cmd-c": "editor::ToggleComments" } }
+]
+"#
+        );
+    }
+}
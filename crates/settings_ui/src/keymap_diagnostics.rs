@@ -0,0 +1,335 @@
+use editor::{scroll::Autoscroll, Editor};
+use fs::Fs;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusableView, Render, SharedString, Task,
+    View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use settings::{KeymapBindingInfo, KeymapBindingStatus, KeymapFile, Settings, SettingsAssets};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::{asset_str, paths, ResultExt};
+use vim::VimModeSetting;
+use welcome::BaseKeymap;
+use workspace::{ModalView, Workspace};
+
+actions!(settings_ui, [ShowKeymapDiagnostics]);
+
+const USER_KEYMAP_SOURCE: &str = "User";
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(toggle);
+    })
+    .detach();
+}
+
+fn toggle(workspace: &mut Workspace, _: &ShowKeymapDiagnostics, cx: &mut ViewContext<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    let keymaps = bundled_keymaps(cx);
+    cx.spawn(|workspace, mut cx| async move {
+        let user_keymap = fs.load(&paths::KEYMAP).await.unwrap_or_default();
+        let bindings = load_bindings(keymaps, &user_keymap);
+        workspace.update(&mut cx, |workspace, cx| {
+            let weak_workspace = cx.view().downgrade();
+            workspace.toggle_modal(cx, |cx| {
+                let delegate = KeymapDiagnosticsDelegate::new(
+                    cx.view().downgrade(),
+                    weak_workspace,
+                    fs,
+                    bindings,
+                );
+                KeymapDiagnostics::new(delegate, cx)
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Returns the bundled keymaps that are in effect, in the order they are loaded.
+fn bundled_keymaps(cx: &AppContext) -> Vec<(SharedString, String)> {
+    let mut keymaps = vec![("Default".into(), settings::default_keymap().into_owned())];
+    if VimModeSetting::get_global(cx).0 {
+        keymaps.push(("Vim".into(), settings::vim_keymap().into_owned()));
+    }
+    let base_keymap = BaseKeymap::get_global(cx);
+    if let Some(asset_path) = base_keymap.asset_path() {
+        keymaps.push((
+            base_keymap.to_string().into(),
+            asset_str::<SettingsAssets>(asset_path).into_owned(),
+        ));
+    }
+    keymaps
+}
+
+fn load_bindings(
+    bundled_keymaps: Vec<(SharedString, String)>,
+    user_keymap: &str,
+) -> Vec<KeymapBindingInfo> {
+    let mut keymaps = bundled_keymaps
+        .into_iter()
+        .filter_map(|(source, content)| Some((source, KeymapFile::parse(&content).log_err()?)))
+        .collect::<Vec<_>>();
+    if !user_keymap.trim().is_empty() {
+        if let Some(keymap) = KeymapFile::parse(user_keymap).log_err() {
+            keymaps.push((USER_KEYMAP_SOURCE.into(), keymap));
+        }
+    }
+
+    let mut bindings = settings::analyze_keymaps(&keymaps);
+    bindings.sort_by(|a, b| {
+        a.context
+            .cmp(&b.context)
+            .then_with(|| a.keystrokes.cmp(&b.keystrokes))
+    });
+    bindings
+}
+
+pub struct KeymapDiagnostics {
+    picker: View<Picker<KeymapDiagnosticsDelegate>>,
+}
+
+impl FocusableView for KeymapDiagnostics {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for KeymapDiagnostics {}
+impl ModalView for KeymapDiagnostics {}
+
+impl KeymapDiagnostics {
+    fn new(delegate: KeymapDiagnosticsDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for KeymapDiagnostics {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(48.)).child(self.picker.clone())
+    }
+}
+
+pub struct KeymapDiagnosticsDelegate {
+    view: WeakView<KeymapDiagnostics>,
+    workspace: WeakView<Workspace>,
+    fs: Arc<dyn Fs>,
+    bindings: Vec<KeymapBindingInfo>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl KeymapDiagnosticsDelegate {
+    fn new(
+        view: WeakView<KeymapDiagnostics>,
+        workspace: WeakView<Workspace>,
+        fs: Arc<dyn Fs>,
+        bindings: Vec<KeymapBindingInfo>,
+    ) -> Self {
+        Self {
+            view,
+            workspace,
+            fs,
+            bindings,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    fn binding_label(binding: &KeymapBindingInfo) -> String {
+        format!(
+            "{}  {}  ({})",
+            binding.keystrokes,
+            action_label(&binding.action),
+            binding.context.as_deref().unwrap_or("any context")
+        )
+    }
+
+    fn open_user_binding(&self, binding: &KeymapBindingInfo, cx: &mut WindowContext) {
+        let fs = self.fs.clone();
+        let context = binding.context.clone();
+        let keystrokes = binding.keystrokes.clone();
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let open_keymap = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(paths::KEYMAP.clone(), false, cx)
+        });
+        cx.spawn(|mut cx| async move {
+            let item = open_keymap.await?;
+            let content = fs.load(&paths::KEYMAP).await?;
+            let Some(offset) = settings::binding_offset(&content, context.as_deref(), &keystrokes)
+            else {
+                return Ok(());
+            };
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                        selections.select_ranges([offset..offset])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for KeymapDiagnosticsDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search key bindings...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .bindings
+            .iter()
+            .enumerate()
+            .map(|(id, binding)| {
+                let label = Self::binding_label(binding);
+                StringMatchCandidate {
+                    id,
+                    char_bag: label.as_str().into(),
+                    string: label,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    10000,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                matches.sort_unstable_by_key(|string_match| string_match.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, _| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(binding_match) = self.matches.get(self.selected_index) {
+            let binding = &self.bindings[binding_match.candidate_id];
+            if binding.source == USER_KEYMAP_SOURCE {
+                self.open_user_binding(binding, cx);
+            }
+        }
+
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let binding_match = &self.matches[ix];
+        let binding = &self.bindings[binding_match.candidate_id];
+        let (status, color) = match &binding.status {
+            KeymapBindingStatus::Active => (None, Color::Muted),
+            KeymapBindingStatus::Overridden { source, action } => (
+                Some(if action.is_null() {
+                    format!("disabled by the {source} keymap")
+                } else {
+                    format!(
+                        "overridden by {} in the {source} keymap",
+                        action_label(action)
+                    )
+                }),
+                Color::Warning,
+            ),
+            KeymapBindingStatus::Shadowed { keystrokes } => {
+                (Some(format!("waits for {keystrokes}")), Color::Warning)
+            }
+        };
+        let source = if binding.source == USER_KEYMAP_SOURCE {
+            "User keymap (enter to open)".to_string()
+        } else {
+            format!("{} keymap", binding.source)
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(
+                            binding_match.string.clone(),
+                            binding_match.positions.clone(),
+                        ))
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Label::new(source)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .when_some(status, |this, status| {
+                                    this.child(
+                                        Label::new(status).size(LabelSize::Small).color(color),
+                                    )
+                                }),
+                        ),
+                ),
+        )
+    }
+}
+
+fn action_label(action: &serde_json::Value) -> String {
+    match action {
+        serde_json::Value::Null => "no action".to_string(),
+        serde_json::Value::String(name) => name.clone(),
+        action => action.to_string(),
+    }
+}
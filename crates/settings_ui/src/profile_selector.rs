@@ -0,0 +1,235 @@
+use db::kvp::KEY_VALUE_STORE;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusableView, Render, Task, View,
+    ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use settings::SettingsStore;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(settings_ui, [SelectSettingsProfile]);
+
+const BASE_SETTINGS_LABEL: &str = "Base settings";
+const ACTIVE_PROFILE_KEY: &str = "active_settings_profile";
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(toggle);
+    })
+    .detach();
+    restore_active_profile(cx);
+}
+
+/// Switches back to the profile that was active when Zed was last closed.
+fn restore_active_profile(cx: &mut AppContext) {
+    cx.spawn(|mut cx| async move {
+        let profile = cx
+            .background_executor()
+            .spawn(async { KEY_VALUE_STORE.read_kvp(ACTIVE_PROFILE_KEY) })
+            .await?;
+        if let Some(profile) = profile {
+            cx.update_global(|store: &mut SettingsStore, cx| {
+                store.set_active_profile(Some(profile), cx)
+            })??;
+            cx.refresh()?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+fn persist_active_profile(profile: Option<String>, cx: &AppContext) {
+    cx.background_executor()
+        .spawn(async move {
+            match profile {
+                Some(profile) => {
+                    KEY_VALUE_STORE
+                        .write_kvp(ACTIVE_PROFILE_KEY.to_string(), profile)
+                        .await
+                }
+                None => {
+                    KEY_VALUE_STORE
+                        .delete_kvp(ACTIVE_PROFILE_KEY.to_string())
+                        .await
+                }
+            }
+        })
+        .detach_and_log_err(cx);
+}
+
+fn toggle(workspace: &mut Workspace, _: &SelectSettingsProfile, cx: &mut ViewContext<Workspace>) {
+    workspace.toggle_modal(cx, |cx| {
+        ProfileSelector::new(ProfileSelectorDelegate::new(cx.view().downgrade(), cx), cx)
+    });
+}
+
+pub struct ProfileSelector {
+    picker: View<Picker<ProfileSelectorDelegate>>,
+}
+
+impl FocusableView for ProfileSelector {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ProfileSelector {}
+impl ModalView for ProfileSelector {}
+
+impl ProfileSelector {
+    fn new(delegate: ProfileSelectorDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for ProfileSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct ProfileSelectorDelegate {
+    view: WeakView<ProfileSelector>,
+    /// The profile names, with the base settings (no profile) first.
+    profiles: Vec<Option<String>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl ProfileSelectorDelegate {
+    fn new(view: WeakView<ProfileSelector>, cx: &mut ViewContext<ProfileSelector>) -> Self {
+        let store = cx.global::<SettingsStore>();
+        let active_profile = store.active_profile().map(ToString::to_string);
+        let profiles = std::iter::once(None)
+            .chain(store.profile_names().into_iter().map(Some))
+            .collect::<Vec<_>>();
+        let selected_index = profiles
+            .iter()
+            .position(|profile| *profile == active_profile)
+            .unwrap_or(0);
+        Self {
+            view,
+            profiles,
+            matches: Vec::new(),
+            selected_index,
+        }
+    }
+
+    fn profile_label(profile: &Option<String>) -> &str {
+        profile.as_deref().unwrap_or(BASE_SETTINGS_LABEL)
+    }
+}
+
+impl PickerDelegate for ProfileSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Select a settings profile...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(id, profile)| {
+                let label = Self::profile_label(profile);
+                StringMatchCandidate {
+                    id,
+                    char_bag: label.into(),
+                    string: label.into(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, _| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(profile_match) = self.matches.get(self.selected_index) {
+            let profile = self.profiles[profile_match.candidate_id].clone();
+            cx.update_global(|store: &mut SettingsStore, cx| {
+                store.set_active_profile(profile.clone(), cx).log_err();
+            });
+            cx.refresh();
+            persist_active_profile(profile, cx);
+        }
+
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let profile_match = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    profile_match.string.clone(),
+                    profile_match.positions.clone(),
+                )),
+        )
+    }
+}
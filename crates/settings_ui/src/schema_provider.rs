@@ -0,0 +1,487 @@
+use editor::{CompletionProvider, Editor, HoverProvider};
+use feature_flags::FeatureFlagAppExt;
+use gpui::{AppContext, HighlightStyle, Model, Task, UnderlineStyle};
+use language::{
+    Anchor, Buffer, CodeLabel, Completion, Documentation, LanguageRegistry, LanguageServerId,
+    ToOffset,
+};
+use parking_lot::RwLock;
+use project::{Hover, HoverBlock, HoverBlockKind};
+use serde_json::{json, Value};
+use settings::{
+    JsonLocationKind, JsonPathSegment, KeymapFile, SettingsJsonSchemaParams, SettingsStore,
+};
+use std::{cell::RefCell, path::Path, rc::Rc, sync::Arc};
+use theme::ThemeFamilyContent;
+use ui::prelude::*;
+use util::{paths, ResultExt};
+
+/// The server id of the completions that come from the schema, which no
+/// language server has.
+const SCHEMA_COMPLETION_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
+/// Zed's own configuration files, which get completions, hover docs and
+/// deprecation warnings from the schema Zed generates for them, in addition to
+/// those of the JSON language server, if it's running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConfigFileKind {
+    Settings,
+    Keymap,
+    Theme,
+}
+
+impl ConfigFileKind {
+    fn for_path(abs_path: &Path) -> Option<Self> {
+        if abs_path == paths::SETTINGS.as_path()
+            || abs_path.ends_with(*paths::LOCAL_SETTINGS_RELATIVE_PATH)
+        {
+            Some(Self::Settings)
+        } else if abs_path == paths::KEYMAP.as_path() {
+            Some(Self::Keymap)
+        } else if abs_path.starts_with(paths::THEMES_DIR.as_path())
+            && abs_path.extension().map_or(false, |ext| ext == "json")
+        {
+            Some(Self::Theme)
+        } else {
+            None
+        }
+    }
+
+    fn schema(&self, language_names: &[String], cx: &AppContext) -> Value {
+        match self {
+            Self::Settings => {
+                let font_names = cx.text_system().all_font_names();
+                cx.global::<SettingsStore>().json_schema(
+                    &SettingsJsonSchemaParams {
+                        language_names,
+                        staff_mode: cx.is_staff(),
+                        font_names: &font_names,
+                    },
+                    cx,
+                )
+            }
+            Self::Keymap => KeymapFile::generate_json_schema(&cx.all_action_names()),
+            Self::Theme => {
+                let mut schema = serde_json::to_value(schemars::schema_for!(ThemeFamilyContent))
+                    .unwrap_or_default();
+                // Themes can still use the old name of the scrollbar thumb color.
+                if let Some(Value::Object(properties)) =
+                    schema.pointer_mut("/definitions/ThemeColorsContent/properties")
+                {
+                    properties.insert(
+                        "scrollbar_thumb.background".into(),
+                        json!({
+                            "type": ["string", "null"],
+                            "deprecated": true,
+                            "deprecationMessage": "Use `scrollbar.thumb.background` instead."
+                        }),
+                    );
+                }
+                schema
+            }
+        }
+    }
+}
+
+/// The schema of a configuration file, which is regenerated when the settings
+/// or the available languages change.
+struct ConfigSchema {
+    kind: ConfigFileKind,
+    languages: Option<Arc<LanguageRegistry>>,
+    cached: RefCell<Option<(usize, Arc<Value>)>>,
+}
+
+impl ConfigSchema {
+    fn get(&self, cx: &AppContext) -> Arc<Value> {
+        let languages_version = self
+            .languages
+            .as_ref()
+            .map_or(0, |languages| languages.version());
+        if let Some((version, schema)) = self.cached.borrow().as_ref() {
+            if *version == languages_version {
+                return schema.clone();
+            }
+        }
+
+        let language_names = self
+            .languages
+            .as_ref()
+            .map(|languages| languages.language_names())
+            .unwrap_or_default();
+        let schema = Arc::new(self.kind.schema(&language_names, cx));
+        *self.cached.borrow_mut() = Some((languages_version, schema.clone()));
+        schema
+    }
+
+    fn invalidate(&self) {
+        self.cached.borrow_mut().take();
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|editor: &mut Editor, cx| {
+        let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some(kind) = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| Some(file.as_local()?.abs_path(cx)))
+            .and_then(|abs_path| ConfigFileKind::for_path(&abs_path))
+        else {
+            return;
+        };
+
+        let schema = Rc::new(ConfigSchema {
+            kind,
+            languages: editor
+                .project()
+                .map(|project| project.read(cx).languages().clone()),
+            cached: Default::default(),
+        });
+        let completion_provider = SchemaCompletionProvider {
+            schema: schema.clone(),
+            next: editor.take_completion_provider(),
+        };
+        let hover_provider = SchemaHoverProvider {
+            schema: schema.clone(),
+            next: editor.take_hover_provider(),
+        };
+        editor.set_completion_provider(Box::new(completion_provider));
+        editor.set_hover_provider(Box::new(hover_provider));
+
+        highlight_deprecated_keys(editor, &schema, cx);
+        cx.subscribe(&buffer, {
+            let schema = schema.clone();
+            move |editor, _, event, cx| {
+                if let language::Event::Edited | language::Event::Reloaded = event {
+                    highlight_deprecated_keys(editor, &schema, cx);
+                }
+            }
+        })
+        .detach();
+        cx.observe_global::<SettingsStore>(move |editor, cx| {
+            schema.invalidate();
+            highlight_deprecated_keys(editor, &schema, cx);
+        })
+        .detach();
+    })
+    .detach();
+}
+
+/// Underlines the keys that the schema deprecates. Hovering them explains why.
+fn highlight_deprecated_keys(
+    editor: &mut Editor,
+    schema: &ConfigSchema,
+    cx: &mut ViewContext<Editor>,
+) {
+    let schema = schema.get(cx);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let text = snapshot.text();
+    let ranges = settings::deprecated_keys(&text, &schema)
+        .into_iter()
+        .map(|(key, _)| {
+            snapshot.anchor_after(key.range.start)..snapshot.anchor_before(key.range.end)
+        })
+        .collect::<Vec<_>>();
+    if ranges.is_empty() {
+        editor.clear_highlights::<ConfigSchema>(cx);
+        return;
+    }
+
+    let style = HighlightStyle {
+        underline: Some(UnderlineStyle {
+            thickness: px(1.),
+            color: Some(cx.theme().status().warning),
+            wavy: true,
+        }),
+        ..Default::default()
+    };
+    editor.highlight_text::<ConfigSchema>(ranges, style, cx);
+}
+
+/// Completes keys and values from the schema, alongside the completions of
+/// the provider it replaced.
+struct SchemaCompletionProvider {
+    schema: Rc<ConfigSchema>,
+    next: Option<Box<dyn CompletionProvider>>,
+}
+
+impl CompletionProvider for SchemaCompletionProvider {
+    fn completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<Vec<Completion>>> {
+        let schema_completions = self.schema_completions(buffer, buffer_position, cx);
+        let Some(next) = self.next.as_ref() else {
+            return Task::ready(Ok(schema_completions));
+        };
+        let next_completions = next.completions(buffer, buffer_position, cx);
+        cx.spawn(|_, _| async move {
+            // The language server's completions take precedence over the same ones from the
+            // schema, and its failure doesn't hide the schema's completions.
+            let mut completions = next_completions.await.log_err().unwrap_or_default();
+            let schema_completions = schema_completions
+                .into_iter()
+                .filter(|completion| {
+                    completions
+                        .iter()
+                        .all(|other| other.label.text != completion.label.text)
+                })
+                .collect::<Vec<_>>();
+            completions.extend(schema_completions);
+            Ok(completions)
+        })
+    }
+
+    fn resolve_completions(
+        &self,
+        completion_indices: Vec<usize>,
+        completions: Arc<RwLock<Box<[Completion]>>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<bool>> {
+        // The schema's completions are documented from the start, so only the others need
+        // resolving.
+        let completion_indices = {
+            let completions = completions.read();
+            completion_indices
+                .into_iter()
+                .filter(|ix| completions[*ix].server_id != SCHEMA_COMPLETION_SERVER_ID)
+                .collect::<Vec<_>>()
+        };
+        match self.next.as_ref() {
+            Some(next) if !completion_indices.is_empty() => {
+                next.resolve_completions(completion_indices, completions, cx)
+            }
+            _ => Task::ready(Ok(false)),
+        }
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        buffer: Model<Buffer>,
+        completion: Completion,
+        push_to_history: bool,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<Option<language::Transaction>>> {
+        match self.next.as_ref() {
+            Some(next) if completion.server_id != SCHEMA_COMPLETION_SERVER_ID => {
+                next.apply_additional_edits_for_completion(buffer, completion, push_to_history, cx)
+            }
+            _ => Task::ready(Ok(None)),
+        }
+    }
+}
+
+impl SchemaCompletionProvider {
+    fn schema_completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Vec<Completion> {
+        let schema = self.schema.get(cx);
+        let buffer = buffer.read(cx);
+        let text = buffer.text();
+        let offset = buffer_position.to_offset(buffer);
+        let Some(location) = settings::json_location_at_offset(&text, offset) else {
+            return Vec::new();
+        };
+
+        let root = schema.as_ref();
+        let schemas = settings::schemas_at_path(root, &location.path);
+        let candidates = match location.kind {
+            JsonLocationKind::Key => settings::schema_properties(root, &schemas)
+                .into_iter()
+                .filter(|property| property.deprecation.is_none())
+                .map(|property| {
+                    let new_text = Value::String(property.name.clone()).to_string();
+                    (property.name, new_text, property.description)
+                })
+                .collect::<Vec<_>>(),
+            JsonLocationKind::Value => settings::schema_values(&schemas)
+                .into_iter()
+                .map(|value| (value.clone(), value, None))
+                .collect(),
+        };
+
+        let old_range =
+            buffer.anchor_before(location.range.start)..buffer.anchor_after(location.range.end);
+        candidates
+            .into_iter()
+            .map(|(label, new_text, description)| Completion {
+                old_range: old_range.clone(),
+                new_text,
+                label: CodeLabel {
+                    filter_range: 0..label.len(),
+                    text: label,
+                    runs: Vec::new(),
+                },
+                documentation: Some(
+                    description.map_or(Documentation::Undocumented, |description| {
+                        Documentation::MultiLinePlainText(description)
+                    }),
+                ),
+                server_id: SCHEMA_COMPLETION_SERVER_ID,
+                lsp_completion: Default::default(),
+            })
+            .collect()
+    }
+}
+
+/// Shows the schema's docs for keys and values that the provider it replaced
+/// has nothing to show for.
+struct SchemaHoverProvider {
+    schema: Rc<ConfigSchema>,
+    next: Option<Box<dyn HoverProvider>>,
+}
+
+impl HoverProvider for SchemaHoverProvider {
+    fn hover(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<Option<Hover>>> {
+        let schema_hover = self.schema_hover(buffer, buffer_position, cx);
+        let Some(next) = self.next.as_ref() else {
+            return Task::ready(Ok(schema_hover));
+        };
+        let next_hover = next.hover(buffer, buffer_position, cx);
+        cx.spawn(|_, _| async move {
+            match next_hover.await.log_err().flatten() {
+                Some(hover) if !hover.is_empty() => Ok(Some(hover)),
+                _ => Ok(schema_hover),
+            }
+        })
+    }
+}
+
+impl SchemaHoverProvider {
+    fn schema_hover(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<Hover> {
+        let schema = self.schema.get(cx);
+        let buffer = buffer.read(cx);
+        let text = buffer.text();
+        let offset = buffer_position.to_offset(buffer);
+        let location = settings::json_location_at_offset(&text, offset)?;
+        if location.range.is_empty() {
+            return None;
+        }
+
+        let mut path = location.path;
+        if location.kind == JsonLocationKind::Key {
+            let key = serde_json::from_str(&text[location.range.clone()])
+                .unwrap_or_else(|_| text[location.range.clone()].trim_matches('"').to_string());
+            path.push(JsonPathSegment::Key(key));
+        }
+        let schemas = settings::schemas_at_path(&schema, &path);
+
+        let mut contents = Vec::new();
+        if let Some(deprecation) = settings::schema_deprecation(&schemas) {
+            contents.push(HoverBlock {
+                text: format!("**Deprecated:** {deprecation}"),
+                kind: HoverBlockKind::Markdown,
+            });
+        }
+        if let Some(description) = settings::schema_description(&schemas) {
+            contents.push(HoverBlock {
+                text: description,
+                kind: HoverBlockKind::Markdown,
+            });
+        }
+        if contents.is_empty() {
+            return None;
+        }
+
+        Some(Hover {
+            contents,
+            range: Some(
+                buffer.anchor_before(location.range.start)..buffer.anchor_after(location.range.end),
+            ),
+            language: buffer.language().cloned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::actions::{ConfirmCompletion, ShowCompletions};
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use project::Project;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_local_settings_file(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+            init(cx);
+        });
+
+        let text = "{\n  \"language_overrides\": {},\n  \"tab_si\"\n}";
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ ".zed": { "settings.json": text } }))
+            .await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/project/.zed/settings.json", cx)
+            })
+            .await
+            .unwrap();
+        let (editor, cx) =
+            cx.add_window_view(|cx| Editor::for_buffer(buffer.clone(), Some(project), cx));
+        cx.run_until_parked();
+
+        // Keys that are only accepted for backward compatibility are flagged.
+        editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let (_, ranges) = editor.text_highlights::<ConfigSchema>(cx).unwrap();
+            assert_eq!(
+                ranges
+                    .iter()
+                    .map(|range| snapshot.text_for_range(range.clone()).collect::<String>())
+                    .collect::<Vec<_>>(),
+                ["\"language_overrides\""]
+            );
+        });
+
+        // Keys are completed from the settings' schema.
+        let offset = text.find("tab_si").unwrap() + "tab_si".len();
+        editor.update(cx, |editor, cx| {
+            cx.focus_self();
+            editor.change_selections(None, cx, |selections| {
+                selections.select_ranges([offset..offset])
+            });
+        });
+        cx.dispatch_action(ShowCompletions);
+        cx.run_until_parked();
+        editor
+            .update(cx, |editor, cx| {
+                assert!(editor.context_menu_visible());
+                editor.confirm_completion(&ConfirmCompletion::default(), cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, _| {
+            assert_eq!(
+                buffer.text(),
+                "{\n  \"language_overrides\": {},\n  \"tab_size\"\n}"
+            );
+        });
+    }
+}
@@ -0,0 +1,802 @@
+mod importer;
+mod keymap_diagnostics;
+mod profile_selector;
+mod schema_provider;
+
+use anyhow::{anyhow, Context as _, Result};
+use editor::{actions::SelectAll, Editor, EditorEvent};
+use feature_flags::FeatureFlagAppExt;
+use fs::Fs;
+use futures::StreamExt;
+use gpui::{
+    actions, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Keystroke, Render,
+    Subscription, Task, View, VisualContext, WeakView,
+};
+use serde_json::Value;
+use settings::{KeymapConflict, KeymapFile, SettingsJsonSchemaParams, SettingsStore};
+use std::sync::Arc;
+use ui::{popover_menu, prelude::*, Checkbox, ContextMenu, Selection, Tooltip};
+use util::{paths, ResultExt};
+use workspace::{
+    item::{Item, ItemEvent},
+    Workspace, WorkspaceId,
+};
+
+pub use importer::{ImportSublimeTextSettings, ImportVsCodeSettings};
+pub use keymap_diagnostics::ShowKeymapDiagnostics;
+pub use profile_selector::SelectSettingsProfile;
+
+actions!(settings_ui, [OpenSettingsEditor]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+        workspace.register_action(|workspace, _: &OpenSettingsEditor, cx| {
+            if let Some(settings_editor) = workspace.item_of_type::<SettingsEditor>(cx) {
+                workspace.activate_item(&settings_editor, cx);
+            } else {
+                let settings_editor = SettingsEditor::new(workspace, cx);
+                workspace.add_item(Box::new(settings_editor), cx)
+            }
+        });
+    })
+    .detach();
+    importer::init(cx);
+    keymap_diagnostics::init(cx);
+    profile_selector::init(cx);
+    schema_provider::init(cx);
+}
+
+/// A single setting that can be edited with a control, identified by its
+/// path of keys within the settings file.
+struct SettingEntry {
+    key_path: Vec<String>,
+    kind: SettingKind,
+}
+
+enum SettingKind {
+    Boolean,
+    Number,
+    String { options: Vec<String> },
+}
+
+/// The text field shown in place of a number or string setting while it's edited.
+struct ValueEditor {
+    entry_ix: usize,
+    editor: View<Editor>,
+    _subscription: Subscription,
+}
+
+pub struct SettingsEditor {
+    fs: Arc<dyn Fs>,
+    workspace: WeakView<Workspace>,
+    focus_handle: FocusHandle,
+    entries: Vec<SettingEntry>,
+    value_editor: Option<ValueEditor>,
+    keymap_conflicts: Vec<KeymapConflict>,
+    binding_keystrokes: View<Editor>,
+    binding_action: View<Editor>,
+    binding_context: View<Editor>,
+    binding_error: Option<SharedString>,
+    _settings_subscription: Subscription,
+    _watch_keymap: Task<Result<()>>,
+}
+
+impl SettingsEditor {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let workspace = cx.view().downgrade();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let settings_subscription = cx.observe_global::<SettingsStore>(|_, cx| cx.notify());
+
+            // The keymap isn't part of the settings store, so the conflicts are
+            // recomputed whenever keymap.json changes.
+            let mut keymap_rx = settings::watch_config_file(
+                cx.background_executor(),
+                fs.clone(),
+                paths::KEYMAP.clone(),
+            );
+            let watch_keymap = cx.spawn(|this, mut cx| async move {
+                while let Some(content) = keymap_rx.next().await {
+                    let conflicts = keymap_conflicts(&content).log_err().unwrap_or_default();
+                    this.update(&mut cx, |this, cx| {
+                        this.keymap_conflicts = conflicts;
+                        cx.notify();
+                    })?;
+                }
+                Ok(())
+            });
+
+            let binding_field = |placeholder: &str, cx: &mut ViewContext<Self>| {
+                cx.new_view(|cx| {
+                    let mut editor = Editor::single_line(cx);
+                    editor.set_placeholder_text(placeholder, cx);
+                    editor
+                })
+            };
+
+            Self {
+                fs,
+                workspace,
+                focus_handle: cx.focus_handle(),
+                entries: setting_entries(cx),
+                value_editor: None,
+                keymap_conflicts: Vec::new(),
+                binding_keystrokes: binding_field("Keystrokes, e.g. cmd-k cmd-s", cx),
+                binding_action: binding_field("Action, e.g. workspace::Save", cx),
+                binding_context: binding_field("Context (optional), e.g. Editor", cx),
+                binding_error: None,
+                _settings_subscription: settings_subscription,
+                _watch_keymap: watch_keymap,
+            }
+        })
+    }
+
+    fn set_value(&self, key_path: Vec<String>, value: Value, cx: &mut AppContext) {
+        settings::update_settings_file_value(self.fs.clone(), cx, key_path, value);
+    }
+
+    fn edit_value(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(entry_ix) else {
+            return;
+        };
+        let text = match current_value(&entry.key_path, cx) {
+            Value::String(text) => text,
+            Value::Null => String::new(),
+            value => value.to_string(),
+        };
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text(text, cx);
+            editor.select_all(&SelectAll, cx);
+            editor
+        });
+        cx.focus_view(&editor);
+        let subscription = cx.subscribe(&editor, |this, _, event: &EditorEvent, cx| {
+            if let EditorEvent::Blurred = event {
+                this.commit_value(cx);
+            }
+        });
+        self.value_editor = Some(ValueEditor {
+            entry_ix,
+            editor,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if self.value_editor.is_some() {
+            self.commit_value(cx);
+            cx.focus(&self.focus_handle);
+        }
+    }
+
+    /// Writes the value typed into the value editor, unless it isn't a valid number for a
+    /// number setting.
+    fn commit_value(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(value_editor) = self.value_editor.take() else {
+            return;
+        };
+        cx.notify();
+        let Some(entry) = self.entries.get(value_editor.entry_ix) else {
+            return;
+        };
+        let text = value_editor.editor.read(cx).text(cx);
+        let value = match entry.kind {
+            SettingKind::Number => {
+                let current = current_value(&entry.key_path, cx);
+                let Some(value) = parse_number(&text, current.is_u64() || current.is_i64()) else {
+                    return;
+                };
+                value
+            }
+            SettingKind::String { .. } => Value::String(text),
+            SettingKind::Boolean => return,
+        };
+        if value != current_value(&entry.key_path, cx) {
+            self.set_value(entry.key_path.clone(), value, cx);
+        }
+    }
+
+    fn save_binding(&mut self, cx: &mut ViewContext<Self>) {
+        let keystrokes = self.binding_keystrokes.read(cx).text(cx);
+        let action = self.binding_action.read(cx).text(cx);
+        let context = self.binding_context.read(cx).text(cx);
+        let (keystrokes, action) = match parse_binding(&keystrokes, &action, cx) {
+            Ok(binding) => binding,
+            Err(error) => {
+                self.binding_error = Some(format!("{error:#}").into());
+                cx.notify();
+                return;
+            }
+        };
+        let context = Some(context.trim().to_string()).filter(|context| !context.is_empty());
+
+        let update = settings::update_keymap_file(self.fs.clone(), cx, move |content| {
+            settings::set_keymap_bindings(&content, context.as_deref(), &[(keystrokes, action)])
+        });
+        cx.spawn(|this, mut cx| async move {
+            let result = update.await;
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(()) => {
+                        this.binding_error = None;
+                        for field in [
+                            &this.binding_keystrokes,
+                            &this.binding_action,
+                            &this.binding_context,
+                        ] {
+                            field.update(cx, |field, cx| field.clear(cx));
+                        }
+                    }
+                    Err(error) => this.binding_error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Fills the binding form with a binding from keymap.json, so that it can be changed.
+    fn edit_binding(&mut self, conflict: &KeymapConflict, cx: &mut ViewContext<Self>) {
+        let action = match &conflict.action {
+            Value::String(name) => name.clone(),
+            action => action.to_string(),
+        };
+        let context = conflict.context.clone().unwrap_or_default();
+        self.binding_keystrokes.update(cx, |field, cx| {
+            field.set_text(conflict.keystrokes.clone(), cx)
+        });
+        self.binding_action
+            .update(cx, |field, cx| field.set_text(action, cx));
+        self.binding_context
+            .update(cx, |field, cx| field.set_text(context, cx));
+        cx.focus_view(&self.binding_action);
+    }
+
+    fn open_keymap(&self, cx: &mut WindowContext) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_abs_path(paths::KEYMAP.clone(), false, cx)
+                    .detach_and_log_err(cx);
+            })
+            .log_err();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &SettingEntry, cx: &mut ViewContext<Self>) -> Div {
+        let value = current_value(&entry.key_path, cx);
+        let control = match &entry.kind {
+            SettingKind::Boolean => {
+                let key_path = entry.key_path.clone();
+                let selection = if value.as_bool().unwrap_or(false) {
+                    Selection::Selected
+                } else {
+                    Selection::Unselected
+                };
+                Checkbox::new(("setting-checkbox", ix), selection)
+                    .on_click(cx.listener(move |this, selection: &Selection, cx| {
+                        let enabled = *selection == Selection::Selected;
+                        this.set_value(key_path.clone(), Value::Bool(enabled), cx);
+                    }))
+                    .into_any_element()
+            }
+            SettingKind::Number => {
+                let number = value.as_f64().unwrap_or(0.);
+                let is_integer = value.is_u64() || value.is_i64();
+                let step = if is_integer { 1. } else { 0.5 };
+                let decrement_key_path = entry.key_path.clone();
+                let increment_key_path = entry.key_path.clone();
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new(("setting-decrement", ix), IconName::Dash)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                let value = number_value(number - step, is_integer);
+                                this.set_value(decrement_key_path.clone(), value, cx);
+                            })),
+                    )
+                    .child(self.render_value(ix, value.to_string(), cx))
+                    .child(
+                        IconButton::new(("setting-increment", ix), IconName::Plus)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                let value = number_value(number + step, is_integer);
+                                this.set_value(increment_key_path.clone(), value, cx);
+                            })),
+                    )
+                    .into_any_element()
+            }
+            SettingKind::String { options } => {
+                let label = value.as_str().unwrap_or_default().to_string();
+                if options.is_empty() {
+                    self.render_value(ix, label, cx).into_any_element()
+                } else {
+                    let key_path = entry.key_path.clone();
+                    let options = options.clone();
+                    let editor = cx.view().downgrade();
+                    popover_menu(("setting-menu", ix))
+                        .trigger(
+                            Button::new(("setting-menu-trigger", ix), label)
+                                .style(ButtonStyle::Subtle)
+                                .icon(IconName::ChevronDown)
+                                .icon_position(IconPosition::End)
+                                .icon_size(IconSize::Small),
+                        )
+                        .menu(move |cx| {
+                            let key_path = key_path.clone();
+                            let options = options.clone();
+                            let editor = editor.clone();
+                            Some(ContextMenu::build(cx, move |mut menu, _| {
+                                for option in options {
+                                    let key_path = key_path.clone();
+                                    let editor = editor.clone();
+                                    let value = Value::String(option.clone());
+                                    menu = menu.entry(option, None, move |cx| {
+                                        editor
+                                            .update(cx, |editor, cx| {
+                                                editor.set_value(
+                                                    key_path.clone(),
+                                                    value.clone(),
+                                                    cx,
+                                                )
+                                            })
+                                            .ok();
+                                    });
+                                }
+                                menu
+                            }))
+                        })
+                        .into_any_element()
+                }
+            }
+        };
+
+        h_flex()
+            .w_full()
+            .justify_between()
+            .py_1()
+            .child(Label::new(entry.key_path.join(".")))
+            .child(control)
+    }
+
+    /// Renders the value of a number or string setting, which turns into a text field when
+    /// clicked.
+    fn render_value(&self, ix: usize, label: String, cx: &mut ViewContext<Self>) -> AnyElement {
+        match &self.value_editor {
+            Some(value_editor) if value_editor.entry_ix == ix => {
+                text_field(&value_editor.editor, cx)
+                    .w_48()
+                    .into_any_element()
+            }
+            _ => {
+                let label = if label.is_empty() {
+                    "(empty)".to_string()
+                } else {
+                    label
+                };
+                Button::new(("setting-value", ix), label)
+                    .style(ButtonStyle::Subtle)
+                    .tooltip(|cx| Tooltip::text("Click to edit", cx))
+                    .on_click(cx.listener(move |this, _, cx| this.edit_value(ix, cx)))
+                    .into_any_element()
+            }
+        }
+    }
+
+    fn render_binding_form(&self, cx: &mut ViewContext<Self>) -> Div {
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(text_field(&self.binding_keystrokes, cx).flex_1())
+                    .child(text_field(&self.binding_action, cx).flex_1())
+                    .child(text_field(&self.binding_context, cx).flex_1())
+                    .child(
+                        Button::new("save-binding", "Save Binding")
+                            .style(ButtonStyle::Filled)
+                            .tooltip(|cx| {
+                                Tooltip::text(
+                                    "Add the binding to keymap.json, replacing the keystrokes' \
+                                     current binding in that context",
+                                    cx,
+                                )
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.save_binding(cx))),
+                    ),
+            )
+            .children(
+                self.binding_error
+                    .clone()
+                    .map(|error| Label::new(error).color(Color::Error)),
+            )
+    }
+
+    fn render_keymap(&self, cx: &mut ViewContext<Self>) -> Div {
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Headline::new("Key Bindings").size(HeadlineSize::Medium))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Button::new("show-keymap-diagnostics", "Show All Bindings")
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Search bindings and their conflicts",
+                                            &ShowKeymapDiagnostics,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(|_, cx| {
+                                        cx.dispatch_action(Box::new(ShowKeymapDiagnostics))
+                                    }),
+                            )
+                            .child(
+                                Button::new("open-keymap", "Open keymap.json")
+                                    .style(ButtonStyle::Filled)
+                                    .tooltip(|cx| Tooltip::text("Edit your key bindings", cx))
+                                    .on_click(cx.listener(|this, _, cx| this.open_keymap(cx))),
+                            ),
+                    ),
+            )
+            .child(self.render_binding_form(cx))
+            .when(self.keymap_conflicts.is_empty(), |this| {
+                this.child(
+                    Label::new("None of your key bindings conflict with the defaults.")
+                        .color(Color::Muted),
+                )
+            })
+            .children(
+                self.keymap_conflicts
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, conflict)| {
+                        let context = conflict.context.as_deref().unwrap_or("any context");
+                        let edited_conflict = conflict.clone();
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Icon::new(IconName::ExclamationTriangle)
+                                    .size(IconSize::Small)
+                                    .color(Color::Warning),
+                            )
+                            .child(Label::new(format!(
+                                "{} in {} is bound to {}, replacing {}",
+                                conflict.keystrokes,
+                                context,
+                                conflict.action,
+                                conflict.overridden_action
+                            )))
+                            .child(
+                                Button::new(("edit-binding", ix), "Edit")
+                                    .style(ButtonStyle::Subtle)
+                                    .on_click(cx.listener(move |this, _, cx| {
+                                        this.edit_binding(&edited_conflict, cx)
+                                    })),
+                            )
+                    }),
+            )
+    }
+}
+
+impl Render for SettingsEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let entries = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| self.render_entry(ix, entry, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("settings-editor")
+            .key_context("SettingsEditor")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .overflow_y_scroll()
+            .bg(cx.theme().colors().editor_background)
+            .child(Headline::new("Settings").size(HeadlineSize::XLarge))
+            .child(self.render_keymap(cx))
+            .child(
+                v_flex()
+                    .child(Headline::new("Preferences").size(HeadlineSize::Medium))
+                    .children(entries),
+            )
+    }
+}
+
+impl EventEmitter<ItemEvent> for SettingsEditor {}
+
+impl FocusableView for SettingsEditor {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for SettingsEditor {
+    type Event = ItemEvent;
+
+    fn tab_content(&self, _: Option<usize>, selected: bool, _: &WindowContext) -> AnyElement {
+        Label::new("Settings")
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("settings editor")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: WorkspaceId,
+        _: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        None
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(workspace::item::ItemEvent)) {
+        f(*event)
+    }
+}
+
+/// Lists every setting that has a default value of a type we can edit, along
+/// with the choices offered by the settings schema for string settings.
+fn setting_entries(cx: &AppContext) -> Vec<SettingEntry> {
+    let store = cx.global::<SettingsStore>();
+    let font_names = cx.text_system().all_font_names();
+    let schema = store.json_schema(
+        &SettingsJsonSchemaParams {
+            language_names: &[],
+            staff_mode: cx.is_staff(),
+            font_names: &font_names,
+        },
+        cx,
+    );
+
+    let mut entries = Vec::new();
+    collect_entries(
+        store.raw_default_settings(),
+        &mut Vec::new(),
+        &schema,
+        &mut entries,
+    );
+    entries
+}
+
+fn collect_entries(
+    value: &Value,
+    key_path: &mut Vec<String>,
+    schema: &Value,
+    entries: &mut Vec<SettingEntry>,
+) {
+    let kind = match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                key_path.push(key.clone());
+                collect_entries(value, key_path, schema, entries);
+                key_path.pop();
+            }
+            return;
+        }
+        Value::Bool(_) => SettingKind::Boolean,
+        Value::Number(_) => SettingKind::Number,
+        Value::String(_) => SettingKind::String {
+            options: string_options(schema, key_path),
+        },
+        Value::Null | Value::Array(_) => return,
+    };
+    entries.push(SettingEntry {
+        key_path: key_path.clone(),
+        kind,
+    });
+}
+
+/// Returns the values allowed by the schema for the string setting at the given key path.
+fn string_options(root: &Value, key_path: &[String]) -> Vec<String> {
+    let mut schema = resolve_schema(root, root);
+    for key in key_path {
+        match schema
+            .and_then(|schema| schema.get("properties"))
+            .and_then(|properties| properties.get(key))
+        {
+            Some(property) => schema = resolve_schema(root, property),
+            None => return Vec::new(),
+        }
+    }
+
+    let Some(schema) = schema else {
+        return Vec::new();
+    };
+    let mut options = Vec::new();
+    collect_enum_values(root, schema, &mut options);
+    options
+}
+
+fn collect_enum_values(root: &Value, schema: &Value, options: &mut Vec<String>) {
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        options.extend(values.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            for variant in variants {
+                if let Some(variant) = resolve_schema(root, variant) {
+                    collect_enum_values(root, variant, options);
+                }
+            }
+        }
+    }
+}
+
+/// Follows `$ref` pointers into the schema's definitions, including references
+/// that schemars wraps in a single-element `allOf`.
+fn resolve_schema<'a>(root: &'a Value, schema: &'a Value) -> Option<&'a Value> {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.strip_prefix("#/definitions/")?;
+        let definition = root.get("definitions")?.get(name)?;
+        return resolve_schema(root, definition);
+    }
+    if let Some([inner]) = schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+    {
+        return resolve_schema(root, inner);
+    }
+    Some(schema)
+}
+
+fn current_value(key_path: &[String], cx: &AppContext) -> Value {
+    let store = cx.global::<SettingsStore>();
+    let lookup = |mut value: &Value| {
+        for key in key_path {
+            value = value.get(key)?;
+        }
+        Some(value.clone())
+    };
+    lookup(store.raw_user_settings())
+        .or_else(|| lookup(store.raw_default_settings()))
+        .unwrap_or(Value::Null)
+}
+
+fn keymap_conflicts(content: &str) -> Result<Vec<KeymapConflict>> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let base = KeymapFile::parse(&settings::default_keymap())?;
+    Ok(KeymapFile::parse(content)?.conflicts(&base))
+}
+
+/// Checks a binding entered in the binding form, returning its keystrokes and its action as
+/// written in keymap.json. Actions with arguments are entered as JSON arrays, and `null`
+/// disables the keystrokes.
+fn parse_binding(keystrokes: &str, action: &str, cx: &AppContext) -> Result<(String, Value)> {
+    let keystrokes = keystrokes.split_whitespace().collect::<Vec<_>>();
+    if keystrokes.is_empty() {
+        return Err(anyhow!("Enter the keystrokes to bind"));
+    }
+    for keystroke in &keystrokes {
+        Keystroke::parse(keystroke).with_context(|| format!("Invalid keystroke {keystroke:?}"))?;
+    }
+
+    let action = action.trim();
+    let action = if action.is_empty() {
+        return Err(anyhow!("Enter the action to bind the keystrokes to"));
+    } else if action.starts_with('[') || action == "null" {
+        serde_json::from_str(action).context("Invalid action")?
+    } else {
+        Value::String(action.to_string())
+    };
+    match &action {
+        Value::String(name) => {
+            cx.build_action(name, None)?;
+        }
+        Value::Array(items) => match items.as_slice() {
+            [Value::String(name), data] => {
+                cx.build_action(name, Some(data.clone()))?;
+            }
+            _ => return Err(anyhow!("Expected an action name followed by its arguments")),
+        },
+        _ => {}
+    }
+
+    Ok((keystrokes.join(" "), action))
+}
+
+/// Parses a number typed for a number setting, which has to be a whole number if the
+/// setting's value is.
+fn parse_number(text: &str, is_integer: bool) -> Option<Value> {
+    let text = text.trim();
+    if is_integer {
+        text.parse::<i64>().ok().map(Value::from)
+    } else {
+        let number = text
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())?;
+        Some(Value::from(number))
+    }
+}
+
+fn text_field(editor: &View<Editor>, cx: &WindowContext) -> Div {
+    div()
+        .px_2()
+        .py_1()
+        .border_1()
+        .border_color(cx.theme().colors().border)
+        .rounded_md()
+        .child(editor.clone())
+}
+
+fn number_value(number: f64, is_integer: bool) -> Value {
+    if is_integer {
+        Value::from(number.round() as i64)
+    } else {
+        Value::from(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_string_options() {
+        let schema = json!({
+            "properties": {
+                "theme": { "type": "string", "enum": ["One Dark", "One Light"] },
+                "git": { "allOf": [{ "$ref": "#/definitions/GitSettings" }] }
+            },
+            "definitions": {
+                "GitSettings": {
+                    "properties": {
+                        "git_gutter": { "$ref": "#/definitions/GitGutterSetting" }
+                    }
+                },
+                "GitGutterSetting": {
+                    "oneOf": [
+                        { "type": "string", "enum": ["tracked_files"] },
+                        { "type": "string", "enum": ["hide"] }
+                    ]
+                }
+            }
+        });
+
+        let key_path = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            string_options(&schema, &key_path(&["theme"])),
+            ["One Dark", "One Light"]
+        );
+        assert_eq!(
+            string_options(&schema, &key_path(&["git", "git_gutter"])),
+            ["tracked_files", "hide"]
+        );
+        assert!(string_options(&schema, &key_path(&["missing"])).is_empty());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(" 42 ", true), Some(json!(42)));
+        assert_eq!(parse_number("1.5", true), None);
+        assert_eq!(parse_number("1.5", false), Some(json!(1.5)));
+        assert_eq!(parse_number("inf", false), None);
+        assert_eq!(parse_number("twelve", false), None);
+    }
+}
@@ -0,0 +1,742 @@
+//! Expansion of [Emmet](https://docs.emmet.io) abbreviations into snippets.
+//!
+//! Markup abbreviations such as `ul>li.item$*3` expand into nested elements, and
+//! stylesheet abbreviations such as `m10` expand into declarations. Every empty attribute
+//! value and element body becomes a tab stop.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Elements that have no closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+const HTML_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "mark",
+    "meta",
+    "nav",
+    "noscript",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "script",
+    "section",
+    "select",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "video",
+    "wbr",
+];
+
+/// Attributes added to elements that are rarely useful without them.
+const DEFAULT_ATTRIBUTES: &[(&str, &[(&str, &str)])] = &[
+    ("a", &[("href", "")]),
+    ("form", &[("action", "")]),
+    ("img", &[("src", ""), ("alt", "")]),
+    ("input", &[("type", "text")]),
+    ("label", &[("for", "")]),
+    ("link", &[("rel", "stylesheet"), ("href", "")]),
+];
+
+const CSS_PROPERTIES: &[(&str, &str)] = &[
+    ("ai", "align-items"),
+    ("b", "bottom"),
+    ("bd", "border"),
+    ("bdrs", "border-radius"),
+    ("bg", "background"),
+    ("bgc", "background-color"),
+    ("c", "color"),
+    ("cur", "cursor"),
+    ("d", "display"),
+    ("fl", "float"),
+    ("fw", "font-weight"),
+    ("fxd", "flex-direction"),
+    ("fz", "font-size"),
+    ("g", "gap"),
+    ("h", "height"),
+    ("jc", "justify-content"),
+    ("l", "left"),
+    ("lh", "line-height"),
+    ("m", "margin"),
+    ("mah", "max-height"),
+    ("maw", "max-width"),
+    ("mb", "margin-bottom"),
+    ("mih", "min-height"),
+    ("miw", "min-width"),
+    ("ml", "margin-left"),
+    ("mr", "margin-right"),
+    ("mt", "margin-top"),
+    ("op", "opacity"),
+    ("ov", "overflow"),
+    ("p", "padding"),
+    ("pb", "padding-bottom"),
+    ("pl", "padding-left"),
+    ("pos", "position"),
+    ("pr", "padding-right"),
+    ("pt", "padding-top"),
+    ("r", "right"),
+    ("t", "top"),
+    ("ta", "text-align"),
+    ("w", "width"),
+    ("z", "z-index"),
+];
+
+const CSS_KEYWORDS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "ai",
+        &[
+            ("c", "center"),
+            ("fe", "flex-end"),
+            ("fs", "flex-start"),
+            ("s", "stretch"),
+        ],
+    ),
+    (
+        "d",
+        &[
+            ("b", "block"),
+            ("f", "flex"),
+            ("g", "grid"),
+            ("i", "inline"),
+            ("ib", "inline-block"),
+            ("if", "inline-flex"),
+            ("n", "none"),
+        ],
+    ),
+    ("fl", &[("l", "left"), ("n", "none"), ("r", "right")]),
+    ("fw", &[("b", "bold"), ("n", "normal")]),
+    ("fxd", &[("c", "column"), ("r", "row")]),
+    (
+        "jc",
+        &[
+            ("c", "center"),
+            ("fe", "flex-end"),
+            ("fs", "flex-start"),
+            ("sa", "space-around"),
+            ("sb", "space-between"),
+        ],
+    ),
+    (
+        "ov",
+        &[
+            ("a", "auto"),
+            ("h", "hidden"),
+            ("s", "scroll"),
+            ("v", "visible"),
+        ],
+    ),
+    (
+        "pos",
+        &[
+            ("a", "absolute"),
+            ("f", "fixed"),
+            ("r", "relative"),
+            ("s", "static"),
+            ("st", "sticky"),
+        ],
+    ),
+    (
+        "ta",
+        &[
+            ("c", "center"),
+            ("j", "justify"),
+            ("l", "left"),
+            ("r", "right"),
+        ],
+    ),
+];
+
+/// Properties whose numeric values don't take a unit.
+const UNITLESS_CSS_PROPERTIES: &[&str] = &["font-weight", "line-height", "opacity", "z-index"];
+
+/// Properties that only take keywords, and so can't be abbreviated with a number.
+const KEYWORD_CSS_PROPERTIES: &[&str] = &[
+    "align-items",
+    "cursor",
+    "display",
+    "flex-direction",
+    "float",
+    "justify-content",
+    "overflow",
+    "position",
+    "text-align",
+];
+
+/// Returns the markup abbreviation that ends `line_prefix`, the text of a line before
+/// the cursor.
+pub fn markup_abbreviation_before(line_prefix: &str) -> Option<&str> {
+    let mut bracket_depth = 0;
+    let mut start = 0;
+    for (ix, c) in line_prefix.char_indices().rev() {
+        match c {
+            ']' | '}' => bracket_depth += 1,
+            '[' | '{' => bracket_depth -= 1,
+            '<' if bracket_depth == 0 => {
+                // The cursor follows a tag, so the abbreviation starts where the tag ends.
+                let tag_end = line_prefix[ix..].find('>')?;
+                start = ix + tag_end + 1;
+                break;
+            }
+            c if c.is_whitespace() && bracket_depth == 0 => {
+                // Don't expand attribute names within a tag.
+                if line_prefix[..ix].rfind('<') > line_prefix[..ix].rfind('>') {
+                    return None;
+                }
+                start = ix + c.len_utf8();
+                break;
+            }
+            _ => {}
+        }
+    }
+    let abbreviation = &line_prefix[start..];
+    if abbreviation.is_empty() || bracket_depth != 0 {
+        None
+    } else {
+        Some(abbreviation)
+    }
+}
+
+/// Returns the stylesheet abbreviation that ends `line_prefix`, the text of a line before
+/// the cursor.
+pub fn stylesheet_abbreviation_before(line_prefix: &str) -> Option<&str> {
+    let start = line_prefix
+        .rfind(|c: char| c.is_whitespace() || c == '{' || c == ';')
+        .map_or(0, |ix| ix + 1);
+    let abbreviation = &line_prefix[start..];
+    if abbreviation.is_empty() {
+        None
+    } else {
+        Some(abbreviation)
+    }
+}
+
+/// Expands a markup abbreviation into snippet text, with nested elements indented by
+/// `indent`. Returns `None` if the abbreviation isn't valid, or names elements that
+/// aren't HTML elements, custom elements or (in JSX) components.
+pub fn expand_markup(abbreviation: &str, indent: &str, jsx: bool) -> Option<String> {
+    let mut parser = Parser {
+        chars: abbreviation.chars().peekable(),
+    };
+    let (nodes, _) = parser.parse_siblings()?;
+    if parser.chars.next().is_some() || !nodes.iter().all(|node| node.is_valid(jsx)) {
+        return None;
+    }
+
+    let mut renderer = Renderer {
+        indent,
+        jsx,
+        output: String::new(),
+        next_tabstop: 1,
+    };
+    renderer.render(&nodes, 0, None, None);
+    Some(renderer.output)
+}
+
+/// Expands a stylesheet abbreviation, such as `m10` or `d:f`, into snippet text.
+pub fn expand_stylesheet(abbreviation: &str) -> Option<String> {
+    if let Some((name, keyword)) = abbreviation.split_once(':') {
+        let property = css_property(name)?;
+        let (_, keywords) = CSS_KEYWORDS.iter().find(|(n, _)| *n == name)?;
+        let (_, keyword) = keywords.iter().find(|(k, _)| *k == keyword)?;
+        return Some(format!("{property}: {keyword};"));
+    }
+
+    let name_len = abbreviation
+        .find(|c: char| !c.is_ascii_lowercase())
+        .unwrap_or(abbreviation.len());
+    let (name, value) = abbreviation.split_at(name_len);
+    let property = css_property(name)?;
+    if value.is_empty() {
+        return Some(format!("{property}: ${{1}};"));
+    }
+    if value.starts_with('#') {
+        return Some(format!("{property}: {value};"));
+    }
+    if KEYWORD_CSS_PROPERTIES.contains(&property) {
+        return None;
+    }
+
+    let number_len = value
+        .char_indices()
+        .find(|(ix, c)| !(c.is_ascii_digit() || *c == '.' || (*ix == 0 && *c == '-')))
+        .map_or(value.len(), |(ix, _)| ix);
+    let (number, unit) = value.split_at(number_len);
+    number.parse::<f64>().ok()?;
+    let unit = match unit {
+        "" if UNITLESS_CSS_PROPERTIES.contains(&property) || number == "0" => "",
+        "" => "px",
+        "p" => "%",
+        "e" => "em",
+        "r" => "rem",
+        "x" => "ex",
+        "px" | "em" | "rem" | "ex" | "%" | "vh" | "vw" => unit,
+        _ => return None,
+    };
+    Some(format!("{property}: {number}{unit};"))
+}
+
+fn css_property(name: &str) -> Option<&'static str> {
+    CSS_PROPERTIES
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == name)
+        .map(|(_, property)| *property)
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    /// The element's name, or `None` for a group or an element whose name is implied by
+    /// its parent.
+    name: Option<String>,
+    is_group: bool,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    repeat: usize,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn is_valid(&self, jsx: bool) -> bool {
+        let is_valid_name = self.name.as_deref().map_or(true, |name| {
+            HTML_ELEMENTS.contains(&name)
+                || name.contains('-')
+                || (jsx && name.starts_with(|c: char| c.is_ascii_uppercase()))
+        });
+        is_valid_name && self.children.iter().all(|child| child.is_valid(jsx))
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses a list of siblings, returning them along with the number of levels that the
+    /// list was ended by climbing up with `^`.
+    fn parse_siblings(&mut self) -> Option<(Vec<Node>, usize)> {
+        let mut nodes = Vec::new();
+        loop {
+            let mut node = self.parse_node()?;
+            match self.chars.peek() {
+                Some('>') => {
+                    self.chars.next();
+                    if node.is_group {
+                        return None;
+                    }
+                    let (children, climb) = self.parse_siblings()?;
+                    node.children = children;
+                    nodes.push(node);
+                    if climb > 1 {
+                        return Some((nodes, climb - 1));
+                    } else if climb == 0 {
+                        return Some((nodes, 0));
+                    }
+                }
+                Some('^') => {
+                    let mut climb = 0;
+                    while self.chars.next_if_eq(&'^').is_some() {
+                        climb += 1;
+                    }
+                    nodes.push(node);
+                    return Some((nodes, climb));
+                }
+                Some('+') => {
+                    self.chars.next();
+                    nodes.push(node);
+                }
+                Some(')') | None => {
+                    nodes.push(node);
+                    return Some((nodes, 0));
+                }
+                Some(_) => return None,
+            }
+        }
+    }
+
+    fn parse_node(&mut self) -> Option<Node> {
+        let mut node = Node::default();
+        if self.chars.next_if_eq(&'(').is_some() {
+            node.is_group = true;
+            (node.children, _) = self.parse_siblings()?;
+            self.chars.next_if_eq(&')')?;
+        } else {
+            let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '-' || c == ':');
+            if !name.is_empty() {
+                node.name = Some(name);
+            }
+            loop {
+                match self.chars.peek() {
+                    Some('#') => {
+                        self.chars.next();
+                        node.id = Some(self.parse_identifier()?);
+                    }
+                    Some('.') => {
+                        self.chars.next();
+                        node.classes.push(self.parse_identifier()?);
+                    }
+                    Some('[') => {
+                        self.chars.next();
+                        self.parse_attributes(&mut node.attributes)?;
+                    }
+                    Some('{') => {
+                        self.chars.next();
+                        node.text = Some(self.take_while(|c| c != '}'));
+                        self.chars.next_if_eq(&'}')?;
+                    }
+                    _ => break,
+                }
+            }
+            if node.name.is_none()
+                && node.id.is_none()
+                && node.classes.is_empty()
+                && node.attributes.is_empty()
+            {
+                return None;
+            }
+        }
+
+        node.repeat = 1;
+        if self.chars.next_if_eq(&'*').is_some() {
+            node.repeat = self.take_while(|c| c.is_ascii_digit()).parse().ok()?;
+        }
+        Some(node)
+    }
+
+    fn parse_identifier(&mut self) -> Option<String> {
+        let identifier = self.take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '$'));
+        if identifier.is_empty() {
+            None
+        } else {
+            Some(identifier)
+        }
+    }
+
+    fn parse_attributes(&mut self, attributes: &mut Vec<(String, String)>) -> Option<()> {
+        loop {
+            self.take_while(|c| c == ' ');
+            if self.chars.next_if_eq(&']').is_some() {
+                return Some(());
+            }
+            let name = self.take_while(|c| !matches!(c, ' ' | '=' | ']'));
+            if name.is_empty() {
+                return None;
+            }
+            let mut value = String::new();
+            if self.chars.next_if_eq(&'=').is_some() {
+                value = match self.chars.next_if(|c| *c == '"' || *c == '\'') {
+                    Some(quote) => {
+                        let value = self.take_while(|c| c != quote);
+                        self.chars.next_if_eq(&quote)?;
+                        value
+                    }
+                    None => self.take_while(|c| !matches!(c, ' ' | ']')),
+                };
+            }
+            attributes.push((name, value));
+        }
+    }
+
+    fn take_while(&mut self, mut predicate: impl FnMut(char) -> bool) -> String {
+        let mut result = String::new();
+        while let Some(c) = self.chars.next_if(|c| predicate(*c)) {
+            result.push(c);
+        }
+        result
+    }
+}
+
+struct Renderer<'a> {
+    indent: &'a str,
+    jsx: bool,
+    output: String,
+    next_tabstop: usize,
+}
+
+impl<'a> Renderer<'a> {
+    fn render(
+        &mut self,
+        nodes: &[Node],
+        depth: usize,
+        parent_name: Option<&str>,
+        number: Option<usize>,
+    ) {
+        for node in nodes {
+            for index in 1..=node.repeat {
+                let number = if node.repeat > 1 { Some(index) } else { number };
+                if node.is_group {
+                    self.render(&node.children, depth, parent_name, number);
+                } else {
+                    self.render_element(node, depth, parent_name, number);
+                }
+            }
+        }
+    }
+
+    fn render_element(
+        &mut self,
+        node: &Node,
+        depth: usize,
+        parent_name: Option<&str>,
+        number: Option<usize>,
+    ) {
+        let name = node
+            .name
+            .as_deref()
+            .unwrap_or_else(|| implicit_element_name(parent_name));
+
+        let mut attributes = Vec::new();
+        if let Some(id) = &node.id {
+            attributes.push(("id", numbered(id, number)));
+        }
+        if !node.classes.is_empty() {
+            let classes = node
+                .classes
+                .iter()
+                .map(|class| numbered(class, number))
+                .collect::<Vec<_>>();
+            let class_attribute = if self.jsx { "className" } else { "class" };
+            attributes.push((class_attribute, classes.join(" ")));
+        }
+        if let Some((_, defaults)) = DEFAULT_ATTRIBUTES.iter().find(|(n, _)| *n == name) {
+            for (attribute, value) in defaults.iter() {
+                if !node.attributes.iter().any(|(a, _)| a == *attribute) {
+                    let attribute = match *attribute {
+                        "for" if self.jsx => "htmlFor",
+                        attribute => attribute,
+                    };
+                    attributes.push((attribute, value.to_string()));
+                }
+            }
+        }
+        for (attribute, value) in &node.attributes {
+            attributes.push((attribute.as_str(), numbered(value, number)));
+        }
+
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output.push_str(&self.indent.repeat(depth));
+        self.output.push('<');
+        self.output.push_str(name);
+        for (attribute, value) in attributes {
+            self.output.push_str(&format!(" {attribute}=\""));
+            if value.is_empty() {
+                self.push_tabstop();
+            } else {
+                self.output.push_str(&escape(&value));
+            }
+            self.output.push('"');
+        }
+
+        if VOID_ELEMENTS.contains(&name) {
+            self.output.push_str(if self.jsx { " />" } else { ">" });
+            return;
+        }
+        self.output.push('>');
+        if node.children.is_empty() {
+            match &node.text {
+                Some(text) => self.output.push_str(&escape(&numbered(text, number))),
+                None => self.push_tabstop(),
+            }
+        } else {
+            if let Some(text) = &node.text {
+                self.output.push('\n');
+                self.output.push_str(&self.indent.repeat(depth + 1));
+                self.output.push_str(&escape(&numbered(text, number)));
+            }
+            self.render(&node.children, depth + 1, Some(name), number);
+            self.output.push('\n');
+            self.output.push_str(&self.indent.repeat(depth));
+        }
+        self.output.push_str(&format!("</{name}>"));
+    }
+
+    fn push_tabstop(&mut self) {
+        self.output.push_str(&format!("${{{}}}", self.next_tabstop));
+        self.next_tabstop += 1;
+    }
+}
+
+fn implicit_element_name(parent_name: Option<&str>) -> &'static str {
+    match parent_name {
+        Some("ul" | "ol") => "li",
+        Some("table" | "tbody" | "thead" | "tfoot") => "tr",
+        Some("tr") => "td",
+        Some("select" | "optgroup") => "option",
+        _ => "div",
+    }
+}
+
+/// Replaces each run of `$` in `text` with the number of the repeated element, padded
+/// with zeros to the length of the run.
+fn numbered(text: &str, number: Option<usize>) -> String {
+    let Some(number) = number else {
+        return text.to_string();
+    };
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            let mut width = 1;
+            while chars.next_if_eq(&'$').is_some() {
+                width += 1;
+            }
+            result.push_str(&format!("{number:0width$}"));
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Escapes the characters that have a meaning in snippet syntax.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '$' | '}' | '\\') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markup_abbreviation_before() {
+        assert_eq!(markup_abbreviation_before("    ul>li*3"), Some("ul>li*3"));
+        assert_eq!(
+            markup_abbreviation_before("<div>a[title=\"x y\"]"),
+            Some("a[title=\"x y\"]")
+        );
+        assert_eq!(markup_abbreviation_before("p{one two}"), Some("p{one two}"));
+        assert_eq!(markup_abbreviation_before("hello "), None);
+        assert_eq!(markup_abbreviation_before("<a href"), None);
+    }
+
+    #[test]
+    fn test_expand_markup() {
+        assert_eq!(
+            expand_markup("ul>li.item$*3", "  ", false).unwrap(),
+            "<ul>\n  <li class=\"item1\">${1}</li>\n  <li class=\"item2\">${2}</li>\n  <li class=\"item3\">${3}</li>\n</ul>"
+        );
+        assert_eq!(
+            expand_markup("div#main>p{Hi}+img^footer", "\t", false).unwrap(),
+            "<div id=\"main\">\n\t<p>Hi</p>\n\t<img src=\"${1}\" alt=\"${2}\">\n</div>\n<footer>${3}</footer>"
+        );
+        assert_eq!(
+            expand_markup("(dt+dd)*2", "  ", false).unwrap(),
+            "<dt>${1}</dt>\n<dd>${2}</dd>\n<dt>${3}</dt>\n<dd>${4}</dd>"
+        );
+        assert_eq!(
+            expand_markup(".card>label+Button[disabled]", "  ", true).unwrap(),
+            "<div className=\"card\">\n  <label htmlFor=\"${1}\">${2}</label>\n  <Button disabled=\"${3}\">${4}</Button>\n</div>"
+        );
+        assert_eq!(
+            expand_markup("p{costs $5}", "  ", false).unwrap(),
+            "<p>costs \\$5</p>"
+        );
+
+        assert_eq!(expand_markup("hello", "  ", false), None);
+        assert_eq!(expand_markup("Button", "  ", false), None);
+        assert_eq!(expand_markup("ul>", "  ", false), None);
+    }
+
+    #[test]
+    fn test_expand_stylesheet() {
+        assert_eq!(expand_stylesheet("m10").unwrap(), "margin: 10px;");
+        assert_eq!(expand_stylesheet("w50p").unwrap(), "width: 50%;");
+        assert_eq!(expand_stylesheet("mt-1.5e").unwrap(), "margin-top: -1.5em;");
+        assert_eq!(expand_stylesheet("z10").unwrap(), "z-index: 10;");
+        assert_eq!(expand_stylesheet("c#f00").unwrap(), "color: #f00;");
+        assert_eq!(expand_stylesheet("d:f").unwrap(), "display: flex;");
+        assert_eq!(expand_stylesheet("pos").unwrap(), "position: ${1};");
+
+        assert_eq!(expand_stylesheet("d10"), None);
+        assert_eq!(expand_stylesheet("margin"), None);
+        assert_eq!(expand_stylesheet("m10q"), None);
+    }
+}
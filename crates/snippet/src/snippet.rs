@@ -1,3 +1,5 @@
+pub mod emmet;
+
 use anyhow::{anyhow, Context, Result};
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
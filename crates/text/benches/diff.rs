@@ -0,0 +1,77 @@
+//! Measures how long diffing takes on large files. Run with
+//! `cargo bench -p text --bench diff`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::prelude::*;
+use text::diff::{line_diff_with_algorithm, text_diff, DiffAlgorithm};
+
+const LINE_COUNT: usize = 100_000;
+
+fn bench_diff(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let old = random_file(&mut rng, LINE_COUNT);
+    let few_edits = edit_lines(&mut rng, &old, 0.001);
+    let many_edits = edit_lines(&mut rng, &old, 0.1);
+    let rewrite = random_file(&mut rng, LINE_COUNT);
+
+    for (name, new) in [
+        ("0.1% of lines edited", &few_edits),
+        ("10% of lines edited", &many_edits),
+        ("rewritten", &rewrite),
+    ] {
+        let mut group = c.benchmark_group(format!("diff 100k lines, {name}"));
+        // Each sample diffs the whole file, so the default of 100 samples
+        // would make a single run take minutes.
+        group.sample_size(10);
+        for algorithm in [DiffAlgorithm::Auto, DiffAlgorithm::Histogram] {
+            group.bench_function(
+                BenchmarkId::new("line_diff", format!("{algorithm:?}")),
+                |b| b.iter(|| black_box(line_diff_with_algorithm(&old, new, algorithm))),
+            );
+        }
+        group.bench_function("text_diff", |b| b.iter(|| black_box(text_diff(&old, new))));
+        group.finish();
+    }
+}
+
+fn random_file(rng: &mut StdRng, line_count: usize) -> String {
+    (0..line_count).map(|_| random_line(rng)).collect()
+}
+
+/// Code has many repeated lines, like closing braces and blank lines, and
+/// many unique ones.
+fn random_line(rng: &mut StdRng) -> String {
+    let indent = " ".repeat(4 * rng.gen_range(0..4));
+    match rng.gen_range(0..10) {
+        0 => "\n".to_string(),
+        1 => format!("{indent}}}\n"),
+        _ => format!(
+            "{indent}let value_{} = compute({}, {});\n",
+            rng.gen::<u32>(),
+            rng.gen_range(0..100),
+            rng.gen_range(0..100)
+        ),
+    }
+}
+
+fn edit_lines(rng: &mut StdRng, text: &str, probability: f64) -> String {
+    let mut edited = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        if rng.gen_bool(probability) {
+            match rng.gen_range(0..3) {
+                0 => {}
+                1 => edited.push_str(&random_line(rng)),
+                _ => {
+                    edited.push_str(line);
+                    edited.push_str(&random_line(rng));
+                }
+            }
+        } else {
+            edited.push_str(line);
+        }
+    }
+    edited
+}
+
+criterion_group!(benches, bench_diff);
+criterion_main!(benches);
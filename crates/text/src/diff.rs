@@ -0,0 +1,617 @@
+//! Line- and word-level diffs between two strings.
+//!
+//! Diffs are computed with Myers' algorithm, which finds a minimal diff but
+//! slows down quadratically as the number of differences grows. When two
+//! inputs differ too much, the diff falls back to the histogram algorithm
+//! used by git, which runs in close to linear time and still produces
+//! readable diffs by anchoring on lines that occur rarely in both inputs.
+
+use collections::HashMap;
+use std::{hash::Hash, mem, ops::Range};
+
+/// The number of differences after which [`DiffAlgorithm::Auto`] stops
+/// running Myers' algorithm and switches to the histogram algorithm.
+const MAX_MYERS_COST: usize = 1024;
+
+/// Tokens that occur more often than this aren't used as anchors by the
+/// histogram algorithm.
+const MAX_HISTOGRAM_OCCURRENCES: usize = 64;
+
+/// Modified line hunks larger than this many bytes aren't refined into
+/// word-level edits by [`text_diff`].
+const MAX_WORD_DIFF_LEN: usize = 8 * 1024;
+
+/// Modified words larger than this many bytes aren't refined into
+/// character-level edits by [`text_diff`].
+const MAX_CHAR_DIFF_LEN: usize = 256;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    /// Myers' algorithm, falling back to the histogram algorithm for inputs
+    /// with many differences.
+    #[default]
+    Auto,
+    /// Myers' algorithm, regardless of how long it takes.
+    Myers,
+    /// The histogram algorithm.
+    Histogram,
+}
+
+/// A range of tokens in the old input that was replaced by a range of tokens
+/// in the new input. One of the ranges may be empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+/// A range of lines in the old text that was replaced by a range of lines in
+/// the new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineDiffHunk {
+    pub old_rows: Range<u32>,
+    pub new_rows: Range<u32>,
+    pub old_bytes: Range<usize>,
+    pub new_bytes: Range<usize>,
+}
+
+/// Returns the ranges of lines that differ between `old` and `new`.
+pub fn line_diff(old: &str, new: &str) -> Vec<LineDiffHunk> {
+    line_diff_with_algorithm(old, new, DiffAlgorithm::Auto)
+}
+
+pub fn line_diff_with_algorithm(
+    old: &str,
+    new: &str,
+    algorithm: DiffAlgorithm,
+) -> Vec<LineDiffHunk> {
+    let old_lines = tokenize_lines(old);
+    let new_lines = tokenize_lines(new);
+    let old_tokens = old_lines.iter().map(|range| &old[range.clone()]);
+    let new_tokens = new_lines.iter().map(|range| &new[range.clone()]);
+    let (old_ids, new_ids) = intern(old_tokens, new_tokens);
+    diff(&old_ids, &new_ids, algorithm)
+        .into_iter()
+        .map(|hunk| LineDiffHunk {
+            old_bytes: byte_range(&old_lines, &hunk.old, old.len()),
+            new_bytes: byte_range(&new_lines, &hunk.new, new.len()),
+            old_rows: hunk.old.start as u32..hunk.old.end as u32,
+            new_rows: hunk.new.start as u32..hunk.new.end as u32,
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of words that differ between `old` and `new`.
+///
+/// Words are runs of alphanumeric characters, runs of whitespace, and
+/// individual punctuation characters.
+pub fn word_diff(old: &str, new: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    diff_by(old, new, tokenize_words)
+}
+
+/// Returns the edits that turn `old` into `new`, as pairs of a byte range in
+/// `old` and the byte range in `new` that replaces it.
+///
+/// The texts are diffed line by line, modified lines are then diffed word by
+/// word, and short modified words character by character, so that the edits
+/// touch as little of the unchanged text as possible.
+pub fn text_diff(old: &str, new: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut edits = Vec::new();
+    for hunk in line_diff(old, new) {
+        if !is_refinable(&hunk.old_bytes, &hunk.new_bytes, MAX_WORD_DIFF_LEN) {
+            edits.push((hunk.old_bytes, hunk.new_bytes));
+            continue;
+        }
+
+        let old_hunk = &old[hunk.old_bytes.clone()];
+        let new_hunk = &new[hunk.new_bytes.clone()];
+        for (old_range, new_range) in word_diff(old_hunk, new_hunk) {
+            let old_range = offset_range(old_range, hunk.old_bytes.start);
+            let new_range = offset_range(new_range, hunk.new_bytes.start);
+            if !is_refinable(&old_range, &new_range, MAX_CHAR_DIFF_LEN) {
+                edits.push((old_range, new_range));
+                continue;
+            }
+
+            let old_word = &old[old_range.clone()];
+            let new_word = &new[new_range.clone()];
+            edits.extend(diff_by(old_word, new_word, tokenize_chars).into_iter().map(
+                |(old_char_range, new_char_range)| {
+                    (
+                        offset_range(old_char_range, old_range.start),
+                        offset_range(new_char_range, new_range.start),
+                    )
+                },
+            ));
+        }
+    }
+    edits
+}
+
+/// Whether a modification is small enough to be diffed with finer tokens.
+fn is_refinable(old: &Range<usize>, new: &Range<usize>, max_len: usize) -> bool {
+    !old.is_empty() && !new.is_empty() && old.len() + new.len() <= max_len
+}
+
+fn diff_by(
+    old: &str,
+    new: &str,
+    tokenize: fn(&str) -> Vec<Range<usize>>,
+) -> Vec<(Range<usize>, Range<usize>)> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let (old_ids, new_ids) = intern(
+        old_tokens.iter().map(|range| &old[range.clone()]),
+        new_tokens.iter().map(|range| &new[range.clone()]),
+    );
+    diff(&old_ids, &new_ids, DiffAlgorithm::Auto)
+        .into_iter()
+        .map(|hunk| {
+            (
+                byte_range(&old_tokens, &hunk.old, old.len()),
+                byte_range(&new_tokens, &hunk.new, new.len()),
+            )
+        })
+        .collect()
+}
+
+/// Returns the ranges of tokens that differ between `old` and `new`.
+pub fn diff<T: Eq + Hash>(old: &[T], new: &[T], algorithm: DiffAlgorithm) -> Vec<DiffHunk> {
+    let mut hunks = Vec::new();
+    match algorithm {
+        DiffAlgorithm::Auto => {
+            if !diff_myers(
+                old,
+                new,
+                0..old.len(),
+                0..new.len(),
+                MAX_MYERS_COST,
+                &mut hunks,
+            ) {
+                diff_histogram(old, new, &mut hunks);
+            }
+        }
+        DiffAlgorithm::Myers => {
+            diff_myers(old, new, 0..old.len(), 0..new.len(), usize::MAX, &mut hunks);
+        }
+        DiffAlgorithm::Histogram => diff_histogram(old, new, &mut hunks),
+    }
+    hunks.sort_unstable_by_key(|hunk| (hunk.old.start, hunk.new.start));
+    coalesce(hunks)
+}
+
+/// Diffs the given ranges with Myers' algorithm, appending the hunks it
+/// finds. Returns false without appending anything if the ranges differ in
+/// more than `max_cost` tokens.
+fn diff_myers<T: Eq>(
+    old: &[T],
+    new: &[T],
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+    max_cost: usize,
+    hunks: &mut Vec<DiffHunk>,
+) -> bool {
+    let (old_range, new_range) = trim_common_affixes(old, new, old_range, new_range);
+    if old_range.is_empty() || new_range.is_empty() {
+        if !old_range.is_empty() || !new_range.is_empty() {
+            hunks.push(DiffHunk {
+                old: old_range,
+                new: new_range,
+            });
+        }
+        return true;
+    }
+
+    let old = &old[old_range.clone()];
+    let new = &new[new_range.clone()];
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max_cost = max_cost.min(old.len() + new.len()) as isize;
+
+    // `furthest[k]` is the furthest index in `old` reached on diagonal
+    // `k - offset`, and `trace[d]` is a snapshot of it taken before trying
+    // `d` edits, used to recover the path afterwards.
+    let offset = max_cost + 1;
+    let mut furthest = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = Vec::new();
+    for cost in 0..=max_cost {
+        trace.push(furthest[(offset - cost) as usize..=(offset + cost) as usize].to_vec());
+        for diagonal in (-cost..=cost).step_by(2) {
+            let ix = (offset + diagonal) as usize;
+            let mut x =
+                if diagonal == -cost || (diagonal != cost && furthest[ix - 1] < furthest[ix + 1]) {
+                    furthest[ix + 1]
+                } else {
+                    furthest[ix - 1] + 1
+                };
+            let mut y = x - diagonal;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[ix] = x;
+
+            if x >= n && y >= m {
+                let start = hunks.len();
+                backtrack(&trace, n, m, old_range.start, new_range.start, hunks);
+                hunks[start..].reverse();
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn backtrack(
+    trace: &[Vec<isize>],
+    mut x: isize,
+    mut y: isize,
+    old_start: usize,
+    new_start: usize,
+    hunks: &mut Vec<DiffHunk>,
+) {
+    for cost in (1..trace.len() as isize).rev() {
+        let furthest = &trace[cost as usize];
+        let reached = |diagonal: isize| furthest[(diagonal + cost) as usize];
+        let diagonal = x - y;
+        let previous_diagonal = if diagonal == -cost
+            || (diagonal != cost && reached(diagonal - 1) < reached(diagonal + 1))
+        {
+            diagonal + 1
+        } else {
+            diagonal - 1
+        };
+        let previous_x = reached(previous_diagonal);
+        let previous_y = previous_x - previous_diagonal;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+        }
+
+        let old = old_start + previous_x as usize..old_start + x as usize;
+        let new = new_start + previous_y as usize..new_start + y as usize;
+        match hunks.last_mut() {
+            // Hunks are found from the end, so extend the last one backwards.
+            Some(last) if last.old.start == old.end && last.new.start == new.end => {
+                last.old.start = old.start;
+                last.new.start = new.start;
+            }
+            _ => hunks.push(DiffHunk { old, new }),
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+}
+
+/// Diffs the inputs with the histogram algorithm: each range is split
+/// around the longest run of tokens that starts with the least frequent
+/// token they have in common, and the pieces on either side are diffed in
+/// turn.
+fn diff_histogram<T: Eq + Hash>(old: &[T], new: &[T], hunks: &mut Vec<DiffHunk>) {
+    let mut stack = vec![(0..old.len(), 0..new.len())];
+    while let Some((old_range, new_range)) = stack.pop() {
+        let (old_range, new_range) = trim_common_affixes(old, new, old_range, new_range);
+        if old_range.is_empty() || new_range.is_empty() {
+            if !old_range.is_empty() || !new_range.is_empty() {
+                hunks.push(DiffHunk {
+                    old: old_range,
+                    new: new_range,
+                });
+            }
+            continue;
+        }
+
+        let Some((old_match, new_match)) =
+            longest_rare_match(old, new, old_range.clone(), new_range.clone())
+        else {
+            // There are no rare tokens to anchor on, so diff this range
+            // precisely if it's small enough, or replace it entirely.
+            let cost = old_range.len() + new_range.len();
+            if !diff_myers(
+                old,
+                new,
+                old_range.clone(),
+                new_range.clone(),
+                MAX_MYERS_COST.min(cost),
+                hunks,
+            ) {
+                hunks.push(DiffHunk {
+                    old: old_range,
+                    new: new_range,
+                });
+            }
+            continue;
+        };
+
+        stack.push((old_match.end..old_range.end, new_match.end..new_range.end));
+        stack.push((
+            old_range.start..old_match.start,
+            new_range.start..new_match.start,
+        ));
+    }
+}
+
+/// Finds the longest run of equal tokens in the given ranges that contains
+/// the token occurring the fewest times in `old_range`.
+fn longest_rare_match<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let mut occurrences = HashMap::<&T, Vec<usize>>::default();
+    for ix in old_range.clone() {
+        let positions = occurrences.entry(&old[ix]).or_default();
+        if positions.len() <= MAX_HISTOGRAM_OCCURRENCES {
+            positions.push(ix);
+        }
+    }
+
+    let mut best: Option<(usize, Range<usize>, Range<usize>)> = None;
+    let mut new_ix = new_range.start;
+    while new_ix < new_range.end {
+        let mut next_new_ix = new_ix + 1;
+        if let Some(positions) = occurrences.get(&new[new_ix]) {
+            let count = positions.len();
+            if count <= MAX_HISTOGRAM_OCCURRENCES
+                && best
+                    .as_ref()
+                    .map_or(true, |(best_count, _, _)| count <= *best_count)
+            {
+                for &old_ix in positions {
+                    let (mut old_start, mut new_start) = (old_ix, new_ix);
+                    while old_start > old_range.start
+                        && new_start > new_range.start
+                        && old[old_start - 1] == new[new_start - 1]
+                    {
+                        old_start -= 1;
+                        new_start -= 1;
+                    }
+                    let (mut old_end, mut new_end) = (old_ix + 1, new_ix + 1);
+                    while old_end < old_range.end
+                        && new_end < new_range.end
+                        && old[old_end] == new[new_end]
+                    {
+                        old_end += 1;
+                        new_end += 1;
+                    }
+
+                    next_new_ix = next_new_ix.max(new_end);
+                    let is_better = best.as_ref().map_or(true, |(best_count, best_old, _)| {
+                        count < *best_count || old_end - old_start > best_old.len()
+                    });
+                    if is_better {
+                        best = Some((count, old_start..old_end, new_start..new_end));
+                    }
+                }
+            }
+        }
+        new_ix = next_new_ix;
+    }
+    best.map(|(_, old, new)| (old, new))
+}
+
+fn trim_common_affixes<T: Eq>(
+    old: &[T],
+    new: &[T],
+    mut old_range: Range<usize>,
+    mut new_range: Range<usize>,
+) -> (Range<usize>, Range<usize>) {
+    while !old_range.is_empty()
+        && !new_range.is_empty()
+        && old[old_range.start] == new[new_range.start]
+    {
+        old_range.start += 1;
+        new_range.start += 1;
+    }
+    while !old_range.is_empty()
+        && !new_range.is_empty()
+        && old[old_range.end - 1] == new[new_range.end - 1]
+    {
+        old_range.end -= 1;
+        new_range.end -= 1;
+    }
+    (old_range, new_range)
+}
+
+/// Merges hunks that touch each other. Expects them to be sorted.
+fn coalesce(hunks: Vec<DiffHunk>) -> Vec<DiffHunk> {
+    let mut coalesced: Vec<DiffHunk> = Vec::with_capacity(hunks.len());
+    for hunk in hunks {
+        match coalesced.last_mut() {
+            Some(last) if last.old.end == hunk.old.start && last.new.end == hunk.new.start => {
+                last.old.end = hunk.old.end;
+                last.new.end = hunk.new.end;
+            }
+            _ => coalesced.push(hunk),
+        }
+    }
+    coalesced
+}
+
+/// Maps tokens to integers, so that they can be compared cheaply.
+fn intern<'a, T: 'a + Eq + Hash + ?Sized>(
+    old: impl Iterator<Item = &'a T>,
+    new: impl Iterator<Item = &'a T>,
+) -> (Vec<u32>, Vec<u32>) {
+    let mut ids = HashMap::<&T, u32>::default();
+    let mut intern = |token| {
+        let next_id = ids.len() as u32;
+        *ids.entry(token).or_insert(next_id)
+    };
+    let old = old.map(&mut intern).collect();
+    let new = new.map(&mut intern).collect();
+    (old, new)
+}
+
+/// Returns the byte range of each line in `text`, including its newline.
+fn tokenize_lines(text: &str) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        lines.push(start..start + line.len());
+        start += line.len();
+    }
+    lines
+}
+
+fn tokenize_words(text: &str) -> Vec<Range<usize>> {
+    #[derive(PartialEq)]
+    enum CharKind {
+        Word,
+        Whitespace,
+        Punctuation,
+    }
+
+    fn char_kind(c: char) -> CharKind {
+        if c.is_alphanumeric() || c == '_' {
+            CharKind::Word
+        } else if c.is_whitespace() {
+            CharKind::Whitespace
+        } else {
+            CharKind::Punctuation
+        }
+    }
+
+    let mut words = Vec::new();
+    let mut current: Option<(usize, CharKind)> = None;
+    for (ix, c) in text.char_indices() {
+        let kind = char_kind(c);
+        let continues_word = matches!(
+            &current,
+            Some((_, current_kind)) if *current_kind == kind && kind != CharKind::Punctuation
+        );
+        if !continues_word {
+            if let Some((start, _)) = mem::replace(&mut current, Some((ix, kind))) {
+                words.push(start..ix);
+            }
+        }
+    }
+    if let Some((start, _)) = current {
+        words.push(start..text.len());
+    }
+    words
+}
+
+fn tokenize_chars(text: &str) -> Vec<Range<usize>> {
+    text.char_indices()
+        .map(|(ix, c)| ix..ix + c.len_utf8())
+        .collect()
+}
+
+/// Converts a range of tokens into the range of bytes they cover.
+fn byte_range(tokens: &[Range<usize>], range: &Range<usize>, text_len: usize) -> Range<usize> {
+    let start = tokens
+        .get(range.start)
+        .map_or(text_len, |token| token.start);
+    if range.is_empty() {
+        start..start
+    } else {
+        start..tokens[range.end - 1].end
+    }
+}
+
+fn offset_range(range: Range<usize>, offset: usize) -> Range<usize> {
+    range.start + offset..range.end + offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    fn apply(old: &str, new: &str, edits: &[(Range<usize>, Range<usize>)]) -> String {
+        let mut text = old.to_string();
+        for (old_range, new_range) in edits.iter().rev() {
+            text.replace_range(old_range.clone(), &new[new_range.clone()]);
+        }
+        text
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let old = "one\ntwo\nthree\nfour\n";
+        let new = "one\nTWO\nthree\nfour\nfive\n";
+        assert_eq!(
+            line_diff(old, new),
+            [
+                LineDiffHunk {
+                    old_rows: 1..2,
+                    new_rows: 1..2,
+                    old_bytes: 4..8,
+                    new_bytes: 4..8,
+                },
+                LineDiffHunk {
+                    old_rows: 4..4,
+                    new_rows: 4..5,
+                    old_bytes: 19..19,
+                    new_bytes: 19..24,
+                },
+            ]
+        );
+        assert_eq!(line_diff(old, old), []);
+    }
+
+    #[test]
+    fn test_text_diff() {
+        let old = "fn main() {\n    println!(\"hello\");\n}\n";
+        let new = "fn main() {\n    eprintln!(\"hello, world\");\n}\n";
+        let edits = text_diff(old, new);
+        assert_eq!(
+            edits
+                .iter()
+                .map(|(old_range, new_range)| (&old[old_range.clone()], &new[new_range.clone()]))
+                .collect::<Vec<_>>(),
+            [("", "e"), ("", ", world")]
+        );
+        assert_eq!(apply(old, new, &edits), new);
+    }
+
+    #[test]
+    fn test_algorithms_agree_on_minimal_diffs() {
+        let old = [1, 2, 3, 4, 5, 6];
+        let new = [1, 3, 4, 7, 5, 6, 8];
+        let expected = [
+            DiffHunk {
+                old: 1..2,
+                new: 1..1,
+            },
+            DiffHunk {
+                old: 4..4,
+                new: 3..4,
+            },
+            DiffHunk {
+                old: 6..6,
+                new: 6..7,
+            },
+        ];
+        for algorithm in [
+            DiffAlgorithm::Auto,
+            DiffAlgorithm::Myers,
+            DiffAlgorithm::Histogram,
+        ] {
+            assert_eq!(diff(&old, &new, algorithm), expected, "{algorithm:?}");
+        }
+    }
+
+    #[gpui::test(iterations = 100)]
+    fn test_random_diffs(mut rng: StdRng) {
+        let words = ["a", "b", "c", "\n", " ", "fn", "{", "}"];
+        let mut random_text = |len| {
+            (0..len)
+                .map(|_| *words.choose(&mut rng).unwrap())
+                .collect::<String>()
+        };
+        let old = random_text(200);
+        let new = random_text(200);
+
+        assert_eq!(apply(&old, &new, &text_diff(&old, &new)), new);
+        for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Histogram] {
+            let hunks = line_diff_with_algorithm(&old, &new, algorithm);
+            let edits = hunks
+                .into_iter()
+                .map(|hunk| (hunk.old_bytes, hunk.new_bytes))
+                .collect::<Vec<_>>();
+            assert_eq!(apply(&old, &new, &edits), new, "{algorithm:?}");
+        }
+    }
+}
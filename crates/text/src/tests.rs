@@ -524,6 +524,30 @@ fn test_undo_redo() {
     assert_eq!(buffer.text(), "1234");
 }
 
+#[test]
+fn test_has_later_overlapping_transactions() {
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "one two three".into());
+    buffer.set_group_interval(Duration::from_secs(0));
+
+    buffer.edit([(0..3, "ONE")]);
+    buffer.edit([(8..13, "THREE")]);
+    buffer.edit([(9..10, "h")]);
+    assert_eq!(buffer.text(), "ONE two ThREE");
+
+    let transaction_ids = buffer
+        .undo_history()
+        .iter()
+        .map(|entry| entry.transaction_id())
+        .collect::<Vec<_>>();
+    assert!(!buffer.has_later_overlapping_transactions(transaction_ids[0]));
+    assert!(buffer.has_later_overlapping_transactions(transaction_ids[1]));
+    assert!(!buffer.has_later_overlapping_transactions(transaction_ids[2]));
+
+    buffer.undo_transaction(transaction_ids[0]);
+    assert_eq!(buffer.text(), "one two ThREE");
+    assert_eq!(buffer.undo_history().len(), 2);
+}
+
 #[test]
 fn test_history() {
     let mut now = Instant::now();
@@ -1,4 +1,5 @@
 mod anchor;
+pub mod diff;
 pub mod locator;
 #[cfg(any(test, feature = "test-support"))]
 pub mod network;
@@ -123,6 +124,14 @@ impl HistoryEntry {
     pub fn transaction_id(&self) -> TransactionId {
         self.transaction.id
     }
+
+    pub fn transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+
+    pub fn last_edit_at(&self) -> Instant {
+        self.last_edit_at
+    }
 }
 
 struct History {
@@ -1162,6 +1171,34 @@ impl Buffer {
         self.history.redo_stack.last()
     }
 
+    /// The transactions that can be undone, from oldest to newest.
+    pub fn undo_history(&self) -> &[HistoryEntry] {
+        &self.history.undo_stack
+    }
+
+    /// Whether a transaction that was undone out of order would touch text that later
+    /// transactions in the undo history also edited.
+    pub fn has_later_overlapping_transactions(&self, transaction_id: TransactionId) -> bool {
+        let undo_stack = &self.history.undo_stack;
+        let Some(ix) = undo_stack
+            .iter()
+            .position(|entry| entry.transaction.id == transaction_id)
+        else {
+            return false;
+        };
+        let ranges = self
+            .edited_ranges_for_transaction::<usize>(&undo_stack[ix].transaction)
+            .collect::<Vec<_>>();
+        undo_stack[ix + 1..].iter().any(|later_entry| {
+            self.edited_ranges_for_transaction::<usize>(&later_entry.transaction)
+                .any(|later_range| {
+                    ranges.iter().any(|range| {
+                        later_range.start <= range.end && range.start <= later_range.end
+                    })
+                })
+        })
+    }
+
     pub fn start_transaction(&mut self) -> Option<TransactionId> {
         self.start_transaction_at(Instant::now())
     }
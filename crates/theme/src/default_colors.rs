@@ -76,6 +76,7 @@ impl ThemeColors {
             editor_active_wrap_guide: neutral().light_alpha().step_8(),
             editor_document_highlight_read_background: neutral().light_alpha().step_3(),
             editor_document_highlight_write_background: neutral().light_alpha().step_4(),
+            editor_word_highlight_background: neutral().light_alpha().step_3(),
             terminal_background: neutral().light().step_1(),
             terminal_foreground: black().light().step_12(),
             terminal_bright_foreground: black().light().step_11(),
@@ -170,6 +171,7 @@ impl ThemeColors {
             editor_active_wrap_guide: neutral().dark_alpha().step_4(),
             editor_document_highlight_read_background: neutral().dark_alpha().step_4(),
             editor_document_highlight_write_background: neutral().dark_alpha().step_4(),
+            editor_word_highlight_background: neutral().dark_alpha().step_3(),
             terminal_background: neutral().dark().step_1(),
             terminal_foreground: white().dark().step_12(),
             terminal_bright_foreground: white().dark().step_11(),
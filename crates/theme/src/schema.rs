@@ -366,6 +366,10 @@ pub struct ThemeColorsContent {
     #[serde(rename = "editor.document_highlight.write_background")]
     pub editor_document_highlight_write_background: Option<String>,
 
+    /// Other occurrences of the word under the cursor.
+    #[serde(rename = "editor.word_highlight.background")]
+    pub editor_word_highlight_background: Option<String>,
+
     /// Terminal background color.
     #[serde(rename = "terminal.background")]
     pub terminal_background: Option<String>,
@@ -718,6 +722,10 @@ impl ThemeColorsContent {
                 .editor_document_highlight_write_background
                 .as_ref()
                 .and_then(|color| try_parse_color(&color).ok()),
+            editor_word_highlight_background: self
+                .editor_word_highlight_background
+                .as_ref()
+                .and_then(|color| try_parse_color(&color).ok()),
             terminal_background: self
                 .terminal_background
                 .as_ref()
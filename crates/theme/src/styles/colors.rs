@@ -165,6 +165,8 @@ pub struct ThemeColors {
     /// special attention. Usually a document highlight is visualized by changing
     /// the background color of its range.
     pub editor_document_highlight_write_background: Hsla,
+    /// Other occurrences of the word under the cursor.
+    pub editor_word_highlight_background: Hsla,
 
     // ===
     // Terminal
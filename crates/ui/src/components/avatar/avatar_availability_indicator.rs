@@ -3,6 +3,7 @@ use crate::prelude::*;
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum Availability {
     Free,
+    Away,
     Busy,
 }
 
@@ -42,6 +43,7 @@ impl RenderOnce for AvatarAvailabilityIndicator {
             .rounded(indicator_size)
             .bg(match self.availability {
                 Availability::Free => cx.theme().status().created,
+                Availability::Away => cx.theme().status().warning,
                 Availability::Busy => cx.theme().status().deleted,
             })
     }
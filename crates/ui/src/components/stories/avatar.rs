@@ -27,6 +27,11 @@ impl Render for AvatarStory {
                         Avatar::new("https://avatars.githubusercontent.com/u/326587?v=4")
                             .indicator(AvatarAvailabilityIndicator::new(Availability::Free)),
                     ))
+                    .child(StoryItem::new(
+                        "With away availability indicator",
+                        Avatar::new("https://avatars.githubusercontent.com/u/326587?v=4")
+                            .indicator(AvatarAvailabilityIndicator::new(Availability::Away)),
+                    ))
                     .child(StoryItem::new(
                         "With busy availability indicator",
                         Avatar::new("https://avatars.githubusercontent.com/u/326587?v=4")
@@ -0,0 +1,311 @@
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, AppContext, DismissEvent, Model, ParentElement, Task, ViewContext, WeakView,
+    WindowContext,
+};
+use language::{Buffer, TransactionId};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::Workspace;
+
+actions!(undo_history, [Toggle]);
+
+/// The length at which the text of a transaction is cut off in its summary.
+const MAX_SUMMARY_LEN: usize = 48;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(|workspace, _: &Toggle, cx| {
+                let project = workspace.project().clone();
+                let handle = cx.view().downgrade();
+                workspace.toggle_modal(cx, move |cx| {
+                    let delegate = UndoHistoryDelegate::new(handle, project, cx);
+                    Picker::uniform_list(delegate, cx).width(rems(34.))
+                })
+            });
+        },
+    )
+    .detach();
+}
+
+/// A transaction in the undo history of one of the project's open buffers.
+struct HistoryEntry {
+    buffer: Model<Buffer>,
+    transaction_id: TransactionId,
+    last_edit_at: Instant,
+    label: String,
+    has_conflict: bool,
+}
+
+pub struct UndoHistoryDelegate {
+    workspace: WeakView<Workspace>,
+    entries: Vec<HistoryEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl UndoHistoryDelegate {
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Self {
+        let mut entries = Vec::new();
+        for buffer_handle in project.read(cx).opened_buffers() {
+            let buffer = buffer_handle.read(cx);
+            let Some(file) = buffer.file() else {
+                continue;
+            };
+            let path = file.path().to_string_lossy().to_string();
+            for history_entry in buffer.undo_history().iter().rev() {
+                let transaction_id = history_entry.transaction_id();
+                let summary = transaction_summary(buffer, history_entry.transaction());
+                entries.push(HistoryEntry {
+                    buffer: buffer_handle.clone(),
+                    transaction_id,
+                    last_edit_at: history_entry.last_edit_at(),
+                    label: format!("{path}: {summary}"),
+                    has_conflict: buffer.has_later_overlapping_transactions(transaction_id),
+                });
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_edit_at));
+
+        Self {
+            workspace,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for UndoHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Revert a recent edit...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| StringMatchCandidate::new(id, entry.label.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the most recent transactions first.
+                matches.sort_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+        // Reverting a transaction that later edits build upon would leave those edits
+        // in a mangled state.
+        if entry.has_conflict {
+            return;
+        }
+
+        let buffer = entry.buffer.clone();
+        buffer.update(cx, |buffer, cx| {
+            buffer.undo_transaction(entry.transaction_id, cx);
+        });
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_project_item::<Editor>(buffer, cx);
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+        let detail = if entry.has_conflict {
+            "edited since".to_string()
+        } else {
+            format_elapsed(entry.last_edit_at.elapsed())
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .disabled(entry.has_conflict)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(Label::new(detail).color(Color::Muted)),
+        )
+    }
+}
+
+/// Describes a transaction by the first line of text it inserted.
+fn transaction_summary(buffer: &Buffer, transaction: &language::Transaction) -> String {
+    let inserted_text = buffer
+        .edited_ranges_for_transaction::<usize>(transaction)
+        .map(|range| buffer.text_for_range(range).collect::<String>())
+        .find_map(|text| {
+            let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+            Some(line.to_string())
+        });
+    match inserted_text {
+        Some(text) if text.chars().count() > MAX_SUMMARY_LEN => {
+            let mut summary = text.chars().take(MAX_SUMMARY_LEN).collect::<String>();
+            summary.push('…');
+            summary
+        }
+        Some(text) => text,
+        None => "Deleted text".to_string(),
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        1..=59 => format!("{minutes} min ago"),
+        60..=1439 => format!("{} hr ago", minutes / 60),
+        _ => format!("{} days ago", minutes / 1440),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_revert_transaction(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.txt": "one two three" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.set_group_interval(Duration::ZERO);
+            buffer.edit([(0..3, "ONE")], None, cx);
+            buffer.edit([(8..13, "THREE")], None, cx);
+            buffer.edit([(13..13, "!")], None, cx);
+        });
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let picker = cx.new_view(|cx| {
+            let delegate = UndoHistoryDelegate::new(workspace.downgrade(), project.clone(), cx);
+            Picker::uniform_list(delegate, cx)
+        });
+        picker.update(cx, |picker, cx| picker.update_matches("".to_string(), cx));
+        cx.run_until_parked();
+
+        picker.update(cx, |picker, cx| {
+            let delegate = &mut picker.delegate;
+            let labels = delegate
+                .matches
+                .iter()
+                .map(|mat| mat.string.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(labels, ["a.txt: !", "a.txt: THREE", "a.txt: ONE"]);
+
+            // The second transaction was edited afterwards, so it can't be reverted.
+            delegate.set_selected_index(1, cx);
+            delegate.confirm(false, cx);
+            assert_eq!(buffer.read(cx).text(), "ONE two THREE!");
+
+            delegate.set_selected_index(2, cx);
+            delegate.confirm(false, cx);
+            assert_eq!(buffer.read(cx).text(), "one two THREE!");
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+        });
+    }
+}
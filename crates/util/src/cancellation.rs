@@ -0,0 +1,139 @@
+//! Cancellation of background work that outlives the task that started it.
+//!
+//! Dropping a `Task` cancels it, but work that is detached, or handed off to
+//! another executor, keeps running until it finishes on its own. Such work can
+//! instead be tied to a [`CancellationToken`] owned by the model or view that
+//! started it, and cancelled when that model or view is released.
+
+use collections::HashMap;
+use futures::future::{self, Either};
+use parking_lot::Mutex;
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<State>,
+}
+
+#[derive(Default)]
+struct State {
+    cancelled: AtomicBool,
+    wakers: Mutex<Wakers>,
+}
+
+#[derive(Default)]
+struct Wakers {
+    next_id: usize,
+    wakers: HashMap<usize, Waker>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels all work tied to this token, including work started after it
+    /// was cancelled.
+    pub fn cancel(&self) {
+        if !self.state.cancelled.swap(true, SeqCst) {
+            let wakers = std::mem::take(&mut self.state.wakers.lock().wakers);
+            for waker in wakers.into_values() {
+                waker.wake();
+            }
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(SeqCst)
+    }
+
+    /// Returns a future that resolves once this token is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+            id: None,
+        }
+    }
+
+    /// Runs `future` until it completes, or until this token is cancelled,
+    /// in which case it is dropped and `None` is returned.
+    pub fn run<F: Future>(&self, future: F) -> impl Future<Output = Option<F::Output>> {
+        let cancelled = self.cancelled();
+        async move {
+            // Polls the cancellation first, so that work tied to a cancelled
+            // token never starts.
+            match future::select(cancelled, pin!(future)).await {
+                Either::Left(_) => None,
+                Either::Right((output, _)) => Some(output),
+            }
+        }
+    }
+}
+
+pub struct Cancelled {
+    token: CancellationToken,
+    id: Option<usize>,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let state = self.token.state.clone();
+        let mut wakers = state.wakers.lock();
+        // Checked while holding the lock, so that `cancel` can't wake the
+        // registered wakers between the check and the registration.
+        if state.cancelled.load(SeqCst) {
+            return Poll::Ready(());
+        }
+        let id = *self.id.get_or_insert_with(|| {
+            wakers.next_id += 1;
+            wakers.next_id
+        });
+        wakers.wakers.insert(id, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.token.state.wakers.lock().wakers.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        smol::block_on(async {
+            let token = CancellationToken::new();
+            assert_eq!(token.run(async { 1 }).await, Some(1));
+
+            let (tx, rx) = futures::channel::oneshot::channel::<()>();
+            let pending = smol::spawn({
+                let token = token.clone();
+                async move { token.run(rx).await }
+            });
+            assert!(!token.is_cancelled());
+            token.cancel();
+            assert!(token.is_cancelled());
+            assert_eq!(pending.await, None);
+            assert!(tx.is_canceled());
+
+            assert_eq!(token.run(async { 2 }).await, None);
+            token.cancelled().await;
+        });
+    }
+}
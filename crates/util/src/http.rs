@@ -1,7 +1,10 @@
-use crate::http_proxy_from_env;
+use crate::{http_proxy_from_env, no_proxy_from_env};
 pub use anyhow::{anyhow, Result};
 use futures::future::BoxFuture;
-use isahc::config::{Configurable, RedirectPolicy};
+use isahc::{
+    config::{Configurable, RedirectPolicy},
+    http::header::RETRY_AFTER,
+};
 pub use isahc::{
     http::{Method, StatusCode, Uri},
     Error,
@@ -14,6 +17,53 @@ use std::fmt;
 use std::{sync::Arc, time::Duration};
 pub use url::Url;
 
+/// How long an attempt at a request sent with [`HttpClient::get_with_retries`] may take,
+/// including reading the response, before it is abandoned.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Waits for the given duration before a failed request is retried. Callers pass their
+/// executor's timer, so that the wait follows the executor's clock, which tests can advance.
+pub type RetryTimer = Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// How requests that fail with a transient error, like a dropped connection or a
+/// `503 Service Unavailable` response, are retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times a request is sent again after its first attempt fails.
+    pub max_retries: u32,
+    /// How long to wait before the first retry. Each later retry waits twice as long as
+    /// the one before it, unless the server asks for a different wait with `Retry-After`.
+    pub initial_backoff: Duration,
+    /// The longest time to wait before a retry. A request whose server asks to wait
+    /// longer than this isn't retried.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Never retries.
+    pub const NONE: Self = Self {
+        max_retries: 0,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
 /// An [`HttpClient`] that has a base URL.
 pub struct HttpClientWithUrl {
     base_url: Mutex<String>,
@@ -94,6 +144,39 @@ pub trait HttpClient: Send + Sync {
         }
     }
 
+    /// Like [`Self::get`], but gives up on attempts that take longer than `timeout`, if it's
+    /// given, and retries failed attempts as described by `retry_policy`, waiting with
+    /// `timer`. Redirects are followed.
+    fn get_with_retries<'a>(
+        &'a self,
+        uri: &str,
+        timeout: Option<Duration>,
+        retry_policy: RetryPolicy,
+        timer: RetryTimer,
+    ) -> BoxFuture<'a, Result<Response<AsyncBody>, Error>> {
+        let uri = uri.to_string();
+        async move {
+            send_with_retries(
+                self,
+                || {
+                    let mut request =
+                        isahc::Request::builder().redirect_policy(RedirectPolicy::Follow);
+                    if let Some(timeout) = timeout {
+                        request = request.timeout(timeout);
+                    }
+                    request
+                        .method(Method::GET)
+                        .uri(uri.as_str())
+                        .body(AsyncBody::default())
+                },
+                retry_policy,
+                &timer,
+            )
+            .await
+        }
+        .boxed()
+    }
+
     fn post_json<'a>(
         &'a self,
         uri: &str,
@@ -111,12 +194,86 @@ pub trait HttpClient: Send + Sync {
     }
 }
 
+/// Sends the request built by `build_request`. If the request is idempotent, it's built and
+/// sent again after transient failures, as described by `retry_policy`, waiting with `timer`.
+pub async fn send_with_retries<C: HttpClient + ?Sized>(
+    client: &C,
+    build_request: impl Fn() -> Result<Request<AsyncBody>, isahc::http::Error>,
+    retry_policy: RetryPolicy,
+    timer: &RetryTimer,
+) -> Result<Response<AsyncBody>, Error> {
+    let mut retry = 0;
+    loop {
+        let request = build_request()?;
+        let uri = request.uri().clone();
+        let is_idempotent = is_idempotent(request.method());
+        let result = client.send(request).await;
+        if retry >= retry_policy.max_retries || !is_idempotent || !is_transient(&result) {
+            return result;
+        }
+
+        let backoff = match result.as_ref().ok().and_then(retry_after) {
+            Some(retry_after) if retry_after > retry_policy.max_backoff => return result,
+            Some(retry_after) => retry_after,
+            None => retry_policy.backoff(retry),
+        };
+        match &result {
+            Ok(response) => log::info!(
+                "retrying request to {uri} in {backoff:?} after status {}",
+                response.status()
+            ),
+            Err(error) => log::info!("retrying request to {uri} in {backoff:?}: {error}"),
+        }
+        timer(backoff).await;
+        retry += 1;
+    }
+}
+
+/// The wait requested by a response's `Retry-After` header. Only the header's delay in
+/// seconds is understood, not its date form.
+fn retry_after(response: &Response<AsyncBody>) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Whether sending a request with the given method more than once has the same effect as
+/// sending it once.
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Whether a request that had this result might succeed if it were sent again.
+fn is_transient(result: &Result<Response<AsyncBody>, Error>) -> bool {
+    match result {
+        Ok(response) => matches!(
+            response.status(),
+            StatusCode::REQUEST_TIMEOUT
+                | StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        ),
+        Err(error) => error.is_network() || error.is_timeout(),
+    }
+}
+
 pub fn client() -> Arc<dyn HttpClient> {
     Arc::new(
         isahc::HttpClient::builder()
             .connect_timeout(Duration::from_secs(5))
             .low_speed_timeout(100, Duration::from_secs(5))
             .proxy(http_proxy_from_env())
+            .proxy_blacklist(no_proxy_from_env())
             .build()
             .unwrap(),
     )
@@ -186,3 +343,165 @@ impl HttpClient for FakeHttpClient {
         Box::pin(async move { future.await.map(Into::into) })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    /// Responds to each request with the next of the given statuses, and the
+    /// given `Retry-After` header.
+    struct SequenceHttpClient {
+        statuses: Vec<u16>,
+        retry_after: Option<&'static str>,
+        requests: AtomicUsize,
+    }
+
+    impl HttpClient for SequenceHttpClient {
+        fn send(&self, _: Request<AsyncBody>) -> BoxFuture<Result<Response<AsyncBody>, Error>> {
+            let ix = self.requests.fetch_add(1, SeqCst);
+            let status = self.statuses[ix.min(self.statuses.len() - 1)];
+            let retry_after = self.retry_after;
+            async move {
+                let mut response = Response::builder().status(status);
+                if let Some(retry_after) = retry_after {
+                    response = response.header(RETRY_AFTER, retry_after);
+                }
+                Ok(response.body(Default::default()).unwrap())
+            }
+            .boxed()
+        }
+    }
+
+    fn sequence(statuses: &[u16]) -> SequenceHttpClient {
+        SequenceHttpClient {
+            statuses: statuses.to_vec(),
+            retry_after: None,
+            requests: AtomicUsize::new(0),
+        }
+    }
+
+    /// A timer that returns right away, recording the waits it was asked for.
+    fn recording_timer() -> (RetryTimer, Arc<Mutex<Vec<Duration>>>) {
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let timer: RetryTimer = Arc::new({
+            let waits = waits.clone();
+            move |duration| {
+                waits.lock().push(duration);
+                async {}.boxed()
+            }
+        });
+        (timer, waits)
+    }
+
+    const RETRY_POLICY: RetryPolicy = RetryPolicy {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(2),
+    };
+
+    #[test]
+    fn test_retrying_transient_failures() {
+        let (timer, waits) = recording_timer();
+        let client = sequence(&[503, 502, 200]);
+        let response = smol::block_on(client.get_with_retries(
+            "http://example.com",
+            Some(DEFAULT_REQUEST_TIMEOUT),
+            RETRY_POLICY,
+            timer.clone(),
+        ))
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(client.requests.load(SeqCst), 3);
+        assert_eq!(
+            waits.lock().as_slice(),
+            [Duration::from_millis(1), Duration::from_millis(2)]
+        );
+
+        // Retries stop after the policy's limit.
+        let client = sequence(&[503]);
+        let response = smol::block_on(client.get_with_retries(
+            "http://example.com",
+            Some(DEFAULT_REQUEST_TIMEOUT),
+            RETRY_POLICY,
+            timer.clone(),
+        ))
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(client.requests.load(SeqCst), 3);
+
+        // Errors that won't go away aren't retried.
+        let client = sequence(&[404, 200]);
+        let response = smol::block_on(client.get_with_retries(
+            "http://example.com",
+            Some(DEFAULT_REQUEST_TIMEOUT),
+            RETRY_POLICY,
+            timer,
+        ))
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(client.requests.load(SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let policy = RetryPolicy {
+            max_retries: 1,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_secs(10),
+        };
+
+        // The server's requested wait replaces the backoff.
+        let (timer, waits) = recording_timer();
+        let client = SequenceHttpClient {
+            retry_after: Some("3"),
+            ..sequence(&[429, 200])
+        };
+        let response =
+            smol::block_on(client.get_with_retries("http://example.com", None, policy, timer))
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(waits.lock().as_slice(), [Duration::from_secs(3)]);
+
+        // Requests whose server asks for a longer wait than the policy allows aren't retried.
+        let (timer, waits) = recording_timer();
+        let client = SequenceHttpClient {
+            retry_after: Some("3600"),
+            ..sequence(&[503, 200])
+        };
+        let response =
+            smol::block_on(client.get_with_retries("http://example.com", None, policy, timer))
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(client.requests.load(SeqCst), 1);
+        assert!(waits.lock().is_empty());
+    }
+
+    #[test]
+    fn test_not_retrying_non_idempotent_requests() {
+        let client = sequence(&[503, 200]);
+        let response = smol::block_on(send_with_retries(
+            &client,
+            || {
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("http://example.com")
+                    .body(AsyncBody::default())
+            },
+            RETRY_POLICY,
+            &recording_timer().0,
+        ))
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(client.requests.load(SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(4), Duration::from_secs(8));
+        assert_eq!(policy.backoff(40), Duration::from_secs(8));
+    }
+}
@@ -8,10 +8,17 @@ use serde::{Deserialize, Serialize};
 
 lazy_static::lazy_static! {
     pub static ref HOME: PathBuf = dirs::home_dir().expect("failed to determine home directory");
-    pub static ref CONFIG_DIR: PathBuf = HOME.join(".config").join("zed");
+    pub static ref CONFIG_DIR: PathBuf = if cfg!(target_os = "macos") {
+        HOME.join(".config").join("zed")
+    } else {
+        dirs::config_dir()
+            .unwrap_or_else(|| HOME.join(".config"))
+            .join("zed")
+    };
     pub static ref CONVERSATIONS_DIR: PathBuf = CONFIG_DIR.join("conversations");
     pub static ref EMBEDDINGS_DIR: PathBuf = CONFIG_DIR.join("embeddings");
     pub static ref THEMES_DIR: PathBuf = CONFIG_DIR.join("themes");
+    pub static ref PROJECT_TEMPLATES_DIR: PathBuf = CONFIG_DIR.join("templates");
     pub static ref LOGS_DIR: PathBuf = if cfg!(target_os = "macos") {
         HOME.join("Library/Logs/Zed")
     } else {
@@ -27,6 +34,7 @@ lazy_static::lazy_static! {
     pub static ref COPILOT_DIR: PathBuf = SUPPORT_DIR.join("copilot");
     pub static ref DEFAULT_PRETTIER_DIR: PathBuf = SUPPORT_DIR.join("prettier");
     pub static ref DB_DIR: PathBuf = SUPPORT_DIR.join("db");
+    pub static ref ASSET_CACHE_DIR: PathBuf = SUPPORT_DIR.join("cache");
     pub static ref CRASHES_DIR: PathBuf = if cfg!(target_os = "macos") {
         HOME.join("Library/Logs/DiagnosticReports")
     } else {
@@ -38,6 +46,13 @@ lazy_static::lazy_static! {
         CRASHES_DIR.join("retired")
     };
     pub static ref SETTINGS: PathBuf = CONFIG_DIR.join("settings.json");
+    pub static ref MACHINE_SETTINGS: PathBuf = if cfg!(target_os = "macos") {
+        SUPPORT_DIR.join("machine_settings.json")
+    } else {
+        dirs::data_dir()
+            .unwrap_or_else(|| HOME.join(".local/share"))
+            .join("zed/machine_settings.json")
+    };
     pub static ref KEYMAP: PathBuf = CONFIG_DIR.join("keymap.json");
     pub static ref TASKS: PathBuf = CONFIG_DIR.join("tasks.json");
     pub static ref LAST_USERNAME: PathBuf = CONFIG_DIR.join("last-username.txt");
@@ -0,0 +1,101 @@
+use anyhow::{anyhow, Result};
+use futures::{future::BoxFuture, AsyncWriteExt};
+use smol::process::{Command, Stdio};
+#[cfg(feature = "test-support")]
+use std::fmt;
+use std::{path::PathBuf, sync::Arc};
+
+/// A program to run, along with the input to write to its stdin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessRequest {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_directory: PathBuf,
+    pub stdin: String,
+}
+
+/// The result of running a [`ProcessRequest`] to completion.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessOutput {
+    /// The exit code, or `None` if the process was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl ProcessOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Spawns processes, so that code which shells out can be tested without
+/// depending on the programs installed on the machine.
+pub trait ProcessRunner: Send + Sync {
+    fn run(&self, request: ProcessRequest) -> BoxFuture<'static, Result<ProcessOutput>>;
+}
+
+pub fn runner() -> Arc<dyn ProcessRunner> {
+    Arc::new(RealProcessRunner)
+}
+
+struct RealProcessRunner;
+
+impl ProcessRunner for RealProcessRunner {
+    fn run(&self, request: ProcessRequest) -> BoxFuture<'static, Result<ProcessOutput>> {
+        Box::pin(async move {
+            let mut child = Command::new(&request.program)
+                .args(&request.args)
+                .current_dir(&request.working_directory)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let stdin = child
+                .stdin
+                .as_mut()
+                .ok_or_else(|| anyhow!("failed to acquire stdin"))?;
+            stdin.write_all(request.stdin.as_bytes()).await?;
+            stdin.flush().await?;
+
+            let output = child.output().await?;
+            Ok(ProcessOutput {
+                exit_code: output.status.code(),
+                stdout: output.stdout,
+                stderr: output.stderr,
+            })
+        })
+    }
+}
+
+#[cfg(feature = "test-support")]
+pub struct FakeProcessRunner {
+    handler: Box<dyn 'static + Send + Sync + Fn(ProcessRequest) -> Result<ProcessOutput>>,
+}
+
+#[cfg(feature = "test-support")]
+impl FakeProcessRunner {
+    pub fn create<F>(handler: F) -> Arc<dyn ProcessRunner>
+    where
+        F: 'static + Send + Sync + Fn(ProcessRequest) -> Result<ProcessOutput>,
+    {
+        Arc::new(Self {
+            handler: Box::new(handler),
+        })
+    }
+}
+
+#[cfg(feature = "test-support")]
+impl fmt::Debug for FakeProcessRunner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakeProcessRunner").finish()
+    }
+}
+
+#[cfg(feature = "test-support")]
+impl ProcessRunner for FakeProcessRunner {
+    fn run(&self, request: ProcessRequest) -> BoxFuture<'static, Result<ProcessOutput>> {
+        let output = (self.handler)(request);
+        Box::pin(async move { output })
+    }
+}
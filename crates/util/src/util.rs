@@ -1,8 +1,11 @@
 pub mod arc_cow;
+pub mod asset_cache;
+pub mod cancellation;
 pub mod fs;
 pub mod github;
 pub mod http;
 pub mod paths;
+pub mod process;
 mod semantic_version;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
@@ -66,6 +69,22 @@ pub fn http_proxy_from_env() -> Option<isahc::http::Uri> {
     None
 }
 
+/// The hosts that shouldn't be reached through the proxy, from the comma-separated
+/// `NO_PROXY` environment variable.
+pub fn no_proxy_from_env() -> Vec<String> {
+    let Some(hosts) = ["NO_PROXY", "no_proxy"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+    else {
+        return Vec::new();
+    };
+    hosts
+        .split(',')
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
 /// Removes characters from the end of the string if its length is greater than `max_chars` and
 /// appends "..." to the string. Returns string unchanged if its length is smaller than max_chars.
 pub fn truncate_and_trailoff(s: &str, max_chars: usize) -> String {
@@ -144,11 +144,14 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
         .as_ref()
         .map(|action| action.boxed_clone())
     {
+        // Record what the keystroke actually ran, so that count prefixes and
+        // `workspace::RepeatLastAction` replay the same way.
+        let dispatched_actions = workspace::dispatched_actions(action.as_ref(), cx);
         Vim::update(cx, |vim, _| {
             if vim.workspace_state.recording {
                 vim.workspace_state
                     .recorded_actions
-                    .push(ReplayableAction::Action(action.boxed_clone()));
+                    .extend(dispatched_actions.into_iter().map(ReplayableAction::Action));
 
                 if vim.workspace_state.stop_recording_after_next_action {
                     vim.workspace_state.recording = false;
@@ -15,6 +15,7 @@ pub enum BaseKeymap {
     SublimeText,
     Atom,
     TextMate,
+    Emacs,
 }
 
 impl Display for BaseKeymap {
@@ -25,17 +26,19 @@ impl Display for BaseKeymap {
             BaseKeymap::SublimeText => write!(f, "Sublime Text"),
             BaseKeymap::Atom => write!(f, "Atom"),
             BaseKeymap::TextMate => write!(f, "TextMate"),
+            BaseKeymap::Emacs => write!(f, "Emacs"),
         }
     }
 }
 
 impl BaseKeymap {
-    pub const OPTIONS: [(&'static str, Self); 5] = [
+    pub const OPTIONS: [(&'static str, Self); 6] = [
         ("VSCode (Default)", Self::VSCode),
         ("Atom", Self::Atom),
         ("JetBrains", Self::JetBrains),
         ("Sublime Text", Self::SublimeText),
         ("TextMate", Self::TextMate),
+        ("Emacs", Self::Emacs),
     ];
 
     pub fn asset_path(&self) -> Option<&'static str> {
@@ -44,6 +47,7 @@ impl BaseKeymap {
             BaseKeymap::SublimeText => Some("keymaps/sublime_text.json"),
             BaseKeymap::Atom => Some("keymaps/atom.json"),
             BaseKeymap::TextMate => Some("keymaps/textmate.json"),
+            BaseKeymap::Emacs => Some("keymaps/emacs.json"),
             BaseKeymap::VSCode => None,
         }
     }
@@ -1,4 +1,5 @@
 use crate::{
+    notifications::simple_message_notification::MessageNotification,
     pane::{self, Pane},
     persistence::model::ItemId,
     searchable::SearchableItemHandle,
@@ -13,8 +14,8 @@ use client::{
 };
 use gpui::{
     AnyElement, AnyView, AppContext, Entity, EntityId, EventEmitter, FocusHandle, FocusableView,
-    HighlightStyle, Model, Pixels, Point, SharedString, Task, View, ViewContext, WeakView,
-    WindowContext,
+    HighlightStyle, Model, Pixels, Point, SharedString, Task, View, ViewContext, VisualContext,
+    WeakView, WindowContext,
 };
 use project::{Project, ProjectEntryId, ProjectPath};
 use schemars::JsonSchema;
@@ -215,6 +216,11 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
     fn pixel_position_of_cursor(&self, _: &AppContext) -> Option<Point<Pixels>> {
         None
     }
+    /// Whether the text typed into this item can be shown to others, as in screencast mode.
+    /// Items that may take secrets, like terminals, should keep the default.
+    fn shows_typed_text(&self, _: &AppContext) -> bool {
+        false
+    }
 }
 
 pub trait ItemHandle: 'static + Send {
@@ -279,6 +285,7 @@ pub trait ItemHandle: 'static + Send {
     fn serialized_item_kind(&self) -> Option<&'static str>;
     fn show_toolbar(&self, cx: &AppContext) -> bool;
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>>;
+    fn shows_typed_text(&self, cx: &AppContext) -> bool;
 }
 
 pub trait WeakItemHandle: Send + Sync {
@@ -297,6 +304,41 @@ impl dyn ItemHandle {
     }
 }
 
+/// Offers to reload an item whose file was changed on disk while it had unsaved edits,
+/// and dismisses that offer once the conflict has been resolved.
+fn update_conflict_notification<T: Item>(
+    workspace: &mut Workspace,
+    item: &View<T>,
+    has_conflict: bool,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let notification_id = item.item_id().as_u64() as usize;
+    if !has_conflict {
+        workspace.dismiss_notification::<MessageNotification>(notification_id, cx);
+        return;
+    }
+
+    let file_name = item
+        .project_path(cx)
+        .and_then(|path| Some(path.path.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "This file".to_string());
+    let project = workspace.project().clone();
+    let item = item.downgrade();
+    workspace.show_notification(notification_id, cx, |cx| {
+        cx.new_view(|_| {
+            MessageNotification::new(format!(
+                "{file_name} has changed on disk since you last edited it."
+            ))
+            .with_click_message("Discard your changes and reload")
+            .on_click(move |cx| {
+                if let Some(item) = item.upgrade() {
+                    item.reload(project.clone(), cx).detach_and_log_err(cx);
+                }
+            })
+        })
+    });
+}
+
 impl<T: Item> ItemHandle for View<T> {
     fn subscribe_to_item_events(
         &self,
@@ -423,6 +465,7 @@ impl<T: Item> ItemHandle for View<T> {
             let mut pending_autosave = DelayedDebouncedEditAction::new();
             let pending_update = Rc::new(RefCell::new(None));
             let pending_update_scheduled = Arc::new(AtomicBool::new(false));
+            let mut had_conflict = self.has_conflict(cx);
 
             let mut event_subscription =
                 Some(cx.subscribe(self, move |workspace, item, event, cx| {
@@ -494,6 +537,12 @@ impl<T: Item> ItemHandle for View<T> {
                                 cx.emit(pane::Event::ChangeItemTitle);
                                 cx.notify();
                             });
+
+                            let has_conflict = item.has_conflict(cx);
+                            if has_conflict != had_conflict {
+                                had_conflict = has_conflict;
+                                update_conflict_notification(workspace, &item, has_conflict, cx);
+                            }
                         }
 
                         ItemEvent::Edit => {
@@ -624,6 +673,10 @@ impl<T: Item> ItemHandle for View<T> {
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>> {
         self.read(cx).pixel_position_of_cursor(cx)
     }
+
+    fn shows_typed_text(&self, cx: &AppContext) -> bool {
+        self.read(cx).shows_typed_text(cx)
+    }
 }
 
 impl From<Box<dyn ItemHandle>> for AnyView {
@@ -1008,6 +1061,10 @@ pub mod test {
             self.has_conflict
         }
 
+        fn shows_typed_text(&self, _: &AppContext) -> bool {
+            true
+        }
+
         fn can_save(&self, cx: &AppContext) -> bool {
             !self.project_items.is_empty()
                 && self
@@ -2,9 +2,10 @@ use crate::{Toast, Workspace};
 use collections::HashMap;
 use gpui::{
     svg, AnyView, AppContext, AsyncWindowContext, DismissEvent, Entity, EntityId, EventEmitter,
-    Global, PromptLevel, Render, Task, View, ViewContext, VisualContext, WindowContext,
+    Global, Model, PromptLevel, Render, Task, View, ViewContext, VisualContext, WindowContext,
 };
 use language::DiagnosticSeverity;
+use project::Project;
 
 use std::{any::TypeId, ops::DerefMut};
 use ui::prelude::*;
@@ -271,6 +272,69 @@ impl Render for LanguageServerPrompt {
 
 impl EventEmitter<DismissEvent> for LanguageServerPrompt {}
 
+/// Asks the host of a shared project whether a guest may join it.
+pub struct JoinRequestPrompt {
+    project: Model<Project>,
+    request_id: u64,
+    requester_login: SharedString,
+}
+
+impl JoinRequestPrompt {
+    pub fn new(project: Model<Project>, request_id: u64, requester_login: SharedString) -> Self {
+        Self {
+            project,
+            request_id,
+            requester_login,
+        }
+    }
+
+    fn respond(&mut self, allow: bool, cx: &mut ViewContext<Self>) {
+        let request_id = self.request_id;
+        self.project.update(cx, |project, _| {
+            project.respond_to_join_request(request_id, allow)
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for JoinRequestPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .id("join_request_prompt_notification")
+            .elevation_3(cx)
+            .items_start()
+            .p_2()
+            .gap_2()
+            .w_full()
+            .child(
+                v_flex()
+                    .overflow_hidden()
+                    .child(Label::new(format!(
+                        "{} wants to join this project",
+                        self.requester_login
+                    )))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                ui::Button::new("allow", "Allow")
+                                    .on_click(cx.listener(|this, _, cx| this.respond(true, cx))),
+                            )
+                            .child(
+                                ui::Button::new("decline", "Decline")
+                                    .on_click(cx.listener(|this, _, cx| this.respond(false, cx))),
+                            ),
+                    ),
+            )
+            .child(
+                ui::IconButton::new("close", ui::IconName::Close)
+                    .on_click(cx.listener(|this, _, cx| this.respond(false, cx))),
+            )
+    }
+}
+
+impl EventEmitter<DismissEvent> for JoinRequestPrompt {}
+
 pub mod simple_message_notification {
     use gpui::{
         div, DismissEvent, EventEmitter, InteractiveElement, ParentElement, Render, SharedString,
@@ -248,6 +248,11 @@ impl Pane {
             cx.on_focus(&focus_handle, Pane::focus_in),
             cx.on_focus_in(&focus_handle, Pane::focus_in),
             cx.on_focus_out(&focus_handle, Pane::focus_out),
+            cx.subscribe(&project, |_, _, event, cx| {
+                if let project::Event::CollaboratorActivePathsChanged = event {
+                    cx.notify();
+                }
+            }),
         ];
 
         let handle = cx.view().downgrade();
@@ -1210,11 +1215,47 @@ impl Pane {
         project: Model<Project>,
         cx: &mut WindowContext,
     ) -> Task<Result<()>> {
-        if Self::can_autosave_item(item, cx) {
-            item.save(project, cx)
-        } else {
-            Task::ready(Ok(()))
+        if !Self::can_autosave_item(item, cx) {
+            return Task::ready(Ok(()));
         }
+
+        let Some(changed_on_disk) = Self::item_changed_on_disk(item, &project, cx) else {
+            return item.save(project, cx);
+        };
+        let item = item.boxed_clone();
+        cx.spawn(|mut cx| async move {
+            // The worktree may not have observed an external change yet, in which case the
+            // item can't report a conflict. Leave it dirty rather than clobbering the file.
+            if changed_on_disk.await {
+                log::info!("skipping autosave of an item that changed on disk");
+                return Ok(());
+            }
+            cx.update(|cx| item.save(project, cx))?.await
+        })
+    }
+
+    /// Compares the modification time of the item's file on disk with the one last observed
+    /// by its worktree, or returns `None` if the item isn't backed by a local file.
+    fn item_changed_on_disk(
+        item: &dyn ItemHandle,
+        project: &Model<Project>,
+        cx: &AppContext,
+    ) -> Option<Task<bool>> {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return None;
+        }
+        let project_path = item.project_path(cx)?;
+        let entry = project.entry_for_path(&project_path, cx)?;
+        let abs_path = project.absolute_path(&project_path, cx)?;
+
+        let fs = project.fs().clone();
+        Some(cx.background_executor().spawn(async move {
+            match fs.metadata(&abs_path).await {
+                Ok(Some(metadata)) => metadata.mtime != entry.mtime,
+                _ => false,
+            }
+        }))
     }
 
     pub fn focus(&mut self, cx: &mut ViewContext<Pane>) {
@@ -1291,6 +1332,9 @@ impl Pane {
         let is_active = ix == self.active_item_index;
 
         let label = item.tab_content(Some(detail), is_active, cx);
+        let presence_dots = item
+            .project_path(cx)
+            .and_then(|path| crate::render_presence_dots(&self.project, &path, cx));
         let close_side = &ItemSettings::get_global(cx).close_position;
 
         let indicator = maybe!({
@@ -1378,7 +1422,8 @@ impl Pane {
                             .detach_and_log_err(cx);
                     })),
             )
-            .child(label);
+            .child(label)
+            .children(presence_dots);
 
         let single_entry_to_resolve = {
             let item_entries = self.items[ix].project_entry_ids(cx);
@@ -2073,6 +2118,62 @@ impl NavHistory {
             .retain(|entry| entry.item.id() != item_id);
     }
 
+    /// Updates the paths of the closed items whose files were renamed, and
+    /// forgets the closed items whose files were deleted, so that navigating
+    /// back or reopening them doesn't try to open a missing file. Items that
+    /// are still open are left alone, as their paths are recorded when they're
+    /// closed.
+    pub fn update_closed_item_paths(
+        &mut self,
+        renamed_paths: &[(ProjectPath, ProjectPath)],
+        deleted_paths: &[ProjectPath],
+        open_item_ids: &HashSet<EntityId>,
+    ) {
+        let mut deleted_item_ids = Vec::new();
+        let mut state = self.0.lock();
+        for (item_id, (project_path, abs_path)) in state.paths_by_item.iter_mut() {
+            if open_item_ids.contains(item_id) {
+                continue;
+            }
+
+            // Renaming a directory renames its descendants, so the most specific rename wins.
+            let rename = renamed_paths
+                .iter()
+                .filter(|(old_path, _)| {
+                    old_path.worktree_id == project_path.worktree_id
+                        && project_path.path.starts_with(&old_path.path)
+                })
+                .max_by_key(|(old_path, _)| old_path.path.components().count());
+            if let Some((old_path, new_path)) = rename {
+                let suffix = project_path.path.strip_prefix(&old_path.path).unwrap();
+                let path: Arc<Path> = if suffix.as_os_str().is_empty() {
+                    new_path.path.clone()
+                } else {
+                    new_path.path.join(suffix).into()
+                };
+                if let Some(abs_path) = abs_path.as_mut() {
+                    if let Some(root) = abs_path
+                        .ancestors()
+                        .nth(project_path.path.components().count())
+                    {
+                        *abs_path = root.join(&path);
+                    }
+                }
+                project_path.path = path;
+            } else if deleted_paths.iter().any(|deleted| {
+                deleted.worktree_id == project_path.worktree_id
+                    && project_path.path.starts_with(&deleted.path)
+            }) {
+                deleted_item_ids.push(*item_id);
+            }
+        }
+        drop(state);
+
+        for item_id in deleted_item_ids {
+            self.remove_item(item_id);
+        }
+    }
+
     pub fn path_for_item(&self, item_id: EntityId) -> Option<(ProjectPath, Option<PathBuf>)> {
         self.0.lock().paths_by_item.get(&item_id).cloned()
     }
@@ -1,6 +1,6 @@
 pub mod model;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, bail, Context, Result};
 use db::{define_connection, query, sqlez::connection::Connection, sqlez_macros::sql};
@@ -155,6 +155,7 @@ define_connection! {
     //   window_width: Option<f32>, // WindowBounds::Fixed RectF width
     //   window_height: Option<f32>, // WindowBounds::Fixed RectF height
     //   display: Option<Uuid>, // Display id
+    //   pinned_paths: Option<Vec<PathBuf>>, // A JSON array of absolute paths
     // )
     //
     // pane_groups(
@@ -291,6 +292,10 @@ define_connection! {
     // Add pane group flex data
     sql!(
         ALTER TABLE pane_groups ADD COLUMN flexes TEXT;
+    ),
+    // Add the files pinned in the project panel
+    sql!(
+        ALTER TABLE workspaces ADD COLUMN pinned_paths TEXT;
     )
     ];
 }
@@ -653,6 +658,32 @@ impl WorkspaceDb {
         }
     }
 
+    /// Returns the absolute paths of the files pinned in the workspace's project panel.
+    pub fn pinned_paths(&self, workspace_id: WorkspaceId) -> Result<Vec<PathBuf>> {
+        let pinned_paths = self.select_row_bound::<WorkspaceId, Option<String>>(sql!(
+            SELECT pinned_paths FROM workspaces WHERE workspace_id = ?
+        ))?(workspace_id)?
+        .flatten();
+        Ok(match pinned_paths {
+            Some(pinned_paths) => serde_json::from_str(&pinned_paths)?,
+            None => Vec::new(),
+        })
+    }
+
+    pub async fn set_pinned_paths(
+        &self,
+        workspace_id: WorkspaceId,
+        pinned_paths: Vec<PathBuf>,
+    ) -> Result<()> {
+        let pinned_paths = serde_json::to_string(&pinned_paths)?;
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                UPDATE workspaces SET pinned_paths = ?2 WHERE workspace_id = ?1
+            ))?((workspace_id, pinned_paths))
+        })
+        .await
+    }
+
     query! {
         pub(crate) async fn set_window_bounds(workspace_id: WorkspaceId, bounds: SerializedWindowsBounds, display: Uuid) -> Result<()> {
             UPDATE workspaces
@@ -949,6 +980,32 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_pinned_paths() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_pinned_paths").await);
+
+        let workspace = SerializedWorkspace {
+            id: 1,
+            location: (["/tmp"]).into(),
+            center_group: Default::default(),
+            bounds: Default::default(),
+            display: Default::default(),
+            docks: Default::default(),
+        };
+        db.save_workspace(workspace.clone()).await;
+        assert_eq!(db.pinned_paths(1).unwrap(), Vec::<PathBuf>::new());
+
+        let pinned_paths = vec![PathBuf::from("/tmp/b.txt"), PathBuf::from("/tmp/a.txt")];
+        db.set_pinned_paths(1, pinned_paths.clone()).await.unwrap();
+        assert_eq!(db.pinned_paths(1).unwrap(), pinned_paths);
+
+        // Saving the rest of the workspace keeps its pins.
+        db.save_workspace(workspace).await;
+        assert_eq!(db.pinned_paths(1).unwrap(), pinned_paths);
+    }
+
     use crate::persistence::model::SerializedWorkspace;
     use crate::persistence::model::{SerializedItem, SerializedPane, SerializedPaneGroup};
 
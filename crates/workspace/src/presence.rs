@@ -0,0 +1,49 @@
+use gpui::{div, AppContext, Div, Hsla, Model, ParentElement as _, Styled as _};
+use project::{Project, ProjectPath};
+use theme::ActiveTheme as _;
+use ui::h_flex;
+
+/// The player colors of the collaborators who currently have the given file active.
+pub fn collaborator_colors_at_path(
+    project: &Model<Project>,
+    path: &ProjectPath,
+    cx: &AppContext,
+) -> Vec<Hsla> {
+    let project = project.read(cx);
+    let participant_indices = project.user_store().read(cx).participant_indices();
+    let mut colors = project
+        .collaborators_at_path(path)
+        .filter_map(|collaborator| participant_indices.get(&collaborator.user_id))
+        .map(|participant_index| participant_index.0)
+        .collect::<Vec<_>>();
+    colors.sort_unstable();
+    colors
+        .into_iter()
+        .map(|participant_index| {
+            cx.theme()
+                .players()
+                .color_for_participant(participant_index)
+                .cursor
+        })
+        .collect()
+}
+
+/// A row of small dots, one in each collaborator's color, for the collaborators who
+/// currently have the given file active.
+pub fn render_presence_dots(
+    project: &Model<Project>,
+    path: &ProjectPath,
+    cx: &AppContext,
+) -> Option<Div> {
+    let colors = collaborator_colors_at_path(project, path, cx);
+    if colors.is_empty() {
+        return None;
+    }
+    Some(
+        h_flex().gap_0p5().children(
+            colors
+                .into_iter()
+                .map(|color| div().size_1p5().rounded_full().bg(color)),
+        ),
+    )
+}
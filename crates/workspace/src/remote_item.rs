@@ -0,0 +1,409 @@
+//! Items on a guest that show content only the host of a project has, like the
+//! results of work that runs on the host.
+//!
+//! A guest opens a remote item by sending a request to the host, which starts a
+//! [`RemoteItemSource`] to serve it. The source's state is sent to the guest
+//! when the item is opened, and again whenever the source notifies, until the
+//! guest closes the item or leaves the project. When a guest reconnects, it
+//! reopens its remote items with their original requests.
+
+use crate::{item::Item, Workspace, WorkspaceStore};
+use anyhow::{anyhow, Context as _, Result};
+use client::{
+    proto::{self, PeerId},
+    Client, TypedEnvelope,
+};
+use collections::HashMap;
+use derive_more::{Deref, DerefMut};
+use gpui::{
+    AnyWindowHandle, AppContext, AsyncAppContext, Context as _, Entity, EntityId, Global, Model,
+    ModelContext, Subscription, Task, View, ViewContext, VisualContext as _, WeakModel,
+};
+use project::Project;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use util::{post_inc, ResultExt};
+
+/// Serves the content of one kind of remote item on the host.
+pub trait RemoteItemSource: 'static + Sized {
+    /// Identifies this kind of item between guests and the host.
+    const KIND: &'static str;
+    /// What the guest asked to see, like the query of a search.
+    type Request: Serialize + DeserializeOwned;
+    /// The content shown by the item on the guest.
+    type State: Serialize + DeserializeOwned;
+
+    fn open(project: Model<Project>, request: Self::Request, cx: &mut ModelContext<Self>) -> Self;
+    fn state(&self, cx: &AppContext) -> Self::State;
+}
+
+/// An item on a guest that shows the content of a [`RemoteItemSource`] on the host.
+pub trait RemoteItem: Item {
+    type Source: RemoteItemSource;
+
+    /// Replaces the item's content with the host's latest state.
+    fn set_remote_state(
+        &mut self,
+        state: <Self::Source as RemoteItemSource>::State,
+        cx: &mut ViewContext<Self>,
+    );
+}
+
+type RemoteItemSourceBuilder =
+    fn(Model<Project>, &str, u64, &mut ModelContext<WorkspaceStore>) -> Result<HostedSource>;
+
+#[derive(Default, Deref, DerefMut)]
+struct RemoteItemSourceBuilders(HashMap<&'static str, RemoteItemSourceBuilder>);
+
+impl Global for RemoteItemSourceBuilders {}
+
+/// Allows guests to open remote items served by `S` in projects this app hosts.
+pub fn register_remote_item_source<S: RemoteItemSource>(cx: &mut AppContext) {
+    let builders = cx.default_global::<RemoteItemSourceBuilders>();
+    builders.insert(S::KIND, |project, request, item_id, cx| {
+        let request = serde_json::from_str(request)?;
+        let source = cx.new_model(|cx| S::open(project, request, cx));
+        Ok(HostedSource {
+            _subscription: cx.observe(&source, move |store, _, cx| {
+                store.send_remote_item_state(item_id, cx)
+            }),
+            state: Box::new(move |cx: &AppContext| -> Result<String> {
+                Ok(serde_json::to_string(&source.read(cx).state(cx))?)
+            }),
+        })
+    });
+}
+
+#[derive(Default)]
+pub(crate) struct RemoteItems {
+    next_item_id: u64,
+    /// On the host, the sources serving the items guests have opened.
+    hosted: HashMap<u64, HostedItem>,
+    /// On a guest, the items showing content from a host.
+    opened: Vec<OpenedItem>,
+    project_subscriptions: HashMap<EntityId, Subscription>,
+}
+
+struct HostedItem {
+    project: WeakModel<Project>,
+    project_id: u64,
+    peer_id: PeerId,
+    source: HostedSource,
+}
+
+struct HostedSource {
+    state: Box<dyn Fn(&AppContext) -> Result<String>>,
+    _subscription: Subscription,
+}
+
+struct OpenedItem {
+    view_id: EntityId,
+    project: WeakModel<Project>,
+    project_id: u64,
+    item_id: u64,
+    kind: &'static str,
+    request: String,
+    set_state: Box<dyn Fn(&str, &mut AppContext) -> Result<()>>,
+}
+
+impl Workspace {
+    /// Opens an item in the active pane that shows content from the host of
+    /// this workspace's project.
+    pub fn open_remote_item<I: RemoteItem>(
+        &mut self,
+        request: <I::Source as RemoteItemSource>::Request,
+        build_item: impl 'static
+            + FnOnce(<I::Source as RemoteItemSource>::State, &mut ViewContext<I>) -> I,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<View<I>>> {
+        let project = self.project.clone();
+        let Some(project_id) = project
+            .read(cx)
+            .remote_id()
+            .filter(|_| project.read(cx).is_remote())
+        else {
+            return Task::ready(Err(anyhow!(
+                "remote items can only be opened in a remote project"
+            )));
+        };
+        let request = match serde_json::to_string(&request) {
+            Ok(request) => request,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+
+        let client = self.app_state.client.clone();
+        let store = self.app_state.workspace_store.clone();
+        cx.spawn(|this, mut cx| async move {
+            let response = client
+                .request(proto::OpenRemoteItem {
+                    project_id,
+                    kind: <I::Source as RemoteItemSource>::KIND.to_string(),
+                    request: request.clone(),
+                })
+                .await?;
+            let state = serde_json::from_str(&response.state)?;
+            this.update(&mut cx, |this, cx| {
+                let item = cx.new_view(|cx| build_item(state, cx));
+                let window = cx.window_handle();
+                store.update(cx, |store, cx| {
+                    store.add_opened_remote_item(
+                        &item,
+                        window,
+                        &project,
+                        project_id,
+                        response.item_id,
+                        request,
+                        cx,
+                    )
+                });
+                this.add_item(Box::new(item.clone()), cx);
+                item
+            })
+        })
+    }
+}
+
+impl WorkspaceStore {
+    pub(crate) async fn handle_open_remote_item(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::OpenRemoteItem>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::OpenRemoteItemResponse> {
+        let peer_id = envelope.original_sender_id()?;
+        let request = envelope.payload;
+        this.update(&mut cx, |this, cx| {
+            let project = this
+                .shared_project(request.project_id, cx)
+                .context("project not found")?;
+            let build_source = cx
+                .try_global::<RemoteItemSourceBuilders>()
+                .and_then(|builders| builders.get(request.kind.as_str()).copied())
+                .ok_or_else(|| anyhow!("unknown remote item kind {:?}", request.kind))?;
+
+            let item_id = post_inc(&mut this.remote_items.next_item_id);
+            let source = build_source(project.clone(), &request.request, item_id, cx)?;
+            let state = (source.state)(cx)?;
+            this.subscribe_to_remote_item_project(&project, cx);
+            this.remote_items.hosted.insert(
+                item_id,
+                HostedItem {
+                    project: project.downgrade(),
+                    project_id: request.project_id,
+                    peer_id,
+                    source,
+                },
+            );
+            Ok(proto::OpenRemoteItemResponse { item_id, state })
+        })?
+    }
+
+    pub(crate) async fn handle_update_remote_item(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateRemoteItem>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let update = envelope.payload;
+        this.update(&mut cx, |this, cx| {
+            let item = this.remote_items.opened.iter().find(|item| {
+                item.project_id == update.project_id && item.item_id == update.item_id
+            });
+            if let Some(item) = item {
+                (item.set_state)(&update.state, cx)?;
+            }
+            Ok(())
+        })?
+    }
+
+    pub(crate) async fn handle_close_remote_item(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CloseRemoteItem>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let peer_id = envelope.original_sender_id()?;
+        let item_id = envelope.payload.item_id;
+        this.update(&mut cx, |this, _| {
+            let hosted = &mut this.remote_items.hosted;
+            if hosted
+                .get(&item_id)
+                .map_or(false, |item| item.peer_id == peer_id)
+            {
+                hosted.remove(&item_id);
+            }
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    fn shared_project(&self, project_id: u64, cx: &mut AppContext) -> Option<Model<Project>> {
+        self.workspaces.iter().find_map(|workspace| {
+            workspace
+                .update(cx, |workspace, cx| {
+                    let project = workspace.project();
+                    let is_shared = project.read(cx).is_local()
+                        && project.read(cx).remote_id() == Some(project_id);
+                    is_shared.then(|| project.clone())
+                })
+                .ok()
+                .flatten()
+        })
+    }
+
+    fn send_remote_item_state(&mut self, item_id: u64, cx: &mut ModelContext<Self>) {
+        let Some(item) = self.remote_items.hosted.get(&item_id) else {
+            return;
+        };
+        if let Some(state) = (item.source.state)(cx).log_err() {
+            self.client
+                .send(proto::UpdateRemoteItem {
+                    project_id: item.project_id,
+                    peer_id: Some(item.peer_id),
+                    item_id,
+                    state,
+                })
+                .log_err();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_opened_remote_item<I: RemoteItem>(
+        &mut self,
+        item: &View<I>,
+        window: AnyWindowHandle,
+        project: &Model<Project>,
+        project_id: u64,
+        item_id: u64,
+        request: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let view = item.downgrade();
+        let view_id = item.entity_id();
+        self.remote_items.opened.push(OpenedItem {
+            view_id,
+            project: project.downgrade(),
+            project_id,
+            item_id,
+            kind: <I::Source as RemoteItemSource>::KIND,
+            request,
+            set_state: Box::new(move |state: &str, cx: &mut AppContext| -> Result<()> {
+                let state = serde_json::from_str(state)?;
+                window.update(cx, |_, cx| {
+                    view.update(cx, |item, cx| item.set_remote_state(state, cx))
+                })??;
+                Ok(())
+            }),
+        });
+        self.subscribe_to_remote_item_project(project, cx);
+        cx.observe_release(item, move |this, _, cx| {
+            if let Some(ix) = this
+                .remote_items
+                .opened
+                .iter()
+                .position(|item| item.view_id == view_id)
+            {
+                let item = this.remote_items.opened.remove(ix);
+                this.close_remote_item(item.project_id, item.item_id, cx);
+            }
+        })
+        .detach();
+    }
+
+    fn close_remote_item(&self, project_id: u64, item_id: u64, cx: &mut ModelContext<Self>) {
+        let request = self.client.request(proto::CloseRemoteItem {
+            project_id,
+            item_id,
+        });
+        cx.spawn(|_, _| async move { request.await.log_err() })
+            .detach();
+    }
+
+    fn subscribe_to_remote_item_project(
+        &mut self,
+        project: &Model<Project>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.remote_items
+            .project_subscriptions
+            .entry(project.entity_id())
+            .or_insert_with(|| cx.subscribe(project, Self::handle_remote_item_project_event));
+    }
+
+    fn handle_remote_item_project_event(
+        &mut self,
+        project: Model<Project>,
+        event: &project::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let weak_project = project.downgrade();
+        match event {
+            project::Event::Rejoined => self.reopen_remote_items(&project, cx),
+            project::Event::CollaboratorLeft(peer_id)
+            | project::Event::CollaboratorUpdated {
+                old_peer_id: peer_id,
+                ..
+            } => {
+                // A guest that reconnects reopens its items, so the sources
+                // serving its old connection aren't needed anymore.
+                self.remote_items
+                    .hosted
+                    .retain(|_, item| item.project != weak_project || item.peer_id != *peer_id);
+            }
+            project::Event::RemoteIdChanged(remote_id) => {
+                self.remote_items.hosted.retain(|_, item| {
+                    item.project != weak_project || Some(item.project_id) == *remote_id
+                });
+            }
+            project::Event::DisconnectedFromHost => {
+                self.remote_items
+                    .opened
+                    .retain(|item| item.project != weak_project);
+            }
+            project::Event::Closed => {
+                self.remote_items
+                    .hosted
+                    .retain(|_, item| item.project != weak_project);
+                self.remote_items
+                    .opened
+                    .retain(|item| item.project != weak_project);
+                self.remote_items
+                    .project_subscriptions
+                    .remove(&project.entity_id());
+            }
+            _ => {}
+        }
+    }
+
+    fn reopen_remote_items(&mut self, project: &Model<Project>, cx: &mut ModelContext<Self>) {
+        let Some(project_id) = project.read(cx).remote_id() else {
+            return;
+        };
+        let weak_project = project.downgrade();
+        let (items, opened) = std::mem::take(&mut self.remote_items.opened)
+            .into_iter()
+            .partition::<Vec<_>, _>(|item| item.project == weak_project);
+        self.remote_items.opened = opened;
+
+        for mut item in items {
+            let request = self.client.request(proto::OpenRemoteItem {
+                project_id,
+                kind: item.kind.to_string(),
+                request: item.request.clone(),
+            });
+            cx.spawn(|this, mut cx| async move {
+                let response = request.await?;
+                this.update(&mut cx, |this, cx| {
+                    item.project_id = project_id;
+                    item.item_id = response.item_id;
+                    // The item may have been closed while it was reopened.
+                    if (item.set_state)(&response.state, cx).log_err().is_some() {
+                        this.remote_items.opened.push(item);
+                    } else {
+                        this.close_remote_item(project_id, response.item_id, cx);
+                    }
+                })
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+}
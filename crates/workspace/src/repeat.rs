@@ -0,0 +1,112 @@
+use crate::Workspace;
+use gpui::{
+    actions, impl_actions, Action, AppContext, Global, KeystrokeEvent, ViewContext, WindowContext,
+};
+use serde::Deserialize;
+
+actions!(workspace, [RepeatLastAction]);
+
+/// Appends a digit to the count applied to the next action, so that e.g.
+/// `["workspace::CountPrefix", 1]` followed by `["workspace::CountPrefix", 2]`
+/// runs the next action twelve times.
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct CountPrefix(pub usize);
+
+impl_actions!(workspace, [CountPrefix]);
+
+/// Counts are capped so that a mistyped prefix can't stall the app.
+const MAX_COUNT: usize = 999;
+
+#[derive(Default)]
+struct ActionRepeat {
+    /// The last action run by a keystroke, for `RepeatLastAction`.
+    last_action: Option<Box<dyn Action>>,
+    /// The count typed so far, if any.
+    count: Option<usize>,
+    /// The action bound to the most recent keystroke, and the actions that
+    /// keystroke actually ran once counts and repeats were applied.
+    last_dispatch: Option<(Box<dyn Action>, Vec<Box<dyn Action>>)>,
+}
+
+impl Global for ActionRepeat {}
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(ActionRepeat::default());
+    cx.observe_keystrokes(observe_keystrokes).detach();
+}
+
+/// Returns the actions run by the most recent keystroke bound to `action`,
+/// with its count prefix applied and `RepeatLastAction` expanded. Anything
+/// that records keystrokes for replay should record these instead, so that
+/// replaying doesn't depend on the count or last action at replay time.
+pub fn dispatched_actions(action: &dyn Action, cx: &AppContext) -> Vec<Box<dyn Action>> {
+    cx.try_global::<ActionRepeat>()
+        .and_then(|state| state.last_dispatch.as_ref())
+        .filter(|(keystroke_action, _)| keystroke_action.partial_eq(action))
+        .map(|(_, actions)| actions.iter().map(|action| action.boxed_clone()).collect())
+        .unwrap_or_else(|| vec![action.boxed_clone()])
+}
+
+pub(crate) fn count_prefix(
+    _: &mut Workspace,
+    action: &CountPrefix,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let state = cx.default_global::<ActionRepeat>();
+    let count = state.count.unwrap_or(0) * 10 + action.0 % 10;
+    state.count = Some(count.min(MAX_COUNT));
+}
+
+pub(crate) fn repeat_last_action(
+    _: &mut Workspace,
+    action: &RepeatLastAction,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let state = cx.default_global::<ActionRepeat>();
+    let count = state.count.take().unwrap_or(1);
+    let actions = state
+        .last_action
+        .iter()
+        .flat_map(|last_action| (0..count).map(|_| last_action.boxed_clone()))
+        .collect::<Vec<_>>();
+    state.last_dispatch = Some((
+        action.boxed_clone(),
+        actions.iter().map(|action| action.boxed_clone()).collect(),
+    ));
+
+    for action in actions {
+        cx.dispatch_action(action);
+    }
+}
+
+fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext) {
+    let Some(action) = keystroke_event.action.as_ref() else {
+        // A count only applies to the action bound to the next keystroke.
+        if !cx.has_pending_keystrokes() {
+            let state = cx.default_global::<ActionRepeat>();
+            state.count = None;
+            state.last_dispatch = None;
+        }
+        return;
+    };
+
+    let state = cx.default_global::<ActionRepeat>();
+    if action.as_any().is::<RepeatLastAction>() {
+        return;
+    }
+    if action.as_any().is::<CountPrefix>() {
+        state.last_dispatch = Some((action.boxed_clone(), Vec::new()));
+        return;
+    }
+
+    // The keystroke already ran the action once.
+    let count = state.count.take().unwrap_or(1);
+    state.last_action = Some(action.boxed_clone());
+    state.last_dispatch = Some((
+        action.boxed_clone(),
+        (0..count).map(|_| action.boxed_clone()).collect(),
+    ));
+    for _ in 1..count {
+        cx.dispatch_action(action.boxed_clone());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::Workspace;
+use gpui::{
+    actions, AnyWindowHandle, Keystroke, KeystrokeEvent, Render, Subscription, Task, ViewContext,
+    WeakView, WindowContext,
+};
+use std::{collections::VecDeque, time::Duration};
+use ui::prelude::*;
+
+actions!(workspace, [ToggleScreencastMode]);
+
+/// How many keystrokes and actions the overlay shows at once.
+const MAX_ENTRIES: usize = 6;
+
+/// How long the overlay stays up after the last keystroke.
+const HIDE_AFTER: Duration = Duration::from_secs(3);
+
+/// An overlay showing the keystrokes typed in a window and the actions they
+/// ran, for presenting and pairing.
+pub struct ScreencastOverlay {
+    workspace: WeakView<Workspace>,
+    window: AnyWindowHandle,
+    entries: VecDeque<ScreencastEntry>,
+    _hide: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+struct ScreencastEntry {
+    keystrokes: String,
+    action: Option<SharedString>,
+    /// Whether the entry is plain typed text that later text can be appended to.
+    is_text: bool,
+}
+
+pub(crate) fn toggle_screencast_mode(
+    workspace: &mut Workspace,
+    _: &ToggleScreencastMode,
+    cx: &mut ViewContext<Workspace>,
+) {
+    if workspace.screencast_overlay.take().is_none() {
+        let workspace_handle = cx.view().downgrade();
+        workspace.screencast_overlay =
+            Some(cx.new_view(|cx| ScreencastOverlay::new(workspace_handle, cx)));
+    }
+    cx.notify();
+}
+
+impl ScreencastOverlay {
+    fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        let this = cx.view().downgrade();
+        let subscription = cx.observe_keystrokes(move |event, cx| {
+            this.update(cx, |this, cx| this.observe_keystroke(event, cx))
+                .ok();
+        });
+        Self {
+            workspace,
+            window: cx.window_handle(),
+            entries: VecDeque::new(),
+            _hide: None,
+            _subscription: subscription,
+        }
+    }
+
+    fn observe_keystroke(&mut self, event: &KeystrokeEvent, cx: &mut ViewContext<Self>) {
+        if cx.window_handle() != self.window {
+            return;
+        }
+
+        let text = if event.action.is_none() && !cx.has_pending_keystrokes() {
+            typed_text(&event.keystroke)
+        } else {
+            None
+        };
+        if text.is_some() && !self.may_show_typed_text(cx) {
+            return;
+        }
+
+        let appended = match (&text, self.entries.back_mut()) {
+            (Some(text), Some(last)) if last.is_text => {
+                last.keystrokes.push_str(text);
+                true
+            }
+            _ => false,
+        };
+        if !appended {
+            self.entries.push_back(ScreencastEntry {
+                is_text: text.is_some(),
+                keystrokes: text.unwrap_or_else(|| event.keystroke.to_string()),
+                action: event
+                    .action
+                    .as_ref()
+                    .map(|action| action.name().to_string().into()),
+            });
+            if self.entries.len() > MAX_ENTRIES {
+                self.entries.pop_front();
+            }
+        }
+
+        self._hide = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(HIDE_AFTER).await;
+            this.update(&mut cx, |this, cx| {
+                this.entries.clear();
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn entries(&self) -> Vec<(String, Option<String>)> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.keystrokes.clone(),
+                    entry.action.as_ref().map(ToString::to_string),
+                )
+            })
+            .collect()
+    }
+
+    /// Typed text may be a secret, so it is only shown when it goes into the
+    /// focused item of the center pane, that item allows it, and it isn't a
+    /// private file such as a `.env` file. Text typed into terminals, panels
+    /// and modals is never shown.
+    fn may_show_typed_text(&self, cx: &WindowContext) -> bool {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return false;
+        };
+        let workspace = workspace.read(cx);
+        let Some(item) = workspace.active_item(cx) else {
+            return false;
+        };
+        if !item.focus_handle(cx).contains_focused(cx) || !item.shows_typed_text(cx) {
+            return false;
+        }
+        !item
+            .project_path(cx)
+            .and_then(|project_path| {
+                workspace
+                    .project()
+                    .read(cx)
+                    .entry_for_path(&project_path, cx)
+            })
+            .map_or(false, |entry| entry.is_private)
+    }
+}
+
+impl Render for ScreencastOverlay {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.entries.is_empty() {
+            return div();
+        }
+
+        let colors = cx.theme().colors();
+        div()
+            .absolute()
+            .z_index(100)
+            .left_0()
+            .right_0()
+            .bottom_8()
+            .flex()
+            .justify_center()
+            .child(
+                h_flex()
+                    .gap_3()
+                    .px_3()
+                    .py_2()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(colors.border)
+                    .bg(colors.elevated_surface_background)
+                    .shadow_lg()
+                    .children(self.entries.iter().map(|entry| {
+                        v_flex()
+                            .items_center()
+                            .child(Label::new(entry.keystrokes.clone()).size(LabelSize::Large))
+                            .children(entry.action.clone().map(|action| {
+                                Label::new(action)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                            }))
+                    })),
+            )
+    }
+}
+
+/// Returns the text a keystroke types when it isn't bound to an action.
+fn typed_text(keystroke: &Keystroke) -> Option<String> {
+    let modifiers = &keystroke.modifiers;
+    if modifiers.control || modifiers.alt || modifiers.command || modifiers.function {
+        return None;
+    }
+    if let Some(ime_key) = &keystroke.ime_key {
+        return Some(ime_key.clone());
+    }
+    match keystroke.key.as_str() {
+        "space" => Some(" ".to_string()),
+        key if key.chars().count() == 1 => Some(key.to_string()),
+        _ => None,
+    }
+}
@@ -5,6 +5,10 @@ pub mod notifications;
 pub mod pane;
 pub mod pane_group;
 mod persistence;
+mod presence;
+pub mod remote_item;
+mod repeat;
+mod screencast;
 pub mod searchable;
 pub mod shared_screen;
 mod status_bar;
@@ -49,7 +53,10 @@ pub use persistence::{
     WorkspaceDb, DB as WORKSPACE_DB,
 };
 use postage::stream::Stream;
+pub use presence::{collaborator_colors_at_path, render_presence_dots};
 use project::{Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId};
+pub use repeat::{dispatched_actions, CountPrefix, RepeatLastAction};
+pub use screencast::{ScreencastOverlay, ToggleScreencastMode};
 use serde::Deserialize;
 use settings::Settings;
 use shared_screen::SharedScreen;
@@ -75,7 +82,7 @@ pub use ui;
 use ui::Label;
 use util::ResultExt;
 use uuid::Uuid;
-pub use workspace_settings::{AutosaveSetting, WorkspaceSettings};
+pub use workspace_settings::{AutosaveSetting, ExistingItemScope, WorkspaceSettings};
 
 use crate::persistence::{
     model::{DockData, DockStructure, SerializedItem, SerializedPane, SerializedPaneGroup},
@@ -122,9 +129,12 @@ actions!(
         ToggleBottomDock,
         CloseAllDocks,
         ToggleGraphicsProfiler,
+        ShowInputLatency,
     ]
 );
 
+const INPUT_LATENCY_TOAST_ID: usize = 0x1a7e9c5;
+
 #[derive(Clone, PartialEq)]
 pub struct OpenPaths {
     pub paths: Vec<PathBuf>,
@@ -139,6 +149,9 @@ pub struct ActivatePaneInDirection(pub SplitDirection);
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct SwapPaneInDirection(pub SplitDirection);
 
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct MoveItemToPaneInDirection(pub SplitDirection);
+
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct NewFileInDirection(pub SplitDirection);
 
@@ -176,6 +189,7 @@ impl_actions!(
         ActivatePaneInDirection,
         CloseAllItemsAndPanes,
         CloseInactiveTabsAndPanes,
+        MoveItemToPaneInDirection,
         NewFileInDirection,
         OpenTerminal,
         Save,
@@ -245,6 +259,7 @@ pub fn init_settings(cx: &mut AppContext) {
 pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
     init_settings(cx);
     notifications::init(cx);
+    repeat::init(cx);
 
     cx.on_action(Workspace::close_global);
     cx.on_action(restart);
@@ -373,6 +388,7 @@ impl Global for GlobalAppState {}
 pub struct WorkspaceStore {
     workspaces: HashSet<WindowHandle<Workspace>>,
     client: Arc<Client>,
+    remote_items: remote_item::RemoteItems,
     _subscriptions: Vec<client::Subscription>,
 }
 
@@ -506,6 +522,7 @@ pub struct Workspace {
     modal_layer: View<ModalLayer>,
     titlebar_item: Option<AnyView>,
     notifications: Vec<(TypeId, usize, Box<dyn NotificationHandle>)>,
+    screencast_overlay: Option<View<ScreencastOverlay>>,
     project: Model<Project>,
     follower_states: HashMap<View<Pane>, FollowerState>,
     last_leaders_by_pane: HashMap<WeakView<Pane>, PeerId>,
@@ -583,10 +600,74 @@ impl Workspace {
                     }
                 }
 
+                project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                    // A renamed entry is removed from its old path and added at its new one.
+                    let added_paths = changes
+                        .iter()
+                        .filter(|(_, _, change)| {
+                            matches!(
+                                change,
+                                project::PathChange::Added | project::PathChange::AddedOrUpdated
+                            )
+                        })
+                        .map(|(path, entry_id, _)| (*entry_id, path.clone()))
+                        .collect::<HashMap<_, _>>();
+                    let mut renamed_paths = Vec::new();
+                    let mut deleted_paths = Vec::new();
+                    for (path, entry_id, change) in changes.iter() {
+                        if *change != project::PathChange::Removed {
+                            continue;
+                        }
+                        let old_path = ProjectPath {
+                            worktree_id: *worktree_id,
+                            path: path.clone(),
+                        };
+                        match added_paths.get(entry_id) {
+                            Some(new_path) => renamed_paths.push((
+                                old_path,
+                                ProjectPath {
+                                    worktree_id: *worktree_id,
+                                    path: new_path.clone(),
+                                },
+                            )),
+                            None => deleted_paths.push(old_path),
+                        }
+                    }
+                    if !renamed_paths.is_empty() || !deleted_paths.is_empty() {
+                        for pane in this.panes.iter() {
+                            pane.update(cx, |pane, _| {
+                                let open_item_ids = pane
+                                    .items()
+                                    .map(|item| item.item_id())
+                                    .collect::<HashSet<_>>();
+                                pane.nav_history_mut().update_closed_item_paths(
+                                    &renamed_paths,
+                                    &deleted_paths,
+                                    &open_item_ids,
+                                )
+                            });
+                        }
+                    }
+                }
+
                 project::Event::Notification(message) => this.show_notification(0, cx, |cx| {
                     cx.new_view(|_| MessageNotification::new(message.clone()))
                 }),
 
+                project::Event::JoinRequested {
+                    request_id,
+                    requester_id,
+                } => {
+                    this.prompt_for_join_request(*request_id, *requester_id, cx);
+                }
+
+                project::Event::JoinRequestCancelled { request_id } => {
+                    this.dismiss_notification::<notifications::JoinRequestPrompt>(
+                        *request_id as usize,
+                        cx,
+                    );
+                }
+
                 project::Event::LanguageServerPrompt(request) => {
                     let mut hasher = DefaultHasher::new();
                     request.message.as_str().hash(&mut hasher);
@@ -753,6 +834,7 @@ impl Workspace {
             modal_layer,
             titlebar_item: None,
             notifications: Default::default(),
+            screencast_overlay: None,
             left_dock,
             bottom_dock,
             right_dock,
@@ -1441,6 +1523,20 @@ impl Workspace {
             let mut tasks = Vec::with_capacity(abs_paths.len());
 
             for abs_path in &abs_paths {
+                // Open files reached through a symlink or with a different casing under the
+                // worktree that already contains them, so they aren't opened twice.
+                let worktree_abs_paths = this
+                    .update(&mut cx, |this, cx| {
+                        this.project
+                            .read(cx)
+                            .worktrees()
+                            .filter_map(|worktree| {
+                                Some(worktree.read(cx).as_local()?.abs_path().clone())
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let abs_path = &resolve_abs_path(abs_path, &worktree_abs_paths, fs.as_ref()).await;
                 let visible = match visible {
                     OpenVisible::All => Some(true),
                     OpenVisible::None => Some(false),
@@ -1979,7 +2075,23 @@ impl Workspace {
             })
         });
 
-        let task = self.load_path(path.into(), cx);
+        let path = path.into();
+        if let Some((existing_pane, item)) = self
+            .project
+            .read(cx)
+            .entry_for_path(&path, cx)
+            .zip(pane.upgrade())
+            .and_then(|(entry, pane)| self.existing_item_for_entry(entry.id, &pane, cx))
+        {
+            existing_pane.update(cx, |pane, cx| {
+                if let Some(index) = pane.index_for_item(item.as_ref()) {
+                    pane.activate_item(index, focus_item, focus_item, cx);
+                }
+            });
+            return Task::ready(Ok(item));
+        }
+
+        let task = self.load_path(path, cx);
         cx.spawn(move |mut cx| async move {
             let (project_entry_id, build_item) = task.await?;
             pane.update(&mut cx, |pane, cx| {
@@ -1988,6 +2100,26 @@ impl Workspace {
         })
     }
 
+    /// Finds an open item for the given entry, preferring the given pane and then looking in
+    /// the other panes if the [`ExistingItemScope`] setting allows it.
+    fn existing_item_for_entry(
+        &self,
+        entry_id: ProjectEntryId,
+        pane: &View<Pane>,
+        cx: &AppContext,
+    ) -> Option<(View<Pane>, Box<dyn ItemHandle>)> {
+        if let Some(item) = pane.read(cx).item_for_entry(entry_id, cx) {
+            return Some((pane.clone(), item));
+        }
+        match WorkspaceSettings::get_global(cx).existing_item_scope {
+            ExistingItemScope::Pane => None,
+            ExistingItemScope::Workspace => self.panes.iter().find_map(|pane| {
+                let item = pane.read(cx).item_for_entry(entry_id, cx)?;
+                Some((pane.clone(), item))
+            }),
+        }
+    }
+
     pub fn split_path(
         &mut self,
         path: impl Into<ProjectPath>,
@@ -2058,8 +2190,8 @@ impl Workspace {
 
         let entry_id = project_item.read(cx).entry_id(cx);
         if let Some(item) = entry_id
-            .and_then(|entry_id| self.active_pane().read(cx).item_for_entry(entry_id, cx))
-            .and_then(|item| item.downcast())
+            .and_then(|entry_id| self.existing_item_for_entry(entry_id, &self.active_pane, cx))
+            .and_then(|(_, item)| item.downcast())
         {
             self.activate_item(&item, cx);
             return item;
@@ -2082,8 +2214,8 @@ impl Workspace {
 
         let entry_id = project_item.read(cx).entry_id(cx);
         if let Some(item) = entry_id
-            .and_then(|entry_id| self.active_pane().read(cx).item_for_entry(entry_id, cx))
-            .and_then(|item| item.downcast())
+            .and_then(|entry_id| self.existing_item_for_entry(entry_id, &self.active_pane, cx))
+            .and_then(|(_, item)| item.downcast())
         {
             self.activate_item(&item, cx);
             return item;
@@ -2283,6 +2415,29 @@ impl Workspace {
         }
     }
 
+    /// Moves the active item to the pane in the given direction, the way dragging its tab
+    /// there would. If there's no pane in that direction, the active pane is split.
+    pub fn move_item_to_pane_in_direction(
+        &mut self,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let source = self.active_pane.clone();
+        let Some(item_id) = source.read(cx).active_item().map(|item| item.item_id()) else {
+            return;
+        };
+        let destination = match self.find_pane_in_direction(direction, cx) {
+            Some(pane) => pane,
+            // Splitting off a pane's only item would leave the layout unchanged.
+            None if source.read(cx).items_len() > 1 => {
+                self.split_pane(source.clone(), direction, cx)
+            }
+            None => return,
+        };
+        let destination_index = destination.read(cx).active_item_index() + 1;
+        self.move_item(source, destination, item_id, destination_index, cx);
+    }
+
     fn handle_pane_focused(&mut self, pane: View<Pane>, cx: &mut ViewContext<Self>) {
         if self.active_pane != pane {
             self.active_pane = pane.clone();
@@ -2504,6 +2659,47 @@ impl Workspace {
         weak_pane.upgrade()
     }
 
+    fn prompt_for_join_request(
+        &mut self,
+        request_id: u64,
+        requester_id: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let requester = self
+            .app_state
+            .user_store
+            .update(cx, |user_store, cx| user_store.get_user(requester_id, cx));
+        cx.spawn(|this, mut cx| async move {
+            let requester = requester.await;
+            this.update(&mut cx, |this, cx| {
+                let project = this.project.clone();
+                // The request may have been answered elsewhere or timed out while the
+                // requester was being looked up.
+                if !project.read(cx).has_pending_join_request(request_id) {
+                    return;
+                }
+                match requester {
+                    Ok(requester) => {
+                        this.show_notification(request_id as usize, cx, |cx| {
+                            cx.new_view(|_| {
+                                notifications::JoinRequestPrompt::new(
+                                    project,
+                                    request_id,
+                                    requester.github_login.clone().into(),
+                                )
+                            })
+                        });
+                    }
+                    // Turn the guest away if they couldn't be asked about.
+                    Err(_) => project.update(cx, |project, _| {
+                        project.respond_to_join_request(request_id, false)
+                    }),
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn collaborator_left(&mut self, peer_id: PeerId, cx: &mut ViewContext<Self>) {
         self.follower_states.retain(|_, state| {
             if state.leader_id == peer_id {
@@ -3304,7 +3500,18 @@ impl Workspace {
         }));
     }
 
+    /// Serializes the workspace now, rather than after a pending delay. The
+    /// returned task completes once the workspace is written to the database.
+    pub fn flush_serialization(&mut self, cx: &mut WindowContext) -> Task<()> {
+        self._schedule_serialize.take();
+        self.serialize_workspace_internal(cx)
+    }
+
     fn serialize_workspace(&self, cx: &mut WindowContext) {
+        self.serialize_workspace_internal(cx).detach();
+    }
+
+    fn serialize_workspace_internal(&self, cx: &mut WindowContext) -> Task<()> {
         fn serialize_pane_handle(pane_handle: &View<Pane>, cx: &WindowContext) -> SerializedPane {
             let (items, active) = {
                 let pane = pane_handle.read(cx);
@@ -3417,10 +3624,10 @@ impl Workspace {
                     docks,
                 };
 
-                cx.spawn(|_| persistence::DB.save_workspace(serialized_workspace))
-                    .detach();
+                return cx.spawn(|_| persistence::DB.save_workspace(serialized_workspace));
             }
         }
+        Task::ready(())
     }
 
     pub(crate) fn load_workspace(
@@ -3516,6 +3723,9 @@ impl Workspace {
             .on_action(cx.listener(Self::close_all_items_and_panes))
             .on_action(cx.listener(Self::save_all))
             .on_action(cx.listener(Self::send_keystrokes))
+            .on_action(cx.listener(repeat::count_prefix))
+            .on_action(cx.listener(repeat::repeat_last_action))
+            .on_action(cx.listener(screencast::toggle_screencast_mode))
             .on_action(cx.listener(Self::add_folder_to_project))
             .on_action(cx.listener(Self::follow_next_collaborator))
             .on_action(cx.listener(|workspace, _: &Unfollow, cx| {
@@ -3546,6 +3756,11 @@ impl Workspace {
             .on_action(cx.listener(|workspace, action: &SwapPaneInDirection, cx| {
                 workspace.swap_pane_in_direction(action.0, cx)
             }))
+            .on_action(
+                cx.listener(|workspace, action: &MoveItemToPaneInDirection, cx| {
+                    workspace.move_item_to_pane_in_direction(action.0, cx)
+                }),
+            )
             .on_action(cx.listener(|this, _: &ToggleLeftDock, cx| {
                 this.toggle_dock(DockPosition::Left, cx);
             }))
@@ -3573,6 +3788,23 @@ impl Workspace {
                 }),
             )
             .on_action(|_: &ToggleGraphicsProfiler, cx| cx.toggle_graphics_profiler())
+            .on_action(cx.listener(Workspace::show_input_latency))
+    }
+
+    fn show_input_latency(&mut self, _: &ShowInputLatency, cx: &mut ViewContext<Self>) {
+        let latency = cx.input_latency();
+        let message = if latency.samples == 0 {
+            "No keystrokes have been measured yet".to_string()
+        } else {
+            format!(
+                "Keystroke to screen latency over {} keystrokes: mean {:?}, max {:?}, last {:?}",
+                latency.samples,
+                latency.mean(),
+                latency.max,
+                latency.last
+            )
+        };
+        self.show_toast(Toast::new(INPUT_LATENCY_TOAST_ID, message), cx);
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -3657,6 +3889,25 @@ fn window_bounds_env_override(cx: &AsyncAppContext) -> Option<WindowBounds> {
         })
 }
 
+async fn resolve_abs_path(
+    abs_path: &Path,
+    worktree_abs_paths: &[Arc<Path>],
+    fs: &dyn fs::Fs,
+) -> PathBuf {
+    let is_in_worktree = |path: &Path| {
+        worktree_abs_paths
+            .iter()
+            .any(|worktree_path| path.starts_with(worktree_path))
+    };
+    if is_in_worktree(abs_path) {
+        return abs_path.to_path_buf();
+    }
+    match fs.canonicalize(abs_path).await {
+        Ok(canonical_path) if is_in_worktree(&canonical_path) => canonical_path,
+        _ => abs_path.to_path_buf(),
+    }
+}
+
 fn open_items(
     serialized_workspace: Option<SerializedWorkspace>,
     mut project_paths_to_open: Vec<(PathBuf, Option<ProjectPath>)>,
@@ -3912,6 +4163,7 @@ impl Render for Workspace {
                             )),
                     )
                     .children(self.render_notifications(cx))
+                    .children(self.screencast_overlay.clone())
                     .children(self.zoomed.as_ref().and_then(|view| {
                         let zoomed_view = view.upgrade()?;
                         let div = div()
@@ -3948,8 +4200,12 @@ impl WorkspaceStore {
             _subscriptions: vec![
                 client.add_request_handler(cx.weak_model(), Self::handle_follow),
                 client.add_message_handler(cx.weak_model(), Self::handle_update_followers),
+                client.add_request_handler(cx.weak_model(), Self::handle_open_remote_item),
+                client.add_message_handler(cx.weak_model(), Self::handle_update_remote_item),
+                client.add_request_handler(cx.weak_model(), Self::handle_close_remote_item),
             ],
             client,
+            remote_items: Default::default(),
         }
     }
 
@@ -4672,7 +4928,7 @@ mod tests {
         },
     };
     use fs::FakeFs;
-    use gpui::{px, DismissEvent, TestAppContext, VisualTestContext};
+    use gpui::{px, DismissEvent, KeyBinding, TestAppContext, VisualTestContext};
     use project::{Project, ProjectEntryId};
     use serde_json::json;
     use settings::SettingsStore;
@@ -5185,6 +5441,142 @@ mod tests {
         item.update(cx, |item, _| assert_eq!(item.save_count, 5));
     }
 
+    #[gpui::test]
+    async fn test_autosave_skips_files_changed_on_disk(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "1.txt": "one" })).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let project_path = project.update(cx, |project, cx| {
+            let worktree_id = project.worktrees().next().unwrap().read(cx).id();
+            ProjectPath {
+                worktree_id,
+                path: Path::new("1.txt").into(),
+            }
+        });
+        let entry_id = project.update(cx, |project, cx| {
+            project.entry_for_path(&project_path, cx).unwrap().id
+        });
+        let item = cx.new_view(|cx| {
+            let project_item = cx.new_model(|_| TestProjectItem {
+                entry_id: Some(entry_id),
+                project_path: Some(project_path),
+            });
+            TestItem::new(cx).with_project_items(&[project_item])
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item.clone()), cx);
+        });
+        item.update(cx, |item, cx| {
+            cx.update_global(|settings: &mut SettingsStore, cx| {
+                settings.update_user_settings::<WorkspaceSettings>(cx, |settings| {
+                    settings.autosave = Some(AutosaveSetting::OnFocusChange);
+                })
+            });
+            cx.focus_self();
+            item.is_dirty = true;
+        });
+
+        // The file is saved when it hasn't changed on disk.
+        item.update(cx, |_, cx| cx.blur());
+        cx.executor().run_until_parked();
+        item.update(cx, |item, _| assert_eq!(item.save_count, 1));
+
+        // Modify the file without letting the worktree observe the change yet.
+        fs.pause_events();
+        fs.insert_file("/root/1.txt", "changed externally".into())
+            .await;
+        item.update(cx, |item, cx| {
+            cx.focus_self();
+            item.is_dirty = true;
+        });
+        item.update(cx, |_, cx| cx.blur());
+        cx.executor().run_until_parked();
+        item.update(cx, |item, _| assert_eq!(item.save_count, 1));
+    }
+
+    #[gpui::test]
+    async fn test_notification_for_conflicting_item(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let item = cx.new_view(|cx| {
+            TestItem::new(cx)
+                .with_dirty(true)
+                .with_project_items(&[TestProjectItem::new(1, "1.txt", cx)])
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item.clone()), cx);
+            assert!(workspace.notifications.is_empty());
+        });
+
+        // The file changes on disk while the item has unsaved edits.
+        item.update(cx, |item, cx| {
+            item.has_conflict = true;
+            cx.emit(ItemEvent::UpdateTab);
+        });
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(workspace.notifications.len(), 1);
+        });
+
+        // Further updates don't show the notification again.
+        item.update(cx, |_, cx| cx.emit(ItemEvent::UpdateTab));
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(workspace.notifications.len(), 1);
+        });
+
+        // The notification is dismissed once the conflict is resolved.
+        item.update(cx, |item, cx| {
+            item.has_conflict = false;
+            cx.emit(ItemEvent::UpdateTab);
+        });
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace.notifications.is_empty());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_move_item_to_pane_in_direction(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let left_pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+
+        // A pane's only item isn't split off into a new pane.
+        let item1 = cx.new_view(|cx| TestItem::new(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item1.clone()), cx);
+            workspace.move_item_to_pane_in_direction(SplitDirection::Right, cx);
+            assert_eq!(workspace.panes().len(), 1);
+        });
+
+        let item2 = cx.new_view(|cx| TestItem::new(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item2.clone()), cx);
+            workspace.move_item_to_pane_in_direction(SplitDirection::Right, cx);
+            assert_eq!(workspace.panes().len(), 2);
+        });
+        workspace.update(cx, |workspace, cx| {
+            assert_ne!(workspace.active_pane(), &left_pane);
+            let right_pane = workspace.active_pane().read(cx);
+            assert_eq!(right_pane.items_len(), 1);
+            assert_eq!(right_pane.active_item().unwrap().item_id(), item2.item_id());
+            assert_eq!(left_pane.read(cx).items_len(), 1);
+            assert_eq!(
+                left_pane.read(cx).active_item().unwrap().item_id(),
+                item1.item_id()
+            );
+        });
+    }
+
     #[gpui::test]
     async fn test_pane_navigation(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -5239,6 +5631,95 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_navigation_history_follows_renamed_and_deleted_paths(
+        cx: &mut gpui::TestAppContext,
+    ) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "dir": { "1.txt": "" },
+                "2.txt": "",
+                "3.txt": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let new_item = |path: &str, cx: &mut VisualTestContext| {
+            let project_path = project.update(cx, |project, cx| {
+                let worktree = project.worktrees().next().unwrap();
+                ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: Path::new(path).into(),
+                }
+            });
+            let entry_id = project.update(cx, |project, cx| {
+                project.entry_for_path(&project_path, cx).unwrap().id
+            });
+            cx.new_view(|cx| {
+                let project_item = cx.new_model(|_| TestProjectItem {
+                    entry_id: Some(entry_id),
+                    project_path: Some(project_path),
+                });
+                TestItem::new(cx).with_project_items(&[project_item])
+            })
+        };
+        let item_1 = new_item("dir/1.txt", cx);
+        let item_2 = new_item("2.txt", cx);
+        let item_3 = new_item("3.txt", cx);
+        let pane = workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item_1.clone()), cx);
+            workspace.add_item(Box::new(item_2.clone()), cx);
+            workspace.add_item(Box::new(item_3.clone()), cx);
+            workspace.active_pane().clone()
+        });
+
+        let item_3_id = item_3.item_id();
+        pane.update(cx, |pane, cx| {
+            pane.close_items(cx, SaveIntent::Close, |item_id| item_id != item_3_id)
+        })
+        .await
+        .unwrap();
+
+        fs.rename(
+            Path::new("/root/dir"),
+            Path::new("/root/renamed"),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        fs.remove_file(Path::new("/root/2.txt"), Default::default())
+            .await
+            .unwrap();
+        fs.remove_file(Path::new("/root/3.txt"), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        pane.update(cx, |pane, _| {
+            let history = pane.nav_history();
+            // A closed item that was renamed is reopened from its new path.
+            assert_eq!(
+                history
+                    .path_for_item(item_1.item_id())
+                    .map(|(project_path, abs_path)| (project_path.path, abs_path)),
+                Some((
+                    Path::new("renamed/1.txt").into(),
+                    Some(PathBuf::from("/root/renamed/1.txt"))
+                ))
+            );
+            // A closed item that was deleted is forgotten.
+            assert_eq!(history.path_for_item(item_2.item_id()), None);
+            // An item that is still open keeps its history.
+            assert!(history.path_for_item(item_3_id).is_some());
+        });
+    }
+
     #[gpui::test]
     async fn test_toggle_docks_and_panels(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -5639,6 +6120,114 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_count_prefix_and_repeat_last_action(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(repeat::init);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        actions!(test_repeat, [Increment]);
+        let count = Rc::new(RefCell::new(0));
+        let item = cx.new_view(|cx| TestItem::new(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item.clone()), cx);
+            item.focus_handle(cx).focus(cx);
+            let count = count.clone();
+            workspace.register_action(move |_, _: &Increment, _| *count.borrow_mut() += 1);
+        });
+        cx.update(|cx| {
+            cx.bind_keys([
+                KeyBinding::new("ctrl-i", Increment, None),
+                KeyBinding::new("ctrl-r", RepeatLastAction, None),
+                KeyBinding::new("alt-1", CountPrefix(1), None),
+                KeyBinding::new("alt-2", CountPrefix(2), None),
+            ])
+        });
+
+        cx.simulate_keystrokes("ctrl-i");
+        assert_eq!(*count.borrow(), 1);
+
+        cx.simulate_keystrokes("alt-1 alt-2 ctrl-i");
+        assert_eq!(*count.borrow(), 13);
+
+        cx.simulate_keystrokes("ctrl-r");
+        assert_eq!(*count.borrow(), 14);
+
+        cx.simulate_keystrokes("alt-2 ctrl-r");
+        assert_eq!(*count.borrow(), 16);
+
+        // A count is dropped by a keystroke that isn't bound to an action.
+        cx.simulate_keystrokes("alt-2 x ctrl-i");
+        assert_eq!(*count.borrow(), 17);
+    }
+
+    #[gpui::test]
+    async fn test_screencast_mode(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        actions!(test_screencast, [Increment]);
+        let item = cx.new_view(|cx| TestItem::new(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(item.clone()), cx);
+            item.focus_handle(cx).focus(cx);
+            workspace.register_action(|_, _: &Increment, _| {});
+        });
+        cx.update(|cx| cx.bind_keys([KeyBinding::new("ctrl-i", Increment, None)]));
+
+        cx.dispatch_action(ToggleScreencastMode);
+        let overlay = workspace.update(cx, |workspace, _| {
+            workspace.screencast_overlay.clone().unwrap()
+        });
+
+        // Consecutive typed characters are shown together.
+        cx.simulate_keystrokes("ctrl-i a b space c ctrl-i");
+        overlay.update(cx, |overlay, _| {
+            assert_eq!(
+                overlay.entries(),
+                [
+                    (
+                        "^I".to_string(),
+                        Some("test_screencast::Increment".to_string())
+                    ),
+                    ("ab c".to_string(), None),
+                    (
+                        "^I".to_string(),
+                        Some("test_screencast::Increment".to_string())
+                    ),
+                ]
+            );
+        });
+
+        // The overlay clears itself once typing stops.
+        cx.executor().advance_clock(Duration::from_secs(5));
+        overlay.update(cx, |overlay, _| assert!(overlay.entries().is_empty()));
+
+        // Text typed anywhere but the focused item isn't shown.
+        cx.update(|cx| cx.blur());
+        cx.simulate_keystrokes("a b ctrl-i");
+        overlay.update(cx, |overlay, _| {
+            assert_eq!(
+                overlay.entries(),
+                [(
+                    "^I".to_string(),
+                    Some("test_screencast::Increment".to_string())
+                )]
+            );
+        });
+
+        cx.dispatch_action(ToggleScreencastMode);
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace.screencast_overlay.is_none())
+        });
+    }
+
     pub fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
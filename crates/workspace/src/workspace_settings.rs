@@ -8,6 +8,7 @@ pub struct WorkspaceSettings {
     pub confirm_quit: bool,
     pub show_call_status_icon: bool,
     pub autosave: AutosaveSetting,
+    pub existing_item_scope: ExistingItemScope,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -30,6 +31,11 @@ pub struct WorkspaceSettingsContent {
     ///
     /// Default: off
     pub autosave: Option<AutosaveSetting>,
+    /// Where to look for an item that is already open when opening a file,
+    /// before opening it again in the active pane.
+    ///
+    /// Default: pane
+    pub existing_item_scope: Option<ExistingItemScope>,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
@@ -45,6 +51,15 @@ pub enum AutosaveSetting {
     OnWindowChange,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingItemScope {
+    /// Only reuse items in the pane the file is opened in.
+    Pane,
+    /// Reuse items in any pane, activating the pane that contains them.
+    Workspace,
+}
+
 impl Settings for WorkspaceSettings {
     const KEY: Option<&'static str> = None;
 
@@ -35,6 +35,7 @@ pub fn app_menus() -> Vec<Menu<'static>> {
             items: vec![
                 MenuItem::action("New", workspace::NewFile),
                 MenuItem::action("New Window", workspace::NewWindow),
+                MenuItem::action("New Project…", project_templates::NewProject),
                 MenuItem::separator(),
                 MenuItem::action("Open…", workspace::Open),
                 MenuItem::action("Open Recent...", recent_projects::OpenRecent),
@@ -13,7 +13,7 @@ use env_logger::Builder;
 use fs::RealFs;
 #[cfg(target_os = "macos")]
 use fsevent::StreamFlags;
-use futures::StreamExt;
+use futures::{FutureExt as _, StreamExt};
 use gpui::{App, AppContext, AsyncAppContext, Context, SemanticVersion, Task};
 use isahc::{prelude::Configurable, Request};
 use language::LanguageRegistry;
@@ -26,7 +26,8 @@ use parking_lot::Mutex;
 use release_channel::{parse_zed_link, AppCommitSha, ReleaseChannel, RELEASE_CHANNEL};
 use serde::{Deserialize, Serialize};
 use settings::{
-    default_settings, handle_settings_file_changes, watch_config_file, Settings, SettingsStore,
+    default_settings, handle_machine_settings_file_changes, handle_settings_file_changes,
+    watch_config_file, Settings, SettingsStore,
 };
 use simplelog::ConfigBuilder;
 use smol::process::Command;
@@ -45,6 +46,7 @@ use std::{
 };
 use theme::{ActiveTheme, SystemAppearance, ThemeRegistry, ThemeSettings};
 use util::{
+    asset_cache::{self, AssetCache},
     async_maybe,
     http::{HttpClient, HttpClientWithUrl},
     paths::{self, CRASHES_DIR, CRASHES_RETIRED_DIR},
@@ -74,6 +76,9 @@ fn main() {
 
     log::info!("========== starting zed ==========");
     let app = App::new().with_assets(Assets);
+    let executor = app.background_executor();
+    AssetCache::init_global(Arc::new(move |duration| executor.timer(duration).boxed()));
+    let app = app.with_image_disk_cache(AssetCache::global());
 
     let (installation_id, existing_installation_id_found) = app
         .background_executor()
@@ -89,12 +94,26 @@ fn main() {
         fs.clone(),
         paths::SETTINGS.clone(),
     );
+    let machine_settings_file_rx = watch_config_file(
+        &app.background_executor(),
+        fs.clone(),
+        paths::MACHINE_SETTINGS.clone(),
+    );
     let user_keymap_file_rx = watch_config_file(
         &app.background_executor(),
         fs.clone(),
         paths::KEYMAP.clone(),
     );
 
+    app.background_executor()
+        .spawn(async {
+            AssetCache::global()
+                .collect_garbage(asset_cache::DEFAULT_MAX_SIZE)
+                .await
+                .log_err();
+        })
+        .detach();
+
     let login_shell_env_loaded = if stdout_is_a_pty() {
         Task::ready(())
     } else {
@@ -136,6 +155,7 @@ fn main() {
             .unwrap();
         cx.set_global(store);
         handle_settings_file_changes(user_settings_file_rx, cx);
+        handle_machine_settings_file_changes(machine_settings_file_rx, cx);
         handle_keymap_file_changes(user_keymap_file_rx, cx);
         client::init_settings(cx);
 
@@ -164,6 +184,7 @@ fn main() {
         project::Project::init(&client, cx);
         client::init(&client, cx);
         command_palette::init(cx);
+        command_history::init(cx);
         language::init(cx);
         editor::init(cx);
         diagnostics::init(cx);
@@ -241,11 +262,14 @@ fn main() {
         recent_projects::init(cx);
 
         go_to_line::init(cx);
+        hex_editor::init(cx);
+        log_viewer::init(cx);
         file_finder::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
         tasks_ui::init(cx);
+        undo_history::init(cx);
         channel::init(&client, user_store.clone(), cx);
         search::init(cx);
         semantic_index::init(fs.clone(), http.clone(), languages.clone(), cx);
@@ -254,6 +278,8 @@ fn main() {
 
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
+        line_ending_selector::init(cx);
         theme_selector::init(cx);
         language_tools::init(cx);
         call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
@@ -261,8 +287,11 @@ fn main() {
         collab_ui::init(&app_state, cx);
         feedback::init(cx);
         markdown_preview::init(cx);
+        notebook::init(cx);
         welcome::init(cx);
         extensions_ui::init(cx);
+        settings_ui::init(cx);
+        project_templates::init(cx);
 
         cx.set_menus(app_menus());
         initialize_workspace(app_state.clone(), cx);
@@ -10,6 +10,7 @@ use futures::{FutureExt, SinkExt, StreamExt};
 use gpui::{AppContext, AsyncAppContext, Global};
 use itertools::Itertools;
 use language::{Bias, Point};
+use project::{search::SearchQuery, BatchEditSummary};
 use release_channel::parse_zed_link;
 use std::path::Path;
 use std::sync::atomic::Ordering;
@@ -320,6 +321,86 @@ pub async fn handle_cli_connection(
                     })
                     .log_err();
             }
+            CliRequest::Replace {
+                path,
+                query,
+                replacement,
+                regex,
+                dry_run,
+            } => {
+                let result = replace_in_project(
+                    PathBuf::from(path),
+                    query,
+                    replacement,
+                    regex,
+                    dry_run,
+                    &app_state,
+                    &mut cx,
+                )
+                .await;
+                let status = match result {
+                    Ok(summary) => {
+                        responses
+                            .send(CliResponse::Stdout {
+                                message: summary.to_string(),
+                            })
+                            .log_err();
+                        0
+                    }
+                    Err(error) => {
+                        responses
+                            .send(CliResponse::Stderr {
+                                message: format!("error replacing: {error:#}"),
+                            })
+                            .log_err();
+                        1
+                    }
+                };
+                responses.send(CliResponse::Exit { status }).log_err();
+            }
         }
     }
 }
+
+/// Opens the project at `path` and replaces every match of `query` in it,
+/// using the same search as the project search.
+async fn replace_in_project(
+    path: PathBuf,
+    query: String,
+    replacement: String,
+    regex: bool,
+    dry_run: bool,
+    app_state: &Arc<AppState>,
+    cx: &mut AsyncAppContext,
+) -> Result<BatchEditSummary> {
+    let query = if regex {
+        SearchQuery::regex(query, false, true, false, Vec::new(), Vec::new())?
+    } else {
+        SearchQuery::text(query, false, true, false, Vec::new(), Vec::new())?
+    }
+    .with_replacement(replacement);
+
+    let (workspace, _) = cx
+        .update(|cx| workspace::open_paths(&[path], app_state, None, cx))?
+        .await?;
+    let project = workspace.update(cx, |workspace, _| workspace.project().clone())?;
+    // Matches are only found in files that have been scanned.
+    let scans = project.update(cx, |project, cx| {
+        project
+            .worktrees()
+            .filter_map(|worktree| Some(worktree.read(cx).as_local()?.scan_complete()))
+            .collect::<Vec<_>>()
+    })?;
+    futures::future::join_all(scans).await;
+
+    project
+        .update(cx, |project, cx| {
+            project.batch_replace(
+                query.clone(),
+                dry_run,
+                move |text| query.replacement_for(text).map(|text| text.into_owned()),
+                cx,
+            )
+        })?
+        .await
+}
@@ -5,18 +5,25 @@ mod open_listener;
 pub use app_menus::*;
 use assistant::AssistantPanel;
 use breadcrumbs::Breadcrumbs;
+use call::ActiveCall;
+use client::Client;
 use collections::VecDeque;
 use editor::{Editor, MultiBuffer};
 use gpui::{
-    actions, point, px, AppContext, Context, FocusableView, PromptLevel, TitlebarOptions, View,
-    ViewContext, VisualContext, WindowBounds, WindowKind, WindowOptions,
+    actions, point, px, AppContext, Context, FocusableView, Global, PromptLevel, SharedString,
+    Task, TitlebarOptions, View, ViewContext, VisualContext, WindowBounds, WindowHandle,
+    WindowKind, WindowOptions,
 };
 pub use only_instance::*;
 pub use open_listener::*;
 
 use anyhow::{anyhow, Context as _};
 use assets::Assets;
-use futures::{channel::mpsc, select_biased, StreamExt};
+use futures::{
+    channel::mpsc,
+    future::{self, Either},
+    select_biased, StreamExt,
+};
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
 use release_channel::{AppCommitSha, ReleaseChannel};
@@ -26,10 +33,11 @@ use settings::{
     initial_local_settings_content, watch_config_file, KeymapFile, Settings, SettingsStore,
     DEFAULT_KEYMAP_PATH,
 };
-use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
+use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc, time::Duration};
 use task::{oneshot_source::OneshotSource, static_source::StaticSource};
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use util::{
+    asset_cache::AssetCache,
     asset_str,
     paths::{self, LOCAL_SETTINGS_RELATIVE_PATH},
     ResultExt,
@@ -48,6 +56,7 @@ actions!(
     zed,
     [
         About,
+        ClearAssetCache,
         DebugElements,
         DecreaseBufferFontSize,
         Hide,
@@ -75,6 +84,11 @@ pub fn init(cx: &mut AppContext) {
     cx.on_action(|_: &HideOthers, cx| cx.hide_other_apps());
     cx.on_action(|_: &ShowAll, cx| cx.unhide_other_apps());
     cx.on_action(quit);
+    cx.on_action(|_: &ClearAssetCache, cx| {
+        cx.background_executor()
+            .spawn(async { AssetCache::global().clear().await.log_err() })
+            .detach();
+    });
 }
 
 pub fn build_window_options(
@@ -126,21 +140,32 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             activity_indicator::ActivityIndicator::new(workspace, app_state.languages.clone(), cx);
         let active_buffer_language =
             cx.new_view(|_| language_selector::ActiveBufferLanguage::new(workspace));
+        let active_buffer_encoding =
+            cx.new_view(|_| encoding_selector::ActiveBufferEncoding::new());
+        let active_line_ending =
+            cx.new_view(|_| line_ending_selector::ActiveLineEnding::new(workspace));
         let vim_mode_indicator = cx.new_view(|cx| vim::ModeIndicator::new(cx));
         let feedback_button =
             cx.new_view(|_| feedback::deploy_feedback_button::DeployFeedbackButton::new(workspace));
         let cursor_position = cx.new_view(|_| editor::items::CursorPosition::new());
+        let connection_status = cx.new_view(|cx| collab_ui::ConnectionStatus::new(workspace, cx));
+        let active_collaborators = cx.new_view(collab_ui::ActiveCollaborators::new);
         workspace.status_bar().update(cx, |status_bar, cx| {
             status_bar.add_left_item(diagnostic_summary, cx);
             status_bar.add_left_item(activity_indicator, cx);
+            status_bar.add_left_item(connection_status, cx);
+            status_bar.add_right_item(active_collaborators, cx);
             status_bar.add_right_item(feedback_button, cx);
             status_bar.add_right_item(copilot, cx);
+            status_bar.add_right_item(active_buffer_encoding, cx);
+            status_bar.add_right_item(active_line_ending, cx);
             status_bar.add_right_item(active_buffer_language, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
             status_bar.add_right_item(cursor_position, cx);
         });
 
         auto_update::notify_of_any_new_update(cx);
+        show_keymap_errors(workspace, cx);
 
         let handle = cx.view().downgrade();
         cx.on_window_should_close(move |cx| {
@@ -378,7 +403,12 @@ fn initialize_pane(workspace: &mut Workspace, pane: &View<Pane>, cx: &mut ViewCo
         pane.toolbar().update(cx, |toolbar, cx| {
             let breadcrumbs = cx.new_view(|_| Breadcrumbs::new());
             toolbar.add_item(breadcrumbs, cx);
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+            let workspace_id = workspace.database_id();
+            let buffer_search_bar = cx.new_view(|cx| {
+                let mut buffer_search_bar = search::BufferSearchBar::new(cx);
+                buffer_search_bar.set_workspace_id(workspace_id, cx);
+                buffer_search_bar
+            });
             toolbar.add_item(buffer_search_bar.clone(), cx);
 
             let quick_action_bar =
@@ -393,6 +423,8 @@ fn initialize_pane(workspace: &mut Workspace, pane: &View<Pane>, cx: &mut ViewCo
             let syntax_tree_item =
                 cx.new_view(|_| language_tools::SyntaxTreeToolbarItemView::new());
             toolbar.add_item(syntax_tree_item, cx);
+            let log_toolbar = cx.new_view(log_viewer::LogToolbar::new);
+            toolbar.add_item(log_toolbar, cx);
         })
     });
 }
@@ -411,7 +443,21 @@ fn about(_: &mut Workspace, _: &About, cx: &mut gpui::ViewContext<Workspace>) {
         .detach();
 }
 
+/// How long quitting waits for the shutdown sequence before quitting anyway.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Set while the shutdown sequence is running, in which case quitting again
+/// quits immediately.
+struct ShuttingDown;
+
+impl Global for ShuttingDown {}
+
 fn quit(_: &Quit, cx: &mut AppContext) {
+    if cx.has_global::<ShuttingDown>() {
+        cx.quit();
+        return;
+    }
+
     let should_confirm = WorkspaceSettings::get_global(cx).confirm_quit;
     cx.spawn(|mut cx| async move {
         let mut workspace_windows = cx.update(|cx| {
@@ -449,7 +495,7 @@ fn quit(_: &Quit, cx: &mut AppContext) {
         }
 
         // If the user cancels any save prompt, then keep the app open.
-        for window in workspace_windows {
+        for window in &workspace_windows {
             if let Some(should_close) = window
                 .update(&mut cx, |workspace, cx| {
                     workspace.prepare_to_close(true, cx)
@@ -461,12 +507,62 @@ fn quit(_: &Quit, cx: &mut AppContext) {
                 }
             }
         }
+
+        let shutdown = cx.update(|cx| {
+            cx.set_global(ShuttingDown);
+            shut_down(workspace_windows, cx)
+        })?;
+        let timeout = cx.background_executor().timer(SHUTDOWN_TIMEOUT);
+        if let Either::Right(_) = future::select(shutdown, timeout).await {
+            log::error!("timed out shutting down, quitting anyway");
+        }
         cx.update(|cx| cx.quit())?;
         anyhow::Ok(())
     })
     .detach_and_log_err(cx);
 }
 
+/// Shuts down the app's connections and processes in order, so that each step
+/// can still rely on the ones after it. The remaining `on_app_quit` handlers
+/// run afterwards, when the app quits.
+fn shut_down(workspace_windows: Vec<WindowHandle<Workspace>>, cx: &mut AppContext) -> Task<()> {
+    let mut serializations = Vec::new();
+    let mut projects = Vec::new();
+    for window in workspace_windows {
+        window
+            .update(cx, |workspace, cx| {
+                serializations.push(workspace.flush_serialization(cx));
+                projects.push(workspace.project().clone());
+            })
+            .log_err();
+    }
+    let client = Client::global(cx);
+
+    // Leaving the call unshares the user's projects, and lets collaborators
+    // know they've left.
+    let leave_call =
+        ActiveCall::try_global(cx).map(|call| call.update(cx, |call, cx| call.hang_up(cx)));
+
+    cx.spawn(|mut cx| async move {
+        future::join_all(serializations).await;
+        if let Some(leave_call) = leave_call {
+            leave_call.await.log_err();
+        }
+
+        let shutdowns = projects
+            .iter()
+            .filter_map(|project| {
+                project
+                    .update(&mut cx, |project, cx| project.shutdown_language_servers(cx))
+                    .log_err()
+            })
+            .collect::<Vec<_>>();
+        future::join_all(shutdowns).await;
+
+        client.disconnect(&cx);
+    })
+}
+
 fn open_log_file(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     const MAX_LINES: usize = 1000;
     workspace
@@ -580,11 +676,49 @@ pub fn handle_keymap_file_changes(
     .detach();
 }
 
+/// Identifies the notification that lists invalid bindings in the user's keymap.
+const KEYMAP_ERRORS_NOTIFICATION_ID: usize = 0x6b65796d;
+
+/// The problems found the last time the user's keymap was loaded. Kept so that
+/// workspaces opened afterwards, including the first one at startup, show them too.
+#[derive(Default)]
+struct KeymapErrors(Option<SharedString>);
+
+impl Global for KeymapErrors {}
+
 fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
-    keymap_content.clone().add_to_cx(cx).log_err();
+    let errors = keymap_content.clone().add_to_cx(cx).err().map(|error| {
+        log::error!("{error:#}");
+        SharedString::from(format!("Some of your key bindings are invalid:\n{error:#}"))
+    });
+    cx.set_global(KeymapErrors(errors));
     cx.set_menus(app_menus());
+
+    for window in cx.windows() {
+        if let Some(workspace) = window.downcast::<Workspace>() {
+            workspace.update(cx, show_keymap_errors).log_err();
+        }
+    }
+}
+
+fn show_keymap_errors(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    // Replace any notification about a previous version of the keymap.
+    workspace.dismiss_notification::<MessageNotification>(KEYMAP_ERRORS_NOTIFICATION_ID, cx);
+    let Some(message) = cx
+        .try_global::<KeymapErrors>()
+        .and_then(|errors| errors.0.clone())
+    else {
+        return;
+    };
+    workspace.show_notification(KEYMAP_ERRORS_NOTIFICATION_ID, cx, |cx| {
+        cx.new_view(|_| {
+            MessageNotification::new(message)
+                .with_click_message("Open keymap")
+                .on_click(|cx| cx.dispatch_action(Box::new(OpenKeymap)))
+        })
+    });
 }
 
 pub fn load_default_keymap(cx: &mut AppContext) {
@@ -784,6 +918,7 @@ fn open_settings_file(
 mod tests {
     use super::*;
     use assets::Assets;
+    use client::test::FakeServer;
     use collections::HashSet;
     use editor::{scroll::Autoscroll, DisplayPoint, Editor};
     use gpui::{
@@ -798,8 +933,8 @@ mod tests {
     use theme::{ThemeRegistry, ThemeSettings};
     use workspace::{
         item::{Item, ItemHandle},
-        open_new, open_paths, pane, NewFile, OpenVisible, SaveIntent, SplitDirection,
-        WorkspaceHandle,
+        open_new, open_paths, pane, ExistingItemScope, NewFile, OpenVisible, SaveIntent,
+        SplitDirection, WorkspaceHandle,
     };
 
     #[gpui::test]
@@ -1190,6 +1325,103 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_open_existing_items(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                "/root",
+                json!({
+                    "file1": "contents 1",
+                    "file2": "contents 2",
+                }),
+            )
+            .await;
+        app_state
+            .fs
+            .as_fake()
+            .insert_symlink("/link1", "/root/file1".into())
+            .await;
+
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let entries = cx.read(|cx| workspace.file_project_paths(cx));
+        let file1 = entries[0].clone();
+        let file2 = entries[1].clone();
+
+        // Opening a file through a symlink activates the item opened for its target.
+        let item1 = window
+            .update(cx, |w, cx| w.open_path(file1.clone(), None, true, cx))
+            .unwrap()
+            .await
+            .unwrap();
+        window
+            .update(cx, |w, cx| w.open_path(file2.clone(), None, true, cx))
+            .unwrap()
+            .await
+            .unwrap();
+        let item1b = window
+            .update(cx, |w, cx| {
+                w.open_abs_path(PathBuf::from("/link1"), true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(item1.item_id(), item1b.item_id());
+        cx.read(|cx| {
+            let pane = workspace.read(cx).active_pane().read(cx);
+            assert_eq!(pane.items_len(), 2);
+            assert_eq!(pane.active_item().unwrap().item_id(), item1.item_id());
+            assert_eq!(workspace.read(cx).worktrees(cx).count(), 1);
+        });
+
+        // By default, files open in the given pane even if another pane has them.
+        let right_pane = window
+            .update(cx, |w, cx| {
+                w.split_pane(w.active_pane().clone(), SplitDirection::Right, cx)
+            })
+            .unwrap();
+        let item2b = window
+            .update(cx, |w, cx| {
+                w.open_path(file2.clone(), Some(right_pane.downgrade()), true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_ne!(item2b.item_id(), item1.item_id());
+        cx.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(right_pane.read(cx).items_len(), 1);
+            assert_eq!(workspace.read(cx).active_pane(), &right_pane);
+        });
+
+        // With a workspace-wide scope, the pane containing the item is activated instead.
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorkspaceSettings>(cx, |settings| {
+                    settings.existing_item_scope = Some(ExistingItemScope::Workspace);
+                });
+            });
+        });
+        let item1c = window
+            .update(cx, |w, cx| {
+                w.open_path(file1.clone(), Some(right_pane.downgrade()), true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(item1.item_id(), item1c.item_id());
+        cx.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(right_pane.read(cx).items_len(), 1);
+            assert_ne!(workspace.read(cx).active_pane(), &right_pane);
+        });
+    }
+
     #[gpui::test]
     async fn test_open_paths(cx: &mut TestAppContext) {
         let app_state = init_test(cx);
@@ -2765,6 +2997,25 @@ mod tests {
         cx.background_executor().run_until_parked();
     }
 
+    #[gpui::test]
+    async fn test_shut_down_disconnects_global_client(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let client = app_state.client.clone();
+        cx.update(|cx| Client::set_global(client.clone(), cx));
+        let _server = FakeServer::for_client(1, &client, cx).await;
+        assert!(matches!(
+            *client.status().borrow(),
+            client::Status::Connected { .. }
+        ));
+
+        // The client is disconnected even when there are no workspaces to get it from.
+        cx.update(|cx| shut_down(Vec::new(), cx)).await;
+        assert!(matches!(
+            *client.status().borrow(),
+            client::Status::SignedOut
+        ));
+    }
+
     fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
         cx.update(|cx| {
             let mut app_state = AppState::test(cx);